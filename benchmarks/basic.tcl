@@ -30,3 +30,11 @@ benchmark list-1.1 {list of six items} {
 }
 
 pdump
+
+benchmark expr-1.1 {expr, single braced argument} {
+    expr {1 + 2}
+}
+
+benchmark expr-1.2 {expr, several arguments} {
+    expr 1 + 2
+}
@@ -0,0 +1,23 @@
+# Ensemble dispatch benchmarks.
+pclear
+
+benchmark string-1.1 {string length, 1M calls} {
+    for {set i 0} {$i < 1000000} {incr i} {
+        string length abc
+    }
+} 1
+
+benchmark string-1.2 {string trimright, last of many subcommands, 1M calls} {
+    for {set i 0} {$i < 1000000} {incr i} {
+        string trimright abc
+    }
+} 1
+
+benchmark dict-1.1 {dict size, 1M calls} {
+    set d {a 1 b 2}
+    for {set i 0} {$i < 1000000} {incr i} {
+        dict size $d
+    }
+} 1
+
+pdump
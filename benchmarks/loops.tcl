@@ -0,0 +1,17 @@
+# Loop benchmarks.
+pclear
+
+benchmark while-1.1 {while, 1M iterations, empty body} {
+    set i 0
+    while {[incr i] < 1000000} {}
+} 1
+
+benchmark for-1.1 {for, 1M iterations, empty body} {
+    for {set i 0} {$i < 1000000} {incr i} {}
+} 1
+
+benchmark foreach-1.1 {foreach, 1000 items, empty body} {
+    foreach i [lrepeat 1000 x] {}
+} 100
+
+pdump
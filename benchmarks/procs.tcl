@@ -0,0 +1,35 @@
+# Proc call benchmarks: each call pushes and pops a scope.
+pclear
+
+proc fib {n} {
+    if {$n < 2} {
+        return $n
+    }
+    expr {[fib [expr {$n - 1}]] + [fib [expr {$n - 2}]]}
+}
+
+proc ack {m n} {
+    if {$m == 0} {
+        return [expr {$n + 1}]
+    }
+    if {$n == 0} {
+        return [ack [expr {$m - 1}] 1]
+    }
+    ack [expr {$m - 1}] [ack $m [expr {$n - 1}]]
+}
+
+proc noop {} {}
+
+benchmark proc-1.1 {call an empty proc} {
+    noop
+}
+
+benchmark fib-1.1 {fib 20, recursive} {
+    fib 20
+} 1
+
+benchmark ack-1.1 {ack 2 8, recursive} {
+    ack 2 8
+} 1
+
+pdump
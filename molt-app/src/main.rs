@@ -1,6 +1,7 @@
 use molt_forked::prelude::*;
-use molt_shell::{cmd_ident, cmd_ok, measure_cmd, BenchCtx};
+use molt_shell::{cmd_history, cmd_ident, cmd_ok, measure_cmd, BenchCtx, HistoryCtx};
 use std::env;
+use std::io::{self, IsTerminal};
 
 fn main() {
     // FIRST, get the command line arguments.
@@ -14,44 +15,108 @@ fn main() {
 
         match subcmd {
             "bench" => {
-                let mut interp = Interp::new(
-                    (YourCtx::default(), BenchCtx::new()),
-                    gen_command!(
-                        (YourCtx, BenchCtx),
-                        // native commands
-                        [
-                            // TODO: Requires file access.  Ultimately, might go in an extension crate if
-                            // the necessary operations aren't available in core::).
-                            (_SOURCE, cmd_source),
-                            // TODO: Useful for entire programs written in Molt; but not necessarily wanted in
-                            // extension scripts).
-                            (_EXIT, cmd_exit),
-                            // TODO: Developer Tools
-                            (_PARSE, cmd_parse),
-                            (_PDUMP, cmd_pdump),
-                            (_PCLEAR, cmd_pclear)
-                        ],
-                        // embedded commands
-                        [
-                            ("ident", "", cmd_ident, ""),
-                            ("measure", "", measure_cmd, ""),
-                            ("ok", "", cmd_ok, "")
-                        ]
-                    ),
-                    true,
-                    "molt-bench",
-                );
-                // NEXT, install the test commands into the interpreter.
-
-                // NEXT, create and initialize the interpreter.
-                // let mut interp = Interp::new(((), BenchCtx::new()));
-                molt_shell::benchmark(&mut interp, &args[2..]);
+                // NEXT, each benchmark script gets a fresh interpreter.
+                let new_interp = || {
+                    Interp::new(
+                        (YourCtx::default(), BenchCtx::new()),
+                        gen_command!(
+                            (YourCtx, BenchCtx),
+                            // native commands
+                            [
+                                // TODO: Requires file access.  Ultimately, might go in an extension crate if
+                                // the necessary operations aren't available in core::).
+                                (_SOURCE, cmd_source),
+                                // TODO: Useful for entire programs written in Molt; but not necessarily wanted in
+                                // extension scripts).
+                                (_EXIT, cmd_exit),
+                                // TODO: Developer Tools
+                                (_PARSE, cmd_parse),
+                                (_PDUMP, cmd_pdump),
+                                (_PCLEAR, cmd_pclear)
+                            ],
+                            // embedded commands
+                            [
+                                (
+                                    "ident",
+                                    cmd_ident,
+                                    ArgSpec { min: 2, max: 2, usage: "value" }
+                                ),
+                                (
+                                    "measure",
+                                    measure_cmd,
+                                    ArgSpec {
+                                        min: 4,
+                                        max: 4,
+                                        usage: "name description nanos"
+                                    }
+                                ),
+                                (
+                                    "ok",
+                                    cmd_ok,
+                                    ArgSpec { min: 1, max: 0, usage: "?arg ...?" }
+                                )
+                            ]
+                        ),
+                        true,
+                        "molt-bench",
+                    )
+                };
+                std::process::exit(molt_shell::benchmark_with(new_interp, &args[2..]));
             }
             "shell" => {
-                let mut interp = Interp::default();
+                let mut interp =
+                    InterpBuilder::new((YourCtx::default(), HistoryCtx::new()))
+                        .commands(gen_command!(
+                            (YourCtx, HistoryCtx),
+                            // native commands
+                            [
+                                // TODO: Requires file access.  Ultimately, might go in an extension crate if
+                                // the necessary operations aren't available in core::).
+                                (_SOURCE, cmd_source),
+                                // Files and file channels, from the `file-io` feature.
+                                (_FILE, cmd_file),
+                                (_GLOB, cmd_glob),
+                                (_OPEN, cmd_open),
+                                (_CLOSE, cmd_close),
+                                (_READ, cmd_read),
+                                (_GETS, cmd_gets),
+                                (_EOF, cmd_eof),
+                                // External programs, from the `exec` feature.
+                                (_EXEC, cmd_exec),
+                                // TODO: Useful for entire programs written in Molt; but not necessarily wanted in
+                                // extension scripts).
+                                (_EXIT, cmd_exit),
+                                // TODO: Developer Tools
+                                (_PARSE, cmd_parse),
+                                (_PDUMP, cmd_pdump),
+                                (_PCLEAR, cmd_pclear)
+                            ],
+                            // embedded commands
+                            [(
+                                "history",
+                                cmd_history,
+                                ArgSpec { min: 1, max: 3, usage: "?subcommand? ?arg?" }
+                            )]
+                        ))
+                        .name("molt-shell")
+                        .build();
                 if args.len() == 2 {
-                    println!("Molt {}", env!("CARGO_PKG_VERSION"));
-                    molt_shell::repl(&mut interp);
+                    if io::stdin().is_terminal() {
+                        println!("Molt {}", env!("CARGO_PKG_VERSION"));
+                        molt_shell::history_repl(&mut interp);
+                    } else {
+                        std::process::exit(molt_shell::read_script(
+                            &mut interp,
+                            io::stdin(),
+                            &[],
+                        ));
+                    }
+                } else if args[2] == "-c" {
+                    if args.len() == 3 {
+                        eprintln!("missing script after \"-c\"");
+                        std::process::exit(1);
+                    }
+                    std::process::exit(molt_shell::command(&mut interp, &args[3..]));
                 } else {
                     molt_shell::script(&mut interp, &args[2..]);
                 }
@@ -75,7 +140,11 @@ fn main() {
                             (_PCLEAR, cmd_pclear),
                         ],
                         // embedded commands
-                        [("test", "", test_cmd, "")]
+                        [(
+                            "test",
+                            test_cmd,
+                            ArgSpec { min: 4, max: 0, usage: "name description args..." }
+                        )]
                     ),
                     true,
                     "molt-test",
@@ -107,6 +176,7 @@ fn print_help() {
     println!();
     println!("  help                          -- This help");
     println!("  shell [<script>] [args...]    -- The Molt shell");
+    println!("  shell -c <script> [args...]   -- Evaluate a script given as an argument");
     println!("  test  [<script>] [args...]    -- The Molt test harness");
     println!("  bench [<script>] [args...]    -- The Molt benchmark tool");
     println!();
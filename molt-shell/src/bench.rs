@@ -24,6 +24,10 @@ use std::{env, fs, path::PathBuf};
 /// of options, see The Molt Book or execute this function with an empty argument
 /// list.
 ///
+/// Exits the process with a nonzero status if the script fails or, when comparing
+/// against a `-baseline`, if any benchmark regressed.  The `-compare` option needs a fresh
+/// interpreter for each script; use [`benchmark_with`] for that.
+///
 /// See [`molt::interp`](../molt/interp/index.html) for details on how to configure and
 /// add commands to a Molt interpreter.
 ///
@@ -48,72 +52,209 @@ use std::{env, fs, path::PathBuf};
 ///     eprintln!("Usage: mybench *filename.tcl");
 /// }
 /// ```
-pub fn benchmark<Ctx>(interp: &mut Interp<(Ctx, BenchCtx)>, args: &[String]) {
+pub fn benchmark<Ctx: HasBenchCtx>(interp: &mut Interp<Ctx>, args: &[String]) {
+    let code = run_benchmarks(interp, || None, args);
+
+    if code != 0 {
+        std::process::exit(code);
+    }
+}
+
+/// Executes the Molt benchmark harness, given the command-line arguments, creating
+/// a fresh interpreter for each benchmark script by calling `new_interp`.
+///
+/// This supports all of the options supported by [`benchmark`], including `-compare`,
+/// which runs a second script and reports the change in each benchmark.  Returns the
+/// exit status: 0 on success, 1 if a script failed or a benchmark regressed by more
+/// than the `-threshold`.
+pub fn benchmark_with<Ctx, F>(mut new_interp: F, args: &[String]) -> i32
+where
+    Ctx: HasBenchCtx,
+    F: FnMut() -> Interp<Ctx>,
+{
+    let mut interp = new_interp();
+    run_benchmarks(&mut interp, || Some(new_interp()), args)
+}
+
+/// The benchmark options.
+struct BenchOptions {
+    output: Output,
+    compare: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Text,
+    Csv,
+    Json,
+}
+
+/// The default regression threshold, in percent.
+const DEFAULT_THRESHOLD: f64 = 5.0;
+
+fn parse_options(args: &[String]) -> Result<BenchOptions, String> {
+    let mut opts = BenchOptions {
+        output: Output::Text,
+        compare: None,
+        baseline: None,
+        threshold: DEFAULT_THRESHOLD,
+    };
+
+    let mut iter = args.iter();
+    while let Some(opt) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for \"{}\"", opt))
+        };
+
+        match opt.as_ref() {
+            "-csv" => opts.output = Output::Csv,
+            "-json" => opts.output = Output::Json,
+            "-compare" => opts.compare = Some(value()?),
+            "-baseline" => opts.baseline = Some(value()?),
+            "-threshold" => {
+                let text = value()?;
+                opts.threshold = match text.parse::<f64>() {
+                    Ok(threshold) if threshold >= 0.0 => threshold,
+                    _ => return Err(format!("Invalid threshold: \"{}\"", text)),
+                };
+            }
+            _ => return Err(format!("Unknown option: \"{}\"", opt)),
+        }
+    }
+
+    if opts.compare.is_some() && opts.baseline.is_some() {
+        return Err("Use either -compare or -baseline, not both".into());
+    }
+
+    if opts.output != Output::Text && (opts.compare.is_some() || opts.baseline.is_some())
+    {
+        return Err("The -csv and -json options can't be used when comparing".into());
+    }
+
+    Ok(opts)
+}
+
+/// Runs the benchmark harness; `next_interp` returns an interpreter for the `-compare`
+/// script, if one can be had.  Returns the exit status.
+fn run_benchmarks<Ctx, F>(
+    interp: &mut Interp<Ctx>,
+    mut next_interp: F,
+    args: &[String],
+) -> i32
+where
+    Ctx: HasBenchCtx,
+    F: FnMut() -> Option<Interp<Ctx>>,
+{
     // FIRST, get the script file name
     if args.is_empty() {
         eprintln!("Missing benchmark script.");
         write_usage();
-        return;
+        return 1;
     }
 
     // NEXT, parse any options.
-    let mut output_csv = false;
-
-    let mut iter = args[1..].iter();
-    loop {
-        let opt = iter.next();
-        if opt.is_none() {
-            break;
+    let opts = match parse_options(&args[1..]) {
+        Ok(opts) => opts,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            write_usage();
+            return 1;
         }
-
-        let opt = opt.unwrap();
-
-        match opt.as_ref() {
-            "-csv" => {
-                output_csv = true;
+    };
+
+    // NEXT, run the script.
+    let ctx = match run_script(interp, &args[0]) {
+        Ok(ctx) => ctx,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            return 1;
+        }
+    };
+
+    // NEXT, get the results to compare with, if any.  With -compare, the script named
+    // on the command line is the old version; with -baseline, it's the new one.
+    let (old, new) = if let Some(other) = &opts.compare {
+        let Some(mut other_interp) = next_interp() else {
+            eprintln!("The -compare option requires molt_shell::benchmark_with.");
+            return 1;
+        };
+
+        match run_script(&mut other_interp, other) {
+            Ok(other_ctx) => (ctx, other_ctx),
+            Err(msg) => {
+                eprintln!("{}", msg);
+                return 1;
             }
-            _ => {
-                eprintln!("Unknown option: \"{}\"", opt);
-                write_usage();
-                return;
+        }
+    } else if let Some(baseline) = &opts.baseline {
+        match read_json_file(baseline) {
+            Ok(baseline_ctx) => (baseline_ctx, ctx),
+            Err(msg) => {
+                eprintln!("{}", msg);
+                return 1;
             }
         }
-    }
+    } else {
+        // NEXT, output the benchmark results:
+        match opts.output {
+            Output::Text => write_formatted_text(&ctx),
+            Output::Csv => write_csv(&ctx),
+            Output::Json => print!("{}", to_json(&ctx)),
+        }
+        return 0;
+    };
+
+    // NEXT, output the comparison.
+    let comparisons = compare_benchmarks(&old, &new);
+
+    write_version();
+    println!();
+    print!("{}", format_comparison(&comparisons, opts.threshold));
 
-    // NEXT, get the parent folder from the path, if any.  We'll cd into the parent so
-    // the `source` command can find scripts there.
-    let path = PathBuf::from(&args[0]);
+    if comparisons.iter().any(|c| c.is_regression(opts.threshold)) {
+        1
+    } else {
+        0
+    }
+}
 
-    // NEXT, load the benchmark Tcl library
+/// Executes the benchmark script in the interpreter, returning its measurements.
+fn run_script<Ctx: HasBenchCtx>(
+    interp: &mut Interp<Ctx>,
+    script_path: &str,
+) -> Result<BenchCtx, String> {
+    // FIRST, load the benchmark Tcl library
     if let Err(exception) = interp.eval(include_str!("bench.tcl")) {
         panic!("Error in benchmark Tcl library: {}", exception.value().as_str());
     }
 
-    // NEXT, execute the script.
-    match fs::read_to_string(&args[0]) {
-        Ok(script) => {
-            if let Some(parent) = path.parent() {
-                let _ = env::set_current_dir(parent);
-            }
-
-            match interp.eval(&script) {
-                Ok(_) => (),
-                Err(exception) => {
-                    eprintln!("{}", exception.value());
-                    std::process::exit(1);
-                }
-            }
+    // NEXT, read the script.
+    let script = fs::read_to_string(script_path)
+        .map_err(|e| format!("Couldn't read \"{}\": {}", script_path, e))?;
+
+    // NEXT, cd into the script's parent folder, if any, so the `source` command can
+    // find scripts there.  Come back afterwards, so that relative paths to other scripts
+    // still work.
+    let cwd = env::current_dir().ok();
+    if let Some(parent) = PathBuf::from(script_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            let _ = env::set_current_dir(parent);
         }
-        Err(e) => println!("{}", e),
     }
 
-    // NEXT, output the test results:
-    let ctx = &mut interp.context.1;
+    let result = interp.eval(&script);
 
-    if output_csv {
-        write_csv(ctx);
-    } else {
-        write_formatted_text(ctx);
+    if let Some(cwd) = cwd {
+        let _ = env::set_current_dir(cwd);
+    }
+
+    match result {
+        Ok(_) => Ok(std::mem::take(interp.context.bench_ctx_mut())),
+        Err(exception) => Err(exception.value().to_string()),
     }
 }
 
@@ -164,9 +305,352 @@ fn write_version() {
 fn write_usage() {
     write_version();
     println!();
-    println!("Usage: molt bench filename.tcl [-csv]");
+    println!("Usage: molt bench filename.tcl [-csv | -json]");
+    println!("       molt bench old.tcl -compare new.tcl [-threshold percent]");
+    println!("       molt bench filename.tcl -baseline file.json [-threshold percent]");
+}
+
+//-----------------------------------------------------------------------------
+// Comparisons
+
+/// A benchmark's times in two runs, matched by benchmark name.  A benchmark that
+/// appears in only one of the runs has `None` for the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub name: String,
+    pub description: String,
+    pub old: Option<MoltInt>,
+    pub new: Option<MoltInt>,
+}
+
+impl Comparison {
+    /// The change from the old time to the new time, as a percentage of the old time.
+    /// Returns `None` unless the benchmark appears in both runs.
+    pub fn delta(&self) -> Option<f64> {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) if old > 0 => {
+                Some((new - old) as f64 * 100.0 / old as f64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the new time is slower than the old by more than `threshold` percent.
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.delta().is_some_and(|delta| delta > threshold)
+    }
+}
+
+/// Matches the benchmarks in two runs by name.  The comparisons are in the order of the
+/// old run, followed by any benchmarks found only in the new run.
+pub fn compare_benchmarks(old: &BenchCtx, new: &BenchCtx) -> Vec<Comparison> {
+    let mut comparisons: Vec<Comparison> = old
+        .measurements
+        .iter()
+        .map(|record| Comparison {
+            name: record.name.clone(),
+            description: record.description.clone(),
+            old: Some(record.nanos),
+            new: None,
+        })
+        .collect();
+
+    for record in &new.measurements {
+        match comparisons
+            .iter_mut()
+            .find(|c| c.name == record.name && c.new.is_none())
+        {
+            Some(comparison) => comparison.new = Some(record.nanos),
+            None => comparisons.push(Comparison {
+                name: record.name.clone(),
+                description: record.description.clone(),
+                old: None,
+                new: Some(record.nanos),
+            }),
+        }
+    }
+
+    comparisons
+}
+
+/// Formats the comparisons as a table, flagging regressions beyond the threshold.
+pub fn format_comparison(comparisons: &[Comparison], threshold: f64) -> String {
+    let time = |nanos: Option<MoltInt>| nanos.map_or("-".to_string(), |n| n.to_string());
+
+    let mut out = format!("{:>8} {:>8} {:>8} -- Benchmark\n", "Old", "New", "Delta");
+    let mut regressions = 0;
+
+    for comparison in comparisons {
+        let delta = comparison.delta().map_or("-".to_string(), |d| format!("{:+.1}%", d));
+        let flag = if comparison.is_regression(threshold) {
+            regressions += 1;
+            " [REGRESSION]"
+        } else {
+            ""
+        };
+
+        out.push_str(&format!(
+            "{:>8} {:>8} {:>8} -- {} {}{}\n",
+            time(comparison.old),
+            time(comparison.new),
+            delta,
+            comparison.name,
+            comparison.description,
+            flag
+        ));
+    }
+
+    out.push_str(&format!(
+        "\n{} benchmarks, {} regressed by more than {}%\n",
+        comparisons.len(),
+        regressions,
+        threshold
+    ));
+
+    out
 }
 
+//-----------------------------------------------------------------------------
+// JSON Output
+
+/// Formats the benchmark results as JSON, as output by the `-json` option and read by
+/// the `-baseline` option.
+fn to_json(ctx: &BenchCtx) -> String {
+    let baseline = ctx.baseline();
+
+    let records: Vec<String> = ctx
+        .measurements
+        .iter()
+        .map(|record| {
+            // JSON has no representation for Inf or NaN.
+            let norm = record.nanos as f64 / (baseline as f64);
+            let norm = if norm.is_finite() { norm.to_string() } else { "null".into() };
+
+            format!(
+                "    {{\"name\": {}, \"description\": {}, \"nanos\": {}, \"norm\": {}}}",
+                json_string(&record.name),
+                json_string(&record.description),
+                record.nanos,
+                norm,
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"version\": {},\n  \"benchmarks\": [\n{}\n  ]\n}}\n",
+        json_string(env!("CARGO_PKG_VERSION")),
+        records.join(",\n")
+    )
+}
+
+fn json_string(string: &str) -> String {
+    let mut out = String::from("\"");
+
+    for ch in string.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+/// Reads a `-json` dump of benchmark results.
+fn read_json_file(path: &str) -> Result<BenchCtx, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read \"{}\": {}", path, e))?;
+    from_json(&text).map_err(|msg| format!("Invalid baseline \"{}\": {}", path, msg))
+}
+
+fn from_json(text: &str) -> Result<BenchCtx, String> {
+    let mut parser = JsonParser { chars: text.chars().peekable() };
+    let json = parser.value()?;
+    parser.skip_whitespace();
+    if parser.chars.peek().is_some() {
+        return Err("trailing characters".into());
+    }
+
+    let Some(Json::Array(records)) = json.get("benchmarks") else {
+        return Err("no \"benchmarks\" array".into());
+    };
+
+    let mut ctx = BenchCtx::new();
+
+    for record in records {
+        let (Some(Json::String(name)), Some(Json::Number(nanos))) =
+            (record.get("name"), record.get("nanos"))
+        else {
+            return Err("each benchmark needs a \"name\" and \"nanos\"".into());
+        };
+        let description = match record.get("description") {
+            Some(Json::String(description)) => description.clone(),
+            _ => String::new(),
+        };
+
+        ctx.record(name.clone(), description, *nanos as MoltInt);
+    }
+
+    Ok(ctx)
+}
+
+/// Just enough JSON to read back what `to_json` writes.
+#[derive(Debug)]
+enum Json {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => {
+                members.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            _ => None,
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(ch) if ch == expected => Ok(()),
+            Some(ch) => Err(format!("expected '{}', got '{}'", expected, ch)),
+            None => Err(format!("expected '{}', got end of input", expected)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(ch) if *ch == '-' || ch.is_ascii_digit() => self.number(),
+            Some(_) => match self.word().as_str() {
+                "null" => Ok(Json::Null),
+                "true" | "false" => Ok(Json::Bool),
+                word => Err(format!("unexpected \"{}\"", word)),
+            },
+            None => Err("unexpected end of input".into()),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            members.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => return Err("expected ',' or '}'".into()),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&']').is_some() {
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err("expected ',' or ']'".into()),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String =
+                            (0..4).filter_map(|_| self.chars.next()).collect();
+                        let ch = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("bad escape \"\\u{}\"", hex))?;
+                        out.push(ch);
+                    }
+                    Some(ch) => out.push(ch),
+                    None => return Err("unterminated string".into()),
+                },
+                Some(ch) => out.push(ch),
+                None => return Err("unterminated string".into()),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let word = self.word();
+        word.parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| format!("bad number \"{}\"", word))
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(ch) =
+            self.chars.next_if(|ch| ch.is_alphanumeric() || "+-.".contains(*ch))
+        {
+            word.push(ch);
+        }
+        word
+    }
+}
+
+#[derive(Default)]
 pub struct BenchCtx {
     // The baseline, in microseconds
     baseline: Option<MoltInt>,
@@ -183,6 +667,31 @@ impl BenchCtx {
     fn baseline(&self) -> MoltInt {
         self.baseline.unwrap_or(1)
     }
+
+    /// Records a measurement; the first one recorded is the baseline.
+    pub fn record(&mut self, name: String, description: String, nanos: MoltInt) {
+        if self.baseline.is_none() {
+            self.baseline = Some(nanos);
+        }
+
+        self.measurements.push(Measurement { name, description, nanos });
+    }
+}
+
+/// Gives the benchmark harness access to its [`BenchCtx`] within an interpreter's
+/// context.
+///
+/// This is implemented for the `(Ctx, BenchCtx)` tuple layout; implement it for your own
+/// context type to add the `measure` command to an interpreter whose context isn't a
+/// tuple.
+pub trait HasBenchCtx: 'static {
+    fn bench_ctx_mut(&mut self) -> &mut BenchCtx;
+}
+
+impl<Ctx: 'static> HasBenchCtx for (Ctx, BenchCtx) {
+    fn bench_ctx_mut(&mut self) -> &mut BenchCtx {
+        &mut self.1
+    }
 }
 
 struct Measurement {
@@ -199,8 +708,8 @@ struct Measurement {
 /// # measure *name* *description* *micros*
 ///
 /// Records a benchmark measurement.
-pub fn measure_cmd<Ctx: 'static>(
-    interp: &mut Interp<(Ctx, BenchCtx)>,
+pub fn measure_cmd<Ctx: HasBenchCtx>(
+    interp: &mut Interp<Ctx>,
     argv: &[Value],
 ) -> MoltResult {
     check_args(1, argv, 4, 4, "name description nanos")?;
@@ -210,16 +719,8 @@ pub fn measure_cmd<Ctx: 'static>(
     let description = argv[2].to_string();
     let nanos = argv[3].as_int()?;
 
-    // NEXT, get the test context
-    let ctx = &mut interp.context.1;
-
-    if ctx.baseline.is_none() {
-        ctx.baseline = Some(nanos);
-    }
-
-    let record = Measurement { name, description, nanos };
-
-    ctx.measurements.push(record);
+    // NEXT, record the measurement in the test context
+    interp.context.bench_ctx_mut().record(name, description, nanos);
 
     molt_ok!()
 }
@@ -227,8 +728,8 @@ pub fn measure_cmd<Ctx: 'static>(
 /// # ident value
 ///
 /// Returns its argument.
-pub fn cmd_ident<Ctx: 'static>(
-    _interp: &mut Interp<(Ctx, BenchCtx)>,
+pub fn cmd_ident<Ctx: HasBenchCtx>(
+    _interp: &mut Interp<Ctx>,
     argv: &[Value],
 ) -> MoltResult {
     check_args(1, argv, 2, 2, "value")?;
@@ -239,9 +740,165 @@ pub fn cmd_ident<Ctx: 'static>(
 /// # ok ...
 ///
 /// Takes any number of arguments, and returns "".
-pub fn cmd_ok<Ctx: 'static>(
-    _interp: &mut Interp<(Ctx, BenchCtx)>,
+pub fn cmd_ok<Ctx: HasBenchCtx>(
+    _interp: &mut Interp<Ctx>,
     _argv: &[Value],
 ) -> MoltResult {
     molt_ok!()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(records: &[(&str, MoltInt)]) -> BenchCtx {
+        let mut ctx = BenchCtx::new();
+        for (name, nanos) in records {
+            ctx.record(name.to_string(), format!("{} benchmark", name), *nanos);
+        }
+        ctx
+    }
+
+    fn opts(args: &[&str]) -> Result<BenchOptions, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_options(&args)
+    }
+
+    #[test]
+    fn test_compare_benchmarks() {
+        let old = ctx(&[("a", 100), ("b", 200), ("gone", 50)]);
+        let new = ctx(&[("b", 190), ("a", 150), ("added", 10)]);
+        let comparisons = compare_benchmarks(&old, &new);
+
+        let summary: Vec<_> =
+            comparisons.iter().map(|c| (c.name.as_str(), c.old, c.new)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", Some(100), Some(150)),
+                ("b", Some(200), Some(190)),
+                ("gone", Some(50), None),
+                ("added", None, Some(10)),
+            ]
+        );
+
+        assert_eq!(comparisons[0].delta(), Some(50.0));
+        assert_eq!(comparisons[1].delta(), Some(-5.0));
+        assert_eq!(comparisons[2].delta(), None);
+        assert_eq!(comparisons[3].delta(), None);
+
+        assert!(comparisons[0].is_regression(5.0));
+        assert!(!comparisons[0].is_regression(50.0));
+        assert!(!comparisons[1].is_regression(0.0));
+        assert!(!comparisons[2].is_regression(0.0));
+    }
+
+    #[test]
+    fn test_format_comparison() {
+        let old = ctx(&[("a", 100), ("b", 200)]);
+        let new = ctx(&[("a", 150), ("b", 190), ("c", 5)]);
+        let table = format_comparison(&compare_benchmarks(&old, &new), 5.0);
+
+        assert_eq!(
+            table,
+            "     Old      New    Delta -- Benchmark\n\
+             \x20    100      150   +50.0% -- a a benchmark [REGRESSION]\n\
+             \x20    200      190    -5.0% -- b b benchmark\n\
+             \x20      -        5        - -- c c benchmark\n\
+             \n\
+             3 benchmarks, 1 regressed by more than 5%\n"
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut old = ctx(&[("a", 100), ("b", 250)]);
+        old.record("q\"uote".into(), "back\\slash\nnewline".into(), 7);
+
+        let json = to_json(&old);
+        let new = from_json(&json).unwrap();
+
+        let records = |ctx: &BenchCtx| -> Vec<(String, String, MoltInt)> {
+            ctx.measurements
+                .iter()
+                .map(|m| (m.name.clone(), m.description.clone(), m.nanos))
+                .collect()
+        };
+        assert_eq!(records(&old), records(&new));
+        assert_eq!(new.baseline(), 100);
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        assert!(from_json("").is_err());
+        assert!(from_json("[]").is_err());
+        assert!(from_json("{\"benchmarks\": [{\"name\": \"a\"}]}").is_err());
+        assert!(from_json("{\"benchmarks\": []} extra").is_err());
+        assert!(from_json("{\"benchmarks\": [1, }").is_err());
+
+        let ctx =
+            from_json("{\"benchmarks\": [{\"name\": \"a\", \"nanos\": 5}], \"x\": null}")
+                .unwrap();
+        assert_eq!(ctx.measurements.len(), 1);
+        assert_eq!(ctx.measurements[0].description, "");
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let parsed = opts(&["-compare", "new.tcl", "-threshold", "2.5"]).unwrap();
+        assert_eq!(parsed.compare.as_deref(), Some("new.tcl"));
+        assert_eq!(parsed.threshold, 2.5);
+
+        let parsed = opts(&["-json"]).unwrap();
+        assert!(parsed.output == Output::Json);
+        assert_eq!(parsed.threshold, DEFAULT_THRESHOLD);
+
+        assert!(opts(&["-compare"]).is_err());
+        assert!(opts(&["-threshold", "-1"]).is_err());
+        assert!(opts(&["-threshold", "abc"]).is_err());
+        assert!(opts(&["-compare", "a", "-baseline", "b"]).is_err());
+        assert!(opts(&["-csv", "-compare", "a"]).is_err());
+        assert!(opts(&["-nonesuch"]).is_err());
+    }
+
+    #[test]
+    fn test_benchmark_with_compare() {
+        let new_interp = || {
+            Interp::new(
+                ((), BenchCtx::new()),
+                gen_command!(
+                    ((), BenchCtx),
+                    [],
+                    [(
+                        "measure",
+                        measure_cmd,
+                        ArgSpec { min: 4, max: 4, usage: "name description nanos" }
+                    )]
+                ),
+                false,
+                "bench-test",
+            )
+        };
+
+        let dir =
+            env::temp_dir().join(format!("molt-bench-compare-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let old = dir.join("old.tcl");
+        let new = dir.join("new.tcl");
+        fs::write(&old, "measure a {A} 100; measure b {B} 200").unwrap();
+        fs::write(&new, "measure a {A} 150; measure b {B} 200").unwrap();
+
+        let run = |options: &[&str]| {
+            let mut args = vec![old.to_str().unwrap().to_string()];
+            args.extend(options.iter().map(|opt| opt.to_string()));
+            benchmark_with(new_interp, &args)
+        };
+        let new = new.to_str().unwrap();
+
+        assert_eq!(run(&["-compare", new]), 1);
+        assert_eq!(run(&["-compare", new, "-threshold", "50"]), 0);
+        assert_eq!(run(&["-compare", "nonesuch.tcl"]), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
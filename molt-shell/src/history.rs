@@ -0,0 +1,225 @@
+//! Molt REPL Command History
+//!
+//! The [`HistoryCtx`] records the commands entered at the REPL, so that they can be
+//! listed and re-executed.  It is shared between the readline loop in
+//! [`history_repl`](../fn.history_repl.html), which records each line and performs
+//! `!!`/`!N` expansion, and the `history` command, [`cmd_history`].
+//!
+//! Events are numbered from 1; `history clear` resets the numbering.
+
+use molt_forked::prelude::*;
+
+/// The REPL's command history.
+#[derive(Debug, Default)]
+pub struct HistoryCtx {
+    // The recorded events; event N is entries[N - 1].
+    entries: Vec<String>,
+}
+
+impl HistoryCtx {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Records a line as the next history event.
+    pub fn add(&mut self, line: &str) {
+        self.entries.push(line.to_string());
+    }
+
+    /// Returns the text of the given event, if it exists.
+    pub fn get(&self, event: usize) -> Option<&str> {
+        event
+            .checked_sub(1)
+            .and_then(|i| self.entries.get(i))
+            .map(|s| s.as_str())
+    }
+
+    /// Returns the text of the most recent event, if any.
+    pub fn last(&self) -> Option<&str> {
+        self.entries.last().map(|s| s.as_str())
+    }
+
+    /// Returns the number of recorded events.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no events have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Erases the history and resets the event numbering.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the history as a numbered listing, one event per line.
+    pub fn listing(&self) -> String {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| format!("{:6}  {}", i + 1, entry))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Performs history expansion on a line of REPL input.
+    ///
+    /// Only lines that begin with `!!` (the previous event) or `!N` (event N) are
+    /// expanded; the event designator is replaced by the event's text, and the rest of
+    /// the line is kept as is.  Returns `Ok(None)` if the line isn't a history
+    /// reference, and an error message if it names an event that doesn't exist.
+    pub fn expand(&self, line: &str) -> Result<Option<String>, String> {
+        let Some(rest) = line.strip_prefix('!') else {
+            return Ok(None);
+        };
+
+        let (event, tail) = if let Some(tail) = rest.strip_prefix('!') {
+            (self.last(), tail)
+        } else {
+            let digits =
+                rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits == 0 {
+                return Ok(None);
+            }
+
+            let event = rest[..digits].parse::<usize>().ok().and_then(|n| self.get(n));
+            (event, &rest[digits..])
+        };
+
+        match event {
+            Some(text) => Ok(Some(format!("{}{}", text, tail))),
+            None => {
+                let designator = &line[..line.len() - tail.len()];
+                Err(format!("{}: event not found", designator))
+            }
+        }
+    }
+}
+
+/// Gives the REPL and the `history` command access to the [`HistoryCtx`] within an
+/// interpreter's context.
+///
+/// This is implemented for the `(Ctx, HistoryCtx)` tuple layout; implement it for your
+/// own context type to use [`history_repl`](../fn.history_repl.html) with it.
+pub trait HasHistoryCtx: 'static {
+    fn history_ctx_mut(&mut self) -> &mut HistoryCtx;
+}
+
+impl<Ctx: 'static> HasHistoryCtx for (Ctx, HistoryCtx) {
+    fn history_ctx_mut(&mut self) -> &mut HistoryCtx {
+        &mut self.1
+    }
+}
+
+/// # history ?subcommand? ?arg?
+///
+/// Lists the REPL's command history, or with `history clear`, erases it; with
+/// `history redo n`, re-executes event `n` and returns its result.
+pub fn cmd_history<Ctx: HasHistoryCtx>(
+    interp: &mut Interp<Ctx>,
+    argv: &[Value],
+) -> MoltResult {
+    check_args(1, argv, 1, 3, "?subcommand? ?arg?")?;
+
+    if argv.len() == 1 {
+        return molt_ok!(interp.context.history_ctx_mut().listing());
+    }
+
+    match argv[1].as_str() {
+        "clear" => {
+            check_args(2, argv, 2, 2, "")?;
+            interp.context.history_ctx_mut().clear();
+            molt_ok!()
+        }
+        "redo" => {
+            check_args(2, argv, 3, 3, "n")?;
+            let event = argv[2].as_int()?;
+            let script = usize::try_from(event)
+                .ok()
+                .and_then(|n| interp.context.history_ctx_mut().get(n))
+                .map(|s| s.to_string());
+
+            match script {
+                Some(script) => interp.eval(&script),
+                None => molt_err!("event \"{}\" not found", event),
+            }
+        }
+        subcmd => molt_err!("bad option \"{}\": must be clear or redo", subcmd),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(lines: &[&str]) -> HistoryCtx {
+        let mut ctx = HistoryCtx::new();
+        for line in lines {
+            ctx.add(line);
+        }
+        ctx
+    }
+
+    #[test]
+    fn test_expand() {
+        let ctx = history(&["set x 1", "incr x"]);
+
+        assert_eq!(ctx.expand("set y 2"), Ok(None));
+        assert_eq!(ctx.expand("!= 1"), Ok(None));
+        assert_eq!(ctx.expand("!!"), Ok(Some("incr x".into())));
+        assert_eq!(ctx.expand("!! 5"), Ok(Some("incr x 5".into())));
+        assert_eq!(ctx.expand("!1"), Ok(Some("set x 1".into())));
+        assert_eq!(ctx.expand("!2; puts $x"), Ok(Some("incr x; puts $x".into())));
+
+        assert_eq!(ctx.expand("!3"), Err("!3: event not found".into()));
+        assert_eq!(ctx.expand("!0 x"), Err("!0: event not found".into()));
+        assert_eq!(HistoryCtx::new().expand("!!"), Err("!!: event not found".into()));
+    }
+
+    fn interp() -> Interp<((), HistoryCtx)> {
+        Interp::new(
+            ((), HistoryCtx::new()),
+            gen_command!(
+                ((), HistoryCtx),
+                [],
+                [(
+                    "history",
+                    cmd_history,
+                    ArgSpec { min: 1, max: 3, usage: "?subcommand? ?arg?" }
+                )]
+            ),
+            false,
+            "history-test",
+        )
+    }
+
+    #[test]
+    fn test_cmd_history() {
+        let mut interp = interp();
+        interp.context.1.add("set x 1");
+        interp.context.1.add("incr x");
+
+        assert_eq!(
+            interp.eval("history"),
+            Ok(Value::from("     1  set x 1\n     2  incr x"))
+        );
+
+        interp.eval("set x 1").unwrap();
+        assert_eq!(interp.eval("history redo 2"), Ok(Value::from(2)));
+        assert_eq!(interp.eval("history redo 2"), Ok(Value::from(3)));
+        assert_eq!(
+            interp.eval("history redo 3").unwrap_err().value(),
+            Value::from("event \"3\" not found")
+        );
+        assert_eq!(
+            interp.eval("history bogus").unwrap_err().value(),
+            Value::from("bad option \"bogus\": must be clear or redo")
+        );
+
+        assert_eq!(interp.eval("history clear"), Ok(Value::empty()));
+        assert!(interp.context.1.is_empty());
+        assert_eq!(interp.eval("history"), Ok(Value::empty()));
+    }
+}
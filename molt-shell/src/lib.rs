@@ -6,15 +6,22 @@
 //! In each case, begin by creating a `molt::Interp` and adding any application-specific
 //! extensions.  Then:
 //!
-//! * To invoke the REPL, use [`molt_shell::repl`](./fn.repl.html).
+//! * To invoke the REPL, use [`molt_shell::repl`](./fn.repl.html); for a REPL with
+//!   `!!`/`!N` history expansion and the `history` command, use
+//!   [`molt_shell::history_repl`](./fn.history_repl.html).
 //! * To execute a script, use [`molt_shell::script`](./fn.script.html).
+//! * To execute a script given on the command line, use
+//!   [`molt_shell::command`](./fn.command.html); to execute a script read from
+//!   standard input, use [`molt_shell::read_script`](./fn.read_script.html).
 //! * To execute the test harness on a Molt test script, use
 //!   [`molt_shell::test_harness`](./test_harness/index.html).
 //! * To execute the benchmark harness on a Molt test script, use
 //!   [`molt_shell::bench`](./bench/index.html).
 
 pub mod bench;
+pub mod history;
 mod shell;
 
 pub use bench::*;
+pub use history::*;
 pub use shell::*;
@@ -1,7 +1,8 @@
+use crate::history::HasHistoryCtx;
 use molt_forked::prelude::*;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
-use std::fs;
+use std::io::Read;
 
 /// Invokes an interactive REPL for the given interpreter, using `rustyline` line editing.
 ///
@@ -10,8 +11,12 @@ use std::fs;
 /// application to terminate (but the `exit` command can be removed or redefined by the
 /// application).
 ///
+/// A line that leaves a brace, bracket, or quote open is not evaluated; the REPL prompts
+/// for more lines until the script is complete.  Malformed input, e.g., `set a {b}c`,
+/// is reported at once.
+///
 /// To change the prompt, set the `tcl_prompt1` TCL variable to a script that returns
-/// the desired prompt.
+/// the desired prompt; `tcl_prompt2` does the same for continuation lines.
 ///
 /// See [`molt::interp`](../molt/interp/index.html) for details on how to configure and
 /// add commands to a Molt interpreter.
@@ -31,37 +36,132 @@ use std::fs;
 /// ```
 pub fn repl<Ctx: 'static>(interp: &mut Interp<Ctx>) {
     let mut rl = Editor::<()>::new();
+    let mut pending = String::new();
 
     loop {
-        let readline = if let Ok(pscript) = interp.scalar("tcl_prompt1") {
-            match interp.eval(pscript.as_str()) {
-                Ok(prompt) => rl.readline(prompt.as_str()),
-                Err(exception) => {
-                    println!("{}", exception.value());
-                    rl.readline("% ")
+        match read_line(interp, &mut rl, !pending.is_empty()) {
+            Ok(line) => {
+                if pending.is_empty() && line.trim().is_empty() {
+                    continue;
+                }
+
+                let line = match accumulate(interp, &mut pending, &line) {
+                    Input::Eval(script) => script,
+                    Input::More => continue,
+                    Input::Malformed(msg) => {
+                        println!("{}", msg);
+                        continue;
+                    }
+                };
+
+                match interp.eval(&line) {
+                    Ok(value) => {
+                        rl.add_history_entry(line.as_str());
+
+                        // Don't output empty values.
+                        if !value.as_str().is_empty() {
+                            println!("{}", value);
+                        }
+                    }
+                    Err(exception) => {
+                        println!("{}", exception.value());
+                    }
                 }
             }
-        } else {
-            rl.readline("% ")
-        };
+            Err(ReadlineError::Interrupted) => {
+                println!("^C");
+                break;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(err) => {
+                println!("I/O Error: {:?}", err);
+                break;
+            }
+        }
+    }
+}
 
-        match readline {
-            Ok(line) => {
-                let line = line.trim();
-                if !line.is_empty() {
-                    match interp.eval(line) {
-                        Ok(value) => {
-                            rl.add_history_entry(line);
-
-                            // Don't output empty values.
-                            if !value.as_str().is_empty() {
-                                println!("{}", value);
-                            }
+/// Invokes an interactive REPL with command history, as for [`repl`].
+///
+/// Each successfully evaluated line is recorded in the interpreter's
+/// [`HistoryCtx`](./history/struct.HistoryCtx.html) (found via [`HasHistoryCtx`]), which
+/// the `history` command, [`cmd_history`](./history/fn.cmd_history.html), lists and
+/// replays.  A line beginning with `!!` or `!N` is expanded to the previous event or
+/// event `N`; the expanded text is echoed before it is evaluated, and is recorded in
+/// expanded form.  Only the first line of a multi-line script is expanded.
+///
+/// # Example
+///
+/// ```no_run
+/// use molt_forked::prelude::*;
+/// use molt_shell::{cmd_history, HistoryCtx};
+///
+/// let mut interp = Interp::new(
+///     ((), HistoryCtx::new()),
+///     gen_command!(
+///         ((), HistoryCtx),
+///         [],
+///         [("history", cmd_history, ArgSpec { min: 1, max: 3, usage: "?subcommand? ?arg?" })]
+///     ),
+///     true,
+///     "my-shell",
+/// );
+///
+/// molt_shell::history_repl(&mut interp);
+/// ```
+pub fn history_repl<Ctx: HasHistoryCtx>(interp: &mut Interp<Ctx>) {
+    let mut rl = Editor::<()>::new();
+    let mut pending = String::new();
+
+    loop {
+        match read_line(interp, &mut rl, !pending.is_empty()) {
+            Ok(mut line) => {
+                if pending.is_empty() {
+                    line = line.trim().to_string();
+                    if line.is_empty() {
+                        continue;
+                    }
+
+                    match interp.context.history_ctx_mut().expand(&line) {
+                        Ok(Some(expanded)) => {
+                            println!("{}", expanded);
+                            line = expanded;
+                        }
+                        Ok(None) => (),
+                        Err(msg) => {
+                            println!("{}", msg);
+                            continue;
                         }
-                        Err(exception) => {
-                            println!("{}", exception.value());
+                    }
+                }
+
+                let line = match accumulate(interp, &mut pending, &line) {
+                    Input::Eval(script) => script,
+                    Input::More => continue,
+                    Input::Malformed(msg) => {
+                        println!("{}", msg);
+                        continue;
+                    }
+                };
+
+                match interp.eval(&line) {
+                    Ok(value) => {
+                        // `history clear` empties the shared history; keep the
+                        // line editor's history in step.
+                        if interp.context.history_ctx_mut().is_empty() {
+                            rl.clear_history();
+                        }
+                        interp.context.history_ctx_mut().add(&line);
+                        rl.add_history_entry(line.as_str());
+
+                        // Don't output empty values.
+                        if !value.as_str().is_empty() {
+                            println!("{}", value);
                         }
                     }
+                    Err(exception) => {
+                        println!("{}", exception.value());
+                    }
                 }
             }
             Err(ReadlineError::Interrupted) => {
@@ -77,6 +177,62 @@ pub fn repl<Ctx: 'static>(interp: &mut Interp<Ctx>) {
     }
 }
 
+/// What the REPL does with the input accumulated so far.
+#[derive(Debug, PartialEq, Eq)]
+enum Input {
+    /// Evaluate the script.
+    Eval(String),
+    /// Read another line: the script leaves a delimiter open.
+    More,
+    /// Report the error: no further input can make the script parse.
+    Malformed(String),
+}
+
+/// Adds a line of input to the `pending` script, and decides what to do with it.  The
+/// pending script is cleared unless more input is needed.
+fn accumulate<Ctx>(interp: &mut Interp<Ctx>, pending: &mut String, line: &str) -> Input {
+    if !pending.is_empty() {
+        pending.push('\n');
+    }
+    pending.push_str(line);
+
+    match interp.completeness(pending) {
+        Completeness::Complete => {
+            let script = pending.trim().to_string();
+            pending.clear();
+            Input::Eval(script)
+        }
+        Completeness::Incomplete { .. } => Input::More,
+        Completeness::Malformed { message } => {
+            pending.clear();
+            Input::Malformed(message)
+        }
+    }
+}
+
+/// Reads a line of input, using `tcl_prompt1` to compute the prompt if it's defined, or
+/// `tcl_prompt2` when continuing an incomplete script.
+fn read_line<Ctx: 'static>(
+    interp: &mut Interp<Ctx>,
+    rl: &mut Editor<()>,
+    continuing: bool,
+) -> Result<String, ReadlineError> {
+    let (var, default) =
+        if continuing { ("tcl_prompt2", "> ") } else { ("tcl_prompt1", "% ") };
+
+    if let Ok(pscript) = interp.scalar(var) {
+        match interp.eval(pscript.as_str()) {
+            Ok(prompt) => rl.readline(prompt.as_str()),
+            Err(exception) => {
+                println!("{}", exception.value());
+                rl.readline(default)
+            }
+        }
+    } else {
+        rl.readline(default)
+    }
+}
+
 /// Executes a script from a set of command line arguments.
 ///
 /// `args[0]` is presumed to be the name of a Molt script file, with any subsequent
@@ -119,16 +275,92 @@ pub fn repl<Ctx: 'static>(interp: &mut Interp<Ctx>) {
 pub fn script<Ctx: 'static>(interp: &mut Interp<Ctx>, args: &[String]) {
     let arg0 = &args[0];
     let argv = &args[1..];
-    match fs::read_to_string(&args[0]) {
-        Ok(script) => execute_script(interp, script, arg0, argv),
-        Err(e) => println!("{}", e),
+    execute_script(interp, arg0, argv);
+}
+
+/// Executes a script given on the command line, as for `molt shell -c <script>`, and
+/// returns the process exit status.
+///
+/// `args[0]` is the text of the script, with any subsequent arguments being arguments
+/// to pass to the script; they are exposed as `argv`, and `arg0` is set to `-c`.
+/// Returns `0` if the script succeeds; otherwise prints the error's `errorInfo` to
+/// stderr and returns `1`.
+///
+/// # Example
+///
+/// ```no_run
+/// use molt_forked::prelude::*;
+///
+/// let mut interp = Interp::default();
+/// let status = molt_shell::command(&mut interp, &["puts [expr {2 * 21}]".into()]);
+/// std::process::exit(status);
+/// ```
+pub fn command<Ctx: 'static>(interp: &mut Interp<Ctx>, args: &[String]) -> i32 {
+    match eval_with_args(interp, &args[0], "-c", &args[1..]) {
+        Ok(_) => 0,
+        Err(exception) => {
+            eprintln!("{}", exception.into_report_string());
+            1
+        }
     }
 }
 
-/// Executes a script read from a file, with any command-line arguments, in
-/// the context of the given interpreter.  The `script` is the text of the
-/// script, `arg0` is the name of the script file, and `argv` contains the script
-/// arguments.
+/// Executes a script read from the given reader, e.g., standard input when it isn't a
+/// terminal, and returns the process exit status.
+///
+/// The script is read in full before it is evaluated.  `args` are exposed to the script
+/// as `argv`, and `arg0` is set to `-`.  Returns `0` if the script succeeds; otherwise
+/// prints the error's `errorInfo` (or the I/O error) to stderr and returns `1`.
+///
+/// # Example
+///
+/// ```no_run
+/// use molt_forked::prelude::*;
+///
+/// let mut interp = Interp::default();
+/// let status = molt_shell::read_script(&mut interp, std::io::stdin(), &[]);
+/// std::process::exit(status);
+/// ```
+pub fn read_script<Ctx: 'static, R: Read>(
+    interp: &mut Interp<Ctx>,
+    mut reader: R,
+    args: &[String],
+) -> i32 {
+    let mut script = String::new();
+
+    if let Err(e) = reader.read_to_string(&mut script) {
+        eprintln!("{}", e);
+        return 1;
+    }
+
+    match eval_with_args(interp, &script, "-", args) {
+        Ok(_) => 0,
+        Err(exception) => {
+            eprintln!("{}", exception.into_report_string());
+            1
+        }
+    }
+}
+
+/// Sets the `arg0` and `argv` variables and evaluates the script, returning the result.
+///
+/// This is the common core of [`command`] and [`read_script`], for clients that want
+/// to handle the result themselves.
+pub fn eval_with_args<Ctx: 'static>(
+    interp: &mut Interp<Ctx>,
+    script: &str,
+    arg0: &str,
+    argv: &[String],
+) -> MoltResult {
+    let argv: MoltList = argv.iter().map(Value::from).collect();
+    interp.set_scalar("arg0", Value::from(arg0))?;
+    interp.set_scalar("argv", Value::from(argv))?;
+    interp.eval(script)
+}
+
+/// Executes a script file, with any command-line arguments, in the context of the
+/// given interpreter.  `arg0` is the name of the script file, and `argv` contains the
+/// script arguments.  While it runs, the file is reported by `info script`.
 ///
 /// # Molt Variables
 ///
@@ -137,12 +369,7 @@ pub fn script<Ctx: 'static>(interp: &mut Interp<Ctx>, args: &[String]) {
 ///
 /// * The Molt variable `arg0` will be set to the `arg0` value.
 /// * The Molt variable `argv` will be set to the `argv` array as a Molt list.
-fn execute_script<Ctx: 'static>(
-    interp: &mut Interp<Ctx>,
-    script: String,
-    arg0: &str,
-    argv: &[String],
-) {
+fn execute_script<Ctx: 'static>(interp: &mut Interp<Ctx>, arg0: &str, argv: &[String]) {
     let argv: MoltList = argv.iter().map(Value::from).collect();
     interp
         .set_scalar("arg0", Value::from(arg0))
@@ -151,7 +378,7 @@ fn execute_script<Ctx: 'static>(
         .set_scalar("argv", Value::from(argv))
         .expect("argv predefined as array!");
 
-    match interp.eval(&script) {
+    match interp.eval_file(arg0) {
         Ok(_) => (),
         Err(exception) => {
             eprintln!("{}", exception.value());
@@ -159,3 +386,109 @@ fn execute_script<Ctx: 'static>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        let mut interp = Interp::default();
+        let args = ["set x [lindex $argv 1]".to_string(), "a".into(), "b".into()];
+        assert_eq!(command(&mut interp, &args), 0);
+        assert_eq!(interp.scalar("x"), Ok(Value::from("b")));
+        assert_eq!(interp.scalar("arg0"), Ok(Value::from("-c")));
+
+        assert_eq!(command(&mut interp, &["error oops".into()]), 1);
+        assert_eq!(command(&mut interp, &["set".into()]), 1);
+    }
+
+    #[test]
+    fn test_accumulate() {
+        let mut interp = Interp::default();
+        let mut pending = String::new();
+
+        // A complete line is evaluated at once.
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "  set a 1 "),
+            Input::Eval("set a 1".into())
+        );
+        assert!(pending.is_empty());
+
+        // An open brace asks for more, until it is closed.
+        assert_eq!(accumulate(&mut interp, &mut pending, "proc p {} {"), Input::More);
+        assert_eq!(accumulate(&mut interp, &mut pending, ""), Input::More);
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "    return [list a"),
+            Input::More
+        );
+        assert_eq!(accumulate(&mut interp, &mut pending, "  b]"), Input::More);
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "}"),
+            Input::Eval("proc p {} {\n\n    return [list a\n  b]\n}".into())
+        );
+        assert!(pending.is_empty());
+
+        // Open quotes and brackets ask for more, too.
+        assert_eq!(accumulate(&mut interp, &mut pending, "set b \"x"), Input::More);
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "y\""),
+            Input::Eval("set b \"x\ny\"".into())
+        );
+        assert_eq!(accumulate(&mut interp, &mut pending, "set c [list"), Input::More);
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "x]"),
+            Input::Eval("set c [list\nx]".into())
+        );
+
+        // Malformed input is reported at once, and discarded.
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "set a {b}c"),
+            Input::Malformed("extra characters after close-brace".into())
+        );
+        assert!(pending.is_empty());
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "set a \"b\"c"),
+            Input::Malformed("extra characters after close-quote".into())
+        );
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "set a $b(c"),
+            Input::Malformed("missing )".into())
+        );
+
+        // ...including when a continuation line makes it so.
+        assert_eq!(accumulate(&mut interp, &mut pending, "set a [list {"), Input::More);
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "}x]"),
+            Input::Malformed("extra characters after close-brace".into())
+        );
+        assert!(pending.is_empty());
+
+        // A stray close-brace is just a word.
+        assert_eq!(
+            accumulate(&mut interp, &mut pending, "set a }"),
+            Input::Eval("set a }".into())
+        );
+    }
+
+    #[test]
+    fn test_read_script() {
+        let mut interp = Interp::default();
+        let input = "set x 1\nset y [expr {$x + [llength $argv]}]\n";
+        assert_eq!(read_script(&mut interp, input.as_bytes(), &["a".into()]), 0);
+        assert_eq!(interp.scalar("y"), Ok(Value::from(2)));
+        assert_eq!(interp.scalar("arg0"), Ok(Value::from("-")));
+
+        assert_eq!(read_script(&mut interp, "error oops".as_bytes(), &[]), 1);
+    }
+
+    #[test]
+    fn test_eval_with_args() {
+        let mut interp = Interp::default();
+        let result = eval_with_args(&mut interp, "list $arg0 $argv", "me", &["1".into()]);
+        assert_eq!(result, Ok(Value::from("me 1")));
+
+        let result = eval_with_args(&mut interp, "error oops", "me", &[]);
+        assert_eq!(result.unwrap_err().value(), Value::from("oops"));
+    }
+}
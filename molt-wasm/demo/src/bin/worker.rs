@@ -0,0 +1,25 @@
+//! The demo's web worker, which runs the commands offloaded by the main app.
+
+/// Lists the primes up to `n`, using a sieve.
+fn primes(args: Vec<String>) -> Result<String, String> {
+    let n: usize = match args.first().map(|arg| arg.parse()) {
+        Some(Ok(n)) => n,
+        _ => return Err("expected a non-negative integer".into()),
+    };
+
+    let mut is_prime = vec![true; n + 1];
+    let mut primes = Vec::new();
+
+    for i in 2..=n {
+        if is_prime[i] {
+            primes.push(i.to_string());
+            for multiple in (i * i..=n).step_by(i) {
+                is_prime[multiple] = false;
+            }
+        }
+    }
+
+    Ok(primes.join(" "))
+}
+
+molt_wasm::offload_worker!(("primes", primes));
@@ -1,10 +1,16 @@
-use molt_wasm::{molt::prelude::*, RunState, Terminal};
-use std::{mem, rc::Rc};
+use gloo::timers::callback::Timeout;
+use molt_wasm::{
+    gen_ctxvar_command,
+    molt::prelude::*,
+    offload::{Offloader, Pending},
+    schedule_events, CancelHandle, HistEntry, RunState, Terminal,
+};
+use std::{cell::RefCell, mem, rc::Rc, time::Duration};
 use yew::prelude::*;
 use yew_icons::{Icon, IconId};
 include!(concat!(env!("OUT_DIR"), "/compile_info.rs"));
 
-const INIT_CMDS: [&str; 9] = [
+const INIT_CMDS: [&str; 12] = [
     "about",
     "proc say_hello {name} {
     puts \"Hello, $name!\"
@@ -20,38 +26,102 @@ const INIT_CMDS: [&str; 9] = [
     lappend a $i
 }",
     "set a",
+    "ctxvar set num [square 5]",
     "square \"it-should-error\"",
+    "primes 50",
+    "after 1000 {puts \"a second later\"}",
     "help -all",
     "brower -help",
 ];
 
+/// The number of history entries the terminal renders.
+const MAX_VISIBLE: usize = 500;
+
+/// How long a command may run.  The page can't take a click while a script runs, so
+/// this is what stops a runaway script, e.g., `while 1 {}`.
+const TIME_LIMIT: Duration = Duration::from_secs(5);
+
 impl App {
     #[inline]
     fn execute(&mut self, cmd: String) {
-        let out = self.interp.eval(&cmd);
-        let mut outs = mem::take(&mut self.interp.std_buff);
+        // A new command runs even after Stop.
+        self.cancel.reset();
+        let interp = &mut *self.interp.borrow_mut();
+        let out = interp.eval(&cmd);
+        let pending = out.as_ref().ok().and_then(Pending::from_value);
+        let mut outs = mem::take(&mut interp.std_buff);
         outs.push(out);
-        Rc::make_mut(&mut self.interp.context.hist)
-            .push(Terminal::to_hist(cmd.trim().into(), outs));
+        let hist = Rc::make_mut(&mut interp.context.hist);
+        if let Some(pending) = pending {
+            self.pending.push((pending, hist.len()));
+        }
+        hist.push(Terminal::to_hist(cmd.trim().into(), outs));
+        self.events_timer = schedule_events(interp, self.events_due.clone());
+    }
+
+    /// Runs the scripts scheduled by `after` that are due, showing their output as a
+    /// history entry of its own.
+    fn run_events(&mut self) {
+        let interp = &mut *self.interp.borrow_mut();
+        let result = interp.run_pending_events();
+        let mut outs = mem::take(&mut interp.std_buff);
+        if let Err(e) = result {
+            outs.push(Err(e));
+        }
+        if !outs.is_empty() {
+            let hist = Rc::make_mut(&mut interp.context.hist);
+            hist.push(Terminal::to_hist("# after".into(), outs));
+        }
+        self.events_timer = schedule_events(interp, self.events_due.clone());
+    }
+
+    /// Replaces a pending command's history entry with its result.
+    fn resolve(&mut self, pending: Pending, result: Result<String, String>) {
+        let Some(i) = self.pending.iter().position(|(p, _)| *p == pending) else {
+            return;
+        };
+        let (_, index) = self.pending.remove(i);
+
+        let interp = &mut *self.interp.borrow_mut();
+        let hist = Rc::make_mut(&mut interp.context.hist);
+        // The history may have been cleared in the meantime.
+        if let Some((RunState::Pending, cmd, _, _)) = hist.get(index) {
+            let out = result
+                .map(Value::from)
+                .map_err(|msg| Exception::molt_err(Value::from(msg)));
+            hist[index] = Terminal::to_hist(cmd.clone(), vec![out]);
+        }
     }
 }
 pub enum AppMsg {
     RunCmd(String, bool),
+    Resolved(Pending, Result<String, String>),
+    /// A script scheduled by `after` is due.
+    RunEvents,
+    /// Cancel the scripts scheduled by `after`, e.g., a loop that keeps rescheduling
+    /// itself.
+    Stop,
     ToggleDark,
 }
 
-pub fn cmd_square(interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
-    // Correct number of arguments?
-    check_args(1, argv, 2, 2, "x")?;
+pub fn cmd_square(_interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
+    // The argument count is checked by the dispatcher; see the ArgSpec below.
     // Get x, if it's an integer
     let x = argv[1].as_int()?;
-    let out = x * x;
-    interp.context.num = out as usize;
-    molt_ok!(out)
+    molt_ok!(x * x)
 }
 
-pub fn cmd_about(interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 1, 1, "")?;
+/// Lists the primes up to n.  The sieve runs on the demo's web worker, so a large n
+/// doesn't freeze the page.
+pub fn cmd_primes(interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
+    let n = argv[1].as_int()?;
+    if n < 0 {
+        return molt_err!("expected a non-negative integer but got \"{}\"", argv[1]);
+    }
+    interp.context.offloader.run("primes", vec![n.to_string()])
+}
+
+pub fn cmd_about(interp: &mut Interp<AppCtx>, _argv: &[Value]) -> MoltResult {
     molt_ok!(
         "{} {} ({})\n{} {}\nType \"help\" for more information.",
         interp.name,
@@ -62,12 +132,19 @@ pub fn cmd_about(interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
     )
 }
 
-pub fn cmd_clear(interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 1, 1, "")?;
+pub fn cmd_clear(interp: &mut Interp<AppCtx>, _argv: &[Value]) -> MoltResult {
     Rc::make_mut(&mut interp.context.hist).clear();
     molt_ok!()
 }
 
+/// Returns the session so far as plain text, e.g., to paste into a bug report.
+pub fn cmd_session(interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
+    match argv[1].as_str() {
+        "export" => molt_ok!(Terminal::export_text(&interp.context.hist)),
+        sub => molt_err!("unknown subcommand \"{}\", must be: export", sub),
+    }
+}
+
 pub fn cmd_brower_alert(_interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
     if let Some(window) = web_sys::window() {
         let input = if let Some(v) = argv.get(2) {
@@ -148,11 +225,22 @@ const cmd_brower: fn(&mut Interp<AppCtx>, &[Value]) -> Result<Value, Exception>
 
 pub struct AppCtx {
     num: usize,
-    pub hist: Rc<Vec<(RunState, String, Html)>>,
+    pub hist: Rc<Vec<HistEntry>>,
+    offloader: Offloader,
 }
 pub struct App {
     darkmode: bool,
-    interp: Interp<AppCtx>,
+    // Shared with the terminal's `check_complete` callback.
+    interp: Rc<RefCell<Interp<AppCtx>>>,
+    check_complete: Callback<String, bool>,
+    // Offloaded commands awaiting their results, with their history entries' indices.
+    pending: Vec<(Pending, usize)>,
+    // The timer for the next script scheduled by `after`, and the callback it calls.
+    events_timer: Option<Timeout>,
+    events_due: Callback<()>,
+    // The interpreter's cancel handle.  The cancellation is sticky, so that Stop cancels
+    // the next script scheduled by `after`, which drops the rest of them.
+    cancel: CancelHandle,
 }
 
 impl Component for App {
@@ -160,25 +248,90 @@ impl Component for App {
 
     type Properties = ();
 
-    fn create(_ctx: &Context<Self>) -> Self {
-        let interp = Interp::new(
-            AppCtx { num: 0, hist: Rc::new(Vec::new()) },
-            gen_command!(
-                AppCtx,
-                // native commands
-                [],
-                // embedded commands
-                [
-                    ("about", " ", cmd_about, "display app information"),
-                    ("square", "", cmd_square, "square input and set app context number"),
-                    ("clear", " ", cmd_clear, "clear history"),
-                    ("brower", "", cmd_brower, "call brower APIs"),
-                ]
-            ),
-            false,
-            "molt-wasm-demo",
-        );
-        let mut app = Self { darkmode: true, interp };
+    fn create(ctx: &Context<Self>) -> Self {
+        let offloader = {
+            let resolved = ctx
+                .link()
+                .callback(|(pending, result)| AppMsg::Resolved(pending, result));
+            Offloader::spawn("./worker.js", move |pending, result| {
+                resolved.emit((pending, result))
+            })
+        };
+        let mut interp =
+            InterpBuilder::new(AppCtx { num: 0, hist: Rc::new(Vec::new()), offloader })
+                .commands(gen_command!(
+                    AppCtx,
+                    // native commands
+                    [],
+                    // embedded commands
+                    [
+                        (
+                            "about",
+                            cmd_about,
+                            ArgSpec { min: 1, max: 1, usage: "" },
+                            "display app information"
+                        ),
+                        (
+                            "square",
+                            cmd_square,
+                            ArgSpec { min: 2, max: 2, usage: "x" },
+                            "square input"
+                        ),
+                        (
+                            "primes",
+                            cmd_primes,
+                            ArgSpec { min: 2, max: 2, usage: "n" },
+                            "list the primes up to n, on a web worker"
+                        ),
+                        (
+                            "ctxvar",
+                            gen_ctxvar_command!(AppCtx, [("num", num, usize)]),
+                            ArgSpec { min: 3, max: 4, usage: "get|set name ?value?" },
+                            "read and write app context variables"
+                        ),
+                        (
+                            "clear",
+                            cmd_clear,
+                            ArgSpec { min: 1, max: 1, usage: "" },
+                            "clear history"
+                        ),
+                        (
+                            "session",
+                            cmd_session,
+                            ArgSpec { min: 2, max: 2, usage: "export" },
+                            "return the session as plain text"
+                        ),
+                        ("brower", "", cmd_brower, "call brower APIs"),
+                    ]
+                ))
+                // User scripts run in a safe interpreter, with the demo's commands.
+                .safe(&[
+                    "about", "square", "primes", "ctxvar", "clear", "session", "brower",
+                ])
+                .name("molt-wasm-demo")
+                .build();
+        interp.set_time_limit(Some(TIME_LIMIT));
+        interp.set_sticky_cancel(true);
+        let cancel = interp.cancel_handle();
+        let interp = Rc::new(RefCell::new(interp));
+        let check_complete = {
+            let interp = interp.clone();
+            Callback::from(move |cmd: String| {
+                !matches!(
+                    interp.borrow_mut().completeness(&cmd),
+                    Completeness::Incomplete { .. }
+                )
+            })
+        };
+        let mut app = Self {
+            darkmode: true,
+            interp,
+            check_complete,
+            pending: Vec::new(),
+            events_timer: None,
+            events_due: ctx.link().callback(|_| AppMsg::RunEvents),
+            cancel,
+        };
         for cmd in INIT_CMDS {
             app.execute(cmd.into());
         }
@@ -189,14 +342,18 @@ impl Component for App {
         match msg {
             AppMsg::RunCmd(cmd, previous_is_uncompleted) => {
                 if previous_is_uncompleted {
-                    let previous = Rc::make_mut(&mut self.interp.context.hist).pop();
-                    if let Some((_, previous_cmd, previous_out)) = previous {
+                    let previous =
+                        Rc::make_mut(&mut self.interp.borrow_mut().context.hist).pop();
+                    if let Some((_, previous_cmd, previous_out, previous_text)) = previous
+                    {
                         if cmd.trim().is_empty() {
                             // If uncompleted continue with nothing, then just return error
-                            Rc::make_mut(&mut self.interp.context.hist).push((
+                            let hist = &mut self.interp.borrow_mut().context.hist;
+                            Rc::make_mut(hist).push((
                                 RunState::Err,
                                 previous_cmd,
                                 previous_out,
+                                previous_text,
                             ));
                         } else {
                             self.execute(previous_cmd + "\n" + &cmd)
@@ -208,24 +365,41 @@ impl Component for App {
                     self.execute(cmd)
                 }
             }
+            AppMsg::Resolved(pending, result) => self.resolve(pending, result),
+            AppMsg::RunEvents => self.run_events(),
+            AppMsg::Stop => {
+                self.cancel.cancel();
+                self.run_events();
+            }
             AppMsg::ToggleDark => self.darkmode = !self.darkmode,
         }
         true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        let interp = self.interp.borrow();
+        let pending_prefix = match interp.context.hist.last() {
+            Some((RunState::Uncompleted, cmd, _, _)) => Some(cmd.clone()),
+            _ => None,
+        };
         html! {
             <>
                 <div>
                     <div onclick={ctx.link().callback(|_|AppMsg::ToggleDark)}>
                         <Icon icon_id={if self.darkmode{IconId::FeatherMoon}else{IconId::FeatherSun}} height={"20px".to_owned()} width={"20px".to_owned()}/>
                     </div>
+                    <div onclick={ctx.link().callback(|_|AppMsg::Stop)} title="stop the scripts scheduled by `after`">
+                        <Icon icon_id={IconId::FeatherStopCircle} height={"20px".to_owned()} width={"20px".to_owned()}/>
+                    </div>
                     <a href="https://github.com/zao111222333/molt-forked/tree/master/molt-wasm/demo"><code>{"code"}</code><Icon icon_id={IconId::BootstrapGithub} height={"10px".to_owned()} width={"15px".to_owned()}/></a>
-                    <code>{" The context number is "}</code><code style="color:red;">{self.interp.context.num}</code><code>{", run `square [number]` to change it"}</code>
+                    <code>{" The context number is "}</code><code style="color:red;">{interp.context.num}</code><code>{", run `ctxvar set num [square 5]` to change it"}</code>
                 </div>
                 <Terminal
                     class={if self.darkmode{ "terminal dark" }else{ "terminal" }}
-                    hist={self.interp.context.hist.clone()}
+                    hist={interp.context.hist.clone()}
+                    max_visible={Some(MAX_VISIBLE)}
+                    check_complete={Some(self.check_complete.clone())}
+                    pending_prefix={pending_prefix}
                     on_run_cmd={ctx.link().callback(|(cmd,previous_is_uncompleted)|AppMsg::RunCmd(cmd,previous_is_uncompleted))}
                 />
             </>
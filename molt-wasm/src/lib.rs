@@ -2,17 +2,26 @@ use gloo::{console::debug, timers::callback::Timeout};
 // re-export molt_forked
 use molt::prelude::*;
 pub use molt_forked as molt;
+// The host cancels a running script through the interpreter's handle.
+pub use molt_forked::cancel::CancelHandle;
 use std::{mem, rc::Rc};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
 use web_sys::HtmlTextAreaElement;
 use yew::prelude::*;
 use yew_icons::{Icon, IconId};
 
+pub mod offload;
+
 pub struct Terminal {
     input_div_ref: NodeRef,
     hist_div_ref: NodeRef,
     input: String,
     input_tmp: String,
     current_hist_idx: Option<usize>,
+    // Set when Enter inserted a newline because the input was incomplete.
+    continuing: bool,
+    // Why the last copy to the clipboard failed; shown until it times out.
+    copy_error: Option<String>,
 }
 #[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub enum RunState {
@@ -20,14 +29,316 @@ pub enum RunState {
     Ok,
     Err,
     Uncompleted,
+    /// The command offloaded its work, and its result hasn't arrived yet; see
+    /// [`offload`].
+    Pending,
+}
+
+/// A history entry: the run state, the command, its rendered output, and the output as
+/// plain text, for copying and [`Terminal::export_text`].
+pub type HistEntry = (RunState, String, Html, String);
+
+/// The text shown for an error in the history.  Incomplete input also says where the
+/// unclosed delimiter was opened, e.g. "missing close-brace opened at line 2".
+fn error_text(e: &Exception) -> String {
+    match e.uncompleted_position() {
+        Some((_, line)) => format!("{} opened at line {}", e.value(), line),
+        None => e.error_info().to_string(),
+    }
+}
+
+/// A piece of command output: plain text, or a URL to render as a link.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Segment<'a> {
+    Text(&'a str),
+    Link(&'a str),
+}
+
+/// Splits text into plain text and http(s) URLs.  A URL ends at white space, at a
+/// character that can't appear unescaped in a URL, or before trailing punctuation.
+fn split_links(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let tail = &rest[start..];
+        let len = tail
+            .find(|c: char| {
+                c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | '`')
+            })
+            .unwrap_or(tail.len());
+        let url = tail[..len].trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        if url.ends_with("//") {
+            // Just a scheme; not a link.
+            segments.push(Segment::Text(url));
+        } else {
+            segments.push(Segment::Link(url));
+        }
+        rest = &tail[url.len()..];
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    segments
+}
+
+/// Renders command output, wrapping http(s) URLs in links.  Everything is rendered as
+/// text nodes and attribute values, which yew escapes, so output can't inject HTML.
+pub fn linkify(text: &str) -> Html {
+    html! {
+        {for split_links(text).into_iter().map(|segment| match segment {
+            Segment::Text(text) => html!({ text.to_string() }),
+            Segment::Link(url) => html!(
+                <a href={url.to_string()} target="_blank" rel="noopener noreferrer">{ url.to_string() }</a>
+            ),
+        })}
+    }
+}
+
+/// Sets a timer that calls `on_due` when the next script scheduled by `after` is due, or
+/// returns `None` if there are none.  When it fires, the app calls
+/// [`Interp::run_pending_events`] and sets the timer again.  Dropping the [`Timeout`]
+/// cancels it, so the app keeps only the latest one, and sets a new one whenever a
+/// command may have scheduled a script.
+pub fn schedule_events<Ctx>(
+    interp: &Interp<Ctx>,
+    on_due: Callback<()>,
+) -> Option<Timeout> {
+    let delay = interp.next_event_delay()?;
+    let millis = u32::try_from(delay.as_millis()).unwrap_or(u32::MAX);
+    Some(Timeout::new(millis, move || on_due.emit(())))
+}
+
+/// A context field type that [`gen_ctxvar_command!`] can read and write.
+pub trait CtxVar: Sized {
+    /// Converts the field's value to a Molt `Value`.
+    fn to_value(&self) -> Value;
+    /// Converts a Molt `Value` to the field's type, or explains why it can't.
+    fn from_value(value: &Value) -> Result<Self, Exception>;
+}
+
+macro_rules! impl_ctxvar_int {
+    ($($ty:ty),*) => {
+        $(
+            impl CtxVar for $ty {
+                fn to_value(&self) -> Value {
+                    Value::from(*self as MoltInt)
+                }
+                fn from_value(value: &Value) -> Result<Self, Exception> {
+                    <$ty>::try_from(value.as_int()?).or_else(|_| {
+                        molt_err!("expected {} but got \"{}\"", stringify!($ty), value)
+                    })
+                }
+            }
+        )*
+    };
 }
 
-#[derive(Debug, Properties, PartialEq)]
+impl_ctxvar_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl CtxVar for f64 {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+    fn from_value(value: &Value) -> Result<Self, Exception> {
+        value.as_float()
+    }
+}
+
+impl CtxVar for bool {
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+    fn from_value(value: &Value) -> Result<Self, Exception> {
+        value.as_bool()
+    }
+}
+
+impl CtxVar for String {
+    fn to_value(&self) -> Value {
+        Value::from(self.as_str())
+    }
+    fn from_value(value: &Value) -> Result<Self, Exception> {
+        Ok(value.as_str().to_string())
+    }
+}
+
+/// Generates a `ctxvar get|set name ?value?` command that bridges Tcl and the
+/// application context: each entry exposes the context field `field`, of type `ty`
+/// (see [`CtxVar`]), as the variable `name`.  `get` returns the field's value; `set`
+/// converts the value to the field's type, stores it, and returns it.
+///
+/// # Example
+///
+/// ```
+/// use molt_wasm::{gen_ctxvar_command, molt::prelude::*};
+///
+/// struct AppCtx {
+///     num: usize,
+///     title: String,
+/// }
+///
+/// let mut interp = Interp::new(
+///     AppCtx { num: 0, title: "demo".into() },
+///     gen_command!(
+///         AppCtx,
+///         [],
+///         [(
+///             "ctxvar",
+///             gen_ctxvar_command!(AppCtx, [("num", num, usize), ("title", title, String)]),
+///             ArgSpec { min: 3, max: 4, usage: "get|set name ?value?" },
+///             "read and write app context variables"
+///         )]
+///     ),
+///     false,
+///     "demo",
+/// );
+///
+/// assert_eq!(interp.eval("ctxvar set num 25"), Ok(Value::from(25)));
+/// assert_eq!(interp.context.num, 25);
+/// assert_eq!(interp.eval("ctxvar get title"), Ok(Value::from("demo")));
+/// ```
+#[macro_export]
+macro_rules! gen_ctxvar_command {
+  ($ctx_type:ty, [ $( ($name:literal, $field:ident, $field_ty:ty$(,)?) ),* $(,)?] $(,)?) => {
+    {
+      fn f(
+        interp: &mut $crate::molt::prelude::Interp<$ctx_type>,
+        argv: &[$crate::molt::prelude::Value],
+      ) -> $crate::molt::prelude::MoltResult {
+        use $crate::CtxVar;
+        $crate::molt::prelude::check_args(1, argv, 3, 4, "get|set name ?value?")?;
+        let name = argv[2].as_str();
+        match argv[1].as_str() {
+          "get" => {
+            $crate::molt::prelude::check_args(2, argv, 3, 3, "name")?;
+            match name {
+              $( $name => Ok(<$field_ty as CtxVar>::to_value(&interp.context.$field)), )*
+              _ => $crate::molt::molt_err!(
+                "unknown context variable \"{}\": must be {}", name, [$($name),*].join(", ")
+              ),
+            }
+          }
+          "set" => {
+            $crate::molt::prelude::check_args(2, argv, 4, 4, "name value")?;
+            match name {
+              $(
+                $name => {
+                  interp.context.$field = <$field_ty as CtxVar>::from_value(&argv[3])?;
+                  Ok(<$field_ty as CtxVar>::to_value(&interp.context.$field))
+                }
+              )*
+              _ => $crate::molt::molt_err!(
+                "unknown context variable \"{}\": must be {}", name, [$($name),*].join(", ")
+              ),
+            }
+          }
+          subcmd => $crate::molt::molt_err!("bad option \"{}\": must be get or set", subcmd),
+        }
+      }
+      f
+    }
+  };
+}
+
+/// The history entry as plain text: the command after a `% ` prompt, followed by its
+/// output, if any.
+fn entry_text(entry: &HistEntry) -> String {
+    let (run_state, cmd, _, out) = entry;
+    let mut text = format!("% {}", cmd);
+    // An uncompleted command's output isn't shown; the next entry continues it.
+    if *run_state != RunState::Uncompleted && !out.is_empty() {
+        text.push('\n');
+        text.push_str(out);
+    }
+    text
+}
+
+/// Somewhere the terminal can copy text to.  [`BrowserClipboard`] is the page's
+/// clipboard; tests substitute their own.
+pub trait Clipboard {
+    /// Writes the text, calling `on_error` with a message if it fails, possibly later.
+    fn write_text(&self, text: String, on_error: Callback<String>);
+}
+
+/// The page's clipboard, `navigator.clipboard`.  Writing may be refused, e.g., if the
+/// page isn't served securely or the user denies permission.
+pub struct BrowserClipboard;
+
+impl Clipboard for BrowserClipboard {
+    fn write_text(&self, text: String, on_error: Callback<String>) {
+        let Some(window) = web_sys::window() else {
+            on_error.emit("no global `window` exists".into());
+            return;
+        };
+        let clipboard = window.navigator().clipboard();
+        if clipboard.is_undefined() {
+            on_error.emit("the clipboard is not available".into());
+            return;
+        }
+        let promise = clipboard.write_text(&text);
+        spawn_local(async move {
+            if let Err(e) = JsFuture::from(promise).await {
+                let reason = e.as_string().unwrap_or_else(|| "permission denied".into());
+                on_error.emit(format!("copy failed: {}", reason));
+            }
+        });
+    }
+}
+
+/// Copies the plain text of the `i`th history entry, if there is one.
+fn copy_entry(
+    hist: &[HistEntry],
+    i: usize,
+    clipboard: &dyn Clipboard,
+    on_error: Callback<String>,
+) {
+    if let Some(entry) = hist.get(i) {
+        clipboard.write_text(entry_text(entry), on_error);
+    }
+}
+
+#[derive(Debug, Properties)]
 pub struct TerminalProp {
     pub class: &'static str,
-    pub hist: Rc<Vec<(RunState, String, Html)>>,
+    pub hist: Rc<Vec<HistEntry>>,
     // new input, last one is uncompleted
     pub on_run_cmd: Callback<(String, bool)>,
+    /// If set, only the last N history entries are rendered.
+    #[prop_or_default]
+    pub max_visible: Option<usize>,
+    /// If set, Enter first asks whether the command is complete; if it isn't, a newline
+    /// is inserted instead of running it.  Answer false only when more input could
+    /// complete the command, e.g. when `Interp::completeness` is `Incomplete`, so that
+    /// malformed input is run, and its error shown, at once.
+    #[prop_or_default]
+    pub check_complete: Option<Callback<String, bool>>,
+    /// The uncompleted command the next input continues, if any; it is prepended
+    /// to the input given to `check_complete`.
+    #[prop_or_default]
+    pub pending_prefix: Option<String>,
+}
+
+// The history only grows through `Rc::make_mut`, so an unchanged pointer means an
+// unchanged history; comparing the pointers avoids walking every entry's `Html`.
+impl PartialEq for TerminalProp {
+    fn eq(&self, other: &Self) -> bool {
+        self.class == other.class
+            && Rc::ptr_eq(&self.hist, &other.hist)
+            && self.on_run_cmd == other.on_run_cmd
+            && self.max_visible == other.max_visible
+            && self.check_complete == other.check_complete
+            && self.pending_prefix == other.pending_prefix
+    }
 }
 
 pub enum TerminalMsg {
@@ -35,6 +346,12 @@ pub enum TerminalMsg {
     UpdateInput(String),
     // RunCmd,
     KeyDown(Key),
+    /// Copy the history entry with this index to the clipboard.
+    Copy(usize),
+    /// Copying failed, for this reason.
+    CopyFailed(String),
+    /// Stop showing the copy error.
+    ClearCopyError,
 }
 
 pub enum Key {
@@ -43,20 +360,62 @@ pub enum Key {
     ArrowDown,
 }
 
+/// What pressing Enter does with the current input.
+#[derive(Debug, PartialEq)]
+enum EnterAction {
+    /// Emit the command through `on_run_cmd`, with whether it continues an
+    /// uncompleted one.
+    Submit(String, bool),
+    /// Keep editing: the input is incomplete.
+    Newline,
+    /// Nothing to run.
+    Ignore,
+}
+
+/// Decides what Enter does.  Without `check_complete` the input is always
+/// submitted.  An empty input is never checked, so that it still ends a pending
+/// continuation.
+fn enter_action(
+    input: &str,
+    previous_is_uncompleted: bool,
+    pending_prefix: Option<&str>,
+    check_complete: Option<&Callback<String, bool>>,
+) -> EnterAction {
+    if let Some(check_complete) = check_complete {
+        if !input.trim().is_empty() {
+            let cmd = match pending_prefix {
+                Some(prefix) => format!("{}\n{}", prefix, input),
+                None => input.to_string(),
+            };
+            if !check_complete.emit(cmd) {
+                return EnterAction::Newline;
+            }
+        }
+    }
+
+    if previous_is_uncompleted {
+        EnterAction::Submit(input.to_string(), true)
+    } else if !input.is_empty() {
+        EnterAction::Submit(input.to_string(), false)
+    } else {
+        EnterAction::Ignore
+    }
+}
+
+/// How long a copy error stays on screen.
+const COPY_ERROR_MILLIS: u32 = 3000;
+
 impl Terminal {
-    pub fn to_hist(
-        cmd_ctx: String,
-        outs: Vec<Result<Value, Exception>>,
-    ) -> (RunState, String, Html) {
+    pub fn to_hist(cmd_ctx: String, outs: Vec<Result<Value, Exception>>) -> HistEntry {
         let mut run_state = RunState::Ok;
         let out_html = html! {
             {for outs.iter().enumerate().map(
                 |(i,out)|{
                     match out{
-                    Ok(s) => html!(<code class="stdout" style="margin:0px;white-space:pre-wrap;"> { s.to_string() }{if i==(outs.len()-1){html!()}else{html!(<br />)}}</code>),
+                    Ok(s) => html!(<code class="stdout" style="margin:0px;white-space:pre-wrap;"> { linkify(s.as_str()) }{if i==(outs.len()-1){html!()}else{html!(<br />)}}</code>),
                     Err(s) => {
                         run_state=RunState::Err;
-                        html!(<code class="stderr" style="margin:0px;white-space:pre-wrap;"> { s.error_info().to_string() }{if i==(outs.len()-1){html!()}else{html!(<br />)}}</code>)},
+                        html!(<code class="stderr" style="margin:0px;white-space:pre-wrap;"> { linkify(&error_text(s)) }{if i==(outs.len()-1){html!()}else{html!(<br />)}}</code>)},
                     }
                 }
             )}
@@ -67,8 +426,32 @@ impl Terminal {
                     run_state = RunState::Uncompleted;
                 }
             }
+        } else if let Some(Ok(value)) = outs.last() {
+            if offload::Pending::from_value(value).is_some() {
+                run_state = RunState::Pending;
+            }
+        }
+        let out_text = outs
+            .iter()
+            .map(|out| match out {
+                Ok(s) => s.to_string(),
+                Err(e) => error_text(e),
+            })
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (run_state, cmd_ctx, out_html, out_text)
+    }
+
+    /// Returns the session as plain text, each command after a `% ` prompt and followed
+    /// by its output, e.g., for the host app to save or share.
+    pub fn export_text(hist: &[HistEntry]) -> String {
+        let mut text = String::new();
+        for entry in hist {
+            text.push_str(&entry_text(entry));
+            text.push('\n');
         }
-        (run_state, cmd_ctx, out_html)
+        text
     }
     fn input_div_cursor_to_end(&mut self) {
         if let Some(textarea) = self.input_div_ref.cast::<HtmlTextAreaElement>() {
@@ -92,6 +475,8 @@ impl Component for Terminal {
             input: String::new(),
             input_tmp: String::new(),
             current_hist_idx: None,
+            continuing: false,
+            copy_error: None,
         }
     }
 
@@ -99,16 +484,34 @@ impl Component for Terminal {
         match msg {
             TerminalMsg::KeyDown(key) => match key {
                 Key::Enter => {
-                    let cmd = mem::take(&mut self.input);
-                    if let Some((RunState::Uncompleted, _, _)) = ctx.props().hist.last() {
-                        ctx.props().on_run_cmd.emit((cmd, true));
-                    } else {
-                        if !cmd.is_empty() {
-                            ctx.props().on_run_cmd.emit((cmd, false));
+                    let props = ctx.props();
+                    let previous_is_uncompleted = matches!(
+                        props.hist.last(),
+                        Some((RunState::Uncompleted, _, _, _))
+                    );
+                    match enter_action(
+                        &self.input,
+                        previous_is_uncompleted,
+                        props.pending_prefix.as_deref(),
+                        props.check_complete.as_ref(),
+                    ) {
+                        EnterAction::Newline => {
+                            // The keydown's default is prevented when `check_complete`
+                            // is set, so the newline is ours to insert.
+                            self.input.push('\n');
+                            self.continuing = true;
+                            self.input_div_cursor_to_end();
+                            return true;
                         }
+                        EnterAction::Submit(cmd, continued) => {
+                            props.on_run_cmd.emit((cmd, continued));
+                        }
+                        EnterAction::Ignore => {}
                     }
+                    self.input.clear();
+                    self.continuing = false;
                     if let Some(element) = self.hist_div_ref.cast::<web_sys::Element>() {
-                        Timeout::new(5 as u32, move || {
+                        Timeout::new(5, move || {
                             element.set_scroll_top(element.scroll_height());
                         })
                         .forget();
@@ -124,7 +527,7 @@ impl Component for Terminal {
                             self.input_tmp = mem::take(&mut self.input);
                         }
                         *i -= 1;
-                        if let Some((_, hist_cmd, _)) = ctx.props().hist.get(*i) {
+                        if let Some((_, hist_cmd, _, _)) = ctx.props().hist.get(*i) {
                             self.input = hist_cmd.clone();
                         }
                         self.input_div_cursor_to_end();
@@ -133,7 +536,7 @@ impl Component for Terminal {
                     None => {
                         let i = ctx.props().hist.len() - 1;
                         self.current_hist_idx = Some(i);
-                        if let Some((_, hist_cmd, _)) = ctx.props().hist.get(i) {
+                        if let Some((_, hist_cmd, _, _)) = ctx.props().hist.get(i) {
                             self.input_tmp = mem::take(&mut self.input);
                             self.input = hist_cmd.clone();
                         }
@@ -151,7 +554,7 @@ impl Component for Terminal {
                             true
                         } else {
                             *i += 1;
-                            if let Some((_, hist_cmd, _)) = ctx.props().hist.get(*i) {
+                            if let Some((_, hist_cmd, _, _)) = ctx.props().hist.get(*i) {
                                 self.input = hist_cmd.clone();
                             }
                             true
@@ -167,8 +570,26 @@ impl Component for Terminal {
                 if self.input == "\n" {
                     self.input.clear();
                 }
+                if !self.input.contains('\n') {
+                    self.continuing = false;
+                }
+                true
+            }
+            TerminalMsg::Copy(i) => {
+                let on_error = ctx.link().callback(TerminalMsg::CopyFailed);
+                copy_entry(&ctx.props().hist, i, &BrowserClipboard, on_error);
+                false
+            }
+            TerminalMsg::CopyFailed(msg) => {
+                self.copy_error = Some(msg);
+                let link = ctx.link().clone();
+                Timeout::new(COPY_ERROR_MILLIS, move || {
+                    link.send_message(TerminalMsg::ClearCopyError)
+                })
+                .forget();
                 true
             }
+            TerminalMsg::ClearCopyError => self.copy_error.take().is_some(),
             TerminalMsg::None => false,
         }
     }
@@ -194,12 +615,23 @@ impl Component for Terminal {
         }
     }
     fn view(&self, ctx: &Context<Self>) -> Html {
+        let hist = &ctx.props().hist;
+        let hidden = match ctx.props().max_visible {
+            Some(max) => hist.len().saturating_sub(max),
+            None => 0,
+        };
+        let intercept_enter = ctx.props().check_complete.is_some();
         html! {
           <div class={ctx.props().class}>
             <ul ref={self.hist_div_ref.clone()}
             class="history"
             style="text-wrap:nowrap;margin:0px;overflow-y:auto;overflow-x:auto;">
-                { for ctx.props().hist.iter().map(|(run_state,cmd_ctx,out_html)|{
+                { if hidden > 0 { html!(
+                    <li key="hidden" class="hidden-entries" style="padding:0px;margin:0px;list-style:none;white-space:nowrap;">
+                        <code class="command">{ format!("… {} earlier entries hidden (run clear or scroll)", hidden) }</code>
+                    </li>
+                )} else { html!() } }
+                { for hist.iter().enumerate().skip(hidden).map(|(i,(run_state,cmd_ctx,out_html,_))|{
                     let (icon_class,icon,last_line,out_html) = match run_state{
                         RunState::Ok => ("stdout-icon",IconId::BootstrapCheckLg,html!(),out_html.clone()),
                         RunState::Err => ("stderr-icon",IconId::FontAwesomeSolidXmark,html!(),out_html.clone()),
@@ -208,14 +640,18 @@ impl Component for Terminal {
                                 <Icon class="command" icon_id={IconId::FontAwesomeSolidEllipsis} height={"10px".to_owned()} width={"15px".to_owned()}/>
                             </div>
                         ),html!()),
+                        RunState::Pending => ("command",IconId::FontAwesomeSolidEllipsis,html!(),out_html.clone()),
                     };
                     html!{
-                        <li style="padding:0px;margin:0px;list-style:none;white-space:nowrap;">
+                        <li key={i} style="padding:0px;margin:0px;list-style:none;white-space:nowrap;">
                         <div style="display:flex;flex-wrap:nowrap;">
                             <Icon class={icon_class} icon_id={icon} height={"10px".to_owned()} width={"15px".to_owned()}/>
                             <code class="command" style="white-space: pre-wrap;">
                                 {cmd_ctx}
                             </code>
+                            <span class="copy" title="copy" style="cursor:pointer;" onclick={ctx.link().callback(move |_| TerminalMsg::Copy(i))}>
+                                <Icon icon_id={IconId::FontAwesomeRegularCopy} height={"10px".to_owned()} width={"15px".to_owned()}/>
+                            </span>
                         </div>
                         {last_line}
                         <div style="padding-left:15px">
@@ -224,7 +660,17 @@ impl Component for Terminal {
                         </li>
                     }
                 })}
+                { if let Some(msg) = &self.copy_error { html!(
+                    <li key="copy-error" class="copy-error" style="padding:0px;margin:0px;list-style:none;white-space:nowrap;">
+                        <code class="stderr">{ msg.clone() }</code>
+                    </li>
+                )} else { html!() } }
             </ul>
+            { if self.continuing { html!(
+                <div class="continuation" style="display:flex;flex-wrap:nowrap;">
+                    <Icon class="command" icon_id={IconId::FontAwesomeSolidEllipsis} height={"10px".to_owned()} width={"15px".to_owned()}/>
+                </div>
+            )} else { html!() } }
             <textarea
                 class="input"
                 style="width:100%;"
@@ -234,9 +680,14 @@ impl Component for Terminal {
                     let input: web_sys::HtmlInputElement = e.target_unchecked_into();
                     TerminalMsg::UpdateInput(input.value())
                 })}
-                onkeydown={ctx.link().callback(|e: KeyboardEvent| {
+                onkeydown={ctx.link().callback(move |e: KeyboardEvent| {
                     match e.key().as_str(){
-                    "Enter" => TerminalMsg::KeyDown(Key::Enter),
+                    "Enter" => {
+                        if intercept_enter {
+                            e.prevent_default();
+                        }
+                        TerminalMsg::KeyDown(Key::Enter)
+                    },
                     "ArrowUp" => TerminalMsg::KeyDown(Key::ArrowUp),
                     "ArrowDown" => TerminalMsg::KeyDown(Key::ArrowDown),
                     _ => TerminalMsg::None,
@@ -247,3 +698,312 @@ impl Component for Terminal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_enter_action_without_check() {
+        assert_eq!(
+            enter_action("set a {", false, None, None),
+            EnterAction::Submit("set a {".into(), false)
+        );
+        assert_eq!(
+            enter_action("}", true, Some("set a {"), None),
+            EnterAction::Submit("}".into(), true)
+        );
+        assert_eq!(
+            enter_action("", true, None, None),
+            EnterAction::Submit("".into(), true)
+        );
+        assert_eq!(enter_action("", false, None, None), EnterAction::Ignore);
+    }
+
+    #[test]
+    fn test_enter_action_with_check() {
+        let checked = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let check_complete = {
+            let checked = checked.clone();
+            Callback::from(move |cmd: String| {
+                let completeness = Interp::empty((), "check").completeness(&cmd);
+                let complete = !matches!(completeness, Completeness::Incomplete { .. });
+                checked.borrow_mut().push(cmd);
+                complete
+            })
+        };
+        let check = Some(&check_complete);
+
+        // An incomplete proc stays in the textarea...
+        let mut input = String::from("proc a {} {");
+        assert_eq!(enter_action(&input, false, None, check), EnterAction::Newline);
+        input.push('\n');
+        input.push_str("  return 1");
+        assert_eq!(enter_action(&input, false, None, check), EnterAction::Newline);
+
+        // ...until it is closed, and then it is submitted whole.
+        input.push_str("\n}");
+        assert_eq!(
+            enter_action(&input, false, None, check),
+            EnterAction::Submit("proc a {} {\n  return 1\n}".into(), false)
+        );
+
+        // The pending prefix is part of the check but not of the submitted input.
+        assert_eq!(enter_action("b", true, Some("set a {"), check), EnterAction::Newline);
+        assert_eq!(
+            enter_action("b}", true, Some("set a {"), check),
+            EnterAction::Submit("b}".into(), true)
+        );
+        assert_eq!(checked.borrow().last().unwrap(), "set a {\nb}");
+
+        // Malformed input is submitted, so that its error is shown at once...
+        assert_eq!(
+            enter_action("set a {b}c", false, None, check),
+            EnterAction::Submit("set a {b}c".into(), false)
+        );
+        // ...including when it continues an uncompleted command.
+        assert_eq!(
+            enter_action("}x", true, Some("set a {"), check),
+            EnterAction::Submit("}x".into(), true)
+        );
+
+        // An empty input isn't checked, so it still ends a continuation.
+        let count = checked.borrow().len();
+        assert_eq!(
+            enter_action("", true, Some("set a {"), check),
+            EnterAction::Submit("".into(), true)
+        );
+        assert_eq!(enter_action("", false, None, check), EnterAction::Ignore);
+        assert_eq!(checked.borrow().len(), count);
+    }
+
+    struct CtxVarCtx {
+        num: usize,
+        ratio: f64,
+        name: String,
+    }
+
+    fn ctxvar_interp() -> Interp<CtxVarCtx> {
+        Interp::new(
+            CtxVarCtx { num: 0, ratio: 0.5, name: "molt".into() },
+            gen_command!(
+                CtxVarCtx,
+                [],
+                [(
+                    "ctxvar",
+                    gen_ctxvar_command!(
+                        CtxVarCtx,
+                        [
+                            ("num", num, usize),
+                            ("ratio", ratio, f64),
+                            ("name", name, String)
+                        ]
+                    ),
+                    ArgSpec { min: 3, max: 4, usage: "get|set name ?value?" }
+                )]
+            ),
+            false,
+            "ctxvar-test",
+        )
+    }
+
+    #[test]
+    fn test_ctxvar_get_set() {
+        let mut interp = ctxvar_interp();
+
+        assert_eq!(interp.eval("ctxvar get num"), Ok(Value::from(0)));
+        assert_eq!(interp.eval("ctxvar get ratio"), Ok(Value::from(0.5)));
+        assert_eq!(interp.eval("ctxvar get name"), Ok(Value::from("molt")));
+
+        assert_eq!(interp.eval("ctxvar set num [expr {5 * 5}]"), Ok(Value::from(25)));
+        assert_eq!(interp.context.num, 25);
+        assert_eq!(interp.eval("ctxvar set ratio 2"), Ok(Value::from(2.0)));
+        assert_eq!(interp.context.ratio, 2.0);
+        assert_eq!(interp.eval("ctxvar set name {a b}"), Ok(Value::from("a b")));
+        assert_eq!(interp.context.name, "a b");
+
+        interp.context.num = 7;
+        assert_eq!(interp.eval("ctxvar get num"), Ok(Value::from(7)));
+    }
+
+    #[test]
+    fn test_ctxvar_errors() {
+        let mut interp = ctxvar_interp();
+        let err = |interp: &mut Interp<CtxVarCtx>, script| {
+            interp.eval(script).unwrap_err().value().to_string()
+        };
+
+        assert_eq!(
+            err(&mut interp, "ctxvar get nonesuch"),
+            "unknown context variable \"nonesuch\": must be num, ratio, name"
+        );
+        assert_eq!(
+            err(&mut interp, "ctxvar set nonesuch 1"),
+            "unknown context variable \"nonesuch\": must be num, ratio, name"
+        );
+        assert_eq!(
+            err(&mut interp, "ctxvar set num abc"),
+            "expected integer but got \"abc\""
+        );
+        assert_eq!(
+            err(&mut interp, "ctxvar set num -1"),
+            "expected usize but got \"-1\""
+        );
+        assert_eq!(
+            err(&mut interp, "ctxvar set ratio abc"),
+            "expected floating-point number but got \"abc\""
+        );
+        assert_eq!(interp.context.num, 0);
+        assert_eq!(interp.context.ratio, 0.5);
+
+        assert_eq!(
+            err(&mut interp, "ctxvar get num 1"),
+            "wrong # args: should be \"ctxvar get name\""
+        );
+        assert_eq!(
+            err(&mut interp, "ctxvar set num"),
+            "wrong # args: should be \"ctxvar set name value\""
+        );
+        assert_eq!(
+            err(&mut interp, "ctxvar put num 1"),
+            "bad option \"put\": must be get or set"
+        );
+    }
+
+    #[test]
+    fn test_split_links() {
+        assert_eq!(split_links(""), vec![]);
+        assert_eq!(split_links("no links"), vec![Segment::Text("no links")]);
+        assert_eq!(
+            split_links("see https://github.com/zao111222333/molt-forked."),
+            vec![
+                Segment::Text("see "),
+                Segment::Link("https://github.com/zao111222333/molt-forked"),
+                Segment::Text("."),
+            ]
+        );
+        assert_eq!(
+            split_links("http://a.b\nhttps://c.d/e?f=1"),
+            vec![
+                Segment::Link("http://a.b"),
+                Segment::Text("\n"),
+                Segment::Link("https://c.d/e?f=1"),
+            ]
+        );
+        assert_eq!(
+            split_links("(http://a.b)"),
+            vec![Segment::Text("("), Segment::Link("http://a.b"), Segment::Text(")")]
+        );
+        assert_eq!(
+            split_links("http:// x"),
+            vec![Segment::Text("http://"), Segment::Text(" x")]
+        );
+        assert_eq!(split_links("ftp://a.b"), vec![Segment::Text("ftp://a.b")]);
+    }
+
+    #[test]
+    fn test_split_links_no_injection() {
+        // Markup never becomes part of a link, so it is only ever rendered as text.
+        let segments = split_links(
+            "http://a.b/\"onmouseover=\"alert(1)<script>x</script> 'http://c.d'<b>",
+        );
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Link("http://a.b/"),
+                Segment::Text("\"onmouseover=\"alert(1)<script>x</script> '"),
+                Segment::Link("http://c.d"),
+                Segment::Text("'<b>"),
+            ]
+        );
+        for segment in segments {
+            if let Segment::Link(url) = segment {
+                assert!(!url.contains(['<', '>', '"', '\'', '`']));
+                assert!(url.starts_with("http://") || url.starts_with("https://"));
+            }
+        }
+    }
+
+    fn entry(run_state: RunState, cmd: &str, out: &str) -> HistEntry {
+        (run_state, cmd.into(), html!(), out.into())
+    }
+
+    #[test]
+    fn test_to_hist_text() {
+        let (run_state, cmd, _, text) = Terminal::to_hist(
+            "set a 1; puts x; error oops".into(),
+            vec![
+                Ok(Value::from("1")),
+                Ok(Value::empty()),
+                Err(Exception::molt_err(Value::from("oops"))),
+            ],
+        );
+        assert_eq!(run_state, RunState::Err);
+        assert_eq!(cmd, "set a 1; puts x; error oops");
+        assert_eq!(
+            text,
+            format!("1\n{}", error_text(&Exception::molt_err("oops".into())))
+        );
+
+        let (run_state, _, _, text) = Terminal::to_hist("set b".into(), vec![]);
+        assert_eq!(run_state, RunState::Ok);
+        assert_eq!(text, "");
+    }
+
+    #[test]
+    fn test_export_text() {
+        let hist = vec![
+            entry(RunState::Ok, "set a 1", "1"),
+            entry(RunState::Ok, "proc f {} {}", ""),
+            entry(RunState::Uncompleted, "set b {", "missing close-brace"),
+            entry(RunState::Err, "}\nerror oops", "oops"),
+        ];
+        assert_eq!(entry_text(&hist[0]), "% set a 1\n1");
+        assert_eq!(entry_text(&hist[1]), "% proc f {} {}");
+        assert_eq!(entry_text(&hist[2]), "% set b {");
+        assert_eq!(
+            Terminal::export_text(&hist),
+            "% set a 1\n1\n% proc f {} {}\n% set b {\n% }\nerror oops\noops\n"
+        );
+        assert_eq!(Terminal::export_text(&[]), "");
+    }
+
+    // Records what's copied, or refuses, as a browser without permission would.
+    struct FakeClipboard {
+        copied: RefCell<Vec<String>>,
+        refuse: bool,
+    }
+
+    impl Clipboard for FakeClipboard {
+        fn write_text(&self, text: String, on_error: Callback<String>) {
+            if self.refuse {
+                on_error.emit("copy failed: permission denied".into());
+            } else {
+                self.copied.borrow_mut().push(text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_copy_entry() {
+        let hist =
+            vec![entry(RunState::Ok, "set a 1", "1"), entry(RunState::Ok, "incr a", "2")];
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let on_error = {
+            let errors = errors.clone();
+            Callback::from(move |msg: String| errors.borrow_mut().push(msg))
+        };
+
+        let clipboard = FakeClipboard { copied: RefCell::new(Vec::new()), refuse: false };
+        copy_entry(&hist, 1, &clipboard, on_error.clone());
+        copy_entry(&hist, 2, &clipboard, on_error.clone());
+        assert_eq!(*clipboard.copied.borrow(), vec!["% incr a\n2".to_string()]);
+        assert!(errors.borrow().is_empty());
+
+        let clipboard = FakeClipboard { copied: RefCell::new(Vec::new()), refuse: true };
+        copy_entry(&hist, 0, &clipboard, on_error);
+        assert!(clipboard.copied.borrow().is_empty());
+        assert_eq!(*errors.borrow(), vec!["copy failed: permission denied".to_string()]);
+    }
+}
@@ -0,0 +1,279 @@
+//! Offloading CPU-heavy commands to a web worker.
+//!
+//! An embedded command that does a lot of work, e.g., parsing a big file, freezes the
+//! page until it returns.  Such a command can instead hand the work to a pure function
+//! running on a web worker, and return at once:
+//!
+//! * Write the work as an [`OffloadFn`]: `fn(Vec<String>) -> Result<String, String>`.
+//! * Add a worker binary to the app whose `main` is generated by [`offload_worker!`],
+//!   listing the functions by name, and have Trunk build it as a worker, e.g.,
+//!   `<link data-trunk rel="rust" href="Cargo.toml" data-bin="worker" data-type="worker" />`.
+//! * Spawn the worker with [`Offloader::spawn`], keeping the [`Offloader`] in the
+//!   interpreter's context.
+//! * In the command, validate the arguments and return [`Offloader::run`]'s result: a
+//!   [`Pending`] value.  When the worker replies, the `Offloader` calls its `on_resolve`
+//!   callback with the `Pending` handle and the function's result, and the app shows it.
+//!
+//! Only strings cross the boundary: the worker runs in its own wasm instance, with no
+//! access to the interpreter, its context, or the page, so the function gets its
+//! arguments as strings and returns its result or error message as a string.
+//!
+//! ```
+//! use molt_wasm::{molt::prelude::*, offload::Offloader};
+//!
+//! fn count(args: Vec<String>) -> Result<String, String> {
+//!     Ok(args.len().to_string())
+//! }
+//!
+//! // A worker binary's main.rs is just the function and:
+//! // molt_wasm::offload_worker!(("count", count));
+//!
+//! // Offloader::local runs the functions in place, e.g., for tests.
+//! let results = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+//! let mut offloader = {
+//!     let results = results.clone();
+//!     Offloader::local(&[("count", count)], move |pending, result| {
+//!         results.borrow_mut().push((pending, result))
+//!     })
+//! };
+//!
+//! let value = offloader.run("count", vec!["a".into(), "b".into()]).unwrap();
+//! let pending = molt_wasm::offload::Pending::from_value(&value).unwrap();
+//! assert_eq!(results.borrow()[0], (pending, Ok("2".to_string())));
+//! ```
+use crate::molt::prelude::*;
+use gloo::worker::{
+    HandlerId, Registrable, Spawnable, Worker, WorkerBridge, WorkerScope,
+};
+use std::{cell::RefCell, fmt, rc::Rc, str::FromStr};
+
+/// A pure function that can run on a web worker, taking and returning only strings.
+pub type OffloadFn = fn(Vec<String>) -> Result<String, String>;
+
+/// A request to the worker: the call's ID, the function's name, and its arguments.
+type Request = (usize, String, Vec<String>);
+
+/// The worker's reply: the call's ID and the function's result.
+type Reply = (usize, Result<String, String>);
+
+/// The handle of an offloaded call whose result hasn't arrived yet.  A command that
+/// offloads its work returns it as its result; see [`Offloader::run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pending(pub usize);
+
+impl Pending {
+    /// Returns the handle, if the value is the result of [`Offloader::run`].
+    pub fn from_value(value: &Value) -> Option<Self> {
+        value.as_copy::<Pending>()
+    }
+}
+
+impl fmt::Display for Pending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pending#{}", self.0)
+    }
+}
+
+impl FromStr for Pending {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix("pending#")
+            .and_then(|id| id.parse().ok())
+            .map(Pending)
+            .ok_or_else(|| format!("not a pending result: \"{}\"", s))
+    }
+}
+
+thread_local! {
+    // The functions registered by the worker's `main`.
+    static FUNCTIONS: RefCell<Vec<(&'static str, OffloadFn)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Calls the named function, if it's one of the `functions`.
+fn call(
+    functions: &[(&str, OffloadFn)],
+    name: &str,
+    args: Vec<String>,
+) -> Result<String, String> {
+    match functions.iter().find(|(n, _)| *n == name) {
+        Some((_, func)) => func(args),
+        None => Err(format!("unknown offloaded function \"{}\"", name)),
+    }
+}
+
+/// The worker that runs offloaded functions.  Its binary registers it with
+/// [`register_offload_worker`], usually via [`offload_worker!`].
+pub struct OffloadWorker;
+
+impl Worker for OffloadWorker {
+    type Message = ();
+    type Input = Request;
+    type Output = Reply;
+
+    fn create(_scope: &WorkerScope<Self>) -> Self {
+        Self
+    }
+
+    fn update(&mut self, _scope: &WorkerScope<Self>, _msg: Self::Message) {}
+
+    fn received(&mut self, scope: &WorkerScope<Self>, msg: Self::Input, id: HandlerId) {
+        let (call_id, name, args) = msg;
+        let result = FUNCTIONS.with(|functions| call(&functions.borrow(), &name, args));
+        scope.respond(id, (call_id, result));
+    }
+}
+
+/// Runs the current web worker as an [`OffloadWorker`] for the given functions.  Call
+/// this from the worker binary's `main`, or let [`offload_worker!`] do it.
+pub fn register_offload_worker(functions: &[(&'static str, OffloadFn)]) {
+    FUNCTIONS.with(|f| *f.borrow_mut() = functions.to_vec());
+    OffloadWorker::registrar().register();
+}
+
+/// Generates the `main` function of a worker binary that runs the listed functions,
+/// each given as `(name, function)`.  The functions must be [`OffloadFn`]s.
+///
+/// ```ignore
+/// fn primes(args: Vec<String>) -> Result<String, String> { ... }
+///
+/// molt_wasm::offload_worker!(("primes", primes));
+/// ```
+///
+/// [`OffloadFn`]: offload/type.OffloadFn.html
+#[macro_export]
+macro_rules! offload_worker {
+    ($( ($name:literal, $func:path) ),* $(,)?) => {
+        fn main() {
+            $crate::offload::register_offload_worker(&[
+                $( ($name, $func as $crate::offload::OffloadFn) ),*
+            ]);
+        }
+    };
+}
+
+/// Where the offloaded functions run.
+enum Backend {
+    Worker(WorkerBridge<OffloadWorker>),
+    Local(Vec<(&'static str, OffloadFn)>),
+}
+
+/// Sends calls to an [`OffloadWorker`] and reports their results as they arrive.
+pub struct Offloader {
+    backend: Backend,
+    next_id: usize,
+    on_resolve: Rc<dyn Fn(Pending, Result<String, String>)>,
+}
+
+impl Offloader {
+    /// Spawns the worker script at `path`, e.g., `"./worker.js"`.  `on_resolve` is
+    /// called with each call's [`Pending`] handle and result when the worker replies.
+    pub fn spawn<F>(path: &str, on_resolve: F) -> Self
+    where
+        F: Fn(Pending, Result<String, String>) + 'static,
+    {
+        let on_resolve: Rc<dyn Fn(Pending, Result<String, String>)> = Rc::new(on_resolve);
+        let bridge = {
+            let on_resolve = on_resolve.clone();
+            OffloadWorker::spawner()
+                .callback(move |(id, result): Reply| on_resolve(Pending(id), result))
+                .spawn(path)
+        };
+
+        Self {
+            backend: Backend::Worker(bridge),
+            next_id: 0,
+            on_resolve,
+        }
+    }
+
+    /// Runs the functions on the calling thread instead of on a worker, e.g., for tests.
+    /// Each call is resolved before [`run`](#method.run) returns.
+    pub fn local<F>(functions: &[(&'static str, OffloadFn)], on_resolve: F) -> Self
+    where
+        F: Fn(Pending, Result<String, String>) + 'static,
+    {
+        Self {
+            backend: Backend::Local(functions.to_vec()),
+            next_id: 0,
+            on_resolve: Rc::new(on_resolve),
+        }
+    }
+
+    /// Starts a call of the named function, returning its [`Pending`] handle as a `Value`
+    /// for the command to return.  The result is passed to `on_resolve` later.
+    pub fn run(&mut self, name: &str, args: Vec<String>) -> MoltResult {
+        self.next_id += 1;
+        let pending = Pending(self.next_id);
+
+        match &self.backend {
+            Backend::Worker(bridge) => bridge.send((pending.0, name.to_string(), args)),
+            Backend::Local(functions) => {
+                (self.on_resolve)(pending, call(functions, name, args))
+            }
+        }
+
+        molt_ok!(Value::from_other(pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(args: Vec<String>) -> Result<String, String> {
+        Ok(args.join("+"))
+    }
+
+    fn fail(args: Vec<String>) -> Result<String, String> {
+        Err(format!("failed with {} args", args.len()))
+    }
+
+    #[test]
+    fn test_pending_value() {
+        let value = Value::from_other(Pending(3));
+        assert_eq!(value.as_str(), "pending#3");
+        assert_eq!(Pending::from_value(&value), Some(Pending(3)));
+        assert_eq!(Pending::from_value(&Value::from("pending#7")), Some(Pending(7)));
+        assert_eq!(Pending::from_value(&Value::from("7")), None);
+    }
+
+    #[test]
+    fn test_call() {
+        let functions: &[(&str, OffloadFn)] = &[("join", join), ("fail", fail)];
+
+        assert_eq!(
+            call(functions, "join", vec!["a".into(), "b".into()]),
+            Ok("a+b".into())
+        );
+        assert_eq!(call(functions, "fail", vec![]), Err("failed with 0 args".into()));
+        assert_eq!(
+            call(functions, "nonesuch", vec![]),
+            Err("unknown offloaded function \"nonesuch\"".into())
+        );
+    }
+
+    #[test]
+    fn test_local_offloader() {
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let mut offloader = {
+            let results = results.clone();
+            Offloader::local(&[("join", join), ("fail", fail)], move |pending, result| {
+                results.borrow_mut().push((pending, result))
+            })
+        };
+
+        let first = offloader.run("join", vec!["1".into(), "2".into()]).unwrap();
+        let second = offloader.run("fail", vec!["x".into()]).unwrap();
+
+        assert_eq!(Pending::from_value(&first), Some(Pending(1)));
+        assert_eq!(Pending::from_value(&second), Some(Pending(2)));
+        assert_eq!(
+            *results.borrow(),
+            vec![
+                (Pending(1), Ok("1+2".to_string())),
+                (Pending(2), Err("failed with 1 args".to_string())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! Times a loop that catches an error on every iteration.  The errors never reach the top
+//! level, and their stack traces are never read.
+//!
+//! Run with `cargo bench -p molt-forked --bench errors`.
+use molt_forked::prelude::*;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn main() {
+    let mut interp = Interp::default();
+    let script = format!(
+        "for {{set i 0}} {{$i < {}}} {{incr i}} {{ catch {{ expr {{1/0}} }} }}",
+        ITERATIONS
+    );
+
+    let start = Instant::now();
+    interp.eval(&script).unwrap();
+    let elapsed = start.elapsed();
+    println!(
+        "{:<14} {:>10?} total, {:>10?} per iteration",
+        "catch 1/0",
+        elapsed,
+        elapsed / ITERATIONS
+    );
+}
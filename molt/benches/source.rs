@@ -0,0 +1,42 @@
+//! Compares sourcing a large generated file with and without a progress callback.
+//!
+//! Run with `cargo bench -p molt-forked --bench source`.
+use molt_forked::prelude::*;
+use std::fs;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+const COMMANDS: usize = 100_000;
+const RUNS: u32 = 10;
+
+fn measure(label: &str, path: &str, mut source: impl FnMut(&mut Interp<()>, &str)) {
+    let mut total = Duration::ZERO;
+    for _ in 0..RUNS {
+        let mut interp = Interp::default();
+        let start = Instant::now();
+        source(&mut interp, path);
+        total += start.elapsed();
+    }
+    println!("{:<14} {:>10?} per {} commands", label, total / RUNS, COMMANDS);
+}
+
+fn main() {
+    let path = std::env::temp_dir()
+        .join(format!("molt-bench-source-{}.tcl", std::process::id()));
+    let script: String = (0..COMMANDS)
+        .map(|i| format!("set config{} {{value {}}}\n", i % 1000, i))
+        .collect();
+    fs::write(&path, script).unwrap();
+    let path = path.to_string_lossy().to_string();
+
+    measure("eval_file", &path, |interp, path| {
+        interp.eval_file(path).unwrap();
+    });
+    measure("with progress", &path, |interp, path| {
+        interp
+            .source_file_with_progress(path, &mut |_, _| ControlFlow::Continue(()))
+            .unwrap();
+    });
+
+    let _ = fs::remove_file(&path);
+}
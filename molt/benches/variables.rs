@@ -0,0 +1,44 @@
+//! Compares setting many scalars one at a time with setting them as a batch.
+//!
+//! Run with `cargo bench -p molt-forked --bench variables`.
+use molt_forked::prelude::*;
+use std::hint::black_box;
+use std::time::Instant;
+
+const VARS: usize = 1000;
+const ITERATIONS: u32 = 2000;
+
+fn measure(label: &str, mut body: impl FnMut()) {
+    // Warm up, so that every variable exists before timing starts.
+    body();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        body();
+    }
+    let per_iter = start.elapsed() / ITERATIONS;
+    println!("{:<14} {:>10?} per {} scalars", label, per_iter, VARS);
+}
+
+fn main() {
+    let names: Vec<String> = (0..VARS).map(|i| format!("telemetry{}", i)).collect();
+    let pairs: Vec<(&str, Value)> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), Value::from(i as MoltInt)))
+        .collect();
+
+    let mut interp = Interp::default();
+    measure("set_scalar", || {
+        for (name, value) in &pairs {
+            interp.set_scalar(name, value.clone()).unwrap();
+        }
+        black_box(&interp);
+    });
+
+    let mut interp = Interp::default();
+    measure("set_scalars", || {
+        interp.set_scalars(&pairs).unwrap();
+        black_box(&interp);
+    });
+}
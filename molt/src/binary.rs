@@ -0,0 +1,634 @@
+//! Binary
+//!
+//! The `binary` command, which packs values into byte strings and unpacks them, and
+//! encodes byte strings as text.  A byte string is a `Value` with one character per byte,
+//! from U+0000 to U+00FF; see `Value::from_bytes` and `Value::as_bytes`.
+//!
+//! `binary format` and `binary scan` take templates of fields, each a type followed by an
+//! optional count, a number or `*`:
+//!
+//! * `a`, `A`: a string of *count* bytes, padded with nulls or spaces; `binary scan`
+//!   strips trailing spaces and nulls for `A`.  `*` is the whole string.
+//! * `b`, `B`: a string of *count* binary digits, from the low or high bit of each byte.
+//! * `h`, `H`: a string of *count* hexadecimal digits, from the low or high half of each
+//!   byte.
+//! * `c`: 8-bit integers.
+//! * `s`, `S`, `t`: 16-bit integers, little-endian, big-endian, or in native order.
+//! * `i`, `I`, `n`: 32-bit integers, likewise.
+//! * `w`, `W`, `m`: 64-bit integers, likewise.
+//! * `r`, `R`, `f`: 32-bit floats, little-endian, big-endian, or in native order.
+//! * `q`, `Q`, `d`: 64-bit floats, likewise.
+//! * `x`: *count* null bytes, or skips *count* bytes.
+//! * `X`: moves back *count* bytes; `*` moves to the start.
+//! * `@`: moves to the absolute position *count*; `*` moves to the end.
+//!
+//! A numeric field without a count is a single number; with one, it's a list of *count*
+//! numbers, or of all of them for `*`.  In `binary scan`, a `u` after an integer type
+//! scans it as unsigned.
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+
+/// The name of the `binary` command.
+pub const _BINARY: &str = "binary";
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+const BASE64_DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// # binary *subcommand* ?*arg* ...?
+///
+/// Packs values into byte strings, unpacks them, and encodes them as text.
+///
+/// * `binary format formatString ?arg ...?`: packs the args into a byte string.
+/// * `binary scan string formatString ?varName ...?`: unpacks the byte string into the
+///   variables, returning the number of variables set.
+/// * `binary encode hex|base64 data`, `binary decode hex|base64 data`: converts a byte
+///   string to and from hexadecimal or base64 text.
+pub fn cmd_binary<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("decode", cmd_binary_decode),
+            ("encode", cmd_binary_encode),
+            ("format", cmd_binary_format),
+            ("scan", cmd_binary_scan),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// # binary decode hex|base64 *data*
+fn cmd_binary_decode<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        2,
+        [("base64", cmd_binary_decode_base64), ("hex", cmd_binary_decode_hex)],
+    );
+    f(interp, argv)
+}
+
+/// # binary encode hex|base64 *data*
+fn cmd_binary_encode<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        2,
+        [("base64", cmd_binary_encode_base64), ("hex", cmd_binary_encode_hex)],
+    );
+    f(interp, argv)
+}
+
+/// # binary encode hex *data*
+fn cmd_binary_encode_hex<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(3, argv, 4, 4, "data")?;
+
+    let bytes = argv[3].as_bytes()?;
+    let mut text = String::with_capacity(bytes.len() * 2);
+    for byte in bytes.iter() {
+        text.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        text.push(HEX_DIGITS[(byte & 0xF) as usize] as char);
+    }
+    molt_ok!(text)
+}
+
+/// # binary decode hex *data*
+///
+/// Whitespace in the data is ignored.
+fn cmd_binary_decode_hex<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(3, argv, 4, 4, "data")?;
+
+    let mut digits = Vec::new();
+    for (i, ch) in argv[3].as_str().chars().enumerate() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        match ch.to_digit(16) {
+            Some(digit) => digits.push(digit as u8),
+            None => {
+                return molt_err!(
+                    "invalid hexadecimal digit \"{}\" at position {}",
+                    ch,
+                    i
+                )
+            }
+        }
+    }
+
+    if digits.len() % 2 != 0 {
+        return molt_err!("odd number of hexadecimal digits");
+    }
+
+    molt_ok!(Value::from_bytes(
+        digits.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect()
+    ))
+}
+
+/// # binary encode base64 *data*
+fn cmd_binary_encode_base64<Ctx>(
+    _interp: &mut Interp<Ctx>,
+    argv: &[Value],
+) -> MoltResult {
+    check_args(3, argv, 4, 4, "data")?;
+
+    let bytes = argv[3].as_bytes()?;
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, byte)| acc | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64_DIGITS[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    molt_ok!(text)
+}
+
+/// # binary decode base64 *data*
+///
+/// Whitespace in the data is ignored, and so is missing `=` padding.
+fn cmd_binary_decode_base64<Ctx>(
+    _interp: &mut Interp<Ctx>,
+    argv: &[Value],
+) -> MoltResult {
+    check_args(3, argv, 4, 4, "data")?;
+
+    let mut bytes = Vec::new();
+    let mut group = 0u32;
+    let mut digits = 0;
+    let mut padded = false;
+
+    for (i, ch) in argv[3].as_str().chars().enumerate() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        if ch == '=' {
+            padded = true;
+            continue;
+        }
+        let digit = match BASE64_DIGITS.iter().position(|d| *d as char == ch) {
+            Some(digit) if !padded => digit as u32,
+            _ => {
+                return molt_err!("invalid base64 character \"{}\" at position {}", ch, i)
+            }
+        };
+
+        group = group << 6 | digit;
+        digits += 1;
+        if digits == 4 {
+            bytes.extend_from_slice(&group.to_be_bytes()[1..]);
+            group = 0;
+            digits = 0;
+        }
+    }
+
+    // A final group of two or three digits holds one or two bytes.
+    match digits {
+        0 => {}
+        1 => return molt_err!("invalid base64 data: incomplete final group"),
+        _ => {
+            let group = group << (6 * (4 - digits));
+            bytes.extend_from_slice(&group.to_be_bytes()[1..digits]);
+        }
+    }
+
+    molt_ok!(Value::from_bytes(bytes))
+}
+
+/// # binary format *formatString* ?*arg* ...?
+fn cmd_binary_format<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 0, "formatString ?arg ...?")?;
+
+    let mut out: Vec<u8> = Vec::new();
+    let mut pos = 0;
+    let mut args = argv[3..].iter();
+
+    for field in parse_template(argv[2].as_str())? {
+        match field.kind {
+            'x' => {
+                let count = match field.count {
+                    Count::One => 1,
+                    Count::N(n) => n,
+                    Count::All => {
+                        return molt_err!("cannot use \"*\" in format string with \"x\"")
+                    }
+                };
+                put(&mut out, &mut pos, &vec![0; count]);
+            }
+            'X' => {
+                pos = match field.count {
+                    Count::One => pos.saturating_sub(1),
+                    Count::N(n) => pos.saturating_sub(n),
+                    Count::All => 0,
+                };
+            }
+            '@' => {
+                pos = match field.count {
+                    Count::One => {
+                        return molt_err!("missing count for \"@\" field specifier")
+                    }
+                    Count::N(n) => n,
+                    Count::All => out.len(),
+                };
+                if out.len() < pos {
+                    out.resize(pos, 0);
+                }
+            }
+            _ => {
+                let Some(arg) = args.next() else {
+                    return molt_err!("not enough arguments for all format specifiers");
+                };
+                let bytes = format_field(&field, arg)?;
+                put(&mut out, &mut pos, &bytes);
+            }
+        }
+    }
+
+    if args.next().is_some() {
+        return molt_err!("too many arguments for all format specifiers");
+    }
+
+    molt_ok!(Value::from_bytes(out))
+}
+
+/// # binary scan *string* *formatString* ?*varName* ...?
+fn cmd_binary_scan<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "value formatString ?varName ...?")?;
+
+    let bytes = argv[2].as_bytes()?;
+    let fields = parse_template(argv[3].as_str())?;
+    let mut pos = 0;
+    let mut var_names = argv[4..].iter();
+    let mut num_set = 0;
+
+    for field in fields {
+        match field.kind {
+            'x' => {
+                pos = match field.count {
+                    Count::One => pos + 1,
+                    Count::N(n) => pos + n,
+                    Count::All => bytes.len(),
+                }
+                .min(bytes.len());
+            }
+            'X' => {
+                pos = match field.count {
+                    Count::One => pos.saturating_sub(1),
+                    Count::N(n) => pos.saturating_sub(n),
+                    Count::All => 0,
+                };
+            }
+            '@' => {
+                pos = match field.count {
+                    Count::One => {
+                        return molt_err!("missing count for \"@\" field specifier")
+                    }
+                    Count::N(n) => n.min(bytes.len()),
+                    Count::All => bytes.len(),
+                };
+            }
+            _ => {
+                let Some(var_name) = var_names.next() else {
+                    return molt_err!("not enough arguments for all format specifiers");
+                };
+
+                // Running out of data ends the scan.
+                let Some(value) = scan_field(&field, &bytes, &mut pos) else {
+                    break;
+                };
+                interp.set_var(var_name, value)?;
+                num_set += 1;
+            }
+        }
+    }
+
+    molt_ok!(num_set)
+}
+
+/// How many of a field's type a `binary` field takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Count {
+    /// No count was given.
+    One,
+    /// A numeric count.
+    N(usize),
+    /// `*`: all of them.
+    All,
+}
+
+/// A field of a `binary format` or `binary scan` template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field {
+    kind: char,
+    unsigned: bool,
+    count: Count,
+}
+
+/// Parses a template into its fields.  Whitespace between fields is ignored.
+fn parse_template(template: &str) -> Result<Vec<Field>, Exception> {
+    let mut fields = Vec::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(kind) = chars.next() {
+        if kind.is_whitespace() {
+            continue;
+        }
+        if !"aAbBhHcsStiInwWmrRfqQdxX@".contains(kind) {
+            return molt_err!("bad field specifier \"{}\"", kind);
+        }
+
+        let unsigned = chars.next_if_eq(&'u').is_some();
+
+        let count = if chars.next_if_eq(&'*').is_some() {
+            Count::All
+        } else {
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            if digits.is_empty() {
+                Count::One
+            } else {
+                match digits.parse() {
+                    Ok(n) => Count::N(n),
+                    Err(_) => return molt_err!("count \"{}\" is too large", digits),
+                }
+            }
+        };
+
+        fields.push(Field { kind, unsigned, count });
+    }
+
+    Ok(fields)
+}
+
+/// Writes the bytes at the position in the output, extending it as needed, and moves the
+/// position past them.
+fn put(out: &mut Vec<u8>, pos: &mut usize, bytes: &[u8]) {
+    let end = *pos + bytes.len();
+    if out.len() < end {
+        out.resize(end, 0);
+    }
+    out[*pos..end].copy_from_slice(bytes);
+    *pos = end;
+}
+
+/// Returns the size in bytes of a numeric field type, or `None` for the other types.
+fn numeric_size(kind: char) -> Option<usize> {
+    match kind {
+        'c' => Some(1),
+        's' | 'S' | 't' => Some(2),
+        'i' | 'I' | 'n' | 'r' | 'R' | 'f' => Some(4),
+        'w' | 'W' | 'm' | 'q' | 'Q' | 'd' => Some(8),
+        _ => None,
+    }
+}
+
+/// Packs the argument of one `binary format` field.
+fn format_field(field: &Field, arg: &Value) -> Result<Vec<u8>, Exception> {
+    match field.kind {
+        'a' | 'A' => {
+            let bytes = arg.as_bytes()?;
+            let len = match field.count {
+                Count::One => 1,
+                Count::N(n) => n,
+                Count::All => bytes.len(),
+            };
+            let pad = if field.kind == 'a' { 0 } else { b' ' };
+            let mut out: Vec<u8> = bytes.iter().copied().take(len).collect();
+            out.resize(len, pad);
+            Ok(out)
+        }
+        'b' | 'B' | 'h' | 'H' => {
+            let (radix, per_byte, name) = match field.kind {
+                'b' | 'B' => (2, 8, "binary"),
+                _ => (16, 2, "hexadecimal"),
+            };
+            let digits: Vec<u32> = arg
+                .as_str()
+                .chars()
+                .map(|c| c.to_digit(radix))
+                .collect::<Option<_>>()
+                .ok_or_else(|| {
+                    Exception::molt_err(
+                        format!("expected {} string but got \"{}\" instead", name, arg)
+                            .into(),
+                    )
+                })?;
+            let len = match field.count {
+                Count::One => 1,
+                Count::N(n) => n,
+                Count::All => digits.len(),
+            };
+            let bits = 8 / per_byte;
+            let mut out = vec![0u8; len.div_ceil(per_byte)];
+            for (i, digit) in digits.iter().take(len).enumerate() {
+                // The low-first types fill each byte from its low end.
+                let slot = if field.kind.is_ascii_lowercase() {
+                    i % per_byte
+                } else {
+                    per_byte - 1 - i % per_byte
+                };
+                out[i / per_byte] |= (*digit as u8) << (slot * bits);
+            }
+            Ok(out)
+        }
+        kind => {
+            let size = numeric_size(kind).expect("numeric field");
+            let values = match field.count {
+                Count::One => vec![arg.clone()],
+                Count::N(n) => {
+                    let list = arg.as_list()?;
+                    if list.len() < n {
+                        return molt_err!(
+                            "number of elements in list does not match count"
+                        );
+                    }
+                    list[..n].to_vec()
+                }
+                Count::All => arg.as_list()?.to_vec(),
+            };
+
+            let mut out = Vec::with_capacity(values.len() * size);
+            for value in values {
+                out.extend(pack_number(kind, size, &value)?);
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Packs a number as the field type, of the given size.  An integer too large for the
+/// size is truncated.
+fn pack_number(kind: char, size: usize, value: &Value) -> Result<Vec<u8>, Exception> {
+    let (little, big) = match kind {
+        'c' | 's' | 'i' | 'w' | 'r' | 'q' => (true, false),
+        'S' | 'I' | 'W' | 'R' | 'Q' => (false, true),
+        _ => (cfg!(target_endian = "little"), cfg!(target_endian = "big")),
+    };
+    debug_assert!(little != big);
+
+    let mut bytes = match kind {
+        'r' | 'R' | 'f' => (value.as_float()? as f32).to_le_bytes().to_vec(),
+        'q' | 'Q' | 'd' => value.as_float()?.to_le_bytes().to_vec(),
+        _ => value.as_int()?.to_le_bytes()[..size].to_vec(),
+    };
+    if big {
+        bytes.reverse();
+    }
+    Ok(bytes)
+}
+
+/// Unpacks one `binary scan` field from the bytes at the position, moving the position
+/// past it, or returns `None` if there aren't enough bytes left.
+fn scan_field(field: &Field, bytes: &[u8], pos: &mut usize) -> Option<Value> {
+    let rest = &bytes[*pos..];
+
+    match field.kind {
+        'a' | 'A' => {
+            let len = match field.count {
+                Count::One => 1,
+                Count::N(n) => n,
+                Count::All => rest.len(),
+            };
+            let mut text = rest.get(..len)?;
+            *pos += len;
+            if field.kind == 'A' {
+                while let [init @ .., b' ' | 0] = text {
+                    text = init;
+                }
+            }
+            Some(Value::from_bytes(text.to_vec()))
+        }
+        'b' | 'B' | 'h' | 'H' => {
+            let (per_byte, digits) = match field.kind {
+                'b' | 'B' => (8, &HEX_DIGITS[..2]),
+                _ => (2, &HEX_DIGITS[..]),
+            };
+            let len = match field.count {
+                Count::One => 1,
+                Count::N(n) => n,
+                Count::All => rest.len() * per_byte,
+            };
+            let used = rest.get(..len.div_ceil(per_byte))?;
+            *pos += used.len();
+
+            let bits = 8 / per_byte;
+            let mask = (1u8 << bits) - 1;
+            let text: String = (0..len)
+                .map(|i| {
+                    let slot = if field.kind.is_ascii_lowercase() {
+                        i % per_byte
+                    } else {
+                        per_byte - 1 - i % per_byte
+                    };
+                    digits[((used[i / per_byte] >> (slot * bits)) & mask) as usize]
+                        as char
+                })
+                .collect();
+            Some(Value::from(text))
+        }
+        kind => {
+            let size = numeric_size(kind).expect("numeric field");
+            let count = match field.count {
+                Count::One => 1,
+                Count::N(n) => n,
+                Count::All => rest.len() / size,
+            };
+            let used = rest.get(..count * size)?;
+            *pos += used.len();
+
+            let values: MoltList =
+                used.chunks(size).map(|chunk| unpack_number(field, chunk)).collect();
+            if field.count == Count::One {
+                values.into_iter().next()
+            } else {
+                Some(Value::from(values))
+            }
+        }
+    }
+}
+
+/// Unpacks a number of the field's type from bytes of its size.
+fn unpack_number(field: &Field, chunk: &[u8]) -> Value {
+    let mut bytes = chunk.to_vec();
+    let big = match field.kind {
+        'S' | 'I' | 'W' | 'R' | 'Q' => true,
+        't' | 'n' | 'm' | 'f' | 'd' => cfg!(target_endian = "big"),
+        _ => false,
+    };
+    if big {
+        bytes.reverse();
+    }
+
+    match field.kind {
+        'r' | 'R' | 'f' => Value::from(f32::from_le_bytes(
+            bytes.try_into().expect("4 bytes"),
+        ) as MoltFloat),
+        'q' | 'Q' | 'd' => {
+            Value::from(MoltFloat::from_le_bytes(bytes.try_into().expect("8 bytes")))
+        }
+        _ => {
+            // Sign-extend from the top bit, unless the field is unsigned.
+            let negative = !field.unsigned && bytes[bytes.len() - 1] & 0x80 != 0;
+            let fill = if negative { 0xFF } else { 0 };
+            let mut wide = [fill; 8];
+            wide[..bytes.len()].copy_from_slice(&bytes);
+            let int = MoltInt::from_le_bytes(wide);
+            if field.unsigned && int < 0 {
+                // Only a 64-bit integer can be too large when unsigned.
+                Value::from(u64::from_le_bytes(wide).to_string())
+            } else {
+                Value::from(int)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template() {
+        assert_eq!(
+            parse_template("a3 cu* @10 S").unwrap(),
+            vec![
+                Field { kind: 'a', unsigned: false, count: Count::N(3) },
+                Field { kind: 'c', unsigned: true, count: Count::All },
+                Field { kind: '@', unsigned: false, count: Count::N(10) },
+                Field { kind: 'S', unsigned: false, count: Count::One },
+            ]
+        );
+        assert!(parse_template("z").is_err());
+    }
+
+    #[test]
+    fn test_numbers_round_trip() {
+        for kind in "csStiInwWmrRfqQd".chars() {
+            let field = Field { kind, unsigned: false, count: Count::One };
+            let size = numeric_size(kind).unwrap();
+            let bytes = pack_number(kind, size, &Value::from(-2)).unwrap();
+            assert_eq!(bytes.len(), size);
+
+            let mut pos = 0;
+            let value = scan_field(&field, &bytes, &mut pos).unwrap();
+            assert_eq!(value.as_float().unwrap(), -2.0, "field type {}", kind);
+            assert_eq!(pos, size);
+        }
+    }
+
+    #[test]
+    fn test_unsigned() {
+        let field = Field { kind: 'w', unsigned: true, count: Count::One };
+        assert_eq!(unpack_number(&field, &[0xFF; 8]).as_str(), "18446744073709551615");
+
+        let field = Field { kind: 'c', unsigned: true, count: Count::One };
+        assert_eq!(unpack_number(&field, &[0xFF]).as_int(), Ok(255));
+    }
+}
@@ -0,0 +1,67 @@
+//! Cooperative Cancellation
+//!
+//! A [`CancelHandle`] lets an application stop a running script, e.g., from a Cancel
+//! button.  Get one from [`Interp::cancel_handle`], and call
+//! [`cancel`](CancelHandle::cancel) on it; the interpreter checks it between commands and
+//! between the operators of an expression, and returns a `ResultCode::Cancelled`
+//! exception, "evaluation canceled", with error code `CANCELED`.
+//!
+//! Natively the handle is `Send` and `Sync`, so another thread can cancel the script.  Under
+//! the `wasm` feature there is only one thread; the host flips the handle between the turns
+//! of its event loop.
+//!
+//! [`Interp::cancel_handle`]: ../interp/struct.Interp.html#method.cancel_handle
+
+cfg_if::cfg_if! {
+  if #[cfg(feature = "wasm")] {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A cheaply cloned handle on an interpreter's cancellation flag; see the
+    /// [module level documentation](index.html).
+    #[derive(Debug, Clone, Default)]
+    pub struct CancelHandle(Rc<Cell<bool>>);
+
+    impl CancelHandle {
+        /// Asks the interpreter to stop the script it's running.
+        pub fn cancel(&self) {
+            self.0.set(true);
+        }
+
+        /// Clears the request, so that evaluation may proceed.
+        pub fn reset(&self) {
+            self.0.set(false);
+        }
+
+        /// Returns true if cancellation has been requested.
+        pub fn is_canceled(&self) -> bool {
+            self.0.get()
+        }
+    }
+  } else {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// A cheaply cloned handle on an interpreter's cancellation flag; see the
+    /// [module level documentation](index.html).
+    #[derive(Debug, Clone, Default)]
+    pub struct CancelHandle(Arc<AtomicBool>);
+
+    impl CancelHandle {
+        /// Asks the interpreter to stop the script it's running.
+        pub fn cancel(&self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+
+        /// Clears the request, so that evaluation may proceed.
+        pub fn reset(&self) {
+            self.0.store(false, Ordering::Relaxed);
+        }
+
+        /// Returns true if cancellation has been requested.
+        pub fn is_canceled(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+  }
+}
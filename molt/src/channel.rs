@@ -0,0 +1,489 @@
+//! Channels
+//!
+//! A channel is a named stream that scripts read from and write to: `puts` writes to one,
+//! and `flush` flushes one.  Each `Interp` has a table of its channels.  The standard
+//! channels, `stdout` and `stderr`, are always present.  A channel's output goes to a
+//! [`Writer`]: by default, the process's standard output and error for the standard
+//! channels, or, under the `std_buff` feature, the interpreter's `std_buff`; a host can
+//! replace them with writers of its own, e.g., to capture or stream the output.
+//!
+//! The `file-io` feature adds the commands that open and use file channels: `open`,
+//! `close`, `read`, `gets`, and `eof`.  Embedded and wasm builds that shouldn't touch the
+//! file system can leave it out.  Add the commands to an interpreter as extra native
+//! commands:
+//!
+//! ```
+//! # use molt_forked::prelude::*;
+//! # #[cfg(feature = "file-io")]
+//! let command = gen_command!(
+//!     (),
+//!     [
+//!         (_OPEN, cmd_open),
+//!         (_CLOSE, cmd_close),
+//!         (_READ, cmd_read),
+//!         (_GETS, cmd_gets),
+//!         (_EOF, cmd_eof),
+//!     ],
+//!     []
+//! );
+//! ```
+use crate::types::*;
+#[cfg(feature = "file-io")]
+use crate::{check_args, interp::Interp, value::Value};
+use std::collections::HashMap;
+#[cfg(feature = "file-io")]
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+#[cfg(feature = "file-io")]
+use std::io::{BufRead, BufReader};
+
+/// The name of the `open` command.
+#[cfg(feature = "file-io")]
+pub const _OPEN: &str = "open";
+/// The name of the `close` command.
+#[cfg(feature = "file-io")]
+pub const _CLOSE: &str = "close";
+/// The name of the `read` command.
+#[cfg(feature = "file-io")]
+pub const _READ: &str = "read";
+/// The name of the `gets` command.
+#[cfg(feature = "file-io")]
+pub const _GETS: &str = "gets";
+/// The name of the `eof` command.
+#[cfg(feature = "file-io")]
+pub const _EOF: &str = "eof";
+
+/// Where a channel's output goes.  Every [`std::io::Write`] is a `Writer`, so a host can
+/// pass a file, a buffer, or its own sink to
+/// [`Interp::set_stdout_writer`](../interp/struct.Interp.html#method.set_stdout_writer);
+/// see also [`Interp::set_stdout_handler`](../interp/struct.Interp.html#method.set_stdout_handler).
+pub trait Writer {
+    /// Writes the text.
+    fn write_output(&mut self, text: &str) -> io::Result<()>;
+
+    /// Flushes any output the writer has buffered.
+    fn flush_output(&mut self) -> io::Result<()>;
+}
+
+impl<W: Write> Writer for W {
+    fn write_output(&mut self, text: &str) -> io::Result<()> {
+        self.write_all(text.as_bytes())
+    }
+
+    fn flush_output(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+/// Lets `write!` and `writeln!` write to a `Writer`, e.g., to
+/// [`Interp::stdout_writer`](../interp/struct.Interp.html#method.stdout_writer).
+impl Write for dyn Writer + '_ {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = std::str::from_utf8(buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.write_output(text)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_output()
+    }
+}
+
+/// A `Writer` that passes the text to a host's function; see
+/// [`Interp::set_stdout_handler`](../interp/struct.Interp.html#method.set_stdout_handler).
+pub(crate) struct FnWriter<F>(pub(crate) F);
+
+impl<F: FnMut(&str)> Writer for FnWriter<F> {
+    fn write_output(&mut self, text: &str) -> io::Result<()> {
+        (self.0)(text);
+        Ok(())
+    }
+
+    fn flush_output(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A channel: a reader, a writer, or both.  Only file channels have readers.
+pub(crate) struct Channel {
+    writer: Option<Box<dyn Writer>>,
+
+    #[cfg(feature = "file-io")]
+    reader: Option<Box<dyn BufRead>>,
+
+    // Whether the last read reached the end of the input.
+    #[cfg(feature = "file-io")]
+    eof: bool,
+}
+
+impl Channel {
+    /// Creates an output channel.  Under `std_buff`, the standard channels have no writer
+    /// by default, and their output goes to the interpreter's `std_buff`.
+    pub(crate) fn output(writer: Option<Box<dyn Writer>>) -> Self {
+        Self {
+            writer,
+            #[cfg(feature = "file-io")]
+            reader: None,
+            #[cfg(feature = "file-io")]
+            eof: false,
+        }
+    }
+
+    /// Creates a file channel from a reader and a writer, either of which may be omitted.
+    #[cfg(feature = "file-io")]
+    pub(crate) fn file(
+        reader: Option<Box<dyn BufRead>>,
+        writer: Option<Box<dyn Writer>>,
+    ) -> Self {
+        Self { writer, reader, eof: false }
+    }
+
+    /// Returns the channel's writer, if it has one.
+    pub(crate) fn writer(&mut self) -> Option<&mut dyn Writer> {
+        self.writer.as_deref_mut().map(|writer| writer as &mut dyn Writer)
+    }
+
+    /// Replaces the channel's writer, flushing the old one.
+    pub(crate) fn set_writer(&mut self, writer: Option<Box<dyn Writer>>) {
+        if let Some(old) = self.writer.as_mut() {
+            let _ = old.flush_output();
+        }
+        self.writer = writer;
+    }
+
+    /// Writes the text to the channel.
+    pub(crate) fn write_str(&mut self, name: &str, text: &str) -> Result<(), Exception> {
+        let Some(writer) = self.writer.as_mut() else {
+            return molt_err!("channel \"{}\" wasn't opened for writing", name);
+        };
+
+        match writer.write_output(text) {
+            Ok(()) => Ok(()),
+            Err(e) => molt_err!("error writing \"{}\": {}", name, e),
+        }
+    }
+
+    /// Flushes any output buffered for the channel.  Flushing a channel that wasn't opened
+    /// for writing does nothing.
+    pub(crate) fn flush(&mut self, name: &str) -> Result<(), Exception> {
+        match self.writer.as_mut().map(|writer| writer.flush_output()) {
+            Some(Err(e)) => molt_err!("error flushing \"{}\": {}", name, e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether the last read reached the end of the input.
+    #[cfg(feature = "file-io")]
+    pub(crate) fn eof(&self) -> bool {
+        self.eof
+    }
+
+    /// Returns the channel's reader, or an error if it wasn't opened for reading.
+    #[cfg(feature = "file-io")]
+    fn reader(&mut self, name: &str) -> Result<&mut dyn BufRead, Exception> {
+        match self.reader.as_deref_mut() {
+            Some(reader) => Ok(reader as &mut dyn BufRead),
+            None => molt_err!("channel \"{}\" wasn't opened for reading", name),
+        }
+    }
+
+    /// Reads the next line, without its line ending, or returns `None` at the end of the
+    /// input.
+    #[cfg(feature = "file-io")]
+    pub(crate) fn read_line(&mut self, name: &str) -> Result<Option<String>, Exception> {
+        let mut line = String::new();
+        let count = match self.reader(name)?.read_line(&mut line) {
+            Ok(count) => count,
+            Err(e) => return molt_err!("error reading \"{}\": {}", name, e),
+        };
+
+        if !line.ends_with('\n') {
+            self.eof = true;
+            if count == 0 {
+                return Ok(None);
+            }
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+
+        Ok(Some(line))
+    }
+
+    /// Reads the rest of the input, or at most `max_chars` characters of it.
+    #[cfg(feature = "file-io")]
+    pub(crate) fn read(
+        &mut self,
+        name: &str,
+        max_chars: Option<usize>,
+    ) -> Result<String, Exception> {
+        let reader = self.reader(name)?;
+        let mut text = String::new();
+
+        let result = match max_chars {
+            None => reader.read_to_string(&mut text).map(|_| true),
+            Some(max_chars) => read_chars(reader, max_chars, &mut text),
+        };
+
+        match result {
+            Ok(eof) => {
+                self.eof = eof;
+                Ok(text)
+            }
+            Err(e) => molt_err!("error reading \"{}\": {}", name, e),
+        }
+    }
+}
+
+/// Reads up to `max_chars` UTF-8 characters, returning whether the input ran out first.
+#[cfg(feature = "file-io")]
+fn read_chars(
+    reader: &mut dyn BufRead,
+    max_chars: usize,
+    text: &mut String,
+) -> io::Result<bool> {
+    for _ in 0..max_chars {
+        let mut bytes = [0u8; 4];
+        if reader.read(&mut bytes[..1])? == 0 {
+            return Ok(true);
+        }
+
+        let len = match bytes[0] {
+            0xF0..=0xFF => 4,
+            0xE0..=0xEF => 3,
+            0xC0..=0xDF => 2,
+            _ => 1,
+        };
+        reader.read_exact(&mut bytes[1..len])?;
+
+        match std::str::from_utf8(&bytes[..len]) {
+            Ok(c) => text.push_str(c),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+
+    Ok(false)
+}
+
+/// An interpreter's channels, by name.
+pub(crate) struct ChannelTable {
+    channels: HashMap<String, Channel>,
+
+    // The number used to name the next file channel.
+    #[cfg(feature = "file-io")]
+    next_id: usize,
+}
+
+impl ChannelTable {
+    /// Creates the table with the standard channels.
+    pub(crate) fn new() -> Self {
+        let mut channels = HashMap::new();
+        channels.insert("stdout".into(), Channel::output(Self::default_writer("stdout")));
+        channels.insert("stderr".into(), Channel::output(Self::default_writer("stderr")));
+
+        Self {
+            channels,
+            #[cfg(feature = "file-io")]
+            next_id: 1,
+        }
+    }
+
+    /// Returns the default writer for a standard channel: the process's standard output or
+    /// error, or, under `std_buff`, none, so that the output goes to the interpreter's
+    /// buffer.
+    pub(crate) fn default_writer(name: &str) -> Option<Box<dyn Writer>> {
+        cfg_if::cfg_if! {
+          if #[cfg(feature = "std_buff")] {
+            let _ = name;
+            None
+          } else {
+            Some(if name == "stderr" { Box::new(io::stderr()) } else { Box::new(io::stdout()) })
+          }
+        }
+    }
+
+    /// Returns the named channel.
+    pub(crate) fn get(&mut self, name: &str) -> Result<&mut Channel, Exception> {
+        match self.channels.get_mut(name) {
+            Some(channel) => Ok(channel),
+            None => molt_err!("can not find channel named \"{}\"", name),
+        }
+    }
+
+    /// Adds a file channel, returning its name.
+    #[cfg(feature = "file-io")]
+    pub(crate) fn add(&mut self, channel: Channel) -> String {
+        let name = format!("file{}", self.next_id);
+        self.next_id += 1;
+        self.channels.insert(name.clone(), channel);
+        name
+    }
+
+    /// Flushes and removes the named channel.  The standard channels can't be closed.
+    #[cfg(feature = "file-io")]
+    pub(crate) fn close(&mut self, name: &str) -> Result<(), Exception> {
+        if name == "stdout" || name == "stderr" {
+            return molt_err!("can not close standard channel \"{}\"", name);
+        }
+
+        self.get(name)?;
+        let mut channel = self.channels.remove(name).expect("channel exists");
+        channel.flush(name)
+    }
+}
+
+/// # open *fileName* ?*access*?
+///
+/// Opens the file, returning the name of a new channel for it.  The *access* is `r`
+/// (the default), `r+`, `w`, `w+`, `a`, or `a+`, as for C's `fopen`.
+#[cfg(feature = "file-io")]
+pub fn cmd_open<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "fileName ?access?")?;
+
+    let path = argv[1].as_str();
+    let access = argv.get(2).map_or("r", |access| access.as_str());
+
+    let mut options = OpenOptions::new();
+    let (read, write) = match access {
+        "r" => (true, false),
+        "r+" => (true, true),
+        "w" => {
+            options.create(true).truncate(true);
+            (false, true)
+        }
+        "w+" => {
+            options.create(true).truncate(true);
+            (true, true)
+        }
+        "a" => {
+            options.create(true).append(true);
+            (false, true)
+        }
+        "a+" => {
+            options.create(true).append(true);
+            (true, true)
+        }
+        _ => return molt_err!("illegal access mode \"{}\"", access),
+    };
+    options.read(read).write(write);
+
+    let opened = options.open(path).and_then(|file| {
+        let reader: Option<Box<dyn BufRead>> =
+            if read { Some(Box::new(BufReader::new(file.try_clone()?))) } else { None };
+        let writer: Option<Box<dyn Writer>> =
+            if write { Some(Box::new(io::BufWriter::new(file))) } else { None };
+        Ok(Channel::file(reader, writer))
+    });
+
+    match opened {
+        Ok(channel) => molt_ok!(interp.channels().add(channel)),
+        Err(e) => molt_err!("couldn't open \"{}\": {}", path, describe(&e)),
+    }
+}
+
+/// # close *channelId*
+///
+/// Flushes and closes the channel.
+#[cfg(feature = "file-io")]
+pub fn cmd_close<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 2, "channelId")?;
+    interp.channels().close(argv[1].as_str())?;
+    molt_ok!()
+}
+
+/// # read ?-nonewline? *channelId*
+/// # read *channelId* *numChars*
+///
+/// Reads the rest of the channel's input, optionally dropping a final newline, or at most
+/// *numChars* characters of it.
+#[cfg(feature = "file-io")]
+pub fn cmd_read<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "channelId ?numChars? or read ?-nonewline? channelId")?;
+
+    if argv.len() == 3 && argv[1].as_str() != "-nonewline" {
+        let count = argv[2].as_int()?;
+        if count < 0 {
+            return molt_err!("expected non-negative integer but got \"{}\"", argv[2]);
+        }
+        let name = argv[1].as_str();
+        return molt_ok!(interp
+            .channels()
+            .get(name)?
+            .read(name, Some(count as usize))?);
+    }
+
+    let name = argv[argv.len() - 1].as_str();
+    let mut text = interp.channels().get(name)?.read(name, None)?;
+
+    if argv.len() == 3 && text.ends_with('\n') {
+        text.pop();
+    }
+
+    molt_ok!(text)
+}
+
+/// # gets *channelId* ?*varName*?
+///
+/// Reads the next line from the channel, without its newline.  Returns the line, or, if
+/// *varName* is given, assigns the line to it and returns its length in characters, or
+/// -1 at the end of the input.
+#[cfg(feature = "file-io")]
+pub fn cmd_gets<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "channelId ?varName?")?;
+
+    let name = argv[1].as_str();
+    let line = interp.channels().get(name)?.read_line(name)?;
+
+    let Some(var) = argv.get(2) else {
+        return molt_ok!(line.unwrap_or_default());
+    };
+
+    match line {
+        Some(line) => {
+            let len = line.chars().count() as MoltInt;
+            interp.set_var(var, Value::from(line))?;
+            molt_ok!(len)
+        }
+        None => {
+            interp.set_var(var, Value::empty())?;
+            molt_ok!(-1)
+        }
+    }
+}
+
+/// # eof *channelId*
+///
+/// Returns 1 if the last read from the channel reached the end of its input, and 0
+/// otherwise.
+#[cfg(feature = "file-io")]
+pub fn cmd_eof<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 2, "channelId")?;
+
+    let name = argv[1].as_str();
+    molt_ok!(interp.channels().get(name)?.eof())
+}
+
+/// Describes an I/O error in TCL's style, e.g., "no such file or directory".
+#[cfg(any(feature = "file-io", feature = "exec"))]
+pub(crate) fn describe(error: &io::Error) -> String {
+    match error.kind() {
+        io::ErrorKind::NotFound => "no such file or directory".into(),
+        io::ErrorKind::PermissionDenied => "permission denied".into(),
+        io::ErrorKind::AlreadyExists => "file already exists".into(),
+        io::ErrorKind::DirectoryNotEmpty => "directory not empty".into(),
+        _ => {
+            // Drop Rust's " (os error N)" suffix.
+            let message = error.to_string();
+            match message.find(" (os error") {
+                Some(i) => message[..i].to_lowercase(),
+                None => message.to_lowercase(),
+            }
+        }
+    }
+}
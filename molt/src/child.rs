@@ -0,0 +1,402 @@
+//! Child Interpreters
+//!
+//! An interpreter can create child interpreters, each with its own commands, procs, and
+//! variables, to run scripts apart from its own: a plug-in's, say, or an untrusted
+//! user's.  A child is an `Interp<()>` with the same standard commands as its parent.
+//! A child that isn't safe also has `source`; a safe one has nothing that reaches outside
+//! the interpreter, as with [`Interp::new_safe`].
+//!
+//! Aliases bridge the two: an alias is a command in one interpreter that calls a command
+//! prefix in another, e.g., so that a safe child can log through its parent.  An alias
+//! in a child can call its parent only while the parent is evaluating a script in the
+//! child, e.g., via `interp eval` or [`Interp::eval_in_child`].
+//!
+//! The `interp` command manages an interpreter's children; see also
+//! [`Interp::create_child`].  The `alias` command, like [`Interp::add_alias`], defines
+//! aliases within a single interpreter, e.g., `alias ll {lsort -dictionary}`.
+//!
+//! [`Interp::add_alias`]: ../interp/struct.Interp.html#method.add_alias
+//! [`Interp::create_child`]: ../interp/struct.Interp.html#method.create_child
+//! [`Interp::new_safe`]: ../interp/struct.Interp.html#method.new_safe
+//! [`Interp::eval_in_child`]: ../interp/struct.Interp.html#method.eval_in_child
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The name of the `alias` command.
+pub const _ALIAS: &str = "alias";
+
+/// The name of the `interp` command.
+pub const _INTERP: &str = "interp";
+
+/// An alias: a command that calls a command prefix in the same interpreter, its
+/// parent, or one of its children.
+#[derive(Debug, Clone)]
+pub(crate) struct Alias {
+    pub(crate) target: AliasTarget,
+    pub(crate) prefix: MoltList,
+}
+
+/// The interpreter in which an alias's target command is called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum AliasTarget {
+    Own,
+    Parent,
+    Child(String),
+}
+
+impl Alias {
+    /// Returns the words of the command to call for a call of the alias.
+    pub(crate) fn command(&self, words: &[Value]) -> Value {
+        let mut command = self.prefix.clone();
+        command.extend_from_slice(&words[1..]);
+        Value::from(command)
+    }
+}
+
+/// A child's link to its parent, set while the parent is evaluating a script in it.  The
+/// parent's type is erased, so that every child is an `Interp<()>`.
+pub(crate) struct ParentLink {
+    interp: *mut (),
+    eval: fn(*mut (), &Value) -> MoltResult,
+}
+
+impl ParentLink {
+    /// Creates a link to the parent.
+    pub(crate) fn new<Ctx: 'static>(parent: &mut Interp<Ctx>) -> Self {
+        Self {
+            interp: parent as *mut Interp<Ctx> as *mut (),
+            eval: eval_in_parent::<Ctx>,
+        }
+    }
+
+    /// Evaluates the command in the parent.
+    pub(crate) fn eval(&self, command: &Value) -> MoltResult {
+        (self.eval)(self.interp, command)
+    }
+}
+
+fn eval_in_parent<Ctx: 'static>(parent: *mut (), command: &Value) -> MoltResult {
+    // SAFETY: the link is set only by `Interp::eval_in_child`, for the duration of the
+    // child's evaluation.  The parent's `&mut` is held by that call and isn't otherwise
+    // used until the child returns, and the child has been taken out of the parent's
+    // table of children, so this is the only live reference to the parent.
+    let parent = unsafe { &mut *(parent as *mut Interp<Ctx>) };
+    parent.eval_value(command)
+}
+
+/// # alias ?*name* ?*prefix*??
+///
+/// Defines, queries, or deletes an alias in this interpreter: a command that calls the
+/// command prefix, with the alias's arguments appended.
+///
+/// * `alias`: returns the names of the interpreter's aliases.
+/// * `alias name`: returns the alias's command prefix.
+/// * `alias name prefix`: defines the alias; an empty prefix deletes it.
+pub fn cmd_alias<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 1, 3, "?name? ?prefix?")?;
+
+    let Some(name) = argv.get(1) else {
+        return molt_ok!(alias_names(interp.aliases()));
+    };
+    let name = name.as_str();
+
+    let Some(prefix) = argv.get(2) else {
+        return match interp.aliases().get(name) {
+            Some(alias) => molt_ok!(alias.prefix.clone()),
+            None => molt_err!("alias \"{}\" not found", name),
+        };
+    };
+
+    let prefix = prefix.as_list()?;
+    if prefix.is_empty() {
+        if !interp.remove_alias(name) {
+            return molt_err!("alias \"{}\" not found", name);
+        }
+        return molt_ok!();
+    }
+    if prefix[0].as_str() == name {
+        return molt_err!(
+            "cannot define or rename alias \"{}\": would create a loop",
+            name
+        );
+    }
+
+    interp.add_alias(name, prefix.to_vec());
+    molt_ok!()
+}
+
+/// # interp *subcommand* ?*arg* ...?
+///
+/// Creates and manages child interpreters.
+///
+/// * `interp alias srcPath srcCmd ?targetPath targetCmd ?arg ...??`: defines, queries,
+///   or, with an empty `targetPath`, deletes an alias.
+/// * `interp aliases ?path?`: returns the names of the interpreter's aliases.
+/// * `interp children`: returns the names of the child interpreters.
+/// * `interp create ?-safe? ?--? ?path?`: creates a child interpreter.
+/// * `interp delete ?path ...?`: deletes child interpreters.
+/// * `interp eval path arg ?arg ...?`: evaluates a script in a child.
+/// * `interp exists path`: returns whether the child exists.
+/// * `interp issafe ?path?`: returns whether the interpreter is safe.
+/// * `interp limit path commands|time ?-option ?value??`: queries or sets a child's
+///   command count or time limit.
+///
+/// The empty path is the interpreter itself.
+pub fn cmd_interp<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("alias", cmd_interp_alias),
+            ("aliases", cmd_interp_aliases),
+            ("children", cmd_interp_children),
+            ("create", cmd_interp_create),
+            ("delete", cmd_interp_delete),
+            ("eval", cmd_interp_eval),
+            ("exists", cmd_interp_exists),
+            ("issafe", cmd_interp_issafe),
+            ("limit", cmd_interp_limit),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// Returns the aliases of the interpreter at the path, the interpreter itself or one of
+/// its children.
+fn aliases_at<'a, Ctx>(
+    interp: &'a mut Interp<Ctx>,
+    path: &str,
+) -> Result<&'a mut HashMap<String, Alias>, Exception> {
+    if path.is_empty() {
+        Ok(interp.aliases())
+    } else {
+        Ok(child_at(interp, path)?.aliases())
+    }
+}
+
+/// Returns the names of the aliases, sorted.
+fn alias_names(aliases: &HashMap<String, Alias>) -> MoltList {
+    let mut names: Vec<&String> = aliases.keys().collect();
+    names.sort();
+    names.into_iter().map(|name| Value::from(name.as_str())).collect()
+}
+
+/// Returns the named child.
+fn child_at<'a, Ctx>(
+    interp: &'a mut Interp<Ctx>,
+    path: &str,
+) -> Result<&'a mut Interp<()>, Exception> {
+    match interp.child(path) {
+        Some(child) => Ok(child),
+        None => molt_err!("could not find interpreter \"{}\"", path),
+    }
+}
+
+/// # interp alias *srcPath* *srcCmd* ?*targetPath* *targetCmd* ?*arg* ...??
+fn cmd_interp_alias<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "srcPath srcCmd ?targetPath targetCmd ?arg ...??")?;
+
+    let src_path = argv[2].as_str();
+    let src_cmd = argv[3].as_str();
+
+    // Query
+    if argv.len() == 4 {
+        return match aliases_at(interp, src_path)?.get(src_cmd) {
+            Some(alias) => molt_ok!(alias.prefix.clone()),
+            None => molt_err!("alias \"{}\" not found", src_cmd),
+        };
+    }
+
+    // Delete
+    let target_path = argv[4].as_str();
+    if argv.len() == 5 {
+        if !target_path.is_empty() {
+            return molt_err!(
+                "wrong # args: should be \"interp alias srcPath srcCmd ?targetPath targetCmd ?arg ...??\""
+            );
+        }
+        return match aliases_at(interp, src_path)?.remove(src_cmd) {
+            Some(_) => molt_ok!(),
+            None => molt_err!("alias \"{}\" not found", src_cmd),
+        };
+    }
+
+    // Define
+    let target = if target_path == src_path {
+        AliasTarget::Own
+    } else if target_path.is_empty() {
+        AliasTarget::Parent
+    } else if src_path.is_empty() {
+        child_at(interp, target_path)?;
+        AliasTarget::Child(target_path.into())
+    } else {
+        return molt_err!(
+            "can't alias from \"{}\" to \"{}\": one of them must be the current interpreter",
+            src_path,
+            target_path
+        );
+    };
+
+    if target == AliasTarget::Own && argv[5].as_str() == src_cmd {
+        return molt_err!(
+            "cannot define or rename alias \"{}\": would create a loop",
+            src_cmd
+        );
+    }
+
+    let alias = Alias { target, prefix: argv[5..].to_vec() };
+    aliases_at(interp, src_path)?.insert(src_cmd.into(), alias);
+    molt_ok!(src_cmd)
+}
+
+/// # interp aliases ?*path*?
+fn cmd_interp_aliases<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 3, "?path?")?;
+
+    let path = argv.get(2).map_or("", Value::as_str);
+    molt_ok!(alias_names(aliases_at(interp, path)?))
+}
+
+/// # interp children
+fn cmd_interp_children<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    let names: MoltList = interp.child_names().into_iter().map(Value::from).collect();
+    molt_ok!(names)
+}
+
+/// # interp create ?-safe? ?--? ?*path*?
+fn cmd_interp_create<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 5, "?-safe? ?--? ?path?")?;
+
+    let mut safe = false;
+    let mut i = 2;
+    while i < argv.len() {
+        match argv[i].as_str() {
+            "-safe" => safe = true,
+            "--" => {
+                i += 1;
+                break;
+            }
+            _ => break,
+        }
+        i += 1;
+    }
+
+    match &argv[i..] {
+        [] => molt_ok!(interp.create_child(safe)),
+        [path] => {
+            interp.create_named_child(path.as_str(), safe)?;
+            molt_ok!(path.clone())
+        }
+        _ => molt_err!("wrong # args: should be \"interp create ?-safe? ?--? ?path?\""),
+    }
+}
+
+/// # interp delete ?*path* ...?
+fn cmd_interp_delete<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 0, "?path ...?")?;
+
+    for path in &argv[2..] {
+        if !interp.delete_child(path.as_str()) {
+            return molt_err!("could not find interpreter \"{}\"", path);
+        }
+    }
+    molt_ok!()
+}
+
+/// # interp eval *path* *arg* ?*arg* ...?
+fn cmd_interp_eval<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "path arg ?arg ...?")?;
+
+    let path = argv[2].as_str();
+    let script = if argv.len() == 4 {
+        argv[3].clone()
+    } else {
+        let words: Vec<&str> = argv[3..].iter().map(|arg| arg.as_str().trim()).collect();
+        Value::from(words.join(" "))
+    };
+
+    if path.is_empty() {
+        interp.eval_value(&script)
+    } else {
+        interp.eval_in_child(path, &script)
+    }
+}
+
+/// # interp exists *path*
+fn cmd_interp_exists<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "path")?;
+
+    let path = argv[2].as_str();
+    molt_ok!(path.is_empty() || interp.child(path).is_some())
+}
+
+/// # interp issafe ?*path*?
+fn cmd_interp_issafe<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 3, "?path?")?;
+
+    match argv.get(2).map(Value::as_str) {
+        None | Some("") => molt_ok!(interp.is_safe()),
+        Some(path) => molt_ok!(child_at(interp, path)?.is_safe()),
+    }
+}
+
+/// # interp limit *path* commands|time ?*-option* ?*value*??
+///
+/// The option is `-value`, the number of commands, for `commands`, and
+/// `-milliseconds` for `time`.  The empty string removes the limit.
+fn cmd_interp_limit<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 6, "path limitType ?-option? ?value?")?;
+
+    let option = match argv[3].as_str() {
+        "commands" => "-value",
+        "time" => "-milliseconds",
+        other => {
+            return molt_err!("bad limit type \"{}\": must be commands or time", other)
+        }
+    };
+    if let Some(given) = argv.get(4) {
+        if given.as_str() != option {
+            return molt_err!("bad option \"{}\": must be {}", given, option);
+        }
+    }
+
+    let child = child_at(interp, argv[2].as_str())?;
+    let current = if option == "-value" {
+        child.command_limit().map(|limit| limit as MoltInt)
+    } else {
+        child.time_limit().map(|limit| limit.as_millis() as MoltInt)
+    };
+
+    let Some(value) = argv.get(5) else {
+        let current = current.map_or_else(Value::empty, Value::from);
+        return if argv.len() == 5 {
+            molt_ok!(current)
+        } else {
+            molt_ok!(Value::from(vec![Value::from(option), current]))
+        };
+    };
+
+    let limit = if value.as_str().is_empty() {
+        None
+    } else {
+        match value.as_int()? {
+            limit if limit < 0 => {
+                return molt_err!("expected non-negative integer but got \"{}\"", value)
+            }
+            limit => Some(limit as u64),
+        }
+    };
+
+    if option == "-value" {
+        child.set_command_limit(limit);
+    } else {
+        child.set_time_limit(limit.map(Duration::from_millis));
+    }
+    molt_ok!()
+}
@@ -0,0 +1,503 @@
+//! Clock
+//!
+//! The `clock` command, which gets the current time and converts times to and from
+//! strings.  A time is an integer number of seconds since the Epoch, 1970-01-01 00:00:00
+//! UTC.  The current time comes from `std::time` natively and from `wasm_timer` under
+//! the `wasm` feature, as elsewhere in Molt.
+//!
+//! Molt has no time zone database, so all times are formatted and scanned as UTC.
+//!
+//! `clock format` and `clock scan` take formats like C's `strftime`:
+//!
+//! * `%a`, `%A`: the abbreviated or full weekday name, e.g., `Mon` or `Monday`.
+//! * `%b`, `%h`, `%B`: the abbreviated or full month name, e.g., `Jan` or `January`.
+//! * `%d`, `%e`: the day of the month, padded with a zero or a space.
+//! * `%H`, `%I`: the hour on the 24-hour or 12-hour clock; `%p` is `AM` or `PM`.
+//! * `%j`: the day of the year, 001 to 366.
+//! * `%m`, `%M`, `%S`: the month, minute, and second.
+//! * `%s`: the time in seconds since the Epoch.
+//! * `%u`, `%w`: the day of the week, 1 to 7 from Monday, or 0 to 6 from Sunday.
+//! * `%y`, `%Y`: the year within the century, or the full year.
+//! * `%z`, `%Z`: the time zone, always `+0000` and `UTC`.
+//! * `%D`, `%F`, `%R`, `%T`: `%m/%d/%y`, `%Y-%m-%d`, `%H:%M`, and `%H:%M:%S`.
+//! * `%n`, `%t`, `%%`: a newline, tab, or `%`.
+//!
+//! `clock scan` accepts the numeric fields, the month names, `%p`, `%s`, and the
+//! composite fields.
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use std::time::Duration;
+
+cfg_if::cfg_if! {
+  if #[cfg(feature = "wasm")] {
+    use wasm_timer::{SystemTime, UNIX_EPOCH};
+  }else{
+    use std::time::{SystemTime, UNIX_EPOCH};
+  }
+}
+
+/// The name of the `clock` command.
+pub const _CLOCK: &str = "clock";
+
+/// The default format for `clock format`.
+const DEFAULT_FORMAT: &str = "%a %b %d %H:%M:%S %Z %Y";
+
+/// The formats `clock scan` tries, in order, when no format is given.
+const FREE_FORMATS: [&str; 4] =
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d", "%H:%M:%S"];
+
+const SECONDS_PER_DAY: MoltInt = 86400;
+
+const WEEKDAYS: [&str; 7] =
+    ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// # clock *subcommand* ?*arg* ...?
+///
+/// Gets the current time and converts times to and from strings.
+///
+/// * `clock format timeVal ?-format string? ?-gmt boolean?`: formats the time.
+/// * `clock microseconds`, `clock milliseconds`, `clock seconds`: returns the current
+///   time since the Epoch.
+/// * `clock scan string ?-format string? ?-gmt boolean?`: parses the time.
+pub fn cmd_clock<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("format", cmd_clock_format),
+            ("microseconds", cmd_clock_microseconds),
+            ("milliseconds", cmd_clock_milliseconds),
+            ("scan", cmd_clock_scan),
+            ("seconds", cmd_clock_seconds),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// # clock format *timeVal* ?-format *string*? ?-gmt *boolean*?
+fn cmd_clock_format<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "clockval ?-format string? ?-gmt boolean?";
+    check_args(2, argv, 3, 0, USAGE)?;
+
+    let secs = argv[2].as_int()?;
+    let fmt = parse_options(argv, USAGE)?;
+
+    molt_ok!(format_time(secs, fmt.unwrap_or(DEFAULT_FORMAT)))
+}
+
+/// # clock microseconds
+fn cmd_clock_microseconds<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(now().as_micros() as MoltInt)
+}
+
+/// # clock milliseconds
+fn cmd_clock_milliseconds<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(now().as_millis() as MoltInt)
+}
+
+/// # clock scan *string* ?-format *string*? ?-gmt *boolean*?
+fn cmd_clock_scan<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "string ?-format string? ?-gmt boolean?";
+    check_args(2, argv, 3, 0, USAGE)?;
+
+    let input = argv[2].as_str();
+
+    match parse_options(argv, USAGE)? {
+        Some(fmt) => molt_ok!(scan_time(input, fmt)?),
+        None => match FREE_FORMATS.iter().find_map(|fmt| scan_time(input, fmt).ok()) {
+            Some(secs) => molt_ok!(secs),
+            None => molt_err!("unable to convert date-time string \"{}\"", input),
+        },
+    }
+}
+
+/// # clock seconds
+fn cmd_clock_seconds<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(now().as_secs() as MoltInt)
+}
+
+/// Parses the `-format` and `-gmt` options following the subcommand's argument,
+/// returning the format, if any.  Times are always UTC, so `-gmt` is checked and
+/// ignored.
+fn parse_options<'a>(
+    argv: &'a [Value],
+    usage: &str,
+) -> Result<Option<&'a str>, Exception> {
+    let mut fmt = None;
+
+    for pair in argv[3..].chunks(2) {
+        let [opt, value] = pair else {
+            return molt_err!("wrong # args: should be \"clock {} {}\"", argv[1], usage);
+        };
+
+        match opt.as_str() {
+            "-format" => fmt = Some(value.as_str()),
+            "-gmt" => {
+                value.as_bool()?;
+            }
+            _ => return molt_err!("bad option \"{}\": must be -format or -gmt", opt),
+        }
+    }
+
+    Ok(fmt)
+}
+
+/// Returns the current time since the Epoch.
+fn now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Returns the year, month, and day of the days since the Epoch, in the proleptic
+/// Gregorian calendar.  This is Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: MoltInt) -> (MoltInt, MoltInt, MoltInt) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Returns the days since the Epoch of the year, month, and day; the inverse of
+/// `civil_from_days`.
+fn days_from_civil(year: MoltInt, month: MoltInt, day: MoltInt) -> MoltInt {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Formats the time, in seconds since the Epoch, as UTC.  Unknown conversions are
+/// copied to the output as is.
+fn format_time(secs: MoltInt, fmt: &str) -> String {
+    let days = secs.div_euclid(SECONDS_PER_DAY);
+    let time = secs.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let (hour, minute, second) = (time / 3600, time / 60 % 60, time % 60);
+    let weekday = (days + 4).rem_euclid(7) as usize;
+    let yday = days - days_from_civil(year, 1, 1) + 1;
+    let hour12 = if hour % 12 == 0 { 12 } else { hour % 12 };
+    let month_name = MONTHS[month as usize - 1];
+
+    let mut result = String::with_capacity(fmt.len() * 2);
+    let mut chars = fmt.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let field = match chars.next() {
+            Some('a') => WEEKDAYS[weekday][..3].to_string(),
+            Some('A') => WEEKDAYS[weekday].to_string(),
+            Some('b' | 'h') => month_name[..3].to_string(),
+            Some('B') => month_name.to_string(),
+            Some('d') => format!("{:02}", day),
+            Some('D') => format!("{:02}/{:02}/{:02}", month, day, year.rem_euclid(100)),
+            Some('e') => format!("{:2}", day),
+            Some('F') => format!("{:04}-{:02}-{:02}", year, month, day),
+            Some('H') => format!("{:02}", hour),
+            Some('I') => format!("{:02}", hour12),
+            Some('j') => format!("{:03}", yday),
+            Some('m') => format!("{:02}", month),
+            Some('M') => format!("{:02}", minute),
+            Some('n') => "\n".to_string(),
+            Some('p') => (if hour < 12 { "AM" } else { "PM" }).to_string(),
+            Some('R') => format!("{:02}:{:02}", hour, minute),
+            Some('s') => secs.to_string(),
+            Some('S') => format!("{:02}", second),
+            Some('t') => "\t".to_string(),
+            Some('T') => format!("{:02}:{:02}:{:02}", hour, minute, second),
+            Some('u') => (if weekday == 0 { 7 } else { weekday }).to_string(),
+            Some('w') => weekday.to_string(),
+            Some('y') => format!("{:02}", year.rem_euclid(100)),
+            Some('Y') => format!("{:04}", year),
+            Some('z') => "+0000".to_string(),
+            Some('Z') => "UTC".to_string(),
+            Some('%') => "%".to_string(),
+            Some(other) => format!("%{}", other),
+            None => "%".to_string(),
+        };
+
+        result.push_str(&field);
+    }
+
+    result
+}
+
+/// Parses the time, as UTC, according to the format, returning the seconds since the
+/// Epoch.  Whitespace in the format matches any amount of whitespace.  If the format has
+/// no date fields the date is today's.
+fn scan_time(input: &str, fmt: &str) -> Result<MoltInt, Exception> {
+    let mut scanner = TimeScanner { input: input.chars().collect(), pos: 0 };
+
+    let (mut year, mut month, mut day) = (None, None, None);
+    let (mut hour, mut minute, mut second) = (0, 0, 0);
+    let mut pm = None;
+    let mut epoch = None;
+
+    // Expand the composite fields, so that only simple ones remain.
+    let fmt = fmt
+        .replace("%D", "%m/%d/%y")
+        .replace("%F", "%Y-%m-%d")
+        .replace("%R", "%H:%M")
+        .replace("%T", "%H:%M:%S");
+
+    let mut chars = fmt.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            scanner.skip_space();
+            continue;
+        }
+
+        if c != '%' {
+            scanner.literal(c)?;
+            continue;
+        }
+
+        match chars.next() {
+            Some('b' | 'B' | 'h') => month = Some(scanner.month()?),
+            Some('d' | 'e') => {
+                scanner.skip_space();
+                day = Some(scanner.number(2)?);
+            }
+            Some('H' | 'I') => hour = scanner.number(2)?,
+            Some('j') => {
+                let yday = scanner.number(3)?;
+                month = Some(1);
+                day = Some(yday);
+            }
+            Some('m') => month = Some(scanner.number(2)?),
+            Some('M') => minute = scanner.number(2)?,
+            Some('p') => pm = Some(scanner.meridian()?),
+            Some('s') => epoch = Some(scanner.signed()?),
+            Some('S') => second = scanner.number(2)?,
+            Some('y') => {
+                let yy = scanner.number(2)?;
+                year = Some(if yy < 69 { 2000 + yy } else { 1900 + yy });
+            }
+            Some('Y') => year = Some(scanner.number(4)?),
+            Some('n' | 't') => scanner.skip_space(),
+            Some('%') => scanner.literal('%')?,
+            Some(other) => {
+                return molt_err!("unsupported format field \"%{}\" in clock scan", other)
+            }
+            None => scanner.literal('%')?,
+        }
+    }
+
+    if scanner.pos < scanner.input.len() {
+        return molt_err!("input string does not match supplied format");
+    }
+
+    if let Some(secs) = epoch {
+        return Ok(secs);
+    }
+
+    // NEXT, apply AM/PM to a 12-hour clock time.
+    if let Some(pm) = pm {
+        if !(1..=12).contains(&hour) {
+            return molt_err!("input string does not match supplied format");
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+
+    if !(1..=12).contains(&month.unwrap_or(1))
+        || !(1..=366).contains(&day.unwrap_or(1))
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return molt_err!("input string does not match supplied format");
+    }
+
+    // NEXT, default the date to today.
+    let days = if year.is_none() && month.is_none() && day.is_none() {
+        (now().as_secs() as MoltInt).div_euclid(SECONDS_PER_DAY)
+    } else {
+        days_from_civil(year.unwrap_or(1970), month.unwrap_or(1), 1) + day.unwrap_or(1)
+            - 1
+    };
+
+    Ok(days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second)
+}
+
+/// The input to `scan_time`, and the position within it.
+struct TimeScanner {
+    input: Vec<char>,
+    pos: usize,
+}
+
+impl TimeScanner {
+    fn skip_space(&mut self) {
+        while self.input.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Matches a literal character.
+    fn literal(&mut self, c: char) -> Result<(), Exception> {
+        if self.input.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            molt_err!("input string does not match supplied format")
+        }
+    }
+
+    /// Scans an unsigned number of at most the given number of digits.
+    fn number(&mut self, max_digits: usize) -> Result<MoltInt, Exception> {
+        let start = self.pos;
+        while self.pos - start < max_digits
+            && self.input.get(self.pos).is_some_and(|c| c.is_ascii_digit())
+        {
+            self.pos += 1;
+        }
+
+        if self.pos == start {
+            return molt_err!("input string does not match supplied format");
+        }
+
+        Ok(self.input[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0))
+    }
+
+    /// Scans an optionally signed number of any length.
+    fn signed(&mut self) -> Result<MoltInt, Exception> {
+        let negative = self.input.get(self.pos) == Some(&'-');
+        if matches!(self.input.get(self.pos), Some('-' | '+')) {
+            self.pos += 1;
+        }
+
+        let start = self.pos;
+        while self.input.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        let digits: String = self.input[start..self.pos].iter().collect();
+        match digits.parse::<MoltInt>() {
+            Ok(secs) if negative => Ok(-secs),
+            Ok(secs) => Ok(secs),
+            Err(_) => molt_err!("input string does not match supplied format"),
+        }
+    }
+
+    /// Scans a month name, full or abbreviated, ignoring case; returns the month number.
+    fn month(&mut self) -> Result<MoltInt, Exception> {
+        let rest: String =
+            self.input[self.pos..].iter().collect::<String>().to_lowercase();
+
+        for (i, name) in MONTHS.iter().enumerate() {
+            let name = name.to_lowercase();
+            for candidate in [&name[..], &name[..3]] {
+                if rest.starts_with(candidate) {
+                    self.pos += candidate.len();
+                    return Ok(i as MoltInt + 1);
+                }
+            }
+        }
+
+        molt_err!("input string does not match supplied format")
+    }
+
+    /// Scans `AM` or `PM`, ignoring case; returns true for `PM`.
+    fn meridian(&mut self) -> Result<bool, Exception> {
+        let rest: String = self.input[self.pos..]
+            .iter()
+            .take(2)
+            .collect::<String>()
+            .to_uppercase();
+
+        match rest.as_str() {
+            "AM" | "PM" => {
+                self.pos += 2;
+                Ok(rest == "PM")
+            }
+            _ => molt_err!("input string does not match supplied format"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 2, 29), 11016);
+
+        for days in (-800_000..800_000).step_by(997) {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0, DEFAULT_FORMAT), "Thu Jan 01 00:00:00 UTC 1970");
+        assert_eq!(
+            format_time(951782400 + 13 * 3600 + 5 * 60 + 9, "%Y-%m-%d %H:%M:%S %j %u %w"),
+            "2000-02-29 13:05:09 060 2 2"
+        );
+        assert_eq!(format_time(-1, "%F %T"), "1969-12-31 23:59:59");
+        assert_eq!(
+            format_time(0, "%A %B %e %I %p %y %s %%"),
+            "Thursday January  1 12 AM 70 0 %"
+        );
+        assert_eq!(format_time(43200, "%D %R %z %q"), "01/01/70 12:00 +0000 %q");
+    }
+
+    #[test]
+    fn test_scan_time() {
+        assert_eq!(scan_time("1970-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap(), 0);
+        assert_eq!(scan_time("2000-02-29", "%F").unwrap(), 951782400);
+        assert_eq!(
+            scan_time("29 feb 2000 1:05:09 pm", "%d %b %Y %I:%M:%S %p").unwrap(),
+            951829509
+        );
+        assert_eq!(scan_time("12/31/69", "%D").unwrap(), -86400);
+        assert_eq!(scan_time("1234567", "%s").unwrap(), 1234567);
+        assert_eq!(scan_time("1970 060", "%Y %j").unwrap(), 59 * 86400);
+
+        assert!(scan_time("2000-13-01", "%F").is_err());
+        assert!(scan_time("2000-01-01x", "%F").is_err());
+        assert!(scan_time("2000/01/01", "%F").is_err());
+        assert!(scan_time("2000", "%Q").is_err());
+    }
+}
@@ -3,12 +3,20 @@
 //! This module defines the standard Molt commands.
 
 use crate::{
-    dict::{dict_new, dict_path_insert, dict_path_remove, list_to_dict},
+    dict::{
+        dict_new, dict_path_get, dict_path_insert, dict_path_remove, list_to_dict,
+        PathLookup,
+    },
+    format,
     interp::Interp,
+    list::{list_to_string, sort_list, SortMode, SortOptions},
+    scan,
     types::*,
-    util, *,
+    util::{self, StringUtils},
+    *,
 };
 use std::fs;
+use std::rc::Rc;
 cfg_if::cfg_if! {
   if #[cfg(feature = "wasm")] {
     use wasm_timer::Instant;
@@ -19,15 +27,21 @@ cfg_if::cfg_if! {
 
 pub const _APPEND: &str = "append";
 pub const _ARRAY: &str = "array";
+pub const _ASSERT_CODE: &str = "assert_code";
 pub const _ASSERT_EQ: &str = "assert_eq";
+pub const _ASSERT_ERR: &str = "assert_err";
 pub const _BREAK: &str = "break";
 pub const _CATCH: &str = "catch";
+pub const _CONCAT: &str = "concat";
 pub const _CONTINUE: &str = "continue";
 pub const _DICT: &str = "dict";
 pub const _ERROR: &str = "error";
+pub const _EVAL: &str = "eval";
 pub const _EXPR: &str = "expr";
+pub const _FLUSH: &str = "flush";
 pub const _FOR: &str = "for";
 pub const _FOREACH: &str = "foreach";
+pub const _FORMAT: &str = "format";
 pub const _GLOBAL: &str = "global";
 pub const _IF: &str = "if";
 pub const _INCR: &str = "incr";
@@ -35,17 +49,31 @@ pub const _INFO: &str = "info";
 pub const _JOIN: &str = "join";
 pub const _LAPPEND: &str = "lappend";
 pub const _LINDEX: &str = "lindex";
+pub const _LINSERT: &str = "linsert";
 pub const _LIST: &str = "list";
 pub const _LLENGTH: &str = "llength";
+pub const _LMAP: &str = "lmap";
+pub const _LRANGE: &str = "lrange";
+pub const _LREPLACE: &str = "lreplace";
+pub const _LREPEAT: &str = "lrepeat";
+pub const _LREVERSE: &str = "lreverse";
+pub const _LSEARCH: &str = "lsearch";
+pub const _LSORT: &str = "lsort";
 pub const _PROC: &str = "proc";
 pub const _PUTS: &str = "puts";
 pub const _RENAME: &str = "rename";
 pub const _RETURN: &str = "return";
+pub const _SCAN: &str = "scan";
 pub const _SET: &str = "set";
 pub const _STRING: &str = "string";
+pub const _SUBST: &str = "subst";
+pub const _SWITCH: &str = "switch";
+pub const _TAILCALL: &str = "tailcall";
 pub const _THROW: &str = "throw";
 pub const _TIME: &str = "time";
 pub const _UNSET: &str = "unset";
+pub const _UPLEVEL: &str = "uplevel";
+pub const _UPVAR: &str = "upvar";
 pub const _WHILE: &str = "while";
 pub const _SOURCE: &str = "source";
 pub const _EXIT: &str = "exit";
@@ -137,13 +165,20 @@ pub fn cmd_array_get<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResul
 /// # parse *script*
 ///
 /// A command for parsing an arbitrary script and outputting the parsed form.
-/// This is an undocumented debugging aid.  The output can be greatly improved.
+/// This is an undocumented debugging aid.  The output can be greatly improved.  The
+/// script's comments follow its parsed form, one per line.
 pub fn cmd_parse<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 2, 2, "script")?;
 
     let script = &argv[1];
+    let (script, comments) = parser::parse_with_comments(script.as_str())?;
 
-    molt_ok!(format!("{:?}", parser::parse(script.as_str())?))
+    // The comments follow the script, one per line.
+    let mut out = format!("{:?}", script);
+    for comment in comments {
+        out.push_str(&format!("\n{:?}", comment));
+    }
+    molt_ok!(out)
 }
 
 /// # array set arrayName list
@@ -187,7 +222,35 @@ pub fn cmd_array_unset<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltRes
     molt_ok!()
 }
 
-/// assert_eq received, expected
+/// # assert_code *script* *code*
+///
+/// Asserts that evaluating the script returns the given result code, e.g., `break` or
+/// `5`, and returns the script's result.
+/// See molt-book for full semantics.
+pub fn cmd_assert_code<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 3, 3, "script code")?;
+
+    let expected = ResultCode::from_value(&argv[2])?;
+    let result = interp.eval_value(&argv[1]);
+
+    let (code, value) = match &result {
+        Ok(value) => (ResultCode::Okay, value.clone()),
+        Err(exception) => (exception.code(), exception.value()),
+    };
+
+    if code.as_int() == expected.as_int() {
+        molt_ok!(value)
+    } else {
+        molt_err!(
+            "assert_code failed:\n  expected: {}\n  actual:   {}\n  result:   {}",
+            expected,
+            code,
+            quoted(&value)
+        )
+    }
+}
+
+/// # assert_eq *received* *expected*
 ///
 /// Asserts that two values have identical string representations.
 /// See molt-book for full semantics.
@@ -197,10 +260,60 @@ pub fn cmd_assert_eq<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResu
     if argv[1] == argv[2] {
         molt_ok!()
     } else {
-        molt_err!("assertion failed: received \"{}\", expected \"{}\".", argv[1], argv[2])
+        molt_err!(
+            "assert_eq failed:\n  expected: {}\n  actual:   {}",
+            quoted(&argv[2]),
+            quoted(&argv[1])
+        )
+    }
+}
+
+/// # assert_err *script* ?*pattern*?
+///
+/// Asserts that evaluating the script throws an error whose message matches the glob
+/// pattern, if given, and returns the error message.
+/// See molt-book for full semantics.
+pub fn cmd_assert_err<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 3, "script ?pattern?")?;
+
+    let message = match interp.eval_value(&argv[1]) {
+        Err(exception) if exception.is_error() => {
+            interp.set_global_error_data(exception.error_data());
+            exception.value()
+        }
+        Ok(value) => {
+            return molt_err!(
+                "assert_err failed: no error\n  result:   {}",
+                quoted(&value)
+            )
+        }
+        Err(exception) => {
+            return molt_err!(
+                "assert_err failed: no error\n  code:     {}\n  result:   {}",
+                exception.code(),
+                quoted(&exception.value())
+            )
+        }
+    };
+
+    match argv.get(2) {
+        Some(pattern) if !util::glob_match(pattern.as_str(), message.as_str()) => {
+            molt_err!(
+                "assert_err failed:\n  pattern:  {}\n  error:    {}",
+                quoted(pattern),
+                quoted(&message)
+            )
+        }
+        _ => molt_ok!(message),
     }
 }
 
+/// Formats a value as a list element, so that leading, trailing, and empty white space
+/// is visible in assertion messages.
+fn quoted(value: &Value) -> String {
+    list_to_string(std::slice::from_ref(value))
+}
+
 /// # break
 ///
 /// Breaks a loops.
@@ -222,6 +335,20 @@ pub fn cmd_catch<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     // If the script called `return x`, should get Return, -level 1, -code Okay here
     let result = interp.eval_value(&argv[1]);
 
+    // A cancellation may be beyond the script's reach.
+    if let Err(exception) = &result {
+        if !interp.catchable(exception) {
+            return result;
+        }
+    }
+
+    // Errors set errorInfo and errorCode even when caught.
+    if let Err(exception) = &result {
+        if exception.is_error() || exception.code() == ResultCode::Cancelled {
+            interp.set_global_error_data(exception.error_data());
+        }
+    }
+
     let (code, value) = match &result {
         Ok(val) => (0, val.clone()),
         Err(exception) => match exception.code() {
@@ -230,7 +357,10 @@ pub fn cmd_catch<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
             ResultCode::Return => (2, exception.value()),
             ResultCode::Break => (3, exception.value()),
             ResultCode::Continue => (4, exception.value()),
-            ResultCode::Other(_) => unimplemented!(), // TODO: Not in use yet
+            ResultCode::TailCall => unreachable!(), // Never catchable.
+            // Caught as an error.
+            ResultCode::Cancelled => (1, exception.value()),
+            ResultCode::Other(code) => (code, exception.value()),
         },
     };
 
@@ -245,6 +375,14 @@ pub fn cmd_catch<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     Ok(Value::from(code))
 }
 
+/// # concat ?*arg* ...?
+///
+/// Joins the arguments with single spaces, after trimming each of leading and trailing
+/// whitespace; see [`util::concat`].
+pub fn cmd_concat<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    molt_ok!(util::concat(&argv[1..]))
+}
+
 /// # continue
 ///
 /// Continues with the next iteration of the inmost loop.
@@ -267,18 +405,20 @@ pub fn cmd_dict<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     //                 ("append", " ", cmd_todo, "[TODO] dict append dictionaryVariable key ?string ...?"),
     //                 ("create", " ", cmd_dict_new,"dict create ?key value ...?"),
     //                 ("exists", " ", cmd_dict_exists,"dict exists dictionaryValue key ?key ...?"),
-    //                 ("filter", " ", cmd_todo, "[TODO] dict filter dictionaryValue filterType arg ?arg ...?"),
+    //                 ("filter", " ", cmd_dict_filter, "dict filter dictionaryValue filterType arg ?arg ...?"),
     //                 // dict filter dictionaryValue key ?globPattern ...?
     //                 // dict filter dictionaryValue script {keyVariable valueVariable} script
     //                 // dict filter dictionaryValue value ?globPattern ...?
     //                 ("for", "    ", cmd_todo, "[TODO] dict for {keyVariable valueVariable} dictionaryValue body"),
     //                 ("get", "    ", cmd_dict_get,"dict get dictionaryValue ?key ...?"),
+    //                 ("getdef", " ", cmd_dict_getdef,"dict getdef dictionaryValue ?key ...? key default"),
+    //                 ("getwithdefault", " ", cmd_dict_getdef,"dict getwithdefault dictionaryValue ?key ...? key default"),
     //                 ("incr", "   ", cmd_todo,"[TODO] dict incr dictionaryVariable key ?increment?"),
     //                 ("info", "   ", cmd_todo,"[TODO] dict info dictionaryValue"),
     //                 ("keys", "   ", cmd_dict_keys,"dict keys dictionaryValue ?globPattern?"),
     //                 ("lappend", "", cmd_todo,"[TODO] dict lappend dictionaryVariable key ?value ...?"),
-    //                 ("map", "    ", cmd_todo,"[TODO] dict map {keyVariable valueVariable} dictionaryValue body"),
-    //                 ("merge", "  ", cmd_todo,"[TODO] dict merge ?dictionaryValue ...?"),
+    //                 ("map", "    ", cmd_dict_map,"dict map {keyVariable valueVariable} dictionaryValue body"),
+    //                 ("merge", "  ", cmd_dict_merge,"dict merge ?dictionaryValue ...?"),
     //                 ("remove", " ", cmd_dict_remove,"dict remove dictionaryValue ?key ...?"),
     //                 ("replace", "", cmd_todo,"[TODO] dict replace dictionaryValue ?key value ...?"),
     //                 ("set", "    ", cmd_dict_set,"dict set dictionaryVariable key ?key ...? value"),
@@ -295,13 +435,21 @@ pub fn cmd_dict<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
         [
             ("create", cmd_dict_new),
             ("exists", cmd_dict_exists),
+            ("filter", cmd_dict_filter),
+            ("for", cmd_dict_for),
             ("get", cmd_dict_get),
+            ("getdef", cmd_dict_getdef),
+            ("getwithdefault", cmd_dict_getdef),
             ("keys", cmd_dict_keys),
+            ("map", cmd_dict_map),
+            ("merge", cmd_dict_merge),
             ("remove", cmd_dict_remove),
             ("set", cmd_dict_set),
             ("size", cmd_dict_size),
             ("unset", cmd_dict_unset),
+            ("update", cmd_dict_update),
             ("values", cmd_dict_values),
+            ("with", cmd_dict_with),
         ],
     );
 
@@ -330,57 +478,221 @@ fn cmd_dict_new<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
 }
 
 /// # dict exists *dictionary* key ?*key* ...?
+///
+/// A value along the path that isn't a dictionary simply means the key doesn't exist.
 fn cmd_dict_exists<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 4, 0, "dictionary key ?key ...?")?;
 
-    let mut value: Value = argv[2].clone();
-    let indices = &argv[3..];
+    let found = matches!(dict_path_get(&argv[2], &argv[3..]), Ok(PathLookup::Found(_)));
+    molt_ok!(found)
+}
 
-    for index in indices {
-        if let Ok(dict) = value.as_dict() {
-            if let Some(val) = dict.get(index) {
-                value = val.clone();
-            } else {
-                return molt_ok!(false);
+/// # dict filter *dictionary* *filterType* *arg* ?*arg* ...?
+///
+/// Returns the entries of the dictionary selected by the filter, in insertion order.
+/// `key` and `value` select the entries whose key or value matches any of the glob
+/// patterns; `script` evaluates the body for each entry and keeps those for which it
+/// returns true.
+fn cmd_dict_filter<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "dictionary filterType ?arg ...?")?;
+
+    let dict = argv[2].as_dict()?;
+
+    match argv[3].as_str() {
+        "key" => {
+            let patterns = &argv[4..];
+            let filtered: MoltDict = dict
+                .iter()
+                .filter(|(key, _)| {
+                    patterns.iter().any(|p| util::glob_match(p.as_str(), key.as_str()))
+                })
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            molt_ok!(filtered)
+        }
+        "value" => {
+            let patterns = &argv[4..];
+            let filtered: MoltDict = dict
+                .iter()
+                .filter(|(_, value)| {
+                    patterns.iter().any(|p| util::glob_match(p.as_str(), value.as_str()))
+                })
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            molt_ok!(filtered)
+        }
+        "script" => {
+            check_args(
+                2,
+                argv,
+                6,
+                6,
+                "dictionary script {keyVariable valueVariable} script",
+            )?;
+            let (key_var, value_var) = dict_loop_vars(&argv[4])?;
+            let body = argv[5].as_script()?;
+            let mut filtered = dict_new();
+
+            for (key, value) in dict.iter() {
+                interp.set_var(&key_var, key.clone())?;
+                interp.set_var(&value_var, value.clone())?;
+
+                match interp.eval_parsed(&body) {
+                    Ok(flag) => {
+                        if flag.as_bool()? {
+                            filtered.insert(key.clone(), value.clone());
+                        }
+                    }
+                    Err(exception) => match exception.code() {
+                        ResultCode::Break => break,
+                        ResultCode::Continue => (),
+                        _ => return Err(exception),
+                    },
+                }
+            }
+            molt_ok!(filtered)
+        }
+        filter_type => {
+            molt_err!("bad filterType \"{}\": must be key, script, or value", filter_type)
+        }
+    }
+}
+
+/// # dict for {*keyVar* *valueVar*} *dictionary* *body*
+///
+/// Evaluates the body for each entry of the dictionary, in insertion order, with the
+/// key and value assigned to the variables.  `break` and `continue` work as they do in
+/// `foreach`.
+fn cmd_dict_for<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 5, 5, "{keyVariable valueVariable} dictionary script")?;
+
+    let (key_var, value_var) = dict_loop_vars(&argv[2])?;
+    let dict = argv[3].as_dict()?;
+    let body = argv[4].as_script()?;
+
+    for (key, value) in dict.iter() {
+        interp.set_var(&key_var, key.clone())?;
+        interp.set_var(&value_var, value.clone())?;
+
+        if let Err(exception) = interp.eval_parsed(&body) {
+            match exception.code() {
+                ResultCode::Break => break,
+                ResultCode::Continue => (),
+                _ => return Err(exception),
             }
-        } else {
-            return molt_ok!(false);
         }
     }
 
-    molt_ok!(true)
+    molt_ok!()
 }
 
 /// # dict get *dictionary* ?*key* ...?
 fn cmd_dict_get<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 3, 0, "dictionary ?key ...?")?;
 
-    let mut value: Value = argv[2].clone();
-    let indices = &argv[3..];
+    match dict_path_get(&argv[2], &argv[3..])? {
+        PathLookup::Found(value) => molt_ok!(value),
+        PathLookup::Missing(key) => molt_err!("key \"{}\" not known in dictionary", key),
+    }
+}
 
-    for index in indices {
-        let dict = value.as_dict()?;
+/// # dict getdef *dictionary* ?*key* ...? *key* *default*
+///
+/// Like `dict get`, but returns the default if a key along the path is missing.  It's
+/// still an error if a value along the path isn't a dictionary.  Also available as
+/// `dict getwithdefault`.
+fn cmd_dict_getdef<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 5, 0, "dictionary ?key ...? key default")?;
 
-        if let Some(val) = dict.get(index) {
-            value = val.clone();
-        } else {
-            return molt_err!("key \"{}\" not known in dictionary", index);
-        }
-    }
+    let default = &argv[argv.len() - 1];
+    let keys = &argv[3..(argv.len() - 1)];
 
-    molt_ok!(value)
+    match dict_path_get(&argv[2], keys)? {
+        PathLookup::Found(value) => molt_ok!(value),
+        PathLookup::Missing(_) => molt_ok!(default.clone()),
+    }
 }
 
-/// # dict keys *dictionary*
-/// TODO: Add filtering when we have glob matching.
+/// # dict keys *dictionary* ?*pattern*?
+///
+/// Returns the keys in insertion order, optionally only those matching the glob pattern.
 fn cmd_dict_keys<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(2, argv, 3, 3, "dictionary")?;
+    check_args(2, argv, 3, 4, "dictionary ?pattern?")?;
 
     let dict = argv[2].as_dict()?;
-    let keys: MoltList = dict.keys().cloned().collect();
+    let keys: MoltList = match argv.get(3) {
+        Some(pattern) => dict
+            .keys()
+            .filter(|key| util::glob_match(pattern.as_str(), key.as_str()))
+            .cloned()
+            .collect(),
+        None => dict.keys().cloned().collect(),
+    };
     molt_ok!(keys)
 }
 
+/// # dict map {*keyVar* *valueVar*} *dictionary* *body*
+///
+/// Evaluates the body for each entry of the dictionary, and returns a new dictionary
+/// mapping each key to the body's result.  `continue` omits the entry; `break` stops
+/// the iteration and returns the entries mapped so far.
+fn cmd_dict_map<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 5, 5, "{keyVariable valueVariable} dictionary script")?;
+
+    let (key_var, value_var) = dict_loop_vars(&argv[2])?;
+    let dict = argv[3].as_dict()?;
+    let body = argv[4].as_script()?;
+    let mut mapped = dict_new();
+
+    for (key, value) in dict.iter() {
+        interp.set_var(&key_var, key.clone())?;
+        interp.set_var(&value_var, value.clone())?;
+
+        match interp.eval_parsed(&body) {
+            Ok(new_value) => {
+                mapped.insert(key.clone(), new_value);
+            }
+            Err(exception) => match exception.code() {
+                ResultCode::Break => break,
+                ResultCode::Continue => (),
+                _ => return Err(exception),
+            },
+        }
+    }
+
+    molt_ok!(mapped)
+}
+
+/// # dict merge ?*dictionary* ...?
+///
+/// Merges the dictionaries left to right.  A key keeps the position of its first
+/// occurrence, and takes the value of its last.
+fn cmd_dict_merge<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 0, "?dictionary ...?")?;
+
+    let mut merged = dict_new();
+
+    for dict_val in &argv[2..] {
+        for (key, value) in dict_val.as_dict()?.iter() {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    molt_ok!(merged)
+}
+
+/// Gets the key and value variable names from a `dict filter script` or
+/// `dict map` variable list.
+fn dict_loop_vars(var_list: &Value) -> Result<(Value, Value), Exception> {
+    let vars = var_list.as_list()?;
+
+    if vars.len() != 2 {
+        return molt_err!("must have exactly two variable names");
+    }
+
+    Ok((vars[0].clone(), vars[1].clone()))
+}
+
 /// # dict remove *dictionary* ?*key* ...?
 fn cmd_dict_remove<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 3, 0, "dictionary ?key ...?")?;
@@ -435,27 +747,166 @@ fn cmd_dict_unset<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     }
 }
 
-/// # dict values *dictionary*
-/// TODO: Add filtering when we have glob matching.
+/// # dict update *dictVarName* *key* *varName* ?*key* *varName* ...? *body*
+///
+/// Sets each variable to the value of its key in the dictionary, unsetting it if the
+/// key is missing, and evaluates the body.  Then, even if the body fails, puts the
+/// variables back into the dictionary, removing the keys whose variables are unset,
+/// unless the body unset the dictionary variable itself.  Returns the body's result.
+fn cmd_dict_update<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "dictVarName key varName ?key varName ...? script";
+    check_args(2, argv, 6, 0, USAGE)?;
+
+    if !argv.len().is_multiple_of(2) {
+        return molt_err!(
+            "wrong # args: should be \"{} {}\"",
+            Value::from(&argv[0..2]),
+            USAGE
+        );
+    }
+
+    let dict_var = &argv[2];
+    let pairs = &argv[3..(argv.len() - 1)];
+    let body = &argv[argv.len() - 1];
+
+    let dict = interp.var(dict_var)?.as_dict()?;
+    for pair in pairs.chunks(2) {
+        match dict.get(&pair[0]) {
+            Some(value) => interp.set_var(&pair[1], value.clone())?,
+            None => interp.unset_var(&pair[1]),
+        }
+    }
+
+    let result = interp.eval_value(body);
+
+    if interp.var_exists(dict_var) {
+        let mut dict_val = interp.var(dict_var)?;
+        for pair in pairs.chunks(2) {
+            dict_val = if interp.var_exists(&pair[1]) {
+                dict_path_insert(&dict_val, &pair[0..1], &interp.var(&pair[1])?)?
+            } else {
+                dict_path_remove(&dict_val, &pair[0..1])?
+            };
+        }
+        interp.set_var(dict_var, dict_val)?;
+    }
+
+    result
+}
+
+/// # dict values *dictionary* ?*pattern*?
+///
+/// Returns the values in insertion order, optionally only those matching the glob
+/// pattern.
 fn cmd_dict_values<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(2, argv, 3, 3, "dictionary")?;
+    check_args(2, argv, 3, 4, "dictionary ?pattern?")?;
 
     let dict = argv[2].as_dict()?;
-    let values: MoltList = dict.values().cloned().collect();
+    let values: MoltList = match argv.get(3) {
+        Some(pattern) => dict
+            .values()
+            .filter(|value| util::glob_match(pattern.as_str(), value.as_str()))
+            .cloned()
+            .collect(),
+        None => dict.values().cloned().collect(),
+    };
     molt_ok!(values)
 }
 
-/// error *message*
+/// # dict with *dictVarName* ?*key* ...? *body*
 ///
-/// Returns an error with the given message.
-///
-/// ## TCL Liens
+/// Sets a variable for each key of the dictionary, or of the dictionary nested in it
+/// at the end of the path of keys, to the key's value, and evaluates the body.  Then,
+/// even if the body fails, puts the variables back into the dictionary as `dict update`
+/// does.  Returns the body's result.
+fn cmd_dict_with<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "dictVarName ?key ...? script")?;
+
+    let dict_var = &argv[2];
+    let keys = &argv[3..(argv.len() - 1)];
+    let body = &argv[argv.len() - 1];
+
+    let dict = match dict_path_get(&interp.var(dict_var)?, keys)? {
+        PathLookup::Found(value) => value.as_dict()?,
+        PathLookup::Missing(key) => {
+            return molt_err!("key \"{}\" not known in dictionary", key)
+        }
+    };
+    for (key, value) in dict.iter() {
+        interp.set_var(key, value.clone())?;
+    }
+
+    let result = interp.eval_value(body);
+
+    if interp.var_exists(dict_var) {
+        let dict_val = interp.var(dict_var)?;
+        let mut inner = match dict_path_get(&dict_val, keys)? {
+            PathLookup::Found(value) => value,
+            PathLookup::Missing(_) => Value::from(dict_new()),
+        };
+        for key in dict.keys() {
+            let key = std::slice::from_ref(key);
+            inner = if interp.var_exists(&key[0]) {
+                dict_path_insert(&inner, key, &interp.var(&key[0])?)?
+            } else {
+                dict_path_remove(&inner, key)?
+            };
+        }
+
+        if keys.is_empty() {
+            interp.set_var(dict_var, inner)?;
+        } else {
+            interp.set_var(dict_var, dict_path_insert(&dict_val, keys, &inner)?)?;
+        }
+    }
+
+    result
+}
+
+/// error *message* ?*info*? ?*code*?
 ///
-/// * In Standard TCL, `error` can optionally set the stack trace and an error code.
+/// Returns an error with the given message.  If *info* is given and non-empty, the
+/// error's stack trace (`errorInfo`) begins with it rather than with the message; if
+/// *code* is given, it is the error code (`errorCode`), and defaults to `NONE`.  The
+/// code is conventionally a list, and is stored as-is.
 pub fn cmd_error<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 2, 2, "message")?;
+    check_args(1, argv, 2, 4, "message ?info? ?code?")?;
+
+    let error_code = argv.get(3).cloned();
+
+    match argv.get(2) {
+        Some(info) if !info.as_str().is_empty() => Err(Exception::molt_return_err(
+            argv[1].clone(),
+            0,
+            error_code,
+            Some(info.clone()),
+        )),
+        _ => Err(Exception::molt_err2(
+            error_code.unwrap_or_else(|| Value::from("NONE")),
+            argv[1].clone(),
+        )),
+    }
+}
+
+/// # eval *arg* ?*arg* ...?
+///
+/// Concatenates the arguments as `concat` does, and evaluates the result as a script.
+pub fn cmd_eval<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "arg ?arg ...?")?;
+
+    // A single argument is evaluated as is, keeping its parsed script.
+    let result = if argv.len() == 2 {
+        interp.eval_value(&argv[1])
+    } else {
+        interp.eval_value(&util::concat(&argv[1..]))
+    };
 
-    molt_err!(argv[1].clone())
+    result.map_err(|mut exception| {
+        if exception.is_error() {
+            exception.add_error_info("    (while evaluating eval'd script)");
+        }
+        exception
+    })
 }
 
 /// # exit ?*returnCode*?
@@ -471,53 +922,59 @@ pub fn cmd_exit<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     std::process::exit(return_code as i32)
 }
 
-/// # expr expr
+/// # expr *arg* ?*arg* ...?
 ///
-/// Evaluates an expression and returns its result.
+/// Evaluates an expression and returns its result.  Multiple arguments are concatenated,
+/// as by `concat`, into a single expression; a single argument is evaluated as is.
 ///
 /// ## TCL Liens
 ///
 /// See the Molt Book.
 
 pub fn cmd_expr<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 2, 0, "expr")?;
+    check_args(1, argv, 2, 0, "arg ?arg ...?")?;
 
     if argv.len() == 2 {
         interp.expr(&argv[1])
     } else {
-        let values = argv[1..].iter().map(|v| v.as_str()).collect::<Vec<_>>();
-        interp.expr(&Value::from(values.join(" ")))
+        interp.expr(&util::concat(&argv[1..]))
     }
 }
 
 /// # for *start* *test* *next* *command*
 ///
-/// A standard "for" loop.  start, next, and command are scripts; test is an expression
-///
+/// A standard "for" loop.  start, next, and command are scripts; test is an expression.
+/// Returns the empty string.
 pub fn cmd_for<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 5, 5, "start test next command")?;
 
     let start = &argv[1];
     let test = &argv[2];
-    let next = &argv[3];
-    let command = &argv[4];
+    let next = argv[3].as_script()?;
+    let command = argv[4].as_script()?;
 
     // Start
     interp.eval_value(start)?;
 
     while interp.expr_bool(test)? {
-        let result = interp.eval_value(command);
-
-        if let Err(exception) = result {
-            match exception.code() {
-                ResultCode::Break => break,
-                ResultCode::Continue => (),
-                _ => return Err(exception),
+        if !command.commands().is_empty() {
+            let result = interp.eval_parsed(&command);
+
+            if let Err(exception) = result {
+                match exception.code() {
+                    ResultCode::Break => break,
+                    ResultCode::Continue => (),
+                    _ => return Err(exception),
+                }
             }
         }
 
-        // Execute next script.  Break is allowed, but continue is not.
-        let result = interp.eval_value(next);
+        // Execute next script, even after a continue.  Break is allowed, but continue
+        // is not.
+        if next.commands().is_empty() {
+            continue;
+        }
+        let result = interp.eval_parsed(&next);
 
         if let Err(exception) = result {
             match exception.code() {
@@ -539,6 +996,7 @@ pub fn cmd_for<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
 /// *varList* and calling the *body* as a script once for each set of assignments.
 /// On the last iteration, the second and subsequents variables in the *varList* will
 /// be assigned the empty string if there are not enough list elements to fill them.
+/// Returns the empty string.
 ///
 /// ## TCL Liens
 ///
@@ -548,7 +1006,7 @@ pub fn cmd_foreach<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
 
     let var_list = &*argv[1].as_list()?;
     let list = &*argv[2].as_list()?;
-    let body = &argv[3];
+    let body = argv[3].as_script()?;
 
     let mut i = 0;
 
@@ -562,7 +1020,10 @@ pub fn cmd_foreach<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
             }
         }
 
-        let result = interp.eval_value(body);
+        if body.commands().is_empty() {
+            continue;
+        }
+        let result = interp.eval_parsed(&body);
 
         if let Err(exception) = result {
             match exception.code() {
@@ -576,6 +1037,16 @@ pub fn cmd_foreach<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
     molt_ok!()
 }
 
+/// # format *formatString* ?*arg* ...?
+///
+/// Formats the arguments as C's `sprintf` does, returning the result; see the `format`
+/// module for the conversions supported.
+pub fn cmd_format<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "formatString ?arg ...?")?;
+
+    molt_ok!(format::format(argv[1].as_str(), &argv[2..])?)
+}
+
 /// # global ?*varName* ...?
 ///
 /// Appends any number of values to a variable's value, which need not
@@ -587,7 +1058,7 @@ pub fn cmd_global<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     if interp.scope_level() > 0 {
         for name in &argv[1..] {
             // TODO: Should upvar take the name as a Value?
-            interp.upvar(0, name.as_str());
+            interp.upvar(0, name.as_str())?;
         }
     }
     molt_ok!()
@@ -698,21 +1169,26 @@ pub fn cmd_if<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     }
 }
 
-/// # incr *varName* ?*increment* ...?
+/// # incr *varName* ?*increment*?
 ///
-/// Increments an integer variable by a value.
+/// Increments an integer variable by a value, 1 by default, and returns the new value.
+/// A variable or array element that doesn't exist is created with the value 0 before
+/// being incremented.  A result too large for an integer is an error.
 pub fn cmd_incr<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 2, 3, "varName ?increment?")?;
 
     let increment: MoltInt = if argv.len() == 3 { argv[2].as_int()? } else { 1 };
 
-    let new_value = increment
-        + interp
-            .var(&argv[1])
-            .and_then(|val| Ok(val.as_int()?))
-            .unwrap_or_else(|_| 0);
+    let old_value =
+        if interp.var_exists(&argv[1]) { interp.var(&argv[1])?.as_int()? } else { 0 };
+
+    let Some(new_value) = old_value.checked_add(increment) else {
+        return molt_err!("integer overflow");
+    };
 
-    interp.set_var_return(&argv[1], new_value.into())
+    // The new value is built directly from the integer, so it has no string rep until
+    // one is needed.
+    interp.set_var_return(&argv[1], Value::from(new_value))
 }
 
 /// # info *subcommand* ?*arg*...?
@@ -728,8 +1204,13 @@ pub fn cmd_info<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
             ("default", cmd_info_default),
             ("exists", cmd_info_exists),
             ("globals", cmd_info_globals),
+            ("hostname", cmd_info_hostname),
             ("locals", cmd_info_locals),
+            ("nameofexecutable", cmd_info_nameofexecutable),
+            ("patchlevel", cmd_info_patchlevel),
             ("procs", cmd_info_procs),
+            ("script", cmd_info_script),
+            ("tclversion", cmd_info_tclversion),
             ("vars", cmd_info_vars),
         ],
     );
@@ -755,8 +1236,12 @@ pub fn cmd_info_cmdtype<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltRe
 }
 
 /// # info commands ?*pattern*?
-pub fn cmd_info_commands<Ctx>(interp: &mut Interp<Ctx>, _argv: &[Value]) -> MoltResult {
-    molt_ok!(Value::from(interp.command_names()))
+///
+/// Returns the sorted names of the commands, optionally only those matching the glob
+/// pattern.
+pub fn cmd_info_commands<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 3, "?pattern?")?;
+    molt_ok!(filter_names(interp.command_names(), argv.get(2)))
 }
 
 /// # info default *procname* *arg* *varname*
@@ -795,15 +1280,103 @@ pub fn cmd_info_globals<Ctx>(interp: &mut Interp<Ctx>, _argv: &[Value]) -> MoltR
     molt_ok!(Value::from(interp.vars_in_global_scope()))
 }
 
+/// # info hostname
+///
+/// Returns the name of the host, or the empty string if it can't be determined.  Hidden
+/// in safe interpreters.
+pub fn cmd_info_hostname<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    check_not_safe(interp, argv)?;
+    molt_ok!(hostname().unwrap_or_default())
+}
+
+/// Throws an error if the interpreter is safe, for the subcommands that reveal
+/// information about the host, as standard TCL hides them in safe interpreters.
+fn check_not_safe<Ctx>(interp: &Interp<Ctx>, argv: &[Value]) -> Result<(), Exception> {
+    if interp.is_safe() {
+        return molt_err!("not allowed to invoke subcommand {} of {}", argv[1], argv[0]);
+    }
+    Ok(())
+}
+
+/// Looks up the host name without resorting to platform APIs.
+fn hostname() -> Option<String> {
+    let from_file = |path| {
+        fs::read_to_string(path)
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    };
+
+    from_file("/proc/sys/kernel/hostname")
+        .or_else(|| from_file("/etc/hostname"))
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+}
+
 /// # info locals
 /// TODO: Add glob matching as a feature, and provide optional pattern argument.
 pub fn cmd_info_locals<Ctx>(interp: &mut Interp<Ctx>, _argv: &[Value]) -> MoltResult {
     molt_ok!(Value::from(interp.vars_in_local_scope()))
 }
 
+/// # info nameofexecutable
+///
+/// Returns the path of the running executable, or the empty string if it can't be
+/// determined.  Hidden in safe interpreters.
+pub fn cmd_info_nameofexecutable<Ctx>(
+    interp: &mut Interp<Ctx>,
+    argv: &[Value],
+) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    check_not_safe(interp, argv)?;
+
+    match std::env::current_exe() {
+        Ok(path) => molt_ok!(path.to_string_lossy().to_string()),
+        Err(_) => molt_ok!(),
+    }
+}
+
+/// # info patchlevel
+///
+/// Returns the interpreter's patch level; see `Interp::set_tcl_version_strings`.
+pub fn cmd_info_patchlevel<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(interp.tcl_version_strings().0)
+}
+
 /// # info procs ?*pattern*?
-pub fn cmd_info_procs<Ctx>(interp: &mut Interp<Ctx>, _argv: &[Value]) -> MoltResult {
-    molt_ok!(Value::from(interp.proc_names()))
+///
+/// Returns the sorted names of the procedures, optionally only those matching the glob
+/// pattern.
+pub fn cmd_info_procs<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 3, "?pattern?")?;
+    molt_ok!(filter_names(interp.proc_names(), argv.get(2)))
+}
+
+/// Keeps the names that match the glob pattern, if there is one.
+fn filter_names(mut names: MoltList, pattern: Option<&Value>) -> MoltList {
+    if let Some(pattern) = pattern {
+        names.retain(|name| util::glob_match(pattern.as_str(), name.as_str()));
+    }
+    names
+}
+
+/// # info script
+///
+/// Returns the path of the innermost file being sourced, or the empty string.
+pub fn cmd_info_script<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(interp.script_file().unwrap_or_default())
+}
+
+/// # info tclversion
+///
+/// Returns the version of Tcl the interpreter claims to be; see
+/// `Interp::set_tcl_version_strings`.
+pub fn cmd_info_tclversion<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(interp.tcl_version_strings().1)
 }
 
 /// # info vars
@@ -835,16 +1408,12 @@ pub fn cmd_join<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
 pub fn cmd_lappend<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 2, 0, "varName ?value ...?")?;
 
-    let var_result = interp.var(&argv[1]);
-
-    let mut list: MoltList = if var_result.is_ok() {
-        var_result.expect("got value").to_list()?
-    } else {
-        Vec::new()
+    let mut list: MoltList = match interp.var(&argv[1]) {
+        Ok(value) => value.to_list()?,
+        Err(_) => Vec::new(),
     };
 
-    let mut values = argv[2..].to_owned();
-    list.append(&mut values);
+    list.extend(argv[2..].iter().cloned());
     interp.set_var_return(&argv[1], Value::from(list))
 }
 
@@ -866,18 +1435,36 @@ pub fn lindex_into(list: &Value, indices: &[Value]) -> MoltResult {
 
     for index_val in indices {
         let list = value.as_list()?;
-        let index = index_val.as_int()?;
+        let index = parse_index(index_val, list.len())?;
 
-        value = if index < 0 || index as usize >= list.len() {
-            Value::empty()
-        } else {
-            list[index as usize].clone()
+        value = match usize::try_from(index).ok().and_then(|i| list.get(i)) {
+            Some(element) => element.clone(),
+            None => Value::empty(),
         };
     }
 
     molt_ok!(value)
 }
 
+/// # linsert *list* *index* ?*element* ...?
+///
+/// Returns a copy of the list with the elements inserted before the element at the
+/// index.  An index of `end` inserts them after the last element, `end-1` before it, and
+/// so on; indices out of range insert them at the beginning or the end.
+pub fn cmd_linsert<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 3, 0, "list index ?element ...?")?;
+
+    let list = argv[1].as_list()?;
+    let index = parse_index(&argv[2], list.len() + 1)?.clamp(0, list.len() as MoltInt);
+
+    let mut result: MoltList = Vec::with_capacity(list.len() + argv.len() - 3);
+    result.extend_from_slice(&list[..index as usize]);
+    result.extend_from_slice(&argv[3..]);
+    result.extend_from_slice(&list[index as usize..]);
+
+    molt_ok!(result)
+}
+
 /// # list ?*arg*...?
 ///
 /// Converts its arguments into a canonical list.
@@ -895,79 +1482,413 @@ pub fn cmd_llength<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
     molt_ok!(argv[1].as_list()?.len() as MoltInt)
 }
 
-/// # pdump
+/// # lmap *varList* *list* ?*varList* *list* ...? *body*
 ///
-/// Dumps profile data.  Developer use only.
-pub fn cmd_pdump<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 1, 1, "")?;
-
-    interp.profile_dump();
+/// Loops like `foreach`, but collects the result of each execution of the *body* into a
+/// list, which is returned.  If the *body* executes `continue` the iteration contributes
+/// nothing to the result; if it executes `break`, the loop stops and the items collected
+/// so far are returned.
+///
+/// Each *varList* is paired with the *list* that follows it.  The number of iterations is
+/// set by the pair that needs the most; variables of exhausted lists are assigned the
+/// empty string.
+pub fn cmd_lmap<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 4, 0, "varList list ?varList list ...? command")?;
 
-    molt_ok!()
-}
+    if !argv.len().is_multiple_of(2) {
+        return molt_err!(
+            "wrong # args: should be \"{} varList list ?varList list ...? command\"",
+            argv[0]
+        );
+    }
 
-/// # pclear
-///
-/// Clears profile data.  Developer use only.
-pub fn cmd_pclear<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 1, 1, "")?;
+    let body = &argv[argv.len() - 1];
+    let mut pairs: Vec<(Rc<MoltList>, Rc<MoltList>)> = Vec::new();
+    let mut iterations = 0;
 
-    interp.profile_clear();
+    for pair in argv[1..argv.len() - 1].chunks(2) {
+        let var_list = pair[0].as_list()?;
+        let list = pair[1].as_list()?;
 
-    molt_ok!()
-}
+        if var_list.is_empty() {
+            return molt_err!("lmap varlist is empty");
+        }
 
-/// # proc *name* *args* *body*
-///
-/// Defines a procedure.
-pub fn cmd_proc<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 4, 4, "name args body")?;
+        iterations = iterations.max(list.len().div_ceil(var_list.len()));
+        pairs.push((var_list, list));
+    }
 
-    // FIRST, get the arguments
-    let name = argv[1].as_str();
-    let args = &*argv[2].as_list()?;
+    let mut result: MoltList = Vec::new();
 
-    // NEXT, validate the argument specs
-    for arg in args {
-        let vec = arg.as_list()?;
+    for i in 0..iterations {
+        for (var_list, list) in &pairs {
+            for (j, var) in var_list.iter().enumerate() {
+                let value = list
+                    .get(i * var_list.len() + j)
+                    .cloned()
+                    .unwrap_or_else(Value::empty);
+                interp.set_var(var, value)?;
+            }
+        }
 
-        if vec.is_empty() {
-            return molt_err!("argument with no name");
-        } else if vec.len() > 2 {
-            return molt_err!("too many fields in argument specifier \"{}\"", arg);
+        match interp.eval_value(body) {
+            Ok(value) => result.push(value),
+            Err(exception) => match exception.code() {
+                ResultCode::Break => break,
+                ResultCode::Continue => (),
+                _ => return Err(exception),
+            },
         }
     }
 
-    // NEXT, add the command.
-    interp.add_proc(name, args, &argv[3]);
-
-    molt_ok!()
+    molt_ok!(result)
 }
 
-/// # puts *string*
+/// # lrange *list* *first* *last*
 ///
-/// Outputs the string to stdout.
-///
-/// ## TCL Liens
+/// Returns the elements of the list from index *first* through index *last*; indices
+/// out of range are clamped to the list.
+pub fn cmd_lrange<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 4, 4, "list first last")?;
+
+    let list = argv[1].as_list()?;
+    let first = parse_index(&argv[2], list.len())?.max(0);
+    let last = parse_index(&argv[3], list.len())?.min(list.len() as MoltInt - 1);
+
+    if last < first {
+        return molt_ok!();
+    }
+
+    molt_ok!(&list[first as usize..=last as usize])
+}
+
+/// # lreplace *list* *first* *last* ?*element* ...?
 ///
-/// * Does not support `-nonewline`
-/// * Does not support `channelId`
-pub fn cmd_puts<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(1, argv, 2, 2, "string")?;
-    cfg_if::cfg_if! {
-      if #[cfg(feature = "std_buff")] {
-        interp.std_buff.push(Ok(argv[1].clone()));
-      } else {
-        println!("{}", argv[1]);
-      }
+/// Returns a copy of the list with the elements from index *first* through index *last*
+/// replaced by the given elements.  If *last* is less than *first*, no elements are
+/// deleted and the new ones are inserted before *first*; if *first* is past the end of
+/// the list, they are appended.
+pub fn cmd_lreplace<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 4, 0, "list first last ?element ...?")?;
+
+    let list = argv[1].as_list()?;
+    let len = list.len() as MoltInt;
+    let first = parse_index(&argv[2], list.len())?.clamp(0, len);
+    let last = parse_index(&argv[3], list.len())?.min(len - 1);
+    let end = if last < first { first } else { last + 1 };
+
+    let mut result: MoltList = list[..first as usize].to_vec();
+    result.extend_from_slice(&argv[4..]);
+    result.extend_from_slice(&list[end as usize..]);
+
+    molt_ok!(result)
+}
+
+/// # lrepeat *count* ?*element* ...?
+///
+/// Returns a list consisting of *count* repetitions of the *element*s.
+pub fn cmd_lrepeat<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "count ?value ...?")?;
+
+    let count = argv[1].as_int()?;
+
+    if count < 0 {
+        return molt_err!("bad count \"{}\": must be integer >= 0", count);
+    }
+
+    let elements = &argv[2..];
+
+    if elements.is_empty() {
+        return molt_ok!();
+    }
+
+    let mut result: MoltList = Vec::new();
+    let reserved = match (count as usize).checked_mul(elements.len()) {
+        Some(len) => result.try_reserve_exact(len).is_ok(),
+        None => false,
+    };
+
+    if !reserved {
+        return molt_err!("max length exceeded");
+    }
+
+    for _ in 0..count {
+        result.extend_from_slice(elements);
+    }
+
+    molt_ok!(result)
+}
+
+/// # lreverse *list*
+///
+/// Returns the elements of the list in reverse order.
+pub fn cmd_lreverse<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 2, "list")?;
+
+    molt_ok!(argv[1].as_list()?.iter().rev().cloned().collect::<MoltList>())
+}
+
+/// # lsearch ?*options*? *list* *pattern*
+///
+/// Returns the index of the first element of the list that matches the pattern, or -1
+/// if there is none.  The options are:
+///
+/// * `-glob`, the default, `-exact`: how elements are matched; `-glob` matches as
+///   `string match`.
+/// * `-all`: returns a list of the indices of all matching elements.
+/// * `-inline`: returns the matching element, or the empty string, rather than its
+///   index; with `-all`, returns a list of the matching elements.
+/// * `-not`: finds the elements that don't match.
+/// * `-start index`: starts the search at the index, which may be `end` or `end-N`.
+///
+/// ## TCL Liens
+///
+/// * The `-regexp`, `-sorted`, `-nocase`, `-index`, and comparison type options
+///   (e.g., `-integer`) aren't supported.
+pub fn cmd_lsearch<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 3, 0, "?-option value ...? list pattern")?;
+
+    // FIRST, get the options.
+    let mut glob = true;
+    let mut all = false;
+    let mut inline = false;
+    let mut not = false;
+    let mut start = None;
+    let mut argi = 1;
+
+    while argi < argv.len() - 2 {
+        match argv[argi].as_str() {
+            "-exact" => glob = false,
+            "-glob" => glob = true,
+            "-all" => all = true,
+            "-inline" => inline = true,
+            "-not" => not = true,
+            "-start" => {
+                if argi + 3 >= argv.len() {
+                    return molt_err!("missing starting index");
+                }
+                argi += 1;
+                start = Some(&argv[argi]);
+            }
+            opt => {
+                return molt_err!(
+                    "bad option \"{}\": must be -all, -exact, -glob, -inline, -not, or -start",
+                    opt
+                );
+            }
+        }
+        argi += 1;
     }
+
+    // NEXT, search the list.
+    let list = argv[argv.len() - 2].as_list()?;
+    let pattern = argv[argv.len() - 1].as_str();
+    let start = match start {
+        Some(index) => parse_index(index, list.len())?.max(0) as usize,
+        None => 0,
+    };
+
+    let mut matches = list.iter().enumerate().skip(start).filter(|(_, item)| {
+        let matched = if glob {
+            util::glob_match(pattern, item.as_str())
+        } else {
+            pattern == item.as_str()
+        };
+        matched != not
+    });
+
+    if all {
+        if inline {
+            molt_ok!(matches.map(|(_, item)| item.clone()).collect::<MoltList>())
+        } else {
+            molt_ok!(matches
+                .map(|(i, _)| Value::from(i as MoltInt))
+                .collect::<MoltList>())
+        }
+    } else {
+        match matches.next() {
+            Some((_, item)) if inline => molt_ok!(item.clone()),
+            Some((i, _)) => molt_ok!(i as MoltInt),
+            None if inline => molt_ok!(),
+            None => molt_ok!(-1),
+        }
+    }
+}
+
+/// # lsort ?*options*? *list*
+///
+/// Returns the elements of the list, sorted.  The options are:
+///
+/// * `-ascii`, the default, `-dictionary`, `-integer`, `-real`: how elements are
+///   compared.
+/// * `-increasing`, the default, `-decreasing`: the order.
+/// * `-index index`: sorts sublists by the element at the index, which may be `end`
+///   or `end-N`, or a list of indices into nested sublists.
+/// * `-unique`: keeps only the last of each run of elements that compare equal.
+///
+/// The sort is stable.
+///
+/// ## TCL Liens
+///
+/// * The `-command`, `-nocase`, `-indices`, and `-stride` options aren't supported.
+pub fn cmd_lsort<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "?options? list")?;
+
+    // FIRST, get the options.
+    let mut options = SortOptions::default();
+    let mut index = None;
+    let mut argi = 1;
+
+    while argi < argv.len() - 1 {
+        match argv[argi].as_str() {
+            "-ascii" => options.mode = SortMode::Ascii,
+            "-dictionary" => options.mode = SortMode::Dictionary,
+            "-integer" => options.mode = SortMode::Integer,
+            "-real" => options.mode = SortMode::Real,
+            "-increasing" => options.decreasing = false,
+            "-decreasing" => options.decreasing = true,
+            "-unique" => options.unique = true,
+            "-index" => {
+                if argi + 2 >= argv.len() {
+                    return molt_err!("\"-index\" option must be followed by list index");
+                }
+                argi += 1;
+                index = Some(argv[argi].as_list()?);
+            }
+            opt => {
+                return molt_err!(
+                    "bad option \"{}\": must be -ascii, -decreasing, -dictionary, \
+                     -increasing, -index, -integer, -real, or -unique",
+                    opt
+                );
+            }
+        }
+        argi += 1;
+    }
+
+    // NEXT, get the sort keys: the elements, or their sublists' elements.
+    let list = argv[argv.len() - 1].as_list()?;
+    let keys = match &index {
+        None => list.to_vec(),
+        Some(indices) => list
+            .iter()
+            .map(|item| sublist_item(item, indices))
+            .collect::<Result<_, _>>()?,
+    };
+
+    molt_ok!(sort_list(&list, &keys, options)?)
+}
+
+/// Returns the element of the nested sublists at the indices, for `lsort -index`.
+fn sublist_item(item: &Value, indices: &[Value]) -> MoltResult {
+    let mut value = item.clone();
+
+    for index in indices {
+        let sublist = value.as_list()?;
+        let i = parse_index(index, sublist.len())?;
+
+        value = match usize::try_from(i).ok().and_then(|i| sublist.get(i)) {
+            Some(element) => element.clone(),
+            None => return molt_err!("element {} missing from sublist \"{}\"", i, value),
+        };
+    }
+
+    molt_ok!(value)
+}
+
+/// # pdump
+///
+/// Dumps profile data.  Developer use only.
+pub fn cmd_pdump<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 1, 1, "")?;
+
+    interp.profile_dump();
+
     molt_ok!()
 }
 
-// /// # rename *oldName* *newName*
-// ///
-// /// Renames the command called *oldName* to have the *newName*.  If the
-// /// *newName* is "", the command is destroyed.
+/// # pclear
+///
+/// Clears profile data.  Developer use only.
+pub fn cmd_pclear<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 1, 1, "")?;
+
+    interp.profile_clear();
+
+    molt_ok!()
+}
+
+/// # proc *name* *args* *body*
+///
+/// Defines a procedure.
+pub fn cmd_proc<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 4, 4, "name args body")?;
+
+    // FIRST, get the arguments
+    let name = argv[1].as_str();
+    let args = &*argv[2].as_list()?;
+
+    // NEXT, validate the argument specs
+    for arg in args {
+        let vec = arg.as_list()?;
+
+        if vec.is_empty() {
+            return molt_err!("argument with no name");
+        } else if vec.len() > 2 {
+            return molt_err!("too many fields in argument specifier \"{}\"", arg);
+        }
+    }
+
+    // NEXT, add the command.
+    interp.add_proc(name, args, &argv[3])?;
+
+    molt_ok!()
+}
+
+/// # puts ?-nonewline? ?*channelId*? *string*
+///
+/// Outputs the string, followed by a newline unless `-nonewline` is given, to the
+/// channel, `stdout` by default.  The `stdout` channel is the interpreter's stdout
+/// writer; see `Interp::set_stdout_writer`.  Under the `std_buff` feature, output to
+/// `stdout` and `stderr` is pushed onto the interpreter's `std_buff`, one entry per call,
+/// unless the host has set a writer for the channel.
+pub fn cmd_puts<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 4, "?-nonewline? ?channelId? string")?;
+
+    let nonewline = argv.len() > 2 && argv[1].as_str() == "-nonewline";
+    let (channel, string) = match (argv.len(), nonewline) {
+        (2, _) | (3, true) => ("stdout", &argv[argv.len() - 1]),
+        (3, false) | (4, true) => (argv[argv.len() - 2].as_str(), &argv[argv.len() - 1]),
+        _ => {
+            return molt_err!(
+                "wrong # args: should be \"puts ?-nonewline? ?channelId? string\""
+            )
+        }
+    };
+
+    interp.puts(channel, string, nonewline)?;
+    molt_ok!()
+}
+
+/// # flush ?*channelId*?
+///
+/// Flushes any output buffered for the channel, `stdout` by default.  Under the
+/// `std_buff` feature, e.g., on wasm, output to `stdout` and `stderr` isn't buffered,
+/// and flushing them does nothing.
+pub fn cmd_flush<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 1, 2, "?channelId?")?;
+    let channel = argv.get(1).map_or("stdout", |channel| channel.as_str());
+
+    interp.channels().get(channel)?.flush(channel)?;
+    molt_ok!()
+}
+
+/// # rename *oldName* *newName*
+///
+/// Renames the proc called *oldName* to have the *newName*.  If the
+/// *newName* is "", the proc is destroyed.  Only procs and the commands added with
+/// `Interp::add_command` can be renamed, and not onto an existing command.  A proc that is executing when it is renamed or destroyed
+/// finishes normally; only later calls see the change.
 pub fn cmd_rename<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 3, 3, "oldName newName")?;
 
@@ -975,12 +1896,23 @@ pub fn cmd_rename<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     let old_name = argv[1].as_str();
     let new_name = argv[2].as_str();
 
-    if !interp.has_proc(old_name) {
+    let runtime_command = interp.contains_command(old_name);
+    if !runtime_command && !interp.has_proc(old_name) {
         return molt_err!("can't rename \"{}\": command doesn't exist", old_name);
     }
 
+    if !new_name.is_empty() && interp.command_type(new_name).is_ok() {
+        return molt_err!("can't rename to \"{}\": command already exists", new_name);
+    }
+
     // NEXT, rename or remove the command.
-    if new_name.is_empty() {
+    if runtime_command {
+        if new_name.is_empty() {
+            interp.remove_command(old_name);
+        } else {
+            interp.rename_command(old_name, new_name);
+        }
+    } else if new_name.is_empty() {
         interp.remove_proc(old_name);
     } else {
         interp.rename_proc(old_name, new_name);
@@ -1071,6 +2003,39 @@ pub fn cmd_return<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
     }
 }
 
+/// # scan *string* *format* ?*varName* ...?
+///
+/// Parses the *string* as C's `sscanf` does, assigning the fields to the variables and
+/// returning the number of conversions performed, or -1 if the *string* ran out before
+/// the first one.  With no variables, returns a list of the field values instead.  See
+/// the `scan` module for the conversions supported.
+pub fn cmd_scan<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 3, 0, "string format ?varName ...?")?;
+
+    let vars = &argv[3..];
+    let scanned = scan::scan(argv[1].as_str(), argv[2].as_str(), vars.len())?;
+    let no_match = scanned.underflow && scanned.conversions == 0;
+
+    if vars.is_empty() {
+        if no_match {
+            return molt_ok!();
+        }
+        return molt_ok!(scanned
+            .values
+            .into_iter()
+            .map(|value| value.unwrap_or_else(Value::empty))
+            .collect::<MoltList>());
+    }
+
+    for (var, value) in vars.iter().zip(scanned.values) {
+        if let Some(value) = value {
+            interp.set_var(var, value)?;
+        }
+    }
+
+    molt_ok!(if no_match { -1 } else { scanned.conversions })
+}
+
 /// # set *varName* ?*newValue*?
 ///
 /// Sets variable *varName* to *newValue*, returning the value.
@@ -1094,10 +2059,7 @@ pub fn cmd_source<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
 
     let filename = argv[1].as_str();
 
-    match fs::read_to_string(filename) {
-        Ok(script) => interp.eval(&script),
-        Err(e) => molt_err!("couldn't read file \"{}\": {}", filename, e),
-    }
+    interp.eval_file(filename)
 }
 
 /// # string *subcommand* ?*arg*...?
@@ -1114,25 +2076,25 @@ pub fn cmd_string<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     //                 ("compare","   ", cmd_string_compare,"string compare ?-nocase? ?-length length? string1 string2"),
     //                 ("equal","     ", cmd_string_equal,"string equal ?-nocase? ?-length length? string1 string2"),
     //                 ("first","     ", cmd_string_first,"string first needleString haystackString ?startIndex?"),
-    //                 ("index","     ", cmd_todo,"string index string charIndex"),
-    //                 ("is","        ", cmd_todo,"[TODO] string is class ?-strict? ?-failindex varname? string"),
+    //                 ("index","     ", cmd_string_index,"string index string charIndex"),
+    //                 ("is","        ", cmd_string_is,"string is class ?-strict? ?-failindex varname? string"),
     //                 ("last","      ", cmd_string_last,"string last needleString haystackString ?lastIndex?"),
     //                 ("length","    ", cmd_string_length,"string length string"),
     //                 ("map","       ", cmd_string_map,"string map ?-nocase? mapping string"),
-    //                 ("match","     ", cmd_todo,"[TODO] string match ?-nocase? pattern string"),
+    //                 ("match","     ", cmd_string_match,"string match ?-nocase? pattern string"),
     //                 ("range","     ", cmd_string_range,"string range string first last"),
-    //                 ("repeat","    ", cmd_todo,"[TODO] string repeat string count"),
+    //                 ("repeat","    ", cmd_string_repeat,"string repeat string count"),
     //                 ("replace","   ", cmd_todo,"[TODO] string replace string first last ?newstring?"),
-    //                 ("reverse","   ", cmd_todo,"[TODO] string reverse string"),
+    //                 ("reverse","   ", cmd_string_reverse,"string reverse string"),
     //                 ("tolower","   ", cmd_string_tolower,"string tolower string ?first? ?last?"),
-    //                 ("totitle","   ", cmd_todo,"[TODO] string totitle string ?first? ?last?"),
+    //                 ("totitle","   ", cmd_string_totitle,"string totitle string ?first? ?last?"),
     //                 ("toupper","   ", cmd_string_toupper,"string toupper string ?first? ?last?"),
     //                 ("trim","      ", cmd_string_trim,"string trim string ?chars?"),
     //                 ("trimleft","  ", cmd_string_trim,"string trimleft string ?chars?"),
     //                 ("trimright"," ", cmd_string_trim,"string trimright string ?chars?"),
-    //                 ("bytelength","", cmd_todo,"[TODO] string bytelength string"),
-    //                 ("wordend","   ", cmd_todo,"[TODO] string wordend string charIndex"),
-    //                 ("wordstart"," ", cmd_todo,"[TODO] string wordstart string charIndex"),
+    //                 ("bytelength","", cmd_string_bytelength,"string bytelength string"),
+    //                 ("wordend","   ", cmd_string_wordend,"string wordend string charIndex"),
+    //                 ("wordstart"," ", cmd_string_wordstart,"string wordstart string charIndex"),
     //             ],
     //         );
 
@@ -1140,23 +2102,29 @@ pub fn cmd_string<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     let f = _gen_subcommand_generic!(
         1,
         [
+            ("bytelength", cmd_string_bytelength),
             ("cat", cmd_string_cat),
             ("compare", cmd_string_compare),
             ("equal", cmd_string_equal),
             ("first", cmd_string_first),
-            // ("index", cmd_todo),
+            ("index", cmd_string_index),
+            ("is", cmd_string_is),
             ("last", cmd_string_last),
             ("length", cmd_string_length),
             ("map", cmd_string_map),
+            ("match", cmd_string_match),
             ("range", cmd_string_range),
             // ("replace", cmd_todo),
-            // ("repeat", cmd_todo),
-            // ("reverse", cmd_todo),
+            ("repeat", cmd_string_repeat),
+            ("reverse", cmd_string_reverse),
             ("tolower", cmd_string_tolower),
+            ("totitle", cmd_string_totitle),
             ("toupper", cmd_string_toupper),
             ("trim", cmd_string_trim),
             ("trimleft", cmd_string_trim),
             ("trimright", cmd_string_trim),
+            ("wordend", cmd_string_wordend),
+            ("wordstart", cmd_string_wordstart),
         ],
     );
     //     }
@@ -1169,6 +2137,18 @@ pub fn cmd_todo<Ctx>(_interp: &mut Interp<Ctx>, _argv: &[Value]) -> MoltResult {
     molt_err!("TODO")
 }
 
+/// string bytelength *string*
+///
+/// Returns the length of the string's UTF-8 encoding in bytes.
+pub fn cmd_string_bytelength<Ctx>(
+    _interp: &mut Interp<Ctx>,
+    argv: &[Value],
+) -> MoltResult {
+    check_args(2, argv, 3, 3, "string")?;
+
+    molt_ok!(argv[2].as_str().len() as MoltInt)
+}
+
 /// string cat ?*arg* ...?
 pub fn cmd_string_cat<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     let mut buff = String::new();
@@ -1279,28 +2259,21 @@ pub fn cmd_string_first<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltR
     let needle = argv[2].as_str();
     let haystack = argv[3].as_str();
 
-    let start_char: usize = if argv.len() == 5 {
-        let arg = argv[4].as_int()?;
-
-        if arg < 0 {
-            0
-        } else {
-            arg as usize
-        }
-    } else {
-        0
+    let start_char: usize = match argv.get(4) {
+        Some(index) => parse_index(index, argv[3].char_len())?.max(0) as usize,
+        None => 0,
     };
 
-    let pos_byte: Option<usize> = haystack
-        .char_indices()
-        .nth(start_char)
-        .and_then(|(start_byte, _)| haystack[start_byte..].find(needle));
-
-    let pos_char: MoltInt = match pos_byte {
+    // The byte offset is relative to the start character, so count the characters
+    // in between rather than slicing the haystack at it.
+    let pos_char: MoltInt = match haystack.char_indices().nth(start_char) {
         None => -1,
-        Some(b) => {
-            haystack[b..].char_indices().take_while(|(i, _)| *i < b).count() as MoltInt
-                + start_char as MoltInt
+        Some((start_byte, _)) => {
+            let tail = &haystack[start_byte..];
+            match tail.find(needle) {
+                None => -1,
+                Some(b) => (tail[..b].chars().count() + start_char) as MoltInt,
+            }
         }
     };
 
@@ -1317,7 +2290,7 @@ pub fn cmd_string_last<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltRe
     let count = haystack.chars().count();
 
     let last: Option<usize> = if argv.len() == 5 {
-        let arg = argv[4].as_int()?;
+        let arg = parse_index(&argv[4], count)?;
 
         if arg < 0 {
             return molt_ok!(-1);
@@ -1350,12 +2323,116 @@ pub fn cmd_string_last<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltRe
     molt_ok!(pos_char)
 }
 
+/// string index *string* *charIndex*
+///
+/// Returns the char at the index, or the empty string if the index is out of range.
+pub fn cmd_string_index<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "string charIndex")?;
+
+    let index = parse_index(&argv[3], argv[2].char_len())?;
+
+    if index < 0 {
+        return molt_ok!("");
+    }
+
+    match argv[2].as_str().chars().nth(index as usize) {
+        Some(c) => molt_ok!(c.to_string()),
+        None => molt_ok!(""),
+    }
+}
+
+/// string is *class* ?-strict? ?-failindex *varName*? *string*
+///
+/// Returns 1 if the string is a member of the class, and 0 otherwise.  The character
+/// classes, e.g., `alpha`, test each char of the string; the others, e.g., `integer`,
+/// test the string as a whole.  The empty string is a member of every class unless
+/// `-strict` is given.  If the string isn't a member and `-failindex` is given, the
+/// variable is set to the index of the first char that isn't, or to 0 for the classes
+/// that test the whole string.
+pub fn cmd_string_is<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "class ?-strict? ?-failindex var? str";
+    check_args(2, argv, 4, 7, USAGE)?;
+
+    // FIRST, get the options.
+    let mut strict = false;
+    let mut fail_var = None;
+    let mut queue = argv[3..argv.len() - 1].iter();
+
+    while let Some(opt) = queue.next() {
+        match opt.as_str() {
+            "-strict" => strict = true,
+            "-failindex" => match queue.next() {
+                Some(var) => fail_var = Some(var),
+                None => {
+                    return molt_err!("wrong # args: should be \"string is {}\"", USAGE)
+                }
+            },
+            _ => {
+                return molt_err!("bad option \"{}\": must be -strict or -failindex", opt)
+            }
+        }
+    }
+
+    // NEXT, test the string.  `failed` is the index of the first char that isn't in
+    // the class, if any.
+    let value = &argv[argv.len() - 1];
+    let s = value.as_str();
+    let whole = |ok: bool| if ok { None } else { Some(0) };
+
+    let failed = match argv[2].as_str() {
+        "alnum" => s.chars().position(|c| !c.is_alphanumeric()),
+        "alpha" => s.chars().position(|c| !c.is_alphabetic()),
+        "ascii" => s.chars().position(|c| !c.is_ascii()),
+        "boolean" => whole(Value::get_bool(s).is_ok()),
+        "control" => s.chars().position(|c| !c.is_control()),
+        "dict" => whole(value.as_dict().is_ok()),
+        "digit" => s.chars().position(|c| !c.is_numeric()),
+        "double" => whole(value.as_float().is_ok()),
+        "false" => whole(matches!(Value::get_bool(s), Ok(false))),
+        "graph" => s.chars().position(|c| c.is_whitespace() || c.is_control()),
+        "integer" => whole(value.as_int().is_ok()),
+        "list" => whole(value.as_list().is_ok()),
+        "lower" => s.chars().position(|c| !c.is_lowercase()),
+        "print" => s.chars().position(|c| c.is_control()),
+        "punct" => s
+            .chars()
+            .position(|c| c.is_alphanumeric() || c.is_whitespace() || c.is_control()),
+        "space" => s.chars().position(|c| !c.is_whitespace()),
+        "true" => whole(matches!(Value::get_bool(s), Ok(true))),
+        "upper" => s.chars().position(|c| !c.is_uppercase()),
+        "wordchar" => s.chars().position(|c| !is_word_char(c)),
+        "xdigit" => s.chars().position(|c| !c.is_ascii_hexdigit()),
+        class => {
+            return molt_err!(
+                "bad class \"{}\": must be alnum, alpha, ascii, boolean, control, dict, \
+                 digit, double, false, graph, integer, list, lower, print, punct, space, \
+                 true, upper, wordchar, or xdigit",
+                class
+            );
+        }
+    };
+
+    let failed = if s.is_empty() { strict.then_some(0) } else { failed };
+
+    match failed {
+        None => molt_ok!(true),
+        Some(index) => {
+            if let Some(var) = fail_var {
+                interp.set_var(var, Value::from(index as MoltInt))?;
+            }
+            molt_ok!(false)
+        }
+    }
+}
+
 /// string length *string*
+///
+/// Returns the number of chars, i.e., Unicode code points, in the string; a flag or an
+/// emoji ZWJ sequence counts as several.  See `string bytelength` for the byte count.
 pub fn cmd_string_length<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 3, 3, "string")?;
 
-    let len: MoltInt = argv[2].as_str().chars().count() as MoltInt;
-    molt_ok!(len)
+    molt_ok!(argv[2].char_len() as MoltInt)
 }
 
 /// string map ?-nocase? *charMap* *string*
@@ -1379,19 +2456,10 @@ pub fn cmd_string_map<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltRes
 
     let filtered_keys = char_map
         .iter()
-        .map(|(k, v)| {
-            let new_k =
-                if nocase { Value::from(k.as_str().to_lowercase()) } else { k.clone() };
-
-            let count = new_k.as_str().chars().count();
-
-            (new_k, count, v.clone())
-        })
+        .map(|(k, v)| (k.clone(), k.as_str().chars().count(), v.clone()))
         .filter(|(_, count, _)| *count > 0)
         .collect::<Vec<_>>();
 
-    let string_lower: Option<String> = if nocase { Some(s.to_lowercase()) } else { None };
-
     let mut result = String::new();
     let mut skip = 0;
 
@@ -1404,12 +2472,7 @@ pub fn cmd_string_map<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltRes
         let mut matched = false;
 
         for (from, from_char_count, to) in &filtered_keys {
-            let haystack: &str = match &string_lower {
-                Some(x) => &x[i..],
-                None => &s[i..],
-            };
-
-            if haystack.starts_with(&from.as_str()) {
+            if starts_with_chars(&s[i..], from.as_str(), nocase) {
                 matched = true;
 
                 result.push_str(to.as_str());
@@ -1427,59 +2490,432 @@ pub fn cmd_string_map<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltRes
     molt_ok!(result)
 }
 
+/// string match ?-nocase? *pattern* *string*
+///
+/// Returns 1 if the string matches the glob pattern, and 0 otherwise; see
+/// `util::glob_match`.
+pub fn cmd_string_match<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 5, "?-nocase? pattern string")?;
+
+    if argv.len() == 5 && argv[2].as_str() != "-nocase" {
+        return molt_err!("bad option \"{}\": must be -nocase", argv[2]);
+    }
+
+    let pattern = argv[argv.len() - 2].as_str();
+    let string = argv[argv.len() - 1].as_str();
+
+    if argv.len() == 5 {
+        molt_ok!(util::glob_match(&pattern.to_lowercase(), &string.to_lowercase()))
+    } else {
+        molt_ok!(util::glob_match(pattern, string))
+    }
+}
+
+/// Does the haystack start with the needle?  With `nocase`, the characters are compared
+/// one by one in lowercase, so that case folding can't change the string's length.
+fn starts_with_chars(haystack: &str, needle: &str, nocase: bool) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|n| match chars.next() {
+        Some(h) if nocase => h.to_lowercase().eq(n.to_lowercase()),
+        Some(h) => h == n,
+        None => false,
+    })
+}
+
 /// string range *string* *first* *last*
 pub fn cmd_string_range<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 5, 5, "string first last")?;
 
-    let s = argv[2].as_str();
-    let first = argv[3].as_int()?;
-    let last = argv[4].as_int()?;
+    let len = argv[2].char_len();
+    let first = parse_index(&argv[3], len)?.max(0);
+    let last = parse_index(&argv[4], len)?;
 
-    if last < 0 {
+    if last < first {
         return molt_ok!("");
     }
 
-    let clamp = { |i: MoltInt| if i < 0 { 0 } else { i } };
-
-    let substr = s
+    let substr = argv[2]
+        .as_str()
         .chars()
-        .skip(clamp(first) as usize)
-        .take((clamp(last) - clamp(first) + 1) as usize)
+        .skip(first as usize)
+        .take((last - first + 1) as usize)
         .collect::<String>();
 
     molt_ok!(substr)
 }
 
-/// string tolower *string*
-pub fn cmd_string_tolower<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+/// string repeat *string* *count*
+///
+/// Returns the string repeated *count* times; the empty string if *count* is less than 1.
+pub fn cmd_string_repeat<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "string count")?;
+
+    let string = argv[2].as_str();
+    let count = argv[3].as_int()?.max(0) as usize;
+
+    if string.is_empty() || count == 0 {
+        return molt_ok!();
+    }
+
+    let mut result = String::new();
+    let reserved = match string.len().checked_mul(count) {
+        Some(len) => result.try_reserve_exact(len).is_ok(),
+        None => false,
+    };
+
+    if !reserved {
+        return molt_err!("string size overflow");
+    }
+
+    for _ in 0..count {
+        result.push_str(string);
+    }
+
+    molt_ok!(result)
+}
+
+/// string reverse *string*
+///
+/// Returns the chars of the string in reverse order.
+pub fn cmd_string_reverse<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(2, argv, 3, 3, "string")?;
 
-    let lower = argv[2].as_str().to_lowercase();
-    molt_ok!(lower)
+    molt_ok!(argv[2].as_str().chars().rev().collect::<String>())
 }
 
-/// string toupper *string*
+/// Parses a TCL string or list index, `integer?[+-]integer?` or `end?[+-]integer?`,
+/// given the length of the string or list; `end` is the last element.  The result may
+/// be out of range, e.g., negative; what that means is up to the caller.
+pub(crate) fn parse_index(index: &Value, len: usize) -> Result<MoltInt, Exception> {
+    let bad_index = || {
+        molt_err!(
+            "bad index \"{}\": must be integer?[+-]integer? or end?[+-]integer?",
+            index
+        )
+    };
+    let s = index.as_str();
+
+    if let Some(rest) = s.strip_prefix("end") {
+        let end = len as MoltInt - 1;
+        if rest.is_empty() {
+            return Ok(end);
+        }
+        if !rest.starts_with(['+', '-']) {
+            return bad_index();
+        }
+        return match Value::get_int(rest) {
+            Ok(offset) => end.checked_add(offset).map_or_else(bad_index, Ok),
+            Err(_) => bad_index(),
+        };
+    }
+
+    if let Ok(int) = Value::get_int(s) {
+        return Ok(int);
+    }
+
+    // integer+integer or integer-integer.  The operator can't be the first char, which
+    // would be the first integer's sign.
+    match s.char_indices().skip(1).find(|(_, c)| matches!(c, '+' | '-')) {
+        Some((i, _)) => match (Value::get_int(&s[..i]), Value::get_int(&s[i..])) {
+            (Ok(a), Ok(b)) => a.checked_add(b).map_or_else(bad_index, Ok),
+            _ => bad_index(),
+        },
+        None => bad_index(),
+    }
+}
+
+/// string tolower *string* ?*first*? ?*last*?
+pub fn cmd_string_tolower<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 5, "string ?first? ?last?")?;
+
+    convert_case(argv, |s| s.chars().flat_map(char::to_lowercase).collect())
+}
+
+/// string totitle *string* ?*first*? ?*last*?
+///
+/// Converts the first char in the range to upper case, and the rest to lower case.
+pub fn cmd_string_totitle<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 5, "string ?first? ?last?")?;
+
+    convert_case(argv, |s| {
+        let mut chars = s.chars();
+        chars
+            .next()
+            .into_iter()
+            .flat_map(char::to_uppercase)
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect()
+    })
+}
+
+/// string toupper *string* ?*first*? ?*last*?
 pub fn cmd_string_toupper<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(2, argv, 3, 3, "string")?;
+    check_args(2, argv, 3, 5, "string ?first? ?last?")?;
 
-    let upper = argv[2].as_str().to_uppercase();
-    molt_ok!(upper)
+    convert_case(argv, |s| s.chars().flat_map(char::to_uppercase).collect())
 }
 
-/// string (trim|trimleft|trimright) *string*
+/// Applies the case conversion to the chars of the string `argv[2]` from index `argv[3]`
+/// to `argv[4]`, or to the whole string if no indices are given.  A missing *last* is
+/// *first*.  The indices select chars of the original string; the converted text may be
+/// longer, e.g., "ß" becomes "SS" in upper case.
+fn convert_case(argv: &[Value], convert: fn(&str) -> String) -> MoltResult {
+    let s = argv[2].as_str();
+    let len = argv[2].char_len();
+
+    let (first, last) = match argv.get(3) {
+        Some(first) => {
+            let first = parse_index(first, len)?.max(0);
+            let last = match argv.get(4) {
+                Some(last) => parse_index(last, len)?,
+                None => first,
+            };
+            (first, last.min(len as MoltInt - 1))
+        }
+        None => (0, len as MoltInt - 1),
+    };
+
+    if last < first {
+        return molt_ok!(argv[2].clone());
+    }
+
+    let (first, last) = (first as usize, last as usize);
+    let mut result = s.substring(0, first).to_string();
+    result.push_str(&convert(s.substring(first, last - first + 1)));
+    result.push_str(s.slice(last + 1..));
+    molt_ok!(result)
+}
+
+/// Is the char part of a word, for `string wordstart` and `string wordend`?
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// string wordend *string* *charIndex*
+///
+/// Returns the index just after the end of the word containing the char at the index,
+/// where a word is a run of alphanumeric chars and underscores.  Any other char is a
+/// word by itself.
+pub fn cmd_string_wordend<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "string charIndex")?;
+
+    let len = argv[2].char_len();
+    let index = parse_index(&argv[3], len)?.max(0) as usize;
+
+    if index >= len {
+        return molt_ok!(len as MoltInt);
+    }
+
+    let word_len = argv[2]
+        .as_str()
+        .chars()
+        .skip(index)
+        .take_while(|&c| is_word_char(c))
+        .count();
+    molt_ok!((index + word_len.max(1)) as MoltInt)
+}
+
+/// string wordstart *string* *charIndex*
+///
+/// Returns the index of the first char of the word containing the char at the index;
+/// see `string wordend`.
+pub fn cmd_string_wordstart<Ctx>(
+    _interp: &mut Interp<Ctx>,
+    argv: &[Value],
+) -> MoltResult {
+    check_args(2, argv, 4, 4, "string charIndex")?;
+
+    let len = argv[2].char_len();
+    let index = parse_index(&argv[3], len)?.min(len as MoltInt - 1);
+
+    if index <= 0 {
+        return molt_ok!(0);
+    }
+
+    let index = index as usize;
+    let chars: Vec<char> = argv[2].as_str().chars().take(index + 1).collect();
+    let word_len = chars.iter().rev().take_while(|&&c| is_word_char(c)).count();
+    molt_ok!((index + 1 - word_len.max(1)) as MoltInt)
+}
+
+/// string (trim|trimleft|trimright) *string* ?*chars*?
+///
+/// Trims whitespace, or any of the given chars, from the string.
 pub fn cmd_string_trim<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
-    check_args(2, argv, 3, 3, "string")?;
+    check_args(2, argv, 3, 4, "string ?chars?")?;
 
     let s = argv[2].as_str();
-    let trimmed = match argv[1].as_str() {
-        "trimleft" => s.trim_start(),
-        "trimright" => s.trim_end(),
-        _ => s.trim(),
+    let trimmed = match argv.get(3) {
+        None => match argv[1].as_str() {
+            "trimleft" => s.trim_start(),
+            "trimright" => s.trim_end(),
+            _ => s.trim(),
+        },
+        Some(chars) => {
+            let chars = chars.as_str();
+            let in_set = |c: char| chars.contains(c);
+            match argv[1].as_str() {
+                "trimleft" => s.trim_start_matches(in_set),
+                "trimright" => s.trim_end_matches(in_set),
+                _ => s.trim_matches(in_set),
+            }
+        }
     };
 
     molt_ok!(trimmed)
 }
 
+/// subst ?-nobackslashes? ?-nocommands? ?-novariables? *string*
+///
+/// Performs backslash, variable, and command substitution on the string, as for a
+/// quoted word, and returns the result.
+///
+/// If a command substitution raises `break`, the result up to that point is returned;
+/// if it raises `continue`, that substitution is replaced with the empty string; if it
+/// raises `return`, the returned value is substituted.  Errors are propagated.
+///
+/// ## TCL Liens
+///
+/// * The whole string is parsed before any substitution is performed, so a syntax error
+///   late in the string (e.g., a missing close-bracket) is reported even if an earlier
+///   command substitution would have raised `break`.
+pub fn cmd_subst<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 5, "?-nobackslashes? ?-nocommands? ?-novariables? string")?;
+
+    // FIRST, get the options.
+    let mut backslashes = true;
+    let mut commands = true;
+    let mut variables = true;
+
+    for opt in &argv[1..argv.len() - 1] {
+        match opt.as_str() {
+            "-nobackslashes" => backslashes = false,
+            "-nocommands" => commands = false,
+            "-novariables" => variables = false,
+            _ => {
+                return molt_err!(
+                    "bad option \"{}\": must be -nobackslashes, -nocommands, or \
+                     -novariables",
+                    opt
+                );
+            }
+        }
+    }
+
+    // NEXT, parse the string and perform the substitutions in order.
+    let string = &argv[argv.len() - 1];
+    let tokens = parser::parse_subst(string.as_str(), backslashes, commands, variables)?;
+    let mut result = String::new();
+
+    for token in &tokens {
+        match interp.eval_word(token) {
+            Ok(value) => result.push_str(value.as_str()),
+            Err(exception) => match exception.code() {
+                ResultCode::Break => break,
+                ResultCode::Continue => (),
+                ResultCode::Return => result.push_str(exception.value().as_str()),
+                _ => return Err(exception),
+            },
+        }
+    }
+
+    molt_ok!(result)
+}
+
+/// # switch ?*options*? *string* *pattern* *body* ?*pattern* *body* ...?
+///
+/// Evaluates the body of the first pattern that matches the string, and returns its
+/// result, or "" if no pattern matches.  The patterns and bodies may also be given as a
+/// single list argument.  A body of `-` falls through to the next pattern's body, and a
+/// last pattern of `default` matches any string.
+///
+/// The options are `-exact`, the default, to compare the string with each pattern, and
+/// `-glob`, to match it against each pattern as by `string match`; `--` ends the options.
+///
+/// ## TCL Liens
+///
+/// * Molt has no regular expressions, so `-regexp`, `-matchvar`, and `-indexvar` aren't
+///   supported, nor is `-nocase`.
+pub fn cmd_switch<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "?-option ...? string ?pattern body ...? ?default body?";
+    check_args(1, argv, 3, 0, USAGE)?;
+
+    // FIRST, get the options.  The string and at least one more argument follow them.
+    let mut glob = false;
+    let mut argi = 1;
+
+    while argi < argv.len() - 2 && argv[argi].as_str().starts_with('-') {
+        match argv[argi].as_str() {
+            "-exact" => glob = false,
+            "-glob" => glob = true,
+            "--" => {
+                argi += 1;
+                break;
+            }
+            opt => {
+                return molt_err!("bad option \"{}\": must be -exact, -glob, or --", opt);
+            }
+        }
+        argi += 1;
+    }
+
+    // NEXT, get the string and the pattern/body pairs.
+    let string = &argv[argi];
+    let list;
+    let pairs = match &argv[argi + 1..] {
+        [single] => {
+            list = single.as_list()?;
+            &list[..]
+        }
+        pairs => pairs,
+    };
+
+    if pairs.is_empty() {
+        return molt_err!("wrong # args: should be \"switch {}\"", USAGE);
+    } else if pairs.len() % 2 != 0 {
+        return molt_err!("extra switch pattern with no body");
+    }
+
+    // NEXT, find the first matching pattern, and then the first body at or after it that
+    // isn't a fall-through.
+    let last = pairs.len() - 2;
+    let matched = pairs.chunks(2).enumerate().position(|(i, pair)| {
+        let pattern = pair[0].as_str();
+        (i * 2 == last && pattern == "default")
+            || if glob {
+                util::glob_match(pattern, string.as_str())
+            } else {
+                pattern == string.as_str()
+            }
+    });
+
+    let Some(matched) = matched else {
+        return molt_ok!();
+    };
+
+    match pairs[matched * 2..].chunks(2).find(|pair| pair[1].as_str() != "-") {
+        Some(pair) => interp.eval_value(&pair[1]),
+        None => molt_err!("no body specified for pattern \"{}\"", pairs[last]),
+    }
+}
+
+/// # tailcall *command* ?*arg* ...?
+///
+/// Replaces the calling procedure's invocation with the command: the procedure returns,
+/// and the command is called in its caller's scope and its result returned in turn.
+pub fn cmd_tailcall<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "command ?arg ...?")?;
+
+    if interp.scope_level() == 0 {
+        return molt_err!("tailcall can only be called from a proc");
+    }
+
+    // The command is looked up in the procedure's namespace, not the caller's.
+    let mut words = argv[1..].to_vec();
+    words[0] = format!("::{}", &*interp.command_key(words[0].as_str())).into();
+    Err(Exception::molt_tailcall(Value::from(words)))
+}
+
 /// throw *type* *message*
 ///
 /// Throws an error with the error code and message.
@@ -1544,15 +2980,118 @@ pub fn cmd_unset<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     molt_ok!()
 }
 
+/// # uplevel ?*level*? *arg* ?*arg* ...?
+///
+/// Concatenates the arguments as `eval` does, and evaluates the result in a caller's
+/// scope: *level* levels up the stack, by default `1`, or at level *n* given as `#n`,
+/// counting from `#0`, the global scope.  A first argument that starts with a digit or
+/// `#` is taken as the level.
+pub fn cmd_uplevel<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "?level? command ?arg ...?";
+    check_args(1, argv, 2, 0, USAGE)?;
+
+    let current = interp.scope_level();
+    let first = argv[1].as_str();
+    let (level, args) = if is_level(first) {
+        check_args(1, argv, 3, 0, USAGE)?;
+        (stack_level(first, current)?, &argv[2..])
+    } else {
+        (current.saturating_sub(1), &argv[1..])
+    };
+
+    let result = if args.len() == 1 {
+        interp.eval_in_level(level, &args[0])
+    } else {
+        interp.eval_in_level(level, &util::concat(args))
+    };
+
+    result.map_err(|mut exception| {
+        if exception.is_error() {
+            exception.add_error_info("    (\"uplevel\" body)");
+        }
+        exception
+    })
+}
+
+/// # upvar ?*level*? *otherVar* *myVar* ?*otherVar* *myVar* ...?
+///
+/// Links each local variable *myVar* to the variable *otherVar* in a caller's scope,
+/// given as for `uplevel`, so that a variable can be passed by name.  *otherVar* may be
+/// an array, but not an array element.
+pub fn cmd_upvar<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "?level? otherVar localVar ?otherVar localVar ...?";
+    check_args(1, argv, 3, 0, USAGE)?;
+
+    let current = interp.scope_level();
+    let first = argv[1].as_str();
+    let (level, pairs) = if is_level(first) && argv.len().is_multiple_of(2) {
+        (stack_level(first, current)?, &argv[2..])
+    } else {
+        (current.saturating_sub(1), &argv[1..])
+    };
+
+    if !pairs.len().is_multiple_of(2) {
+        return molt_err!("wrong # args: should be \"{} {}\"", argv[0], USAGE);
+    }
+
+    for pair in pairs.chunks(2) {
+        let (other, name) = (&pair[0], &pair[1]);
+        if other.as_var_name().index().is_some() {
+            return molt_err!(
+                "bad variable name \"{}\": upvar can't link to an array element",
+                other
+            );
+        }
+        if name.as_var_name().index().is_some() {
+            return molt_err!(
+                "bad variable name \"{}\": upvar won't create a scalar variable that looks like an array element",
+                name
+            );
+        }
+        if interp.var_is_defined_here(name.as_str()) {
+            return molt_err!("variable \"{}\" already exists", name);
+        }
+        interp.upvar_as(level, other.as_str(), name.as_str())?;
+    }
+
+    molt_ok!()
+}
+
+/// Is the argument a stack level, as `uplevel` and `upvar` take: does it start with a
+/// digit or `#`?
+fn is_level(arg: &str) -> bool {
+    arg.starts_with(|c: char| c == '#' || c.is_ascii_digit())
+}
+
+/// Converts a stack level argument, relative or `#`-absolute, into an absolute scope
+/// level, given the current one.
+fn stack_level(arg: &str, current: usize) -> Result<usize, Exception> {
+    let level = match arg.strip_prefix('#') {
+        Some(absolute) => absolute.parse::<usize>().ok(),
+        None => arg.parse::<usize>().ok().and_then(|up| current.checked_sub(up)),
+    };
+
+    match level {
+        Some(level) if level <= current => Ok(level),
+        _ => molt_err!("bad level \"{}\"", arg),
+    }
+}
+
 /// # while *test* *command*
 ///
 /// A standard "while" loop.  *test* is a boolean expression; *command* is a script to
-/// execute so long as the expression is true.
+/// execute so long as the expression is true.  Returns the empty string.
 pub fn cmd_while<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 3, 3, "test command")?;
 
+    // The body is parsed once; an empty body, as in a busy-wait, isn't evaluated at all.
+    let body = argv[2].as_script()?;
+
     while interp.expr_bool(&argv[1])? {
-        let result = interp.eval_value(&argv[2]);
+        if body.commands().is_empty() {
+            continue;
+        }
+        let result = interp.eval_parsed(&body);
 
         if let Err(exception) = result {
             match exception.code() {
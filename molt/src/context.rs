@@ -0,0 +1,69 @@
+//! The Context Cache
+//!
+//! An interpreter's context, its `Ctx` type parameter, belongs to the application.  An
+//! extension crate that needs state of its own can't add a field to it; instead, it
+//! stashes the state in the interpreter's context cache, which holds at most one value of
+//! each type, keyed by its `TypeId`.  Each extension defines its own type, so any number
+//! of extensions can share an interpreter without coordinating.  See
+//! [`Interp::save_context`].
+//!
+//! [`Interp::save_context`]: ../interp/struct.Interp.html#method.save_context
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// A map from each type to a value of that type.
+#[derive(Default)]
+pub(crate) struct ContextCache {
+    map: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl ContextCache {
+    /// Saves the value, returning the value of its type saved before, if any.
+    pub(crate) fn insert<T: 'static>(&mut self, data: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(data))
+            .map(|old| *old.downcast().expect("context of the wrong type"))
+    }
+
+    /// Returns the value of the type, if any.
+    pub(crate) fn get<T: 'static>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>()).and_then(|data| data.downcast_ref())
+    }
+
+    /// Returns the value of the type mutably, if any.
+    pub(crate) fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|data| data.downcast_mut())
+    }
+
+    /// Removes the value of the type, returning it, if any.
+    pub(crate) fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .map(|data| *data.downcast().expect("context of the wrong type"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_cache() {
+        let mut cache = ContextCache::default();
+        assert_eq!(cache.insert(1_u32), None);
+        assert_eq!(cache.insert("one".to_string()), None);
+        assert_eq!(cache.insert(2_u32), Some(1));
+
+        *cache.get_mut::<u32>().unwrap() += 1;
+        assert_eq!(cache.get::<u32>(), Some(&3));
+        assert_eq!(cache.get::<String>().map(String::as_str), Some("one"));
+        assert_eq!(cache.get::<i32>(), None);
+
+        assert_eq!(cache.remove::<u32>(), Some(3));
+        assert_eq!(cache.remove::<u32>(), None);
+        assert!(cache.get::<String>().is_some());
+    }
+}
@@ -9,6 +9,7 @@
 use crate::list::list_to_string;
 use crate::molt_err;
 use crate::molt_ok;
+use crate::types::Exception;
 use crate::types::MoltDict;
 use crate::types::MoltList;
 use crate::types::MoltResult;
@@ -20,6 +21,37 @@ pub fn dict_new() -> MoltDict {
     IndexMap::new()
 }
 
+/// The outcome of looking up a path of keys in nested dictionaries.
+#[derive(Debug, PartialEq)]
+pub(crate) enum PathLookup<'a> {
+    /// The value at the end of the path.
+    Found(Value),
+    /// The first key that wasn't found.
+    Missing(&'a Value),
+}
+
+/// Given a Value containing a dictionary and a list of keys, looks up the value at the
+/// end of the (possibly nested) path of keys.  It's an error if the value, or a value
+/// reached along the path before the last key, isn't a dictionary.  With no keys, the
+/// dictionary value itself is found.
+pub(crate) fn dict_path_get<'a>(
+    dict_val: &Value,
+    keys: &'a [Value],
+) -> Result<PathLookup<'a>, Exception> {
+    let mut value = dict_val.clone();
+
+    for key in keys {
+        let dict = value.as_dict()?;
+
+        match dict.get(key) {
+            Some(val) => value = val.clone(),
+            None => return Ok(PathLookup::Missing(key)),
+        }
+    }
+
+    Ok(PathLookup::Found(value))
+}
+
 /// Given a Value containing a dictionary, a list of keys, and a value,
 /// inserts the value into the (possibly nested) dictionary, returning the new
 /// dictionary value.
@@ -122,4 +154,56 @@ mod tests {
 
         assert_eq!(dict_to_string(&dict), "abc 123");
     }
+
+    #[test]
+    fn test_dict_path_get() {
+        let inner = Value::from(list_to_dict(&["x".into(), "1".into()]));
+        let dict = Value::from(list_to_dict(&[
+            "a".into(),
+            inner.clone(),
+            "b".into(),
+            "2".into(),
+        ]));
+        let keys =
+            |keys: &[&str]| keys.iter().map(|k| Value::from(*k)).collect::<MoltList>();
+
+        assert_eq!(dict_path_get(&dict, &[]), Ok(PathLookup::Found(dict.clone())));
+        assert_eq!(dict_path_get(&dict, &keys(&["a"])), Ok(PathLookup::Found(inner)));
+        assert_eq!(
+            dict_path_get(&dict, &keys(&["a", "x"])),
+            Ok(PathLookup::Found(Value::from("1")))
+        );
+
+        // The first missing key is reported.
+        let path = keys(&["c", "x"]);
+        assert_eq!(dict_path_get(&dict, &path), Ok(PathLookup::Missing(&path[0])));
+        let path = keys(&["a", "y", "z"]);
+        assert_eq!(dict_path_get(&dict, &path), Ok(PathLookup::Missing(&path[1])));
+
+        // Looking into a value that isn't a dictionary is an error.
+        assert!(dict_path_get(&dict, &keys(&["b", "x"])).is_err());
+        assert!(dict_path_get(&Value::from("a"), &keys(&["a"])).is_err());
+    }
+
+    #[test]
+    fn test_dict_order() {
+        let list: MoltList = ["z", "1", "a", "2", "m", "3", "a", "4"]
+            .iter()
+            .map(|s| Value::from(*s))
+            .collect();
+
+        // Re-inserting a key keeps its original position.
+        let dict = list_to_dict(&list);
+        assert_eq!(dict_to_string(&dict), "z 1 a 4 m 3");
+
+        let dict = dict_insert(&dict, &"z".into(), &"5".into());
+        assert_eq!(dict_to_string(&dict), "z 5 a 4 m 3");
+
+        let dict = dict_insert(&dict, &"b".into(), &"6".into());
+        assert_eq!(dict_to_string(&dict), "z 5 a 4 m 3 b 6");
+
+        // Removing a key keeps the order of the rest.
+        let dict = dict_remove(&dict, &"a".into());
+        assert_eq!(dict_to_string(&dict), "z 5 m 3 b 6");
+    }
 }
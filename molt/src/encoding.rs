@@ -0,0 +1,267 @@
+//! Encoding
+//!
+//! The `encoding` command and the [`Encoding`] type, which convert strings to and from
+//! byte strings in UTF-8, UTF-16, ISO 8859-1, or ASCII.  A byte string is a `Value` with
+//! one character per byte; see `Value::from_bytes`.
+//!
+//! Conversions are strict: a byte sequence that isn't valid in the encoding, or a
+//! character the encoding can't represent, is an error.
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+
+/// The name of the `encoding` command.
+pub const _ENCODING: &str = "encoding";
+
+/// A character encoding, for converting strings to and from bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// 7-bit ASCII.
+    Ascii,
+    /// ISO 8859-1, a.k.a. Latin-1: one byte per character, U+0000 to U+00FF.
+    Latin1,
+    /// UTF-8.
+    Utf8,
+    /// UTF-16 in big-endian byte order.
+    Utf16Be,
+    /// UTF-16 in little-endian byte order.
+    Utf16Le,
+}
+
+impl Encoding {
+    /// The encodings, in the order `encoding names` lists them.
+    pub const ALL: [Encoding; 5] = [
+        Encoding::Ascii,
+        Encoding::Latin1,
+        Encoding::Utf8,
+        Encoding::Utf16Be,
+        Encoding::Utf16Le,
+    ];
+
+    /// The encoding of the system's strings, UTF-8.
+    pub const SYSTEM: Encoding = Encoding::Utf8;
+
+    /// Returns the encoding's TCL name, e.g., `iso8859-1`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Ascii => "ascii",
+            Encoding::Latin1 => "iso8859-1",
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf16Be => "utf-16be",
+            Encoding::Utf16Le => "utf-16le",
+        }
+    }
+
+    /// Looks up an encoding by name, ignoring case.  `utf-16` and `unicode` are UTF-16 in
+    /// the native byte order, and `latin1` is ISO 8859-1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// assert_eq!(Encoding::from_name("Latin1"), Ok(Encoding::Latin1));
+    /// assert!(Encoding::from_name("ebcdic").is_err());
+    /// ```
+    pub fn from_name(name: &str) -> Result<Encoding, Exception> {
+        let native_utf16 = if cfg!(target_endian = "little") {
+            Encoding::Utf16Le
+        } else {
+            Encoding::Utf16Be
+        };
+
+        match name.to_ascii_lowercase().as_str() {
+            "ascii" => Ok(Encoding::Ascii),
+            "iso8859-1" | "latin1" => Ok(Encoding::Latin1),
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "utf-16be" => Ok(Encoding::Utf16Be),
+            "utf-16le" => Ok(Encoding::Utf16Le),
+            "utf-16" | "unicode" => Ok(native_utf16),
+            _ => molt_err!("unknown encoding \"{}\"", name),
+        }
+    }
+
+    /// Decodes the bytes as a string in this encoding.
+    pub fn decode(&self, bytes: &[u8]) -> Result<String, Exception> {
+        match self {
+            Encoding::Ascii => match bytes.iter().position(|b| !b.is_ascii()) {
+                Some(i) => bad_bytes(bytes, i),
+                None => Ok(bytes.iter().map(|b| *b as char).collect()),
+            },
+            Encoding::Latin1 => Ok(bytes.iter().map(|b| *b as char).collect()),
+            Encoding::Utf8 => match std::str::from_utf8(bytes) {
+                Ok(str) => Ok(str.to_string()),
+                Err(err) => bad_bytes(bytes, err.valid_up_to()),
+            },
+            Encoding::Utf16Be | Encoding::Utf16Le => {
+                let units = bytes.chunks(2).map(|pair| match (self, pair) {
+                    (Encoding::Utf16Be, [hi, lo]) => Some(u16::from_be_bytes([*hi, *lo])),
+                    (_, [lo, hi]) => Some(u16::from_le_bytes([*lo, *hi])),
+                    _ => None,
+                });
+
+                let mut text = String::with_capacity(bytes.len() / 2);
+                let mut index = 0;
+                for unit in char::decode_utf16(units.map_while(|unit| unit)) {
+                    match unit {
+                        Ok(ch) => {
+                            text.push(ch);
+                            index += 2 * ch.len_utf16();
+                        }
+                        Err(_) => return bad_bytes(bytes, index),
+                    }
+                }
+
+                // An odd byte at the end is half a code unit.
+                if index < bytes.len() {
+                    return bad_bytes(bytes, index);
+                }
+                Ok(text)
+            }
+        }
+    }
+
+    /// Encodes the string as bytes in this encoding.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, Exception> {
+        match self {
+            Encoding::Ascii | Encoding::Latin1 => {
+                let max = if *self == Encoding::Ascii { 0x7F } else { 0xFF };
+                text.chars()
+                    .enumerate()
+                    .map(|(i, ch)| {
+                        if ch as u32 <= max {
+                            Ok(ch as u8)
+                        } else {
+                            molt_err!(
+                                "unexpected character at index {}: 'U+{:06X}'",
+                                i,
+                                ch as u32
+                            )
+                        }
+                    })
+                    .collect()
+            }
+            Encoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            Encoding::Utf16Be => {
+                Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect())
+            }
+            Encoding::Utf16Le => {
+                Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect())
+            }
+        }
+    }
+}
+
+/// Returns the error for an invalid byte sequence at the index.
+fn bad_bytes<T>(bytes: &[u8], index: usize) -> Result<T, Exception> {
+    molt_err!(
+        "unexpected byte sequence starting at index {}: '\\x{:02X}'",
+        index,
+        bytes[index]
+    )
+}
+
+/// # encoding *subcommand* ?*arg* ...?
+///
+/// Converts strings to and from byte strings in a given encoding.
+///
+/// * `encoding convertfrom ?encoding? data`: decodes the byte string.
+/// * `encoding convertto ?encoding? string`: encodes the string as a byte string.
+/// * `encoding names`: lists the supported encodings.
+/// * `encoding system`: returns the system encoding, `utf-8`.
+pub fn cmd_encoding<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("convertfrom", cmd_encoding_convertfrom),
+            ("convertto", cmd_encoding_convertto),
+            ("names", cmd_encoding_names),
+            ("system", cmd_encoding_system),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// Returns the encoding named by an optional argument, or the system encoding.
+fn optional_encoding(argv: &[Value]) -> Result<Encoding, Exception> {
+    if argv.len() == 4 {
+        Encoding::from_name(argv[2].as_str())
+    } else {
+        Ok(Encoding::SYSTEM)
+    }
+}
+
+/// # encoding convertfrom ?*encoding*? *data*
+fn cmd_encoding_convertfrom<Ctx>(
+    _interp: &mut Interp<Ctx>,
+    argv: &[Value],
+) -> MoltResult {
+    check_args(2, argv, 3, 4, "?encoding? data")?;
+
+    let encoding = optional_encoding(argv)?;
+    molt_ok!(Value::from_encoded(&argv[argv.len() - 1].as_bytes()?, encoding)?)
+}
+
+/// # encoding convertto ?*encoding*? *string*
+fn cmd_encoding_convertto<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 4, "?encoding? string")?;
+
+    let encoding = optional_encoding(argv)?;
+    molt_ok!(Value::from_bytes(argv[argv.len() - 1].to_encoded(encoding)?))
+}
+
+/// # encoding names
+fn cmd_encoding_names<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    molt_ok!(Value::from(
+        Encoding::ALL
+            .iter()
+            .map(|e| Value::from(e.name()))
+            .collect::<MoltList>()
+    ))
+}
+
+/// # encoding system
+fn cmd_encoding_system<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    molt_ok!(Encoding::SYSTEM.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let text = "h\u{E9}llo \u{1F600}";
+        for encoding in [Encoding::Utf8, Encoding::Utf16Be, Encoding::Utf16Le] {
+            let bytes = encoding.encode(text).unwrap();
+            assert_eq!(encoding.decode(&bytes).unwrap(), text, "{:?}", encoding);
+        }
+
+        let bytes = Encoding::Latin1.encode("h\u{E9}").unwrap();
+        assert_eq!(bytes, vec![b'h', 0xE9]);
+        assert_eq!(Encoding::Latin1.decode(&bytes).unwrap(), "h\u{E9}");
+    }
+
+    #[test]
+    fn test_utf16_byte_order() {
+        assert_eq!(Encoding::Utf16Be.encode("A").unwrap(), vec![0, 0x41]);
+        assert_eq!(Encoding::Utf16Le.encode("A").unwrap(), vec![0x41, 0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Encoding::Ascii.encode("\u{E9}").is_err());
+        assert!(Encoding::Latin1.encode("\u{100}").is_err());
+        assert!(Encoding::Ascii.decode(&[0x80]).is_err());
+        assert!(Encoding::Utf8.decode(&[b'a', 0xC3]).is_err());
+
+        // An odd byte, and an unpaired surrogate.
+        assert!(Encoding::Utf16Le.decode(&[0x41, 0, 0x42]).is_err());
+        assert!(Encoding::Utf16Le.decode(&[0x00, 0xD8]).is_err());
+    }
+}
@@ -5,6 +5,7 @@
 //! * Consider delegating skip_while() to iter::skip_while(), and replacing the
 //!   "skip_sequence" methods with some useful predicate functions.
 
+use crate::parser::Comment;
 use crate::tokenizer::Tokenizer;
 
 /// A struct that holds the parsing context: the iterator over the input string, and
@@ -21,6 +22,9 @@ pub struct EvalPtr<'a> {
 
     // Whether we're evaluating commands or just checking for completeness.
     no_eval: bool,
+
+    // The comments skipped so far, if they are being collected.
+    comments: Option<Vec<Comment>>,
 }
 
 impl<'a> EvalPtr<'a> {
@@ -30,6 +34,7 @@ impl<'a> EvalPtr<'a> {
             bracket_term: false,
             term_char: None,
             no_eval: false,
+            comments: None,
         }
     }
 
@@ -45,6 +50,7 @@ impl<'a> EvalPtr<'a> {
             bracket_term: false,
             term_char: None,
             no_eval: false,
+            comments: None,
         }
     }
 
@@ -75,6 +81,16 @@ impl<'a> EvalPtr<'a> {
         self.no_eval = flag;
     }
 
+    /// Starts collecting the comments that are skipped, for `take_comments`.
+    pub fn collect_comments(&mut self) {
+        self.comments = Some(Vec::new());
+    }
+
+    /// Returns the comments collected so far.
+    pub fn take_comments(&mut self) -> Vec<Comment> {
+        self.comments.take().unwrap_or_default()
+    }
+
     // Returns whether or not we are scanning the input for completeness.
     pub fn is_no_eval(&self) -> bool {
         self.no_eval
@@ -167,14 +183,22 @@ impl<'a> EvalPtr<'a> {
         }
     }
 
-    /// Is the current character a valid variable name character?
+    /// Is the current character a valid variable name character, or the start of a
+    /// namespace separator?
     pub fn next_is_varname_char(&mut self) -> bool {
         match self.tok.peek() {
-            Some(c) => c.is_alphanumeric() || c == '_',
+            Some(c) => c.is_alphanumeric() || c == '_' || self.next_is_namespace_sep(),
             None => false,
         }
     }
 
+    /// Is the current character the start of a namespace separator, i.e., two or more
+    /// colons?
+    pub fn next_is_namespace_sep(&mut self) -> bool {
+        let mut tok = self.tok.clone();
+        tok.next() == Some(':') && tok.next() == Some(':')
+    }
+
     /// Skips past any whitespace at the current point, *including* newlines.
     /// When this is complete we will be at the end of the script or on a non-white-space
     /// character.
@@ -197,6 +221,7 @@ impl<'a> EvalPtr<'a> {
     /// Returns true if it skipped a comment, and false otherwise.
     pub fn skip_comment(&mut self) -> bool {
         if self.next_is('#') {
+            let start = self.mark();
             while !self.at_end() {
                 let c = self.tok.next();
                 if c == Some('\n') {
@@ -208,6 +233,14 @@ impl<'a> EvalPtr<'a> {
                     self.tok.next();
                 }
             }
+
+            if let Some(comments) = &mut self.comments {
+                let input = self.tok.input();
+                comments.push(Comment {
+                    line: input[..start].matches('\n').count() + 1,
+                    text: self.tok.token(start).trim_end_matches('\n').to_string(),
+                });
+            }
             true
         } else {
             false
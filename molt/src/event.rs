@@ -0,0 +1,396 @@
+//! The Event Queue
+//!
+//! The `after` command schedules scripts to run later: after a delay, or when the
+//! application is idle.  Molt has no event loop of its own; the application runs the
+//! scripts that are due by calling [`Interp::run_pending_events`], or, on native
+//! targets, hands control to [`Interp::event_loop`] until none are left.  Under wasm,
+//! where the page can't be blocked, the application sets a timer for
+//! [`Interp::next_event_delay`], and runs the pending events when it fires.
+//!
+//! The `vwait` command waits for a global variable to be written.  Natively, it runs the
+//! pending events until one of them writes the variable.  Under wasm, it takes a script
+//! to run once the variable is written instead; either way, the interpreter learns of
+//! the write from a trace on the variable, or from [`Interp::notify_var_written`].
+//!
+//! [`Interp::run_pending_events`]: ../interp/struct.Interp.html#method.run_pending_events
+//! [`Interp::event_loop`]: ../interp/struct.Interp.html#method.event_loop
+//! [`Interp::next_event_delay`]: ../interp/struct.Interp.html#method.next_event_delay
+//! [`Interp::notify_var_written`]: ../interp/struct.Interp.html#method.notify_var_written
+use crate::check_args;
+use crate::interp::Interp;
+use crate::trace::{TraceCallback, TraceId, TraceOp};
+use crate::types::*;
+use crate::util;
+use crate::value::Value;
+use std::time::Duration;
+cfg_if::cfg_if! {
+  if #[cfg(feature = "wasm")] {
+    use wasm_timer::Instant;
+  } else {
+    use std::time::Instant;
+  }
+}
+
+/// The name of the `after` command.
+pub const _AFTER: &str = "after";
+
+/// The name of the `vwait` command.
+pub const _VWAIT: &str = "vwait";
+
+/// A scheduled script.
+struct Event {
+    id: usize,
+    // When the script is due, or `None` for an idle event.
+    due: Option<Instant>,
+    script: Value,
+}
+
+/// A `vwait` on a global variable.
+struct Wait {
+    // The variable's name, without the leading `::`.
+    name: String,
+    // The trace that notifies the queue when the variable is written.
+    trace: TraceId,
+    // The script to schedule when the variable is written, or `None` if `vwait` is
+    // blocked until it is.
+    script: Option<Value>,
+    written: bool,
+}
+
+/// An interpreter's scheduled scripts, in the order they were scheduled, and its
+/// `vwait` commands.
+#[derive(Default)]
+pub(crate) struct EventQueue {
+    events: Vec<Event>,
+    // The number used to name the next event.
+    next_id: usize,
+    waits: Vec<Wait>,
+}
+
+impl EventQueue {
+    /// Schedules the script, returning the event's name, e.g., `after#0`.  An idle event
+    /// has no due time.
+    pub(crate) fn schedule(&mut self, due: Option<Instant>, script: Value) -> String {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.events.push(Event { id, due, script });
+        format!("after#{}", id)
+    }
+
+    /// Returns the index of the named event, if it's still scheduled.
+    fn find(&self, name: &str) -> Option<usize> {
+        let id: usize = name.strip_prefix("after#")?.parse().ok()?;
+        self.events.iter().position(|event| event.id == id)
+    }
+
+    /// Returns the number the next event scheduled will have.  The events scheduled so
+    /// far all have lower numbers.
+    pub(crate) fn next_id(&self) -> usize {
+        self.next_id
+    }
+
+    /// Returns true if there are no scheduled events.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Drops every scheduled event.
+    pub(crate) fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Returns how long until the next event is due: zero if one is already due, or if
+    /// there's an idle event.
+    pub(crate) fn next_delay(&self, now: Instant) -> Option<Duration> {
+        self.events
+            .iter()
+            .map(|event| match event.due {
+                Some(due) if due > now => due.duration_since(now),
+                _ => Duration::ZERO,
+            })
+            .min()
+    }
+
+    /// Removes and returns the script of the next event that's due, considering only the
+    /// events numbered below `limit`: the timer that's been due longest, or if none is
+    /// due, the oldest idle event.
+    pub(crate) fn take_due(&mut self, now: Instant, limit: usize) -> Option<Value> {
+        let timer = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| event.id < limit)
+            .filter_map(|(i, event)| Some((event.due?, i)))
+            .filter(|(due, _)| *due <= now)
+            .min()
+            .map(|(_, i)| i);
+
+        let index = timer.or_else(|| {
+            self.events
+                .iter()
+                .position(|event| event.id < limit && event.due.is_none())
+        })?;
+
+        Some(self.events.remove(index).script)
+    }
+
+    /// Adds a wait on the named global variable, identified by the trace that notifies
+    /// the queue when the variable is written.
+    fn add_wait(&mut self, name: &str, trace: TraceId, script: Option<Value>) {
+        self.waits
+            .push(Wait { name: name.into(), trace, script, written: false });
+    }
+
+    /// Removes the wait with the given trace, returning true if it was still waiting.
+    #[cfg(not(feature = "wasm"))]
+    fn remove_wait(&mut self, trace: TraceId) -> bool {
+        let len = self.waits.len();
+        self.waits.retain(|wait| wait.trace != trace);
+        self.waits.len() < len
+    }
+
+    /// Returns true if the variable has been written since the wait with the given trace
+    /// began.
+    #[cfg(not(feature = "wasm"))]
+    fn is_written(&self, trace: TraceId) -> bool {
+        self.waits.iter().any(|wait| wait.trace == trace && wait.written)
+    }
+
+    /// Notes that the named global variable has been written.  The blocked waits on it
+    /// are marked written; the waits with scripts are removed, and their traces and
+    /// scripts returned for the caller to remove and schedule.
+    pub(crate) fn written(&mut self, name: &str) -> Vec<(TraceId, Value)> {
+        let mut done = Vec::new();
+        self.waits.retain_mut(|wait| {
+            if wait.name != name {
+                return true;
+            }
+            match wait.script.take() {
+                Some(script) => {
+                    done.push((wait.trace, script));
+                    false
+                }
+                None => {
+                    wait.written = true;
+                    true
+                }
+            }
+        });
+        done
+    }
+}
+
+/// # after *subcommand* ?*arg* ...?
+///
+/// Schedules scripts to run later; see the module documentation for how they get run.
+///
+/// * `after ms`: sleeps for *ms* milliseconds.  Not available under wasm.
+/// * `after ms script ?script ...?`: schedules the concatenated scripts to run at the
+///   global level after *ms* milliseconds, returning the event's ID.
+/// * `after idle script ?script ...?`: schedules the scripts to run when the
+///   application next runs its pending events, after any timers that are due.
+/// * `after cancel id`, `after cancel script ?script ...?`: cancels the event with the
+///   ID, or the first one with the concatenated script.
+/// * `after info ?id?`: returns the IDs of the scheduled events, or the script and
+///   type, `timer` or `idle`, of the event with the ID.
+pub fn cmd_after<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "option ?arg ...?")?;
+
+    match argv[1].as_str() {
+        "cancel" => cmd_after_cancel(interp, argv),
+        "idle" => cmd_after_idle(interp, argv),
+        "info" => cmd_after_info(interp, argv),
+        _ => cmd_after_ms(interp, argv),
+    }
+}
+
+/// # after *ms* ?*script* ...?
+fn cmd_after_ms<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let Ok(ms) = argv[1].as_int() else {
+        return molt_err!(
+            "bad argument \"{}\": must be cancel, idle, info, or an integer",
+            argv[1]
+        );
+    };
+    let delay = Duration::from_millis(ms.max(0) as u64);
+
+    if argv.len() == 2 {
+        cfg_if::cfg_if! {
+          if #[cfg(feature = "wasm")] {
+            let _ = delay;
+            return molt_err!("can't sleep under wasm: give a script to run later");
+          } else {
+            std::thread::sleep(delay);
+            return molt_ok!();
+          }
+        }
+    }
+
+    let script = util::concat(&argv[2..]);
+    let name = interp.events().schedule(Some(Instant::now() + delay), script);
+    molt_ok!(name)
+}
+
+/// # after idle *script* ?*script* ...?
+fn cmd_after_idle<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 0, "script ?script ...?")?;
+    let script = util::concat(&argv[2..]);
+    molt_ok!(interp.events().schedule(None, script))
+}
+
+/// # after cancel *id*|*script* ?*script* ...?
+fn cmd_after_cancel<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 0, "id|command")?;
+    let events = interp.events();
+
+    let index = if argv.len() == 3 { events.find(argv[2].as_str()) } else { None };
+    let index = index.or_else(|| {
+        let script = util::concat(&argv[2..]);
+        events
+            .events
+            .iter()
+            .position(|event| event.script.as_str() == script.as_str())
+    });
+
+    // Canceling an event that has already run, or never existed, isn't an error.
+    if let Some(index) = index {
+        events.events.remove(index);
+    }
+    molt_ok!()
+}
+
+/// # after info ?*id*?
+fn cmd_after_info<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 3, "?id?")?;
+    let events = interp.events();
+
+    let Some(name) = argv.get(2) else {
+        let names: MoltList = events
+            .events
+            .iter()
+            .map(|event| Value::from(format!("after#{}", event.id)))
+            .collect();
+        return molt_ok!(names);
+    };
+
+    match events.find(name.as_str()) {
+        Some(index) => {
+            let event = &events.events[index];
+            let kind = if event.due.is_some() { "timer" } else { "idle" };
+            molt_ok!(vec![event.script.clone(), Value::from(kind)])
+        }
+        None => molt_err!("event \"{}\" doesn't exist", name),
+    }
+}
+
+/// # vwait *varName* ?*script* ...?
+///
+/// Waits for the global variable to be written or unset.  With no script, runs the
+/// pending events until one of them writes the variable, returning an error if there
+/// are none left to run; this isn't available under wasm.  With scripts, returns at
+/// once, and schedules the concatenated scripts as an idle event once the variable is
+/// written.
+pub fn cmd_vwait<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(1, argv, 2, 0, "varName ?script ...?")?;
+
+    let var_name = argv[1].as_str();
+    let name = var_name.strip_prefix("::").unwrap_or(var_name);
+    let trace = interp.add_trace(
+        &Value::from(format!("::{}", name)),
+        vec![TraceOp::Write, TraceOp::Unset],
+        TraceCallback::Notify(name.into()),
+    )?;
+
+    if argv.len() > 2 {
+        let script = util::concat(&argv[2..]);
+        interp.events().add_wait(name, trace, Some(script));
+        return molt_ok!();
+    }
+
+    cfg_if::cfg_if! {
+      if #[cfg(feature = "wasm")] {
+        interp.untrace_var(trace);
+        molt_err!("can't wait for variable \"{}\" under wasm: give a script to run", var_name)
+      } else {
+        interp.events().add_wait(name, trace, None);
+        let result = vwait_blocking(interp, trace, var_name);
+        if interp.events().remove_wait(trace) {
+            interp.untrace_var(trace);
+        }
+        result
+      }
+    }
+}
+
+/// Runs the pending events until the variable the wait is on is written.
+#[cfg(not(feature = "wasm"))]
+fn vwait_blocking<Ctx>(
+    interp: &mut Interp<Ctx>,
+    trace: TraceId,
+    var_name: &str,
+) -> MoltResult {
+    while !interp.events().is_written(trace) {
+        let Some(delay) = interp.next_event_delay() else {
+            return molt_err!(
+                "can't wait for variable \"{}\": would wait forever",
+                var_name
+            );
+        };
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        interp.run_pending_events()?;
+    }
+    molt_ok!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_queue() {
+        let mut queue = EventQueue::default();
+        let now = Instant::now();
+        assert_eq!(queue.next_delay(now), None);
+
+        let later = now + Duration::from_secs(60);
+        assert_eq!(queue.schedule(Some(later), Value::from("later")), "after#0");
+        assert_eq!(queue.next_delay(now), Some(Duration::from_secs(60)));
+
+        queue.schedule(None, Value::from("idle"));
+        queue.schedule(Some(now), Value::from("now"));
+        assert_eq!(queue.next_delay(now), Some(Duration::ZERO));
+
+        // Due timers run before idle events, and events past the limit wait.
+        assert_eq!(queue.take_due(now, 2), Some(Value::from("idle")));
+        assert_eq!(queue.take_due(now, 3), Some(Value::from("now")));
+        assert_eq!(queue.take_due(now, 3), None);
+        assert_eq!(queue.take_due(later, 3), Some(Value::from("later")));
+        assert!(queue.is_empty());
+
+        queue.schedule(None, Value::from("idle"));
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_cancel_drops_events() {
+        let mut interp = Interp::default();
+        let handle = interp.cancel_handle();
+        interp.set_sticky_cancel(true);
+        interp
+            .eval("proc tick {} { incr ::ticks; after idle tick }; tick")
+            .unwrap();
+        assert_eq!(interp.run_pending_events().unwrap(), 1);
+
+        // The canceled script doesn't carry on through the scripts it scheduled.
+        handle.cancel();
+        let exception = interp.run_pending_events().unwrap_err();
+        assert_eq!(exception.code(), ResultCode::Cancelled);
+        assert!(!interp.has_pending_events());
+
+        handle.reset();
+        assert_eq!(interp.eval("set ticks").unwrap().as_str(), "2");
+    }
+}
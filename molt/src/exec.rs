@@ -0,0 +1,310 @@
+//! The `exec` Command
+//!
+//! This module, enabled by the `exec` feature, defines the `exec` command, which runs an
+//! external program and returns its output.  Add it to an interpreter as an extra native
+//! command:
+//!
+//! ```
+//! # use molt_forked::prelude::*;
+//! let command = gen_command!((), [(_EXEC, cmd_exec)], []);
+//! ```
+//!
+//! Only a single program is run; pipelines and background processes aren't supported.
+use crate::channel::describe;
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+
+/// The name of the `exec` command.
+pub const _EXEC: &str = "exec";
+
+/// Where the program's standard input comes from.
+enum Input {
+    /// The interpreter's standard input.
+    Inherit,
+    /// The value, as with `<< value`.
+    Value(String),
+    /// The named file, as with `< fileName`.
+    File(String),
+}
+
+/// Where one of the program's output streams goes.
+enum Output {
+    /// Captured into the result.
+    Capture,
+    /// The named file, truncated first unless appending, as with `> fileName` and
+    /// `>> fileName`.
+    File(String, bool),
+    /// Wherever standard output goes, as with `2>@1`.
+    Stdout,
+    /// The interpreter's own stream, as for standard error with `-ignorestderr`.
+    Inherit,
+}
+
+/// A parsed `exec` command line.
+struct Pipeline {
+    words: Vec<String>,
+    input: Input,
+    stdout: Output,
+    stderr: Output,
+}
+
+/// # exec ?-ignorestderr? ?-keepnewline? ?--? *arg* ?*arg* ...?
+///
+/// Runs the program named by the first *arg*, passing it the remaining *args*, and
+/// returns its standard output, less any trailing newline unless `-keepnewline` is given.
+/// The *args* may include the redirections `< fileName`, `<< value`, `> fileName`,
+/// `>> fileName`, `2> fileName`, `2>> fileName`, and `2>@1`.
+///
+/// If the program writes to its standard error, it's an error, with the program's
+/// output and error output as the message, unless the error output is redirected or
+/// `-ignorestderr` is given, in which case it goes to the interpreter's standard
+/// error.  If the program exits with a nonzero status, it's an error with the error code
+/// `CHILDSTATUS pid status`.
+pub fn cmd_exec<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "?-ignorestderr? ?-keepnewline? ?--? arg ?arg ...?";
+    check_args(1, argv, 2, 0, USAGE)?;
+
+    let mut ignore_stderr = false;
+    let mut keep_newline = false;
+    let mut argi = 1;
+
+    while let Some(opt) = argv.get(argi).map(|arg| arg.as_str()) {
+        match opt {
+            "-ignorestderr" => ignore_stderr = true,
+            "-keepnewline" => keep_newline = true,
+            "--" => {
+                argi += 1;
+                break;
+            }
+            _ if opt.starts_with('-') => {
+                return molt_err!(
+                    "bad option \"{}\": must be -ignorestderr, -keepnewline, or --",
+                    opt
+                );
+            }
+            _ => break,
+        }
+        argi += 1;
+    }
+
+    if argi >= argv.len() {
+        return molt_err!("wrong # args: should be \"{} {}\"", argv[0], USAGE);
+    }
+
+    let mut pipeline = parse(&argv[argi..])?;
+    if ignore_stderr && matches!(pipeline.stderr, Output::Capture) {
+        pipeline.stderr = Output::Inherit;
+    }
+
+    run(pipeline, keep_newline)
+}
+
+/// Parses the arguments into the program's words and its redirections.
+fn parse(args: &[Value]) -> Result<Pipeline, Exception> {
+    let mut pipeline = Pipeline {
+        words: Vec::new(),
+        input: Input::Inherit,
+        stdout: Output::Capture,
+        stderr: Output::Capture,
+    };
+
+    let mut args = args.iter().map(|arg| arg.as_str());
+
+    while let Some(arg) = args.next() {
+        if arg == "2>@1" {
+            pipeline.stderr = Output::Stdout;
+            continue;
+        }
+
+        if arg == "|" || arg == "|&" || arg == "&" {
+            return molt_err!("\"{}\" isn't supported: exec runs a single program", arg);
+        }
+
+        // Longer operators first, so that ">>" isn't taken for ">".
+        let Some(op) = ["2>>", "2>", "<<", "<", ">>", ">"]
+            .into_iter()
+            .find(|op| arg.starts_with(op))
+        else {
+            pipeline.words.push(arg.to_string());
+            continue;
+        };
+
+        let target = match &arg[op.len()..] {
+            "" => match args.next() {
+                Some(target) => target.to_string(),
+                None => {
+                    return molt_err!("can't specify \"{}\" as last word in command", op)
+                }
+            },
+            target => target.to_string(),
+        };
+
+        match op {
+            "<<" => pipeline.input = Input::Value(target),
+            "<" => pipeline.input = Input::File(target),
+            ">>" => pipeline.stdout = Output::File(target, true),
+            ">" => pipeline.stdout = Output::File(target, false),
+            "2>>" => pipeline.stderr = Output::File(target, true),
+            _ => pipeline.stderr = Output::File(target, false),
+        }
+    }
+
+    if pipeline.words.is_empty() {
+        return molt_err!("didn't specify command to execute");
+    }
+
+    Ok(pipeline)
+}
+
+/// Runs the parsed command, returning its output or the error it produced.
+fn run(pipeline: Pipeline, keep_newline: bool) -> MoltResult {
+    let program = &pipeline.words[0];
+    let mut command = Command::new(program);
+    command.args(&pipeline.words[1..]);
+
+    command.stdin(match &pipeline.input {
+        Input::Inherit => Stdio::inherit(),
+        Input::Value(_) => Stdio::piped(),
+        Input::File(name) => match File::open(name) {
+            Ok(file) => Stdio::from(file),
+            Err(e) => {
+                return molt_err!("couldn't read file \"{}\": {}", name, describe(&e))
+            }
+        },
+    });
+
+    // The stdout file, if any, is kept so that `2>@1` can share it.
+    let stdout_file = match &pipeline.stdout {
+        Output::File(name, append) => Some(create(name, *append)?),
+        _ => None,
+    };
+
+    // With `2>@1`, both streams write to one pipe, so their output interleaves as the
+    // program wrote it.
+    let mut merged = None;
+
+    match (&pipeline.stderr, &stdout_file) {
+        (Output::Stdout, Some(file)) => {
+            command.stderr(file.try_clone().map_err(os_error)?);
+        }
+        (Output::Stdout, None) => {
+            let (reader, writer) = io::pipe().map_err(os_error)?;
+            let writer2 = writer.try_clone().map_err(os_error)?;
+            command.stdout(writer);
+            command.stderr(writer2);
+            merged = Some(reader);
+        }
+        (Output::Inherit, _) => {
+            command.stderr(Stdio::inherit());
+        }
+        (Output::File(name, append), _) => {
+            command.stderr(create(name, *append)?);
+        }
+        (Output::Capture, _) => {
+            command.stderr(Stdio::piped());
+        }
+    }
+
+    match stdout_file {
+        Some(file) => {
+            command.stdout(file);
+        }
+        None if merged.is_none() => {
+            command.stdout(Stdio::piped());
+        }
+        None => {}
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return molt_err!("couldn't execute \"{}\": {}", program, describe(&e)),
+    };
+
+    // The command holds the write ends of the merged pipe; drop it so that reading the
+    // pipe sees end-of-file when the program exits.
+    drop(command);
+
+    // Write the input on another thread, so that a program that writes more output than
+    // the pipe holds before reading all of its input can't deadlock.
+    let writer = match (pipeline.input, child.stdin.take()) {
+        (Input::Value(value), Some(mut stdin)) => Some(std::thread::spawn(move || {
+            // The program may exit without reading its input; that isn't an error.
+            let _ = stdin.write_all(value.as_bytes());
+        })),
+        _ => None,
+    };
+
+    let mut stdout = Vec::new();
+    if let Some(mut reader) = merged {
+        if let Err(e) = reader.read_to_end(&mut stdout) {
+            return molt_err!("error reading output from command: {}", describe(&e));
+        }
+    }
+
+    let pid = Value::from(child.id() as MoltInt);
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(e) => {
+            return molt_err!("error waiting for \"{}\": {}", program, describe(&e))
+        }
+    };
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    stdout.extend(output.stdout);
+
+    let mut result = String::from_utf8_lossy(&stdout).into_owned();
+    result.push_str(&String::from_utf8_lossy(&output.stderr));
+    if !keep_newline && result.ends_with('\n') {
+        result.pop();
+    }
+
+    match output.status.code() {
+        Some(0) if output.stderr.is_empty() => molt_ok!(result),
+        Some(0) => molt_err!(result),
+        Some(status) => {
+            if result.is_empty() {
+                result = "child process exited abnormally".into();
+            }
+            let error_code = Value::from(vec![
+                Value::from("CHILDSTATUS"),
+                pid,
+                Value::from(status as MoltInt),
+            ]);
+            Err(Exception::molt_err2(error_code, Value::from(result)))
+        }
+        None => {
+            if result.is_empty() {
+                result = "child killed".into();
+            }
+            let error_code = Value::from(vec![Value::from("CHILDKILLED"), pid]);
+            Err(Exception::molt_err2(error_code, Value::from(result)))
+        }
+    }
+}
+
+/// Creates or opens the named output file, appending to it or truncating it.
+fn create(name: &str, append: bool) -> Result<File, Exception> {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+
+    match options.open(name) {
+        Ok(file) => Ok(file),
+        Err(e) => molt_err!("couldn't write file \"{}\": {}", name, describe(&e)),
+    }
+}
+
+/// Converts an error setting up the program's streams into an exception.
+fn os_error(error: io::Error) -> Exception {
+    Exception::molt_err(Value::from(describe(&error)))
+}
@@ -6,7 +6,8 @@
 use crate::eval_ptr::EvalPtr;
 use crate::interp::Interp;
 use crate::list;
-use crate::parser::Word;
+use crate::parser::{self, Word};
+use crate::rng::Rng;
 use crate::tokenizer::Tokenizer;
 use crate::*;
 
@@ -15,15 +16,16 @@ use crate::*;
 
 type DatumResult = Result<Datum, Exception>;
 
-/// The value type.
+/// The type of a [`Datum`].
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
-pub(crate) enum Type {
+pub enum DatumType {
     Int,
     Float,
     String,
 }
 
-/// A parsed value.
+/// A typed expression value, as returned by
+/// [`Interp::expr_value`](crate::interp::Interp::expr_value).
 ///
 /// **Note**: Originally did this as a struct containing an enum with associated values
 /// for the data, but that complicated the logic.  We need to easily compare the types
@@ -33,8 +35,8 @@ pub(crate) enum Type {
 /// I could have used a union to save space, but we don't keep large numbers of these
 /// around.
 #[derive(Debug, PartialEq)]
-pub(crate) struct Datum {
-    vtype: Type,
+pub struct Datum {
+    vtype: DatumType,
     int: MoltInt,
     flt: MoltFloat,
     str: String,
@@ -43,7 +45,7 @@ pub(crate) struct Datum {
 impl Datum {
     fn none() -> Self {
         Self {
-            vtype: Type::String,
+            vtype: DatumType::String,
             int: 0,
             flt: 0.0,
             str: String::new(),
@@ -52,7 +54,7 @@ impl Datum {
 
     pub(crate) fn int(int: MoltInt) -> Self {
         Self {
-            vtype: Type::Int,
+            vtype: DatumType::Int,
             int,
             flt: 0.0,
             str: String::new(),
@@ -61,7 +63,7 @@ impl Datum {
 
     pub(crate) fn float(flt: MoltFloat) -> Self {
         Self {
-            vtype: Type::Float,
+            vtype: DatumType::Float,
             int: 0,
             flt,
             str: String::new(),
@@ -70,17 +72,49 @@ impl Datum {
 
     fn string(string: &str) -> Self {
         Self {
-            vtype: Type::String,
+            vtype: DatumType::String,
             int: 0,
             flt: 0.0,
             str: string.to_string(),
         }
     }
 
+    /// Returns the datum's type.
+    pub fn datum_type(&self) -> DatumType {
+        self.vtype
+    }
+
+    /// Returns the integer value, if the datum is an integer.
+    pub fn as_int(&self) -> Option<MoltInt> {
+        if self.vtype == DatumType::Int {
+            Some(self.int)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the floating-point value, if the datum is a float.
+    pub fn as_float(&self) -> Option<MoltFloat> {
+        if self.vtype == DatumType::Float {
+            Some(self.flt)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the string value, if the datum is a non-numeric string.
+    pub fn as_str(&self) -> Option<&str> {
+        if self.vtype == DatumType::String {
+            Some(&self.str)
+        } else {
+            None
+        }
+    }
+
     // Only for checking integers.
     fn is_true(&self) -> bool {
         match self.vtype {
-            Type::Int => self.int != 0,
+            DatumType::Int => self.int != 0,
             _ => {
                 panic!("Datum::is_true called for non-integer");
             }
@@ -97,12 +131,18 @@ const MAX_MATH_ARGS: usize = 2;
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 enum ArgType {
     None,
-    Float,  // Must convert to Type::Float
-    Int,    // Must convert to Type::Int
-    Number, // Either Type::Int or Type::Float is OK
+    Float,  // Must convert to DatumType::Float
+    Int,    // Must convert to DatumType::Int
+    Number, // Either DatumType::Int or DatumType::Float is OK
 }
 
-type MathFunc = fn(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult;
+/// A math function: either a pure function of its arguments, or one that uses the
+/// interpreter's random number generator.
+#[derive(Copy, Clone)]
+enum MathFunc {
+    Pure(fn(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult),
+    Random(fn(rng: &mut Rng, args: &[Datum; MAX_MATH_ARGS]) -> DatumResult),
+}
 
 struct BuiltinFunc {
     name: &'static str,
@@ -111,30 +151,42 @@ struct BuiltinFunc {
     func: MathFunc,
 }
 
-const FUNC_TABLE: [BuiltinFunc; 4] = [
+const FUNC_TABLE: [BuiltinFunc; 6] = [
     BuiltinFunc {
         name: "abs",
         num_args: 1,
         arg_types: [ArgType::Number, ArgType::None],
-        func: expr_abs_func,
+        func: MathFunc::Pure(expr_abs_func),
     },
     BuiltinFunc {
         name: "double",
         num_args: 1,
         arg_types: [ArgType::Number, ArgType::None],
-        func: expr_double_func,
+        func: MathFunc::Pure(expr_double_func),
     },
     BuiltinFunc {
         name: "int",
         num_args: 1,
         arg_types: [ArgType::Number, ArgType::None],
-        func: expr_int_func,
+        func: MathFunc::Pure(expr_int_func),
+    },
+    BuiltinFunc {
+        name: "rand",
+        num_args: 0,
+        arg_types: [ArgType::None, ArgType::None],
+        func: MathFunc::Random(expr_rand_func),
     },
     BuiltinFunc {
         name: "round",
         num_args: 1,
         arg_types: [ArgType::Number, ArgType::None],
-        func: expr_round_func,
+        func: MathFunc::Pure(expr_round_func),
+    },
+    BuiltinFunc {
+        name: "srand",
+        num_args: 1,
+        arg_types: [ArgType::Int, ArgType::None],
+        func: MathFunc::Random(expr_srand_func),
     },
 ];
 
@@ -234,7 +286,7 @@ const PREC_TABLE: [i32; 36] = [
     3, // OR
     2, // QUESTY
     1, // COLON
-    13, 13, 13, 13, // UNARY_MINUS, UNARY_PLUS, NOT, BIT_NOT
+    15, 15, 15, 15, // UNARY_MINUS, UNARY_PLUS, NOT, BIT_NOT
 ];
 
 const OP_STRINGS: [&str; 36] = [
@@ -246,14 +298,18 @@ const OP_STRINGS: [&str; 36] = [
 //------------------------------------------------------------------------------------------------
 // Public API
 
-/// Evaluates an expression and returns its value.
-pub fn expr<Ctx: 'static>(interp: &mut Interp<Ctx>, expr: &Value) -> MoltResult {
-    let value = expr_top_level(interp, expr.as_str())?;
+/// Evaluates an expression and returns its value as a typed `Datum`.
+pub fn expr_value<Ctx: 'static>(interp: &mut Interp<Ctx>, expr: &Value) -> DatumResult {
+    expr_top_level(interp, expr.as_str())
+}
 
-    match value.vtype {
-        Type::Int => molt_ok!(Value::from(value.int)),
-        Type::Float => molt_ok!(Value::from(value.flt)),
-        Type::String => molt_ok!(Value::from(value.str)),
+impl From<Datum> for Value {
+    fn from(datum: Datum) -> Self {
+        match datum.vtype {
+            DatumType::Int => Value::from(datum.int),
+            DatumType::Float => Value::from(datum.flt),
+            DatumType::String => Value::from(datum.str),
+        }
     }
 }
 
@@ -275,7 +331,7 @@ fn expr_top_level<'a, Ctx: 'static>(
                 return molt_err!("syntax error in expression \"{}\"", string);
             }
 
-            if value.vtype == Type::Float {
+            if value.vtype == DatumType::Float {
                 // TODO: check for NaN, INF, and throw IEEE floating point error.
             }
 
@@ -335,10 +391,14 @@ fn expr_get_value<'a, Ctx: 'static>(
             if info.no_eval == 0 {
                 match operator {
                     UNARY_MINUS => match value.vtype {
-                        Type::Int => {
-                            value.int = -value.int;
+                        DatumType::Int => {
+                            if let Some(int) = value.int.checked_neg() {
+                                value.int = int;
+                            } else {
+                                return molt_err!("integer overflow");
+                            }
                         }
-                        Type::Float => {
+                        DatumType::Float => {
                             value.flt = -value.flt;
                         }
                         _ => {
@@ -352,7 +412,7 @@ fn expr_get_value<'a, Ctx: 'static>(
                     }
                     NOT => {
                         match value.vtype {
-                            Type::Int => {
+                            DatumType::Int => {
                                 // NOTE: Tcl uses !int here, but in Rust !int_value is a bitwise
                                 // operator, not a logical one.
                                 if value.int == 0 {
@@ -361,7 +421,7 @@ fn expr_get_value<'a, Ctx: 'static>(
                                     value.int = 0;
                                 }
                             }
-                            Type::Float => {
+                            DatumType::Float => {
                                 if value.flt == 0.0 {
                                     value = Datum::int(1);
                                 } else {
@@ -374,7 +434,7 @@ fn expr_get_value<'a, Ctx: 'static>(
                         }
                     }
                     BIT_NOT => {
-                        if let Type::Int = value.vtype {
+                        if let DatumType::Int = value.vtype {
                             // Note: in Rust, unlike C, !int_value is a bitwise operator.
                             value.int = !value.int;
                         } else {
@@ -402,6 +462,7 @@ fn expr_get_value<'a, Ctx: 'static>(
     }
 
     loop {
+        interp.check_interrupted()?;
         operator = info.token;
         // ??? value2.pv.next = value2.pv.buffer;
 
@@ -425,14 +486,14 @@ fn expr_get_value<'a, Ctx: 'static>(
             // For these operators, we need an integer value.  Convert or return
             // an error.
             match value.vtype {
-                Type::Float => {
+                DatumType::Float => {
                     if value.flt == 0.0 {
                         value = Datum::int(0);
                     } else {
                         value = Datum::int(1);
                     }
                 }
-                Type::String => {
+                DatumType::String => {
                     if info.no_eval == 0 {
                         return illegal_type(value.vtype, operator);
                     }
@@ -513,28 +574,28 @@ fn expr_get_value<'a, Ctx: 'static>(
             // For the operators below, no strings are allowed and ints get converted to
             // floats if necessary.
             MULT | DIVIDE | PLUS | MINUS => {
-                if value.vtype == Type::String || value2.vtype == Type::String {
-                    return illegal_type(Type::String, operator);
+                if value.vtype == DatumType::String || value2.vtype == DatumType::String {
+                    return illegal_type(DatumType::String, operator);
                 }
 
-                if value.vtype == Type::Float {
-                    if value2.vtype == Type::Int {
+                if value.vtype == DatumType::Float {
+                    if value2.vtype == DatumType::Int {
                         value2.flt = value2.int as MoltFloat;
-                        value2.vtype = Type::Float;
+                        value2.vtype = DatumType::Float;
                     }
-                } else if value2.vtype == Type::Float {
-                    if value.vtype == Type::Int {
+                } else if value2.vtype == DatumType::Float {
+                    if value.vtype == DatumType::Int {
                         value.flt = value.int as MoltFloat;
-                        value.vtype = Type::Float;
+                        value.vtype = DatumType::Float;
                     }
                 }
             }
 
             // For the operators below, only integers are allowed.
             MOD | LEFT_SHIFT | RIGHT_SHIFT | BIT_AND | BIT_XOR | BIT_OR => {
-                if value.vtype != Type::Int {
+                if value.vtype != DatumType::Int {
                     return illegal_type(value.vtype, operator);
-                } else if value2.vtype != Type::Int {
+                } else if value2.vtype != DatumType::Int {
                     return illegal_type(value2.vtype, operator);
                 }
             }
@@ -542,20 +603,20 @@ fn expr_get_value<'a, Ctx: 'static>(
             // For the operators below, any type is allowed, but the operators must have
             // the same type.
             LESS | GREATER | LEQ | GEQ | EQUAL | NEQ => {
-                if value.vtype == Type::String {
-                    if value2.vtype != Type::String {
+                if value.vtype == DatumType::String {
+                    if value2.vtype != DatumType::String {
                         value2 = expr_as_str(value2);
                     }
-                } else if value2.vtype == Type::String {
-                    if value.vtype != Type::String {
+                } else if value2.vtype == DatumType::String {
+                    if value.vtype != DatumType::String {
                         value = expr_as_str(value);
                     }
-                } else if value.vtype == Type::Float {
-                    if value2.vtype == Type::Int {
+                } else if value.vtype == DatumType::Float {
+                    if value2.vtype == DatumType::Int {
                         value2 = Datum::float(value2.int as MoltFloat);
                     }
-                } else if value2.vtype == Type::Float {
-                    if value.vtype == Type::Int {
+                } else if value2.vtype == DatumType::Float {
+                    if value.vtype == DatumType::Int {
                         value = Datum::float(value.int as MoltFloat);
                     }
                 }
@@ -565,10 +626,10 @@ fn expr_get_value<'a, Ctx: 'static>(
             // For IN and NI, the second value is a list, but we'll parse it as a list
             // as part of evaluation.
             STRING_EQ | STRING_NE | IN | NI => {
-                if value.vtype != Type::String {
+                if value.vtype != DatumType::String {
                     value = expr_as_str(value);
                 }
-                if value2.vtype != Type::String {
+                if value2.vtype != DatumType::String {
                     value2 = expr_as_str(value2);
                 }
             }
@@ -576,10 +637,10 @@ fn expr_get_value<'a, Ctx: 'static>(
             // For the operators below, no strings are allowed, but no int->float conversions
             // are performed.
             AND | OR => {
-                if value.vtype == Type::String {
+                if value.vtype == DatumType::String {
                     return illegal_type(value.vtype, operator);
                 }
-                if value2.vtype == Type::String {
+                if value2.vtype == DatumType::String {
                     return illegal_type(value2.vtype, operator);
                 }
             }
@@ -596,7 +657,7 @@ fn expr_get_value<'a, Ctx: 'static>(
         // Carry out the function of the specified operator.
         match operator {
             MULT => {
-                if value.vtype == Type::Int {
+                if value.vtype == DatumType::Int {
                     // value.int *= value2.int
                     if let Some(int) = value.int.checked_mul(value2.int) {
                         value.int = int;
@@ -608,7 +669,7 @@ fn expr_get_value<'a, Ctx: 'static>(
                 }
             }
             DIVIDE => {
-                if value.vtype == Type::Int {
+                if value.vtype == DatumType::Int {
                     if value2.int == 0 {
                         return molt_err!("divide by zero");
                     }
@@ -627,7 +688,7 @@ fn expr_get_value<'a, Ctx: 'static>(
                 }
             }
             MOD => {
-                assert!(value.vtype == Type::Int);
+                assert!(value.vtype == DatumType::Int);
 
                 if value2.int == 0 {
                     return molt_err!("divide by zero");
@@ -640,7 +701,7 @@ fn expr_get_value<'a, Ctx: 'static>(
                 }
             }
             PLUS => {
-                if value.vtype == Type::Int {
+                if value.vtype == DatumType::Int {
                     // value.int += value2.int;
                     if let Some(int) = value.int.checked_add(value2.int) {
                         value.int = int;
@@ -652,7 +713,7 @@ fn expr_get_value<'a, Ctx: 'static>(
                 }
             }
             MINUS => {
-                if value.vtype == Type::Int {
+                if value.vtype == DatumType::Int {
                     // value.int -= value2.int;
                     if let Some(int) = value.int.checked_sub(value2.int) {
                         value.int = int;
@@ -664,54 +725,66 @@ fn expr_get_value<'a, Ctx: 'static>(
                 }
             }
             LEFT_SHIFT => {
-                // TODO: Use checked_shl
-                value.int <<= value2.int;
+                // Shifting out significant bits is an overflow, as is any shift of a
+                // non-zero value by the word size or more.  (Tcl 8 promotes to a bignum.)
+                if value2.int < 0 {
+                    return molt_err!("negative shift argument");
+                }
+
+                if value.int != 0 {
+                    if value2.int >= MoltInt::BITS as MoltInt {
+                        return molt_err!("integer overflow");
+                    }
+
+                    let int = value.int << value2.int;
+                    if int >> value2.int != value.int {
+                        return molt_err!("integer overflow");
+                    }
+                    value.int = int;
+                }
             }
             RIGHT_SHIFT => {
-                // The following code is a bit tricky:  it ensures that
-                // right shifts propagate the sign bit even on machines
-                // where ">>" won't do it by default.
-                // WHD: Not sure if this is an issue in Rust.
-
-                // TODO: Use checked_shr
-                if value.int < 0 {
-                    value.int = !((!value.int) >> value2.int)
-                } else {
-                    value.int >>= value2.int;
+                // Rust's ">>" on signed integers propagates the sign bit; shifting by
+                // the word size or more leaves just the sign, i.e., 0 or -1.
+                if value2.int < 0 {
+                    return molt_err!("negative shift argument");
                 }
+
+                let shift = value2.int.min(MoltInt::BITS as MoltInt - 1);
+                value.int >>= shift;
             }
             LESS => {
                 let flag = match value.vtype {
-                    Type::Int => value.int < value2.int,
-                    Type::Float => value.flt < value2.flt,
-                    Type::String => value.str < value2.str,
+                    DatumType::Int => value.int < value2.int,
+                    DatumType::Float => value.flt < value2.flt,
+                    DatumType::String => value.str < value2.str,
                 };
 
                 value = if flag { Datum::int(1) } else { Datum::int(0) };
             }
             GREATER => {
                 let flag = match value.vtype {
-                    Type::Int => value.int > value2.int,
-                    Type::Float => value.flt > value2.flt,
-                    Type::String => value.str > value2.str,
+                    DatumType::Int => value.int > value2.int,
+                    DatumType::Float => value.flt > value2.flt,
+                    DatumType::String => value.str > value2.str,
                 };
 
                 value = if flag { Datum::int(1) } else { Datum::int(0) };
             }
             LEQ => {
                 let flag = match value.vtype {
-                    Type::Int => value.int <= value2.int,
-                    Type::Float => value.flt <= value2.flt,
-                    Type::String => value.str <= value2.str,
+                    DatumType::Int => value.int <= value2.int,
+                    DatumType::Float => value.flt <= value2.flt,
+                    DatumType::String => value.str <= value2.str,
                 };
 
                 value = if flag { Datum::int(1) } else { Datum::int(0) };
             }
             GEQ => {
                 let flag = match value.vtype {
-                    Type::Int => value.int >= value2.int,
-                    Type::Float => value.flt >= value2.flt,
-                    Type::String => value.str >= value2.str,
+                    DatumType::Int => value.int >= value2.int,
+                    DatumType::Float => value.flt >= value2.flt,
+                    DatumType::String => value.str >= value2.str,
                 };
 
                 value = if flag { Datum::int(1) } else { Datum::int(0) };
@@ -720,9 +793,9 @@ fn expr_get_value<'a, Ctx: 'static>(
                 // NOTE: comparing floats using == is dangerous; but Tcl leaves that to the
                 // TCL programmer.
                 let flag = match value.vtype {
-                    Type::Int => value.int == value2.int,
-                    Type::Float => value.flt == value2.flt,
-                    Type::String => value.str == value2.str,
+                    DatumType::Int => value.int == value2.int,
+                    DatumType::Float => value.flt == value2.flt,
+                    DatumType::String => value.str == value2.str,
                 };
 
                 value = if flag { Datum::int(1) } else { Datum::int(0) };
@@ -731,9 +804,9 @@ fn expr_get_value<'a, Ctx: 'static>(
                 // NOTE: comparing floats using == is dangerous; but Tcl leaves that to the
                 // TCL programmer.
                 let flag = match value.vtype {
-                    Type::Int => value.int != value2.int,
-                    Type::Float => value.flt != value2.flt,
-                    Type::String => value.str != value2.str,
+                    DatumType::Int => value.int != value2.int,
+                    DatumType::Float => value.flt != value2.flt,
+                    DatumType::String => value.str != value2.str,
                 };
 
                 value = if flag { Datum::int(1) } else { Datum::int(0) };
@@ -778,15 +851,15 @@ fn expr_get_value<'a, Ctx: 'static>(
             // an integer.  Thus we need only consider the possibility of int vs. double
             // for the second value.
             AND => {
-                if value2.vtype == Type::Float {
-                    value2.vtype = Type::Int;
+                if value2.vtype == DatumType::Float {
+                    value2.vtype = DatumType::Int;
                     value2.int = if value2.flt != 0.0 { 1 } else { 0 };
                 }
                 value.int = if value.int != 0 && value2.int != 0 { 1 } else { 0 };
             }
             OR => {
-                if value2.vtype == Type::Float {
-                    value2.vtype = Type::Int;
+                if value2.vtype == DatumType::Float {
+                    value2.vtype = DatumType::Int;
                     value2.int = if value2.flt != 0.0 { 1 } else { 0 };
                 }
                 value.int = if value.int != 0 || value2.int != 0 { 1 } else { 0 };
@@ -1128,6 +1201,7 @@ fn parse_and_eval_script<Ctx: 'static>(
     ctx: &mut EvalPtr,
 ) -> MoltResult {
     // FIRST, skip the '['
+    let open = ctx.mark();
     ctx.skip_char('[');
 
     // NEXT, parse the script up to the matching ']'
@@ -1145,7 +1219,12 @@ fn parse_and_eval_script<Ctx: 'static>(
         if ctx.next_is(']') {
             ctx.next();
         } else {
-            return molt_err_uncompleted!("missing close-bracket");
+            return parser::missing_delimiter(
+                ctx,
+                open,
+                Delimiter::Bracket,
+                "missing close-bracket",
+            );
         }
     }
 
@@ -1184,11 +1263,17 @@ fn expr_math_func<Ctx>(
     info: &mut ExprInfo,
     func_name: &str,
 ) -> DatumResult {
-    // FIRST, is this actually a function?
+    // FIRST, is this actually a function?  Built-in functions come first; then those
+    // added to the interpreter.
     // TODO: this does a linear search of the FUNC_TABLE.  Ultimately, it should probably
-    // be a hash lookup.  And if we want to allow users to add functions, it should be
-    // kept in the Interp.
-    let bfunc = expr_find_func(func_name)?;
+    // be a hash lookup.
+    let bfunc = match expr_find_func(func_name) {
+        Some(bfunc) => bfunc,
+        None if interp.has_math_func(func_name) => {
+            return expr_user_math_func(interp, info, func_name);
+        }
+        None => return molt_err!("unknown math function \"{}\"", func_name),
+    };
 
     // NEXT, get the open paren.
     let _ = expr_lex(interp, info)?;
@@ -1202,7 +1287,7 @@ fn expr_math_func<Ctx>(
 
     if bfunc.num_args == 0 {
         let _ = expr_lex(interp, info)?;
-        if info.token != OPEN_PAREN {
+        if info.token != CLOSE_PAREN {
             return syntax_error(info);
         }
     } else {
@@ -1210,22 +1295,21 @@ fn expr_math_func<Ctx>(
             let arg = expr_get_value(interp, info, -1)?;
 
             // At present we have no string functions.
-            if arg.vtype == Type::String {
+            if arg.vtype == DatumType::String {
                 return molt_err!("argument to math function didn't have numeric value");
             }
 
             // Copy the value to the argument record, converting it if necessary.
-            if arg.vtype == Type::Int {
+            if arg.vtype == DatumType::Int {
                 if bfunc.arg_types[i] == ArgType::Float {
                     args[i] = Datum::float(arg.int as MoltFloat);
                 } else {
                     args[i] = arg;
                 }
             } else {
-                // Type::Float
+                // DatumType::Float
                 if bfunc.arg_types[i] == ArgType::Int {
-                    // TODO: Need to handle overflow?
-                    args[i] = Datum::int(arg.flt as MoltInt);
+                    args[i] = Datum::int(float_to_int(arg.flt)?);
                 } else {
                     args[i] = arg;
                 }
@@ -1255,25 +1339,68 @@ fn expr_math_func<Ctx>(
     }
 
     // NEXT, if we aren't evaluating, return an empty value.
+    info.token = VALUE;
+
     if info.no_eval > 0 {
         return Ok(Datum::none());
     }
 
     // NEXT, invoke the math function.
-    info.token = VALUE;
-    (bfunc.func)(&args)
+    match bfunc.func {
+        MathFunc::Pure(func) => func(&args),
+        MathFunc::Random(func) => func(&mut interp.rng, &args),
+    }
+}
+
+// Find the function in the table of built-in functions.
+fn expr_find_func(func_name: &str) -> Option<&'static BuiltinFunc> {
+    FUNC_TABLE.iter().find(|bfunc| bfunc.name == func_name)
 }
 
-// Find the function in the table.
-// TODO: Really, functions should be registered with the interpreter.
-fn expr_find_func(func_name: &str) -> Result<&'static BuiltinFunc, Exception> {
-    for bfunc in &FUNC_TABLE {
-        if bfunc.name == func_name {
-            return Ok(bfunc);
+/// Parses a call of a math function added to the interpreter, returning the evaluated
+/// value.  Such functions take any number of arguments, of any type.
+fn expr_user_math_func<Ctx>(
+    interp: &mut Interp<Ctx>,
+    info: &mut ExprInfo,
+    func_name: &str,
+) -> DatumResult {
+    // FIRST, get the open paren.
+    let _ = expr_lex(interp, info)?;
+
+    if info.token != OPEN_PAREN {
+        return syntax_error(info);
+    }
+
+    // NEXT, scan off the arguments, if there are any.  An empty argument list is
+    // just the close paren.
+    let mut args: MoltList = Vec::new();
+    info.expr.skip_while(|c| c.is_whitespace());
+
+    if info.expr.is(')') {
+        let _ = expr_lex(interp, info)?;
+    } else {
+        loop {
+            let arg = expr_get_value(interp, info, -1)?;
+            args.push(Value::from(arg));
+
+            match info.token {
+                CLOSE_PAREN => break,
+                COMMA => (),
+                _ => return syntax_error(info),
+            }
         }
     }
 
-    molt_err!("unknown math function \"{}\"", func_name)
+    // NEXT, if we aren't evaluating, return an empty value.
+    info.token = VALUE;
+
+    if info.no_eval > 0 {
+        return Ok(Datum::none());
+    }
+
+    // NEXT, invoke the math function, and get its result as a number if possible.
+    let result = interp.call_math_func(func_name, &args)?;
+    expr_parse_value(&result)
 }
 
 /// If the value already has a numeric data rep, just gets it as a Datum; otherwise,
@@ -1281,7 +1408,7 @@ fn expr_find_func(func_name: &str) -> Result<&'static BuiltinFunc, Exception> {
 ///
 /// NOTE: We don't just use `Value::as_float` or `Value::as_int`, as those expect
 /// to parse strings with no extra whitespace.  (That may be a bug.)
-fn expr_parse_value(value: &Value) -> DatumResult {
+pub(crate) fn expr_parse_value(value: &Value) -> DatumResult {
     match value.already_number() {
         Some(datum) => Ok(datum),
         _ => expr_parse_string(value.as_str()),
@@ -1340,8 +1467,8 @@ fn expr_parse_string(string: &str) -> DatumResult {
 // Converts values to strings for string comparisons.
 fn expr_as_str(value: Datum) -> Datum {
     match value.vtype {
-        Type::Int => Datum::string(&format!("{}", value.int)),
-        Type::Float => Datum::string(&format!("{}", value.flt)),
+        DatumType::Int => Datum::string(&format!("{}", value.int)),
+        DatumType::Float => Datum::string(&format!("{}", value.flt)),
         _ => value,
     }
 }
@@ -1371,9 +1498,9 @@ fn expr_looks_like_int<'a>(ptr: &Tokenizer<'a>) -> bool {
 impl Datum {
     fn is_numeric(&self) -> bool {
         match self.vtype {
-            Type::Int => true,
-            Type::Float => true,
-            Type::String => false,
+            DatumType::Int => true,
+            DatumType::Float => true,
+            DatumType::String => false,
         }
     }
 }
@@ -1381,25 +1508,23 @@ impl Datum {
 #[allow(clippy::collapsible_if)]
 fn expr_abs_func(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult {
     let arg = &args[0];
-    if arg.vtype == Type::Float {
+    if arg.vtype == DatumType::Float {
         if arg.flt < 0.0 {
             Ok(Datum::float(-arg.flt))
         } else {
             Ok(Datum::float(arg.flt))
         }
     } else {
-        // TODO: need to handle integer overflow here.
-        if arg.int < 0 {
-            Ok(Datum::int(-arg.int))
-        } else {
-            Ok(Datum::int(arg.int))
+        match arg.int.checked_abs() {
+            Some(int) => Ok(Datum::int(int)),
+            None => molt_err!("integer overflow"),
         }
     }
 }
 
 fn expr_double_func(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult {
     let arg = &args[0];
-    if arg.vtype == Type::Float {
+    if arg.vtype == DatumType::Float {
         Ok(Datum::float(arg.flt))
     } else {
         Ok(Datum::float(arg.int as MoltFloat))
@@ -1408,23 +1533,44 @@ fn expr_double_func(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult {
 
 fn expr_int_func(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult {
     let arg = &args[0];
-    if arg.vtype == Type::Int {
+    if arg.vtype == DatumType::Int {
         Ok(Datum::int(arg.int))
     } else {
-        // TODO: need to handle integer overflow here.
-        Ok(Datum::int(arg.flt as MoltInt))
+        Ok(Datum::int(float_to_int(arg.flt)?))
     }
 }
 
+fn expr_rand_func(rng: &mut Rng, _args: &[Datum; MAX_MATH_ARGS]) -> DatumResult {
+    Ok(Datum::float(rng.next_float()))
+}
+
+// Reseeds the generator and returns the first value of the new sequence.
+fn expr_srand_func(rng: &mut Rng, args: &[Datum; MAX_MATH_ARGS]) -> DatumResult {
+    rng.seed(args[0].int);
+    Ok(Datum::float(rng.next_float()))
+}
+
 fn expr_round_func(args: &[Datum; MAX_MATH_ARGS]) -> DatumResult {
-    // TODO: need to handle integer overflow here.
     let arg = &args[0];
-    if arg.vtype == Type::Int {
+    if arg.vtype == DatumType::Int {
         Ok(Datum::int(arg.int))
     } else if arg.flt < 0.0 {
-        Ok(Datum::int((arg.flt - 0.5) as MoltInt))
+        Ok(Datum::int(float_to_int(arg.flt - 0.5)?))
+    } else {
+        Ok(Datum::int(float_to_int(arg.flt + 0.5)?))
+    }
+}
+
+// Truncates a float to an integer.  A float out of the integer range, or not a number at
+// all, is an overflow rather than being clamped to the range.
+fn float_to_int(flt: MoltFloat) -> Result<MoltInt, Exception> {
+    // -MoltInt::MIN, the least float too large to convert.
+    const LIMIT: MoltFloat = -(MoltInt::MIN as MoltFloat);
+
+    if (-LIMIT..LIMIT).contains(&flt) {
+        Ok(flt as MoltInt)
     } else {
-        Ok(Datum::int((arg.flt + 0.5) as MoltInt))
+        molt_err!("integer overflow")
     }
 }
 
@@ -1434,8 +1580,8 @@ fn syntax_error(info: &mut ExprInfo) -> DatumResult {
 }
 
 // Return standard illegal type error
-fn illegal_type(bad_type: Type, op: i32) -> DatumResult {
-    let type_str = if bad_type == Type::Float {
+fn illegal_type(bad_type: DatumType, op: i32) -> DatumResult {
+    let type_str = if bad_type == DatumType::Float {
         "floating-point value"
     } else {
         "non-numeric string"
@@ -1464,9 +1610,9 @@ fn illegal_type(bad_type: Type, op: i32) -> DatumResult {
 //     }
 
 //     match &val1.vtype {
-//       Type::Int => val1.int == val2.int,
-//       Type::Float => val1.flt == val2.flt,
-//       Type::String => val1.str == val2.str,
+//       DatumType::Int => val1.int == val2.int,
+//       DatumType::Float => val1.flt == val2.flt,
+//       DatumType::String => val1.str == val2.str,
 //     }
 //   }
 
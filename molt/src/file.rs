@@ -0,0 +1,456 @@
+//! The `file` Command
+//!
+//! This module, enabled by the `file-io` feature, defines the `file` command, which
+//! manipulates file names and the file system.  Add it to an interpreter as an extra
+//! native command, along with the file channel commands:
+//!
+//! ```
+//! # use molt_forked::prelude::*;
+//! let command = gen_command!((), [(_FILE, cmd_file), (_GLOB, cmd_glob)], []);
+//! ```
+//!
+//! File names use `/` as the separator on all platforms, as in Standard TCL; the name
+//! manipulation subcommands work on the strings alone, and don't touch the file system.
+//! The `glob` command, also defined here, matches file names against patterns using the
+//! same matcher as `string match`.
+use crate::channel::describe;
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::util;
+use crate::value::Value;
+use std::fs;
+use std::path::Path;
+
+/// The name of the `file` command.
+pub const _FILE: &str = "file";
+
+/// The name of the `glob` command.
+pub const _GLOB: &str = "glob";
+
+/// # file *subcommand* ?*arg* ...?
+///
+/// Manipulates file names and the file system.
+///
+/// * `file delete ?-force? ?--? ?name ...?`: deletes the files and empty directories;
+///   with `-force`, directories are deleted with their contents.
+/// * `file dirname name`: returns all but the last component of the name.
+/// * `file exists name`: returns 1 if the file exists, and 0 otherwise.
+/// * `file extension name`: returns the extension of the name's last component.
+/// * `file isdirectory name`, `file isfile name`: returns 1 if the file is a directory,
+///   or a regular file.
+/// * `file join name ?name ...?`: joins the names with `/`.
+/// * `file mkdir ?dir ...?`: creates the directories and any missing parents.
+/// * `file tail name`: returns the last component of the name.
+pub fn cmd_file<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("delete", cmd_file_delete),
+            ("dirname", cmd_file_dirname),
+            ("exists", cmd_file_exists),
+            ("extension", cmd_file_extension),
+            ("isdirectory", cmd_file_isdirectory),
+            ("isfile", cmd_file_isfile),
+            ("join", cmd_file_join),
+            ("mkdir", cmd_file_mkdir),
+            ("tail", cmd_file_tail),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// # file delete ?-force? ?--? ?*name* ...?
+fn cmd_file_delete<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 0, "?-force? ?--? ?name ...?")?;
+
+    let mut force = false;
+    let mut argi = 2;
+
+    while let Some(opt) = argv.get(argi).map(|arg| arg.as_str()) {
+        match opt {
+            "-force" => force = true,
+            "--" => {
+                argi += 1;
+                break;
+            }
+            _ if opt.starts_with('-') => {
+                return molt_err!("bad option \"{}\": must be -force or --", opt);
+            }
+            _ => break,
+        }
+        argi += 1;
+    }
+
+    for name in &argv[argi..] {
+        let path = Path::new(name.as_str());
+
+        // A missing file is already deleted; don't follow symbolic links.
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            continue;
+        };
+
+        let result = if !metadata.is_dir() {
+            fs::remove_file(path)
+        } else if force {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_dir(path)
+        };
+
+        if let Err(e) = result {
+            return molt_err!("error deleting \"{}\": {}", name, describe(&e));
+        }
+    }
+
+    molt_ok!()
+}
+
+/// # file dirname *name*
+fn cmd_file_dirname<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "name")?;
+    molt_ok!(dirname(argv[2].as_str()))
+}
+
+/// # file exists *name*
+fn cmd_file_exists<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "name")?;
+    molt_ok!(Path::new(argv[2].as_str()).exists())
+}
+
+/// # file extension *name*
+fn cmd_file_extension<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "name")?;
+    molt_ok!(extension(argv[2].as_str()))
+}
+
+/// # file isdirectory *name*
+fn cmd_file_isdirectory<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "name")?;
+    molt_ok!(Path::new(argv[2].as_str()).is_dir())
+}
+
+/// # file isfile *name*
+fn cmd_file_isfile<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "name")?;
+    molt_ok!(Path::new(argv[2].as_str()).is_file())
+}
+
+/// # file join *name* ?*name* ...?
+fn cmd_file_join<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 0, "name ?name ...?")?;
+    molt_ok!(join(argv[2..].iter().map(|name| name.as_str())))
+}
+
+/// # file mkdir ?*dir* ...?
+fn cmd_file_mkdir<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 0, "?dir ...?")?;
+
+    for name in &argv[2..] {
+        let path = Path::new(name.as_str());
+
+        if path.is_dir() {
+            continue;
+        }
+
+        if path.exists() {
+            return molt_err!("can't create directory \"{}\": file already exists", name);
+        }
+
+        if let Err(e) = fs::create_dir_all(path) {
+            return molt_err!("can't create directory \"{}\": {}", name, describe(&e));
+        }
+    }
+
+    molt_ok!()
+}
+
+/// # file tail *name*
+fn cmd_file_tail<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "name")?;
+    molt_ok!(tail(argv[2].as_str()))
+}
+
+/// # glob ?-nocomplain? ?-directory *dir*? ?--? *pattern* ?*pattern* ...?
+///
+/// Returns a list of the existing files whose names match any of the patterns.  Each
+/// pattern component is matched as by `string match`, with `{a,b}` alternatives; a
+/// component that doesn't begin with `.` doesn't match hidden files.  A pattern ending in
+/// `/` matches only directories.  With `-directory`, relative patterns are matched in
+/// *dir*, and the results include it.  It's an error if nothing matches, unless
+/// `-nocomplain` is given.
+pub fn cmd_glob<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "?-nocomplain? ?-directory dir? ?--? pattern ?pattern ...?";
+    check_args(1, argv, 2, 0, USAGE)?;
+
+    let mut nocomplain = false;
+    let mut directory: Option<&str> = None;
+    let mut argi = 1;
+
+    while let Some(opt) = argv.get(argi).map(|arg| arg.as_str()) {
+        match opt {
+            "-nocomplain" => nocomplain = true,
+            "-directory" => {
+                let Some(dir) = argv.get(argi + 1) else {
+                    return molt_err!("missing argument to \"-directory\"");
+                };
+                directory = Some(dir.as_str());
+                argi += 1;
+            }
+            "--" => {
+                argi += 1;
+                break;
+            }
+            _ if opt.starts_with('-') => {
+                return molt_err!(
+                    "bad option \"{}\": must be -directory, -nocomplain, or --",
+                    opt
+                );
+            }
+            _ => break,
+        }
+        argi += 1;
+    }
+
+    let patterns = &argv[argi..];
+    if patterns.is_empty() {
+        return molt_err!("wrong # args: should be \"{} {}\"", argv[0], USAGE);
+    }
+
+    let mut result: MoltList = Vec::new();
+    for pattern in patterns {
+        for alternative in expand_braces(pattern.as_str())? {
+            result.extend(glob(directory, &alternative).into_iter().map(Value::from));
+        }
+    }
+
+    if result.is_empty() && !nocomplain {
+        let patterns: Vec<&str> = patterns.iter().map(|p| p.as_str()).collect();
+        let plural = if patterns.len() == 1 { "" } else { "s" };
+        return molt_err!(
+            "no files matched glob pattern{} \"{}\"",
+            plural,
+            patterns.join(" ")
+        );
+    }
+
+    molt_ok!(result)
+}
+
+/// Expands the pattern's `{a,b,...}` alternatives, which may nest, into the list of
+/// patterns without them.
+fn expand_braces(pattern: &str) -> Result<Vec<String>, Exception> {
+    let bytes = pattern.as_bytes();
+    let mut i = 0;
+
+    // Find the first unescaped open brace.
+    while i < bytes.len() && bytes[i] != b'{' {
+        i += if bytes[i] == b'\\' { 2 } else { 1 };
+    }
+    if i >= bytes.len() {
+        return Ok(vec![pattern.to_string()]);
+    }
+
+    // Find its close brace, splitting the alternatives at top-level commas.
+    let open = i;
+    let mut depth = 0;
+    let mut starts = vec![open + 1];
+    let mut close = None;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            b',' if depth == 1 => starts.push(i + 1),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let Some(close) = close else {
+        return molt_err!("unmatched open-brace in file name");
+    };
+
+    let prefix = &pattern[..open];
+    let suffix = &pattern[close + 1..];
+    let mut ends: Vec<usize> = starts[1..].iter().map(|start| start - 1).collect();
+    ends.push(close);
+
+    let mut expanded = Vec::new();
+    for (start, end) in starts.into_iter().zip(ends) {
+        let alternative = format!("{}{}{}", prefix, &pattern[start..end], suffix);
+        expanded.extend(expand_braces(&alternative)?);
+    }
+    Ok(expanded)
+}
+
+/// Returns the sorted names of the existing files that match the brace-free pattern,
+/// relative to the directory if the pattern is relative.
+fn glob(directory: Option<&str>, pattern: &str) -> Vec<String> {
+    let (absolute, parts) = components(pattern);
+    let dirs_only = pattern.ends_with('/');
+
+    let mut names = vec![match (absolute, directory) {
+        (true, _) => "/".to_string(),
+        (false, Some(dir)) => dir.to_string(),
+        (false, None) => String::new(),
+    }];
+
+    for part in parts {
+        let mut matched = Vec::new();
+
+        for name in &names {
+            if !is_wild(part) {
+                matched.push(join_name(name, &unescape(part)));
+                continue;
+            }
+
+            let dir = if name.is_empty() { "." } else { name.as_str() };
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+
+            let mut found: Vec<String> = entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|entry| !entry.starts_with('.') || part.starts_with('.'))
+                .filter(|entry| util::glob_match(part, entry))
+                .collect();
+            found.sort();
+            matched.extend(found.iter().map(|entry| join_name(name, entry)));
+        }
+
+        names = matched;
+    }
+
+    names.retain(|name| match fs::metadata(name) {
+        Ok(metadata) => !dirs_only || metadata.is_dir(),
+        Err(_) => false,
+    });
+    if dirs_only {
+        names.iter_mut().for_each(|name| name.push('/'));
+    }
+    names
+}
+
+/// Returns true if the pattern component contains any glob wildcards.
+fn is_wild(part: &str) -> bool {
+    part.contains(['*', '?', '['])
+}
+
+/// Removes the backslash escapes from a pattern component with no wildcards, leaving
+/// the literal file name.
+fn unescape(part: &str) -> String {
+    let mut result = String::new();
+    let mut chars = part.chars();
+    while let Some(c) = chars.next() {
+        result.push(if c == '\\' { chars.next().unwrap_or(c) } else { c });
+    }
+    result
+}
+
+/// Appends an entry name to a directory name, which may be empty or end in `/`.
+fn join_name(dir: &str, entry: &str) -> String {
+    if dir.is_empty() {
+        entry.to_string()
+    } else if dir.ends_with('/') {
+        format!("{}{}", dir, entry)
+    } else {
+        format!("{}/{}", dir, entry)
+    }
+}
+
+/// Returns the name's components, ignoring empty ones, and whether it is absolute.
+fn components(name: &str) -> (bool, Vec<&str>) {
+    (name.starts_with('/'), name.split('/').filter(|part| !part.is_empty()).collect())
+}
+
+/// Joins the names with `/`.  An absolute name discards the names before it.
+fn join<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    let mut absolute = false;
+    let mut parts: Vec<&str> = Vec::new();
+
+    for name in names {
+        let (is_absolute, name_parts) = components(name);
+        if is_absolute {
+            absolute = true;
+            parts.clear();
+        }
+        parts.extend(name_parts);
+    }
+
+    let joined = parts.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+/// Returns all but the last component of the name: `.` for a relative name with one
+/// component, and `/` for an absolute one.
+fn dirname(name: &str) -> String {
+    let (absolute, mut parts) = components(name);
+    parts.pop();
+
+    match (absolute, parts.is_empty()) {
+        (true, _) => format!("/{}", parts.join("/")),
+        (false, true) => ".".into(),
+        (false, false) => parts.join("/"),
+    }
+}
+
+/// Returns the last component of the name, or the empty string for `/`.
+fn tail(name: &str) -> &str {
+    components(name).1.pop().unwrap_or("")
+}
+
+/// Returns the extension of the name's last component, from its last `.`, or the empty
+/// string if it has none.
+fn extension(name: &str) -> &str {
+    let tail = tail(name);
+    tail.rfind('.').map_or("", |i| &tail[i..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(join(["a", "b/", "c"].into_iter()), "a/b/c");
+        assert_eq!(join(["/a", "b"].into_iter()), "/a/b");
+        assert_eq!(join(["a", "/b", "c"].into_iter()), "/b/c");
+        assert_eq!(join(["a//b", ""].into_iter()), "a/b");
+
+        assert_eq!(dirname("a/b/c"), "a/b");
+        assert_eq!(dirname("a/b/"), "a");
+        assert_eq!(dirname("c"), ".");
+        assert_eq!(dirname("/a"), "/");
+        assert_eq!(dirname("/"), "/");
+
+        assert_eq!(tail("a/b/c"), "c");
+        assert_eq!(tail("a/b/"), "b");
+        assert_eq!(tail("/"), "");
+
+        assert_eq!(extension("dir/foo.tar.gz"), ".gz");
+        assert_eq!(extension("dir.d/foo"), "");
+        assert_eq!(extension(".bashrc"), ".bashrc");
+    }
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("a*").unwrap(), vec!["a*"]);
+        assert_eq!(expand_braces("x.{c,h}").unwrap(), vec!["x.c", "x.h"]);
+        assert_eq!(expand_braces("{a,b{1,2}}/z").unwrap(), vec!["a/z", "b1/z", "b2/z"]);
+        assert_eq!(expand_braces("\\{a,b}").unwrap(), vec!["\\{a,b}"]);
+        assert_eq!(expand_braces("{a,}").unwrap(), vec!["a", ""]);
+        assert!(expand_braces("{a,b").is_err());
+    }
+}
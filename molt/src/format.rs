@@ -0,0 +1,401 @@
+//! String Formatting
+//!
+//! The `sprintf`-style formatting used by the `format` command.  A format string contains
+//! literal text and conversion specifiers:
+//!
+//! ```text
+//! %[n$][flags][width][.precision][size]conversion
+//! ```
+//!
+//! * `n$`: formats the *n*th argument, counting from 1, rather than the next one.  If one
+//!   specifier uses it, all must.
+//! * Flags: `-` left-justifies the field; `0` pads it with zeros; `+` always shows the
+//!   sign of a number; a space shows a space for a positive number's sign; `#` selects an
+//!   alternate form: a `0x`, `0`, or `0b` prefix for `%x`, `%o`, and `%b`, and a decimal
+//!   point, or trailing zeros, for `%f`, `%e`, and `%g`.
+//! * The width and precision are integers, or `*` to take them from the next argument.
+//!   Neither may be more than 1,000,000.
+//! * The size modifiers `h`, `l`, `ll`, and `L` are accepted and ignored; integers are always
+//!   64 bits.
+//! * Conversions: `d` and `i` (decimal), `u` (unsigned decimal), `o` (octal), `x` and `X`
+//!   (hexadecimal), `b` (binary), `c` (the character with the given code point), `s`
+//!   (string), `f` (fixed-point), `e` and `E` (exponential), `g` and `G` (the shorter of
+//!   fixed-point and exponential), and `%` (a literal `%`).
+
+use crate::molt_err;
+use crate::types::*;
+use crate::value::Value;
+
+/// The largest field width or precision.  Anything larger is surely a mistake, and would
+/// only make `format` try to allocate more memory than there is.
+const MAX_COUNT: usize = 1_000_000;
+
+/// A conversion specifier's flags, width, and precision.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+/// Formats the arguments according to the format string.
+pub(crate) fn format(fmt: &str, args: &[Value]) -> Result<String, Exception> {
+    let mut result = String::with_capacity(fmt.len());
+    let mut chars = fmt.chars().peekable();
+    let mut next_arg = 0;
+
+    // Whether the specifiers use `n$` positions; unknown until the first one.
+    let mut positional: Option<bool> = None;
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            result.push('%');
+            continue;
+        }
+
+        // FIRST, the position, if any.  Digits here are either a position or the width.
+        let mut spec = Spec::default();
+        let mut digits = String::new();
+        while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+            digits.push(d);
+            chars.next();
+        }
+
+        let is_positional = chars.peek() == Some(&'$');
+        if is_positional {
+            chars.next();
+        }
+        if *positional.get_or_insert(is_positional) != is_positional {
+            return molt_err!("cannot mix \"%\" and \"%n$\" conversion specifiers");
+        }
+
+        if is_positional {
+            let n: usize = digits.parse().unwrap_or(0);
+            if n == 0 || n > args.len() {
+                return molt_err!("\"%n$\" argument index out of range");
+            }
+            next_arg = n - 1;
+            digits.clear();
+        }
+
+        // NEXT, the flags.  A leading `0` read as a width above is the zero flag.
+        if digits.starts_with('0') {
+            spec.zero = true;
+            digits = digits.trim_start_matches('0').to_string();
+        }
+
+        if digits.is_empty() {
+            while let Some(&flag) = chars.peek() {
+                match flag {
+                    '-' => spec.left = true,
+                    '0' => spec.zero = true,
+                    '+' => spec.plus = true,
+                    ' ' => spec.space = true,
+                    '#' => spec.alt = true,
+                    _ => break,
+                }
+                chars.next();
+            }
+
+            // NEXT, the width.
+            if chars.peek() == Some(&'*') {
+                chars.next();
+                let width = next_int(args, &mut next_arg)?;
+                spec.left |= width < 0;
+                spec.width = check_count(width.unsigned_abs())?;
+            } else {
+                while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                    chars.next();
+                }
+            }
+        }
+
+        if !digits.is_empty() {
+            spec.width = parse_count(&digits)?;
+        }
+
+        // NEXT, the precision.
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            if chars.peek() == Some(&'*') {
+                chars.next();
+                let precision = next_int(args, &mut next_arg)?.max(0);
+                spec.precision = Some(check_count(precision.unsigned_abs())?);
+            } else {
+                let mut digits = String::new();
+                while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                    chars.next();
+                }
+                spec.precision =
+                    Some(if digits.is_empty() { 0 } else { parse_count(&digits)? });
+            }
+        }
+
+        // NEXT, skip any size modifier.
+        while let Some('h' | 'l' | 'L') = chars.peek() {
+            chars.next();
+        }
+
+        // NEXT, the conversion.
+        let Some(conversion) = chars.next() else {
+            return molt_err!("format string ended in middle of field specifier");
+        };
+
+        let field = match conversion {
+            'd' | 'i' => signed(&spec, next_value(args, &mut next_arg)?.as_int()?),
+            'u' => {
+                unsigned(&spec, next_value(args, &mut next_arg)?.as_int()? as u64, 10, "")
+            }
+            'o' => {
+                unsigned(&spec, next_value(args, &mut next_arg)?.as_int()? as u64, 8, "0")
+            }
+            'x' => unsigned(
+                &spec,
+                next_value(args, &mut next_arg)?.as_int()? as u64,
+                16,
+                "0x",
+            ),
+            'X' => unsigned(
+                &spec,
+                next_value(args, &mut next_arg)?.as_int()? as u64,
+                16,
+                "0X",
+            ),
+            'b' => unsigned(
+                &spec,
+                next_value(args, &mut next_arg)?.as_int()? as u64,
+                2,
+                "0b",
+            ),
+            'c' => {
+                let code = next_value(args, &mut next_arg)?.as_int()?;
+                let c = u32::try_from(code).ok().and_then(char::from_u32);
+                pad(&spec, "", &c.unwrap_or('\u{FFFD}').to_string(), false)
+            }
+            's' => {
+                let value = next_value(args, &mut next_arg)?;
+                match spec.precision {
+                    Some(precision) => {
+                        let s: String = value.as_str().chars().take(precision).collect();
+                        pad(&spec, "", &s, false)
+                    }
+                    None => pad(&spec, "", value.as_str(), false),
+                }
+            }
+            'f' | 'e' | 'E' | 'g' | 'G' => {
+                float(&spec, next_value(args, &mut next_arg)?.as_float()?, conversion)
+            }
+            _ => return molt_err!("bad field specifier \"{}\"", conversion),
+        };
+
+        result.push_str(&field);
+    }
+
+    Ok(result)
+}
+
+/// Returns the next argument.
+fn next_value<'a>(
+    args: &'a [Value],
+    next_arg: &mut usize,
+) -> Result<&'a Value, Exception> {
+    match args.get(*next_arg) {
+        Some(value) => {
+            *next_arg += 1;
+            Ok(value)
+        }
+        None => molt_err!("not enough arguments for all format specifiers"),
+    }
+}
+
+/// Returns the next argument as an integer, for a `*` width or precision.
+fn next_int(args: &[Value], next_arg: &mut usize) -> Result<MoltInt, Exception> {
+    next_value(args, next_arg)?.as_int()
+}
+
+/// Parses a width or precision.
+fn parse_count(digits: &str) -> Result<usize, Exception> {
+    match digits.parse() {
+        Ok(count) if count <= MAX_COUNT => Ok(count),
+        _ => molt_err!("field width or precision \"{}\" is too large", digits),
+    }
+}
+
+/// Checks a width or precision taken from an argument.
+fn check_count(count: u64) -> Result<usize, Exception> {
+    if count <= MAX_COUNT as u64 {
+        Ok(count as usize)
+    } else {
+        molt_err!("field width or precision \"{}\" is too large", count)
+    }
+}
+
+/// Formats a signed decimal integer.
+fn signed(spec: &Spec, int: MoltInt) -> String {
+    let sign = if int < 0 {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+
+    let digits = int_digits(spec, int.unsigned_abs().to_string());
+    pad(spec, sign, &digits, spec.precision.is_none())
+}
+
+/// Formats an integer, taken as unsigned, in the radix, with the alternate form's prefix.
+fn unsigned(spec: &Spec, int: u64, radix: u32, prefix: &str) -> String {
+    let digits = match radix {
+        2 => format!("{:b}", int),
+        8 => format!("{:o}", int),
+        16 if prefix == "0X" => format!("{:X}", int),
+        16 => format!("{:x}", int),
+        _ => int.to_string(),
+    };
+
+    // The octal prefix is only needed if the number doesn't already start with a 0.
+    let prefix = match prefix {
+        _ if !spec.alt || int == 0 => "",
+        "0" if spec.precision.is_some_and(|p| p > digits.len()) => "",
+        prefix => prefix,
+    };
+
+    let digits = int_digits(spec, digits);
+    pad(spec, prefix, &digits, spec.precision.is_none())
+}
+
+/// Pads an integer's digits with leading zeros to the precision, the minimum number of
+/// digits.
+fn int_digits(spec: &Spec, digits: String) -> String {
+    match spec.precision {
+        Some(0) if digits == "0" => String::new(),
+        Some(precision) if precision > digits.len() => {
+            format!("{}{}", "0".repeat(precision - digits.len()), digits)
+        }
+        _ => digits,
+    }
+}
+
+/// Formats a floating-point number with the `f`, `e`, or `g` conversion.
+fn float(spec: &Spec, flt: MoltFloat, conversion: char) -> String {
+    let sign = if flt.is_sign_negative() && !flt.is_nan() {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+
+    if !flt.is_finite() {
+        let text = if flt.is_nan() { "NaN" } else { "Inf" };
+        return pad(spec, sign, text, false);
+    }
+
+    let flt = flt.abs();
+    let precision = spec.precision.unwrap_or(6);
+
+    let mut text = match conversion {
+        'f' => format!("{:.*}", precision, flt),
+        'e' | 'E' => exponential(flt, precision),
+        _ => {
+            // %g uses %e if the exponent is less than -4 or at least the precision,
+            // and %f otherwise, with the precision counting significant digits.
+            let precision = precision.max(1);
+            let exp = exponent(flt, precision - 1);
+            let mut text = if exp < -4 || exp >= precision as i32 {
+                exponential(flt, precision - 1)
+            } else {
+                format!("{:.*}", (precision as i32 - 1 - exp) as usize, flt)
+            };
+            if !spec.alt {
+                text = trim_zeros(&text);
+            }
+            text
+        }
+    };
+
+    if spec.alt && !text.contains('.') {
+        match text.find('e') {
+            Some(i) => text.insert(i, '.'),
+            None => text.push('.'),
+        }
+    }
+
+    if conversion.is_ascii_uppercase() {
+        text = text.to_uppercase();
+    }
+
+    pad(spec, sign, &text, true)
+}
+
+/// Formats a non-negative number as `d.ddde+XX`, with the precision digits after the
+/// decimal point and at least two exponent digits.
+fn exponential(flt: MoltFloat, precision: usize) -> String {
+    let text = format!("{:.*e}", precision, flt);
+    let (mantissa, exp) = text.split_once('e').expect("exponential format");
+    let exp: i32 = exp.parse().expect("exponent");
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exp.abs())
+}
+
+/// Returns the decimal exponent of the number, once rounded to the precision digits after
+/// the decimal point in exponential form.
+fn exponent(flt: MoltFloat, precision: usize) -> i32 {
+    let text = format!("{:.*e}", precision, flt);
+    let (_, exp) = text.split_once('e').expect("exponential format");
+    exp.parse().expect("exponent")
+}
+
+/// Removes trailing zeros, and then any trailing decimal point, from the fraction of a
+/// `%g` conversion.
+fn trim_zeros(text: &str) -> String {
+    let (number, exp) = match text.find('e') {
+        Some(i) => text.split_at(i),
+        None => (text, ""),
+    };
+
+    if !number.contains('.') {
+        return text.to_string();
+    }
+
+    format!("{}{}", number.trim_end_matches('0').trim_end_matches('.'), exp)
+}
+
+/// Pads the field to the width.  If `zero_fill` is true, i.e., for a finite number, and an
+/// integer has no precision, the field is padded with zeros after its sign or prefix if
+/// the zero flag is given and it's right-justified.
+fn pad(spec: &Spec, prefix: &str, body: &str, zero_fill: bool) -> String {
+    let len = prefix.chars().count() + body.chars().count();
+
+    if len >= spec.width {
+        return format!("{}{}", prefix, body);
+    }
+
+    let fill = spec.width - len;
+    let zero = spec.zero && zero_fill && !spec.left;
+
+    if spec.left {
+        format!("{}{}{}", prefix, body, " ".repeat(fill))
+    } else if zero {
+        format!("{}{}{}", prefix, "0".repeat(fill), body)
+    } else {
+        format!("{}{}{}", " ".repeat(fill), prefix, body)
+    }
+}
@@ -18,6 +18,8 @@
 //! The [`Interp`] class (and the rest of Molt) is intended for use in a single thread.  It is
 //! safe to have `Interps` in different threads; but use `String` (or another `Sync`)
 //! when passing data between them.  In particular, [`Value`] is not `Sync`.
+//! With the `threads` feature, the `worker` command in the `worker` module provides
+//! interpreters on other threads that communicate in just this way.
 //!
 //! # Creating an Interpreter
 //!
@@ -27,13 +29,12 @@
 //! application-specific commands.
 //!
 //! Alternatively, [`Interp::empty`](struct.Interp.html#method.empty) creates an interpreter
-//! with no built-in commands, allowing the application to define only those commands it needs.
-//! Such an empty interpreter can be configured as the parser for data and configuration files,
+//! with no built-in commands.  To define only those commands the application needs, pass a
+//! dispatcher built by [`gen_command_minimal!`](../macro.gen_command_minimal.html) to
+//! `Interp::new`; it can include individual standard commands as well as embedded ones.
+//! Such an interpreter can be configured as the parser for data and configuration files,
 //! or as the basis for a simple console command set.
 //!
-//! **TODO**: Define a way to add various subsets of the standard commands to an initially
-//! empty interpreter.
-//!
 //! ```
 //! use molt::Interp;
 //! let mut interp = Interp::default();
@@ -134,26 +135,27 @@
 //! The usual reason for embedding Molt in an application is to extend it with
 //! application-specific commands.  There are several ways to do this.
 //!
-//! The simplest method, and the one used by most of Molt's built-in commands, is to define a
-//! [`CommandFunc`] and register it with the interpreter using the
-//! [`Interp::add_command`](struct.Interp.html#method.add_command) method. A `CommandFunc` is
-//! simply a Rust function that returns a [`MoltResult`] given an interpreter and a slice of Molt
-//! [`Value`] objects representing the command name and its arguments. The function may interpret
-//! the array of arguments in any way it likes.
+//! The usual method, and the one used by Molt's built-in commands, is to define a Rust
+//! function that returns a [`MoltResult`] given an interpreter and a slice of Molt
+//! [`Value`] objects representing the command name and its arguments, and list it in the
+//! [`gen_command!`](../macro.gen_command.html) that builds the interpreter's dispatcher.
+//! The function may interpret the array of arguments in any way it likes.
+//!
+//! To add a command while the application runs, e.g., an object command or a plugin's
+//! command, wrap the function or a closure in a [`CommandFunc`] and register it with the
+//! [`Interp::add_command`](struct.Interp.html#method.add_command) method.
 //!
 //! The following example defines a command called `square` that squares an integer value.
 //!
 //! ```
-//! use molt::Interp;
-//! use molt::check_args;
-//! use molt::molt_ok;
-//! use molt::types::*;
+//! # use molt_forked::prelude::*;
+//! use std::rc::Rc;
 //!
 //! # let _ = dummy();
 //! # fn dummy() -> MoltResult {
 //! // FIRST, create the interpreter and add the needed command.
 //! let mut interp = Interp::default();
-//! interp.add_command("square", cmd_square);
+//! interp.add_command("square", Rc::new(cmd_square));
 //!
 //! // NEXT, try using the new command.
 //! let val = interp.eval("square 5")?;
@@ -162,17 +164,17 @@
 //! # }
 //!
 //! // The command: square intValue
-//! fn cmd_square(_: &mut Interp, _: &[ContextID], argv: &[Value]) -> MoltResult {
+//! fn cmd_square(_: &mut Interp<()>, argv: &[Value]) -> MoltResult {
 //!     // FIRST, check the number of arguments.  Returns an appropriate error
 //!     // for the wrong number of arguments.
 //!     check_args(1, argv, 2, 2, "intValue")?;
 //!
 //!     // NEXT, get the intValue argument as an int.  Returns an appropriate error
 //!     // if the argument can't be interpreted as an integer.
-//!     let intValue = argv[1].as_int()?;
+//!     let int_value = argv[1].as_int()?;
 //!
 //!     // NEXT, return the product.
-//!     molt_ok!(intValue * intValue)
+//!     molt_ok!(int_value * int_value)
 //! }
 //! ```
 //!
@@ -270,31 +272,32 @@
 //! extension-specific data.
 //!
 //! All of these patterns (and others) are implemented by means of the interpreter's
-//! _context cache_, which is a means of relating mutable data to a particular command or
+//! context and its _context cache_, which relate mutable data to a particular command or
 //! family of commands.  See below.
 //!
 //! # Commands and the Context Cache
 //!
 //! Most Molt commands require access only to the Molt interpreter in order to do their
 //! work.  Some need mutable or immutable access to command-specific data (which is often
-//! application-specific data).  This is provided by means of the interpreter's
-//! _context cache_:
+//! application-specific data).  The application's own data goes in the interpreter's
+//! context, `interp.context`, whose type is the interpreter's `Ctx` type parameter.  An
+//! extension crate can't add to that type, so its data goes in the interpreter's
+//! _context cache_ instead:
 //!
-//! * The interpreter is asked for a new `ContextID`, an ID that is unique in that interpreter.
-//!
-//! * The client associates the context ID with a new instance of a context data structure,
-//!   usually a struct.  This data structure is added to the context cache.
+//! * The extension defines a context data structure, usually a struct, of a type of its
+//!   own.
 //!
 //!   * This struct may contain the data required by the command(s), or keys allowing it
 //!     to access the data elsewhere.
 //!
-//! * The `ContextID` (can be multiple, provided as a slice) is provided to the interpreter when adding commands that require that
-//!   context(s).
+//! * The extension saves an instance of the struct in the context cache with
+//!   [`Interp::save_context`](struct.Interp.html#method.save_context).  The cache holds
+//!   one value of each type, so extensions don't need to coordinate.
 //!
-//! * A command can mutably access its context data when it is executed.
+//! * The extension adds the commands that use the struct with
+//!   [`Interp::add_context_command`](struct.Interp.html#method.add_context_command).
 //!
-//! * The cached data is dropped when the last command referencing a `ContextID` is removed
-//!   from the interpreter.
+//! * A command can mutably access its context data when it is executed.
 //!
 //! This mechanism supports all of the patterns described above.  For example, Molt's
 //! test harness provides a `test` command that defines a single test.  When it executes, it must
@@ -302,11 +305,7 @@
 //! number of failures, etc.  This can be implemented as follows:
 //!
 //! ```
-//! use molt::Interp;
-//! use molt::check_args;
-//! use molt::molt_ok;
-//! use molt::types::*;
-//!
+//! # use molt_forked::prelude::*;
 //! // The context structure to hold the stats
 //! struct Stats {
 //!     num_tests: usize,
@@ -326,52 +325,59 @@
 //! // Create the interpreter.
 //! let mut interp = Interp::default();
 //!
-//! // Create the context struct, assigning a context ID
-//! let context_id = interp.save_context(Stats::new());
+//! // Save the context struct in the context cache.
+//! interp.save_context(Stats::new());
 //!
-//! // Add the `test` command with the given context.
-//! interp.add_context_command("test", cmd_test, &[context_id]);
+//! // Add the `test` command, which uses the context.
+//! interp.add_context_command("test", cmd_test);
 //!
 //! // Try using the new command.  It should increment the `num_passed` statistic.
 //! let val = interp.eval("test ...")?;
-//! assert_eq!(interp.context::<Stats>(context_id).num_passed, 1);
+//! assert_eq!(interp.get_context::<Stats>().unwrap().num_passed, 1);
 //! # molt_ok!()
 //! # }
 //!
 //! // A stub test command.  It ignores its arguments, and
 //! // increments the `num_passed` statistic in its context.
-//! fn cmd_test(interp: &mut Interp, context_ids: &[ContextID], argv: &[Value]) -> MoltResult {
+//! fn cmd_test(interp: &mut Interp<()>, stats: &mut Stats, argv: &[Value]) -> MoltResult {
 //!     // Pretend it passed
-//!     interp.context::<Stats>(context_ids[0]).num_passed += 1;
+//!     stats.num_passed += 1;
 //!
 //!     molt_ok!()
 //! }
 //! ```
 //!
+//! A command listed in `gen_command!` can use the cache as well, with
+//! [`Interp::get_context_mut`](struct.Interp.html#method.get_context_mut) or
+//! [`Interp::with_context`](struct.Interp.html#method.with_context).
+//!
 //! # Ensemble Commands
 //!
 //! An _ensemble command_ is simply a command with subcommands, like the standard Molt `info`
 //! and `array` commands.  At the Rust level, it is simply a command that looks up its subcommand
-//! (e.g., `argv[1]`) in an array of `Subcommand` structs and executes it as a command.
+//! (e.g., `argv[1]`) and executes it as a command.
 //!
-//! The [`Interp::call_subcommand`](struct.Interp.html#method.call_subcommand) method is used
-//! to look up and call the relevant command function, handling all relevant errors in the
-//! TCL-standard way.
+//! The [`gen_subcommand!`](../macro.gen_subcommand.html) macro generates the dispatcher: a
+//! `match` on the subcommand name, which the compiler turns into efficient code, with a
+//! `-help` option and a TCL-style error for unknown subcommands.  (The standard ensembles
+//! use the `_gen_subcommand_generic!` macro, which does the same without the help text.)
 //!
 //! For example, the `array` command is defined as follows.
 //!
 //! ```ignore
-//! const ARRAY_SUBCOMMANDS: [Subcommand; 6] = [
-//!     Subcommand("exists", cmd_array_exists),
-//!     Subcommand("get", cmd_array_get),
-//!     // ...
-//! ];
-//!
-//! pub fn cmd_array(interp: &mut Interp, context_ids: &[ContextID], argv: &[Value]) -> MoltResult {
-//!     interp.call_subcommand(context_ids, argv, 1, &ARRAY_SUBCOMMANDS)
+//! pub fn cmd_array<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+//!     let f = _gen_subcommand_generic!(
+//!         1,
+//!         [
+//!             ("exists", cmd_array_exists),
+//!             ("get", cmd_array_get),
+//!             // ...
+//!         ],
+//!     );
+//!     f(interp, argv)
 //! }
 //!
-//! pub fn cmd_array_exists(interp: &mut Interp, _: &[ContextID], argv: &[Value]) -> MoltResult {
+//! fn cmd_array_exists<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
 //!     check_args(2, argv, 3, 3, "arrayName")?;
 //!     molt_ok!(Value::from(interp.array_exists(argv[2].as_str())))
 //! }
@@ -379,10 +385,9 @@
 //! // ...
 //! ```
 //!
-//! The `cmd_array` and `cmd_array_exists` functions are just normal Molt `CommandFunc`
-//! functions.  The `array` command is added to the interpreter using `Interp::add_command`
-//! in the usual way. Note that the `context_id`s are passed to the subcommand functions, though
-//! in this case it isn't needed.
+//! The `cmd_array` and `cmd_array_exists` functions are just normal Molt command
+//! functions, and `cmd_array` is added to the interpreter's command dispatcher in the usual
+//! way.
 //!
 //! Also, notice that the call to `check_args` in `cmd_array_exists` has `2` as its first
 //! argument, rather than `1`.  That indicates that the first two arguments represent the
@@ -398,32 +403,32 @@
 //!
 //! * An instance is an ensemble command:
 //!   * Whose name is provided to the constructor
-//!   * That has an associated context structure, initialized by the constructor, that belongs
+//!   * That has an associated data structure, initialized by the constructor, that belongs
 //!     to it alone.
 //!
-//! * Each of the object's subcommand functions is passed the object's context ID, so that all
-//!   can access the object's data.
+//! * The instance's [`CommandFunc`] is a closure that owns the object's data, e.g., in an
+//!   `Rc<RefCell<_>>`, so that each of its subcommands can access it.
 //!
 //! Thus, the constructor command will do the following:
 //!
-//! * Create and initialize a context structure, assigning it a `ContextID` via
-//!   `Interp::save_context`.
-//!   * The context structure may be initialized with default values, or configured further
+//! * Create and initialize the data structure.
+//!   * The structure may be initialized with default values, or configured further
 //!     based on the constructor command's arguments.
 //!
 //! * Determine a name for the new instance.
 //!   * The name is usually passed in as an argument, but can be computed.
 //!
-//! * Create the instance using `Interp::add_context_command` and the instance's ensemble
-//!   `CommandFunc`.
+//! * Create the instance using `Interp::add_command` and the instance's closure.
 //!
 //! * Usually, return the name of the newly created command.
 //!
+//! Renaming the instance to the empty string with `rename` deletes it, dropping its data.
+//!
 //! Note that there's no real difference between defining a simple ensemble like `array`, as
 //! shown above, and defining an object command as described here, except that:
 //!
 //! * The instance is usually created "on the fly" rather than at interpreter initialization.
-//! * The instance will always have data in the context cache.
+//! * The instance will always have data of its own.
 //!
 //! # Checking Scripts for Completeness
 //!
@@ -433,26 +438,52 @@
 //! implementing a Read-Eval-Print-Loop, as it allows the REPL to easily determine whether it
 //! should evaluate the input immediately or ask for an additional line of input.
 //!
+//! [`Interp::completeness`](struct.Interp.html#method.completeness) says more: whether the
+//! script is complete, is waiting for a delimiter to be closed, or is malformed, in which
+//! case the REPL should report the error rather than wait for input that can't fix it.
+//!
 //! [The Molt Book]: https://wduquette.github.io/molt/
 //! [`MoltResult`]: ../types/type.MoltResult.html
 //! [`Exception`]: ../types/enum.Exception.html
-//! [`CommandFunc`]: ../types/type.CommandFunc.html
+//! [`CommandFunc`]: type.CommandFunc.html
 //! [`Value`]: ../value/index.html
 //! [`Interp`]: struct.Interp.html
+use crate::cancel::CancelHandle;
+use crate::channel::{Channel, ChannelTable, FnWriter, Writer};
+use crate::child::{Alias, AliasTarget, ParentLink};
+use crate::context::ContextCache;
 use crate::dict::dict_new;
+use crate::event::EventQueue;
 use crate::expr;
+use crate::expr::Datum;
 use crate::gen_command;
 use crate::list::list_to_string;
+use crate::loader::{FsLoader, ScriptLoader};
 use crate::molt_err;
+use crate::molt_err_help;
 use crate::molt_ok;
+use crate::namespace;
+use crate::namespace::ProcIndex;
+use crate::package::{PackageInitFn, Packages};
 use crate::parser;
 use crate::parser::Script;
 use crate::parser::Word;
-use crate::scope::ScopeStack;
+use crate::profile::{ProfileEntry, ProfileReport};
+use crate::rng::Rng;
+use crate::scope::{ScopeStack, SuspendedScopes};
+use crate::trace::{
+    ExecCallback, ExecOp, ExecTraceFn, ExecTraces, TraceCallback, TraceFn, TraceId,
+    TraceOp, VarTrace, VarTraces,
+};
 use crate::types::*;
+use crate::util;
 use crate::value::Value;
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{ControlFlow, Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
+use std::time::Duration;
 cfg_if::cfg_if! {
   if #[cfg(feature = "wasm")] {
     use wasm_timer::Instant;
@@ -467,17 +498,142 @@ const OPT_LEVEL: &str = "-level";
 const OPT_ERRORCODE: &str = "-errorcode";
 const OPT_ERRORINFO: &str = "-errorinfo";
 const ZERO: &str = "0";
+const UNKNOWN: &str = "unknown";
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CommandType {
     Native,
     Embedded,
     Proc,
 }
+
+/// A group of the standard commands that go together, for building an interpreter
+/// with only some of them; see [`Interp::with_command_sets`] and
+/// [`Command::with_command_sets`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandSet {
+    /// Variables, control flow, procs, and errors: `set`, `if`, `proc`, `catch`, etc.
+    Core,
+    /// The list commands: `list`, `lindex`, `lsort`, `join`, etc.
+    Lists,
+    /// The string commands: `binary`, `encoding`, `string`, `format`, and `scan`.
+    Strings,
+    /// The `expr` command.
+    Math,
+    /// The commands that reach outside the interpreter: `puts`, `flush`, `clock`,
+    /// `after`, and `vwait`.
+    IO,
+    /// The commands that examine the interpreter: `info`, `trace`, `time`, `profile`, and
+    /// `help`.
+    Introspection,
+}
+
+impl CommandSet {
+    /// All of the command sets, which together hold all of the standard commands.
+    pub const ALL: [CommandSet; 6] = [
+        CommandSet::Core,
+        CommandSet::Lists,
+        CommandSet::Strings,
+        CommandSet::Math,
+        CommandSet::IO,
+        CommandSet::Introspection,
+    ];
+
+    /// Returns the names of the standard commands in the set.
+    pub fn commands(&self) -> &'static [&'static str] {
+        use crate::prelude::*;
+        match self {
+            CommandSet::Core => &[
+                _ALIAS,
+                _APPEND,
+                _ARRAY,
+                _ASSERT_CODE,
+                _ASSERT_EQ,
+                _ASSERT_ERR,
+                _BREAK,
+                _CATCH,
+                _CONTINUE,
+                _DICT,
+                _ERROR,
+                _EVAL,
+                _FOR,
+                _FOREACH,
+                _GLOBAL,
+                _IF,
+                _INCR,
+                _INTERP,
+                _NAMESPACE,
+                _PACKAGE,
+                _PROC,
+                _RENAME,
+                _RETURN,
+                _SET,
+                _SUBST,
+                _SWITCH,
+                _TAILCALL,
+                _THROW,
+                _UNSET,
+                _UPLEVEL,
+                _UPVAR,
+                _WHILE,
+            ],
+            CommandSet::Lists => &[
+                _CONCAT, _JOIN, _LAPPEND, _LINDEX, _LINSERT, _LIST, _LLENGTH, _LMAP,
+                _LRANGE, _LREPEAT, _LREPLACE, _LREVERSE, _LSEARCH, _LSORT,
+            ],
+            CommandSet::Strings => &[_BINARY, _ENCODING, _FORMAT, _SCAN, _STRING],
+            CommandSet::Math => &[_EXPR],
+            CommandSet::IO => &[_AFTER, _CLOCK, _FLUSH, _PUTS, _VWAIT],
+            CommandSet::Introspection => &["help", _INFO, _PROFILE, _TIME, _TRACE],
+        }
+    }
+}
+
+/// The help text of an embedded command, as given in its `gen_command!` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedHelp {
+    /// The `usage` of the entry's [`ArgSpec`](../types/struct.ArgSpec.html), if it has one.
+    pub usage: Option<&'static str>,
+    /// The entry's help string, if it has one.
+    pub help: Option<&'static str>,
+    /// The padding between the name and the help string, for entries without an `ArgSpec`.
+    pub padding: &'static str,
+}
+
+impl EmbeddedHelp {
+    /// Returns the command's line in the `help` output.
+    fn line(&self, name: &str) -> String {
+        match self.usage {
+            Some(usage) => match self.help {
+                Some(help) => format!("{} {}  {}", name, usage, help),
+                None => format!("{} {}", name, usage),
+            },
+            None => format!("{}  {}{}", name, self.padding, self.help.unwrap_or("")),
+        }
+    }
+}
+
+/// What an application can show about a command, e.g., in a menu; see
+/// [`Interp::command_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandInfo {
+    /// The kind of command.
+    pub kind: CommandType,
+    /// The help string of an embedded command.  Native commands and procs have none.
+    pub help: Option<&'static str>,
+    /// The argument usage: the `ArgSpec` usage of an embedded command, or the argument
+    /// list of a proc.  Native commands have none.
+    pub usage: Option<String>,
+}
+
 pub struct Command<Ctx: 'static> {
     fn_execute: fn(&str, &mut Interp<Ctx>, &[Value]) -> MoltResult,
     fn_type: fn(&str, &Interp<Ctx>) -> Option<CommandType>,
     native_names: &'static [&'static str],
     embedded_names: &'static [&'static str],
+    embedded_helps: &'static [EmbeddedHelp],
+    // The standard commands left out by `with_command_sets`.
+    hidden: Vec<&'static str>,
 }
 impl<Ctx> Command<Ctx> {
     #[inline]
@@ -487,11 +643,143 @@ impl<Ctx> Command<Ctx> {
         native_names: &'static [&'static str],
         embedded_names: &'static [&'static str],
     ) -> Self {
-        Self { fn_execute, fn_type, native_names, embedded_names }
+        Self {
+            fn_execute,
+            fn_type,
+            native_names,
+            embedded_names,
+            embedded_helps: &[],
+            hidden: Vec::new(),
+        }
+    }
+
+    /// Sets the help text of the embedded commands, one entry per name in
+    /// `embedded_names`, in the same order.  `gen_command!` does this for its entries.
+    #[inline]
+    pub fn with_embedded_helps(
+        mut self,
+        embedded_helps: &'static [EmbeddedHelp],
+    ) -> Self {
+        debug_assert_eq!(embedded_helps.len(), self.embedded_names.len());
+        self.embedded_helps = embedded_helps;
+        self
+    }
+
+    /// Keeps only the standard commands in the given sets, hiding the rest: scripts
+    /// can't call them, and they aren't listed by `info commands`.  The extra native
+    /// commands, the embedded commands, and procs are unaffected, and a proc may be
+    /// defined with a hidden command's name.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let command = gen_command!((), [], []).with_command_sets(&[CommandSet::Core]);
+    /// let mut interp = Interp::new((), command, false, "core");
+    ///
+    /// assert_eq!(interp.eval("set x 1; incr x").unwrap().as_str(), "2");
+    /// assert!(interp.eval("puts $x").is_err());
+    /// ```
+    pub fn with_command_sets(mut self, sets: &[CommandSet]) -> Self {
+        self.hidden = CommandSet::ALL
+            .iter()
+            .filter(|set| !sets.contains(set))
+            .flat_map(|set| set.commands().iter().copied())
+            .collect();
+        self
+    }
+
+    /// Hides every command but the standard ones, i.e., the extra native commands, such
+    /// as `source`, `exit`, `exec`, the file commands, and the developer tools, and the
+    /// embedded commands, except those named in `allowed`.  Standard commands already
+    /// hidden by `with_command_sets` stay hidden.  See [`Interp::new_safe`].
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let command = gen_command!((), [(_SOURCE, cmd_source), (_EXIT, cmd_exit)], [])
+    ///     .with_safe_commands(&[_SOURCE]);
+    /// let interp = Interp::new((), command, false, "safe");
+    ///
+    /// assert!(interp.command_info("source").is_some());
+    /// assert!(interp.command_info("exit").is_none());
+    /// ```
+    pub fn with_safe_commands(mut self, allowed: &[&str]) -> Self {
+        let standard: Vec<&str> = CommandSet::ALL
+            .iter()
+            .flat_map(|set| set.commands().iter().copied())
+            .collect();
+        let unsafe_names: Vec<&'static str> = self
+            .native_names
+            .iter()
+            .filter(|name| !standard.contains(name))
+            .chain(self.embedded_names)
+            .copied()
+            .filter(|name| !allowed.contains(name) && !self.hidden.contains(name))
+            .collect();
+        self.hidden.extend(unsafe_names);
+        self
+    }
+
+    /// Returns true if the name is a command hidden by `with_command_sets` or
+    /// `with_safe_commands`.
+    #[inline]
+    fn is_hidden(&self, name: &str) -> bool {
+        !self.hidden.is_empty() && self.hidden.contains(&name)
+    }
+
+    /// Returns the names of the native commands that aren't hidden.
+    fn visible_native_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.native_names.iter().copied().filter(|name| !self.is_hidden(name))
+    }
+
+    /// Returns the names of the embedded commands that aren't hidden.
+    fn visible_embedded_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.embedded_names
+            .iter()
+            .copied()
+            .filter(|name| !self.is_hidden(name))
+    }
+
+    /// Creates a dispatcher with no commands at all; every command is unknown.
+    /// See [`Interp::empty`].
+    pub fn empty() -> Self {
+        fn execute<Ctx>(name: &str, _: &mut Interp<Ctx>, _: &[Value]) -> MoltResult {
+            molt_err!("unknown command \"{}\"", name)
+        }
+
+        fn command_type<Ctx>(_: &str, _: &Interp<Ctx>) -> Option<CommandType> {
+            None
+        }
+
+        Self::new(execute::<Ctx>, command_type::<Ctx>, &[], &[])
     }
 }
-cfg_if::cfg_if! {
-  if #[cfg(feature = "std_buff")] {
+
+/// A math function defined in Rust for use in `expr`; see
+/// [`Interp::add_math_func`](struct.Interp.html#method.add_math_func).  It is passed
+/// the function's arguments, and should return a number.
+pub type MathFunc<Ctx> = fn(&mut Interp<Ctx>, &[Value]) -> MoltResult;
+
+/// A command added at runtime; see
+/// [`Interp::add_command`](struct.Interp.html#method.add_command).  Unlike the commands
+/// of [`gen_command!`](../macro.gen_command.html), it can be a closure, e.g., one that
+/// holds an object's state.  It is passed the words of the command, starting with its
+/// name.
+pub type CommandFunc<Ctx> = Rc<dyn Fn(&mut Interp<Ctx>, &[Value]) -> MoltResult>;
+
+/// A runtime command's destructor, called when the command is deleted; see
+/// [`Interp::add_command_with_destructor`](struct.Interp.html#method.add_command_with_destructor).
+pub type CommandDestructor<Ctx> = Box<dyn FnOnce(&mut Interp<Ctx>)>;
+
+/// A command that uses a value from the context cache; see
+/// [`Interp::add_context_command`](struct.Interp.html#method.add_context_command).  It is
+/// passed the interpreter, the value, and the words of the command.
+pub type ContextCommandFunc<Ctx, T> =
+    fn(&mut Interp<Ctx>, &mut T, &[Value]) -> MoltResult;
+
+/// A handler for unknown commands defined in Rust; see
+/// [`Interp::set_unknown_handler`](struct.Interp.html#method.set_unknown_handler).  It is
+/// passed the words of the command, starting with its name.
+pub type UnknownFn<Ctx> = fn(&mut Interp<Ctx>, &[Value]) -> MoltResult;
+
 /// The Molt Interpreter.
 ///
 /// The `Interp` struct is the primary API for
@@ -523,89 +811,219 @@ cfg_if::cfg_if! {
 /// to create contexts consisting of references and mutable references
 /// within that lifetime. Under the hood, the references are stored as
 /// raw pointers.
-pub struct Interp<Ctx> where
-  Ctx: 'static,
+pub struct Interp<Ctx>
+where
+    Ctx: 'static,
 {
-  pub name: &'static str,
-  // Command Table
-  command: Command<Ctx>,
-  procs: HashMap<String, Rc<Procedure>>,
-  // Variable Table
-  scopes: ScopeStack,
-
-  /// Embedded context
-  pub context: Ctx,
-  pub std_buff: Vec<Result<Value,Exception>>,
-  // Defines the recursion limit for Interp::eval().
-  recursion_limit: usize,
-
-  // Current number of eval levels.
-  num_levels: usize,
-
-  // Profile Map
-  profile_map: HashMap<String, ProfileRecord>,
-
-  // Whether to continue execution in case of error.
-  continue_on_error: bool,
+    pub name: &'static str,
+    // Command Table
+    command: Command<Ctx>,
+    procs: HashMap<String, Rc<Procedure>>,
+    // The procs in namespaces other than the global one, by namespace and relative name.
+    proc_index: ProcIndex,
+    // The namespaces other than the global one, by qualified name without the leading "::".
+    namespaces: BTreeSet<String>,
+    // Variable Table
+    scopes: ScopeStack,
+    // Variable traces.
+    var_traces: VarTraces<Ctx>,
+    // Execution traces.
+    exec_traces: ExecTraces<Ctx>,
+
+    /// Embedded context
+    pub context: Ctx,
+    /// Under the `std_buff` feature, the output of `puts` to `stdout` and `stderr`, one entry
+    /// per call, unless the host has set a writer for the channel.
+    #[cfg(feature = "std_buff")]
+    pub std_buff: Vec<Result<Value, Exception>>,
+    // Defines the recursion limit for Interp::eval().
+    recursion_limit: usize,
+
+    // Current number of eval levels.
+    num_levels: usize,
+
+    // Math functions defined in Rust, for use in expr.
+    math_funcs: HashMap<String, MathFunc<Ctx>>,
+
+    // The Rust handler for unknown commands, if any.
+    unknown_handler: Option<UnknownFn<Ctx>>,
+
+    // The error data of the most recent top-level evaluation, if it failed.
+    last_error: Option<ErrorData>,
+
+    // Profile Map
+    profile_map: HashMap<String, ProfileRecord>,
+
+    // Whether to record each command's calls in the profile map.
+    profiling: bool,
+
+    // Whether to continue execution in case of error.
+    continue_on_error: bool,
+
+    // Whether to catch panics in commands, converting them to errors.
+    catch_panics: bool,
+
+    // Whether `proc` parses the body at once, rather than on the first call.
+    eager_proc_parse: bool,
+
+    // Set when a panic unwinds out of a top-level evaluation.
+    poisoned: Rc<Cell<bool>>,
+
+    // Set to stop the running script; see cancel_handle().
+    cancel: CancelHandle,
+
+    // Whether the cancel flag survives from one top-level evaluation to the next.
+    sticky_cancel: bool,
+
+    // Whether `catch` lets a cancellation error through.
+    cancel_uncatchable: bool,
+
+    // The limits on each top-level evaluation, if any.
+    time_limit: Option<Duration>,
+    command_limit: Option<u64>,
+
+    // When the time limit runs out, and the number of commands run so far.
+    deadline: Option<Instant>,
+    command_count: u64,
+
+    // The scope level at which the current top-level evaluation began.
+    top_scope: usize,
+
+    // The paths of the files being sourced, innermost last.
+    script_files: Vec<String>,
+
+    // Reads the files that `source` evaluates.
+    script_loader: Box<dyn ScriptLoader>,
+
+    // The registered and provided packages.
+    packages: Packages<Ctx>,
+
+    // The child interpreters, by name.
+    children: HashMap<String, Interp<()>>,
+
+    // The children evaluating scripts, which are out of the table while they run, by
+    // name, innermost last, and whether they've been deleted meanwhile.
+    running_children: Vec<(String, bool)>,
+
+    // The commands added at runtime, by name.
+    runtime_commands: HashMap<String, CommandFunc<Ctx>>,
+
+    // The destructors of the runtime commands that have them, by name.
+    command_destructors: HashMap<String, CommandDestructor<Ctx>>,
+
+    // The extensions' state, by type.
+    contexts: ContextCache,
+
+    // The aliases defined in this interpreter, by command name.
+    aliases: HashMap<String, Alias>,
+
+    // The link to the parent, set while the parent is evaluating a script in this child.
+    parent: Option<ParentLink>,
+
+    // Whether this is a safe interpreter; see `Interp::new_safe`.
+    safe: bool,
+
+    // The strings returned by `info patchlevel` and `info tclversion`.
+    patch_level: String,
+    tcl_version: String,
+
+    // The channels `puts` writes to and `flush` flushes, by name.
+    channels: ChannelTable,
+
+    // The scripts scheduled by `after`.
+    events: EventQueue,
+
+    // The random number generator for expr's rand() and srand().
+    pub(crate) rng: Rng,
+}
+
+/// The name under which a command is found; see `Interp::command_key`.
+pub(crate) enum CommandKey<'a> {
+    /// The name as given, less any leading `::`.
+    Name(&'a str),
+    /// The qualified name of a proc in the current namespace.
+    Qualified(Rc<str>),
 }
-  }else{
-    /// The Molt Interpreter.
-///
-/// The `Interp` struct is the primary API for
-/// embedding Molt into a Rust application.  The application creates an instance
-/// of `Interp`, configures with it the required set of application-specific
-/// and standard Molt commands, and then uses it to evaluate Molt scripts and
-/// expressions.  See the
-/// [module level documentation](index.html)
-/// for an overview.
-///
-/// # Example
-///
-/// By default, the `Interp` comes configured with the full set of standard
-/// Molt commands.
-///
-/// ```
-/// use molt::types::*;
-/// use molt::Interp;
-/// use molt::molt_ok;
-/// # fn dummy() -> MoltResult {
-/// let mut interp = Interp::default();
-/// let four = interp.eval("expr {2 + 2}")?;
-/// assert_eq!(four, Value::from(4));
-/// # molt_ok!()
-/// # }
-/// ```
-///
-/// The `Interp` can be associated with a lifetime. If so, it is allowed
-/// to create contexts consisting of references and mutable references
-/// within that lifetime. Under the hood, the references are stored as
-/// raw pointers.
-pub struct Interp<Ctx> where
-  Ctx: 'static,
-{
-  pub name: &'static str,
-  // Command Table
-  command: Command<Ctx>,
-  procs: HashMap<String, Rc<Procedure>>,
-  // Variable Table
-  scopes: ScopeStack,
 
-  /// Embedded context
-  pub context: Ctx,
+impl CommandKey<'_> {
+    /// Returns the name as a `String`.
+    pub(crate) fn into_owned(self) -> String {
+        String::from(&*self)
+    }
+}
 
-  // Defines the recursion limit for Interp::eval().
-  recursion_limit: usize,
+impl Deref for CommandKey<'_> {
+    type Target = str;
 
-  // Current number of eval levels.
-  num_levels: usize,
+    fn deref(&self) -> &str {
+        match self {
+            CommandKey::Name(name) => name,
+            CommandKey::Qualified(name) => name,
+        }
+    }
+}
 
-  // Profile Map
-  profile_map: HashMap<String, ProfileRecord>,
+/// Poisons the interpreter if dropped while a panic unwinds out of a top-level evaluation.
+struct PanicGuard(Rc<Cell<bool>>);
 
-  // Whether to continue execution in case of error.
-  continue_on_error: bool,
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.set(true);
+        }
+    }
 }
-  }
+
+/// A local variable scope, pushed by [`Interp::local_scope`].  The guard derefs to the
+/// interpreter, and pops the scope when it's dropped, whether the code using it returns
+/// early with `?` or panics.  Any scopes pushed above it and left behind are popped too.
+pub struct ScopeGuard<'a, Ctx: 'static> {
+    interp: &'a mut Interp<Ctx>,
+    level: usize,
+}
+
+impl<Ctx> Deref for ScopeGuard<'_, Ctx> {
+    type Target = Interp<Ctx>;
+
+    fn deref(&self) -> &Interp<Ctx> {
+        self.interp
+    }
+}
+
+impl<Ctx> DerefMut for ScopeGuard<'_, Ctx> {
+    fn deref_mut(&mut self) -> &mut Interp<Ctx> {
+        self.interp
+    }
+}
+
+impl<Ctx> Drop for ScopeGuard<'_, Ctx> {
+    fn drop(&mut self) {
+        while self.interp.scopes.current() > self.level {
+            self.interp.pop_scope();
+        }
+    }
+}
+
+/// The scopes above a level, and the traces on their variables, set aside by
+/// [`Interp::eval_in_level`].  They're put back when the guard is dropped, after any
+/// scopes pushed in the meantime are popped.
+struct SuspendGuard<'a, Ctx: 'static> {
+    interp: &'a mut Interp<Ctx>,
+    level: usize,
+    scopes: Option<SuspendedScopes>,
+    traces: Vec<VarTrace<Ctx>>,
+}
+
+impl<Ctx> Drop for SuspendGuard<'_, Ctx> {
+    fn drop(&mut self) {
+        while self.interp.scopes.current() > self.level {
+            self.interp.pop_scope();
+        }
+        if let Some(scopes) = self.scopes.take() {
+            self.interp.scopes.resume(scopes);
+        }
+        self.interp.var_traces.restore(std::mem::take(&mut self.traces));
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -620,18 +1038,51 @@ impl ProfileRecord {
     }
 }
 
-impl Interp<()> {
-    /// Creates a new Molt interpreter with no commands defined.  Use this when crafting
-    /// command languages that shouldn't include the normal TCL commands, or as a base
-    /// to which specific Molt command sets can be added.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// # use molt::interp::Interp;
-    /// let mut interp = Interp::default(());
-    /// ```
-    pub fn default() -> Self {
+/// Builds an [`Interp`], one setting at a time.  This is the clearest way to create an
+/// interpreter that differs from the defaults in more than its context and commands.
+///
+/// [`InterpBuilder::new`] starts from the given context and no commands at all; the
+/// `InterpBuilder::<()>::default()` builder starts from `()` and the standard commands,
+/// and builds the same interpreter as [`Interp::default`].  Either way, the `env` array
+/// is populated, the interpreter's name is `"default-app"`, the recursion limit is 1000,
+/// and errors stop evaluation.
+///
+/// # Example
+///
+/// ```
+/// # use molt_forked::prelude::*;
+/// fn cmd_square(_interp: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+///     let x = argv[1].as_int()?;
+///     molt_ok!(x * x)
+/// }
+///
+/// let mut interp = InterpBuilder::new(())
+///     .commands(gen_command!((), [("square", cmd_square)], []))
+///     .populate_env(false)
+///     .name("calc")
+///     .recursion_limit(100)
+///     .build();
+///
+/// assert_eq!(interp.eval("square 5").unwrap().as_str(), "25");
+/// assert_eq!(interp.recursion_limit(), 100);
+/// assert!(!interp.var_exists(&Value::from("env")));
+/// ```
+pub struct InterpBuilder<Ctx: 'static> {
+    context: Ctx,
+    command: Command<Ctx>,
+    populate_env: bool,
+    name: &'static str,
+    recursion_limit: usize,
+    continue_on_error: bool,
+    eager_proc_parse: bool,
+    command_sets: Option<Vec<CommandSet>>,
+    safe: Option<Vec<&'static str>>,
+}
+
+impl Default for InterpBuilder<()> {
+    /// Returns a builder for the interpreter returned by [`Interp::default`], with the
+    /// standard commands plus `source`, `exit`, and the developer tools.
+    fn default() -> Self {
         use crate::prelude::*;
         let command = gen_command!(
             (),
@@ -651,7 +1102,192 @@ impl Interp<()> {
             // embedded commands
             []
         );
-        Interp::new((), command, true, "default-app")
+        Self::new(()).commands(command)
+    }
+}
+
+impl<Ctx> InterpBuilder<Ctx>
+where
+    Ctx: 'static,
+{
+    /// Creates a builder for an interpreter with the given context and no commands.
+    pub fn new(context: Ctx) -> Self {
+        Self {
+            context,
+            command: Command::empty(),
+            populate_env: true,
+            name: "default-app",
+            recursion_limit: 1000,
+            continue_on_error: false,
+            eager_proc_parse: false,
+            command_sets: None,
+            safe: None,
+        }
+    }
+
+    /// Sets the interpreter's context, the application data its commands can access.
+    pub fn context(mut self, context: Ctx) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Sets the interpreter's commands, usually built by
+    /// [`gen_command!`](../macro.gen_command.html).
+    pub fn commands(mut self, command: Command<Ctx>) -> Self {
+        self.command = command;
+        self
+    }
+
+    /// Sets whether to copy the process's environment variables into the `env` array.
+    pub fn populate_env(mut self, flag: bool) -> Self {
+        self.populate_env = flag;
+        self
+    }
+
+    /// Sets the interpreter's name, which `help` and the type of embedded commands report.
+    pub fn name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Sets the recursion limit; see [`Interp::set_recursion_limit`].
+    pub fn recursion_limit(mut self, limit: usize) -> Self {
+        self.recursion_limit = limit;
+        self
+    }
+
+    /// Sets whether to continue evaluation after an error; see
+    /// [`Interp::set_continue_on_error`].
+    pub fn continue_on_error(mut self, flag: bool) -> Self {
+        self.continue_on_error = flag;
+        self
+    }
+
+    /// Sets whether `proc` parses procedure bodies at once; see
+    /// [`Interp::set_eager_proc_parse`].
+    pub fn eager_proc_parse(mut self, flag: bool) -> Self {
+        self.eager_proc_parse = flag;
+        self
+    }
+
+    /// Keeps only the standard commands in the given sets; see
+    /// [`Command::with_command_sets`].  This applies to the commands however they're set.
+    pub fn command_sets(mut self, sets: &[CommandSet]) -> Self {
+        self.command_sets = Some(sets.to_vec());
+        self
+    }
+
+    /// Makes the interpreter safe, keeping only the standard commands and those named in
+    /// `allowed`, with no `env` array; see [`Interp::new_safe`].
+    pub fn safe(mut self, allowed: &[&'static str]) -> Self {
+        self.safe = Some(allowed.to_vec());
+        self
+    }
+
+    /// Builds the interpreter.
+    pub fn build(mut self) -> Interp<Ctx> {
+        if let Some(sets) = &self.command_sets {
+            self.command = self.command.with_command_sets(sets);
+        }
+        if let Some(allowed) = &self.safe {
+            self.command = self.command.with_safe_commands(allowed);
+        }
+
+        let mut interp = Interp {
+            name: self.name,
+            command: self.command,
+            recursion_limit: self.recursion_limit,
+            procs: HashMap::new(),
+            proc_index: ProcIndex::default(),
+            namespaces: BTreeSet::new(),
+            context: self.context,
+            #[cfg(feature = "std_buff")]
+            std_buff: Vec::new(),
+            scopes: ScopeStack::new(),
+            var_traces: VarTraces::default(),
+            exec_traces: ExecTraces::default(),
+            num_levels: 0,
+            math_funcs: HashMap::new(),
+            unknown_handler: None,
+            last_error: None,
+            profile_map: HashMap::new(),
+            profiling: false,
+            continue_on_error: self.continue_on_error,
+            catch_panics: false,
+            eager_proc_parse: self.eager_proc_parse,
+            poisoned: Rc::new(Cell::new(false)),
+            cancel: CancelHandle::default(),
+            sticky_cancel: false,
+            cancel_uncatchable: false,
+            time_limit: None,
+            command_limit: None,
+            deadline: None,
+            command_count: 0,
+            top_scope: 0,
+            script_files: Vec::new(),
+            script_loader: Box::new(FsLoader),
+            packages: Packages::default(),
+            children: HashMap::new(),
+            running_children: Vec::new(),
+            runtime_commands: HashMap::new(),
+            command_destructors: HashMap::new(),
+            contexts: ContextCache::default(),
+            aliases: HashMap::new(),
+            parent: None,
+            safe: self.safe.is_some(),
+            patch_level: env!("CARGO_PKG_VERSION").into(),
+            tcl_version: "8.5".into(),
+            channels: ChannelTable::new(),
+            events: EventQueue::default(),
+            rng: Rng::new(),
+        };
+
+        interp.set_scalar("errorInfo", Value::empty()).unwrap();
+        if self.populate_env && self.safe.is_none() {
+            // Populate the environment variable, and link it to the environment.
+            interp.populate_env();
+        }
+        interp
+    }
+}
+
+impl Interp<()> {
+    /// Creates a new Molt interpreter with the standard Molt commands, plus `source`,
+    /// `exit`, and the developer tools, and the `env` array.  Use
+    /// [`InterpBuilder`] to change any of these settings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// assert_eq!(interp.eval("info commands").unwrap(), InterpBuilder::default().build().eval("info commands").unwrap());
+    /// ```
+    pub fn default() -> Self {
+        InterpBuilder::default().build()
+    }
+
+    /// Creates a restricted interpreter with only the standard commands in the given
+    /// [`CommandSet`]s: not `source`, `exit`, or the developer tools, and no `env` array.
+    /// Use [`InterpBuilder::command_sets`] for other contexts and settings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::with_command_sets(&[CommandSet::Core, CommandSet::Math]);
+    /// assert_eq!(interp.eval("set x [expr {6 * 7}]").unwrap().as_str(), "42");
+    ///
+    /// let err = interp.eval("puts $x").unwrap_err();
+    /// assert!(err.value().as_str().starts_with("unknown command \"puts\""));
+    /// assert!(interp.command_info("puts").is_none());
+    /// ```
+    pub fn with_command_sets(sets: &[CommandSet]) -> Self {
+        InterpBuilder::new(())
+            .commands(gen_command!((), [], []))
+            .command_sets(sets)
+            .populate_env(false)
+            .build()
     }
 }
 
@@ -674,8 +1310,10 @@ where
     /// to retrieve the full list, and the [`add_command`](#method.add_command) family of
     /// methods to extend the interpreter with new commands.
     ///
-    /// TODO: Define command sets (sets of commands that go together, so that clients can
-    /// add or remove them in groups).
+    /// To keep only some groups of the standard commands, see
+    /// [`Interp::with_command_sets`] and [`Command::with_command_sets`].
+    ///
+    /// This is shorthand for [`InterpBuilder`]; use the builder to change other settings.
     ///
     /// ```
     /// # use molt::types::*;
@@ -696,60 +1334,106 @@ where
         use_env: bool,
         name: &'static str,
     ) -> Self {
-        cfg_if::cfg_if! {
-          if #[cfg(feature = "std_buff")] {
-            let mut interp = Self {
-              name,
-              command,
-              recursion_limit: 1000,
-              procs: HashMap::new(),
-              context,
-              std_buff: Vec::new(),
-              scopes: ScopeStack::new(),
-              num_levels: 0,
-              profile_map: HashMap::new(),
-              continue_on_error: false,
-            };
-          } else {
-            let mut interp = Self {
-              name,
-              recursion_limit: 1000,
-              command,
-              procs: HashMap::new(),
-              context,
-              scopes: ScopeStack::new(),
-              num_levels: 0,
-              profile_map: HashMap::new(),
-              continue_on_error: false,
-            };
-          }
-        }
-
-        interp.set_scalar("errorInfo", Value::empty()).unwrap();
-        if use_env {
-            // Populate the environment variable.
-            // TODO: Really should be a "linked" variable, where sets to it are tracked and
-            // written back to the environment.
-            interp.populate_env();
-        }
-        interp
+        InterpBuilder::new(context)
+            .commands(command)
+            .populate_env(use_env)
+            .name(name)
+            .build()
     }
 
-    /// Populates the TCL `env()` array with the process's environment variables.
+    /// Creates a safe interpreter, for scripts that mustn't reach outside it, e.g., a
+    /// user's scripts in a web page.  It has the standard commands, but none of the
+    /// others in `command`: not `source`, `exec`, the file commands, `exit`, or the
+    /// developer tools, nor any embedded command, except those named in `allowed`.  Nor
+    /// does it have the `env` array, or the `info hostname` and `info nameofexecutable`
+    /// subcommands.  Its children are safe as well, and `interp issafe` returns 1.
     ///
-    /// # TCL Liens
+    /// This is shorthand for [`InterpBuilder::safe`]; use the builder to change other
+    /// settings.
     ///
-    /// Changes to the variable are not mirrored back into the process's environment.
-    #[inline]
-    fn populate_env(&mut self) {
-        for (key, value) in std::env::vars() {
-            // Drop the result, as there's no good reason for this to ever throw an error.
-            let _ = self.set_element("env", &key, value.into());
-        }
-    }
-
-    //--------------------------------------------------------------------------------------------
-    // Script and Expression Evaluation
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// fn cmd_square(_interp: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+    ///     let x = argv[1].as_int()?;
+    ///     molt_ok!(x * x)
+    /// }
+    ///
+    /// let command = gen_command!(
+    ///     (),
+    ///     [(_SOURCE, cmd_source), (_EXIT, cmd_exit)],
+    ///     [
+    ///         ("square", cmd_square, ArgSpec { min: 2, max: 2, usage: "x" }, "square x"),
+    ///         ("shutdown", cmd_exit, ArgSpec { min: 1, max: 1, usage: "" }, "exit")
+    ///     ]
+    /// );
+    /// let mut interp = Interp::new_safe((), command, &["square"], "sandbox");
+    ///
+    /// assert_eq!(interp.eval("square 5").unwrap().as_str(), "25");
+    /// assert_eq!(interp.eval("interp issafe").unwrap().as_str(), "1");
+    /// assert!(interp.eval("source script.tcl").is_err());
+    /// assert!(interp.eval("shutdown").is_err());
+    /// assert!(!interp.var_exists(&Value::from("env")));
+    /// assert!(interp.eval("info hostname").is_err());
+    /// ```
+    pub fn new_safe(
+        context: Ctx,
+        command: Command<Ctx>,
+        allowed: &[&'static str],
+        name: &'static str,
+    ) -> Self {
+        InterpBuilder::new(context)
+            .commands(command)
+            .safe(allowed)
+            .name(name)
+            .build()
+    }
+
+    /// Creates a new Molt interpreter with no commands at all, not even `set` or `proc`,
+    /// and no `env` array.  To build a data or configuration language with just the
+    /// commands it needs, pass a dispatcher built by
+    /// [`gen_command_minimal!`](../macro.gen_command_minimal.html) to
+    /// [`Interp::new`](#method.new) instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::empty((), "data");
+    /// assert!(interp.command_names().is_empty());
+    ///
+    /// let err = interp.eval("set x 1").unwrap_err();
+    /// assert_eq!(err.value().as_str(), "unknown command \"set\"");
+    /// ```
+    pub fn empty(context: Ctx, name: &'static str) -> Self {
+        Self::new(context, Command::empty(), false, name)
+    }
+
+    /// Populates the TCL `env()` array with the process's environment variables, and
+    /// links it so that setting or unsetting its elements sets or unsets the variables.
+    ///
+    /// # TCL Liens
+    ///
+    /// Changes made to the process's environment other than via the array aren't seen in
+    /// it.  An element whose name or value can't be an environment variable, e.g., one
+    /// whose name contains `=`, is kept only in the array.  Under the `wasm` feature there
+    /// is no process environment, and changes are kept only in the array.
+    #[inline]
+    fn populate_env(&mut self) {
+        for (key, value) in std::env::vars() {
+            // Drop the result, as there's no good reason for this to ever throw an error.
+            let _ = self.set_element("env", &key, value.into());
+        }
+
+        self.scopes.link_array(
+            "env",
+            crate::scope::ArrayLink { on_set: set_env_var, on_unset: unset_env_var },
+        );
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Script and Expression Evaluation
 
     /// Evaluates a script one command at a time.  Returns the [`Value`](../value/index.html)
     /// of the last command in the script, or the value of any explicit `return` call in the
@@ -814,8 +1498,136 @@ where
         // example, can maybe evaluate it as a command without using as_script().
         // Tricky, though.  Don't want to have to parse it as a list.  Need a quick way
         // to determine if something is already a list.  (Might need two methods!)
+        self.eval_parsed(&*value.as_script()?)
+    }
+
+    /// Evaluates the script in the named file, as the `source` command does.  While the
+    /// script is being evaluated, the path is available as [`script_file`] and via the
+    /// `info script` command.  The file is read by the interpreter's script loader; see
+    /// [`set_script_loader`](#method.set_script_loader).
+    ///
+    /// Returns an error if the file can't be read.
+    ///
+    /// [`script_file`]: #method.script_file
+    pub fn eval_file(&mut self, path: &str) -> MoltResult {
+        self.eval_file_with(path, |_, _| ControlFlow::Continue(()))
+    }
+
+    /// Evaluates a script as [`eval_value`](#method.eval_value) does, calling `progress`
+    /// after each of its top-level commands with the number of commands done so far and
+    /// the total.  If `progress` returns `ControlFlow::Break`, evaluation stops with the
+    /// error "evaluation aborted", with error code `ABORTED`.
+    ///
+    /// Only the script's own commands are counted; the commands of a proc body or a loop
+    /// body it calls are not.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut interp = Interp::default();
+    /// let script = Value::from("set a 1; set b 2; set c 3");
+    ///
+    /// let exception = interp
+    ///     .eval_script_with_progress(&script, &mut |done, total| {
+    ///         assert_eq!(total, 3);
+    ///         if done == 2 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    ///     })
+    ///     .unwrap_err();
+    /// assert_eq!(exception.value().as_str(), "evaluation aborted");
+    /// assert_eq!(exception.error_code().as_str(), "ABORTED");
+    /// assert!(interp.scalar("b").is_ok());
+    /// assert!(interp.scalar("c").is_err());
+    /// ```
+    pub fn eval_script_with_progress(
+        &mut self,
+        script: &Value,
+        progress: &mut dyn FnMut(usize, usize) -> ControlFlow<()>,
+    ) -> MoltResult {
+        self.eval_parsed_with(&*script.as_script()?, progress)
+    }
+
+    /// Evaluates the script in the named file as [`eval_file`](#method.eval_file) does,
+    /// calling `progress` after each of its top-level commands as
+    /// [`eval_script_with_progress`](#method.eval_script_with_progress) does.  Use it to
+    /// show progress through a large file and to abort it cleanly.
+    pub fn source_file_with_progress(
+        &mut self,
+        path: &str,
+        progress: &mut dyn FnMut(usize, usize) -> ControlFlow<()>,
+    ) -> MoltResult {
+        self.eval_file_with(path, progress)
+    }
+
+    /// Reads and evaluates the named file, calling `progress` after each top-level
+    /// command.
+    fn eval_file_with<F>(&mut self, path: &str, progress: F) -> MoltResult
+    where
+        F: FnMut(usize, usize) -> ControlFlow<()>,
+    {
+        let script = match self.script_loader.load(path) {
+            Ok(script) => Value::from(script),
+            Err(e) => return molt_err!("couldn't read file \"{}\": {}", path, e),
+        };
+
+        self.script_files.push(path.into());
+        let result = match script.as_script() {
+            Ok(parsed) => self.eval_parsed_with(&parsed, progress),
+            Err(exception) => Err(exception),
+        };
+        self.script_files.pop();
+
+        result
+    }
+
+    /// Sets the loader that reads the files that `source` and
+    /// [`eval_file`](#method.eval_file) evaluate, e.g., from an in-memory map or a store
+    /// that the host has fetched.  The default reads them from the file system; see
+    /// [`FsLoader`](../prelude/struct.FsLoader.html).
+    pub fn set_script_loader(&mut self, loader: Box<dyn ScriptLoader>) {
+        self.script_loader = loader;
+    }
+
+    /// Returns the path of the innermost file being evaluated by
+    /// [`eval_file`](#method.eval_file) or the `source` command, or `None` if no file is
+    /// being evaluated.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// assert_eq!(interp.script_file(), None);
+    /// ```
+    pub fn script_file(&self) -> Option<&str> {
+        self.script_files.last().map(|path| path.as_str())
+    }
+
+    /// Evaluates a script that has already been parsed, exactly as `eval_value` would.
+    /// Loop commands use this to get a body's `Script` once rather than per iteration.
+    #[inline]
+    pub(crate) fn eval_parsed(&mut self, script: &Script) -> MoltResult {
+        self.eval_parsed_with(script, |_, _| ControlFlow::Continue(()))
+    }
 
-        // FIRST, check the number of nesting levels
+    /// Evaluates a parsed script as `eval_parsed` does, calling `progress` after each of
+    /// its top-level commands.
+    fn eval_parsed_with<F>(&mut self, script: &Script, progress: F) -> MoltResult
+    where
+        F: FnMut(usize, usize) -> ControlFlow<()>,
+    {
+        // FIRST, refuse to run if an earlier evaluation was abandoned by a panic.
+        if self.poisoned.get() {
+            return molt_err!(
+                "interpreter in inconsistent state: a command panicked during an earlier evaluation"
+            );
+        }
+
+        // NEXT, a panic that unwinds out of a top-level evaluation poisons the interpreter.
+        let top_level = self.num_levels == 0;
+        let _guard = top_level.then(|| self.enter_top_level());
+        let scope_level = self.scopes.current();
+
+        // NEXT, check the number of nesting levels
         self.num_levels += 1;
 
         if self.num_levels > self.recursion_limit {
@@ -824,7 +1636,7 @@ where
         }
 
         // NEXT, evaluate the script and translate the result to Ok or Error
-        let mut result = self.eval_script(&*value.as_script()?);
+        let mut result = self.eval_script_with(script, progress);
 
         // NEXT, decrement the number of nesting levels.
         self.num_levels -= 1;
@@ -845,35 +1657,239 @@ where
                     ResultCode::Continue => {
                         molt_err!("invoked \"continue\" outside of a loop")
                     }
-                    // TODO: Better error message
-                    ResultCode::Other(_) => molt_err!("unexpected result code."),
+                    ResultCode::TailCall => {
+                        molt_err!("tailcall can only be called from a proc")
+                    }
+                    ResultCode::Cancelled => Err(exception),
+                    ResultCode::Other(code) => {
+                        molt_err!("command returned bad code: {}", code)
+                    }
                 };
             }
         }
 
         if let Err(exception) = &result {
-            if exception.is_error() {
-                self.set_global_error_data(exception.error_data())?;
+            if exception.is_error() || exception.code() == ResultCode::Cancelled {
+                self.set_global_error_data(exception.error_data());
             }
         }
 
+        if self.num_levels == 0 {
+            self.set_last_error(&result);
+        }
+
+        // Every command that pushes a scope must pop it, however it returns.
+        if top_level {
+            debug_assert_eq!(
+                scope_level,
+                self.scopes.current(),
+                "unbalanced scope stack"
+            );
+        }
+
         result
     }
 
-    /// Saves the error exception data
+    /// Records the start of a top-level evaluation, returning a guard that poisons the
+    /// interpreter if a panic unwinds through it.  Clears a cancellation request unless
+    /// [`sticky_cancel`](#method.sticky_cancel) is set, and restarts the time and command
+    /// limits.
+    fn enter_top_level(&mut self) -> PanicGuard {
+        self.top_scope = self.scopes.current();
+        if !self.sticky_cancel {
+            self.cancel.reset();
+        }
+        self.restart_limits();
+        PanicGuard(self.poisoned.clone())
+    }
+
+    /// Calls the command, recording the call if profiling is on.
     #[inline]
-    fn set_global_error_data(
-        &mut self,
-        error_data: Option<&ErrorData>,
-    ) -> Result<(), Exception> {
+    fn dispatch(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        if self.profiling {
+            return self.dispatch_profiled(name, words);
+        }
+        self.call_command(name, words)
+    }
+
+    /// Calls the command and records the call in the profile data.  Kept out of line, so
+    /// that profiling adds nothing to the stack frame of an ordinary call.
+    #[inline(never)]
+    fn dispatch_profiled(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        let start = Instant::now();
+        let result = self.call_command(name, words);
+
+        // `profile off` isn't recorded.
+        if self.profiling {
+            self.profile_save(name, start);
+        }
+        result
+    }
+
+    /// Calls the command's function, unless it's a standard command hidden by
+    /// [`Command::with_command_sets`], in which case only a proc can stand in for it.
+    #[inline]
+    fn call_command(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        if !self.aliases.is_empty() && self.aliases.contains_key(name) {
+            return self.call_alias(name, words);
+        }
+
+        if !self.command.is_hidden(name) {
+            return (self.command.fn_execute)(name, self, words);
+        }
+
+        if let Some(func) = self.get_command(name) {
+            return func(self, words);
+        }
+
+        match self.get_proc(name) {
+            Some(proc) => proc.clone().execute(self, words),
+            None => self.unknown_command(name, words),
+        }
+    }
+
+    /// Calls the alias's target command, with the alias's arguments appended to its
+    /// prefix.  Kept out of line, so that aliases add nothing to the stack frame of an
+    /// ordinary call.
+    #[inline(never)]
+    fn call_alias(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        let alias = self.aliases[name].clone();
+        let command = alias.command(words);
+
+        match &alias.target {
+            AliasTarget::Own => self.eval_value(&command),
+            AliasTarget::Parent => match &self.parent {
+                Some(parent) => parent.eval(&command),
+                None => molt_err!(
+                    "can't call alias \"{}\": the parent interpreter isn't evaluating this one",
+                    name
+                ),
+            },
+            AliasTarget::Child(child) => self.eval_in_child(child, &command),
+        }
+    }
+
+    /// Handles a command that isn't defined.  If there's an `unknown` proc, it's called
+    /// with the command's words as its arguments; otherwise the handler set by
+    /// [`set_unknown_handler`](#method.set_unknown_handler) is called with the words.
+    /// With neither, the command is an error.
+    ///
+    /// This is called by the dispatcher generated by [`gen_command!`](../macro.gen_command.html), and should rarely
+    /// be needed otherwise.
+    pub fn unknown_command(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        if name != UNKNOWN {
+            if let Some(proc) = self.get_proc(UNKNOWN) {
+                let proc = proc.clone();
+                let mut argv = Vec::with_capacity(words.len() + 1);
+                argv.push(Value::from(UNKNOWN));
+                argv.extend_from_slice(words);
+                return proc.execute(self, &argv);
+            }
+        }
+
+        match self.unknown_handler {
+            Some(handler) => handler(self, words),
+            None => molt_err_help!(
+                "unknown command \"{}\"{} (run help -all for the full list)",
+                name,
+                util::did_you_mean(&self.similar_command_names(name))
+            ),
+        }
+    }
+
+    /// Executes the command, converting a panic into an error if
+    /// [`catch_panics`](#method.catch_panics) is set.  After a panic, the scope stack and the
+    /// nesting level are restored to what they were when the command was called.
+    #[inline]
+    fn execute(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        let name = &*self.command_key(name);
+        if !self.catch_panics {
+            return self.dispatch(name, words);
+        }
+
+        let num_levels = self.num_levels;
+        let scope = self.scopes.current();
+        let script_files = self.script_files.len();
+
+        match panic::catch_unwind(AssertUnwindSafe(|| self.dispatch(name, words))) {
+            Ok(result) => result,
+            Err(payload) => {
+                while self.scopes.current() > scope {
+                    self.discard_scope();
+                }
+                self.num_levels = num_levels;
+                self.script_files.truncate(script_files);
+
+                let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+                    msg.to_string()
+                } else if let Some(msg) = payload.downcast_ref::<String>() {
+                    msg.clone()
+                } else {
+                    "unknown panic".to_string()
+                };
+                molt_err!("command panicked: {}", msg)
+            }
+        }
+    }
+
+    /// Saves the error exception data in the global `errorInfo` and `errorCode` variables.
+    /// The error code is saved as-is, preserving its list structure.  Also used by `catch`.
+    ///
+    /// The variables are written only when an error is thrown; they are never cleared, so
+    /// they describe the most recent error even after later scripts succeed.  Saving them is
+    /// best-effort: if a script has made one of them an array, it's left alone, rather than
+    /// replacing the error being reported.
+    #[inline]
+    pub(crate) fn set_global_error_data(&mut self, error_data: Option<&ErrorData>) {
         if let Some(data) = error_data {
             // TODO: Might want a public method for this.  Or, if I implement namespaces, that's
             // sufficient.
-            self.scopes.set_global("errorInfo", data.error_info())?;
-            self.scopes.set_global("errorCode", data.error_code())?;
+            let _ = self.scopes.set_global("errorInfo", data.error_info());
+            let _ = self.scopes.set_global("errorCode", data.error_code());
         }
+    }
 
-        Ok(())
+    /// Records the outcome of a top-level evaluation for
+    /// [`last_error_info`](#method.last_error_info) and
+    /// [`last_error_code`](#method.last_error_code).
+    fn set_last_error<T>(&mut self, result: &Result<T, Exception>) {
+        self.last_error = match result {
+            Err(exception)
+                if exception.is_error() || exception.code() == ResultCode::Cancelled =>
+            {
+                exception.error_data().cloned()
+            }
+            _ => None,
+        };
+    }
+
+    /// Returns the stack trace of the error thrown by the most recent top-level evaluation,
+    /// or `None` if it succeeded.
+    ///
+    /// Unlike the `errorInfo` variable, which keeps the most recent error's stack trace
+    /// until the next error, this reflects only the most recent call to
+    /// [`eval`](#method.eval), [`eval_value`](#method.eval_value), or one of the `expr`
+    /// methods.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    ///
+    /// assert!(interp.eval("error oops").is_err());
+    /// assert!(interp.last_error_info().is_some());
+    ///
+    /// assert!(interp.eval("set a 1").is_ok());
+    /// assert!(interp.last_error_info().is_none());
+    /// assert!(!interp.scalar("errorInfo").unwrap().as_str().is_empty());
+    /// ```
+    pub fn last_error_info(&self) -> Option<Value> {
+        self.last_error.as_ref().map(|data| data.error_info())
+    }
+
+    /// Returns the error code of the error thrown by the most recent top-level evaluation,
+    /// or `None` if it succeeded.  See [`last_error_info`](#method.last_error_info).
+    pub fn last_error_code(&self) -> Option<Value> {
+        self.last_error.as_ref().map(|data| data.error_code())
     }
 
     /// Evaluates a parsed Script, producing a normal MoltResult.
@@ -884,10 +1900,25 @@ where
     /// if an error is encountered.
     /// In this case, it will only return an error if the last command
     /// emits one.
+    #[inline]
     pub(crate) fn eval_script(&mut self, script: &Script) -> MoltResult {
+        self.eval_script_with(script, |_, _| ControlFlow::Continue(()))
+    }
+
+    /// Evaluates a parsed Script as `eval_script` does, calling `progress` with the number
+    /// of commands done and the total after each command.  The no-op callbacks used by
+    /// `eval_script` and `eval_parsed` compile away.
+    fn eval_script_with<F>(&mut self, script: &Script, mut progress: F) -> MoltResult
+    where
+        F: FnMut(usize, usize) -> ControlFlow<()>,
+    {
         let mut result_value: MoltResult = Ok(Value::empty());
+        let total = script.commands().len();
+
+        for (index, word_vec) in script.commands().iter().enumerate() {
+            self.check_interrupted()?;
+            self.count_command()?;
 
-        for word_vec in script.commands() {
             let words = match self.eval_word_vec(word_vec.words()) {
                 Ok(words) => words,
                 Err(e) => {
@@ -903,6 +1934,9 @@ where
                             }
                         }
                         result_value = Err(e);
+                        if progress(index + 1, total).is_break() {
+                            return Err(Self::aborted());
+                        }
                         continue;
                     }
                     return Err(e);
@@ -928,7 +1962,11 @@ where
 
             // if let Some(cmd) = self.commands.get(name) {
             // let start = Instant::now();
-            let result = (self.command.fn_execute)(name, self, words.as_slice());
+            let result = if self.exec_traces.is_empty() {
+                self.execute(name, words.as_slice())
+            } else {
+                self.execute_traced(name, words.as_slice())
+            };
             // self.profile_save(&format!("cmd.execute({})", name), start);
 
             if let Ok(v) = result {
@@ -946,14 +1984,11 @@ where
                         // FIRST, new error, an error from within a proc, or an error from
                         // within some other body (ignored).
                         if exception.is_new_error() {
-                            exception.add_error_info("while executing");
                             // TODO: Add command.  In standard TCL, this is the text of the command
                             // before interpolation; at present, we don't have that info in a
-                            // convenient form.  For now, just convert the final words to a string.
-                            exception.add_error_info(&format!(
-                                "  \"{}\"",
-                                &list_to_string(&words)
-                            ));
+                            // convenient form.  For now, use the final words, which are
+                            // formatted only if the error info is used.
+                            exception.add_error_command(&words);
                         }
                         // else if cmd.is_proc() {
                         //   exception.add_error_info("    invoked from within");
@@ -976,6 +2011,10 @@ where
             } else {
                 unreachable!();
             }
+
+            if progress(index + 1, total).is_break() {
+                return Err(Self::aborted());
+            }
             // } else {
             //   let err = molt_err!("invalid command name \"{}\"", name);
             //   if !self.continue_on_error {
@@ -989,6 +2028,11 @@ where
         result_value
     }
 
+    /// The error thrown when a progress callback stops evaluation.
+    fn aborted() -> Exception {
+        Exception::molt_err2(Value::from("ABORTED"), Value::from("evaluation aborted"))
+    }
+
     /// Evaluates a WordVec, producing a list of Values.  The expansion operator is handled
     /// as a special case.
     #[inline]
@@ -998,9 +2042,7 @@ where
         for word in words {
             if let Word::Expand(word_to_expand) = word {
                 let value = self.eval_word(word_to_expand)?;
-                for val in &*value.as_list()? {
-                    list.push(val.clone());
-                }
+                list.extend(value.as_list_iter()?);
             } else {
                 list.push(self.eval_word(word)?);
             }
@@ -1044,7 +2086,7 @@ where
                 // FIRST, set the -code
                 match exception.code() {
                     ResultCode::Okay => unreachable!(), // TODO: Not in use yet
-                    ResultCode::Error => {
+                    ResultCode::Error | ResultCode::Cancelled => {
                         let data =
                             exception.error_data().expect("Error has no error data");
                         opts.insert(OPT_CODE.into(), "1".into());
@@ -1068,6 +2110,10 @@ where
                     ResultCode::Continue => {
                         opts.insert(OPT_CODE.into(), "4".into());
                     }
+                    // Never caught, but treated like a return.
+                    ResultCode::TailCall => {
+                        opts.insert(OPT_CODE.into(), "2".into());
+                    }
                     ResultCode::Other(num) => {
                         opts.insert(OPT_CODE.into(), num.into());
                     }
@@ -1082,7 +2128,9 @@ where
     }
 
     /// Determines whether or not the script is syntactically complete,
-    /// e.g., has no unmatched quotes, brackets, or braces.
+    /// e.g., has no unmatched quotes, brackets, or braces.  Any parse error makes the
+    /// script incomplete; use [`completeness`](#method.completeness) to tell input that
+    /// needs more lines from input that is simply malformed.
     ///
     /// REPLs use this to determine whether or not to ask for another line of
     /// input.
@@ -1101,6 +2149,42 @@ where
         parser::parse(script).is_ok()
     }
 
+    /// Determines whether or not the script is syntactically complete, and if not,
+    /// which delimiter is left open and on which line it was opened.  Scripts that
+    /// fail to parse for other reasons, e.g., `a {b}c`, are malformed: asking for more
+    /// input won't fix them, so a REPL should report the error at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// assert_eq!(interp.completeness("set a [expr {1+1}]"), Completeness::Complete);
+    /// assert_eq!(
+    ///     interp.completeness("set a [expr {\n1+1"),
+    ///     Completeness::Incomplete { kind: Delimiter::Brace, line: 1 }
+    /// );
+    /// assert_eq!(
+    ///     interp.completeness("set a {b}c"),
+    ///     Completeness::Malformed { message: "extra characters after close-brace".into() }
+    /// );
+    /// ```
+    pub fn completeness(&mut self, script: &str) -> Completeness {
+        match parser::parse(script) {
+            Err(exception) => {
+                match (exception.uncompleted_kind(), exception.uncompleted_position()) {
+                    (Some(kind), Some((_, line))) => {
+                        Completeness::Incomplete { kind, line }
+                    }
+                    _ => {
+                        Completeness::Malformed { message: exception.value().to_string() }
+                    }
+                }
+            }
+            Ok(_) => Completeness::Complete,
+        }
+    }
+
     /// Evaluates a [Molt expression](https://wduquette.github.io/molt/ref/expr.html) and
     /// returns its value.  The expression is passed as a `Value` which is interpreted as a
     /// `String`.
@@ -1120,11 +2204,39 @@ where
     /// ```
     #[inline]
     pub fn expr(&mut self, expr: &Value) -> MoltResult {
+        Ok(Value::from(self.expr_value(expr)?))
+    }
+
+    /// Evaluates a [Molt expression](https://wduquette.github.io/molt/ref/expr.html)
+    /// and returns its value as a typed [`Datum`], so that callers can tell an integer
+    /// result from a floating-point or string result without re-parsing it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// # fn dummy() -> Result<String,Exception> {
+    /// let mut interp = Interp::default();
+    ///
+    /// let datum = interp.expr_value(&Value::from("0xFF00 >> 8"))?;
+    /// assert_eq!(datum.datum_type(), DatumType::Int);
+    /// assert_eq!(datum.as_int(), Some(255));
+    ///
+    /// let datum = interp.expr_value(&Value::from("1.5 * 2"))?;
+    /// assert_eq!(datum.as_float(), Some(3.0));
+    /// # Ok("dummy".to_string())
+    /// # }
+    /// ```
+    pub fn expr_value(&mut self, expr: &Value) -> Result<Datum, Exception> {
         // Evaluate the expression and set the errorInfo/errorCode.
-        let result = expr::expr(self, expr);
+        let result = expr::expr_value(self, expr);
 
         if let Err(exception) = &result {
-            self.set_global_error_data(exception.error_data())?;
+            self.set_global_error_data(exception.error_data());
+        }
+
+        if self.num_levels == 0 {
+            self.set_last_error(&result);
         }
 
         result
@@ -1236,8 +2348,12 @@ where
     /// # molt_ok!()
     /// # }
     /// ```
+    ///
+    /// See [`Value::as_var_name`] for how the name is split into array name and index.
+    ///
+    /// [`Value::as_var_name`]: ../value/struct.Value.html#method.as_var_name
     #[inline]
-    pub fn var(&self, var_name: &Value) -> MoltResult {
+    pub fn var(&mut self, var_name: &Value) -> MoltResult {
         let var_name = &*var_name.as_var_name();
         match var_name.index() {
             Some(index) => self.element(var_name.name(), index),
@@ -1353,7 +2469,10 @@ where
     /// # }
     /// ```
     #[inline]
-    pub fn scalar(&self, name: &str) -> MoltResult {
+    pub fn scalar(&mut self, name: &str) -> MoltResult {
+        if !self.var_traces.is_empty() {
+            self.call_traces(name, None, TraceOp::Read)?;
+        }
         self.scopes.get(name)
     }
 
@@ -1379,7 +2498,8 @@ where
     /// ```
     #[inline]
     pub fn set_scalar(&mut self, name: &str, value: Value) -> Result<(), Exception> {
-        self.scopes.set(name, value)
+        self.scopes.set(name, value)?;
+        self.call_write_traces(name, None)
     }
 
     /// Sets the value of the named scalar variable in the current scope, creating the variable
@@ -1404,7 +2524,13 @@ where
     pub fn set_scalar_return(&mut self, name: &str, value: Value) -> MoltResult {
         // Clone the value, since we'll be returning it out again.
         self.scopes.set(name, value.clone())?;
-        Ok(value)
+        if self.var_traces.is_empty() {
+            return Ok(value);
+        }
+
+        // A trace may have changed the value.
+        self.call_write_traces(name, None)?;
+        Ok(self.scopes.get(name).unwrap_or(value))
     }
 
     /// Retrieves the value of the named array element in the current scope.
@@ -1431,7 +2557,10 @@ where
     /// # }
     /// ```
     #[inline]
-    pub fn element(&self, name: &str, index: &str) -> MoltResult {
+    pub fn element(&mut self, name: &str, index: &str) -> MoltResult {
+        if !self.var_traces.is_empty() {
+            self.call_traces(name, Some(index), TraceOp::Read)?;
+        }
         self.scopes.get_elem(name, index)
     }
 
@@ -1462,7 +2591,8 @@ where
         index: &str,
         value: Value,
     ) -> Result<(), Exception> {
-        self.scopes.set_elem(name, index, value)
+        self.scopes.set_elem(name, index, value)?;
+        self.call_write_traces(name, Some(index))
     }
 
     /// Sets the value of an array element in the current scope, creating the variable
@@ -1493,7 +2623,81 @@ where
     ) -> MoltResult {
         // Clone the value, since we'll be returning it out again.
         self.scopes.set_elem(name, index, value.clone())?;
-        Ok(value)
+        if self.var_traces.is_empty() {
+            return Ok(value);
+        }
+
+        // A trace may have changed the value.
+        self.call_write_traces(name, Some(index))?;
+        Ok(self.scopes.get_elem(name, index).unwrap_or(value))
+    }
+
+    /// Sets the named scalar variables in the current scope, in order, exactly as calling
+    /// [`set_scalar`](#method.set_scalar) for each would, but faster: a variable in the
+    /// current scope is found with a single lookup.
+    ///
+    /// Returns an error if one of the variables exists and is an array variable.  The batch
+    /// stops there: the variables before it are set, and it and those after it are not.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.set_scalars(&[("x", Value::from(1)), ("y", Value::from(2))]).unwrap();
+    /// assert_eq!(interp.expr_int(&Value::from("$x + $y")), Ok(3));
+    ///
+    /// interp.eval("set arr(1) a").unwrap();
+    /// let pairs = [("a", Value::from(1)), ("arr", Value::from(2)), ("b", Value::from(3))];
+    /// assert!(interp.set_scalars(&pairs).is_err());
+    /// assert_eq!(interp.scalars(&["a", "b"]), vec![Some(Value::from(1)), None]);
+    /// ```
+    pub fn set_scalars(&mut self, pairs: &[(&str, Value)]) -> Result<(), Exception> {
+        if self.var_traces.is_empty() {
+            return self.scopes.set_many(pairs);
+        }
+
+        for (name, value) in pairs {
+            self.set_scalar(name, value.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Sets elements of the named array variable in the current scope, creating the
+    /// variable if necessary, exactly as calling [`set_element`](#method.set_element) for
+    /// each would, but looking up the variable only once.  Each pair is an index and a
+    /// value.
+    ///
+    /// Returns an error if the variable exists and is not an array variable, in which case
+    /// no element is set.  An empty batch does nothing, not even create the variable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp
+    ///     .set_elements("temp", &[("cpu", Value::from(61)), ("gpu", Value::from(70))])
+    ///     .unwrap();
+    /// assert_eq!(interp.element("temp", "gpu"), Ok(Value::from(70)));
+    /// ```
+    pub fn set_elements(
+        &mut self,
+        name: &str,
+        pairs: &[(&str, Value)],
+    ) -> Result<(), Exception> {
+        self.scopes.set_elems(name, pairs)?;
+
+        for (index, _) in pairs {
+            self.call_write_traces(name, Some(index))?;
+        }
+        Ok(())
+    }
+
+    /// Retrieves the values of the named scalar variables in the current scope.  Each is
+    /// `None` if the variable doesn't exist or is an array variable.
+    pub fn scalars(&self, names: &[&str]) -> Vec<Option<Value>> {
+        names.iter().map(|name| self.scopes.get(name).ok()).collect()
     }
 
     /// Unsets a variable, whether scalar or array, given its name in the current scope.  For
@@ -1521,7 +2725,16 @@ where
     /// ```
     #[inline]
     pub fn unset(&mut self, name: &str) {
+        if self.var_traces.is_empty() || !self.scopes.exists(name) {
+            self.scopes.unset(name);
+            return;
+        }
+
+        let located = self.scopes.locate_var(name).map(|(l, k)| (l, k.into_owned()));
         self.scopes.unset(name);
+        if let Ok((level, key)) = located {
+            self.call_unset_traces(level, &key, name, None);
+        }
     }
 
     /// Unsets the value of the named variable or array element in the current scope.
@@ -1579,7 +2792,15 @@ where
     /// ```
     #[inline]
     pub fn unset_element(&mut self, array_name: &str, index: &str) {
+        if self.var_traces.is_empty() || !self.scopes.elem_exists(array_name, index) {
+            self.scopes.unset_element(array_name, index);
+            return;
+        }
+
         self.scopes.unset_element(array_name, index);
+        if let Ok((level, key)) = self.scopes.locate_var(array_name) {
+            self.call_unset_traces(level, &key, array_name, Some(index));
+        }
     }
 
     /// Gets a list of the names of the variables that are visible in the current scope.
@@ -1645,32 +2866,115 @@ where
     /// Note: the level is the absolute level, not the level relative to the
     /// current stack level, i.e., level=0 is the global scope.
     ///
-    /// This method is used to implement the `upvar` command, which allows variables to be
-    /// passed by name; client code should rarely need to access it directly.
+    /// This method is used to implement the `global` command; see
+    /// [`upvar_as`](#method.upvar_as) for linking to a variable of another name.
+    ///
+    /// Returns an error if the variable would be linked to itself.
     #[inline]
-    pub fn upvar(&mut self, level: usize, name: &str) {
+    pub fn upvar(&mut self, level: usize, name: &str) -> Result<(), Exception> {
         assert!(level <= self.scopes.current(), "Invalid scope level");
-        self.scopes.upvar(level, name);
+        self.scopes.upvar(level, name)
     }
 
-    /// Pushes a variable scope (i.e., a stack level) onto the scope stack.
+    /// Links the variable `name` in the current scope to the variable `other` in the
+    /// scope at the given level, counting from `0`, the global scope, so that `name` can
+    /// be used to read, set, or unset `other`, as the `upvar` command does.  `other` may
+    /// be an array.  The level may be the current one, giving a variable a second name.
     ///
-    /// Procs use this to define their local scope.  Client code should seldom need to call
-    /// this directly, but it can be useful in a few cases.  For example, the Molt
-    /// test harness's `test` command runs its body in a local scope as an aid to test
-    /// cleanup.
+    /// Returns an error if the variable would be linked to itself.  An existing variable
+    /// called `name` in the current scope is replaced by the link.
     ///
-    /// **Note:** a command that pushes a scope must also call `Interp::pop_scope` before it
-    /// exits!
-    #[inline]
-    pub fn push_scope(&mut self) {
-        self.scopes.push();
-    }
-
-    /// Pops a variable scope (i.e., a stack level) off of the scope stack.  Calls to
-    /// `Interp::push_scope` and `Interp::pop_scope` must exist in pairs.
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.set_scalar("total", Value::from(1)).unwrap();
+    ///
+    /// let mut scope = interp.local_scope();
+    /// scope.upvar_as(0, "total", "sum").unwrap();
+    /// scope.eval("incr sum 2").unwrap();
+    /// drop(scope);
+    ///
+    /// assert_eq!(interp.scalar("total").unwrap().as_int(), Ok(3));
+    /// ```
+    pub fn upvar_as(
+        &mut self,
+        level: usize,
+        other: &str,
+        name: &str,
+    ) -> Result<(), Exception> {
+        assert!(level <= self.scopes.current(), "Invalid scope level");
+        self.scopes.upvar_as(level, other, name)
+    }
+
+    /// Is `name` a variable defined in the current scope itself, rather than a link to a
+    /// variable in another scope?  The `upvar` command won't replace such a variable.
+    pub(crate) fn var_is_defined_here(&self, name: &str) -> bool {
+        self.scopes.is_defined_here(name)
+    }
+
+    /// Pushes a variable scope (i.e., a stack level) onto the scope stack.
+    ///
+    /// Procs use this to define their local scope.  Client code should seldom need to call
+    /// this directly, but it can be useful in a few cases.  For example, the Molt
+    /// test harness's `test` command runs its body in a local scope as an aid to test
+    /// cleanup.
+    ///
+    /// **Note:** a command that pushes a scope must also call `Interp::pop_scope` before it
+    /// exits, however it exits!  [`local_scope`](#method.local_scope) does this for you.
+    #[inline]
+    pub fn push_scope(&mut self) {
+        self.scopes.push();
+    }
+
+    /// Pushes a variable scope, returning a [`ScopeGuard`] that pops it when dropped.
+    /// Use the guard as the interpreter while the scope is needed.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// fn in_scope(interp: &mut Interp<()>) -> MoltResult {
+    ///     let mut interp = interp.local_scope();
+    ///     interp.set_scalar("x", Value::from(1))?;
+    ///     interp.eval("error oops")
+    /// }
+    ///
+    /// let mut interp = Interp::default();
+    /// assert!(in_scope(&mut interp).is_err());
+    /// assert_eq!(interp.scope_level(), 0);
+    /// assert!(interp.scalar("x").is_err());
+    /// ```
+    pub fn local_scope(&mut self) -> ScopeGuard<'_, Ctx> {
+        let level = self.scopes.current();
+        self.scopes.push();
+        ScopeGuard { interp: self, level }
+    }
+
+    /// Pops a variable scope (i.e., a stack level) off of the scope stack.  Calls to
+    /// `Interp::push_scope` and `Interp::pop_scope` must exist in pairs.
+    ///
+    /// The `unset` traces on the scope's variables are called as they go away.
     #[inline]
     pub fn pop_scope(&mut self) {
+        if !self.var_traces.is_empty() {
+            for trace in self.var_traces.take_level(self.scopes.current()) {
+                if trace.has_op(TraceOp::Unset) {
+                    let _ = self.call_trace(
+                        trace.callback(),
+                        trace.key(),
+                        trace.index().unwrap_or(""),
+                        TraceOp::Unset,
+                    );
+                }
+            }
+        }
+        self.scopes.pop();
+    }
+
+    /// Pops a scope left behind by a panic, dropping the traces on its variables
+    /// without calling them.
+    fn discard_scope(&mut self) {
+        self.var_traces.take_level(self.scopes.current());
         self.scopes.pop();
     }
 
@@ -1683,6 +2987,42 @@ where
         self.scopes.current()
     }
 
+    /// Evaluates the script in the scope at the given level, counting from `0`, the
+    /// global scope, as the `uplevel` command does.  The scopes below it are set aside
+    /// while the script runs, so that it sees the variables of the scope at that level
+    /// as its own, and procs it calls push their scopes just above it.  It's an error if
+    /// there's no such level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.set_scalar("x", Value::from("global")).unwrap();
+    ///
+    /// let mut scope = interp.local_scope();
+    /// scope.set_scalar("x", Value::from("local")).unwrap();
+    /// scope.eval_in_level(0, &Value::from("append x !")).unwrap();
+    /// assert_eq!(scope.scalar("x").unwrap().as_str(), "local");
+    ///
+    /// drop(scope);
+    /// assert_eq!(interp.scalar("x").unwrap().as_str(), "global!");
+    /// ```
+    pub fn eval_in_level(&mut self, level: usize, script: &Value) -> MoltResult {
+        let current = self.scopes.current();
+        if level > current {
+            return molt_err!("bad level \"{}\"", level);
+        }
+        if level == current {
+            return self.eval_value(script);
+        }
+
+        let scopes = self.scopes.suspend_above(level);
+        let traces = self.var_traces.take_level(level + 1);
+        let guard = SuspendGuard { interp: self, level, scopes: Some(scopes), traces };
+        guard.interp.eval_value(script)
+    }
+
     ///-----------------------------------------------------------------------------------
     /// Array Manipulation Methods
     ///
@@ -1692,7 +3032,17 @@ where
     /// exist, or if the variable is not an array variable.
     #[inline]
     pub(crate) fn array_unset(&mut self, array_name: &str) {
+        if self.var_traces.is_empty() || !self.scopes.array_exists(array_name) {
+            self.scopes.array_unset(array_name);
+            return;
+        }
+
+        let located =
+            self.scopes.locate_var(array_name).map(|(l, k)| (l, k.into_owned()));
         self.scopes.array_unset(array_name);
+        if let Ok((level, key)) = located {
+            self.call_unset_traces(level, &key, array_name, None);
+        }
     }
 
     /// Determines whether or not the name is the name of an array variable.
@@ -1769,6 +3119,9 @@ where
     pub fn array_set(&mut self, array_name: &str, kvlist: &[Value]) -> MoltResult {
         if kvlist.len() % 2 == 0 {
             self.scopes.array_set(array_name, kvlist)?;
+            for kv in kvlist.chunks(2) {
+                self.call_write_traces(array_name, Some(kv[0].as_str()))?;
+            }
             molt_ok!()
         } else {
             molt_err!("list must have an even number of elements")
@@ -1820,24 +3173,239 @@ where
         self.scopes.array_size(array_name)
     }
 
-    // //--------------------------------------------------------------------------------------------
-    // // Command Definition and Handling
-
-    // /// Adds a binary command with no related context to the interpreter.  This is the normal
-    // /// way to add most commands.
-    // ///
-    // /// If the command needs access to some form of application or context data,
-    // /// use [`add_context_command`](#method.add_context_command) instead.  See the
-    // /// [module level documentation](index.html) for an overview and examples.
-    // pub fn add_command(
-    //   &mut self,
-    //   name: &str,
-    //   func: impl Fn(&mut Interp<Ctx>, &[Value]) -> MoltResult + 'static,
-    // ) {
-    //   self
-    //     .commands
-    //     .insert(name.into(), Rc::new(Command::Native(Box::new(func))));
-    // }
+    //--------------------------------------------------------------------------------------------
+    // Command Definition and Handling
+
+    /// Adds a command at runtime, e.g., an object command or a plugin's command.  The
+    /// commands of [`gen_command!`](../macro.gen_command.html) are fixed when the
+    /// interpreter is built; a runtime command is found after them, and before the procs,
+    /// so it can't take the place of a `gen_command!` command, and a proc can't take its
+    /// place.  Adding a command again replaces it, as if the old one had been removed.
+    /// Scripts can rename or delete it with `rename`.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// # use std::cell::Cell;
+    /// # use std::rc::Rc;
+    /// let mut interp = Interp::default();
+    /// let count = Rc::new(Cell::new(0));
+    ///
+    /// let counter = count.clone();
+    /// interp.add_command(
+    ///     "counter",
+    ///     Rc::new(move |_: &mut Interp<()>, _: &[Value]| {
+    ///         counter.set(counter.get() + 1);
+    ///         molt_ok!(counter.get())
+    ///     }),
+    /// );
+    ///
+    /// assert_eq!(interp.eval("counter; counter").unwrap().as_int(), Ok(2));
+    /// interp.eval("rename counter {}").unwrap();
+    /// assert!(interp.eval("counter").is_err());
+    /// assert_eq!(count.get(), 2);
+    /// ```
+    pub fn add_command(&mut self, name: &str, func: CommandFunc<Ctx>) {
+        self.remove_command(name);
+        self.runtime_commands.insert(name.into(), func);
+    }
+
+    /// Adds a command at runtime, as [`add_command`](#method.add_command) does, with a
+    /// destructor, which is called when the command is removed: by
+    /// [`remove_command`](#method.remove_command), by `rename` to the empty string, or by
+    /// adding another command of the same name.  Renaming the command doesn't call it,
+    /// nor does dropping the interpreter.  The destructor cleans up after the command,
+    /// e.g., by telling the application that an object is gone.  See also
+    /// [`define_object!`](../macro.define_object.html).
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// # use std::rc::Rc;
+    /// let mut interp = Interp::default();
+    /// interp.add_command_with_destructor(
+    ///     "plugin",
+    ///     Rc::new(|_: &mut Interp<()>, _: &[Value]| molt_ok!("working")),
+    ///     Box::new(|interp: &mut Interp<()>| {
+    ///         interp.set_scalar("unloaded", Value::from(1)).unwrap();
+    ///     }),
+    /// );
+    ///
+    /// interp.eval("rename plugin plugin2; plugin2").unwrap();
+    /// assert!(!interp.var_exists(&Value::from("unloaded")));
+    /// interp.eval("rename plugin2 {}").unwrap();
+    /// assert!(interp.var_exists(&Value::from("unloaded")));
+    /// ```
+    pub fn add_command_with_destructor(
+        &mut self,
+        name: &str,
+        func: CommandFunc<Ctx>,
+        destructor: CommandDestructor<Ctx>,
+    ) {
+        self.add_command(name, func);
+        self.command_destructors.insert(name.into(), destructor);
+    }
+
+    /// Removes a command added by [`add_command`](#method.add_command), calling its
+    /// destructor, if any.  Returns false if there's no such command.
+    pub fn remove_command(&mut self, name: &str) -> bool {
+        if self.runtime_commands.remove(name).is_none() {
+            return false;
+        }
+        if let Some(destructor) = self.command_destructors.remove(name) {
+            destructor(self);
+        }
+        true
+    }
+
+    /// Renames a command added by [`add_command`](#method.add_command), along with its
+    /// destructor, returning false if there's no such command.  A command with the new
+    /// name is replaced.
+    pub fn rename_command(&mut self, old_name: &str, new_name: &str) -> bool {
+        let Some(func) = self.runtime_commands.remove(old_name) else {
+            return false;
+        };
+        let destructor = self.command_destructors.remove(old_name);
+
+        self.add_command(new_name, func);
+        if let Some(destructor) = destructor {
+            self.command_destructors.insert(new_name.into(), destructor);
+        }
+        true
+    }
+
+    /// Returns the command added by [`add_command`](#method.add_command) with the given
+    /// name, if any.  `gen_command!`'s dispatcher calls this for names it doesn't know.
+    #[inline]
+    pub fn get_command(&self, name: &str) -> Option<CommandFunc<Ctx>> {
+        if self.runtime_commands.is_empty() {
+            return None;
+        }
+        self.runtime_commands.get(name).cloned()
+    }
+
+    /// Returns true if there's a command added by [`add_command`](#method.add_command)
+    /// with the given name.
+    #[inline]
+    pub fn contains_command(&self, name: &str) -> bool {
+        self.runtime_commands.contains_key(name)
+    }
+
+    /// Returns the names of the commands added by [`add_command`](#method.add_command),
+    /// sorted.
+    fn runtime_command_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> =
+            self.runtime_commands.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Saves the data in the interpreter's context cache, which holds at most one value of
+    /// each type, and returns the value of its type saved before, if any.  The cache is
+    /// for state that isn't part of the interpreter's context, e.g., an extension crate's:
+    /// each extension saves a value of its own type, without coordinating with the
+    /// application or the other extensions.  See the
+    /// [module level documentation](index.html) for an example.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// #[derive(Default)]
+    /// struct Stats {
+    ///     calls: usize,
+    /// }
+    ///
+    /// let mut interp = Interp::default();
+    /// interp.save_context(Stats::default());
+    /// interp.get_context_mut::<Stats>().unwrap().calls += 1;
+    /// assert_eq!(interp.get_context::<Stats>().unwrap().calls, 1);
+    ///
+    /// let stats: Stats = interp.remove_context().unwrap();
+    /// assert_eq!(stats.calls, 1);
+    /// assert!(interp.get_context::<Stats>().is_none());
+    /// ```
+    pub fn save_context<T: 'static>(&mut self, data: T) -> Option<T> {
+        self.contexts.insert(data)
+    }
+
+    /// Returns the value of type `T` in the context cache, if any; see
+    /// [`save_context`](#method.save_context).
+    pub fn get_context<T: 'static>(&self) -> Option<&T> {
+        self.contexts.get()
+    }
+
+    /// Returns the value of type `T` in the context cache mutably, if any; see
+    /// [`save_context`](#method.save_context).
+    pub fn get_context_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.contexts.get_mut()
+    }
+
+    /// Removes the value of type `T` from the context cache, returning it, if any; see
+    /// [`save_context`](#method.save_context).
+    pub fn remove_context<T: 'static>(&mut self) -> Option<T> {
+        self.contexts.remove()
+    }
+
+    /// Calls the function with the interpreter and the value of type `T` in the context
+    /// cache, returning its result, or `None` if there's no such value.  The value is
+    /// taken out of the cache for the duration of the call, so that the function can use
+    /// both it and the interpreter.
+    pub fn with_context<T: 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut Interp<Ctx>, &mut T) -> R,
+    ) -> Option<R> {
+        let mut data = self.contexts.remove::<T>()?;
+        let result = f(self, &mut data);
+
+        // The call may have saved another value of the type meanwhile; it wins.
+        if self.contexts.get::<T>().is_none() {
+            self.contexts.insert(data);
+        }
+        Some(result)
+    }
+
+    /// Adds a command at runtime, as [`add_command`](#method.add_command) does, that is
+    /// passed the value of type `T` in the context cache along with the interpreter; see
+    /// [`with_context`](#method.with_context).  It's an error to call the command when
+    /// there's no such value, or while the command, or another using the same value, is
+    /// already running.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// struct Stats {
+    ///     num_passed: usize,
+    /// }
+    ///
+    /// fn cmd_pass(_: &mut Interp<()>, stats: &mut Stats, _: &[Value]) -> MoltResult {
+    ///     stats.num_passed += 1;
+    ///     molt_ok!(stats.num_passed)
+    /// }
+    ///
+    /// let mut interp = Interp::default();
+    /// interp.add_context_command("pass", cmd_pass);
+    /// assert!(interp.eval("pass").is_err());
+    ///
+    /// interp.save_context(Stats { num_passed: 0 });
+    /// assert_eq!(interp.eval("pass; pass").unwrap().as_int(), Ok(2));
+    /// assert_eq!(interp.get_context::<Stats>().unwrap().num_passed, 2);
+    /// ```
+    pub fn add_context_command<T: 'static>(
+        &mut self,
+        name: &str,
+        func: ContextCommandFunc<Ctx, T>,
+    ) {
+        self.add_command(
+            name,
+            Rc::new(move |interp: &mut Interp<Ctx>, argv: &[Value]| {
+                match interp.with_context(|interp, data: &mut T| func(interp, data, argv))
+                {
+                    Some(result) => result,
+                    None => molt_err!(
+                        "context of command \"{}\" isn't available: {}",
+                        argv[0],
+                        std::any::type_name::<T>()
+                    ),
+                }
+            }),
+        );
+    }
 
     /// Adds a procedure to the interpreter.
     ///
@@ -1847,22 +3415,351 @@ where
     /// TODO: If this method is ever made public, the parameter list validation done
     /// in cmd_proc should be moved here.
     #[inline]
-    pub(crate) fn add_proc(&mut self, name: &str, parms: &[Value], body: &Value) {
+    ///
+    /// If the procedure is being redefined with the same body text, e.g., by a script that
+    /// generates procedures in a loop or by `import_state`, the old body `Value` is kept,
+    /// so that its parsed script is reused rather than parsed again.  If eager parsing is
+    /// enabled, a body with a syntax error is rejected.
+    pub(crate) fn add_proc(
+        &mut self,
+        name: &str,
+        parms: &[Value],
+        body: &Value,
+    ) -> Result<(), Exception> {
+        // FIRST, a name is relative to the current namespace.
+        let name = namespace::qualify(self.scopes.namespace(), name).into_owned();
+
+        let body = match self.procs.get(&name) {
+            Some(proc) if proc.body.as_str() == body.as_str() => proc.body.clone(),
+            _ => body.clone(),
+        };
+
+        if self.eager_proc_parse {
+            body.as_script()
+                .map_err(|exception| proc_parse_error(&name, exception))?;
+        }
+
+        let namespace = self.add_namespace(namespace::parent(&name));
+        self.proc_index.insert(&name);
         self.procs.insert(
-            name.into(),
-            Rc::new(Procedure { parms: parms.to_owned(), body: body.clone() }),
+            name,
+            Rc::new(Procedure { parms: parms.to_owned(), body, namespace }),
         );
+        Ok(())
     }
 
     /// Determines whether or not the interpreter contains a command with the given
     /// name.
     #[inline]
     pub fn has_proc(&self, name: &str) -> bool {
-        self.procs.contains_key(name)
+        self.procs.contains_key(&*self.command_key(name))
+    }
+
+    /// Returns the name under which the named command is found: the qualified name,
+    /// without the leading `::`, of a proc in the current namespace, if there is one, and
+    /// the name in the global namespace otherwise.  This is called for every command, so
+    /// it finds the qualified name in the proc index rather than building it.
+    pub(crate) fn command_key<'a>(&self, name: &'a str) -> CommandKey<'a> {
+        if let Some(absolute) = name.strip_prefix("::") {
+            return CommandKey::Name(absolute);
+        }
+
+        let namespace = self.scopes.namespace();
+        if !namespace.is_empty() {
+            if let Some(qualified) = self.proc_index.get(namespace, name) {
+                return CommandKey::Qualified(qualified.clone());
+            }
+        }
+
+        CommandKey::Name(name)
+    }
+
+    /// Adds a math function, which `expr` can call as `name(arg, ...)` with any number
+    /// of arguments.  The function is passed the arguments' values, and its result is
+    /// used as a number.  A function with the name of a built-in function such as `abs`
+    /// is never called.
+    ///
+    /// Scripts can define math functions too: `expr` calls a procedure named
+    /// `tcl::mathfunc::name` or `mathfunc::name` when there is no function `name`.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// fn double2(_: &mut Interp<()>, args: &[Value]) -> MoltResult {
+    ///     if args.len() != 1 {
+    ///         return molt_err!("expected double2(x)");
+    ///     }
+    ///     molt_ok!(args[0].as_float()? * 2.0)
+    /// }
+    ///
+    /// let mut interp = Interp::default();
+    /// interp.add_math_func("double2", double2);
+    /// assert_eq!(interp.eval("expr {double2(1.5) + 1}").unwrap().as_str(), "4.0");
+    /// ```
+    pub fn add_math_func(&mut self, name: &str, func: MathFunc<Ctx>) {
+        self.math_funcs.insert(name.into(), func);
+    }
+
+    /// Defines an alias: a command that calls the command prefix, with the alias's
+    /// arguments appended, as `alias` does.  An alias takes the place of any command or
+    /// proc of the same name until it's removed with
+    /// [`remove_alias`](#method.remove_alias).
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.add_alias("ll", vec![Value::from("lsort"), Value::from("-dictionary")]);
+    /// assert_eq!(interp.eval("ll {b10 b9 a}").unwrap().as_str(), "a b9 b10");
+    /// ```
+    pub fn add_alias(&mut self, name: &str, prefix: MoltList) {
+        let alias = Alias { target: AliasTarget::Own, prefix };
+        self.aliases.insert(name.into(), alias);
+    }
+
+    /// Removes the alias, returning false if there's no such alias.
+    pub fn remove_alias(&mut self, name: &str) -> bool {
+        self.aliases.remove(name).is_some()
+    }
+
+    /// Creates a child interpreter, returning its name, `interp0`, `interp1`, and so on.
+    /// The child has its own commands, procs, and variables, and the same standard
+    /// commands as this interpreter.  A child that isn't safe also has `source` and the
+    /// `env` array; see [`Interp::new_safe`] for safe ones.  A safe interpreter's
+    /// children are always safe.
+    ///
+    /// A child shares its parent's cancellation handle, so canceling the parent cancels
+    /// the script it's running in the child; see
+    /// [`cancel_handle`](#method.cancel_handle).
+    ///
+    /// Scripts manage children with the `interp` command, which can also define aliases
+    /// between the parent and the child.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// let child = interp.create_child(true);
+    ///
+    /// interp.eval(&format!("interp alias {} double {{}} expr 2 *", child)).unwrap();
+    /// let result = interp.eval_in_child(&child, &Value::from("double 21")).unwrap();
+    /// assert_eq!(result.as_int(), Ok(42));
+    ///
+    /// let exception = interp.eval_in_child(&child, &Value::from("source x.tcl")).unwrap_err();
+    /// assert!(exception.value().as_str().starts_with("unknown command \"source\""));
+    /// ```
+    pub fn create_child(&mut self, safe: bool) -> String {
+        let name = (0..)
+            .map(|i| format!("interp{}", i))
+            .find(|name| !self.children.contains_key(name))
+            .expect("an unused name");
+        self.insert_child(name.clone(), safe);
+        name
+    }
+
+    /// Creates a child interpreter with the given name, as
+    /// [`create_child`](#method.create_child) does.  It's an error if the name is taken.
+    pub(crate) fn create_named_child(
+        &mut self,
+        name: &str,
+        safe: bool,
+    ) -> Result<(), Exception> {
+        if name.is_empty() || self.children.contains_key(name) {
+            return molt_err!(
+                "interpreter named \"{}\" already exists, cannot create",
+                name
+            );
+        }
+        self.insert_child(name.into(), safe);
+        Ok(())
+    }
+
+    /// Builds a child interpreter and adds it to the table of children.
+    fn insert_child(&mut self, name: String, safe: bool) {
+        use crate::prelude::*;
+
+        let mut command = gen_command!((), [(_SOURCE, cmd_source)], []);
+        command.hidden = self.command.hidden.clone();
+
+        let mut builder = InterpBuilder::new(()).commands(command).name(self.name);
+        if safe || self.safe {
+            builder = builder.safe(&[]);
+        }
+
+        let mut child = builder.build();
+        child.cancel = self.cancel.clone();
+        child.sticky_cancel = true;
+
+        self.children.insert(name, child);
+    }
+
+    /// Returns the named child interpreter, if it exists.  Aliases in the child to this
+    /// interpreter can't be called while the child is used this way; use
+    /// [`eval_in_child`](#method.eval_in_child) to evaluate scripts that call them.
+    pub fn child(&mut self, name: &str) -> Option<&mut Interp<()>> {
+        self.children.get_mut(name)
+    }
+
+    /// Returns the names of the child interpreters, sorted.
+    pub fn child_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.children.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Deletes the named child interpreter, returning false if there's no such child.  A
+    /// child that's evaluating a script, e.g., one whose alias is deleting it, is deleted
+    /// once the script is done.
+    pub fn delete_child(&mut self, name: &str) -> bool {
+        if self.children.remove(name).is_some() {
+            return true;
+        }
+
+        match self
+            .running_children
+            .iter_mut()
+            .rev()
+            .find(|(running, deleted)| running == name && !*deleted)
+        {
+            Some((_, deleted)) => {
+                *deleted = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Evaluates the script in the named child interpreter, as `interp eval` does.  While
+    /// it runs, the child's aliases to this interpreter can be called.
+    pub fn eval_in_child(&mut self, name: &str, script: &Value) -> MoltResult {
+        let Some(mut child) = self.children.remove(name) else {
+            return molt_err!("could not find interpreter \"{}\"", name);
+        };
+
+        self.running_children.push((name.into(), false));
+        let outer = child.parent.replace(ParentLink::new(self));
+        let result = child.eval_value(script);
+        child.parent = outer;
+        let (_, deleted) = self.running_children.pop().expect("running child");
+
+        // The script may have created another child of the same name, via an alias.
+        if !deleted {
+            self.children.entry(name.into()).or_insert(child);
+        }
+        result
+    }
+
+    /// Returns true if this is a safe interpreter; see [`Interp::new_safe`].
+    pub fn is_safe(&self) -> bool {
+        self.safe
+    }
+
+    /// Returns the aliases defined in this interpreter, for the `interp` command.
+    pub(crate) fn aliases(&mut self) -> &mut HashMap<String, Alias> {
+        &mut self.aliases
+    }
+
+    /// Registers a package, so that `package require` calls its init function the first
+    /// time the package is required.  The init function defines the package's procs,
+    /// math functions, and so on; when it returns, the package is provided with the
+    /// version given here, unless the init function provided it itself.  Registering a
+    /// package again replaces its version and init function.
+    ///
+    /// This is the standard way to ship a Molt extension as a crate: the crate exports an
+    /// init function for the application to register.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// fn init_greet(interp: &mut Interp<()>) -> MoltResult {
+    ///     interp.eval("proc greet {name} { return \"hello, $name\" }")
+    /// }
+    ///
+    /// let mut interp = Interp::default();
+    /// interp.register_package("greet", "1.0", init_greet);
+    /// assert_eq!(interp.eval("package require greet").unwrap().as_str(), "1.0");
+    /// assert_eq!(interp.eval("greet world").unwrap().as_str(), "hello, world");
+    /// ```
+    pub fn register_package(
+        &mut self,
+        name: &str,
+        version: &str,
+        init: PackageInitFn<Ctx>,
+    ) {
+        self.packages.register(name, version, init);
+    }
+
+    /// Returns the interpreter's packages, for the `package` command.
+    pub(crate) fn packages(&mut self) -> &mut Packages<Ctx> {
+        &mut self.packages
+    }
+
+    /// Sets the handler called for a command that isn't defined, or clears it with `None`.
+    /// The handler is passed the command's words, starting with its name, and its result is
+    /// the command's.  It makes auto-loading and fallbacks for DSLs possible.
+    ///
+    /// Scripts can do the same by defining a proc named `unknown`, which takes precedence
+    /// over the handler.  Neither is used by the dispatchers of
+    /// [`gen_command_minimal!`](../macro.gen_command_minimal.html) and
+    /// [`empty`](#method.empty), where an unknown command is always an error.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// fn shout(_: &mut Interp<()>, words: &[Value]) -> MoltResult {
+    ///     molt_ok!(words[0].as_str().to_uppercase())
+    /// }
+    ///
+    /// let mut interp = Interp::default();
+    /// assert!(interp.eval("hello").is_err());
+    ///
+    /// interp.set_unknown_handler(Some(shout));
+    /// assert_eq!(interp.eval("hello world").unwrap().as_str(), "HELLO");
+    /// ```
+    pub fn set_unknown_handler(&mut self, handler: Option<UnknownFn<Ctx>>) {
+        self.unknown_handler = handler;
+    }
+
+    /// Returns the name of the procedure that defines the named math function, if any.
+    fn math_func_proc(&self, name: &str) -> Option<String> {
+        [format!("tcl::mathfunc::{}", name), format!("mathfunc::{}", name)]
+            .into_iter()
+            .find(|proc_name| self.procs.contains_key(proc_name))
+    }
+
+    /// Determines whether there is a math function with the given name, defined either
+    /// by [`add_math_func`](#method.add_math_func) or as a procedure.
+    pub(crate) fn has_math_func(&self, name: &str) -> bool {
+        self.math_funcs.contains_key(name) || self.math_func_proc(name).is_some()
+    }
+
+    /// Calls the named math function.  Like a script evaluation, the call counts against
+    /// the recursion limit.
+    pub(crate) fn call_math_func(&mut self, name: &str, args: &[Value]) -> MoltResult {
+        let _guard = (self.num_levels == 0).then(|| self.enter_top_level());
+        self.num_levels += 1;
+
+        let result = if self.num_levels > self.recursion_limit {
+            molt_err!("too many nested calls to Interp::eval (infinite loop?)")
+        } else if let Some(func) = self.math_funcs.get(name).copied() {
+            func(self, args)
+        } else if let Some(proc_name) = self.math_func_proc(name) {
+            let proc = self.procs[&proc_name].clone();
+            let mut argv = Vec::with_capacity(args.len() + 1);
+            argv.push(Value::from(proc_name));
+            argv.extend_from_slice(args);
+            proc.execute(self, &argv)
+        } else {
+            molt_err!("unknown math function \"{}\"", name)
+        };
+
+        self.num_levels -= 1;
+        result
     }
 
     /// Renames the command.
     ///
+    /// A call of the procedure that is already executing, e.g., a procedure that
+    /// renames itself, isn't affected: the dispatcher holds its own `Rc` of the
+    /// procedure for the length of the call.
+    ///
     /// **Note:** This does not update procedures that reference the command under the old
     /// name.  This is intentional: it is a common TCL programming technique to wrap an
     /// existing command by renaming it and defining a new command with the old name that
@@ -1887,14 +3784,29 @@ where
     /// ```
     #[inline]
     pub fn rename_proc(&mut self, old_name: &str, new_name: &str) {
-        if let Some(proc) = self.procs.remove(old_name) {
-            self.procs.insert(new_name.into(), proc);
+        let old_name = self.command_key(old_name).into_owned();
+        let new_name = namespace::qualify(self.scopes.namespace(), new_name).into_owned();
+
+        if let Some(proc) = self.procs.remove(&old_name) {
+            self.proc_index.remove(&old_name);
+            self.proc_index.insert(&new_name);
+
+            // The procedure now runs in its new name's namespace.
+            let namespace = self.add_namespace(namespace::parent(&new_name));
+            let proc = if proc.namespace == namespace {
+                proc
+            } else {
+                Rc::new(Procedure { namespace, ..(*proc).clone() })
+            };
+            self.procs.insert(new_name, proc);
         }
     }
 
     /// Removes the command with the given name.
     ///
-    /// This would typically be done when destroying an object command.
+    /// This would typically be done when destroying an object command.  As with
+    /// [`rename_proc`](Self::rename_proc), calls that are already executing finish
+    /// normally.
     ///
     /// # Example
     ///
@@ -1928,10 +3840,14 @@ where
         // }
 
         // FINALLY, remove the command itself.
-        self.procs.remove(name);
+        let name = self.command_key(name).into_owned();
+        if self.procs.remove(&name).is_some() {
+            self.proc_index.remove(&name);
+        }
     }
 
-    /// Gets a vector of the names of the existing commands.
+    /// Gets a vector of the names of the existing commands, sorted by name, each name
+    /// appearing once.
     ///
     /// # Example
     ///
@@ -1948,30 +3864,152 @@ where
     /// ```
     #[inline]
     pub fn command_names(&self) -> MoltList {
-        let mut vec: MoltList =
-            self.command.native_names.iter().map(|&s| Value::from(s)).collect();
-        vec.extend(self.command.embedded_names.iter().map(|&s| Value::from(s)));
-        vec.extend(self.procs.keys().map(Value::from));
-        vec
+        let mut names: Vec<&str> = self
+            .command
+            .visible_native_names()
+            .chain(self.command.visible_embedded_names())
+            .chain(self.runtime_command_names())
+            .chain(self.procs.keys().map(String::as_str))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names.into_iter().map(Value::from).collect()
+    }
+
+    /// Returns up to three command names close to the given unknown name, nearest first,
+    /// for the "did you mean" clause of an "unknown command" error.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let interp = Interp::default();
+    /// assert_eq!(interp.similar_command_names("stirng"), vec!["string"]);
+    /// ```
+    pub fn similar_command_names(&self, name: &str) -> Vec<&str> {
+        util::similar_names(
+            name,
+            self.command
+                .visible_native_names()
+                .chain(self.command.visible_embedded_names())
+                .chain(self.runtime_command_names())
+                .chain(self.procs.keys().map(String::as_str)),
+        )
     }
     #[inline]
     pub fn native_command_names(&self) -> String {
-        self.command.native_names.join(", ")
+        let mut names: Vec<&str> = self.command.visible_native_names().collect();
+        names.extend(self.runtime_command_names());
+        names.join(", ")
     }
     #[inline]
     pub fn proc_command_names(&self) -> String {
-        self.procs
-            .keys()
-            .map(String::as_str)
-            .collect::<Vec<&str>>()
-            .join(", ")
+        let mut names: Vec<&str> = self.procs.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names.join(", ")
     }
 
-    /// Returns the body of the named procedure, or an error if the name doesn't
-    /// name a procedure.
+    /// Returns the kind of the named command, or `None` if there's no such command.  A
+    /// hidden standard command is only there if a proc stands in for it.
+    fn command_kind(&self, name: &str) -> Option<CommandType> {
+        if self.command.is_hidden(name) {
+            if self.contains_command(name) {
+                return Some(CommandType::Native);
+            }
+            return self.contains_proc(name).then_some(CommandType::Proc);
+        }
+        (self.command.fn_type)(name, self)
+    }
+
+    /// Returns the kind, help string, and usage of the named command, or `None` if
+    /// there's no such command.  The help and usage of an embedded command are those
+    /// of its `gen_command!` entry; a proc's usage is its argument list.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.eval("proc greet {name {greeting hello}} {}").unwrap();
+    ///
+    /// let info = interp.command_info("greet").unwrap();
+    /// assert_eq!(info.kind, CommandType::Proc);
+    /// assert_eq!(info.usage.as_deref(), Some("name greeting"));
+    /// assert_eq!(interp.command_info("set").unwrap().help, None);
+    /// assert!(interp.command_info("nonesuch").is_none());
+    /// ```
+    pub fn command_info(&self, name: &str) -> Option<CommandInfo> {
+        let kind = self.command_kind(name)?;
+        let (help, usage) = match kind {
+            CommandType::Native => (None, None),
+            CommandType::Embedded => self
+                .command
+                .embedded_names
+                .iter()
+                .position(|&n| n == name)
+                .and_then(|i| self.command.embedded_helps.get(i))
+                .map_or((None, None), |h| {
+                    (h.help.filter(|help| !help.is_empty()), h.usage.map(String::from))
+                }),
+            CommandType::Proc => {
+                (None, self.proc_args(name).ok().map(|args| args.to_string()))
+            }
+        };
+        Some(CommandInfo { kind, help, usage })
+    }
+
+    /// Returns the name and [`CommandInfo`] of every command: the native commands, then
+    /// the embedded commands in `gen_command!` order, then the procs sorted by name.
+    pub fn commands_info(&self) -> Vec<(String, CommandInfo)> {
+        let mut proc_names: Vec<&str> = self.procs.keys().map(String::as_str).collect();
+        proc_names.sort_unstable();
+
+        self.command
+            .visible_native_names()
+            .chain(self.command.visible_embedded_names())
+            .chain(self.runtime_command_names())
+            .chain(proc_names)
+            .filter_map(|name| Some((name.to_string(), self.command_info(name)?)))
+            .collect()
+    }
+
+    /// Returns the output of the `help` command: the embedded commands' help lines, or
+    /// with `all`, the native commands and procs as well.
+    pub fn help_message(&self, all: bool) -> String {
+        let mut embedded = String::new();
+        for (name, help) in self
+            .command
+            .embedded_names
+            .iter()
+            .zip(self.command.embedded_helps)
+            .filter(|(name, _)| !self.command.is_hidden(name))
+        {
+            embedded.push_str("  ");
+            embedded.push_str(&help.line(name));
+            embedded.push('\n');
+        }
+        embedded.push_str("  help  [-all]");
+
+        if !all {
+            return format!("usage of {}:\n{}", self.name, embedded);
+        }
+
+        let mut message = format!(
+            "usage of {}:\ntcl:\n  {}\n{}:\n{}",
+            self.name,
+            self.native_command_names(),
+            self.name,
+            embedded
+        );
+        let proc_command_names = self.proc_command_names();
+        if !proc_command_names.is_empty() {
+            message.push_str("\nprocedure:\n  ");
+            message.push_str(&proc_command_names);
+        }
+        message
+    }
+
+    /// Returns the body of the named procedure, or an error if the name doesn't
+    /// name a procedure.
     #[inline]
     pub fn command_type(&self, cmd_name: &str) -> MoltResult {
-        match (self.command.fn_type)(cmd_name, self) {
+        match self.command_kind(&self.command_key(cmd_name)) {
             Some(CommandType::Native) => molt_ok!("native"),
             Some(CommandType::Proc) => molt_ok!("proc"),
             Some(CommandType::Embedded) => molt_ok!(self.name),
@@ -1979,7 +4017,7 @@ where
         }
     }
 
-    /// Gets a vector of the names of the existing procedures.
+    /// Gets a vector of the names of the existing procedures, sorted by name.
     ///
     /// # Example
     ///
@@ -1996,16 +4034,16 @@ where
     /// ```
     #[inline]
     pub fn proc_names(&self) -> MoltList {
-        let vec: MoltList =
-            self.procs.iter().map(|(name, _)| Value::from(name)).collect();
-        vec
+        let mut names: Vec<&str> = self.procs.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names.into_iter().map(Value::from).collect()
     }
 
     /// Returns the body of the named procedure, or an error if the name doesn't
     /// name a procedure.
     #[inline]
     pub fn proc_body(&self, procname: &str) -> MoltResult {
-        if let Some(proc) = self.procs.get(procname) {
+        if let Some(proc) = self.procs.get(&*self.command_key(procname)) {
             return molt_ok!(proc.body.clone());
         }
 
@@ -2016,14 +4054,13 @@ where
     /// error if the name doesn't name a procedure.
     #[inline]
     pub fn proc_args(&self, procname: &str) -> MoltResult {
-        if let Some(proc) = self.procs.get(procname) {
+        if let Some(proc) = self.procs.get(&*self.command_key(procname)) {
             // Note: the item is guaranteed to be parsible as a list of 1 or 2 elements.
-            let vec: MoltList = proc
+            return molt_ok!(proc
                 .parms
                 .iter()
                 .map(|item| item.as_list().expect("invalid proc parms")[0].clone())
-                .collect();
-            return molt_ok!(Value::from(vec));
+                .collect::<Value>());
         }
 
         molt_err!("\"{}\" isn't a procedure", procname)
@@ -2038,7 +4075,7 @@ where
         procname: &str,
         arg: &str,
     ) -> Result<Option<Value>, Exception> {
-        if let Some(proc) = self.procs.get(procname) {
+        if let Some(proc) = self.procs.get(&*self.command_key(procname)) {
             for argvec in &proc.parms {
                 let argvec = argvec.as_list()?; // Should never fail
                 if argvec[0].as_str() == arg {
@@ -2060,318 +4097,3038 @@ where
     }
 
     //--------------------------------------------------------------------------------------------
-    // Interpreter Configuration
+    // Variable Traces
 
-    /// Gets the interpreter's recursion limit: how deep the stack of script evaluations may be.
+    /// Sets a trace on the variable, which may name a scalar, an array, or an array
+    /// element, as for [`var`](#method.var).  The callback is called whenever one of the
+    /// operations is done on it; see the [`trace`](../trace/index.html) module for the
+    /// details.  The variable needn't exist yet.  Returns the trace's ID, for
+    /// [`untrace_var`](#method.untrace_var).
     ///
-    /// A script stack level is added by each nested script evaluation (i.e., by each call)
-    /// to [`eval`](#method.eval) or [`eval_value`](#method.eval_value).
+    /// Only the variable access methods of `Interp` and the commands built on them call
+    /// traces; [`scalars`](#method.scalars) and the `array` queries, e.g., `array get`,
+    /// do not.
     ///
     /// # Example
+    ///
     /// ```
-    /// # use molt::types::*;
-    /// # use molt::interp::Interp;
+    /// # use molt_forked::prelude::*;
+    /// fn count_writes(
+    ///     interp: &mut Interp<()>,
+    ///     _name: &str,
+    ///     _index: &str,
+    ///     _op: TraceOp,
+    /// ) -> Result<(), Exception> {
+    ///     interp.eval("incr writes")?;
+    ///     Ok(())
+    /// }
+    ///
     /// let mut interp = Interp::default();
-    /// assert_eq!(interp.recursion_limit(), 1000);
+    /// let id = interp
+    ///     .trace_var(&Value::from("x"), &[TraceOp::Write], count_writes)
+    ///     .unwrap();
+    /// interp.eval("set x 1; set x 2").unwrap();
+    /// assert_eq!(interp.scalar("writes").unwrap().as_int(), Ok(2));
+    ///
+    /// assert!(interp.untrace_var(id));
+    /// interp.eval("set x 3").unwrap();
+    /// assert_eq!(interp.scalar("writes").unwrap().as_int(), Ok(2));
     /// ```
+    pub fn trace_var(
+        &mut self,
+        var_name: &Value,
+        ops: &[TraceOp],
+        callback: TraceFn<Ctx>,
+    ) -> Result<TraceId, Exception> {
+        self.add_trace(var_name, ops.to_vec(), TraceCallback::Native(callback))
+    }
+
+    /// Removes the trace with the given ID, as returned by
+    /// [`trace_var`](#method.trace_var).  Returns false if there's no such trace, e.g.,
+    /// because its variable has been unset.
+    pub fn untrace_var(&mut self, id: TraceId) -> bool {
+        self.var_traces.remove(id)
+    }
+
+    /// Sets a trace on the variable in the current scope.
+    pub(crate) fn add_trace(
+        &mut self,
+        var_name: &Value,
+        ops: Vec<TraceOp>,
+        callback: TraceCallback<Ctx>,
+    ) -> Result<TraceId, Exception> {
+        let var_name = var_name.as_var_name();
+        let (level, key) = self.scopes.locate_var(var_name.name())?;
+        let index = var_name.index().map(String::from);
+
+        Ok(self.var_traces.add(level, key.into_owned(), index, ops, callback))
+    }
+
+    /// Removes the script trace on the variable with the given operations and command
+    /// prefix, if there is one.
+    pub(crate) fn remove_script_trace(
+        &mut self,
+        var_name: &Value,
+        ops: &[TraceOp],
+        prefix: &Value,
+    ) -> Result<(), Exception> {
+        let var_name = var_name.as_var_name();
+        let (level, key) = self.scopes.locate_var(var_name.name())?;
+
+        self.var_traces
+            .remove_script(level, &key, var_name.index(), ops, prefix);
+        Ok(())
+    }
+
+    /// Returns the operations and command prefix of each of the variable's script traces.
+    pub(crate) fn script_traces(&self, var_name: &Value) -> Result<MoltList, Exception> {
+        let var_name = var_name.as_var_name();
+        let (level, key) = self.scopes.locate_var(var_name.name())?;
+
+        Ok(self.var_traces.script_info(level, &key, var_name.index()))
+    }
+
+    /// Calls the traces for the operation on the variable or element.  An error is
+    /// reported as the failure of the operation.
+    fn call_traces(
+        &mut self,
+        name: &str,
+        index: Option<&str>,
+        op: TraceOp,
+    ) -> Result<(), Exception> {
+        let Ok((level, key)) = self.scopes.locate_var(name) else {
+            return Ok(());
+        };
+        let callbacks = self.var_traces.to_call(level, &key, index, op);
+        if callbacks.is_empty() {
+            return Ok(());
+        }
+
+        self.var_traces.enter(level, &key);
+        let result = callbacks.iter().try_for_each(|callback| {
+            self.call_trace(callback, name, index.unwrap_or(""), op)
+        });
+        self.var_traces.leave();
+
+        result.map_err(|exception| {
+            let verb = if op == TraceOp::Read { "read" } else { "set" };
+            let name = match index {
+                Some(index) => format!("{}({})", name, index),
+                None => name.to_string(),
+            };
+            Exception::molt_err(Value::from(format!(
+                "can't {} \"{}\": {}",
+                verb,
+                name,
+                exception.value()
+            )))
+        })
+    }
+
+    /// Calls the `write` traces on the variable or element, if there are any traces.
     #[inline]
-    pub fn recursion_limit(&self) -> usize {
-        self.recursion_limit
+    fn call_write_traces(
+        &mut self,
+        name: &str,
+        index: Option<&str>,
+    ) -> Result<(), Exception> {
+        if self.var_traces.is_empty() {
+            return Ok(());
+        }
+        self.call_traces(name, index, TraceOp::Write)
     }
 
-    /// Sets the interpreter's recursion limit: how deep the stack of script evaluations may
-    /// be.  The default is 1000.
-    ///
-    /// A script stack level is added by each nested script evaluation (i.e., by each call)
-    /// to [`eval`](#method.eval) or [`eval_value`](#method.eval_value).
+    /// Calls the `unset` traces on the variable, which lived at the given level under
+    /// the given key, or on its element, and removes the traces that go with it.
+    fn call_unset_traces(
+        &mut self,
+        level: usize,
+        key: &str,
+        name: &str,
+        index: Option<&str>,
+    ) {
+        let active = self.var_traces.is_active(level, key);
+        let taken = self.var_traces.take_unset(level, key, index);
+        if active {
+            return;
+        }
+
+        // An element's array traces stay.
+        let mut calls: Vec<_> = match index {
+            Some(index) => self
+                .var_traces
+                .to_call(level, key, Some(index), TraceOp::Unset)
+                .into_iter()
+                .map(|callback| (callback, index.to_string()))
+                .collect(),
+            None => Vec::new(),
+        };
+        calls.extend(taken.iter().filter(|t| t.has_op(TraceOp::Unset)).map(|t| {
+            (t.callback().clone(), t.index().or(index).unwrap_or("").to_string())
+        }));
+
+        self.var_traces.enter(level, key);
+        for (callback, index) in calls {
+            let _ = self.call_trace(&callback, name, &index, TraceOp::Unset);
+        }
+        self.var_traces.leave();
+    }
+
+    /// Calls a trace's callback.
+    fn call_trace(
+        &mut self,
+        callback: &TraceCallback<Ctx>,
+        name: &str,
+        index: &str,
+        op: TraceOp,
+    ) -> Result<(), Exception> {
+        match callback {
+            TraceCallback::Native(func) => func(self, name, index, op),
+            TraceCallback::Script(prefix) => {
+                let mut cmd = prefix.as_list()?.to_vec();
+                cmd.extend([
+                    Value::from(name),
+                    Value::from(index),
+                    Value::from(op.as_str()),
+                ]);
+                self.eval_value(&Value::from(cmd)).map(|_| ())
+            }
+            TraceCallback::Notify(name) => {
+                self.notify_var_written(name);
+                Ok(())
+            }
+        }
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Execution Traces
+
+    /// Sets an execution trace on every command, returning its ID for
+    /// [`remove_exec_trace`](#method.remove_exec_trace).  The callback is called with the
+    /// command's words and [`ExecOp::Enter`] before each command in a script is called,
+    /// and with its words, [`ExecOp::Leave`], and its result after.  An error from an
+    /// `Enter` call keeps the command from being called, and one from a `Leave` call
+    /// replaces its result; see the [`trace`](../trace/index.html) module for the details.
     ///
     /// # Example
+    ///
     /// ```
-    /// # use molt::types::*;
-    /// # use molt::interp::Interp;
+    /// # use molt_forked::prelude::*;
+    /// fn count_calls(
+    ///     interp: &mut Interp<()>,
+    ///     _words: &[Value],
+    ///     op: ExecOp,
+    ///     _result: Option<&MoltResult>,
+    /// ) -> Result<(), Exception> {
+    ///     if op == ExecOp::Enter {
+    ///         interp.eval("incr ::calls")?;
+    ///     }
+    ///     Ok(())
+    /// }
+    ///
     /// let mut interp = Interp::default();
-    /// interp.set_recursion_limit(100);
-    /// assert_eq!(interp.recursion_limit(), 100);
+    /// interp.eval("set calls 0").unwrap();
+    /// let id = interp.add_exec_trace(count_calls);
+    /// interp.eval("set a 1; set b 2").unwrap();
+    /// assert!(interp.remove_exec_trace(id));
+    /// assert_eq!(interp.scalar("calls").unwrap().as_int(), Ok(2));
     /// ```
-    #[inline]
-    pub fn set_recursion_limit(&mut self, limit: usize) {
-        self.recursion_limit = limit;
+    pub fn add_exec_trace(&mut self, callback: ExecTraceFn<Ctx>) -> TraceId {
+        self.exec_traces.add(
+            None,
+            vec![ExecOp::Enter, ExecOp::Leave],
+            ExecCallback::Native(callback),
+        )
     }
 
-    //--------------------------------------------------------------------------------------------
-    // Profiling
+    /// Removes the execution trace with the given ID, as returned by
+    /// [`add_exec_trace`](#method.add_exec_trace).  Returns false if there's no such trace.
+    pub fn remove_exec_trace(&mut self, id: TraceId) -> bool {
+        self.exec_traces.remove(id)
+    }
 
-    /// Unstable; use at own risk.
-    pub fn profile_save(&mut self, name: &str, start: Instant) {
-        let dur = Instant::now().duration_since(start).as_nanos();
-        let rec = self.profile_map.entry(name.into()).or_insert_with(ProfileRecord::new);
+    /// Sets a script trace on the named command, which must exist.
+    pub(crate) fn add_command_trace(
+        &mut self,
+        name: &str,
+        ops: Vec<ExecOp>,
+        prefix: Value,
+    ) -> Result<(), Exception> {
+        let key = self.command_key(name).into_owned();
+        if self.command_kind(&key).is_none() {
+            return molt_err!("unknown command \"{}\"", name);
+        }
 
-        rec.count += 1;
-        rec.nanos += dur;
+        self.exec_traces.add(Some(key), ops, ExecCallback::Script(prefix));
+        Ok(())
     }
 
-    /// Unstable; use at own risk.
-    pub fn profile_clear(&mut self) {
-        self.profile_map.clear();
+    /// Removes the script trace on the named command with the given operations and
+    /// command prefix, if there is one.
+    pub(crate) fn remove_command_trace(
+        &mut self,
+        name: &str,
+        ops: &[ExecOp],
+        prefix: &Value,
+    ) {
+        let key = self.command_key(name).into_owned();
+        self.exec_traces.remove_script(&key, ops, prefix);
     }
 
-    /// Unstable; use at own risk.
-    pub fn profile_dump(&self) {
-        if self.profile_map.is_empty() {
-            println!("no profile data");
-        } else {
-            for (name, rec) in &self.profile_map {
-                let avg = rec.nanos / rec.count;
-                println!("{} nanos {}, count={}", avg, name, rec.count);
-            }
+    /// Returns the operations and command prefix of each of the command's script traces.
+    pub(crate) fn command_traces(&self, name: &str) -> MoltList {
+        self.exec_traces.script_info(&self.command_key(name))
+    }
+
+    /// Executes the command as [`execute`](#method.execute) does, calling its execution
+    /// traces before and after.  Kept out of line, so that the common case of no traces
+    /// adds nothing to the stack frame of `eval_script`.
+    #[inline(never)]
+    fn execute_traced(&mut self, name: &str, words: &[Value]) -> MoltResult {
+        let key = self.command_key(name).into_owned();
+
+        let enter = self.exec_traces.to_call(&key, ExecOp::Enter);
+        self.call_exec_traces(&enter, words, ExecOp::Enter, None)?;
+
+        let result = self.execute(name, words);
+
+        let leave = self.exec_traces.to_call(&key, ExecOp::Leave);
+        self.call_exec_traces(&leave, words, ExecOp::Leave, Some(&result))?;
+        result
+    }
+
+    /// Calls the execution traces' callbacks for the operation, stopping at the first
+    /// error.
+    fn call_exec_traces(
+        &mut self,
+        callbacks: &[ExecCallback<Ctx>],
+        words: &[Value],
+        op: ExecOp,
+        result: Option<&MoltResult>,
+    ) -> Result<(), Exception> {
+        if callbacks.is_empty() {
+            return Ok(());
         }
+
+        self.exec_traces.set_active(true);
+        let outcome = callbacks.iter().try_for_each(|callback| match callback {
+            ExecCallback::Native(func) => func(self, words, op, result),
+            ExecCallback::Script(prefix) => {
+                let mut cmd = prefix.as_list()?.to_vec();
+                cmd.push(Value::from(list_to_string(words)));
+                if let Some(result) = result {
+                    let (code, value) = match result {
+                        Ok(value) => (0, value.clone()),
+                        Err(exception) => (exception.code().as_int(), exception.value()),
+                    };
+                    cmd.push(Value::from(code));
+                    cmd.push(value);
+                }
+                cmd.push(Value::from(op.as_str()));
+                self.eval_value(&Value::from(cmd)).map(|_| ())
+            }
+        });
+        self.exec_traces.set_active(false);
+        outcome
     }
 
     //--------------------------------------------------------------------------------------------
-    // Error control
+    // Namespaces
 
-    /// get the current continue on error setting. the default is false.
+    /// Returns the fully qualified name of the current namespace: `::` for the global
+    /// namespace, or, e.g., `::a::b` in a proc defined in namespace `::a::b` or in a script
+    /// evaluated by `namespace eval ::a::b`.
     ///
-    /// # Example
     /// ```
-    /// # use molt::types::*;
-    /// # use molt::interp::Interp;
+    /// # use molt_forked::prelude::*;
     /// let mut interp = Interp::default();
-    /// assert_eq!(interp.continue_on_error(), false);
+    /// assert_eq!(interp.current_namespace(), "::");
+    /// interp.eval("namespace eval a { proc where {} { namespace current } }").unwrap();
+    /// assert_eq!(interp.eval("a::where").unwrap().as_str(), "::a");
     /// ```
-    pub fn continue_on_error(&self) -> bool {
-        self.continue_on_error
+    pub fn current_namespace(&self) -> String {
+        namespace::display(self.scopes.namespace())
     }
 
-    /// set whether to continue anyway in case of error.
+    /// Evaluates the script in the named namespace, creating it if need be, as the
+    /// `namespace eval` command does.  A relative name is relative to the current
+    /// namespace.  The script runs in a new scope, whose unqualified variables are the
+    /// namespace's, and procs it defines are defined in the namespace.
     ///
-    /// # Example
     /// ```
-    /// # use molt::types::*;
-    /// # use molt::interp::Interp;
+    /// # use molt_forked::prelude::*;
     /// let mut interp = Interp::default();
-    /// interp.set_continue_on_error(true);
-    /// assert_eq!(interp.continue_on_error(), true);
+    /// interp.namespace_eval("config", &Value::from("set size 10")).unwrap();
+    /// assert_eq!(interp.scalar("config::size").unwrap().as_str(), "10");
+    /// assert!(interp.scalar("size").is_err());
     /// ```
-    pub fn set_continue_on_error(&mut self, c: bool) {
-        self.continue_on_error = c;
+    pub fn namespace_eval(&mut self, name: &str, script: &Value) -> MoltResult {
+        let name = self.resolve_namespace(name);
+        let namespace = self.add_namespace(&name);
+
+        let result = {
+            let level = self.scopes.current();
+            self.scopes.push_namespace(namespace);
+            let mut interp = ScopeGuard { interp: self, level };
+            interp.eval_value(script)
+        };
+
+        result.map_err(|mut exception| {
+            if exception.is_error() {
+                exception.add_error_info(&format!(
+                    "    (in namespace eval \"{}\" script)",
+                    namespace::display(&name)
+                ));
+            }
+            exception
+        })
     }
-}
 
-/// How a procedure is defined: as an argument list and a body script.
-/// The argument list is a list of Values, and the body is a Value; each will
-/// retain its parsed form.
-///
-/// NOTE: We do not save the procedure's name; the name exists only in the
-/// commands table, and can be changed there freely.  The procedure truly doesn't
-/// know what its name is except when it is being executed.
-#[derive(Debug, Clone)]
-pub struct Procedure {
-    /// The procedure's parameter list.  Each item in the list is a name or a
-    /// name/default value pair.  (This is verified by the `proc` command.)
-    parms: MoltList,
+    /// Returns the fully qualified names of the named namespace's children, sorted by
+    /// name.  A relative name is relative to the current namespace, and the empty name
+    /// is the current namespace.  Returns an error if there's no such namespace.
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.eval("namespace eval a { namespace eval b {} }; namespace eval c {}").unwrap();
+    /// assert_eq!(
+    ///     interp.namespace_children("").unwrap(),
+    ///     vec![Value::from("::a"), Value::from("::c")]
+    /// );
+    /// assert_eq!(interp.namespace_children("a").unwrap(), vec![Value::from("::a::b")]);
+    /// ```
+    pub fn namespace_children(&self, name: &str) -> Result<MoltList, Exception> {
+        let parent = self.resolve_namespace(name);
 
-    /// The procedure's body string, as a Value.  As such, it retains both its
-    /// string value, as needed for introspection, and its parsed Script.
-    body: Value,
-}
+        if !parent.is_empty() && !self.namespaces.contains(&parent) {
+            return molt_err!(
+                "namespace \"{}\" not found in \"{}\"",
+                name,
+                self.current_namespace()
+            );
+        }
 
-impl Procedure {
-    pub fn execute<Ctx>(&self, interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
-    where
-        Ctx: 'static,
-    {
-        // FIRST, push the proc's local scope onto the stack.
-        interp.push_scope();
+        Ok(self
+            .namespaces
+            .iter()
+            .filter(|child| namespace::parent(child) == parent)
+            .map(|child| Value::from(namespace::display(child)))
+            .collect())
+    }
 
-        // NEXT, process the proc's argument list.
-        let mut argi = 1; // Skip the proc's name
+    /// Returns the qualified name, without the leading `::`, of the named namespace,
+    /// relative to the current namespace; the empty name is the current namespace.
+    pub(crate) fn resolve_namespace(&self, name: &str) -> String {
+        let current = self.scopes.namespace();
+        if name.is_empty() {
+            current.to_string()
+        } else {
+            namespace::qualify(current, name).trim_end_matches(':').to_string()
+        }
+    }
 
-        for (speci, spec) in self.parms.iter().enumerate() {
-            // FIRST, get the parameter as a vector.  It should be a list of
-            // one or two elements.
-            let vec = &*spec.as_list()?; // Should never fail
-            assert!(vec.len() == 1 || vec.len() == 2);
+    /// Records that the namespace and its ancestors exist, returning its name for use as
+    /// a scope's or procedure's namespace.
+    fn add_namespace(&mut self, name: &str) -> Rc<str> {
+        let mut ancestor = name;
+        while !ancestor.is_empty() && !self.namespaces.contains(ancestor) {
+            self.namespaces.insert(ancestor.to_string());
+            ancestor = namespace::parent(ancestor);
+        }
 
-            // NEXT, if this is the args parameter, give the remaining args,
-            // if any.  Note that "args" has special meaning only if it's the
-            // final arg spec in the list.
-            if vec[0].as_str() == "args" && speci == self.parms.len() - 1 {
-                interp.set_scalar("args", Value::from(&argv[argi..]))?;
+        if name == &**self.scopes.namespace() {
+            self.scopes.namespace().clone()
+        } else {
+            Rc::from(name)
+        }
+    }
 
-                // We've processed all of the args
-                argi = argv.len();
-                break;
-            }
+    //--------------------------------------------------------------------------------------------
+    // State Export and Import
 
-            // NEXT, do we have a matching argument?
-            if argi < argv.len() {
-                // Pair them up
-                interp.set_scalar(vec[0].as_str(), argv[argi].clone())?;
-                argi += 1;
-                continue;
+    /// Exports the procs and global variables selected by the filter as a Molt script.
+    /// Evaluating the script in another interpreter, e.g., via
+    /// [`import_state`](#method.import_state), redefines the procs and sets the variables
+    /// to the same values.
+    ///
+    /// The script is plain text, so it can be sent to interpreters on other threads,
+    /// which `Value`s cannot.  Procs are exported as `proc` commands, scalars as `set`
+    /// commands, and arrays as `array set` commands, in order by name.  Each command is
+    /// built with the canonical list quoting, so every value round-trips exactly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// # fn dummy() -> MoltResult {
+    /// let mut template = Interp::default();
+    /// template.eval("proc double {x} { expr {2*$x} }; set factor 5")?;
+    /// let state = template.export_state(&StateFilter::all());
+    ///
+    /// let mut worker = Interp::default();
+    /// worker.import_state(&state)?;
+    /// assert_eq!(worker.eval("double $factor")?.as_int()?, 10);
+    /// # molt_ok!()
+    /// # }
+    /// ```
+    pub fn export_state(&self, filter: &StateFilter) -> String {
+        let matches = |patterns: &[String], name: &str| {
+            patterns.iter().any(|pattern| util::glob_match(pattern, name))
+        };
+        let mut commands: Vec<String> = Vec::new();
+
+        // FIRST, export the procs.
+        let mut names: Vec<String> = self
+            .proc_names()
+            .iter()
+            .map(|name| name.to_string())
+            .filter(|name| matches(&filter.procs, name))
+            .collect();
+        names.sort();
+
+        for name in &names {
+            // These can't fail, as the name is known to be a proc.
+            let body = self.proc_body(name).expect("proc body");
+            let args = self.proc_args(name).expect("proc args");
+            let mut parms: MoltList = Vec::new();
+
+            for arg in &*args.as_list().expect("proc args list") {
+                match self.proc_default(name, arg.as_str()).expect("proc default") {
+                    Some(default) => parms.push(Value::from(vec![arg.clone(), default])),
+                    None => parms.push(arg.clone()),
+                }
             }
 
-            // NEXT, do we have a default value?
-            if vec.len() == 2 {
-                interp.set_scalar(vec[0].as_str(), vec[1].clone())?;
-            } else {
-                // We don't; we're missing a required argument.
-                return self.wrong_num_args(&argv[0]);
-            }
+            commands.push(list_to_string(&[
+                Value::from("proc"),
+                Value::from(name),
+                Value::from(parms),
+                body,
+            ]));
         }
 
-        // NEXT, do we have any arguments left over?
-
-        if argi != argv.len() {
-            return self.wrong_num_args(&argv[0]);
+        // NEXT, export the global variables.
+        let mut names: Vec<String> = self
+            .vars_in_global_scope()
+            .iter()
+            .map(|name| name.to_string())
+            .filter(|name| matches(&filter.vars, name))
+            .collect();
+        names.sort();
+
+        for name in &names {
+            if let Some(value) = self.scopes.global_scalar(name) {
+                commands.push(list_to_string(&[
+                    Value::from("set"),
+                    Value::from(name),
+                    value,
+                ]));
+            } else if let Some(kvlist) = self.scopes.global_array(name) {
+                commands.push(list_to_string(&[
+                    Value::from("array"),
+                    Value::from("set"),
+                    Value::from(name),
+                    Value::from(kvlist),
+                ]));
+            }
         }
 
-        // NEXT, evaluate the proc's body, getting the result.
-        let result = interp.eval_value(&self.body);
+        let mut script = commands.join("\n");
+        if !script.is_empty() {
+            script.push('\n');
+        }
+        script
+    }
 
-        // NEXT, pop the scope off of the stack; we're done with it.
-        interp.pop_scope();
+    /// Imports state exported by [`export_state`](#method.export_state).  This is simply
+    /// [`eval`](#method.eval); it's provided to document the pairing.
+    pub fn import_state(&mut self, state: &str) -> MoltResult {
+        self.eval(state)
+    }
 
-        if let Err(mut exception) = result {
-            // FIRST, handle the return -code, -level protocol
-            if exception.code() == ResultCode::Return {
-                exception.decrement_level();
-            }
+    //--------------------------------------------------------------------------------------------
+    // Interpreter Configuration
 
-            return match exception.code() {
-                ResultCode::Okay => Ok(exception.value()),
-                ResultCode::Error => Err(exception),
-                ResultCode::Return => Err(exception), // -level > 0
+    /// Gets the interpreter's recursion limit: how deep the stack of script evaluations may be.
+    ///
+    /// A script stack level is added by each nested script evaluation (i.e., by each call)
+    /// to [`eval`](#method.eval) or [`eval_value`](#method.eval_value).
+    ///
+    /// # Example
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::interp::Interp;
+    /// let mut interp = Interp::default();
+    /// assert_eq!(interp.recursion_limit(), 1000);
+    /// ```
+    #[inline]
+    pub fn recursion_limit(&self) -> usize {
+        self.recursion_limit
+    }
+
+    /// Sets the interpreter's recursion limit: how deep the stack of script evaluations may
+    /// be.  The default is 1000.
+    ///
+    /// A script stack level is added by each nested script evaluation (i.e., by each call)
+    /// to [`eval`](#method.eval) or [`eval_value`](#method.eval_value).
+    ///
+    /// # Example
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::interp::Interp;
+    /// let mut interp = Interp::default();
+    /// interp.set_recursion_limit(100);
+    /// assert_eq!(interp.recursion_limit(), 100);
+    /// ```
+    #[inline]
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.recursion_limit = limit;
+    }
+
+    /// Returns the strings returned by `info patchlevel` and `info tclversion`.  By
+    /// default, these are the Molt version and the version of Tcl that Molt most closely
+    /// resembles, `8.5`.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// assert_eq!(interp.tcl_version_strings().1, "8.5");
+    /// ```
+    pub fn tcl_version_strings(&self) -> (&str, &str) {
+        (&self.patch_level, &self.tcl_version)
+    }
+
+    /// Sets the strings returned by `info patchlevel` and `info tclversion`, e.g., to
+    /// satisfy scripts that check for a particular Tcl version.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.set_tcl_version_strings("8.6.13", "8.6");
+    /// assert_eq!(interp.eval("info patchlevel").unwrap().as_str(), "8.6.13");
+    /// assert_eq!(interp.eval("info tclversion").unwrap().as_str(), "8.6");
+    /// ```
+    pub fn set_tcl_version_strings(&mut self, patch_level: &str, tcl_version: &str) {
+        self.patch_level = patch_level.into();
+        self.tcl_version = tcl_version.into();
+    }
+
+    /// Sets the writer to which `puts` writes its output, and which `flush stdout`
+    /// flushes.  The default is the process's standard output, which is line-buffered
+    /// and locked for the duration of each write, or, under the `std_buff` feature, the
+    /// interpreter's `std_buff`.  Any output buffered by the previous writer is flushed
+    /// first.
+    ///
+    /// Replacing the writer lets an application interleave script output with its own
+    /// logging in a single stream, or capture the output exactly.  Any
+    /// [`std::io::Write`] is a [`Writer`](../prelude/trait.Writer.html).
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// use std::{cell::RefCell, io::Write, rc::Rc};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Capture(Rc<RefCell<Vec<u8>>>);
+    ///
+    /// impl Write for Capture {
+    ///     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    ///         self.0.borrow_mut().write(buf)
+    ///     }
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let capture = Capture::default();
+    /// let mut interp = Interp::default();
+    /// interp.set_stdout_writer(Box::new(capture.clone()));
+    /// interp.eval("puts hello; flush stdout").unwrap();
+    /// assert_eq!(*capture.0.borrow(), b"hello\n");
+    /// ```
+    pub fn set_stdout_writer(&mut self, writer: Box<dyn Writer>) {
+        self.standard_channel("stdout").set_writer(Some(writer));
+    }
+
+    /// Sets the writer for `stderr`; see [`set_stdout_writer`](#method.set_stdout_writer).
+    /// The default is the process's standard error, or, under `std_buff`, the
+    /// interpreter's `std_buff`.
+    pub fn set_stderr_writer(&mut self, writer: Box<dyn Writer>) {
+        self.standard_channel("stderr").set_writer(Some(writer));
+    }
+
+    /// Passes the output that `puts` writes to `stdout` to the handler as it's written, so
+    /// that a host can show the output of a long-running script as it goes, rather than
+    /// collecting it from `std_buff` when the evaluation is done.  The handler gets the
+    /// text of each `puts`, including its newline, if any.  Under `std_buff`, errors that
+    /// `continue_on_error` passes over are still pushed onto `std_buff`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let output = Rc::new(RefCell::new(String::new()));
+    /// let sink = output.clone();
+    ///
+    /// let mut interp = Interp::default();
+    /// interp.set_stdout_handler(move |text| sink.borrow_mut().push_str(text));
+    /// interp.eval("puts hello; puts -nonewline world").unwrap();
+    /// assert_eq!(*output.borrow(), "hello\nworld");
+    /// ```
+    pub fn set_stdout_handler(&mut self, handler: impl FnMut(&str) + 'static) {
+        self.set_stdout_writer(Box::new(FnWriter(handler)));
+    }
+
+    /// Passes the output that `puts` writes to `stderr` to the handler as it's written;
+    /// see [`set_stdout_handler`](#method.set_stdout_handler).
+    pub fn set_stderr_handler(&mut self, handler: impl FnMut(&str) + 'static) {
+        self.set_stderr_writer(Box::new(FnWriter(handler)));
+    }
+
+    /// Restores the default writers for `stdout` and `stderr`, replacing those set by
+    /// [`set_stdout_writer`](#method.set_stdout_writer),
+    /// [`set_stdout_handler`](#method.set_stdout_handler), and their `stderr`
+    /// equivalents.
+    pub fn remove_output_handlers(&mut self) {
+        for name in ["stdout", "stderr"] {
+            self.standard_channel(name)
+                .set_writer(ChannelTable::default_writer(name));
+        }
+    }
+
+    /// Returns the writer to which `puts` writes its output, so that commands can write
+    /// to the same stream.  See [`set_stdout_writer`](#method.set_stdout_writer).
+    ///
+    /// # Panics
+    ///
+    /// Under `std_buff`, panics if the host hasn't set a writer.
+    pub fn stdout_writer(&mut self) -> &mut dyn Writer {
+        self.standard_channel("stdout").writer().expect("stdout has a writer")
+    }
+
+    /// Returns the writer for standard error.  See
+    /// [`set_stderr_writer`](#method.set_stderr_writer).
+    ///
+    /// # Panics
+    ///
+    /// Under `std_buff`, panics if the host hasn't set a writer.
+    pub fn stderr_writer(&mut self) -> &mut dyn Writer {
+        self.standard_channel("stderr").writer().expect("stderr has a writer")
+    }
+
+    /// Returns one of the standard channels, which can't be closed.
+    fn standard_channel(&mut self, name: &str) -> &mut Channel {
+        self.channels.get(name).expect("standard channels can't be closed")
+    }
+
+    /// Writes the output of `puts` to the channel.  Under `std_buff`, output to a standard
+    /// channel without a writer is pushed onto `std_buff`.
+    pub(crate) fn puts(
+        &mut self,
+        name: &str,
+        string: &Value,
+        nonewline: bool,
+    ) -> Result<(), Exception> {
+        let channel = self.channels.get(name)?;
+
+        #[cfg(feature = "std_buff")]
+        if (name == "stdout" || name == "stderr") && channel.writer().is_none() {
+            self.std_buff.push(Ok(string.clone()));
+            return Ok(());
+        }
+
+        if nonewline {
+            channel.write_str(name, string.as_str())
+        } else {
+            channel.write_str(name, &format!("{}\n", string.as_str()))
+        }
+    }
+
+    /// Returns the interpreter's channels, for `puts`, `flush`, and the file I/O commands.
+    pub(crate) fn channels(&mut self) -> &mut ChannelTable {
+        &mut self.channels
+    }
+
+    /// Returns the interpreter's event queue, for `after`.
+    pub(crate) fn events(&mut self) -> &mut EventQueue {
+        &mut self.events
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Profiling
+
+    /// Gets whether profiling is on.  The default is false.
+    pub fn profiling(&self) -> bool {
+        self.profiling
+    }
+
+    /// Sets whether to profile: while it's on, each call of a command or proc is counted
+    /// and timed, for [`profile_report`](#method.profile_report).  Turning it off keeps the
+    /// data recorded so far; see [`profile_clear`](#method.profile_clear).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.eval("proc square {x} { expr {$x * $x} }").unwrap();
+    ///
+    /// interp.set_profiling(true);
+    /// interp.eval("square 2; square 3").unwrap();
+    /// interp.set_profiling(false);
+    ///
+    /// let report = interp.profile_report();
+    /// assert_eq!(report.get("square").unwrap().count, 2);
+    /// assert_eq!(report.get("expr").unwrap().count, 2);
+    /// ```
+    pub fn set_profiling(&mut self, flag: bool) {
+        self.profiling = flag;
+    }
+
+    /// Returns the profile data recorded so far, one entry per command, ordered by total
+    /// time.  See [`set_profiling`](#method.set_profiling).
+    pub fn profile_report(&self) -> ProfileReport {
+        ProfileReport::new(
+            self.profile_map
+                .iter()
+                .map(|(name, rec)| ProfileEntry {
+                    name: name.clone(),
+                    count: rec.count as u64,
+                    total: Duration::from_nanos(rec.nanos as u64),
+                })
+                .collect(),
+        )
+    }
+
+    /// Records a call of the named command, or any other section of code, that began at
+    /// `start`, as profiling does.
+    pub fn profile_save(&mut self, name: &str, start: Instant) {
+        let dur = Instant::now().duration_since(start).as_nanos();
+        let rec = self.profile_map.entry(name.into()).or_insert_with(ProfileRecord::new);
+
+        rec.count += 1;
+        rec.nanos += dur;
+    }
+
+    /// Discards the profile data recorded so far.
+    pub fn profile_clear(&mut self) {
+        self.profile_map.clear();
+    }
+
+    /// Unstable; use at own risk.
+    pub fn profile_dump(&self) {
+        if self.profile_map.is_empty() {
+            println!("no profile data");
+        } else {
+            for (name, rec) in &self.profile_map {
+                let avg = rec.nanos / rec.count;
+                println!("{} nanos {}, count={}", avg, name, rec.count);
+            }
+        }
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Error control
+
+    /// get the current continue on error setting. the default is false.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::interp::Interp;
+    /// let mut interp = Interp::default();
+    /// assert_eq!(interp.continue_on_error(), false);
+    /// ```
+    pub fn continue_on_error(&self) -> bool {
+        self.continue_on_error
+    }
+
+    /// set whether to continue anyway in case of error.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt::types::*;
+    /// # use molt::interp::Interp;
+    /// let mut interp = Interp::default();
+    /// interp.set_continue_on_error(true);
+    /// assert_eq!(interp.continue_on_error(), true);
+    /// ```
+    pub fn set_continue_on_error(&mut self, c: bool) {
+        self.continue_on_error = c;
+    }
+
+    /// Gets whether `proc` parses procedure bodies when they are defined.  The default is
+    /// false.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let interp = Interp::default();
+    /// assert!(!interp.eager_proc_parse());
+    /// ```
+    pub fn eager_proc_parse(&self) -> bool {
+        self.eager_proc_parse
+    }
+
+    /// Sets whether `proc` parses procedure bodies when they are defined, so that a syntax
+    /// error is reported by `proc` itself.  By default, as in Tcl, a body is parsed when
+    /// the procedure is first called, and a syntax error is reported by the call.  Either
+    /// way, the error info names the procedure.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.eval("proc lazy {} {set a \"b}").unwrap();
+    /// assert!(interp.eval("lazy").is_err());
+    ///
+    /// interp.set_eager_proc_parse(true);
+    /// let exception = interp.eval("proc eager {} {set a \"b}").unwrap_err();
+    /// assert_eq!(exception.value().as_str(), "missing \"");
+    /// assert!(!interp.has_proc("eager"));
+    /// ```
+    pub fn set_eager_proc_parse(&mut self, flag: bool) {
+        self.eager_proc_parse = flag;
+    }
+
+    /// Gets whether panics in commands are caught.  The default is false.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// assert!(!interp.catch_panics());
+    /// ```
+    pub fn catch_panics(&self) -> bool {
+        self.catch_panics
+    }
+
+    /// Sets whether to catch panics in commands.  When set, a command that panics throws
+    /// the error `command panicked: `*message*, and the interpreter's scope stack and
+    /// nesting level are restored to what they were when the command was called, so the
+    /// interpreter remains usable.  The panic is still reported by the panic hook.
+    ///
+    /// When not set, a panic unwinds through the interpreter.  If the application catches
+    /// it, the interpreter is *poisoned*: every later evaluation throws an "interpreter in
+    /// inconsistent state" error until [`recover`](#method.recover) is called.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// fn cmd_boom(_: &mut Interp<()>, _: &[Value]) -> MoltResult {
+    ///     panic!("boom");
+    /// }
+    ///
+    /// let mut interp = Interp::new((), gen_command!((), [("boom", cmd_boom)], []), false, "app");
+    /// interp.set_catch_panics(true);
+    ///
+    /// let exception = interp.eval("boom").unwrap_err();
+    /// assert_eq!(exception.value().as_str(), "command panicked: boom");
+    /// assert_eq!(interp.eval("set a 1").unwrap().as_str(), "1");
+    /// ```
+    pub fn set_catch_panics(&mut self, flag: bool) {
+        self.catch_panics = flag;
+    }
+
+    /// Returns true if a panic has unwound out of an evaluation, leaving the interpreter
+    /// in an inconsistent state.  See [`set_catch_panics`](#method.set_catch_panics).
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.get()
+    }
+
+    /// Restores a poisoned interpreter to a usable state, popping any scopes and sourced
+    /// files left by the abandoned evaluation and resetting the nesting level.  Variables and procedures
+    /// changed before the panic keep their new values.  Does nothing if the interpreter
+    /// isn't poisoned.
+    pub fn recover(&mut self) {
+        if !self.poisoned.get() {
+            return;
+        }
+
+        while self.scopes.current() > self.top_scope {
+            self.discard_scope();
+        }
+        self.num_levels = 0;
+        self.script_files.clear();
+        self.poisoned.set(false);
+    }
+
+    /// Returns a handle on the interpreter's cancellation flag, a cloneable token.  Calling
+    /// [`cancel`](../cancel/struct.CancelHandle.html#method.cancel) on it, e.g., from
+    /// another thread or from a Stop button, stops the running script: before its next
+    /// command or expression operator, the interpreter returns a `ResultCode::Cancelled`
+    /// exception, whose value is "evaluation canceled" and whose error code is
+    /// `CANCELED`.  The flag is cleared at the start of each top-level evaluation, unless
+    /// [`sticky_cancel`](#method.set_sticky_cancel) is set.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// let handle = interp.cancel_handle();
+    /// interp.set_sticky_cancel(true);
+    ///
+    /// handle.cancel();
+    /// let exception = interp.eval("while {1} {}").unwrap_err();
+    /// assert_eq!(exception.code(), ResultCode::Cancelled);
+    /// assert_eq!(exception.value().as_str(), "evaluation canceled");
+    /// assert_eq!(exception.error_code().as_str(), "CANCELED");
+    ///
+    /// handle.reset();
+    /// assert!(interp.eval("set a 1").is_ok());
+    /// ```
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Gets whether a cancellation request survives from one top-level evaluation to the
+    /// next.  The default is false.
+    pub fn sticky_cancel(&self) -> bool {
+        self.sticky_cancel
+    }
+
+    /// Sets whether a cancellation request survives from one top-level evaluation to the
+    /// next.  When set, every evaluation fails until the application calls
+    /// [`reset`](../cancel/struct.CancelHandle.html#method.reset) on the handle.
+    pub fn set_sticky_cancel(&mut self, flag: bool) {
+        self.sticky_cancel = flag;
+    }
+
+    /// Gets whether `catch` lets a cancellation error through.  The default is false.
+    pub fn cancel_uncatchable(&self) -> bool {
+        self.cancel_uncatchable
+    }
+
+    /// Sets whether `catch` lets a cancellation error through, so that the script can't
+    /// continue after it's canceled.  By default, `catch` catches the error like any other,
+    /// and clears the request unless [`sticky_cancel`](#method.set_sticky_cancel) is set.
+    pub fn set_cancel_uncatchable(&mut self, flag: bool) {
+        self.cancel_uncatchable = flag;
+    }
+
+    /// Returns a `Cancelled` exception if cancellation has been requested, or throws the
+    /// "time limit exceeded" error if the time limit has run out.
+    #[inline]
+    pub(crate) fn check_interrupted(&self) -> Result<(), Exception> {
+        if self.cancel.is_canceled() {
+            Err(Exception::molt_cancelled())
+        } else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Err(Exception::molt_err2(
+                Value::from("TCL LIMIT TIME"),
+                Value::from("time limit exceeded"),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Counts a command, throwing the "command count limit exceeded" error if there's a
+    /// command limit and it's been exceeded.
+    #[inline]
+    fn count_command(&mut self) -> Result<(), Exception> {
+        self.command_count += 1;
+        match self.command_limit {
+            Some(limit) if self.command_count > limit => Err(Exception::molt_err2(
+                Value::from("TCL LIMIT COMMANDS"),
+                Value::from("command count limit exceeded"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Restarts the time and command limits, as each top-level evaluation begins.
+    fn restart_limits(&mut self) {
+        self.command_count = 0;
+        self.deadline = self.time_limit.map(|limit| Instant::now() + limit);
+    }
+
+    /// Gets the time limit on each top-level evaluation, if any.  The default is none.
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit
+    }
+
+    /// Sets the time limit on each top-level evaluation, or removes it with `None`.  Once
+    /// an evaluation has run for that long, the interpreter throws the error "time limit
+    /// exceeded", with error code `TCL LIMIT TIME`, before its next command or expression
+    /// operator.  The error can be caught, but every command after it fails the same
+    /// way, so the script ends.  This protects an application from runaway scripts,
+    /// e.g., `while 1 {}`, when it can't cancel them from another thread; see
+    /// [`cancel_handle`](#method.cancel_handle).
+    ///
+    /// Set during an evaluation, the limit runs from the time it's set.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut interp = Interp::default();
+    /// interp.set_time_limit(Some(Duration::from_millis(10)));
+    ///
+    /// let exception = interp.eval("while 1 {}").unwrap_err();
+    /// assert_eq!(exception.value().as_str(), "time limit exceeded");
+    /// assert_eq!(exception.error_code().as_str(), "TCL LIMIT TIME");
+    ///
+    /// // Each evaluation gets the full limit.
+    /// assert!(interp.eval("set a 1").is_ok());
+    /// ```
+    pub fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.time_limit = limit;
+        self.deadline = limit.map(|limit| Instant::now() + limit);
+    }
+
+    /// Gets the limit on the number of commands in each top-level evaluation, if any.  The
+    /// default is none.
+    pub fn command_limit(&self) -> Option<u64> {
+        self.command_limit
+    }
+
+    /// Sets the limit on the number of commands in each top-level evaluation, or removes it
+    /// with `None`.  Every command counts, including those in proc bodies and loops.  When
+    /// an evaluation would run one more, the interpreter throws the error "command count
+    /// limit exceeded", with error code `TCL LIMIT COMMANDS`.  As with
+    /// [`set_time_limit`](#method.set_time_limit), every command after it fails the same
+    /// way.
+    ///
+    /// A loop whose body has no commands, e.g., `while 1 {}`, runs just the one command;
+    /// use a time limit to stop it.
+    ///
+    /// # Example
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.set_command_limit(Some(100));
+    ///
+    /// let exception = interp.eval("while 1 { incr i }").unwrap_err();
+    /// assert_eq!(exception.value().as_str(), "command count limit exceeded");
+    /// assert_eq!(exception.error_code().as_str(), "TCL LIMIT COMMANDS");
+    /// assert_eq!(interp.scalar("i").unwrap().as_int(), Ok(99));
+    /// ```
+    pub fn set_command_limit(&mut self, limit: Option<u64>) {
+        self.command_limit = limit;
+    }
+
+    /// Decides whether `catch` may catch the exception, a result of its script.  A
+    /// `tailcall` is never caught, as it belongs to the procedure; a
+    /// cancellation is caught only if it's catchable, and catching it clears the
+    /// request unless it's sticky.
+    pub(crate) fn catchable(&self, exception: &Exception) -> bool {
+        if exception.code() == ResultCode::TailCall {
+            return false;
+        }
+
+        if exception.code() != ResultCode::Cancelled {
+            return true;
+        }
+
+        if self.cancel_uncatchable {
+            return false;
+        }
+
+        if !self.sticky_cancel {
+            self.cancel.reset();
+        }
+        true
+    }
+
+    //--------------------------------------------------------------------------------------------
+    // Events
+
+    /// Returns true if there are scripts scheduled by `after` that haven't run yet.
+    pub fn has_pending_events(&self) -> bool {
+        !self.events.is_empty()
+    }
+
+    /// Returns how long until the next script scheduled by `after` is due, or `None` if
+    /// there are none.  The delay is zero if a script is already due, or if one is
+    /// scheduled with `after idle`.  An application without an event loop of its own,
+    /// e.g., under wasm, sets a timer for the delay and then calls
+    /// [`run_pending_events`](#method.run_pending_events).
+    pub fn next_event_delay(&self) -> Option<std::time::Duration> {
+        self.events.next_delay(Instant::now())
+    }
+
+    /// Runs the scripts scheduled by `after` that are due: first the timers, in the order
+    /// they came due, and then the idle events, in the order they were scheduled.  The
+    /// scripts are evaluated at the global level.  Scripts scheduled while this runs wait
+    /// for the next call, so a script that reschedules itself can't keep it running.
+    ///
+    /// Returns the number of scripts run, or the first error, in which case the later
+    /// scripts stay scheduled.  If a script is canceled through the
+    /// [`cancel_handle`](#method.cancel_handle), though, every scheduled script is dropped,
+    /// so that a script that keeps rescheduling itself stops for good.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.eval("after idle {lappend log idle}; after 0 {lappend log timer}").unwrap();
+    /// assert!(interp.has_pending_events());
+    ///
+    /// std::thread::sleep(std::time::Duration::from_millis(1));
+    /// assert_eq!(interp.run_pending_events().unwrap(), 2);
+    /// assert_eq!(interp.eval("set log").unwrap().as_str(), "timer idle");
+    /// assert!(!interp.has_pending_events());
+    /// ```
+    pub fn run_pending_events(&mut self) -> Result<usize, Exception> {
+        let limit = self.events.next_id();
+        let mut count = 0;
+
+        while let Some(script) = self.events.take_due(Instant::now(), limit) {
+            count += 1;
+            if let Err(exception) = self.eval_global(&script) {
+                if exception.code() == ResultCode::Cancelled {
+                    self.events.clear();
+                }
+                return Err(exception);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Evaluates the script at the global level, as event scripts are, even if the
+    /// events are being run by `vwait` in a procedure.
+    fn eval_global(&mut self, script: &Value) -> MoltResult {
+        let level = self.scopes.current();
+        if level == 0 {
+            return self.eval_value(script);
+        }
+
+        let global = self.add_namespace("");
+        self.scopes.push_namespace(global);
+        let mut interp = ScopeGuard { interp: self, level };
+        interp.eval_value(script)
+    }
+
+    /// Notes that the named global variable has been written, waking the `vwait`
+    /// commands waiting on it: a blocked `vwait varName` returns once the script that
+    /// wrote the variable is done, and the script given to `vwait varName script` is
+    /// scheduled as an idle event.  The interpreter calls this itself when a script sets
+    /// or unsets the variable; an application that changes what the variable stands for
+    /// by other means, e.g., from a UI callback, calls it to the same effect.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp.eval("vwait done {set result finished}").unwrap();
+    ///
+    /// interp.notify_var_written("done");
+    /// assert_eq!(interp.run_pending_events().unwrap(), 1);
+    /// assert_eq!(interp.scalar("result").unwrap().as_str(), "finished");
+    /// ```
+    pub fn notify_var_written(&mut self, name: &str) {
+        let name = name.strip_prefix("::").unwrap_or(name);
+
+        for (trace, script) in self.events.written(name) {
+            self.var_traces.remove(trace);
+            self.events.schedule(None, script);
+        }
+    }
+
+    /// Runs the scripts scheduled by `after` until there are none left, sleeping until
+    /// each is due.  Returns the first error, leaving the later scripts scheduled.  Not
+    /// available under wasm, which can't block; see
+    /// [`next_event_delay`](#method.next_event_delay).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// interp
+    ///     .eval("proc tick {n} { lappend ::ticks $n; if {$n < 3} { after 1 tick [incr n] } }")
+    ///     .unwrap();
+    /// interp.eval("after 1 tick 1").unwrap();
+    ///
+    /// interp.event_loop().unwrap();
+    /// assert_eq!(interp.eval("set ticks").unwrap().as_str(), "1 2 3");
+    /// ```
+    #[cfg(not(feature = "wasm"))]
+    pub fn event_loop(&mut self) -> Result<(), Exception> {
+        while let Some(delay) = self.next_event_delay() {
+            if !delay.is_zero() {
+                std::thread::sleep(delay);
+            }
+            self.run_pending_events()?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts a syntax error in a procedure's body into an error naming the procedure.  The
+/// error is no longer marked as incomplete input, as the body is complete as given.
+fn proc_parse_error(name: &str, exception: Exception) -> Exception {
+    let mut error = Exception::molt_err(exception.value());
+    error.add_error_info(&format!("    (compiling body of proc \"{}\")", name));
+    error
+}
+
+/// Returns true if the name and value can be those of an environment variable; the
+/// standard library panics on any other, and on any at all under the `wasm` feature.
+fn is_env_var(name: &str, value: &str) -> bool {
+    !cfg!(feature = "wasm")
+        && !name.is_empty()
+        && !name.contains(['=', '\0'])
+        && !value.contains('\0')
+}
+
+/// Sets an environment variable when an element of the `env` array is set.
+fn set_env_var(name: &str, value: &str) {
+    if is_env_var(name, value) {
+        std::env::set_var(name, value);
+    }
+}
+
+/// Removes an environment variable when an element of the `env` array is unset.
+fn unset_env_var(name: &str) {
+    if is_env_var(name, "") {
+        std::env::remove_var(name);
+    }
+}
+
+/// How a procedure is defined: as an argument list and a body script.
+/// The argument list is a list of Values, and the body is a Value; each will
+/// retain its parsed form.
+///
+/// NOTE: We do not save the procedure's name; the name exists only in the
+/// commands table, and can be changed there freely.  The procedure truly doesn't
+/// know what its name is except when it is being executed.
+#[derive(Debug, Clone)]
+pub struct Procedure {
+    /// The procedure's parameter list.  Each item in the list is a name or a
+    /// name/default value pair.  (This is verified by the `proc` command.)
+    parms: MoltList,
+
+    /// The procedure's body string, as a Value.  As such, it retains both its
+    /// string value, as needed for introspection, and its parsed Script.
+    body: Value,
+
+    /// The namespace in which the body runs: that of the name the procedure was defined
+    /// or last renamed with.
+    namespace: Rc<str>,
+}
+
+impl Procedure {
+    pub fn execute<Ctx>(&self, interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
+    where
+        Ctx: 'static,
+    {
+        let result = self.call(interp, argv);
+
+        match &result {
+            Err(exception) if exception.code() == ResultCode::TailCall => {
+                Self::tail_call(interp, result)
+            }
+            _ => result,
+        }
+    }
+
+    // Calls the procedure once, returning a `tailcall`'s exception to `execute`.
+    fn call<Ctx>(&self, interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
+    where
+        Ctx: 'static,
+    {
+        // FIRST, push the proc's local scope onto the stack, bind the arguments, and
+        // evaluate the body.  The guard pops the scope however that ends, even by a
+        // panic, so that a bad call can't leave it behind.
+        let script = match self.body.as_script() {
+            Ok(script) => script,
+            Err(exception) => return Err(proc_parse_error(argv[0].as_str(), exception)),
+        };
+
+        let result = {
+            let mut interp = interp.local_scope();
+            interp.scopes.set_namespace(self.namespace.clone());
+            self.bind_args(&mut interp, argv)
+                .and_then(|_| interp.eval_parsed(&script))
+        };
+
+        if let Err(mut exception) = result {
+            // FIRST, handle the return -code, -level protocol
+            if exception.code() == ResultCode::Return {
+                exception.decrement_level();
+            }
+
+            return match exception.code() {
+                ResultCode::Okay => Ok(exception.value()),
+                ResultCode::Error => Err(exception),
+                ResultCode::Return => Err(exception), // -level > 0
                 ResultCode::Break => molt_err!("invoked \"break\" outside of a loop"),
                 ResultCode::Continue => {
                     molt_err!("invoked \"continue\" outside of a loop")
                 }
-                // TODO: Better error message
-                ResultCode::Other(_) => molt_err!("unexpected result code."),
-            };
+                // Custom codes are for the caller's control structure to handle.
+                ResultCode::Other(_) => Err(exception),
+                // As is a tailcall, for execute.
+                ResultCode::TailCall => Err(exception),
+                ResultCode::Cancelled => Err(exception),
+            };
+        }
+
+        // NEXT, return the computed result.
+        // Note: no need for special handling for return, break, continue;
+        // interp.eval() returns only Ok or a real error.
+        result
+    }
+
+    // Calls the command of a `tailcall`, in place of the procedure, in the caller's
+    // scope.  When it's a procedure too it's called here in turn rather than nested, so
+    // that tail recursion never reaches the recursion limit.
+    #[inline(never)]
+    fn tail_call<Ctx>(interp: &mut Interp<Ctx>, mut result: MoltResult) -> MoltResult
+    where
+        Ctx: 'static,
+    {
+        while let Err(exception) = &result {
+            if exception.code() != ResultCode::TailCall {
+                break;
+            }
+
+            let words = exception.value().as_list()?;
+            let name = interp.command_key(words[0].as_str()).into_owned();
+            let proc = match interp.command_kind(&name) {
+                Some(CommandType::Proc) => interp.get_proc(&name).cloned(),
+                _ => None,
+            };
+            result = match proc {
+                Some(proc) => proc.call(interp, &words),
+                None => interp.execute(&name, &words),
+            };
+        }
+
+        result
+    }
+
+    // Assigns the arguments to the parameters in the proc's local scope.
+    fn bind_args<Ctx>(&self, interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult
+    where
+        Ctx: 'static,
+    {
+        let mut argi = 1; // Skip the proc's name
+
+        for (speci, spec) in self.parms.iter().enumerate() {
+            // FIRST, get the parameter as a vector.  It should be a list of
+            // one or two elements.
+            let vec = &*spec.as_list()?; // Should never fail
+            assert!(vec.len() == 1 || vec.len() == 2);
+
+            // NEXT, if this is the args parameter, give the remaining args,
+            // if any.  Note that "args" has special meaning only if it's the
+            // final arg spec in the list.
+            if vec[0].as_str() == "args" && speci == self.parms.len() - 1 {
+                interp.set_scalar("args", Value::from(&argv[argi..]))?;
+
+                // We've processed all of the args
+                argi = argv.len();
+                break;
+            }
+
+            // NEXT, do we have a matching argument?
+            if argi < argv.len() {
+                // Pair them up
+                interp.set_scalar(vec[0].as_str(), argv[argi].clone())?;
+                argi += 1;
+                continue;
+            }
+
+            // NEXT, do we have a default value?
+            if vec.len() == 2 {
+                interp.set_scalar(vec[0].as_str(), vec[1].clone())?;
+            } else {
+                // We don't; we're missing a required argument.
+                return self.wrong_num_args(&argv[0]);
+            }
+        }
+
+        // NEXT, do we have any arguments left over?
+        if argi != argv.len() {
+            return self.wrong_num_args(&argv[0]);
+        }
+
+        molt_ok!()
+    }
+
+    // Outputs the wrong # args message for the proc.  The name is passed in
+    // because it can be changed via the `rename` command.
+    fn wrong_num_args(&self, name: &Value) -> MoltResult {
+        let mut msg = String::new();
+        msg.push_str("wrong # args: should be \"");
+        msg.push_str(name.as_str());
+
+        for (i, arg) in self.parms.iter().enumerate() {
+            msg.push(' ');
+
+            // "args" has special meaning only in the last place.
+            if arg.as_str() == "args" && i == self.parms.len() - 1 {
+                msg.push_str("?arg ...?");
+                break;
+            }
+
+            let vec = arg.as_list().expect("error in proc arglist validation!");
+
+            if vec.len() == 1 {
+                msg.push_str(vec[0].as_str());
+            } else {
+                msg.push('?');
+                msg.push_str(vec[0].as_str());
+                msg.push('?');
+            }
+        }
+        msg.push('"');
+
+        molt_err!(&msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::DatumType;
+    use crate::gen_command_minimal;
+    use crate::prelude::{cmd_proc, cmd_set, _PROC, _SET};
+    use std::fs;
+    use std::io::{self, Write};
+
+    #[test]
+    fn test_new() {
+        let interp = Interp::default();
+
+        // Interpreter is not empty
+        assert!(!interp.command_names().is_empty());
+
+        // Note: in theory, we should test here that the normal set of commands is present.
+        // In fact, that should be tested by the `molt test` suite.
+    }
+
+    #[test]
+    fn test_eval() {
+        let mut interp = Interp::default();
+
+        assert_eq!(interp.eval("set a 1"), Ok(Value::from("1")));
+        assert!(ex_match(&interp.eval("error 2"), Exception::molt_err(Value::from("2"))));
+        assert_eq!(interp.eval("return 3"), Ok(Value::from("3")));
+        assert!(ex_match(
+            &interp.eval("break"),
+            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
+        ));
+        assert!(ex_match(
+            &interp.eval("continue"),
+            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
+        ));
+    }
+
+    // Shows that the result is matches the given exception.  Ignores the exception's
+    // ErrorData, if any.
+    fn ex_match(r: &MoltResult, expected: Exception) -> bool {
+        // FIRST, if the results are of different types, there's no match.
+        if let Err(e) = r {
+            e.code() == expected.code() && e.value() == expected.value()
+        } else {
+            false
+        }
+    }
+
+    #[test]
+    fn test_eval_value() {
+        let mut interp = Interp::default();
+
+        assert_eq!(interp.eval_value(&Value::from("set a 1")), Ok(Value::from("1")));
+        assert!(ex_match(
+            &interp.eval_value(&Value::from("error 2")),
+            Exception::molt_err(Value::from("2"))
+        ));
+        assert_eq!(interp.eval_value(&Value::from("return 3")), Ok(Value::from("3")));
+        assert!(ex_match(
+            &interp.eval_value(&Value::from("break")),
+            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
+        ));
+        assert!(ex_match(
+            &interp.eval_value(&Value::from("continue")),
+            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
+        ));
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut interp = Interp::default();
+
+        assert!(interp.complete("abc"));
+        assert!(interp.complete("a {bc} [def] \"ghi\" xyz"));
+
+        assert!(!interp.complete("a {bc"));
+        assert!(!interp.complete("a [bc"));
+        assert!(!interp.complete("a \"bc"));
+    }
+
+    #[test]
+    fn test_export_import_state() {
+        let mut template = Interp::default();
+        template
+            .eval(
+                r#"
+            proc greet {name {greeting "Hello there"} args} {
+                return "$greeting, $name[join $args {}]!"
+            }
+            proc brace {} { return "\{" }
+            set plain abc
+            set spaced {a b  c}
+            set special "\{x \$y \[z\] \\ \"q\"\n"
+            set empty {}
+            set weird(a\ b) 1
+            set weird(\{) 2
+            set weird(\$x) 3
+            set weird(\[cmd\]) 4
+            set weird() 5
+            set weird(\\) "\}"
+            "#,
+            )
+            .unwrap();
+
+        let filter = StateFilter {
+            procs: vec!["*".into()],
+            vars: vec![
+                "plain".into(),
+                "spaced".into(),
+                "special".into(),
+                "e*".into(),
+                "w*".into(),
+            ],
+        };
+        let state = template.export_state(&filter);
+
+        let mut worker = Interp::default();
+        let result = worker.import_state(&state);
+        assert!(result.is_ok(), "{}\n{:?}", state, result);
+
+        // Procs behave the same.
+        for script in &["greet World", "greet World Hi", "greet World Hi . .", "brace"] {
+            assert_eq!(worker.eval(script), template.eval(script));
+        }
+        assert_eq!(worker.proc_body("greet"), template.proc_body("greet"));
+
+        // Variables have the same values.
+        for name in &["plain", "spaced", "special", "empty"] {
+            assert_eq!(worker.scalar(name), template.scalar(name));
+        }
+        let names = template.array_names("weird");
+        assert_eq!(names.len(), 6);
+        for index in &names {
+            assert_eq!(
+                worker.element("weird", index.as_str()),
+                template.element("weird", index.as_str())
+            );
+        }
+        assert_eq!(worker.array_size("weird"), 6);
+
+        // Exporting again gives the same script.
+        assert_eq!(worker.export_state(&filter), state);
+    }
+
+    #[test]
+    fn test_export_state_filter() {
+        let mut interp = Interp::default();
+        interp
+            .eval("proc p1 {} {}; proc p2 {} {}; proc q {} {}; set a 1; set b 2; set c(1) 3")
+            .unwrap();
+
+        assert_eq!(interp.export_state(&StateFilter::default()), "");
+
+        let filter = StateFilter {
+            procs: vec!["p*".into()],
+            vars: vec!["a".into(), "c".into()],
+        };
+        assert_eq!(
+            interp.export_state(&filter),
+            "proc p1 {} {}\nproc p2 {} {}\nset a 1\narray set c {1 3}\n"
+        );
+    }
+
+    #[test]
+    fn test_error_code_list() {
+        let mut interp = Interp::default();
+
+        let exception = interp
+            .eval("throw {ARITH DIVZERO {divide by zero}} \"divide by zero\"")
+            .unwrap_err();
+        let code = exception.error_code_list().unwrap();
+        assert_eq!(code.len(), 3);
+        assert_eq!(code[0].as_str(), "ARITH");
+        assert_eq!(code[2].as_str(), "divide by zero");
+
+        // The global errorCode is the same list.
+        let code = interp.scalar("errorCode").unwrap();
+        assert_eq!(code.as_list().unwrap()[0].as_str(), "ARITH");
+
+        // So is the caught error's -errorcode.
+        let result = interp
+            .eval("catch {error oops {} {MY CODE}} msg opts; dict get $opts -errorcode")
+            .unwrap();
+        assert_eq!(result.as_list().unwrap()[0].as_str(), "MY");
+    }
+
+    #[test]
+    fn test_math_funcs() {
+        fn double2(_: &mut Interp<()>, args: &[Value]) -> MoltResult {
+            if args.len() != 1 {
+                return molt_err!("expected double2(x)");
+            }
+            molt_ok!(args[0].as_float()? * 2.0)
+        }
+
+        fn dist(_: &mut Interp<()>, args: &[Value]) -> MoltResult {
+            if args.len() != 2 {
+                return molt_err!("expected dist(x,y)");
+            }
+            molt_ok!(args[0].as_float()?.hypot(args[1].as_float()?))
+        }
+
+        // Calls itself through expr, without end.
+        fn forever(interp: &mut Interp<()>, _: &[Value]) -> MoltResult {
+            interp.expr(&Value::from("forever()"))
+        }
+
+        let mut interp = Interp::default();
+        interp.add_math_func("double2", double2);
+        interp.add_math_func("dist", dist);
+        interp.add_math_func("forever", forever);
+        interp.eval("proc tcl::mathfunc::half {x} { expr {$x / 2} }").unwrap();
+
+        assert_eq!(interp.eval("expr {double2(3) + half(10)}"), Ok(Value::from(11.0)));
+        assert_eq!(interp.eval("expr {half(double2(2.5))}"), Ok(Value::from(2.5)));
+
+        // Several arguments, which may be any expressions.
+        interp.eval("set x 3; set y 4").unwrap();
+        assert_eq!(interp.eval("expr {dist($x,$y)}"), Ok(Value::from(5.0)));
+        assert_eq!(interp.eval("expr {dist($x * 2, $y + 4)}"), Ok(Value::from(10.0)));
+        assert!(ex_match(
+            &interp.eval("expr {dist($x)}"),
+            Exception::molt_err(Value::from("expected dist(x,y)"))
+        ));
+
+        // Rust functions take precedence over procs.
+        interp.eval("proc mathfunc::double2 {x} { return 0 }").unwrap();
+        assert_eq!(interp.eval("expr {double2(1)}"), Ok(Value::from(2.0)));
+
+        assert!(ex_match(
+            &interp.eval("expr {double2()}"),
+            Exception::molt_err(Value::from("expected double2(x)"))
+        ));
+        interp.set_recursion_limit(20);
+        assert!(ex_match(
+            &interp.eval("expr {forever()}"),
+            Exception::molt_err(Value::from(
+                "too many nested calls to Interp::eval (infinite loop?)"
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_handler() {
+        fn words(_: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+            molt_ok!(format!("handler: {}", list_to_string(argv)))
+        }
+
+        let mut interp = Interp::default();
+        interp.set_unknown_handler(Some(words));
+        assert_eq!(interp.eval("frob a {b c}"), Ok(Value::from("handler: frob a {b c}")));
+
+        // An unknown proc takes precedence over the handler.
+        interp.eval("proc unknown {args} { return \"proc: $args\" }").unwrap();
+        assert_eq!(interp.eval("frob a"), Ok(Value::from("proc: frob a")));
+        interp.eval("rename unknown {}").unwrap();
+        assert_eq!(interp.eval("frob a"), Ok(Value::from("handler: frob a")));
+
+        interp.set_unknown_handler(None);
+        assert!(interp.eval("frob a").is_err());
+    }
+
+    #[test]
+    fn test_exec_trace() {
+        // Logs each call, and refuses to call "exit".
+        fn log(
+            interp: &mut Interp<()>,
+            words: &[Value],
+            op: ExecOp,
+            result: Option<&MoltResult>,
+        ) -> Result<(), Exception> {
+            if words[0].as_str() == "exit" {
+                return molt_err!("not allowed");
+            }
+            let entry = match result {
+                Some(Ok(value)) => format!("{} {} {}", op, words[0], value),
+                _ => format!("{} {}", op, words[0]),
+            };
+            interp.eval_value(&Value::from(vec![
+                Value::from("lappend"),
+                Value::from("::log"),
+                Value::from(entry),
+            ]))?;
+            Ok(())
+        }
+
+        let mut interp = Interp::default();
+        let id = interp.add_exec_trace(log);
+        interp.eval("set a 1").unwrap();
+        assert_eq!(interp.scalar("log").unwrap().as_str(), "{enter set} {leave set 1}");
+        assert!(ex_match(
+            &interp.eval("exit"),
+            Exception::molt_err(Value::from("not allowed"))
+        ));
+
+        assert!(interp.remove_exec_trace(id));
+        assert!(!interp.remove_exec_trace(id));
+        interp.eval("set log {}; set b 2").unwrap();
+        assert_eq!(interp.scalar("log").unwrap().as_str(), "");
+    }
+
+    #[test]
+    fn test_last_error() {
+        let mut interp = Interp::default();
+        assert_eq!(interp.last_error_info(), None);
+        assert_eq!(interp.last_error_code(), None);
+
+        // A failure is reported.
+        assert!(interp.eval("error oops {} {MY CODE}").is_err());
+        assert_eq!(
+            interp.last_error_info(),
+            Some(Value::from("oops\nwhile executing\n  \"error oops {} {MY CODE}\""))
+        );
+        assert_eq!(interp.last_error_code(), Some(Value::from("MY CODE")));
+
+        // A later success clears it, but leaves the variables alone.
+        assert!(interp.eval("set a 1").is_ok());
+        assert_eq!(interp.last_error_info(), None);
+        assert_eq!(interp.last_error_code(), None);
+        assert_eq!(interp.scalar("errorCode").unwrap().as_str(), "MY CODE");
+
+        // An error caught within a successful script doesn't count.
+        assert!(interp.eval("catch {error inner {} INNER}").is_ok());
+        assert_eq!(interp.last_error_info(), None);
+        assert_eq!(interp.scalar("errorCode").unwrap().as_str(), "INNER");
+
+        // Nor does the error of a nested eval.
+        assert!(interp.eval("proc p {} {error deep {} DEEP}; catch p").is_ok());
+        assert_eq!(interp.last_error_code(), None);
+
+        // Expressions count as top-level evaluations.
+        assert!(interp.expr(&Value::from("1 +")).is_err());
+        assert!(interp.last_error_info().is_some());
+        assert!(interp.expr(&Value::from("1 + 1")).is_ok());
+        assert_eq!(interp.last_error_info(), None);
+
+        // Non-error exceptions aren't errors.
+        assert!(interp.eval("return -level 2 x").is_err());
+        assert_eq!(interp.last_error_info(), None);
+    }
+
+    // A writer that appends to a shared buffer, marking each flush with "|".
+    #[derive(Clone, Default)]
+    struct Sink(Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.borrow_mut().push(b'|');
+            Ok(())
+        }
+    }
+
+    impl Sink {
+        fn take(&self) -> String {
+            String::from_utf8(std::mem::take(&mut *self.0.borrow_mut())).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_stdout_writer() {
+        use crate::prelude::*;
+
+        // "log" writes straight to the sink, as Rust-side logging would.
+        fn cmd_log(interp: &mut Interp<Sink>, argv: &[Value]) -> MoltResult {
+            writeln!(interp.context.0.borrow_mut(), "log: {}", argv[1]).unwrap();
+            molt_ok!()
+        }
+
+        let sink = Sink::default();
+        let mut interp = Interp::new(
+            sink.clone(),
+            gen_command!(Sink, [("log", cmd_log)], []),
+            false,
+            "t",
+        );
+
+        // Unbuffered: output appears in order, a line per puts.
+        interp.set_stdout_writer(Box::new(sink.clone()));
+        interp.eval("puts a; log b; puts {c d}; flush; flush stdout").unwrap();
+        assert_eq!(sink.take(), "a\nlog: b\nc d\n||");
+
+        // Block-buffered: puts output waits for the flush.
+        interp.set_stdout_writer(Box::new(io::BufWriter::new(sink.clone())));
+        assert_eq!(sink.take(), "|");
+        interp.eval("puts a; log b; puts c; flush stdout; log d").unwrap();
+        assert_eq!(sink.take(), "log: b\na\nc\n|log: d\n");
+
+        // Line-buffered: each line is written by the end of its puts.
+        interp.set_stdout_writer(Box::new(io::LineWriter::new(sink.clone())));
+        sink.take();
+        interp.eval("puts a; log b; puts \"c\nd\"; log e").unwrap();
+        assert_eq!(sink.take(), "a\nlog: b\nc\nd\nlog: e\n");
+
+        // stderr has its own writer.
+        interp.set_stderr_writer(Box::new(sink.clone()));
+        sink.take();
+        interp.eval("flush stderr").unwrap();
+        assert_eq!(sink.take(), "|");
+        interp
+            .eval("puts stderr oops; puts -nonewline stderr x; puts -nonewline y")
+            .unwrap();
+        assert_eq!(sink.take(), "oops\nx");
+
+        // Commands can write to the same stream.
+        writeln!(interp.stderr_writer(), "direct").unwrap();
+        assert_eq!(sink.take(), "direct\n");
+
+        assert_eq!(
+            interp
+                .eval("puts -nonewline stdout a b")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "wrong # args: should be \"puts ?-nonewline? ?channelId? string\""
+        );
+
+        assert_eq!(
+            interp.eval("flush stdin").unwrap_err().value().as_str(),
+            "can not find channel named \"stdin\""
+        );
+    }
+
+    #[test]
+    fn test_proc_parse_errors() {
+        // Lazy: the proc is defined, and the call fails.
+        let mut interp = Interp::default();
+        assert!(interp.eval("proc p {} \"set a \\{b\"").is_ok());
+        let exception = interp.eval("p").unwrap_err();
+        assert_eq!(exception.value().as_str(), "missing close-brace");
+        assert!(exception
+            .error_info()
+            .as_str()
+            .contains("(compiling body of proc \"p\")"));
+        assert_eq!(exception.uncompleted_kind(), None);
+
+        // Eager: proc fails, and defines nothing.
+        let mut interp = InterpBuilder::default().eager_proc_parse(true).build();
+        assert!(interp.eager_proc_parse());
+        let exception = interp.eval("proc p {} \"set a \\{b\"").unwrap_err();
+        assert_eq!(exception.value().as_str(), "missing close-brace");
+        assert!(exception
+            .error_info()
+            .as_str()
+            .contains("(compiling body of proc \"p\")"));
+        assert!(!interp.has_proc("p"));
+
+        // A good body is fine either way.
+        assert!(interp.eval("proc p {} {set a b}; p").is_ok());
+    }
+
+    #[test]
+    fn test_proc_body_reuse() {
+        let mut interp = Interp::default();
+        let script_of = |interp: &Interp<()>, name: &str| {
+            interp.procs[name].body.as_script().unwrap()
+        };
+
+        // The body is built anew each time, so only the text is the same.
+        interp
+            .eval("for {set i 0} {$i < 1000} {incr i} { proc gen2 {x} \"return \\$x\"; gen2 $i }")
+            .unwrap();
+        let first = script_of(&interp, "gen2");
+        interp
+            .eval("for {set i 0} {$i < 1000} {incr i} { proc gen2 {x} \"return \\$x\"; gen2 $i }")
+            .unwrap();
+        assert!(Rc::ptr_eq(&first, &script_of(&interp, "gen2")));
+        assert_eq!(interp.eval("gen2 7").unwrap().as_str(), "7");
+
+        // A different body is parsed.
+        interp.eval("proc gen2 {x} {return [expr {$x + 1}]}").unwrap();
+        assert!(!Rc::ptr_eq(&first, &script_of(&interp, "gen2")));
+        assert_eq!(interp.eval("gen2 7").unwrap().as_str(), "8");
+
+        // Restoring a snapshot keeps the parsed body.
+        let state = interp.export_state(&StateFilter::all());
+        let before = script_of(&interp, "gen2");
+        interp.import_state(&state).unwrap();
+        assert!(Rc::ptr_eq(&before, &script_of(&interp, "gen2")));
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let default = Interp::default();
+        let mut built = InterpBuilder::default().build();
+
+        assert_eq!(built.command_names(), default.command_names());
+        assert_eq!(built.name, default.name);
+        assert_eq!(built.recursion_limit(), default.recursion_limit());
+        assert_eq!(built.continue_on_error(), default.continue_on_error());
+        assert_eq!(built.array_names("env").len(), default.array_names("env").len());
+        assert_eq!(built.scalar("errorInfo"), Ok(Value::empty()));
+
+        // A new builder has no commands, but the same other settings.
+        let empty = InterpBuilder::new(()).build();
+        assert!(empty.command_names().is_empty());
+        assert_eq!(empty.name, "default-app");
+        assert_eq!(empty.recursion_limit(), 1000);
+        assert!(!empty.continue_on_error());
+        assert!(empty.array_exists("env") || std::env::vars().next().is_none());
+    }
+
+    #[test]
+    fn test_builder_settings() {
+        let mut interp = InterpBuilder::default()
+            .context(())
+            .populate_env(false)
+            .name("built")
+            .recursion_limit(10)
+            .continue_on_error(true)
+            .build();
+
+        assert_eq!(interp.name, "built");
+        assert_eq!(interp.recursion_limit(), 10);
+        assert!(interp.continue_on_error());
+        assert!(!interp.array_exists("env"));
+        assert!(interp.eval("proc r {} { r }; r").is_err());
+
+        // Interp::new is the builder's shorthand.
+        let interp = Interp::new((), Command::empty(), false, "new");
+        assert_eq!(interp.name, "new");
+        assert!(!interp.array_exists("env"));
+    }
+
+    #[test]
+    fn test_trace_var() {
+        fn log(
+            interp: &mut Interp<()>,
+            name: &str,
+            index: &str,
+            op: TraceOp,
+        ) -> Result<(), Exception> {
+            let entry = Value::from(vec![name.into(), index.into(), op.as_str().into()]);
+            interp.eval_value(&Value::from(vec![
+                "lappend".into(),
+                "::log".into(),
+                entry,
+            ]))?;
+            Ok(())
+        }
+
+        fn deny(
+            _: &mut Interp<()>,
+            _: &str,
+            _: &str,
+            _: TraceOp,
+        ) -> Result<(), Exception> {
+            molt_err!("denied")
+        }
+
+        let mut interp = Interp::default();
+        let all = [TraceOp::Read, TraceOp::Write, TraceOp::Unset];
+        let id = interp.trace_var(&Value::from("a"), &all, log).unwrap();
+
+        interp.eval("set a(x) 1; set a(x); unset a(x)").unwrap();
+        assert_eq!(
+            interp.scalar("log").unwrap().as_str(),
+            "{a x write} {a x read} {a x unset}"
+        );
+
+        // Removing it.
+        assert!(interp.untrace_var(id));
+        assert!(!interp.untrace_var(id));
+        interp.eval("set a(y) 1").unwrap();
+        assert_eq!(interp.eval("llength $log").unwrap().as_int(), Ok(3));
+
+        // Errors.
+        interp.trace_var(&Value::from("b"), &[TraceOp::Read], deny).unwrap();
+        interp.set_scalar("b", Value::from(1)).unwrap();
+        let err = interp.scalar("b").unwrap_err();
+        assert_eq!(err.value().as_str(), "can't read \"b\": denied");
+
+        // A trace on a local variable goes away with its scope.
+        interp.push_scope();
+        interp.trace_var(&Value::from("local"), &all, log).unwrap();
+        interp.set_scalar("local", Value::from(1)).unwrap();
+        interp.pop_scope();
+        let log = interp.scalar("log").unwrap().as_list().unwrap();
+        assert_eq!(
+            Value::from(log[3..].to_vec()).as_str(),
+            "{local {} write} {local {} unset}"
+        );
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_env_is_linked() {
+        let mut interp = Interp::default();
+        let name = format!("MOLT_TEST_ENV_{}", std::process::id());
+        let var = |name: &str| std::env::var(name).ok();
+
+        // Setting and unsetting an element.
+        interp.eval(&format!("set env({}) one", name)).unwrap();
+        assert_eq!(var(&name).as_deref(), Some("one"));
+        interp.eval(&format!("unset env({})", name)).unwrap();
+        assert_eq!(var(&name), None);
+
+        // Via array set, and from a proc.
+        interp
+            .eval(&format!("array set env {{{}_A a {}_B b}}", name, name))
+            .unwrap();
+        assert_eq!(var(&format!("{}_A", name)).as_deref(), Some("a"));
+        interp
+            .eval(&format!("proc p {{}} {{ global env; set env({}_B) bb }}; p", name))
+            .unwrap();
+        assert_eq!(var(&format!("{}_B", name)).as_deref(), Some("bb"));
+
+        interp.eval(&format!("array unset env {}_A", name)).unwrap();
+        interp.eval(&format!("unset env({}_B)", name)).unwrap();
+        assert_eq!(var(&format!("{}_A", name)), None);
+        assert_eq!(var(&format!("{}_B", name)), None);
+
+        // Names the environment can't hold stay in the array.
+        interp.eval("set env(A=B) c").unwrap();
+        assert_eq!(interp.eval("set env(A=B)").unwrap().as_str(), "c");
+
+        // An interpreter without the array doesn't touch the environment.
+        let mut interp = InterpBuilder::default().populate_env(false).build();
+        interp.eval(&format!("set env({}) one", name)).unwrap();
+        assert_eq!(var(&name), None);
+    }
+
+    #[test]
+    fn test_assert_messages() {
+        let mut interp = Interp::default();
+        let message = |interp: &mut Interp<()>, script: &str| {
+            interp.eval(script).unwrap_err().value().to_string()
+        };
+
+        assert_eq!(
+            message(&mut interp, "assert_eq {a b} a"),
+            "assert_eq failed:\n  expected: a\n  actual:   {a b}"
+        );
+        assert_eq!(
+            message(&mut interp, "assert_err {list {}}"),
+            "assert_err failed: no error\n  result:   {{}}"
+        );
+        assert_eq!(
+            message(&mut interp, "assert_err {return x}"),
+            "assert_err failed: no error\n  code:     return\n  result:   x"
+        );
+        assert_eq!(
+            message(&mut interp, "assert_err {error {a b}} a"),
+            "assert_err failed:\n  pattern:  a\n  error:    {a b}"
+        );
+        assert_eq!(
+            message(&mut interp, "assert_code {error {a b}} ok"),
+            "assert_code failed:\n  expected: ok\n  actual:   error\n  result:   {a b}"
+        );
+
+        assert_eq!(
+            interp.eval("assert_err {error {a b}} {a *}").unwrap().as_str(),
+            "a b"
+        );
+        assert_eq!(interp.eval("assert_code {expr 1+1} 0").unwrap().as_str(), "2");
+    }
+
+    #[test]
+    fn test_element_index_round_trip() {
+        let mut interp = Interp::default();
+        let indices =
+            ["", "b c", "x)", ")", "x)y", "(p)", "((", "$x", "a;b", " ", "(b c)"];
+
+        for index in indices {
+            interp.set_element("a", index, Value::from(index)).unwrap();
+
+            // The combined name finds the element: the index runs from the first "(" to
+            // the final ")".
+            let var_name = Value::from(format!("a({})", index));
+            assert_eq!(interp.var(&var_name).unwrap().as_str(), index);
+            assert_eq!(interp.element("a", index).unwrap().as_str(), index);
+
+            // Scripts see it too.
+            interp.set_scalar("key", Value::from(index)).unwrap();
+            assert_eq!(interp.eval("set a($key)").unwrap().as_str(), index);
+
+            interp.unset_var(&var_name);
+            assert!(interp.element("a", index).is_err());
+        }
+
+        assert_eq!(interp.eval("array size a").unwrap().as_str(), "0");
+    }
+
+    #[test]
+    fn test_set_scalars() {
+        let mut interp = Interp::default();
+        interp.set_scalar("x", Value::from("old")).unwrap();
+        interp.eval("set arr(1) a").unwrap();
+
+        interp
+            .set_scalars(&[("x", Value::from(1)), ("y", Value::from(2))])
+            .unwrap();
+        assert_eq!(
+            interp.scalars(&["x", "y", "arr", "nonesuch"]),
+            vec![Some(Value::from(1)), Some(Value::from(2)), None, None]
+        );
+
+        // The batch stops at the array: the entries before it are set, the rest aren't.
+        let exception = interp
+            .set_scalars(&[
+                ("a", Value::from(1)),
+                ("arr", Value::from(2)),
+                ("b", Value::from(3)),
+            ])
+            .unwrap_err();
+        assert_eq!(exception.value().as_str(), "can't set \"arr\": variable is array");
+        assert_eq!(interp.scalars(&["a", "b"]), vec![Some(Value::from(1)), None]);
+        assert_eq!(interp.element("arr", "1").unwrap().as_str(), "a");
+
+        // Linked variables are set through the link.
+        {
+            let mut scope = interp.local_scope();
+            scope.eval("global g").unwrap();
+            scope
+                .set_scalars(&[("g", Value::from(5)), ("loc", Value::from(6))])
+                .unwrap();
+            assert_eq!(scope.scalar("loc").unwrap().as_str(), "6");
+        }
+        assert_eq!(interp.scalars(&["g", "loc"]), vec![Some(Value::from(5)), None]);
+    }
+
+    #[test]
+    fn test_set_elements() {
+        let mut interp = Interp::default();
+
+        interp
+            .set_elements("a", &[("x", Value::from(1)), ("y", Value::from(2))])
+            .unwrap();
+        interp.set_elements("a", &[("y", Value::from(3))]).unwrap();
+        assert_eq!(interp.eval("array size a").unwrap().as_str(), "2");
+        assert_eq!(interp.element("a", "x").unwrap().as_str(), "1");
+        assert_eq!(interp.element("a", "y").unwrap().as_str(), "3");
+
+        // An empty batch doesn't create the variable.
+        interp.set_elements("b", &[]).unwrap();
+        assert_eq!(interp.eval("info exists b").unwrap().as_str(), "0");
+
+        // A scalar can't take elements; none are set.
+        interp.set_scalar("s", Value::from(1)).unwrap();
+        let exception = interp
+            .set_elements("s", &[("i", Value::from(1)), ("j", Value::from(2))])
+            .unwrap_err();
+        assert_eq!(
+            exception.value().as_str(),
+            "can't set \"s(i)\": variable isn't array"
+        );
+        assert_eq!(interp.scalar("s").unwrap().as_str(), "1");
+    }
+
+    fn cmd_boom(_interp: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+        panic!("boom {}", argv.len());
+    }
+
+    fn panicky_interp() -> Interp<()> {
+        use crate::prelude::*;
+        Interp::new((), gen_command!((), [("boom", cmd_boom)], []), false, "panicky")
+    }
+
+    #[test]
+    fn test_catch_panics() {
+        let mut interp = panicky_interp();
+        interp.set_catch_panics(true);
+        interp
+            .eval("proc deep {n} { if {$n == 0} { boom } else { deep [expr {$n - 1}] } }")
+            .unwrap();
+
+        // A panic at the top level is an error.
+        let exception = interp.eval("boom").unwrap_err();
+        assert_eq!(exception.value().as_str(), "command panicked: boom 1");
+
+        // A panic in a nested proc call unwinds the scopes and levels.
+        let exception = interp.eval("set x 1; deep 5").unwrap_err();
+        assert_eq!(exception.value().as_str(), "command panicked: boom 1");
+        assert!(exception.error_info().as_str().contains("while executing"));
+        assert_eq!(interp.scopes.current(), 0);
+        assert_eq!(interp.num_levels, 0);
+        assert!(!interp.is_poisoned());
+
+        // The error can be caught, and the interpreter works normally afterwards.
+        assert_eq!(
+            interp.eval("catch {deep 3} msg; set msg").unwrap().as_str(),
+            "command panicked: boom 1"
+        );
+        assert_eq!(interp.eval("proc p {} { return ok }; p").unwrap().as_str(), "ok");
+        assert!(interp.eval("break").is_err());
+        assert_eq!(interp.eval("set x").unwrap().as_str(), "1");
+    }
+
+    #[test]
+    fn test_poisoned_after_panic() {
+        let mut interp = panicky_interp();
+        interp
+            .eval("proc deep {n} { if {$n == 0} { boom } else { deep [expr {$n - 1}] } }")
+            .unwrap();
+        assert!(!interp.catch_panics());
+
+        // The panic propagates, and poisons the interpreter.  The procs' scopes are popped
+        // as it unwinds.
+        let caught = std::panic::catch_unwind(AssertUnwindSafe(|| interp.eval("deep 3")));
+        assert!(caught.is_err());
+        assert!(interp.is_poisoned());
+        assert_eq!(interp.scope_level(), 0);
+
+        let exception = interp.eval("set a 1").unwrap_err();
+        assert!(exception
+            .value()
+            .as_str()
+            .starts_with("interpreter in inconsistent state"));
+        assert!(interp.scalar("a").is_err());
+
+        // Recovery restores the interpreter's invariants.
+        interp.recover();
+        assert!(!interp.is_poisoned());
+        assert_eq!(interp.scopes.current(), 0);
+        assert_eq!(interp.num_levels, 0);
+        assert_eq!(interp.eval("set a 1").unwrap().as_str(), "1");
+        assert_eq!(
+            interp.eval("break").unwrap_err().value().as_str(),
+            "invoked \"break\" outside of a loop"
+        );
+    }
+
+    // Where and how cmd_fault fails: the trigger-th call fails in the given way.
+    struct Fault {
+        calls: usize,
+        trigger: usize,
+        kind: usize,
+    }
+
+    fn cmd_fault(interp: &mut Interp<Fault>, _argv: &[Value]) -> MoltResult {
+        let fault = &mut interp.context;
+        fault.calls += 1;
+        if fault.calls != fault.trigger {
+            return molt_ok!();
+        }
+
+        match fault.kind {
+            0 => molt_err!("injected error"),
+            1 => Err(Exception::molt_break()),
+            2 => Err(Exception::molt_continue()),
+            3 => Err(Exception::molt_return_ext(Value::empty(), 2, ResultCode::Okay)),
+            4 => Err(Exception::molt_return_ext(Value::empty(), 1, ResultCode::Other(5))),
+            _ => panic!("injected panic"),
+        }
+    }
+
+    fn fault_interp() -> Interp<Fault> {
+        use crate::prelude::*;
+        let mut interp = Interp::new(
+            Fault { calls: 0, trigger: 0, kind: 0 },
+            gen_command!(Fault, [("fault", cmd_fault)], []),
+            false,
+            "fault",
+        );
+        interp
+            .eval(
+                "
+                proc leaf {n} { fault; return $n }
+                proc mid {n} {
+                    set sum 0
+                    foreach i {1 2 3} { incr sum [leaf $i]; fault }
+                    catch { leaf $n }
+                    fault
+                    return $sum
+                }
+                proc top {} {
+                    for {set i 0} {$i < 3} {incr i} { mid $i; fault }
+                    while 1 { fault; break }
+                    leaf [mid x]
+                }",
+            )
+            .unwrap();
+        interp
+    }
+
+    #[test]
+    fn test_scope_balance_under_faults() {
+        let mut interp = fault_interp();
+        interp.set_catch_panics(true);
+        let mut rng = Rng::new();
+        rng.seed(3899);
+
+        for _ in 0..500 {
+            interp.context.calls = 0;
+            interp.context.trigger = 1 + (rng.next_float() * 50.0) as usize;
+            interp.context.kind = (rng.next_float() * 6.0) as usize;
+
+            let _ = interp.eval("top");
+            assert_eq!(interp.scope_level(), 0);
+            assert_eq!(interp.num_levels, 0);
+            assert!(!interp.is_poisoned());
         }
+    }
 
-        // NEXT, return the computed result.
-        // Note: no need for special handling for return, break, continue;
-        // interp.eval() returns only Ok or a real error.
-        result
+    #[test]
+    fn test_scope_balance_after_uncaught_panic() {
+        let mut interp = fault_interp();
+
+        for trigger in 1..30 {
+            interp.context.calls = 0;
+            interp.context.trigger = trigger;
+            interp.context.kind = 5;
+
+            let caught =
+                std::panic::catch_unwind(AssertUnwindSafe(|| interp.eval("top")));
+            assert_eq!(interp.scope_level(), 0);
+            if caught.is_err() {
+                assert!(interp.is_poisoned());
+                interp.recover();
+            }
+            assert_eq!(interp.eval("top").unwrap().as_str(), "6");
+        }
     }
 
-    // Outputs the wrong # args message for the proc.  The name is passed in
-    // because it can be changed via the `rename` command.
-    fn wrong_num_args(&self, name: &Value) -> MoltResult {
-        let mut msg = String::new();
-        msg.push_str("wrong # args: should be \"");
-        msg.push_str(name.as_str());
+    fn cmd_cancel(interp: &mut Interp<()>, _argv: &[Value]) -> MoltResult {
+        interp.cancel_handle().cancel();
+        molt_ok!()
+    }
 
-        for (i, arg) in self.parms.iter().enumerate() {
-            msg.push(' ');
+    fn cancel_interp() -> Interp<()> {
+        use crate::prelude::*;
+        Interp::new((), gen_command!((), [("cancel", cmd_cancel)], []), false, "cancel")
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn test_cancel_from_thread() {
+        let mut interp = cancel_interp();
+        let handle = interp.cancel_handle();
+
+        let canceler = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            handle.cancel();
+        });
+
+        let exception = interp.eval("for {set i 0} {1} {incr i} {}").unwrap_err();
+        canceler.join().unwrap();
+        assert_eq!(exception.code(), ResultCode::Cancelled);
+        assert_eq!(exception.value().as_str(), "evaluation canceled");
+        assert_eq!(exception.error_code().as_str(), "CANCELED");
+        assert!(interp.scalar("i").unwrap().as_int().unwrap() > 0);
+
+        // The next top-level evaluation starts afresh.
+        assert_eq!(interp.eval("set i done").unwrap().as_str(), "done");
+    }
+
+    #[test]
+    fn test_cancel_catch() {
+        let mut interp = cancel_interp();
+
+        // By default, catch catches the error, and the script carries on.
+        assert_eq!(
+            interp
+                .eval("set r [catch {cancel; set x 1} msg]; list $r $msg $errorCode [info exists x]")
+                .unwrap()
+                .as_str(),
+            "1 {evaluation canceled} CANCELED 0"
+        );
+        assert_eq!(
+            interp
+                .eval("catch {expr {[cancel] + 1}} msg; set msg")
+                .unwrap()
+                .as_str(),
+            "evaluation canceled"
+        );
+        assert_eq!(
+            interp
+                .eval("catch {cancel; set x 1} msg opts; list [dict get $opts -code] [dict get $opts -errorcode]")
+                .unwrap()
+                .as_str(),
+            "1 CANCELED"
+        );
+
+        // Uncatchable, it ends the script.
+        interp.set_cancel_uncatchable(true);
+        assert!(interp.cancel_uncatchable());
+        let exception = interp.eval("catch {cancel}; set y 2").unwrap_err();
+        assert_eq!(exception.code(), ResultCode::Cancelled);
+        assert_eq!(exception.value().as_str(), "evaluation canceled");
+        assert!(interp.scalar("y").is_err());
+    }
+
+    #[test]
+    fn test_cancel_result_code() {
+        let mut interp = cancel_interp();
+
+        // A cancellation passes through loops and procedures to the top level.
+        let exception = interp
+            .eval("proc spin {} { while 1 { foreach x {1 2} { cancel } } }; spin")
+            .unwrap_err();
+        assert_eq!(exception.code(), ResultCode::Cancelled);
+        assert_eq!(exception.error_code().as_str(), "CANCELED");
+        assert_eq!(
+            interp.last_error_code().map(|code| code.to_string()),
+            Some("CANCELED".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cancel_reset() {
+        let mut interp = cancel_interp();
+        let handle = interp.cancel_handle();
+
+        // Not sticky: a request made between evaluations is cleared.
+        handle.cancel();
+        assert_eq!(interp.eval("set a 1").unwrap().as_str(), "1");
+        assert!(!handle.is_canceled());
+
+        // Sticky: every evaluation fails until the handle is reset, even after a catch.
+        interp.set_sticky_cancel(true);
+        assert!(interp.sticky_cancel());
+        handle.cancel();
+        assert!(interp.eval("set a 2").is_err());
+        assert!(interp.eval("set a 3").is_err());
+        assert_eq!(interp.scalar("a").unwrap().as_str(), "1");
+
+        handle.reset();
+        assert_eq!(
+            interp
+                .eval("catch {cancel} msg; set msg")
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "evaluation canceled"
+        );
+        handle.reset();
+        assert_eq!(interp.eval("set a 4").unwrap().as_str(), "4");
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut interp = Interp::default();
+        assert_eq!(interp.time_limit(), None);
+        assert_eq!(interp.command_limit(), None);
+
+        // A caught limit error doesn't let the script carry on.
+        interp.set_command_limit(Some(10));
+        let exception = interp.eval("catch {while 1 {incr i}}; set done 1").unwrap_err();
+        assert_eq!(exception.error_code().as_str(), "TCL LIMIT COMMANDS");
+        assert!(interp.scalar("done").is_err());
+
+        // The count starts afresh with each evaluation.
+        assert_eq!(interp.eval("set a 1").unwrap().as_str(), "1");
+        interp.set_command_limit(None);
+        assert!(interp.eval("for {set i 0} {$i < 100} {incr i} {}").is_ok());
+
+        // The time limit stops loops with no commands.
+        interp.set_time_limit(Some(Duration::from_millis(10)));
+        let exception = interp.eval("while {1} {}").unwrap_err();
+        assert_eq!(exception.value().as_str(), "time limit exceeded");
+        assert_eq!(exception.error_code().as_str(), "TCL LIMIT TIME");
+        assert_eq!(interp.eval("set a 2").unwrap().as_str(), "2");
+
+        interp.set_time_limit(None);
+        assert_eq!(interp.time_limit(), None);
+    }
+
+    #[test]
+    fn test_eval_file_script_stack() {
+        let dir =
+            std::env::temp_dir().join(format!("molt-info-script-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let outer = dir.join("outer.tcl").to_string_lossy().to_string();
+        let inner = dir.join("inner.tcl").to_string_lossy().to_string();
+        let broken = dir.join("broken.tcl").to_string_lossy().to_string();
+
+        fs::write(
+            &outer,
+            format!(
+                "lappend seen [info script]; source {{{}}}; lappend seen [info script]",
+                inner
+            ),
+        )
+        .unwrap();
+        fs::write(&inner, "lappend seen [info script]").unwrap();
+        fs::write(&broken, "error oops").unwrap();
+
+        let mut interp = Interp::default();
+        assert_eq!(interp.eval("info script").unwrap().as_str(), "");
+
+        interp.eval_file(&outer).unwrap();
+        assert_eq!(
+            interp.scalar("seen").unwrap().as_list().unwrap().as_slice(),
+            &[Value::from(&outer), Value::from(&inner), Value::from(&outer)]
+        );
+        assert_eq!(interp.script_file(), None);
+
+        // The stack is popped on error, too.
+        assert!(interp.eval(&format!("source {{{}}}", broken)).is_err());
+        assert_eq!(interp.script_file(), None);
+
+        let exception = interp
+            .eval_file(&dir.join("nonesuch.tcl").to_string_lossy())
+            .unwrap_err();
+        assert!(exception.value().as_str().starts_with("couldn't read file"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_script_loader() {
+        let mut scripts = HashMap::new();
+        scripts.insert(
+            "main.tcl".to_string(),
+            "lappend seen [info script]; source lib.tcl; lappend seen [info script]"
+                .to_string(),
+        );
+        scripts.insert("lib.tcl".to_string(), "lappend seen [info script]".to_string());
+
+        let mut interp = Interp::default();
+        interp.set_script_loader(Box::new(scripts));
+        interp.eval_file("main.tcl").unwrap();
+        assert_eq!(
+            interp.scalar("seen").unwrap().to_string(),
+            "main.tcl lib.tcl main.tcl"
+        );
+
+        assert_eq!(
+            interp.eval("source other.tcl").unwrap_err().value().as_str(),
+            "couldn't read file \"other.tcl\": no such file"
+        );
+    }
+
+    #[test]
+    fn test_runtime_commands() {
+        // An object command holding its own state, made by a command added at runtime.
+        fn cmd_make(interp: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+            let text = Rc::new(std::cell::RefCell::new(String::new()));
+            let object: CommandFunc<()> = Rc::new(move |_, argv: &[Value]| {
+                crate::check_args(1, argv, 2, 3, "append|get ?text?")?;
+                if argv[1].as_str() == "append" {
+                    text.borrow_mut().push_str(argv[2].as_str());
+                }
+                molt_ok!(text.borrow().clone())
+            });
+            interp.add_command(argv[1].as_str(), object);
+            molt_ok!()
+        }
+
+        let mut interp = Interp::default();
+        interp.add_command("make", Rc::new(cmd_make));
+        interp.eval("make fred; fred append abc; fred append def").unwrap();
+        assert_eq!(interp.eval("fred get").unwrap().as_str(), "abcdef");
+        assert_eq!(interp.command_type("fred").unwrap().as_str(), "native");
+        assert_eq!(interp.eval("info commands fred").unwrap().as_str(), "fred");
+
+        // A proc can't take a runtime command's place, nor can one replace `set`.
+        interp.eval("proc fred {args} { return proc }").unwrap();
+        assert_eq!(interp.eval("fred get").unwrap().as_str(), "abcdef");
+        interp.add_command("set", Rc::new(|_, _| molt_ok!("runtime")));
+        assert_eq!(interp.eval("set x 1").unwrap().as_str(), "1");
+
+        // Renaming moves the command; renaming to "" removes it.
+        interp.eval("rename fred barney").unwrap();
+        assert_eq!(interp.eval("barney get").unwrap().as_str(), "abcdef");
+        assert_eq!(interp.eval("fred get").unwrap().as_str(), "proc");
+        interp.eval("rename barney {}").unwrap();
+        assert!(!interp.contains_command("barney"));
+        assert!(interp.remove_command("make"));
+        assert!(!interp.remove_command("make"));
+
+        // Minimal interpreters find runtime commands too.
+        let mut interp = Interp::new((), gen_command_minimal!((), [], []), false, "min");
+        interp.add_command("hello", Rc::new(|_, _| molt_ok!("hi")));
+        assert_eq!(interp.eval("hello").unwrap().as_str(), "hi");
+    }
+
+    #[test]
+    fn test_child_interp() {
+        let mut interp = Interp::default();
+        let safe = interp.create_child(true);
+        let unsafe_child = interp.create_child(false);
+        assert_eq!(interp.child_names(), vec![safe.clone(), unsafe_child.clone()]);
+        assert!(interp.child(&safe).unwrap().is_safe());
+        assert!(!interp.child(&unsafe_child).unwrap().is_safe());
+
+        // A safe interpreter's children are safe.
+        let child = interp.child(&safe).unwrap();
+        let grandchild = child.create_child(false);
+        assert!(child.child(&grandchild).unwrap().is_safe());
+
+        // Aliases to the parent work only while the parent evaluates in the child.
+        interp
+            .eval("proc log {msg} { global logged; lappend logged $msg }")
+            .unwrap();
+        interp.eval(&format!("interp alias {} log {{}} log", safe)).unwrap();
+        interp.eval_in_child(&safe, &Value::from("log hello")).unwrap();
+        assert_eq!(interp.scalar("logged").unwrap().as_str(), "hello");
+        assert!(interp.child(&safe).unwrap().eval("log hello").is_err());
+
+        assert!(interp.delete_child(&safe));
+        assert!(interp.delete_child(&unsafe_child));
+        assert!(!interp.delete_child(&unsafe_child));
+
+        // A child deleted by its own alias is deleted once its script is done.
+        let child = interp.create_child(false);
+        interp
+            .eval(&format!("interp alias {0} quit {{}} interp delete {0}", child))
+            .unwrap();
+        let result = interp.eval_in_child(&child, &Value::from("quit; set x 1"));
+        assert_eq!(result.unwrap().as_str(), "1");
+        assert!(interp.child(&child).is_none());
+        assert!(!interp.delete_child(&child));
+    }
+
+    #[test]
+    fn test_source_file_with_progress() {
+        let dir =
+            std::env::temp_dir().join(format!("molt-progress-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.tcl").to_string_lossy().to_string();
+
+        // Five top-level commands; the proc and loop bodies' commands don't count.
+        fs::write(
+            &path,
+            "set a 1\nproc f {} {\n    set x 1\n    set y 2\n}\n\
+             foreach i {1 2 3} { f }\nset b 2; set c 3\n",
+        )
+        .unwrap();
+
+        let mut interp = Interp::default();
+        let mut calls = Vec::new();
+        interp
+            .source_file_with_progress(&path, &mut |done, total| {
+                calls.push((done, total));
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(calls, vec![(1, 5), (2, 5), (3, 5), (4, 5), (5, 5)]);
+        assert_eq!(interp.scalar("c").unwrap().as_str(), "3");
+
+        // Aborting mid-file leaves the later globals unset.
+        let mut interp = Interp::default();
+        let exception = interp
+            .source_file_with_progress(&path, &mut |done, _| {
+                if done == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap_err();
+        assert_eq!(exception.value().as_str(), "evaluation aborted");
+        assert_eq!(exception.error_code().as_str(), "ABORTED");
+        assert_eq!(interp.scalar("a").unwrap().as_str(), "1");
+        assert!(interp.scalar("b").is_err());
+        assert!(interp.scalar("c").is_err());
+        assert_eq!(interp.script_file(), None);
+        assert_eq!(interp.last_error_code().unwrap().as_str(), "ABORTED");
+
+        // Errors thrown by the script are reported as usual.
+        let mut interp = Interp::default();
+        let mut calls = 0;
+        let exception = interp
+            .eval_script_with_progress(
+                &Value::from("set a 1; error oops; set b 2"),
+                &mut |_, _| {
+                    calls += 1;
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap_err();
+        assert_eq!(exception.value().as_str(), "oops");
+        assert_eq!(calls, 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_command_names_order() {
+        let mut interp = Interp::default();
+        interp
+            .eval("proc zeta {} {}; proc alpha {} {}; proc mid {} {}")
+            .unwrap();
+
+        // Procs are sorted.
+        assert_eq!(
+            interp.proc_names(),
+            vec![Value::from("alpha"), Value::from("mid"), Value::from("zeta")]
+        );
+        assert!(interp.help_message(true).ends_with("procedure:\n  alpha, mid, zeta"));
+
+        // All commands are sorted together, whatever their kind.
+        let names = interp.command_names();
+        let mut sorted = names.clone();
+        sorted.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(names, sorted);
+        assert!(names.contains(&Value::from("alpha")));
+
+        // A proc that shadows a native command is listed once.
+        interp.eval("proc incr {x} {}").unwrap();
+        let names = interp.command_names();
+        assert_eq!(names.iter().filter(|name| name.as_str() == "incr").count(), 1);
+        assert_eq!(names.len(), sorted.len());
+    }
+
+    #[test]
+    fn test_dict_getdef_keeps_rep() {
+        let mut interp = Interp::default();
+        let mut dict = dict_new();
+        dict.insert(Value::from("a"), Value::from("1"));
+        let value = Value::from(dict);
+        let rep = value.as_dict().unwrap();
+        interp.set_scalar("d", value.clone()).unwrap();
+
+        assert_eq!(interp.eval("dict getdef $d a x"), Ok(Value::from("1")));
+        assert_eq!(interp.eval("dict getdef $d b x"), Ok(Value::from("x")));
+        assert_eq!(interp.eval("dict getdef $d b c x"), Ok(Value::from("x")));
+
+        // The dict is neither copied nor converted to a string.
+        assert!(std::rc::Rc::ptr_eq(&value.as_dict().unwrap(), &rep));
+        assert_eq!(value.try_as_str(), None);
+        assert_eq!(interp.scalar("d").unwrap().as_dict().unwrap().len(), 1);
+    }
 
-            // "args" has special meaning only in the last place.
-            if arg.as_str() == "args" && i == self.parms.len() - 1 {
-                msg.push_str("?arg ...?");
-                break;
-            }
+    #[test]
+    fn test_completeness() {
+        let mut interp = Interp::default();
 
-            let vec = arg.as_list().expect("error in proc arglist validation!");
+        assert_eq!(interp.completeness("abc"), Completeness::Complete);
+        assert_eq!(interp.completeness("set a }"), Completeness::Complete);
 
-            if vec.len() == 1 {
-                msg.push_str(vec[0].as_str());
-            } else {
-                msg.push('?');
-                msg.push_str(vec[0].as_str());
-                msg.push('?');
-            }
-        }
-        msg.push_str("\"");
+        assert_eq!(
+            interp.completeness("a {bc"),
+            Completeness::Incomplete { kind: Delimiter::Brace, line: 1 }
+        );
+        assert_eq!(
+            interp.completeness("a\nb [c\nd"),
+            Completeness::Incomplete { kind: Delimiter::Bracket, line: 2 }
+        );
+        assert_eq!(
+            interp.completeness("a [b\n\n\"c"),
+            Completeness::Incomplete { kind: Delimiter::Quote, line: 3 }
+        );
+        assert_eq!(
+            interp.completeness("a [b {c"),
+            Completeness::Incomplete { kind: Delimiter::Brace, line: 1 }
+        );
 
-        molt_err!(&msg)
+        // No amount of input fixes these.
+        assert_eq!(
+            interp.completeness("a {b}c"),
+            Completeness::Malformed {
+                message: "extra characters after close-brace".into()
+            }
+        );
+        assert_eq!(
+            interp.completeness("a \"b\"c\nd"),
+            Completeness::Malformed {
+                message: "extra characters after close-quote".into()
+            }
+        );
+        assert_eq!(
+            interp.completeness("set x $a(b"),
+            Completeness::Malformed { message: "missing )".into() }
+        );
+        assert_eq!(
+            interp.completeness("a [b {c}d"),
+            Completeness::Malformed {
+                message: "extra characters after close-brace".into()
+            }
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_expr() {
+        let mut interp = Interp::default();
+        assert_eq!(interp.expr(&Value::from("1 + 2")), Ok(Value::from(3)));
+        assert_eq!(
+            interp.expr(&Value::from("a + b")),
+            Err(Exception::molt_err(Value::from("unknown math function \"a\"")))
+        );
+    }
 
     #[test]
-    fn test_new() {
-        let interp = Interp::default();
+    fn test_eval_other_code() {
+        let mut interp = Interp::default();
 
-        // Interpreter is not empty
-        assert!(!interp.command_names().is_empty());
+        assert_eq!(
+            interp.eval("return -code 5 x").unwrap_err().value(),
+            Value::from("command returned bad code: 5")
+        );
+        assert_eq!(
+            interp
+                .eval("proc stop {} { return -code 5 x }; stop")
+                .unwrap_err()
+                .value(),
+            Value::from("command returned bad code: 5")
+        );
+        assert_eq!(interp.eval("catch stop"), Ok(Value::from(5)));
+    }
 
-        // Note: in theory, we should test here that the normal set of commands is present.
-        // In fact, that should be tested by the `molt test` suite.
+    #[test]
+    fn test_rand_per_interp() {
+        let mut a = Interp::default();
+        let mut b = Interp::default();
+        let script = "list [expr {srand(12345)}] [expr {rand()}] [expr {rand()}]";
+
+        let seq = a.eval(script).unwrap();
+        assert_eq!(b.eval(script), Ok(seq.clone()));
+
+        // Advancing one interpreter's sequence doesn't affect the other's.
+        let next = a.eval("expr {rand()}").unwrap();
+        a.eval("expr {rand()}").unwrap();
+        assert_eq!(b.eval("expr {rand()}"), Ok(next));
     }
 
     #[test]
-    fn test_eval() {
-        let mut interp = Interp::default();
+    fn test_empty() {
+        let mut interp = Interp::empty((), "empty");
+        assert!(interp.command_names().is_empty());
+        assert!(interp.command_type("set").is_err());
+        assert_eq!(
+            interp.eval("set x 1").unwrap_err().value(),
+            Value::from("unknown command \"set\"")
+        );
+        assert_eq!(interp.eval(""), Ok(Value::empty()));
+    }
 
-        assert_eq!(interp.eval("set a 1"), Ok(Value::from("1")));
-        assert!(ex_match(&interp.eval("error 2"), Exception::molt_err(Value::from("2"))));
-        assert_eq!(interp.eval("return 3"), Ok(Value::from("3")));
-        assert!(ex_match(
-            &interp.eval("break"),
-            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
-        ));
-        assert!(ex_match(
-            &interp.eval("continue"),
-            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
-        ));
+    fn cmd_double(_: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+        molt_ok!(argv[1].as_int()? * 2)
     }
 
-    // Shows that the result is matches the given exception.  Ignores the exception's
-    // ErrorData, if any.
-    fn ex_match(r: &MoltResult, expected: Exception) -> bool {
-        // FIRST, if the results are of different types, there's no match.
-        if let Err(e) = r {
-            e.code() == expected.code() && e.value() == expected.value()
-        } else {
-            false
-        }
+    #[test]
+    fn test_gen_command_minimal() {
+        let mut interp = Interp::new(
+            (),
+            gen_command_minimal!(
+                (),
+                [(_SET, cmd_set)],
+                [("double", cmd_double, ArgSpec { min: 2, max: 2, usage: "n" })]
+            ),
+            false,
+            "minimal",
+        );
+        assert_eq!(
+            interp.command_names(),
+            vec![Value::from("double"), Value::from("set")]
+        );
+        assert_eq!(interp.eval("set x [double 21]"), Ok(Value::from(42)));
+        assert_eq!(
+            interp.eval("double").unwrap_err().value(),
+            Value::from("wrong # args: should be \"double n\"")
+        );
+        assert_eq!(
+            interp.eval("puts hi").unwrap_err().value(),
+            Value::from("unknown command \"puts\"")
+        );
+        assert!(interp.command_type("help").is_err());
+
+        // Procs are only dispatched when enabled.
+        let mut interp = Interp::new(
+            (),
+            gen_command_minimal!((), [(_PROC, cmd_proc)], [], procs = true),
+            false,
+            "minimal",
+        );
+        interp.eval("proc one {} { proc two {} {} }").unwrap();
+        assert_eq!(interp.eval("one"), Ok(Value::empty()));
+        assert_eq!(interp.command_type("two"), Ok(Value::from("proc")));
+
+        let mut interp = Interp::new(
+            (),
+            gen_command_minimal!((), [(_PROC, cmd_proc)], []),
+            false,
+            "minimal",
+        );
+        interp.eval("proc one {} { proc two {} {} }").unwrap();
+        assert!(interp.eval("one").is_err());
     }
 
     #[test]
-    fn test_eval_value() {
+    fn test_unicode_escapes() {
         let mut interp = Interp::default();
-
-        assert_eq!(interp.eval_value(&Value::from("set a 1")), Ok(Value::from("1")));
-        assert!(ex_match(
-            &interp.eval_value(&Value::from("error 2")),
-            Exception::molt_err(Value::from("2"))
-        ));
-        assert_eq!(interp.eval_value(&Value::from("return 3")), Ok(Value::from("3")));
-        assert!(ex_match(
-            &interp.eval_value(&Value::from("break")),
-            Exception::molt_err(Value::from("invoked \"break\" outside of a loop"))
-        ));
-        assert!(ex_match(
-            &interp.eval_value(&Value::from("continue")),
-            Exception::molt_err(Value::from("invoked \"continue\" outside of a loop"))
-        ));
+        assert_eq!(interp.eval(r#"set x "\u263A""#), Ok(Value::from("\u{263A}")));
+        assert_eq!(interp.eval(r#"set x "\u{1F600}""#), Ok(Value::from("\u{1F600}")));
+
+        #[cfg(feature = "std_buff")]
+        {
+            interp.std_buff.clear();
+            interp.eval(r#"puts "\u263A""#).unwrap();
+            assert_eq!(interp.std_buff, vec![Ok(Value::from("\u{263A}"))]);
+        }
     }
 
     #[test]
-    fn test_complete() {
+    fn test_output_handlers() {
+        use std::cell::RefCell;
+
+        let output = Rc::new(RefCell::new(Vec::new()));
         let mut interp = Interp::default();
+        let sink = output.clone();
+        interp.set_stdout_handler(move |text| {
+            sink.borrow_mut().push(format!("out:{}", text))
+        });
+        let sink = output.clone();
+        interp.set_stderr_handler(move |text| {
+            sink.borrow_mut().push(format!("err:{}", text))
+        });
 
-        assert!(interp.complete("abc"));
-        assert!(interp.complete("a {bc} [def] \"ghi\" xyz"));
+        interp
+            .eval("puts a; puts stderr b; puts -nonewline stdout c")
+            .unwrap();
+        assert_eq!(*output.borrow(), vec!["out:a\n", "err:b\n", "out:c"]);
 
-        assert!(!interp.complete("a {bc"));
-        assert!(!interp.complete("a [bc"));
-        assert!(!interp.complete("a \"bc"));
+        interp.remove_output_handlers();
+        interp.eval("puts -nonewline {}").unwrap();
+        assert_eq!(output.borrow().len(), 3);
+
+        #[cfg(feature = "std_buff")]
+        {
+            assert_eq!(interp.std_buff, vec![Ok(Value::from(""))]);
+        }
     }
 
     #[test]
-    fn test_expr() {
+    fn test_expr_value() {
         let mut interp = Interp::default();
-        assert_eq!(interp.expr(&Value::from("1 + 2")), Ok(Value::from(3)));
+
+        let datum = interp.expr_value(&Value::from("~2 * 3")).unwrap();
+        assert_eq!(datum.datum_type(), DatumType::Int);
+        assert_eq!(datum.as_int(), Some(-9));
+        assert_eq!(datum.as_float(), None);
+
+        let datum = interp.expr_value(&Value::from("2.0")).unwrap();
+        assert_eq!(datum.datum_type(), DatumType::Float);
+        assert_eq!(datum.as_float(), Some(2.0));
+
+        let datum = interp.expr_value(&Value::from("\"abc\"")).unwrap();
+        assert_eq!(datum.datum_type(), DatumType::String);
+        assert_eq!(datum.as_str(), Some("abc"));
+        assert_eq!(Value::from(datum).as_str(), "abc");
+
         assert_eq!(
-            interp.expr(&Value::from("a + b")),
-            Err(Exception::molt_err(Value::from("unknown math function \"a\"")))
+            interp.expr_value(&Value::from("1 << 64")),
+            Err(Exception::molt_err(Value::from("integer overflow")))
         );
     }
 
@@ -2459,4 +7216,304 @@ mod tests {
     fn dummy_cmd(_: &mut Interp<()>, _: &[Value]) -> MoltResult {
         molt_err!("Not really meant to be called")
     }
+
+    #[test]
+    fn test_embedded_arg_spec() {
+        use crate::prelude::*;
+        let mut interp = Interp::new(
+            (),
+            gen_command!(
+                (),
+                // native commands
+                [],
+                // embedded commands
+                [
+                    (
+                        "double",
+                        double_cmd,
+                        ArgSpec { min: 2, max: 2, usage: "x" },
+                        "double x"
+                    ),
+                    ("count", count_cmd, ArgSpec { min: 1, max: 0, usage: "?arg ...?" }),
+                    ("dummy", " ", dummy_cmd, ""),
+                ],
+            ),
+            false,
+            "test",
+        );
+
+        assert_eq!(interp.eval("double 4"), Ok(Value::from(8)));
+        assert!(ex_match(
+            &interp.eval("double"),
+            Exception::molt_err(Value::from("wrong # args: should be \"double x\""))
+        ));
+        assert!(ex_match(
+            &interp.eval("double 1 2"),
+            Exception::molt_err(Value::from("wrong # args: should be \"double x\""))
+        ));
+        assert_eq!(interp.eval("count a b c"), Ok(Value::from(3)));
+
+        // The old form does no arity check.
+        assert!(ex_match(
+            &interp.eval("dummy a b"),
+            Exception::molt_err(Value::from("Not really meant to be called"))
+        ));
+
+        assert_eq!(
+            interp.eval("help"),
+            Ok(Value::from(
+                "usage of test:\n  double x  double x\n  count ?arg ...?\n  dummy   \n  help  [-all]"
+            ))
+        );
+        assert_eq!(interp.command_type("double"), Ok(Value::from("test")));
+    }
+
+    #[test]
+    fn test_command_info() {
+        use crate::prelude::*;
+        fn cmd_square(_: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+            let x = argv[1].as_int()?;
+            molt_ok!(x * x)
+        }
+        fn cmd_about(_: &mut Interp<()>, _: &[Value]) -> MoltResult {
+            molt_ok!()
+        }
+
+        let mut interp = Interp::new(
+            (),
+            gen_command!(
+                (),
+                [],
+                [
+                    (
+                        "square",
+                        cmd_square,
+                        ArgSpec { min: 2, max: 2, usage: "x" },
+                        "square a number"
+                    ),
+                    ("about", "  ", cmd_about, "show the version"),
+                ],
+            ),
+            false,
+            "test",
+        );
+        interp.eval("proc greet {name {greeting hello}} {}").unwrap();
+
+        assert_eq!(
+            interp.command_info("square"),
+            Some(CommandInfo {
+                kind: CommandType::Embedded,
+                help: Some("square a number"),
+                usage: Some("x".into()),
+            })
+        );
+        assert_eq!(
+            interp.command_info("about"),
+            Some(CommandInfo {
+                kind: CommandType::Embedded,
+                help: Some("show the version"),
+                usage: None,
+            })
+        );
+        assert_eq!(
+            interp.command_info("greet"),
+            Some(CommandInfo {
+                kind: CommandType::Proc,
+                help: None,
+                usage: Some("name greeting".into()),
+            })
+        );
+        assert_eq!(
+            interp.command_info("set"),
+            Some(CommandInfo { kind: CommandType::Native, help: None, usage: None })
+        );
+        assert_eq!(interp.command_info("nonesuch"), None);
+
+        let all = interp.commands_info();
+        assert_eq!(all.len(), interp.command_names().len());
+        let names: Vec<&str> = all.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names[names.len() - 3..], ["square", "about", "greet"]);
+
+        assert_eq!(
+            interp.eval("help"),
+            Ok(Value::from(
+                "usage of test:\n  square x  square a number\n  about    show the version\n  help  [-all]"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unknown_command_suggestions() {
+        use crate::prelude::*;
+        fn cmd_tool(interp: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+            gen_subcommand!(
+                (),
+                1,
+                [
+                    ("start", " ", dummy_cmd, "tool start"),
+                    ("stop", "  ", dummy_cmd, "tool stop"),
+                ],
+            )(interp, argv)
+        }
+        let mut interp = Interp::new(
+            (),
+            gen_command!((), [], [("tool", " ", cmd_tool, "tool start|stop")]),
+            false,
+            "test",
+        );
+        interp.eval("proc greet {} {}").unwrap();
+
+        // Native, embedded, and proc names are all candidates.
+        assert!(ex_match(
+            &interp.eval("stirng"),
+            Exception::molt_err(Value::from(
+                "unknown command \"stirng\", did you mean: string? (run help -all for the full list)"
+            ))
+        ));
+        assert!(ex_match(
+            &interp.eval("tol"),
+            Exception::molt_err(Value::from(
+                "unknown command \"tol\", did you mean: tool, for, eval? (run help -all for the full list)"
+            ))
+        ));
+        assert!(ex_match(
+            &interp.eval("greeet"),
+            Exception::molt_err(Value::from(
+                "unknown command \"greeet\", did you mean: greet, lrepeat? (run help -all for the full list)"
+            ))
+        ));
+
+        // Nothing within three edits.
+        assert!(ex_match(
+            &interp.eval("xyzzyplugh"),
+            Exception::molt_err(Value::from(
+                "unknown command \"xyzzyplugh\" (run help -all for the full list)"
+            ))
+        ));
+
+        // Subcommands.
+        assert!(ex_match(
+            &interp.eval("tool stpo"),
+            Exception::molt_err(Value::from(
+                "unknown subcommand in \"tool stpo\", did you mean: stop, start? (run tool -help for usage)"
+            ))
+        ));
+        assert!(ex_match(
+            &interp.eval("tool restart-everything"),
+            Exception::molt_err(Value::from(
+                "unknown subcommand in \"tool restart-everything\" (run tool -help for usage)"
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_proc_bad_call_pops_scope() {
+        let mut interp = Interp::default();
+        interp.eval("proc p {a {b 2}} { return $a }").unwrap();
+        let level = interp.scope_level();
+
+        assert!(interp.eval("p").is_err());
+        assert!(interp.eval("p 1 2 3").is_err());
+        assert_eq!(interp.scope_level(), level);
+        assert_eq!(interp.eval("p 1"), Ok(Value::from(1)));
+
+        // Top-level variables are globals again, i.e., we're back at the global scope.
+        interp.eval("set g 1; proc r {} { global g; return $g }").unwrap();
+        assert_eq!(interp.eval("r"), Ok(Value::from(1)));
+    }
+
+    #[test]
+    fn test_proc_removed_while_executing() {
+        let mut interp = Interp::default();
+        let level = interp.scope_level();
+
+        // The proc finishes the call that removed it.
+        interp
+            .eval("proc once {} { rename once \"\"; set x done; return $x }")
+            .unwrap();
+        assert_eq!(interp.eval("once"), Ok(Value::from("done")));
+        assert!(!interp.has_proc("once"));
+        assert!(interp.eval("once").is_err());
+
+        // Removal at the bottom of a deep recursion: every level still returns.
+        interp
+            .eval(
+                "proc down {n} {
+                    if {$n == 0} { rename down \"\"; return 0 }
+                    expr {[down [expr {$n - 1}]] + 1}
+                }",
+            )
+            .unwrap();
+        assert_eq!(interp.eval("down 50"), Ok(Value::from(50)));
+        assert!(!interp.has_proc("down"));
+        assert_eq!(interp.scope_level(), level);
+
+        // Renaming itself and recursing at the new name.
+        interp
+            .eval(
+                "proc fact {n} {
+                    catch { rename fact fact2 }
+                    if {$n <= 1} { return 1 }
+                    expr {$n * [fact2 [expr {$n - 1}]]}
+                }",
+            )
+            .unwrap();
+        assert_eq!(interp.eval("fact 5"), Ok(Value::from(120)));
+        assert_eq!(interp.scope_level(), level);
+        assert!(!interp.has_proc("fact"));
+        assert_eq!(interp.eval("fact2 4"), Ok(Value::from(24)));
+    }
+
+    #[test]
+    fn test_command_sets() {
+        use crate::prelude::*;
+
+        // Every standard command is in exactly one set.
+        let mut all: Vec<&str> = CommandSet::ALL
+            .iter()
+            .flat_map(|set| set.commands())
+            .copied()
+            .collect();
+        all.sort_unstable();
+        let mut standard: Vec<Value> =
+            Interp::new((), gen_command!((), [], []), false, "test").command_names();
+        standard.push(Value::from("help"));
+        standard.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(all, standard.iter().map(|v| v.as_str()).collect::<Vec<_>>());
+
+        let mut interp =
+            Interp::with_command_sets(&[CommandSet::Core, CommandSet::Lists]);
+        assert_eq!(interp.eval("set x [list a b]; llength $x"), Ok(Value::from(2)));
+        assert!(interp.eval("string length $x").is_err());
+        assert!(interp.eval("help").is_err());
+        assert_eq!(
+            interp.command_type("expr").unwrap_err().value().as_str(),
+            "\"expr\" isn't a command"
+        );
+        assert!(!interp.command_names().contains(&Value::from("puts")));
+        assert!(!interp.command_names().contains(&Value::from("source")));
+        assert!(!interp.var_exists(&Value::from("env")));
+
+        // A proc can stand in for a hidden command.
+        interp.eval("proc puts {msg} { return \"puts: $msg\" }").unwrap();
+        assert_eq!(interp.eval("puts hi"), Ok(Value::from("puts: hi")));
+        assert_eq!(interp.command_type("puts"), Ok(Value::from("proc")));
+
+        // Extra native commands aren't affected.
+        let mut interp = InterpBuilder::new(())
+            .command_sets(&[CommandSet::Math])
+            .commands(gen_command!((), [("double", double_cmd)], []))
+            .build();
+        assert_eq!(interp.eval("double [expr {2 + 3}]"), Ok(Value::from(10)));
+        assert!(interp.eval("set x 1").is_err());
+        assert_eq!(interp.native_command_names(), "expr, double");
+    }
+
+    fn double_cmd(_: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+        molt_ok!(argv[1].as_int()? * 2)
+    }
+
+    fn count_cmd(_: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+        molt_ok!(argv.len() as MoltInt - 1)
+    }
 }
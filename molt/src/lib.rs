@@ -23,23 +23,52 @@
 // #![doc(html_root_url = "https://docs.rs/molt/0.3.0")]
 // #![doc(html_logo_url = "https://github.com/wduquette/molt/raw/master/MoltLogo.png")]
 
+pub use crate::expr::{Datum, DatumType};
 pub use crate::types::*;
+pub mod cancel;
 mod commands;
+mod context;
 pub mod dict;
 mod eval_ptr;
 mod expr;
+mod format;
 pub mod interp;
 mod list;
 pub mod prelude;
 mod tokenizer;
 #[macro_use]
 mod macros;
+mod binary;
+mod channel;
+mod child;
+mod clock;
+mod encoding;
+mod event;
+#[cfg(feature = "exec")]
+mod exec;
+#[cfg(feature = "file-io")]
+mod file;
+mod loader;
+mod namespace;
+mod object;
+mod package;
 mod parser;
+mod profile;
+#[cfg(feature = "regexp")]
+pub mod regexp;
+mod rng;
+mod scan;
 mod scope;
 pub mod test_harness;
+pub mod trace;
 pub mod types;
-mod util;
+pub mod util;
 pub mod value;
+#[cfg(feature = "threads")]
+pub mod worker;
+
+#[doc(hidden)]
+pub use crate::util::{did_you_mean, similar_names};
 
 /// This function is used in command functions to check whether the command's argument
 /// list is of a proper size for the given command.  If it is, `check_args` returns
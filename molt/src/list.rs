@@ -4,6 +4,7 @@ use crate::molt_err;
 use crate::tokenizer::Tokenizer;
 use crate::types::*;
 use crate::value::Value;
+use std::cmp::Ordering;
 
 //--------------------------------------------------------------------------
 // List Parsing
@@ -214,14 +215,19 @@ fn escape_item(hash: bool, item: &str) -> String {
     }
 
     for ch in item.chars() {
-        if ch.is_whitespace() {
-            word.push('\\');
-            word.push(ch);
-            continue;
-        }
-
         match ch {
-            '{' | ';' | '$' | '[' | ']' | '\\' => {
+            // Control characters are written as escapes, so that, e.g., a newline
+            // isn't read back as a backslash-newline continuation.
+            '\n' => word.push_str("\\n"),
+            '\r' => word.push_str("\\r"),
+            '\t' => word.push_str("\\t"),
+            '\x0B' => word.push_str("\\v"),
+            '\x0C' => word.push_str("\\f"),
+            '{' | '}' | ';' | '$' | '[' | ']' | '\\' | '"' => {
+                word.push('\\');
+                word.push(ch);
+            }
+            _ if ch.is_whitespace() => {
                 word.push('\\');
                 word.push(ch);
             }
@@ -245,8 +251,13 @@ fn get_mode(word: &str) -> Mode {
         return Mode::Brace;
     }
 
-    // NEXT, inspect the content.
-    let mut mode = Mode::AsIs;
+    // NEXT, inspect the content.  A leading brace or quote would be read back as the
+    // start of a braced or quoted item, so the item must at least be braced.
+    let mut mode = if word.starts_with('{') || word.starts_with('"') {
+        Mode::Brace
+    } else {
+        Mode::AsIs
+    };
     let mut brace_count = 0;
 
     let mut iter = word.chars().peekable();
@@ -261,12 +272,18 @@ fn get_mode(word: &str) -> Mode {
                 mode = Mode::Brace;
             }
             '{' => brace_count += 1,
-            '}' => brace_count -= 1,
-            '\\' => {
-                if iter.peek() == Some(&'\n') {
+            '}' => {
+                // A close brace before its open brace would end a braced item early.
+                brace_count -= 1;
+                if brace_count < 0 {
                     return Mode::Escape;
-                } else {
-                    mode = Mode::Brace;
+                }
+            }
+            '\\' => {
+                // A trailing backslash would escape the closing brace.
+                match iter.peek() {
+                    Some('\n') | None => return Mode::Escape,
+                    _ => mode = Mode::Brace,
                 }
             }
             _ => (),
@@ -280,6 +297,175 @@ fn get_mode(word: &str) -> Mode {
     }
 }
 
+//--------------------------------------------------------------------------
+// List Sorting
+
+/// How `lsort` compares the elements of a list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum SortMode {
+    /// By Unicode code point.
+    #[default]
+    Ascii,
+    /// Case-insensitively, with embedded numbers compared as integers; see
+    /// [`dictionary_compare`].
+    Dictionary,
+    /// As integers.
+    Integer,
+    /// As floating-point numbers.
+    Real,
+}
+
+/// How `lsort` sorts a list.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SortOptions {
+    pub mode: SortMode,
+    pub decreasing: bool,
+    /// Keep only the last of each run of elements that compare equal.
+    pub unique: bool,
+}
+
+/// An element's sort key, converted once before sorting so that the comparisons don't
+/// convert or shimmer the elements again.
+enum SortKey<'a> {
+    Str(&'a str),
+    Int(MoltInt),
+    Real(MoltFloat),
+}
+
+impl<'a> SortKey<'a> {
+    fn new(value: &'a Value, mode: SortMode) -> Result<Self, Exception> {
+        Ok(match mode {
+            SortMode::Ascii | SortMode::Dictionary => SortKey::Str(value.as_str()),
+            SortMode::Integer => SortKey::Int(value.as_int()?),
+            SortMode::Real => SortKey::Real(value.as_float()?),
+        })
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (SortKey::Str(a), SortKey::Str(b)) => a.cmp(b),
+            (SortKey::Int(a), SortKey::Int(b)) => a.cmp(b),
+            (SortKey::Real(a), SortKey::Real(b)) => {
+                a.partial_cmp(b).unwrap_or(Ordering::Equal)
+            }
+            _ => unreachable!("sort keys of different modes"),
+        }
+    }
+}
+
+/// Sorts the items by the corresponding keys, which are usually the items themselves;
+/// the sort is stable.  Returns an error if a key can't be converted as the mode
+/// requires, e.g., to an integer.
+pub(crate) fn sort_list(
+    items: &[Value],
+    keys: &[Value],
+    options: SortOptions,
+) -> Result<MoltList, Exception> {
+    assert_eq!(items.len(), keys.len());
+
+    let keys = keys
+        .iter()
+        .map(|key| SortKey::new(key, options.mode))
+        .collect::<Result<Vec<_>, _>>()?;
+    let compare = |a: &SortKey, b: &SortKey| {
+        let order = match (options.mode, a, b) {
+            (SortMode::Dictionary, SortKey::Str(a), SortKey::Str(b)) => {
+                dictionary_compare(a, b)
+            }
+            _ => a.compare(b),
+        };
+        if options.decreasing {
+            order.reverse()
+        } else {
+            order
+        }
+    };
+
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| compare(&keys[a], &keys[b]));
+
+    if options.unique {
+        let mut kept = Vec::with_capacity(order.len());
+        for (i, &item) in order.iter().enumerate() {
+            match order.get(i + 1) {
+                Some(&next) if compare(&keys[item], &keys[next]) == Ordering::Equal => (),
+                _ => kept.push(item),
+            }
+        }
+        order = kept;
+    }
+
+    Ok(order.into_iter().map(|i| items[i].clone()).collect())
+}
+
+/// Compares two strings as `lsort -dictionary` does: case-insensitively, except as a
+/// tie-breaker, when upper case comes first; and with embedded runs of digits compared
+/// as integers, so that `x9` comes before `x10`.
+pub(crate) fn dictionary_compare(left: &str, right: &str) -> Ordering {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+    let (mut i, mut j) = (0, 0);
+    let mut tie = Ordering::Equal;
+
+    while i < left.len() && j < right.len() {
+        if left[i].is_ascii_digit() && right[j].is_ascii_digit() {
+            // Compare the numbers by length and then digit by digit, ignoring leading
+            // zeros, except that more leading zeros come later as a tie-breaker.
+            let (li, lzeros) = skip_zeros(&left, i);
+            let (rj, rzeros) = skip_zeros(&right, j);
+            if tie == Ordering::Equal {
+                tie = lzeros.cmp(&rzeros);
+            }
+
+            let lend = digits_end(&left, li);
+            let rend = digits_end(&right, rj);
+            let order = (lend - li)
+                .cmp(&(rend - rj))
+                .then_with(|| left[li..lend].cmp(&right[rj..rend]));
+            if order != Ordering::Equal {
+                return order;
+            }
+
+            i = lend;
+            j = rend;
+            continue;
+        }
+
+        let (lc, rc) = (left[i], right[j]);
+        let order = lc.to_lowercase().cmp(rc.to_lowercase());
+        if order != Ordering::Equal {
+            return order;
+        }
+        if tie == Ordering::Equal && lc != rc {
+            if lc.is_uppercase() && rc.is_lowercase() {
+                tie = Ordering::Less;
+            } else if lc.is_lowercase() && rc.is_uppercase() {
+                tie = Ordering::Greater;
+            }
+        }
+
+        i += 1;
+        j += 1;
+    }
+
+    (left.len() - i).cmp(&(right.len() - j)).then(tie)
+}
+
+/// Skips the leading zeros of the number at `start`, keeping at least one digit; returns
+/// the index of its first significant digit and the number of zeros skipped.
+fn skip_zeros(chars: &[char], start: usize) -> (usize, usize) {
+    let mut i = start;
+    while chars[i] == '0' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) {
+        i += 1;
+    }
+    (i, i - start)
+}
+
+/// Returns the index just past the run of digits at `start`.
+fn digits_end(chars: &[char], start: usize) -> usize {
+    start + chars[start..].iter().take_while(|c| c.is_ascii_digit()).count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -309,6 +495,51 @@ mod tests {
             list_to_string(&[Value::from("{ "), Value::from("abc")]),
             r#"\{\  abc"#
         );
+
+        // Items that can't be braced as-is
+        assert_eq!(list_to_string(&[Value::from("a\\")]), r#"a\\"#);
+        assert_eq!(list_to_string(&[Value::from("}")]), r#"\}"#);
+        assert_eq!(list_to_string(&[Value::from("}{")]), r#"\}\{"#);
+        assert_eq!(list_to_string(&[Value::from("{\n\t")]), r#"\{\n\t"#);
+        assert_eq!(list_to_string(&[Value::from("{\"x\" y")]), r#"\{\"x\"\ y"#);
+
+        // A leading quote or brace
+        assert_eq!(list_to_string(&[Value::from("\"q\"")]), r#"{"q"}"#);
+        assert_eq!(list_to_string(&[Value::from("{a}")]), "{{a}}");
+        assert_eq!(list_to_string(&[Value::from("a\"b")]), "a\"b");
+    }
+
+    #[test]
+    fn test_list_round_trip() {
+        let items = [
+            "",
+            " ",
+            "a b",
+            "\\",
+            "a\\",
+            "\\n",
+            "{",
+            "}",
+            "}{",
+            "{a}",
+            "\"q\"",
+            "a\"b",
+            "$x",
+            "[cmd]",
+            ";",
+            "#",
+            "a\nb",
+            "{\n",
+            "\t\r\x0B\x0C",
+            "\\\n",
+            "é {",
+        ];
+
+        for item in &items {
+            let list = vec![Value::from(*item), Value::from(*item)];
+            let string = list_to_string(&list);
+            assert_eq!(get_list(&string), Ok(list), "item {:?} as {:?}", item, string);
+        }
     }
 
     #[test]
@@ -382,4 +613,49 @@ mod tests {
         // If the list breaks on the semi-colon, the bug still exists.
         assert_eq!(list.len(), 3);
     }
+
+    #[test]
+    fn test_dictionary_compare() {
+        use Ordering::*;
+        assert_eq!(dictionary_compare("abc", "abc"), Equal);
+        assert_eq!(dictionary_compare("abc", "ABD"), Less);
+        assert_eq!(dictionary_compare("Abc", "abc"), Less);
+        assert_eq!(dictionary_compare("abc", "Abc"), Greater);
+        assert_eq!(dictionary_compare("bigBoy", "bigbang"), Greater);
+        assert_eq!(dictionary_compare("x9y", "x10y"), Less);
+        assert_eq!(dictionary_compare("x10", "x9"), Greater);
+        assert_eq!(dictionary_compare("x01", "x1"), Greater);
+        assert_eq!(dictionary_compare("x01", "x2"), Less);
+        assert_eq!(dictionary_compare("ab", "abc"), Less);
+        assert_eq!(dictionary_compare("", "a"), Less);
+    }
+
+    #[test]
+    fn test_sort_list() {
+        let sort = |list: &str, options: SortOptions| {
+            let items = get_list(list).unwrap();
+            sort_list(&items, &items, options).map(|sorted| list_to_string(&sorted))
+        };
+        let mode = |mode| SortOptions { mode, ..SortOptions::default() };
+
+        assert_eq!(sort("b a c", SortOptions::default()), Ok("a b c".into()));
+        assert_eq!(sort("10 9 2", SortOptions::default()), Ok("10 2 9".into()));
+        assert_eq!(sort("10 9 2", mode(SortMode::Integer)), Ok("2 9 10".into()));
+        assert_eq!(sort("1.5 1e0 -2", mode(SortMode::Real)), Ok("-2 1e0 1.5".into()));
+        assert_eq!(sort("x10 X9 x1", mode(SortMode::Dictionary)), Ok("x1 X9 x10".into()));
+        assert!(sort("1 a", mode(SortMode::Integer)).is_err());
+
+        let options = SortOptions {
+            decreasing: true,
+            unique: true,
+            ..mode(SortMode::Integer)
+        };
+        assert_eq!(sort("1 3 2 3 01", options), Ok("3 2 01".into()));
+
+        // The keys decide the order.
+        let items = get_list("a b c").unwrap();
+        let keys = get_list("3 1 2").unwrap();
+        let sorted = sort_list(&items, &keys, mode(SortMode::Integer)).unwrap();
+        assert_eq!(list_to_string(&sorted), "b c a");
+    }
 }
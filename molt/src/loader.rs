@@ -0,0 +1,58 @@
+//! Script Loaders
+//!
+//! A [`ScriptLoader`] reads the scripts that the `source` command and
+//! [`Interp::eval_file`] evaluate.  The default, [`FsLoader`], reads them from the file
+//! system.  An application that has no file system, e.g., under the `wasm` feature, or
+//! that wants to control what scripts can see, installs a loader of its own with
+//! [`Interp::set_script_loader`].  A `HashMap` of paths to scripts is a loader, for
+//! scripts bundled with the application:
+//!
+//! ```
+//! # use molt_forked::prelude::*;
+//! use std::collections::HashMap;
+//!
+//! let mut scripts = HashMap::new();
+//! scripts.insert("lib/util.tcl".to_string(), "proc double {x} { expr {2 * $x} }".to_string());
+//!
+//! let mut interp = Interp::default();
+//! interp.set_script_loader(Box::new(scripts));
+//! interp.eval("source lib/util.tcl").unwrap();
+//! assert_eq!(interp.eval("double 21").unwrap().as_int(), Ok(42));
+//!
+//! let exception = interp.eval("source nonesuch.tcl").unwrap_err();
+//! assert!(exception.value().as_str().starts_with("couldn't read file \"nonesuch.tcl\""));
+//! ```
+//!
+//! [`Interp::eval_file`]: ../interp/struct.Interp.html#method.eval_file
+//! [`Interp::set_script_loader`]: ../interp/struct.Interp.html#method.set_script_loader
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+/// Reads the scripts that `source` evaluates; see the
+/// [module level documentation](index.html).
+pub trait ScriptLoader {
+    /// Returns the text of the script at the path, or an error if it can't be read.
+    fn load(&mut self, path: &str) -> io::Result<String>;
+}
+
+/// The default [`ScriptLoader`], which reads scripts from the file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsLoader;
+
+impl ScriptLoader for FsLoader {
+    fn load(&mut self, path: &str) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+}
+
+/// Loads scripts from a map of paths to scripts.
+impl ScriptLoader for HashMap<String, String> {
+    fn load(&mut self, path: &str) -> io::Result<String> {
+        match self.get(path) {
+            Some(script) => Ok(script.clone()),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "no such file")),
+        }
+    }
+}
@@ -203,28 +203,26 @@ macro_rules! join_strings {
 }
 
 /// A Molt command that has subcommands is called an _ensemble_ command.  In Rust code,
-/// the ensemble is defined as an array of `Subcommand` structs, each one mapping from
+/// the ensemble is defined as a list of `(name, func)` tuples, each one mapping from
 /// a subcommand name to the implementing [`CommandFunc`].  For more information,
 /// see the discussion of command definition in [The Molt Book] and the [`interp`] module.
 ///
-/// The tuple fields are the subcommand's name and implementing [`CommandFunc`].
-///
 /// [The Molt Book]: https://wduquette.github.io/molt/
 /// [`interp`]: ../interp/index.html
 /// [`CommandFunc`]: type.CommandFunc.html
 ///
-/// Calls a subcommand of the current command, looking up its name in an array of
-/// `Subcommand` tuples.
+/// Generates a function that calls a subcommand of the current command, dispatching on
+/// its name with a `match`, so the cost of a lookup doesn't grow with the number of
+/// subcommands.
 ///
-/// The subcommand, if found, is called with the same `context_ids` and `argv` as its
-/// parent ensemble.  `subc` is the index of the subcommand's name in the `argv` array;
-/// in most cases it will be `1`, but it is possible to define subcommands with
-/// subcommands of their own.  The `subcommands` argument is a borrow of an array of
-/// `Subcommand` records, each defining a subcommand's name and `CommandFunc`.
+/// The subcommand, if found, is called with the same `argv` as its parent ensemble.
+/// `subc` is the index of the subcommand's name in the `argv` array; in most cases it will
+/// be `1`, but it is possible to define subcommands with subcommands of their own.
 ///
-/// If the subcommand name is found in the array, the matching `CommandFunc` is called.
-/// otherwise, the error message gives the ensemble syntax.  If an invalid subcommand
-/// name was provided, the error message includes the valid options.
+/// If the subcommand name is found, the matching `CommandFunc` is called; otherwise, the
+/// error message gives the ensemble syntax.  If an invalid subcommand name was provided,
+/// the error message includes the valid options.  Names must match exactly; abbreviations
+/// are not accepted.
 ///
 /// See the implementation of the `array` command in `commands.rs` and the
 /// [module level documentation](index.html) for examples.
@@ -249,28 +247,26 @@ macro_rules! _gen_subcommand_generic {
 }
 
 /// A Molt command that has subcommands is called an _ensemble_ command.  In Rust code,
-/// the ensemble is defined as an array of `Subcommand` structs, each one mapping from
-/// a subcommand name to the implementing [`CommandFunc`].  For more information,
+/// the ensemble is defined as a list of `(name, space, func, help)` tuples, each one mapping
+/// from a subcommand name to the implementing [`CommandFunc`] and its help text.  For more information,
 /// see the discussion of command definition in [The Molt Book] and the [`interp`] module.
 ///
-/// The tuple fields are the subcommand's name and implementing [`CommandFunc`].
-///
 /// [The Molt Book]: https://wduquette.github.io/molt/
 /// [`interp`]: ../interp/index.html
 /// [`CommandFunc`]: type.CommandFunc.html
 ///
-/// Calls a subcommand of the current command, looking up its name in an array of
-/// `Subcommand` tuples.
+/// Generates a function that calls a subcommand of the current command, dispatching on
+/// its name with a `match`, so the cost of a lookup doesn't grow with the number of
+/// subcommands.
 ///
-/// The subcommand, if found, is called with the same `context_ids` and `argv` as its
-/// parent ensemble.  `subc` is the index of the subcommand's name in the `argv` array;
-/// in most cases it will be `1`, but it is possible to define subcommands with
-/// subcommands of their own.  The `subcommands` argument is a borrow of an array of
-/// `Subcommand` records, each defining a subcommand's name and `CommandFunc`.
+/// The subcommand, if found, is called with the same `argv` as its parent ensemble.
+/// `subc` is the index of the subcommand's name in the `argv` array; in most cases it will
+/// be `1`, but it is possible to define subcommands with subcommands of their own.
 ///
-/// If the subcommand name is found in the array, the matching `CommandFunc` is called.
-/// otherwise, the error message gives the ensemble syntax.  If an invalid subcommand
-/// name was provided, the error message includes the valid options.
+/// If the subcommand name is found, the matching `CommandFunc` is called; otherwise, the
+/// error message gives the ensemble syntax.  If an invalid subcommand name was provided,
+/// the error message suggests similar names.  The `-help` subcommand returns the help text.
+/// Names must match exactly; abbreviations are not accepted.
 ///
 /// See the implementation of the `array` command in `commands.rs` and the
 /// [module level documentation](index.html) for examples.
@@ -288,7 +284,11 @@ macro_rules! gen_subcommand {
             $cmd_name => $cmd_func(interp, argv),
           )*
           "-help" => molt_ok!("usage of{}:\n{}",argv[0..$subc].iter().map(|v|v.as_str()).collect::<Vec<&str>>().join(" "),HELP_MSG),
-          _ => molt_err_help!("unknown subcommand in \"{} {}\", usage:\n{}", argv[0..$subc].iter().map(|v|v.as_str()).collect::<Vec<&str>>().join(" "),sub_name,HELP_MSG ),
+          _ => {
+            let cmd_name = argv[0..$subc].iter().map(|v|v.as_str()).collect::<Vec<&str>>().join(" ");
+            let suggestions = $crate::similar_names(sub_name, [$($cmd_name),*]);
+            molt_err_help!("unknown subcommand in \"{} {}\"{} (run {} -help for usage)", cmd_name, sub_name, $crate::did_you_mean(&suggestions), cmd_name)
+          }
         }
       }
       f
@@ -332,75 +332,149 @@ macro_rules! join_helps {
   };
 }
 
+/// Returns the name of an embedded `gen_command!` entry.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _gen_command_embedded_name {
+    ($name:tt, $($rest:tt)*) => {
+        $name
+    };
+}
+
+/// Calls the function of an embedded `gen_command!` entry, checking the argument
+/// count first when the entry carries an [`ArgSpec`](types/struct.ArgSpec.html).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _gen_command_embedded_call {
+    ($interp:ident, $argv:ident, $name:tt, $func:expr, ArgSpec { min: $min:expr, max: $max:expr, usage: $usage:literal $(,)? } $(, $help:tt)? $(,)?) => {{
+        $crate::check_args(1, $argv, $min, $max, $usage)?;
+        $func($interp, $argv)
+    }};
+    ($interp:ident, $argv:ident, $name:tt, $space:tt, $func:expr, $help:tt $(,)?) => {
+        $func($interp, $argv)
+    };
+}
+
+/// Returns the [`EmbeddedHelp`](interp/struct.EmbeddedHelp.html) of an embedded
+/// `gen_command!` entry.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _gen_command_embedded_help {
+    ($name:tt, $func:expr, ArgSpec { min: $min:expr, max: $max:expr, usage: $usage:literal $(,)? }, $help:tt $(,)?) => {
+        $crate::prelude::EmbeddedHelp {
+            usage: Some($usage),
+            help: Some($help),
+            padding: "",
+        }
+    };
+    ($name:tt, $func:expr, ArgSpec { min: $min:expr, max: $max:expr, usage: $usage:literal $(,)? } $(,)?) => {
+        $crate::prelude::EmbeddedHelp { usage: Some($usage), help: None, padding: "" }
+    };
+    ($name:tt, $space:tt, $func:expr, $help:tt $(,)?) => {
+        $crate::prelude::EmbeddedHelp { usage: None, help: Some($help), padding: $space }
+    };
+}
+
+/// Generates the [`Command`](interp/struct.Command.html) dispatcher for an `Interp`.
+///
+/// The first list holds extra native commands as `(name, func)` pairs.  The second list
+/// holds embedded commands, in either of two forms:
+///
+/// * `(name, func, ArgSpec { min, max, usage }, help)`: the dispatcher checks the
+///   argument count against the [`ArgSpec`](types/struct.ArgSpec.html) before calling
+///   `func`, and the `help` output shows `usage`.  The `help` string may be omitted.
+/// * `(name, space, func, help)`: no arity check is done; `space` is padding inserted
+///   between the name and the help string.
+///
+/// The commands are fixed at compile time.  For a name it doesn't know, the dispatcher
+/// tries the commands added at runtime with
+/// [`Interp::add_command`](interp/struct.Interp.html#method.add_command), and then the
+/// procs.
 #[macro_export]
 macro_rules! gen_command {
-  ($ctx_type:ty, [ $( ($native_name:tt, $native_func:expr $(,)?) ),* $(,)?], [ $( ($embedded_name:tt, $embedded_space:tt, $embedded_func:expr, $embedded_help:tt $(,)?) ),* $(,)?] $(,)?) => {
+  ($ctx_type:ty, [ $( ($native_name:tt, $native_func:expr $(,)?) ),* $(,)?], [ $( ( $($embedded:tt)* ) ),* $(,)?] $(,)?) => {
     $crate::prelude::Command::new(
       {fn f(name: &str, interp: &mut $crate::prelude::Interp<$ctx_type>, argv: &[$crate::prelude::Value]) -> $crate::prelude::MoltResult {
-        const HELP_MSG: &str = join_helps!( $( [$embedded_name,$embedded_space,$embedded_help], )* );
         match name {
           // NOTICE: Default native commands
+          $crate::prelude::_AFTER => $crate::prelude::cmd_after(interp, argv),
+          $crate::prelude::_ALIAS => $crate::prelude::cmd_alias(interp, argv),
           $crate::prelude::_APPEND => $crate::prelude::cmd_append(interp, argv),
           $crate::prelude::_ARRAY => $crate::prelude::cmd_array(interp, argv),
+          $crate::prelude::_ASSERT_CODE => $crate::prelude::cmd_assert_code(interp, argv),
           $crate::prelude::_ASSERT_EQ => $crate::prelude::cmd_assert_eq(interp, argv),
+          $crate::prelude::_ASSERT_ERR => $crate::prelude::cmd_assert_err(interp, argv),
+          $crate::prelude::_BINARY => $crate::prelude::cmd_binary(interp, argv),
           $crate::prelude::_BREAK => $crate::prelude::cmd_break(interp, argv),
           $crate::prelude::_CATCH => $crate::prelude::cmd_catch(interp, argv),
+          $crate::prelude::_CLOCK => $crate::prelude::cmd_clock(interp, argv),
+          $crate::prelude::_CONCAT => $crate::prelude::cmd_concat(interp, argv),
           $crate::prelude::_CONTINUE => $crate::prelude::cmd_continue(interp, argv),
           $crate::prelude::_DICT => $crate::prelude::cmd_dict(interp, argv),
+          $crate::prelude::_ENCODING => $crate::prelude::cmd_encoding(interp, argv),
           $crate::prelude::_ERROR => $crate::prelude::cmd_error(interp, argv),
+          $crate::prelude::_EVAL => $crate::prelude::cmd_eval(interp, argv),
           $crate::prelude::_EXPR => $crate::prelude::cmd_expr(interp, argv),
+          $crate::prelude::_FLUSH => $crate::prelude::cmd_flush(interp, argv),
           $crate::prelude::_FOR => $crate::prelude::cmd_for(interp, argv),
           $crate::prelude::_FOREACH => $crate::prelude::cmd_foreach(interp, argv),
+          $crate::prelude::_FORMAT => $crate::prelude::cmd_format(interp, argv),
           $crate::prelude::_GLOBAL => $crate::prelude::cmd_global(interp, argv),
           $crate::prelude::_IF => $crate::prelude::cmd_if(interp, argv),
           $crate::prelude::_INCR => $crate::prelude::cmd_incr(interp, argv),
           $crate::prelude::_INFO => $crate::prelude::cmd_info(interp, argv),
+          $crate::prelude::_INTERP => $crate::prelude::cmd_interp(interp, argv),
           $crate::prelude::_JOIN => $crate::prelude::cmd_join(interp, argv),
           $crate::prelude::_LAPPEND => $crate::prelude::cmd_lappend(interp, argv),
           $crate::prelude::_LINDEX => $crate::prelude::cmd_lindex(interp, argv),
+          $crate::prelude::_LINSERT => $crate::prelude::cmd_linsert(interp, argv),
           $crate::prelude::_LIST => $crate::prelude::cmd_list(interp, argv),
           $crate::prelude::_LLENGTH => $crate::prelude::cmd_llength(interp, argv),
+          $crate::prelude::_LMAP => $crate::prelude::cmd_lmap(interp, argv),
+          $crate::prelude::_LRANGE => $crate::prelude::cmd_lrange(interp, argv),
+          $crate::prelude::_LREPLACE => $crate::prelude::cmd_lreplace(interp, argv),
+          $crate::prelude::_LREPEAT => $crate::prelude::cmd_lrepeat(interp, argv),
+          $crate::prelude::_LREVERSE => $crate::prelude::cmd_lreverse(interp, argv),
+          $crate::prelude::_LSEARCH => $crate::prelude::cmd_lsearch(interp, argv),
+          $crate::prelude::_LSORT => $crate::prelude::cmd_lsort(interp, argv),
+          $crate::prelude::_NAMESPACE => $crate::prelude::cmd_namespace(interp, argv),
+          $crate::prelude::_PACKAGE => $crate::prelude::cmd_package(interp, argv),
           $crate::prelude::_PROC => $crate::prelude::cmd_proc(interp, argv),
+          $crate::prelude::_PROFILE => $crate::prelude::cmd_profile(interp, argv),
           $crate::prelude::_PUTS => $crate::prelude::cmd_puts(interp, argv),
           $crate::prelude::_RENAME => $crate::prelude::cmd_rename(interp, argv),
           $crate::prelude::_RETURN => $crate::prelude::cmd_return(interp, argv),
+          $crate::prelude::_SCAN => $crate::prelude::cmd_scan(interp, argv),
           $crate::prelude::_SET => $crate::prelude::cmd_set(interp, argv),
           $crate::prelude::_STRING => $crate::prelude::cmd_string(interp, argv),
+          $crate::prelude::_SUBST => $crate::prelude::cmd_subst(interp, argv),
+          $crate::prelude::_SWITCH => $crate::prelude::cmd_switch(interp, argv),
+          $crate::prelude::_TAILCALL => $crate::prelude::cmd_tailcall(interp, argv),
           $crate::prelude::_THROW => $crate::prelude::cmd_throw(interp, argv),
           $crate::prelude::_TIME => $crate::prelude::cmd_time(interp, argv),
+          $crate::prelude::_TRACE => $crate::prelude::cmd_trace(interp, argv),
           $crate::prelude::_UNSET => $crate::prelude::cmd_unset(interp, argv),
+          $crate::prelude::_UPLEVEL => $crate::prelude::cmd_uplevel(interp, argv),
+          $crate::prelude::_UPVAR => $crate::prelude::cmd_upvar(interp, argv),
+          $crate::prelude::_VWAIT => $crate::prelude::cmd_vwait(interp, argv),
           $crate::prelude::_WHILE => $crate::prelude::cmd_while(interp, argv),
-          "help" => {
-            if let Some(v)= argv.get(1){
-              if v.as_str()=="-all"{
-                let proc_command_names = interp.proc_command_names();
-                if proc_command_names.is_empty(){
-                  return molt_ok!("usage of {}:\ntcl:\n  {}\n{}:\n{}", interp.name,interp.native_command_names(),interp.name,HELP_MSG);
-                }else{
-                  return molt_ok!("usage of {}:\ntcl:\n  {}\n{}:\n{}\nprocedure:\n  {}", interp.name,interp.native_command_names(),interp.name,HELP_MSG,proc_command_names);
-                }
-              }
-            }
-            molt_ok!("usage of {}:\n{}",interp.name,HELP_MSG)},
+          "help" => $crate::molt_ok!(interp.help_message(argv.get(1).is_some_and(|v| v.as_str() == "-all"))),
           // NOTICE: Extra native commands
           $(
             $native_name => $native_func(interp, argv),
           )*
           // NOTICE: Embedded commands
           $(
-            $embedded_name => $embedded_func(interp, argv),
+            $crate::_gen_command_embedded_name!($($embedded)*) => $crate::_gen_command_embedded_call!(interp, argv, $($embedded)*),
           )*
           // NOTICE: Proc commands
           other => {
-            if let Some(proc) = interp.get_proc(other) {
+            if let Some(func) = interp.get_command(other) {
+              func(interp, argv)
+            } else if let Some(proc) = interp.get_proc(other) {
               proc.clone().execute(interp, argv)
             } else {
-              let proc_command_names = interp.proc_command_names();
-              if proc_command_names.is_empty(){
-                molt_err_help!("unknown command \"{}\", valid commands:\ntcl:\n  {}\n{}:\n{}", name,interp.native_command_names(),interp.name,HELP_MSG)
-              }else{
-                molt_err_help!("unknown command \"{}\", valid commands:\ntcl:\n  {}\n{}:\n{}\nprocedure:\n  {}", name,interp.native_command_names(),interp.name,HELP_MSG,proc_command_names)
-              }
+              interp.unknown_command(name, argv)
             }
           }
         }
@@ -409,44 +483,77 @@ macro_rules! gen_command {
       },
       {fn f(name: &str, interp: &$crate::prelude::Interp<$ctx_type>) -> Option<$crate::prelude::CommandType> {
         match name {
+          $crate::prelude::_AFTER => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_ALIAS => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_APPEND => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_ARRAY => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_ASSERT_CODE => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_ASSERT_EQ => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_ASSERT_ERR => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_BINARY => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_BREAK => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_CATCH => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_CLOCK => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_CONCAT => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_CONTINUE => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_DICT => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_ENCODING => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_ERROR => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_EVAL => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_EXPR => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_FLUSH => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_FOR => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_FOREACH => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_FORMAT => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_GLOBAL => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_IF => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_INCR => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_INFO => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_INTERP => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_JOIN => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_LAPPEND => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_LINDEX => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LINSERT => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_LIST => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_LLENGTH => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LMAP => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LRANGE => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LREPLACE => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LREPEAT => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LREVERSE => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LSEARCH => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_LSORT => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_NAMESPACE => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_PACKAGE => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_PROC => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_PROFILE => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_PUTS => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_RENAME => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_RETURN => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_SCAN => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_SET => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_STRING => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_SUBST => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_SWITCH => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_TAILCALL => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_THROW => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_TIME => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_TRACE => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_UNSET => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_UPLEVEL => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_UPVAR => Some($crate::prelude::CommandType::Native),
+          $crate::prelude::_VWAIT => Some($crate::prelude::CommandType::Native),
           $crate::prelude::_WHILE => Some($crate::prelude::CommandType::Native),
           $(
             $native_name => Some($crate::prelude::CommandType::Native),
           )*
           $(
-            $embedded_name => Some($crate::prelude::CommandType::Embedded),
+            $crate::_gen_command_embedded_name!($($embedded)*) => Some($crate::prelude::CommandType::Embedded),
           )*
           other => {
-            if interp.contains_proc(other) {
+            if interp.contains_command(other) {
+              Some($crate::prelude::CommandType::Native)
+            } else if interp.contains_proc(other) {
               Some($crate::prelude::CommandType::Proc)
             } else {
               None
@@ -457,35 +564,66 @@ macro_rules! gen_command {
       f as fn(&str, &$crate::prelude::Interp<$ctx_type>) -> Option<$crate::prelude::CommandType>
       },
       &[
+        $crate::prelude::_AFTER,
+        $crate::prelude::_ALIAS,
         $crate::prelude::_APPEND,
         $crate::prelude::_ARRAY,
+        $crate::prelude::_ASSERT_CODE,
         $crate::prelude::_ASSERT_EQ,
+        $crate::prelude::_ASSERT_ERR,
+        $crate::prelude::_BINARY,
         $crate::prelude::_BREAK,
         $crate::prelude::_CATCH,
+        $crate::prelude::_CLOCK,
+        $crate::prelude::_CONCAT,
         $crate::prelude::_CONTINUE,
         $crate::prelude::_DICT,
+        $crate::prelude::_ENCODING,
         $crate::prelude::_ERROR,
+        $crate::prelude::_EVAL,
         $crate::prelude::_EXPR,
+        $crate::prelude::_FLUSH,
         $crate::prelude::_FOR,
         $crate::prelude::_FOREACH,
+        $crate::prelude::_FORMAT,
         $crate::prelude::_GLOBAL,
         $crate::prelude::_IF,
         $crate::prelude::_INCR,
         $crate::prelude::_INFO,
+        $crate::prelude::_INTERP,
         $crate::prelude::_JOIN,
         $crate::prelude::_LAPPEND,
         $crate::prelude::_LINDEX,
+        $crate::prelude::_LINSERT,
         $crate::prelude::_LIST,
         $crate::prelude::_LLENGTH,
+        $crate::prelude::_LMAP,
+        $crate::prelude::_LRANGE,
+        $crate::prelude::_LREPLACE,
+        $crate::prelude::_LREPEAT,
+        $crate::prelude::_LREVERSE,
+        $crate::prelude::_LSEARCH,
+        $crate::prelude::_LSORT,
+        $crate::prelude::_NAMESPACE,
+        $crate::prelude::_PACKAGE,
         $crate::prelude::_PROC,
+        $crate::prelude::_PROFILE,
         $crate::prelude::_PUTS,
         $crate::prelude::_RENAME,
         $crate::prelude::_RETURN,
+        $crate::prelude::_SCAN,
         $crate::prelude::_SET,
         $crate::prelude::_STRING,
+        $crate::prelude::_SUBST,
+        $crate::prelude::_SWITCH,
+        $crate::prelude::_TAILCALL,
         $crate::prelude::_THROW,
         $crate::prelude::_TIME,
+        $crate::prelude::_TRACE,
         $crate::prelude::_UNSET,
+        $crate::prelude::_UPLEVEL,
+        $crate::prelude::_UPVAR,
+        $crate::prelude::_VWAIT,
         $crate::prelude::_WHILE,
         $(
             $native_name,
@@ -493,10 +631,225 @@ macro_rules! gen_command {
       ],
       &[
         $(
-          $embedded_name,
+          $crate::_gen_command_embedded_name!($($embedded)*),
         )*
       ]
-    )
+    ).with_embedded_helps(&[
+      $(
+        $crate::_gen_command_embedded_help!($($embedded)*),
+      )*
+    ])
+  };
+}
+
+/// Generates a minimal [`Command`](interp/struct.Command.html) dispatcher, for use with
+/// [`Interp::new`](interp/struct.Interp.html#method.new).
+///
+/// Unlike [`gen_command!`], no standard Molt commands are included, nor the `help`
+/// command: the interpreter has exactly the listed native and embedded commands, which
+/// take the same forms as for [`gen_command!`].  Standard commands can be included
+/// individually, e.g., `(_SET, cmd_set)`.  Procedures are only dispatched if
+/// `procs = true` is given, which is useful only if the `proc` command is included.
+/// Commands added at runtime with
+/// [`Interp::add_command`](interp/struct.Interp.html#method.add_command) are always
+/// dispatched.
+///
+/// # Example
+///
+/// A tiny key/value configuration language:
+///
+/// ```
+/// # use molt_forked::prelude::*;
+/// use std::collections::HashMap;
+///
+/// fn cmd_key(interp: &mut Interp<HashMap<String, String>>, argv: &[Value]) -> MoltResult {
+///     interp.context.insert(argv[1].to_string(), argv[2].to_string());
+///     molt_ok!()
+/// }
+///
+/// let mut interp = Interp::new(
+///     HashMap::new(),
+///     gen_command_minimal!(
+///         HashMap<String, String>,
+///         [],
+///         [("key", cmd_key, ArgSpec { min: 3, max: 3, usage: "name value" })]
+///     ),
+///     false,
+///     "config",
+/// );
+///
+/// interp.eval("key width 80\nkey title {My App}").unwrap();
+/// assert_eq!(interp.context["title"], "My App");
+/// assert_eq!(interp.command_names(), vec![Value::from("key")]);
+///
+/// let err = interp.eval("set x 1").unwrap_err();
+/// assert_eq!(err.value().as_str(), "unknown command \"set\"");
+/// ```
+#[macro_export]
+macro_rules! gen_command_minimal {
+  ($ctx_type:ty, [ $( ($native_name:tt, $native_func:expr $(,)?) ),* $(,)?], [ $( ( $($embedded:tt)* ) ),* $(,)?] $(, procs = $procs:literal)? $(,)?) => {
+    $crate::prelude::Command::new(
+      {fn f(name: &str, interp: &mut $crate::prelude::Interp<$ctx_type>, argv: &[$crate::prelude::Value]) -> $crate::prelude::MoltResult {
+        #[allow(unused_variables)]
+        match name {
+          $(
+            $native_name => $native_func(interp, argv),
+          )*
+          $(
+            $crate::_gen_command_embedded_name!($($embedded)*) => $crate::_gen_command_embedded_call!(interp, argv, $($embedded)*),
+          )*
+          other => {
+            if let Some(func) = interp.get_command(other) {
+              return func(interp, argv);
+            }
+            if false $(|| $procs)? {
+              if let Some(proc) = interp.get_proc(other) {
+                return proc.clone().execute(interp, argv);
+              }
+            }
+            $crate::molt_err!("unknown command \"{}\"", name)
+          }
+        }
+      }
+      f as fn(&str, &mut $crate::prelude::Interp<$ctx_type>, &[$crate::prelude::Value]) -> $crate::prelude::MoltResult
+      },
+      {fn f(name: &str, interp: &$crate::prelude::Interp<$ctx_type>) -> Option<$crate::prelude::CommandType> {
+        match name {
+          $(
+            $native_name => Some($crate::prelude::CommandType::Native),
+          )*
+          $(
+            $crate::_gen_command_embedded_name!($($embedded)*) => Some($crate::prelude::CommandType::Embedded),
+          )*
+          other => {
+            if interp.contains_command(other) {
+              Some($crate::prelude::CommandType::Native)
+            } else if (false $(|| $procs)?) && interp.contains_proc(other) {
+              Some($crate::prelude::CommandType::Proc)
+            } else {
+              None
+            }
+          }
+        }
+      }
+      f as fn(&str, &$crate::prelude::Interp<$ctx_type>) -> Option<$crate::prelude::CommandType>
+      },
+      &[
+        $(
+            $native_name,
+        )*
+      ],
+      &[
+        $(
+          $crate::_gen_command_embedded_name!($($embedded)*),
+        )*
+      ]
+    ).with_embedded_helps(&[
+      $(
+        $crate::_gen_command_embedded_help!($($embedded)*),
+      )*
+    ])
+  };
+}
+
+/// Generates the constructor command of an object type, for the object-command pattern:
+/// `constructor name ?arg ...?` creates an object command called *name*, with state of
+/// its own, and returns the name.  The object's methods are its subcommands, and
+/// `rename name ""` destroys it.  See [`add_object`](object/fn.add_object.html), which the
+/// constructor calls.
+///
+/// The arguments are the interpreter's context type, the object's state type, and a
+/// function that makes the state from the constructor's `argv`, returning
+/// `Result<State, Exception>`.  Then comes the list of methods as `(name, func)` pairs,
+/// where `func` is an [`ObjectMethod`](object/type.ObjectMethod.html): it is passed the
+/// interpreter, the state, and the object command's `argv`, whose `argv[1]` is the
+/// method's name.  Last, `destroy = func` optionally gives an
+/// [`ObjectDestroy`](object/type.ObjectDestroy.html) function, called when the object is
+/// destroyed.
+///
+/// # Example
+///
+/// ```
+/// # use molt_forked::prelude::*;
+/// struct Counter {
+///     count: MoltInt,
+/// }
+///
+/// fn counter_new(_: &mut Interp<()>, argv: &[Value]) -> Result<Counter, Exception> {
+///     check_args(1, argv, 2, 3, "name ?start?")?;
+///     let count = match argv.get(2) {
+///         Some(start) => start.as_int()?,
+///         None => 0,
+///     };
+///     Ok(Counter { count })
+/// }
+///
+/// fn counter_incr(_: &mut Interp<()>, counter: &mut Counter, argv: &[Value]) -> MoltResult {
+///     check_args(2, argv, 2, 2, "")?;
+///     counter.count += 1;
+///     molt_ok!(counter.count)
+/// }
+///
+/// fn counter_get(_: &mut Interp<()>, counter: &mut Counter, argv: &[Value]) -> MoltResult {
+///     check_args(2, argv, 2, 2, "")?;
+///     molt_ok!(counter.count)
+/// }
+///
+/// fn counter_destroy(interp: &mut Interp<()>, counter: &mut Counter) {
+///     interp.set_scalar("last_count", Value::from(counter.count)).unwrap();
+/// }
+///
+/// let mut interp = Interp::new(
+///     (),
+///     gen_command!(
+///         (),
+///         [(
+///             "counter",
+///             define_object!(
+///                 (),
+///                 Counter,
+///                 counter_new,
+///                 [("get", counter_get), ("incr", counter_incr)],
+///                 destroy = counter_destroy,
+///             )
+///         )],
+///         []
+///     ),
+///     false,
+///     "objects",
+/// );
+///
+/// interp.eval("counter fred 10; fred incr; fred incr").unwrap();
+/// assert_eq!(interp.eval("fred get").unwrap().as_int(), Ok(12));
+///
+/// interp.eval("rename fred {}").unwrap();
+/// assert_eq!(interp.scalar("last_count").unwrap().as_int(), Ok(12));
+/// assert!(interp.eval("fred get").is_err());
+/// ```
+#[macro_export]
+macro_rules! define_object {
+  ($ctx_type:ty, $state:ty, $new:expr, [ $( ($method:tt, $method_func:expr $(,)?) ),* $(,)?] $(, destroy = $destroy:expr)? $(,)?) => {
+    {
+      fn dispatch(interp: &mut $crate::prelude::Interp<$ctx_type>, state: &mut $state, argv: &[$crate::prelude::Value]) -> $crate::prelude::MoltResult {
+        $crate::check_args(1, argv, 2, 0, "method ?arg ...?")?;
+        match argv[1].as_str() {
+          $(
+            $method => $method_func(interp, state, argv),
+          )*
+          other => $crate::molt_err!("unknown method \"{}\" of \"{}\": must be {}", other, argv[0], $crate::join_strings!( $($method,)* )),
+        }
+      }
+
+      fn f(interp: &mut $crate::prelude::Interp<$ctx_type>, argv: &[$crate::prelude::Value]) -> $crate::prelude::MoltResult {
+        $crate::check_args(1, argv, 2, 0, "name ?arg ...?")?;
+        let new: fn(&mut $crate::prelude::Interp<$ctx_type>, &[$crate::prelude::Value]) -> Result<$state, $crate::prelude::Exception> = $new;
+        let destroy: Option<$crate::prelude::ObjectDestroy<$ctx_type, $state>> = None $( .or(Some($destroy)) )?;
+        let state = new(interp, argv)?;
+        $crate::prelude::add_object(interp, argv[1].as_str(), state, dispatch, destroy)?;
+        $crate::molt_ok!(argv[1].clone())
+      }
+      f
+    }
   };
 }
 
@@ -0,0 +1,210 @@
+//! Namespaces
+//!
+//! A namespace is a named collection of procs and variables, so that a larger body of
+//! Molt code can keep its names apart from other code's.  Namespaces nest: `::a::b` is
+//! namespace `b` in namespace `a`, in the global namespace `::`.
+//!
+//! Internally, a namespace, proc, or namespace variable is known by its qualified name
+//! without the leading `::`, e.g., `a::b::x`; a name in the global namespace is just
+//! `x`.  The procs are kept in the interpreter's proc table under these names, and the
+//! variables in the global scope.
+//!
+//! Names are resolved as in Standard TCL:
+//!
+//! * A name beginning with `::` is fully qualified.
+//! * Any other name is relative to the current namespace: the namespace of the running
+//!   proc, or the one given to `namespace eval`.  A command that isn't found there is
+//!   looked up in the global namespace, so that the standard commands are always
+//!   available.
+//!
+//! The `namespace` command provides `namespace eval`, `namespace current`, and
+//! `namespace children`.
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::util;
+use crate::value::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The name of the `namespace` command.
+pub const _NAMESPACE: &str = "namespace";
+
+/// Returns true if the name is qualified with a namespace, e.g., `a::x` or `::x`.  This
+/// is checked on every variable access, so it looks for a colon before searching for
+/// `::`.
+#[inline]
+pub(crate) fn is_qualified(name: &str) -> bool {
+    name.as_bytes().contains(&b':') && name.contains("::")
+}
+
+/// Returns the qualified name, without the leading `::`, of a name relative to the
+/// namespace.
+pub(crate) fn qualify<'a>(namespace: &str, name: &'a str) -> Cow<'a, str> {
+    if let Some(absolute) = name.strip_prefix("::") {
+        Cow::Borrowed(absolute)
+    } else if namespace.is_empty() {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("{}::{}", namespace, name))
+    }
+}
+
+/// Returns the namespace part of a qualified name, e.g., `a::b` for `a::b::x`; empty if
+/// the name is in the global namespace.
+pub(crate) fn parent(qualified: &str) -> &str {
+    qualified.rfind("::").map_or("", |i| &qualified[..i])
+}
+
+/// Returns the fully qualified form of a namespace's name, e.g., `::a::b`, or `::` for
+/// the global namespace.
+pub(crate) fn display(namespace: &str) -> String {
+    format!("::{}", namespace)
+}
+
+/// An index of the procs in namespaces other than the global one, so that a command name
+/// can be resolved relative to the current namespace without building the qualified name
+/// on each call.  A proc `a::b::f` is found from namespace `a` as `b::f`, and from
+/// namespace `a::b` as `f`; each entry shares the proc's qualified name.
+#[derive(Default)]
+pub(crate) struct ProcIndex {
+    by_namespace: HashMap<String, HashMap<String, Rc<str>>>,
+}
+
+impl ProcIndex {
+    /// Adds a proc by its qualified name.
+    pub(crate) fn insert(&mut self, qualified: &str) {
+        let key: Rc<str> = Rc::from(qualified);
+        for (i, _) in qualified.match_indices("::") {
+            self.by_namespace
+                .entry(qualified[..i].into())
+                .or_default()
+                .insert(qualified[i + 2..].into(), key.clone());
+        }
+    }
+
+    /// Removes a proc by its qualified name.
+    pub(crate) fn remove(&mut self, qualified: &str) {
+        for (i, _) in qualified.match_indices("::") {
+            if let Some(names) = self.by_namespace.get_mut(&qualified[..i]) {
+                names.remove(&qualified[i + 2..]);
+                if names.is_empty() {
+                    self.by_namespace.remove(&qualified[..i]);
+                }
+            }
+        }
+    }
+
+    /// Returns the qualified name of the proc with the name relative to the namespace,
+    /// if there is one.
+    #[inline]
+    pub(crate) fn get(&self, namespace: &str, name: &str) -> Option<&Rc<str>> {
+        self.by_namespace.get(namespace)?.get(name)
+    }
+}
+
+/// # namespace *subcommand* ?*arg* ...?
+///
+/// Creates and queries namespaces.
+///
+/// * `namespace children ?namespace? ?pattern?`: returns the fully qualified names of the
+///   namespace's children, sorted, optionally only those matching the glob pattern.
+///   The namespace defaults to the current one.
+/// * `namespace current`: returns the fully qualified name of the current namespace.
+/// * `namespace eval namespace arg ?arg ...?`: creates the namespace if need be, and
+///   evaluates the arguments, concatenated as by `eval`, in it.
+pub fn cmd_namespace<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("children", cmd_namespace_children),
+            ("current", cmd_namespace_current),
+            ("eval", cmd_namespace_eval),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// # namespace children ?*namespace*? ?*pattern*?
+fn cmd_namespace_children<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 4, "?name? ?pattern?")?;
+
+    let namespace = argv.get(2).map_or("", |v| v.as_str());
+    let children = interp.namespace_children(namespace)?;
+
+    let Some(pattern) = argv.get(3) else {
+        return molt_ok!(children);
+    };
+
+    // A relative pattern is relative to the namespace.
+    let parent = interp.resolve_namespace(namespace);
+    let pattern = if pattern.as_str().starts_with("::") {
+        pattern.to_string()
+    } else if parent.is_empty() {
+        format!("::{}", pattern)
+    } else {
+        format!("::{}::{}", parent, pattern)
+    };
+
+    molt_ok!(children
+        .into_iter()
+        .filter(|child| util::glob_match(&pattern, child.as_str()))
+        .collect::<MoltList>())
+}
+
+/// # namespace current
+fn cmd_namespace_current<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+    molt_ok!(interp.current_namespace())
+}
+
+/// # namespace eval *namespace* *arg* ?*arg* ...?
+fn cmd_namespace_eval<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 0, "name arg ?arg ...?")?;
+
+    // A single argument is evaluated as is, keeping its parsed script.
+    let script = if argv.len() == 4 { argv[3].clone() } else { util::concat(&argv[3..]) };
+
+    interp.namespace_eval(argv[2].as_str(), &script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualify() {
+        assert_eq!(qualify("", "x"), "x");
+        assert_eq!(qualify("", "a::x"), "a::x");
+        assert_eq!(qualify("", "::a::x"), "a::x");
+        assert_eq!(qualify("a", "x"), "a::x");
+        assert_eq!(qualify("a", "b::x"), "a::b::x");
+        assert_eq!(qualify("a", "::x"), "x");
+        assert_eq!(qualify("a::b", "::"), "");
+    }
+
+    #[test]
+    fn test_parent_and_display() {
+        assert_eq!(parent("x"), "");
+        assert_eq!(parent("a::x"), "a");
+        assert_eq!(parent("a::b::x"), "a::b");
+        assert_eq!(display(""), "::");
+        assert_eq!(display("a::b"), "::a::b");
+    }
+
+    #[test]
+    fn test_proc_index() {
+        let mut index = ProcIndex::default();
+        index.insert("f");
+        index.insert("a::b::f");
+        assert_eq!(index.get("", "f"), None);
+        assert_eq!(index.get("a", "b::f").map(|key| &**key), Some("a::b::f"));
+        assert_eq!(index.get("a::b", "f").map(|key| &**key), Some("a::b::f"));
+        assert_eq!(index.get("a", "f"), None);
+
+        index.remove("a::b::f");
+        assert_eq!(index.get("a::b", "f"), None);
+        assert!(index.by_namespace.is_empty());
+    }
+}
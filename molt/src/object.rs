@@ -0,0 +1,180 @@
+//! Object Commands
+//!
+//! An object command is an ensemble command that represents an object, with data of its
+//! own: `make fred` creates the object `fred`, `fred incr` calls one of its methods, and
+//! `rename fred ""` destroys it.  [`add_object`] creates such a command at runtime from
+//! the object's state and a method dispatcher; the [`define_object!`] macro generates the
+//! constructor command and the dispatcher from a list of methods.
+//!
+//! The object's state is dropped when its command is deleted.  An object type may also
+//! have a destroy function, which is called with the interpreter and the state first,
+//! e.g., to tell the application that the object is gone.
+//!
+//! [`define_object!`]: ../macro.define_object.html
+
+use crate::interp::{CommandFunc, Interp};
+use crate::types::*;
+use crate::value::Value;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// An object's method, or its method dispatcher: it is passed the interpreter, the
+/// object's state, and the words of the command, starting with the object's name.
+pub type ObjectMethod<Ctx, State> =
+    fn(&mut Interp<Ctx>, &mut State, &[Value]) -> MoltResult;
+
+/// An object's destroy function, called when its command is deleted.
+pub type ObjectDestroy<Ctx, State> = fn(&mut Interp<Ctx>, &mut State);
+
+/// An object's state, shared by its command and its destructor.
+struct Object<State> {
+    state: RefCell<State>,
+
+    // Set if the command was deleted while one of its methods was running, so that the
+    // destroy function is called when the method returns.
+    destroy_pending: Cell<bool>,
+}
+
+/// Creates an object command with the given name, state, and method dispatcher, which is
+/// called for each use of the command.  When the command is deleted, e.g., by
+/// `rename name ""`, the `destroy` function, if any, is called, and the state is dropped.
+/// An object that deletes itself from one of its own methods is destroyed when the
+/// method returns.
+///
+/// A method can't call its own object again, even indirectly; that's an error.  It's an
+/// error if the name is already a command.  See the
+/// [module level documentation](index.html).
+pub fn add_object<Ctx: 'static, State: 'static>(
+    interp: &mut Interp<Ctx>,
+    name: &str,
+    state: State,
+    dispatch: ObjectMethod<Ctx, State>,
+    destroy: Option<ObjectDestroy<Ctx, State>>,
+) -> Result<(), Exception> {
+    if name.is_empty() || interp.command_type(name).is_ok() {
+        return molt_err!("command \"{}\" already exists", name);
+    }
+
+    let object = Rc::new(Object {
+        state: RefCell::new(state),
+        destroy_pending: Cell::new(false),
+    });
+
+    let command = object.clone();
+    let func: CommandFunc<Ctx> = Rc::new(move |interp, argv| {
+        let Ok(mut state) = command.state.try_borrow_mut() else {
+            return molt_err!("object \"{}\" is busy", argv[0]);
+        };
+
+        let result = dispatch(interp, &mut state, argv);
+        if command.destroy_pending.take() {
+            if let Some(destroy) = destroy {
+                destroy(interp, &mut state);
+            }
+        }
+        result
+    });
+
+    match destroy {
+        Some(destroy) => interp.add_command_with_destructor(
+            name,
+            func,
+            Box::new(move |interp| match object.state.try_borrow_mut() {
+                Ok(mut state) => destroy(interp, &mut state),
+                Err(_) => object.destroy_pending.set(true),
+            }),
+        ),
+        None => interp.add_command(name, func),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    struct Door {
+        open: bool,
+    }
+
+    fn door_new(_: &mut Interp<()>, argv: &[Value]) -> Result<Door, Exception> {
+        check_args(1, argv, 2, 2, "name")?;
+        Ok(Door { open: false })
+    }
+
+    fn door_open(_: &mut Interp<()>, door: &mut Door, _: &[Value]) -> MoltResult {
+        door.open = true;
+        molt_ok!()
+    }
+
+    fn door_is_open(_: &mut Interp<()>, door: &mut Door, _: &[Value]) -> MoltResult {
+        molt_ok!(door.open)
+    }
+
+    fn door_eval(interp: &mut Interp<()>, _: &mut Door, argv: &[Value]) -> MoltResult {
+        interp.eval_value(&argv[2])
+    }
+
+    fn door_destroy(interp: &mut Interp<()>, door: &mut Door) {
+        let log = format!("destroyed {}", if door.open { "open" } else { "closed" });
+        interp.set_scalar("log", Value::from(log)).unwrap();
+    }
+
+    fn interp() -> Interp<()> {
+        let mut interp = Interp::default();
+        let make_door = define_object!(
+            (),
+            Door,
+            door_new,
+            [("eval", door_eval), ("isopen", door_is_open), ("open", door_open)],
+            destroy = door_destroy,
+        );
+        interp.add_command("door", std::rc::Rc::new(make_door));
+        interp
+    }
+
+    #[test]
+    fn test_object_lifecycle() {
+        let mut interp = interp();
+        assert_eq!(interp.eval("door front").unwrap().as_str(), "front");
+        assert_eq!(interp.eval("front isopen").unwrap().as_str(), "0");
+        interp.eval("front open").unwrap();
+        assert_eq!(interp.eval("front isopen").unwrap().as_str(), "1");
+
+        assert_eq!(
+            interp.eval("front close").unwrap_err().value().as_str(),
+            "unknown method \"close\" of \"front\": must be eval, isopen or open"
+        );
+        assert_eq!(
+            interp.eval("door front").unwrap_err().value().as_str(),
+            "command \"front\" already exists"
+        );
+
+        // Renaming keeps the object; deleting destroys it.
+        interp.eval("rename front back").unwrap();
+        assert_eq!(interp.eval("back isopen").unwrap().as_str(), "1");
+        assert!(!interp.var_exists(&Value::from("log")));
+        interp.eval("rename back {}").unwrap();
+        assert_eq!(interp.scalar("log").unwrap().as_str(), "destroyed open");
+        assert!(interp.eval("back isopen").is_err());
+    }
+
+    #[test]
+    fn test_object_deletes_itself() {
+        let mut interp = interp();
+        interp.eval("door side").unwrap();
+
+        // The object is destroyed once the method that deleted it returns.
+        let result = interp.eval("side eval {rename side {}; set log}").unwrap_err();
+        assert_eq!(result.value().as_str(), "can't read \"log\": no such variable");
+        assert_eq!(interp.scalar("log").unwrap().as_str(), "destroyed closed");
+        assert!(!interp.contains_command("side"));
+
+        // A method can't call its own object.
+        interp.eval("door side").unwrap();
+        assert_eq!(
+            interp.eval("side eval {side open}").unwrap_err().value().as_str(),
+            "object \"side\" is busy"
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Packages
+//!
+//! A package is a named, versioned body of code that scripts load with
+//! `package require`.  A script provides a package with `package provide`; a Rust crate
+//! that ships a Molt extension registers an init function with
+//! [`Interp::register_package`], which `package require` calls the first time the
+//! package is required.  The init function defines the package's procs, math functions,
+//! and variables; commands written in Rust are added to the interpreter's
+//! [`gen_command!`] as usual.
+//!
+//! A version is a list of non-negative integers separated by dots, e.g., `1.2.3`.  A
+//! requirement for version *v* is satisfied by any version with the same major number
+//! that is at least *v*, or, with `-exact`, only by *v* itself.
+//!
+//! [`Interp::register_package`]: ../interp/struct.Interp.html#method.register_package
+//! [`gen_command!`]: ../macro.gen_command.html
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+
+/// The name of the `package` command.
+pub const _PACKAGE: &str = "package";
+
+/// A package's init function, which [`Interp::register_package`] registers and
+/// `package require` calls to load the package.
+///
+/// [`Interp::register_package`]: ../interp/struct.Interp.html#method.register_package
+pub type PackageInitFn<Ctx> = fn(&mut Interp<Ctx>) -> MoltResult;
+
+/// An interpreter's packages: those registered by Rust code, and those provided so far.
+pub(crate) struct Packages<Ctx: 'static> {
+    // The registered packages' versions and init functions, by name.
+    registered: HashMap<String, (String, PackageInitFn<Ctx>)>,
+
+    // The provided packages' versions, by name.
+    provided: HashMap<String, String>,
+}
+
+impl<Ctx> Default for Packages<Ctx> {
+    fn default() -> Self {
+        Self {
+            registered: HashMap::new(),
+            provided: HashMap::new(),
+        }
+    }
+}
+
+impl<Ctx> Packages<Ctx> {
+    /// Registers the package's version and init function, replacing any registered
+    /// before.
+    pub(crate) fn register(
+        &mut self,
+        name: &str,
+        version: &str,
+        init: PackageInitFn<Ctx>,
+    ) {
+        self.registered.insert(name.into(), (version.into(), init));
+    }
+
+    /// Returns the registered version and init function of the package, if any.
+    pub(crate) fn registered(&self, name: &str) -> Option<(String, PackageInitFn<Ctx>)> {
+        self.registered.get(name).cloned()
+    }
+
+    /// Returns the provided version of the package, if any.
+    pub(crate) fn provided(&self, name: &str) -> Option<&str> {
+        self.provided.get(name).map(String::as_str)
+    }
+
+    /// Records that the package has been provided.  It's an error to provide a different
+    /// version of a package already provided.
+    pub(crate) fn provide(&mut self, name: &str, version: &str) -> Result<(), Exception> {
+        match self.provided.get(name) {
+            Some(have) if compare(have, version)? != Ordering::Equal => molt_err!(
+                "conflicting versions provided for package \"{}\": {}, then {}",
+                name,
+                have,
+                version
+            ),
+            Some(_) => Ok(()),
+            None => {
+                parse_version(version)?;
+                self.provided.insert(name.into(), version.into());
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the names of the registered and provided packages, sorted.
+    pub(crate) fn names(&self) -> BTreeSet<&str> {
+        self.registered
+            .keys()
+            .chain(self.provided.keys())
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Parses a version number into its components.
+fn parse_version(version: &str) -> Result<Vec<u64>, Exception> {
+    let parts: Option<Vec<u64>> = version
+        .split('.')
+        .map(|part| {
+            if !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) {
+                part.parse().ok()
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    match parts {
+        Some(parts) => Ok(parts),
+        None => molt_err!("expected version number but got \"{}\"", version),
+    }
+}
+
+/// Compares two version numbers, component by component.
+fn compare(a: &str, b: &str) -> Result<Ordering, Exception> {
+    Ok(parse_version(a)?.cmp(&parse_version(b)?))
+}
+
+/// Returns true if version `have` satisfies a requirement for version `need`.
+fn satisfies(have: &str, need: &str, exact: bool) -> Result<bool, Exception> {
+    let have = parse_version(have)?;
+    let need = parse_version(need)?;
+
+    if exact {
+        Ok(have == need)
+    } else {
+        Ok(have[0] == need[0] && have >= need)
+    }
+}
+
+/// # package *subcommand* ?*arg* ...?
+///
+/// Provides and loads packages.
+///
+/// * `package names`: returns the names of the registered and provided packages.
+/// * `package provide name ?version?`: records that version *version* of the package
+///   has been provided, or returns the provided version, or the empty string if it
+///   hasn't been.
+/// * `package require ?-exact? name ?version?`: loads the package, if it hasn't been
+///   provided, and returns its version.
+pub fn cmd_package<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("names", cmd_package_names),
+            ("provide", cmd_package_provide),
+            ("require", cmd_package_require),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// # package names
+fn cmd_package_names<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    let names: MoltList =
+        interp.packages().names().into_iter().map(Value::from).collect();
+    molt_ok!(names)
+}
+
+/// # package provide *name* ?*version*?
+fn cmd_package_provide<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 4, "package ?version?")?;
+
+    let name = argv[2].as_str();
+    match argv.get(3) {
+        Some(version) => {
+            interp.packages().provide(name, version.as_str())?;
+            molt_ok!()
+        }
+        None => molt_ok!(interp.packages().provided(name).unwrap_or_default()),
+    }
+}
+
+/// # package require ?-exact? *name* ?*version*?
+fn cmd_package_require<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 5, "?-exact? package ?version?")?;
+
+    let exact = argv[2].as_str() == "-exact";
+    let args = if exact { &argv[3..] } else { &argv[2..] };
+    if args.is_empty() || args.len() > 2 || (exact && args.len() != 2) {
+        return molt_err!(
+            "wrong # args: should be \"package require ?-exact? package ?version?\""
+        );
+    }
+
+    let name = args[0].as_str();
+    let need = args.get(1).map(Value::as_str);
+    let version = require_package(interp, name, need, exact)?;
+    molt_ok!(version)
+}
+
+/// Loads the package, if it hasn't been provided, and returns its version: the work
+/// of `package require`.
+fn require_package<Ctx>(
+    interp: &mut Interp<Ctx>,
+    name: &str,
+    need: Option<&str>,
+    exact: bool,
+) -> Result<String, Exception> {
+    if let Some(need) = need {
+        parse_version(need)?;
+    }
+
+    if let Some(have) = interp.packages().provided(name) {
+        let have = have.to_string();
+        return match need {
+            Some(need) if !satisfies(&have, need, exact)? => molt_err!(
+                "version conflict for package \"{}\": have {}, need {}{}",
+                name,
+                have,
+                if exact { "exactly " } else { "" },
+                need
+            ),
+            _ => Ok(have),
+        };
+    }
+
+    let found = match (interp.packages().registered(name), need) {
+        (Some((version, init)), Some(need)) if satisfies(&version, need, exact)? => {
+            Some((version, init))
+        }
+        (Some(registered), None) => Some(registered),
+        _ => None,
+    };
+    let Some((version, init)) = found else {
+        return match need {
+            Some(need) => molt_err!("can't find package {} {}", name, need),
+            None => molt_err!("can't find package {}", name),
+        };
+    };
+
+    init(interp)?;
+    match interp.packages().provided(name) {
+        Some(have) => Ok(have.to_string()),
+        None => {
+            interp.packages().provide(name, &version)?;
+            Ok(version)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(parse_version("1.2.10").unwrap(), vec![1, 2, 10]);
+        assert!(parse_version("1..2").is_err());
+        assert!(parse_version("1.a").is_err());
+        assert!(parse_version("").is_err());
+
+        assert_eq!(compare("1.10", "1.9").unwrap(), Ordering::Greater);
+        assert_eq!(compare("1", "1.0").unwrap(), Ordering::Less);
+
+        assert!(satisfies("1.2", "1.1", false).unwrap());
+        assert!(satisfies("1.2", "1.2", false).unwrap());
+        assert!(!satisfies("1.2", "1.3", false).unwrap());
+        assert!(!satisfies("2.0", "1.0", false).unwrap());
+        assert!(!satisfies("1.2", "1.1", true).unwrap());
+    }
+
+    #[test]
+    fn test_register_package() {
+        fn init_greet(interp: &mut Interp<()>) -> MoltResult {
+            interp.eval("proc greet {name} { return \"hello, $name\" }")
+        }
+
+        let mut interp = Interp::default();
+        interp.register_package("greet", "1.2", init_greet);
+        assert_eq!(interp.eval("package names").unwrap().as_str(), "greet");
+        assert_eq!(interp.eval("package provide greet").unwrap().as_str(), "");
+
+        assert_eq!(interp.eval("package require greet 1.0").unwrap().as_str(), "1.2");
+        assert_eq!(interp.eval("greet world").unwrap().as_str(), "hello, world");
+        assert_eq!(interp.eval("package provide greet").unwrap().as_str(), "1.2");
+
+        assert_eq!(
+            interp.eval("package require greet 2").unwrap_err().value().as_str(),
+            "version conflict for package \"greet\": have 1.2, need 2"
+        );
+    }
+}
@@ -50,7 +50,7 @@
 
 use crate::{
     eval_ptr::EvalPtr,
-    types::{Exception, VarName},
+    types::{Delimiter, Exception, VarName},
     util::is_varname_char,
     value::Value,
 };
@@ -121,6 +121,14 @@ pub(crate) enum Word {
     String(String),
 }
 
+/// A comment skipped by the parser, with the line on which it begins.  The text
+/// includes the `#`, and any backslash-newlines that continue it.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Comment {
+    pub line: usize,
+    pub text: String,
+}
+
 /// Parses a script, given as a string slice.  Returns a parsed `Script` (or an error).
 pub(crate) fn parse(input: &str) -> Result<Script, Exception> {
     // FIRST, create an EvalPtr as a parsing aid; then parse the script.
@@ -128,13 +136,29 @@ pub(crate) fn parse(input: &str) -> Result<Script, Exception> {
     parse_script(&mut ctx)
 }
 
+/// Parses a script like `parse`, also returning the comments in the order they were
+/// skipped.  Comments within braced words are just text, and aren't included.
+pub(crate) fn parse_with_comments(
+    input: &str,
+) -> Result<(Script, Vec<Comment>), Exception> {
+    let mut ctx = EvalPtr::new(input);
+    ctx.collect_comments();
+    let script = parse_script(&mut ctx)?;
+    Ok((script, ctx.take_comments()))
+}
+
 /// Parses a script represented by an `EvalPtr`.  This form is also used by `expr`.
 pub(crate) fn parse_script(ctx: &mut EvalPtr) -> Result<Script, Exception> {
     let mut script = Script::new();
 
-    // Parse commands from the input until we've reach the end.
+    // Parse commands from the input until we've reach the end.  Trailing whitespace and
+    // comments, and empty commands like the one in "a;;b", produce no words; skip them,
+    // so that every command in the script is one to execute.
     while !ctx.at_end_of_script() {
-        script.commands.push(parse_command(ctx)?);
+        let cmd = parse_command(ctx)?;
+        if !cmd.words.is_empty() {
+            script.commands.push(cmd);
+        }
     }
 
     Ok(script)
@@ -208,6 +232,7 @@ fn parse_next_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
 pub(crate) fn parse_braced_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     // FIRST, skip the opening brace, and count it; non-escaped braces need to
     // balance.
+    let open = ctx.mark();
     ctx.skip_char('{');
     let mut count = 1;
 
@@ -259,7 +284,39 @@ pub(crate) fn parse_braced_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
         }
     }
 
-    molt_err_uncompleted!("missing close-brace")
+    // NEXT, a comment in a braced body is just text, so a brace in it still counts;
+    // say so, as Standard TCL does, since that's the usual cause.
+    let msg = if brace_in_comment(&ctx.tok().input()[open..]) {
+        "missing close-brace: possible unbalanced brace in comment"
+    } else {
+        "missing close-brace"
+    };
+    missing_delimiter(ctx, open, Delimiter::Brace, msg)
+}
+
+/// Does any line of the text, past the first, look like a comment with more open
+/// braces than close braces?
+fn brace_in_comment(text: &str) -> bool {
+    text.lines().skip(1).any(|line| {
+        let line = line.trim_start();
+        if !line.starts_with('#') {
+            return false;
+        }
+
+        let mut count = 0;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '{' => count += 1,
+                '}' => count -= 1,
+                _ => (),
+            }
+        }
+        count > 0
+    })
 }
 
 /// Parses a quoted word, handling backslash, variable, and command substitution. It's
@@ -267,6 +324,7 @@ pub(crate) fn parse_braced_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
 /// if the close quote is missing.
 pub(crate) fn parse_quoted_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     // FIRST, consume the the opening quote.
+    let open = ctx.mark();
     ctx.next();
 
     // NEXT, add tokens to the word until we reach the close quote
@@ -308,15 +366,22 @@ pub(crate) fn parse_quoted_word(ctx: &mut EvalPtr) -> Result<Word, Exception> {
         }
     }
 
-    molt_err!("missing \"")
+    missing_delimiter(ctx, open, Delimiter::Quote, "missing \"")
 }
 
 /// Parses a bare word, handling backslash, variable, and command substitution.
+///
+/// If `index_flag` is set, parses the index of an array reference, e.g., `$a(b c)`.  As
+/// in Tcl, only the ")" ends the index; white space and semi-colons are part of it.
 fn parse_bare_word(ctx: &mut EvalPtr, index_flag: bool) -> Result<Word, Exception> {
     let mut tokens = Tokens::new();
     let mut start = ctx.mark();
 
-    while !ctx.at_end_of_command() && !ctx.next_is_line_white() {
+    while if index_flag {
+        !ctx.at_end()
+    } else {
+        !ctx.at_end_of_command() && !ctx.next_is_line_white()
+    } {
         // Note: the while condition ensures that there's a character.
         if index_flag && ctx.next_is(')') {
             // Parsing an array index, and we're at the end.
@@ -351,10 +416,57 @@ fn parse_bare_word(ctx: &mut EvalPtr, index_flag: bool) -> Result<Word, Exceptio
     Ok(tokens.take())
 }
 
+/// Parses the input for the `subst` command: the whole string is treated as a single
+/// word-like sequence, with backslash, variable, and command substitution each
+/// enabled by the corresponding flag.  Returns the tokens in order, so that the caller
+/// can evaluate them one at a time.
+pub(crate) fn parse_subst(
+    input: &str,
+    backslashes: bool,
+    commands: bool,
+    variables: bool,
+) -> Result<Vec<Word>, Exception> {
+    let mut ctx = EvalPtr::new(input);
+    let mut tokens = Tokens::new();
+    let mut start = ctx.mark();
+
+    while !ctx.at_end() {
+        // Note: the while condition ensures that there's a character.
+        if commands && ctx.next_is('[') {
+            if start != ctx.mark() {
+                tokens.push_str(ctx.token(start));
+            }
+            tokens.push(Word::Script(parse_brackets(&mut ctx)?));
+            start = ctx.mark();
+        } else if variables && ctx.next_is('$') {
+            if start != ctx.mark() {
+                tokens.push_str(ctx.token(start));
+            }
+            parse_dollar(&mut ctx, &mut tokens)?;
+            start = ctx.mark();
+        } else if backslashes && ctx.next_is('\\') {
+            if start != ctx.mark() {
+                tokens.push_str(ctx.token(start));
+            }
+            tokens.push_char(ctx.backslash_subst());
+            start = ctx.mark();
+        } else {
+            ctx.skip();
+        }
+    }
+
+    if start != ctx.mark() {
+        tokens.push_str(ctx.token(start));
+    }
+
+    Ok(tokens.take_list())
+}
+
 /// Parses an embedded script in a bare or quoted word, returning the result as a
 /// Script.  It's an error if the close-bracket is missing.
 fn parse_brackets(ctx: &mut EvalPtr) -> Result<Script, Exception> {
     // FIRST, skip the '['
+    let open = ctx.mark();
     ctx.skip_char('[');
 
     // NEXT, parse the script up to the matching ']'
@@ -368,13 +480,34 @@ fn parse_brackets(ctx: &mut EvalPtr) -> Result<Script, Exception> {
         if ctx.next_is(']') {
             ctx.next();
         } else {
-            return molt_err_uncompleted!("missing close-bracket");
+            return missing_delimiter(
+                ctx,
+                open,
+                Delimiter::Bracket,
+                "missing close-bracket",
+            );
         }
     }
 
     result
 }
 
+/// Returns the incomplete-input error for a delimiter opened at the byte offset `open`
+/// but never closed.
+///
+/// Also used by expr.rs.
+pub(crate) fn missing_delimiter<T>(
+    ctx: &mut EvalPtr,
+    open: usize,
+    kind: Delimiter,
+    msg: &str,
+) -> Result<T, Exception> {
+    let line = ctx.tok().input()[..open].matches('\n').count() + 1;
+    let mut exception = Exception::molt_err(Value::from(msg));
+    exception.to_uncomplete_at(kind, open, line);
+    Err(exception)
+}
+
 /// Parses a "$" in the input, and pushes the result into a list of tokens.  Usually this
 /// will be a variable reference, but it may simply be a bare "$".
 fn parse_dollar(ctx: &mut EvalPtr, tokens: &mut Tokens) -> Result<(), Exception> {
@@ -399,12 +532,18 @@ fn parse_dollar(ctx: &mut EvalPtr, tokens: &mut Tokens) -> Result<(), Exception>
 pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
     // FIRST, is this a braced variable name?
     if ctx.next_is('{') {
+        let open = ctx.mark();
         ctx.skip_char('{');
         let start = ctx.mark();
         ctx.skip_while(|ch| *ch != '}');
 
         if ctx.at_end() {
-            return molt_err_uncompleted!("missing close-brace for variable name");
+            return missing_delimiter(
+                ctx,
+                open,
+                Delimiter::Brace,
+                "missing close-brace for variable name",
+            );
         }
 
         let var_name = parse_varname_literal(ctx.token(start));
@@ -417,8 +556,14 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
             None => Ok(Word::VarRef(var_name.name().into())),
         }
     } else {
+        // The name may be qualified: namespace separators, two or more colons, are part
+        // of it, but a single colon ends it.
         let start = ctx.mark();
         ctx.skip_while(|ch| is_varname_char(*ch));
+        while ctx.next_is_namespace_sep() {
+            ctx.skip_while(|ch| *ch == ':');
+            ctx.skip_while(|ch| is_varname_char(*ch));
+        }
         let name = ctx.token(start).to_string();
 
         if !ctx.next_is('(') {
@@ -428,6 +573,9 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
             // Array; parse out the word that evaluates to the index.
             ctx.skip();
             let index = parse_bare_word(ctx, true)?;
+            if !ctx.next_is(')') {
+                return molt_err!("missing )");
+            }
             ctx.skip_char(')');
             Ok(Word::ArrayRef(name, Box::new(index)))
         }
@@ -438,7 +586,9 @@ pub(crate) fn parse_varname(ctx: &mut EvalPtr) -> Result<Word, Exception> {
 /// name.
 ///
 /// If it contains an opening parenthesis and ends with a closing parenthesis, then
-/// it's an array reference; otherwise it's just a scalar name.
+/// it's an array reference; otherwise it's just a scalar name.  The name ends at the
+/// first "(", and the index runs from there to the final ")", so it may contain
+/// parentheses itself.
 pub(crate) fn parse_varname_literal(literal: &str) -> VarName {
     let mut ctx = EvalPtr::new(literal);
 
@@ -461,7 +611,7 @@ pub(crate) fn parse_varname_literal(literal: &str) -> VarName {
 
     // NEXT, skip to the final character.
     let start = ctx.mark();
-    let chars_left = ctx.tok().as_str().len() - 1;
+    let chars_left = ctx.tok().as_str().chars().count() - 1;
 
     for _ in 0..chars_left {
         ctx.skip();
@@ -545,6 +695,16 @@ impl Tokens {
             Word::Tokens(self.list)
         }
     }
+
+    /// Takes the accumulated tokens as a list of `Word`s, without merging them into a
+    /// single `Word`.
+    fn take_list(mut self) -> Vec<Word> {
+        if self.got_string {
+            self.list.push(Word::String(self.string));
+        }
+
+        self.list
+    }
 }
 
 #[cfg(test)]
@@ -629,7 +789,139 @@ mod tests {
         assert_eq!(cmds[0].words, vec![Word::Value(Value::from("a"))]);
         assert_eq!(cmds[1].words, vec![Word::Value(Value::from("b"))]);
 
-        assert_eq!(parse("a {"), molt_err_uncompleted!("missing close-brace"));
+        // Empty commands are skipped.
+        let cmds = parse("a;; b\n\n# done\n").unwrap().commands;
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[0].words, vec![Word::Value(Value::from("a"))]);
+        assert_eq!(cmds[1].words, vec![Word::Value(Value::from("b"))]);
+        assert!(parse(" ;\n# comment\n").unwrap().commands.is_empty());
+
+        assert_eq!(parse("a {"), missing("missing close-brace", Delimiter::Brace, 2, 1));
+    }
+
+    #[test]
+    fn test_parse_incomplete() {
+        // Each delimiter kind
+        assert_eq!(
+            parse("set a {\nb"),
+            missing("missing close-brace", Delimiter::Brace, 6, 1)
+        );
+        assert_eq!(
+            parse("set a\nset b [list"),
+            missing("missing close-bracket", Delimiter::Bracket, 12, 2)
+        );
+        assert_eq!(parse("set a \"b\nc"), missing("missing \"", Delimiter::Quote, 6, 1));
+        assert_eq!(
+            parse("puts ${a"),
+            missing("missing close-brace for variable name", Delimiter::Brace, 6, 1)
+        );
+
+        // Nested: the innermost open construct is reported.
+        assert_eq!(
+            parse("set a [list b\n{c d"),
+            missing("missing close-brace", Delimiter::Brace, 14, 2)
+        );
+        assert_eq!(
+            parse("set a \"x [list\n\"y"),
+            missing("missing \"", Delimiter::Quote, 15, 2)
+        );
+        assert_eq!(
+            parse("set a {x [list y"),
+            missing("missing close-brace", Delimiter::Brace, 6, 1)
+        );
+        assert_eq!(
+            parse("set a [list [b]\n[c"),
+            missing("missing close-bracket", Delimiter::Bracket, 16, 2)
+        );
+
+        // The position is reported on the exception.
+        let exception = parse("a\nb\nc {").unwrap_err();
+        assert!(exception.is_uncompleted());
+        assert!(exception.is_recoverable_by_more_input());
+        assert_eq!(exception.uncompleted_kind(), Some(Delimiter::Brace));
+        assert_eq!(exception.uncompleted_position(), Some((6, 3)));
+
+        // Other errors aren't incomplete input.
+        let exception = parse("a {b}c").unwrap_err();
+        assert!(!exception.is_uncompleted());
+        assert!(!exception.is_recoverable_by_more_input());
+        assert_eq!(exception.uncompleted_kind(), None);
+        assert_eq!(exception.uncompleted_position(), None);
+    }
+
+    #[test]
+    fn test_parse_recoverable() {
+        // An unclosed delimiter can be closed by more input.
+        for input in ["a {", "a [b", "a \"b", "a ${b", "a [b {c}", "a {b\n[c}\n\"d"] {
+            let exception = parse(input).unwrap_err();
+            assert!(exception.is_recoverable_by_more_input(), "{:?}", input);
+        }
+
+        // Anything else can't.
+        for input in ["a {b}c", "a \"b\"c", "a $b(c", "a [b {c}d]", "a {b}\"c\nd {"] {
+            let exception = parse(input).unwrap_err();
+            assert!(!exception.is_recoverable_by_more_input(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let (script, comments) = parse_with_comments(
+            "# first\na ;# second\n  # third \\\n  continued\nb {\n# braced\n} # not",
+        )
+        .unwrap();
+        assert_eq!(script.commands.len(), 2);
+        assert_eq!(
+            comments,
+            vec![
+                Comment { line: 1, text: "# first".into() },
+                Comment { line: 2, text: "# second".into() },
+                Comment { line: 3, text: "# third \\\n  continued".into() },
+            ]
+        );
+
+        // Comments in bracketed scripts are collected too.
+        let (_, comments) = parse_with_comments("a [\n# inner\nb]").unwrap();
+        assert_eq!(comments, vec![Comment { line: 2, text: "# inner".into() }]);
+
+        // `parse` doesn't collect them.
+        assert_eq!(parse("# c\na").unwrap().commands.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_brace_in_comment() {
+        assert_eq!(
+            parse("proc p {} {\n  # a { in a comment\n  return\n}"),
+            missing(
+                "missing close-brace: possible unbalanced brace in comment",
+                Delimiter::Brace,
+                10,
+                1
+            )
+        );
+
+        // Balanced or escaped braces in comments, and braces on the first line, don't
+        // get the hint.
+        assert!(parse("proc p {} {\n  # {balanced}\n  return\n}").is_ok());
+        assert_eq!(
+            parse("set a {\n  # \\{ escaped\n"),
+            missing("missing close-brace", Delimiter::Brace, 6, 1)
+        );
+        assert_eq!(
+            parse("set a {# {"),
+            missing("missing close-brace", Delimiter::Brace, 6, 1)
+        );
+    }
+
+    fn missing<T>(
+        msg: &str,
+        kind: Delimiter,
+        offset: usize,
+        line: usize,
+    ) -> Result<T, Exception> {
+        let mut exception = Exception::molt_err(Value::from(msg));
+        exception.to_uncomplete_at(kind, offset, line);
+        Err(exception)
     }
 
     #[test]
@@ -710,9 +1002,15 @@ mod tests {
         );
 
         // Strings with missing close-brace
-        assert_eq!(pbrace("{abc"), molt_err_uncompleted!("missing close-brace"));
+        assert_eq!(
+            pbrace("{abc"),
+            missing("missing close-brace", Delimiter::Brace, 0, 1)
+        );
 
-        assert_eq!(pbrace("{a{b}c"), molt_err_uncompleted!("missing close-brace"));
+        assert_eq!(
+            pbrace("{a{b}c"),
+            missing("missing close-brace", Delimiter::Brace, 0, 1)
+        );
     }
 
     fn pbrace(input: &str) -> Result<(Word, String), Exception> {
@@ -780,13 +1078,54 @@ mod tests {
             ))
         );
 
+        // Unicode escapes, in both forms
+        assert_eq!(pqw("\"\\u263A\" "), Ok((Word::Value(Value::from("☺")), " ".into())));
+        assert_eq!(
+            pqw("\"a\\u{1F600}$x\" "),
+            Ok((
+                Word::Tokens(vec![Word::String("a😀".into()), Word::VarRef("x".into())]),
+                " ".into()
+            ))
+        );
+
         // Missing close quote
-        assert_eq!(pqw("\"abc"), molt_err!("missing \""));
+        assert_eq!(pqw("\"abc"), missing("missing \"", Delimiter::Quote, 0, 1));
 
         // Extra characters after close-quote
         assert_eq!(pqw("\"abc\"x "), molt_err!("extra characters after close-quote"));
     }
 
+    #[test]
+    fn test_parse_multibyte_near_delimiters() {
+        // Multibyte characters adjacent to every delimiter, alone and in pairs, must
+        // never cause a slice inside a character; parsing either succeeds or fails
+        // cleanly.
+        let templates = [
+            "{C}", "\"C\"", "[C]", "$C", "${C}", "$a(C)", "C{", "{C", "\"C", "[C", "C\"",
+            "C[", "C$", "C]", "C}", "\\C", "C\\", "$a(C", "C;C", "{C}C", "\"C\"C",
+            "#C\n", "C\\\nC", "{*}C", "\\u{C}", "\\uC",
+        ];
+
+        for ch in ["é", "☺", "😀", "𝄞"] {
+            let words: Vec<String> =
+                templates.iter().map(|t| t.replace('C', ch)).collect();
+
+            for a in &words {
+                let _ = parse(a);
+                let _ = parse_subst(a, true, true, true);
+
+                for b in &words {
+                    let _ = parse(&format!("{}{}", a, b));
+                    let _ = parse(&format!("{} {}", a, b));
+                }
+            }
+        }
+
+        assert_eq!(pbrack("[😀]").unwrap().commands.len(), 1);
+        assert_eq!(pqw("\"☺$😀\"").unwrap().0, Word::Value(Value::from("☺$😀")));
+        assert_eq!(pqw("\"😀\"é "), molt_err!("extra characters after close-quote"));
+    }
+
     fn pqw(input: &str) -> Result<(Word, String), Exception> {
         let mut ctx = EvalPtr::new(input);
         let word = parse_quoted_word(&mut ctx)?;
@@ -870,6 +1209,15 @@ mod tests {
             pbare("a)b", true),
             Ok((Word::Value(Value::from("a")), ")b".into()))
         );
+
+        // Array index: white space and semi-colons don't end it.
+        assert_eq!(
+            pbare("b c;d)e", true),
+            Ok((Word::Value(Value::from("b c;d")), ")e".into()))
+        );
+
+        // Array index: empty.
+        assert_eq!(pbare(")", true), Ok((Word::Value(Value::from("")), ")".into())));
     }
 
     fn pbare(input: &str, index_flag: bool) -> Result<(Word, String), Exception> {
@@ -878,6 +1226,50 @@ mod tests {
         Ok((word, ctx.tok().as_str().to_string()))
     }
 
+    #[test]
+    fn test_parse_subst() {
+        // Plain text, including braces, quotes, and white space
+        assert_eq!(
+            parse_subst("{a} \"b\"\n", true, true, true),
+            Ok(vec![Word::String("{a} \"b\"\n".into())])
+        );
+
+        // All substitutions
+        assert_eq!(
+            parse_subst("a\\tb$x[c]", true, true, true),
+            Ok(vec![
+                Word::String("a\tb".into()),
+                Word::VarRef("x".into()),
+                Word::Script(parse("c").unwrap()),
+            ])
+        );
+
+        // Each substitution can be disabled
+        assert_eq!(
+            parse_subst("a\\tb$x[c]", false, true, true),
+            Ok(vec![
+                Word::String("a\\tb".into()),
+                Word::VarRef("x".into()),
+                Word::Script(parse("c").unwrap()),
+            ])
+        );
+        assert_eq!(
+            parse_subst("a\\tb$x[c]", true, false, true),
+            Ok(vec![
+                Word::String("a\tb".into()),
+                Word::VarRef("x".into()),
+                Word::String("[c]".into()),
+            ])
+        );
+        assert_eq!(
+            parse_subst("a\\tb$x[c]", true, true, false),
+            Ok(vec![Word::String("a\tb$x".into()), Word::Script(parse("c").unwrap()),])
+        );
+
+        // Empty input
+        assert_eq!(parse_subst("", true, true, true), Ok(vec![]));
+    }
+
     #[test]
     fn test_parse_brackets() {
         let script = pbrack("[set a 5]").unwrap();
@@ -892,7 +1284,10 @@ mod tests {
             ]
         );
 
-        assert_eq!(pbrack("[incomplete"), molt_err_uncompleted!("missing close-bracket"));
+        assert_eq!(
+            pbrack("[incomplete"),
+            missing("missing close-bracket", Delimiter::Bracket, 0, 1)
+        );
     }
 
     fn pbrack(input: &str) -> Result<Script, Exception> {
@@ -909,6 +1304,20 @@ mod tests {
         assert_eq!(pvar("$a.bc"), Ok((Word::VarRef("a".into()), ".bc".into())));
         assert_eq!(pvar("$a1_.bc"), Ok((Word::VarRef("a1_".into()), ".bc".into())));
 
+        // Qualified var names
+        assert_eq!(pvar("$::a"), Ok((Word::VarRef("::a".into()), "".into())));
+        assert_eq!(pvar("$a::b c"), Ok((Word::VarRef("a::b".into()), " c".into())));
+        assert_eq!(pvar("$a:::b"), Ok((Word::VarRef("a:::b".into()), "".into())));
+        assert_eq!(pvar("$a:b"), Ok((Word::VarRef("a".into()), ":b".into())));
+        assert_eq!(pvar("$:a"), Ok((Word::Value(Value::from("$")), ":a".into())));
+        assert_eq!(
+            pvar("$a::b(1)"),
+            Ok((
+                Word::ArrayRef("a::b".into(), Box::new(Word::Value(Value::from("1")))),
+                "".into()
+            ))
+        );
+
         // Array names
         assert_eq!(
             pvar("$a(1)"),
@@ -922,7 +1331,7 @@ mod tests {
         assert_eq!(pvar("${a}b"), Ok((Word::VarRef("a".into()), "b".into())));
         assert_eq!(
             pvar("${ab"),
-            molt_err_uncompleted!("missing close-brace for variable name")
+            missing("missing close-brace for variable name", Delimiter::Brace, 1, 1)
         );
 
         // Braced var names with arrays
@@ -937,6 +1346,19 @@ mod tests {
         // Just a bare "$"
         assert_eq!(pvar("$"), Ok((Word::Value(Value::from("$")), "".into())));
         assert_eq!(pvar("$."), Ok((Word::Value(Value::from("$")), ".".into())));
+
+        // Array index with white space
+        assert_eq!(
+            pvar("$a(b c) d"),
+            Ok((
+                Word::ArrayRef("a".into(), Box::new(Word::Value(Value::from("b c")))),
+                " d".into()
+            ))
+        );
+
+        // Unterminated array index
+        assert_eq!(pvar("$a(b"), molt_err!("missing )"));
+        assert_eq!(pvar("$a(b c"), molt_err!("missing )"));
     }
 
     fn pvar(input: &str) -> Result<(Word, String), Exception> {
@@ -965,6 +1387,8 @@ mod tests {
         assert_eq!(parse_varname_literal("a()"), array("a", ""));
         assert_eq!(parse_varname_literal("%(()"), array("%", "("));
         assert_eq!(parse_varname_literal("%())"), array("%", ")"));
+        assert_eq!(parse_varname_literal("a(☺)"), array("a", "☺"));
+        assert_eq!(parse_varname_literal("é(😀é)"), array("é", "😀é"));
     }
 
     fn scalar(name: &str) -> VarName {
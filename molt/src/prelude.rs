@@ -1,20 +1,60 @@
 pub use crate::commands::{
-    cmd_append, cmd_array, cmd_assert_eq, cmd_break, cmd_catch, cmd_continue, cmd_dict,
-    cmd_error, cmd_exit, cmd_expr, cmd_for, cmd_foreach, cmd_global, cmd_if, cmd_incr,
-    cmd_info, cmd_join, cmd_lappend, cmd_lindex, cmd_list, cmd_llength, cmd_parse,
-    cmd_pclear, cmd_pdump, cmd_proc, cmd_puts, cmd_rename, cmd_return, cmd_set,
-    cmd_source, cmd_string, cmd_throw, cmd_time, cmd_unset, cmd_while, _APPEND, _ARRAY,
-    _ASSERT_EQ, _BREAK, _CATCH, _CONTINUE, _DICT, _ERROR, _EXIT, _EXPR, _FOR, _FOREACH,
-    _GLOBAL, _IF, _INCR, _INFO, _JOIN, _LAPPEND, _LINDEX, _LIST, _LLENGTH, _PARSE,
-    _PCLEAR, _PDUMP, _PROC, _PUTS, _RENAME, _RETURN, _SET, _SOURCE, _STRING, _THROW,
-    _TIME, _UNSET, _WHILE,
+    cmd_append, cmd_array, cmd_assert_code, cmd_assert_eq, cmd_assert_err, cmd_break,
+    cmd_catch, cmd_concat, cmd_continue, cmd_dict, cmd_error, cmd_eval, cmd_exit,
+    cmd_expr, cmd_flush, cmd_for, cmd_foreach, cmd_format, cmd_global, cmd_if, cmd_incr,
+    cmd_info, cmd_join, cmd_lappend, cmd_lindex, cmd_linsert, cmd_list, cmd_llength,
+    cmd_lmap, cmd_lrange, cmd_lrepeat, cmd_lreplace, cmd_lreverse, cmd_lsearch,
+    cmd_lsort, cmd_parse, cmd_pclear, cmd_pdump, cmd_proc, cmd_puts, cmd_rename,
+    cmd_return, cmd_scan, cmd_set, cmd_source, cmd_string, cmd_subst, cmd_switch,
+    cmd_tailcall, cmd_throw, cmd_time, cmd_unset, cmd_uplevel, cmd_upvar, cmd_while,
+    _APPEND, _ARRAY, _ASSERT_CODE, _ASSERT_EQ, _ASSERT_ERR, _BREAK, _CATCH, _CONCAT,
+    _CONTINUE, _DICT, _ERROR, _EVAL, _EXIT, _EXPR, _FLUSH, _FOR, _FOREACH, _FORMAT,
+    _GLOBAL, _IF, _INCR, _INFO, _JOIN, _LAPPEND, _LINDEX, _LINSERT, _LIST, _LLENGTH,
+    _LMAP, _LRANGE, _LREPEAT, _LREPLACE, _LREVERSE, _LSEARCH, _LSORT, _PARSE, _PCLEAR,
+    _PDUMP, _PROC, _PUTS, _RENAME, _RETURN, _SCAN, _SET, _SOURCE, _STRING, _SUBST,
+    _SWITCH, _TAILCALL, _THROW, _TIME, _UNSET, _UPLEVEL, _UPVAR, _WHILE,
 };
 
 pub use crate::{
-    check_args, gen_command, gen_subcommand,
-    interp::{Command, CommandType, Interp},
-    join_helps, join_helps_subcmd, join_strings, molt_err, molt_err_help, molt_ok,
-    test_harness::{test_cmd, test_harness, TestCtx},
+    binary::{cmd_binary, _BINARY},
+    cancel::CancelHandle,
+    channel::Writer,
+    check_args,
+    child::{cmd_alias, cmd_interp, _ALIAS, _INTERP},
+    clock::{cmd_clock, _CLOCK},
+    define_object,
+    encoding::{cmd_encoding, Encoding, _ENCODING},
+    event::{cmd_after, cmd_vwait, _AFTER, _VWAIT},
+    expr::{Datum, DatumType},
+    gen_command, gen_command_minimal, gen_subcommand,
+    interp::{
+        Command, CommandDestructor, CommandFunc, CommandInfo, CommandSet, CommandType,
+        ContextCommandFunc, EmbeddedHelp, Interp, InterpBuilder, MathFunc, ScopeGuard,
+        UnknownFn,
+    },
+    join_helps, join_helps_subcmd, join_strings,
+    loader::{FsLoader, ScriptLoader},
+    molt_err, molt_err_help, molt_ok,
+    namespace::{cmd_namespace, _NAMESPACE},
+    object::{add_object, ObjectDestroy, ObjectMethod},
+    package::{cmd_package, PackageInitFn, _PACKAGE},
+    profile::{cmd_profile, ProfileEntry, ProfileReport, ProfileSort, _PROFILE},
+    test_harness::{test_cmd, test_harness, HasTestCtx, TestCtx},
+    trace::{cmd_trace, ExecOp, ExecTraceFn, TraceFn, TraceId, TraceOp, _TRACE},
 };
 
+#[cfg(feature = "file-io")]
+pub use crate::channel::{
+    cmd_close, cmd_eof, cmd_gets, cmd_open, cmd_read, _CLOSE, _EOF, _GETS, _OPEN, _READ,
+};
+
+#[cfg(feature = "exec")]
+pub use crate::exec::{cmd_exec, _EXEC};
+
+#[cfg(feature = "file-io")]
+pub use crate::file::{cmd_file, cmd_glob, _FILE, _GLOB};
+
+#[cfg(feature = "regexp")]
+pub use crate::regexp::{cmd_regexp, cmd_regsub, _REGEXP, _REGSUB};
+
 pub use crate::types::*;
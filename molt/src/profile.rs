@@ -0,0 +1,203 @@
+//! Profile
+//!
+//! The `profile` command and the [`ProfileReport`] type.  While profiling is on, the
+//! interpreter records the number of calls to each command and proc and the time spent in
+//! them; see [`Interp::set_profiling`] and [`Interp::profile_report`].
+//!
+//! A command's time includes the time spent in the commands it calls, so a proc's time
+//! includes that of its body, and a recursive proc's calls are counted at every level.
+//!
+//! [`Interp::set_profiling`]: ../interp/struct.Interp.html#method.set_profiling
+//! [`Interp::profile_report`]: ../interp/struct.Interp.html#method.profile_report
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use std::time::Duration;
+
+/// The name of the `profile` command.
+pub const _PROFILE: &str = "profile";
+
+/// The profile data recorded for one command or proc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileEntry {
+    /// The command's name.
+    pub name: String,
+    /// The number of calls.
+    pub count: u64,
+    /// The total time spent in the calls.
+    pub total: Duration,
+}
+
+impl ProfileEntry {
+    /// Returns the average time per call.
+    pub fn average(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.total.as_nanos() / self.count as u128) as u64)
+        }
+    }
+}
+
+/// How to order the entries of a [`ProfileReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileSort {
+    /// By total time, most first.
+    Time,
+    /// By number of calls, most first.
+    Count,
+}
+
+/// The profile data recorded by the interpreter, one entry per command; see
+/// [`Interp::profile_report`](../interp/struct.Interp.html#method.profile_report).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    entries: Vec<ProfileEntry>,
+}
+
+impl ProfileReport {
+    /// Creates a report from its entries, ordered by total time.
+    pub(crate) fn new(entries: Vec<ProfileEntry>) -> Self {
+        let mut report = Self { entries };
+        report.sort_by(ProfileSort::Time);
+        report
+    }
+
+    /// Returns the entries, in the report's order.
+    pub fn entries(&self) -> &[ProfileEntry] {
+        &self.entries
+    }
+
+    /// Returns the entry for the named command, if it was called.
+    pub fn get(&self, name: &str) -> Option<&ProfileEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Orders the entries, breaking ties by name.
+    pub fn sort_by(&mut self, sort: ProfileSort) {
+        self.entries.sort_by(|a, b| {
+            let order = match sort {
+                ProfileSort::Time => b.total.cmp(&a.total),
+                ProfileSort::Count => b.count.cmp(&a.count),
+            };
+            order.then_with(|| a.name.cmp(&b.name))
+        });
+    }
+
+    /// Is the report empty?
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// # profile *subcommand* ?*arg* ...?
+///
+/// Records how often each command is called and how long it takes.
+///
+/// * `profile on`, `profile off`: starts and stops recording.
+/// * `profile clear`: discards the data recorded so far.
+/// * `profile report ?-sortby time|count?`: returns the data as a list of entries, each a
+///   list of the command's name, the number of calls, and the total time in microseconds.
+pub fn cmd_profile<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("clear", cmd_profile_clear),
+            ("off", cmd_profile_off),
+            ("on", cmd_profile_on),
+            ("report", cmd_profile_report),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// # profile clear
+fn cmd_profile_clear<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    interp.profile_clear();
+    molt_ok!()
+}
+
+/// # profile off
+fn cmd_profile_off<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    interp.set_profiling(false);
+    molt_ok!()
+}
+
+/// # profile on
+fn cmd_profile_on<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    interp.set_profiling(true);
+    molt_ok!()
+}
+
+/// # profile report ?-sortby time|count?
+fn cmd_profile_report<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 4, "?-sortby time|count?")?;
+
+    let sort = match argv.get(2).map(Value::as_str) {
+        None => ProfileSort::Time,
+        Some("-sortby") if argv.len() == 4 => match argv[3].as_str() {
+            "time" => ProfileSort::Time,
+            "count" => ProfileSort::Count,
+            other => {
+                return molt_err!("bad sort key \"{}\": must be time or count", other)
+            }
+        },
+        Some("-sortby") => return molt_err!("missing value for -sortby"),
+        Some(other) => return molt_err!("bad option \"{}\": must be -sortby", other),
+    };
+
+    let mut report = interp.profile_report();
+    report.sort_by(sort);
+
+    let entries: MoltList = report
+        .entries()
+        .iter()
+        .map(|entry| {
+            Value::from(vec![
+                Value::from(entry.name.as_str()),
+                Value::from(entry.count as MoltInt),
+                Value::from(entry.total.as_micros() as MoltInt),
+            ])
+        })
+        .collect();
+    molt_ok!(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, count: u64, micros: u64) -> ProfileEntry {
+        ProfileEntry {
+            name: name.into(),
+            count,
+            total: Duration::from_micros(micros),
+        }
+    }
+
+    #[test]
+    fn test_report_order() {
+        let mut report = ProfileReport::new(vec![
+            entry("a", 1, 50),
+            entry("b", 5, 10),
+            entry("c", 5, 20),
+        ]);
+        let names = |r: &ProfileReport| -> Vec<String> {
+            r.entries().iter().map(|e| e.name.clone()).collect()
+        };
+        assert_eq!(names(&report), vec!["a", "c", "b"]);
+
+        report.sort_by(ProfileSort::Count);
+        assert_eq!(names(&report), vec!["b", "c", "a"]);
+        assert_eq!(report.get("c").unwrap().average(), Duration::from_micros(4));
+        assert!(report.get("d").is_none());
+    }
+}
@@ -0,0 +1,314 @@
+//! Regular Expressions
+//!
+//! This module, enabled by the `regexp` feature, defines the `regexp` and `regsub`
+//! commands, which match and substitute regular expressions using the
+//! [`regex`](https://docs.rs/regex) crate.
+//!
+//! Add the commands to an interpreter as extra native commands:
+//!
+//! ```
+//! # use molt_forked::prelude::*;
+//! let command = gen_command!((), [(_REGEXP, cmd_regexp), (_REGSUB, cmd_regsub)], []);
+//! ```
+//!
+//! The expressions use the `regex` crate's syntax, which is close to, but not the same
+//! as, Standard TCL's; in particular, there are no back references.
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use regex::{Captures, Regex, RegexBuilder};
+
+/// The name of the `regexp` command.
+pub const _REGEXP: &str = "regexp";
+
+/// The name of the `regsub` command.
+pub const _REGSUB: &str = "regsub";
+
+/// The options shared by `regexp` and `regsub`.
+#[derive(Default)]
+struct MatchOptions<'a> {
+    nocase: bool,
+    all: bool,
+    start: Option<&'a Value>,
+}
+
+/// # regexp ?*options*? *exp* *string* ?*matchVar*? ?*subMatchVar* ...?
+///
+/// Matches the regular expression against the string, returning 1 if it matches and 0
+/// otherwise.  If *matchVar* is given, it is set to the matched text, and each
+/// *subMatchVar* to the text matched by the corresponding group, or the empty string if
+/// the group didn't match.  The options are:
+///
+/// * `-all`: matches as many times as possible, returning the number of matches; the
+///   variables are set from the last match.
+/// * `-indices`: sets the variables to the `first last` character indices of the matched
+///   text rather than to the text itself; `-1 -1` for a group that didn't match.
+/// * `-inline`: returns the match and submatches as a list rather than setting
+///   variables; with `-all`, returns those of every match, concatenated.
+/// * `-nocase`: matches case-insensitively.
+/// * `-start index`: starts matching at the character index, which may be `end` or
+///   `end-N`.  A `^` won't match there unless the index is 0.
+/// * `--`: marks the end of the options.
+///
+/// ## TCL Liens
+///
+/// * The `-about`, `-expanded`, `-line`, `-linestop`, and `-lineanchor` options aren't
+///   supported.
+pub fn cmd_regexp<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "?-option ...? exp string ?matchVar? ?subMatchVar ...?";
+    check_args(1, argv, 3, 0, USAGE)?;
+
+    // FIRST, get the options.  The expression and the string follow them.
+    let mut options = MatchOptions::default();
+    let mut indices = false;
+    let mut inline = false;
+    let mut argi = 1;
+
+    while argi < argv.len() - 2 && argv[argi].as_str().starts_with('-') {
+        match argv[argi].as_str() {
+            "-indices" => indices = true,
+            "-inline" => inline = true,
+            "--" => {
+                argi += 1;
+                break;
+            }
+            opt => {
+                if !parse_option(&mut options, argv, &mut argi)? {
+                    return molt_err!(
+                        "bad option \"{}\": must be -all, -indices, -inline, -nocase, \
+                         -start, or --",
+                        opt
+                    );
+                }
+            }
+        }
+        argi += 1;
+    }
+
+    if argv.len() < argi + 2 {
+        return molt_err!("wrong # args: should be \"regexp {}\"", USAGE);
+    }
+
+    let regex = compile(&argv[argi], options.nocase)?;
+    let string = argv[argi + 1].as_str();
+    let vars = &argv[argi + 2..];
+
+    if inline && !vars.is_empty() {
+        return molt_err!("regexp match variables not allowed when using -inline");
+    }
+
+    // NEXT, find the matches.
+    let start = start_offset(string, options.start)?;
+    let matches = find_matches(&regex, string, start, options.all);
+
+    // NEXT, describe each match as a list of the match and its submatches.
+    let describe = |caps: &Captures| -> MoltList {
+        let count = if inline { regex.captures_len() } else { vars.len() };
+        (0..count)
+            .map(|i| match caps.get(i) {
+                Some(m) if indices => {
+                    let first = char_index(string, m.start());
+                    let last = first + m.as_str().chars().count() as MoltInt - 1;
+                    Value::from(&[Value::from(first), Value::from(last)][..])
+                }
+                Some(m) => Value::from(m.as_str()),
+                None if indices => Value::from(&[Value::from(-1), Value::from(-1)][..]),
+                None => Value::empty(),
+            })
+            .collect()
+    };
+
+    if inline {
+        return molt_ok!(matches.iter().flat_map(describe).collect::<MoltList>());
+    }
+
+    if let Some(last) = matches.last() {
+        for (var, value) in vars.iter().zip(describe(last)) {
+            interp.set_var(var, value)?;
+        }
+    }
+
+    if options.all {
+        molt_ok!(matches.len() as MoltInt)
+    } else {
+        molt_ok!(!matches.is_empty())
+    }
+}
+
+/// # regsub ?*options*? *exp* *string* *subSpec* ?*varName*?
+///
+/// Replaces the first match of the regular expression in the string with *subSpec*,
+/// and returns the result.  In *subSpec*, `&` and `\0` stand for the matched text, `\1`
+/// through `\9` for the text matched by the corresponding group, and `\&` and `\\` for
+/// a literal `&` and `\`.  If *varName* is given, the result is stored in it instead, and
+/// the number of replacements is returned.  The options are:
+///
+/// * `-all`: replaces every match.
+/// * `-nocase`: matches case-insensitively.
+/// * `-start index`: starts matching at the character index, which may be `end` or
+///   `end-N`.
+/// * `--`: marks the end of the options.
+///
+/// ## TCL Liens
+///
+/// * The `-command`, `-expanded`, `-line`, `-linestop`, and `-lineanchor` options aren't
+///   supported.
+pub fn cmd_regsub<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    const USAGE: &str = "?-option ...? exp string subSpec ?varName?";
+    check_args(1, argv, 4, 0, USAGE)?;
+
+    // FIRST, get the options.  The expression, string, and subSpec follow them.
+    let mut options = MatchOptions::default();
+    let mut argi = 1;
+
+    while argi < argv.len() - 3 && argv[argi].as_str().starts_with('-') {
+        if argv[argi].as_str() == "--" {
+            argi += 1;
+            break;
+        }
+        if !parse_option(&mut options, argv, &mut argi)? {
+            return molt_err!(
+                "bad option \"{}\": must be -all, -nocase, -start, or --",
+                argv[argi]
+            );
+        }
+        argi += 1;
+    }
+
+    if argv.len() < argi + 3 || argv.len() > argi + 4 {
+        return molt_err!("wrong # args: should be \"regsub {}\"", USAGE);
+    }
+
+    let regex = compile(&argv[argi], options.nocase)?;
+    let string = argv[argi + 1].as_str();
+    let spec = argv[argi + 2].as_str();
+
+    // NEXT, replace the matches.
+    let start = start_offset(string, options.start)?;
+    let matches = find_matches(&regex, string, start, options.all);
+    let mut result = String::with_capacity(string.len());
+    let mut copied = 0;
+
+    for caps in &matches {
+        let m = caps.get(0).expect("group 0 always matches");
+        result.push_str(&string[copied..m.start()]);
+        substitute(&mut result, spec, caps);
+        copied = m.end();
+    }
+    result.push_str(&string[copied..]);
+
+    match argv.get(argi + 3) {
+        Some(var) => {
+            interp.set_var(var, Value::from(result))?;
+            molt_ok!(matches.len() as MoltInt)
+        }
+        None => molt_ok!(result),
+    }
+}
+
+/// Parses an option shared by `regexp` and `regsub`, advancing past its value, if any.
+/// Returns false if the option isn't one of them.
+fn parse_option<'a>(
+    options: &mut MatchOptions<'a>,
+    argv: &'a [Value],
+    argi: &mut usize,
+) -> Result<bool, Exception> {
+    match argv[*argi].as_str() {
+        "-all" => options.all = true,
+        "-nocase" => options.nocase = true,
+        "-start" => {
+            *argi += 1;
+            match argv.get(*argi) {
+                Some(index) => options.start = Some(index),
+                None => return molt_err!("wrong # args: -start requires an index"),
+            }
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Compiles the expression.
+fn compile(exp: &Value, nocase: bool) -> Result<Regex, Exception> {
+    match RegexBuilder::new(exp.as_str()).case_insensitive(nocase).build() {
+        Ok(regex) => Ok(regex),
+        Err(err) => molt_err!("couldn't compile regular expression pattern: {}", err),
+    }
+}
+
+/// Returns the byte offset in the string of the `-start` character index.
+fn start_offset(string: &str, start: Option<&Value>) -> Result<usize, Exception> {
+    let Some(start) = start else {
+        return Ok(0);
+    };
+
+    let len = string.chars().count();
+    let index = crate::commands::parse_index(start, len)?.max(0) as usize;
+
+    Ok(string.char_indices().nth(index).map_or(string.len(), |(i, _)| i))
+}
+
+/// Returns the character index of a byte offset in the string.
+fn char_index(string: &str, offset: usize) -> MoltInt {
+    string[..offset].chars().count() as MoltInt
+}
+
+/// Finds the first match of the expression in the string at or after the byte offset,
+/// or all of them.
+fn find_matches<'s>(
+    regex: &Regex,
+    string: &'s str,
+    start: usize,
+    all: bool,
+) -> Vec<Captures<'s>> {
+    let mut matches = Vec::new();
+    let mut at = start;
+
+    while at <= string.len() {
+        let Some(caps) = regex.captures_at(string, at) else {
+            break;
+        };
+        let m = caps.get(0).expect("group 0 always matches");
+
+        // After an empty match, start the next search a character later.
+        at = if m.is_empty() {
+            m.end() + string[m.end()..].chars().next().map_or(1, char::len_utf8)
+        } else {
+            m.end()
+        };
+
+        matches.push(caps);
+
+        if !all {
+            break;
+        }
+    }
+
+    matches
+}
+
+/// Appends the `regsub` substitution spec to the result, replacing `&` and `\N` with the
+/// match and its groups.
+fn substitute(result: &mut String, spec: &str, caps: &Captures) {
+    let group = |i: usize| caps.get(i).map_or("", |m| m.as_str());
+    let mut chars = spec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => result.push_str(group(0)),
+            '\\' => match chars.peek() {
+                Some(&d) if d.is_ascii_digit() => {
+                    chars.next();
+                    result.push_str(group(d as usize - '0' as usize));
+                }
+                Some(&d) if d == '&' || d == '\\' => {
+                    chars.next();
+                    result.push(d);
+                }
+                _ => result.push('\\'),
+            },
+            _ => result.push(c),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! The pseudo-random number generator behind the `rand()` and `srand()` math functions.
+//!
+//! This is xoshiro256**, seeded via SplitMix64.  Each `Interp` owns its own generator,
+//! so that interpreters don't share sequences and a seeded interpreter is reproducible.
+
+use crate::types::{MoltFloat, MoltInt};
+cfg_if::cfg_if! {
+  if #[cfg(feature = "wasm")] {
+    use wasm_timer::{SystemTime, UNIX_EPOCH};
+  }else{
+    use std::time::{SystemTime, UNIX_EPOCH};
+  }
+}
+
+/// A small, fast, seedable pseudo-random number generator.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    /// Creates a generator seeded from the system clock.
+    pub(crate) fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        // Mix in the generator's address so that interpreters created in the same
+        // clock tick still get different sequences.
+        let mut rng = Self { state: [0; 4] };
+        let addr = &rng as *const Self as u64;
+        rng.seed((nanos ^ addr.rotate_left(32)) as MoltInt);
+        rng
+    }
+
+    /// Reseeds the generator; the same seed always produces the same sequence.
+    pub(crate) fn seed(&mut self, seed: MoltInt) {
+        let mut x = seed as u64;
+        for word in self.state.iter_mut() {
+            x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            *word = z ^ (z >> 31);
+        }
+    }
+
+    /// Returns the next 64 random bits.
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    /// Returns a float uniformly distributed in [0, 1).
+    pub(crate) fn next_float(&mut self) -> MoltFloat {
+        // The top 53 bits fill the mantissa exactly, so 1.0 can't be produced.
+        (self.next_u64() >> 11) as MoltFloat * (1.0 / (1u64 << 53) as MoltFloat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_is_reproducible() {
+        let mut a = Rng::new();
+        let mut b = Rng::new();
+        a.seed(42);
+        b.seed(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_float(), b.next_float());
+        }
+
+        b.seed(43);
+        assert_ne!(a.next_float(), b.next_float());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new();
+        rng.seed(0);
+
+        for _ in 0..10_000 {
+            let x = rng.next_float();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+}
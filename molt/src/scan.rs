@@ -0,0 +1,482 @@
+//! String Scanning
+//!
+//! The `sscanf`-style parsing used by the `scan` command, the complement of the `format`
+//! module.  A format string contains whitespace, which matches any amount of whitespace
+//! in the input, including none; other literal text, which must match exactly; and
+//! conversion specifiers:
+//!
+//! ```text
+//! %[*][n$][width][size]conversion
+//! ```
+//!
+//! * `*`: the field is scanned but not assigned to a variable.
+//! * `n$`: assigns the field to the *n*th variable, counting from 1, rather than the next
+//!   one.  If one specifier uses it, all assigned specifiers must.
+//! * The width is the maximum number of characters to scan for the field.
+//! * The size modifiers `h`, `l`, `ll`, and `L` are accepted and ignored; integers are always
+//!   64 bits.
+//! * Conversions: `d` (decimal), `u` (unsigned decimal), `o` (octal), `x` and `X`
+//!   (hexadecimal), `b` (binary), `i` (an integer in the radix given by its prefix: `0x`,
+//!   `0o` or `0`, `0b`, or decimal), `c` (a single character, as its code point), `s` (a
+//!   run of non-whitespace characters), `f`, `e`, and `g` (a floating-point number),
+//!   `[chars]` and `[^chars]` (a run of characters in or not in the set), `n` (the number
+//!   of characters scanned so far), and `%` (a literal `%`).
+//!
+//! All conversions but `c`, `[`, and `n` skip leading whitespace in the input.
+
+use crate::types::*;
+use crate::value::Value;
+
+/// The result of scanning a string.
+pub(crate) struct Scanned {
+    /// The value for each variable, or `None` if scanning stopped before its conversion.
+    pub values: Vec<Option<Value>>,
+
+    /// The number of conversions performed, including any not assigned.
+    pub conversions: MoltInt,
+
+    /// Whether the input ran out before a conversion or literal could be matched.
+    pub underflow: bool,
+}
+
+/// An element of a parsed format string.
+enum Directive {
+    /// Whitespace, which skips any whitespace in the input.
+    Space,
+
+    /// A literal character, which must match the input.
+    Literal(char),
+
+    /// A conversion, assigned to the variable with the index, if any.
+    Conversion { var: Option<usize>, width: Option<usize>, kind: Kind },
+}
+
+/// The kind of a conversion.
+enum Kind {
+    /// An integer in the radix, or in the radix given by its prefix if the radix is 0.
+    Int(u32),
+    Char,
+    Float,
+    Str,
+
+    /// A run of characters in the set, or not in it if the flag is true.
+    Set(CharSet, bool),
+    Count,
+}
+
+/// The characters and character ranges of a `%[...]` conversion.
+struct CharSet {
+    ranges: Vec<(char, char)>,
+}
+
+impl CharSet {
+    fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+    }
+}
+
+/// Scans the input according to the format string.  If `num_vars` is 0 the values are
+/// returned inline, and there is one value for each assigned conversion; otherwise there
+/// must be one assigned conversion for each variable.
+pub(crate) fn scan(
+    input: &str,
+    fmt: &str,
+    num_vars: usize,
+) -> Result<Scanned, Exception> {
+    let (directives, num_values) = parse_format(fmt, num_vars)?;
+
+    let input: Vec<char> = input.chars().collect();
+    let mut pos = 0;
+    let mut scanned = Scanned {
+        values: vec![None; num_values],
+        conversions: 0,
+        underflow: false,
+    };
+
+    for directive in &directives {
+        let (var, width, kind) = match directive {
+            Directive::Space => {
+                skip_space(&input, &mut pos);
+                continue;
+            }
+            Directive::Literal(c) => {
+                match input.get(pos) {
+                    None => scanned.underflow = true,
+                    Some(ch) if ch == c => {
+                        pos += 1;
+                        continue;
+                    }
+                    Some(_) => (),
+                }
+                break;
+            }
+            Directive::Conversion { var, width, kind } => (var, width, kind),
+        };
+
+        // FIRST, skip leading whitespace, if the conversion calls for it.
+        if !matches!(kind, Kind::Char | Kind::Set(..) | Kind::Count) {
+            skip_space(&input, &mut pos);
+        }
+
+        if pos == input.len() && !matches!(kind, Kind::Count) {
+            scanned.underflow = true;
+            break;
+        }
+
+        // NEXT, scan the field from the input, limited to the width.
+        let end = width.map_or(input.len(), |w| (pos + w).min(input.len()));
+        let field = &input[pos..end];
+
+        let (value, len) = match kind {
+            Kind::Int(radix) => match scan_int(field, *radix)? {
+                Some((int, len)) => (Value::from(int), len),
+                None => break,
+            },
+            Kind::Char => (Value::from(field[0] as MoltInt), 1),
+            Kind::Float => match scan_float(field) {
+                Some((flt, len)) => (Value::from(flt), len),
+                None => break,
+            },
+            Kind::Str => {
+                let len = field.iter().take_while(|c| !c.is_whitespace()).count();
+                (Value::from(field[..len].iter().collect::<String>()), len)
+            }
+            Kind::Set(set, negated) => {
+                let len =
+                    field.iter().take_while(|&&c| set.contains(c) != *negated).count();
+                if len == 0 {
+                    break;
+                }
+                (Value::from(field[..len].iter().collect::<String>()), len)
+            }
+            Kind::Count => (Value::from(pos as MoltInt), 0),
+        };
+
+        pos += len;
+        scanned.conversions += 1;
+
+        if let Some(var) = var {
+            scanned.values[*var] = Some(value);
+        }
+    }
+
+    Ok(scanned)
+}
+
+/// Parses and validates the format string, returning its directives and the number of
+/// values they assign.
+fn parse_format(
+    fmt: &str,
+    num_vars: usize,
+) -> Result<(Vec<Directive>, usize), Exception> {
+    let mut directives = Vec::new();
+    let mut chars = fmt.chars().peekable();
+
+    // Whether the specifiers use `n$` positions; unknown until the first assigned one.
+    let mut positional: Option<bool> = None;
+    let mut assigned: Vec<bool> = Vec::new();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            directives.push(Directive::Space);
+            continue;
+        }
+
+        if c != '%' {
+            directives.push(Directive::Literal(c));
+            continue;
+        }
+
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            directives.push(Directive::Literal('%'));
+            continue;
+        }
+
+        // FIRST, the suppression flag or the position.  Digits here are either a position
+        // or the width.
+        let suppress = chars.peek() == Some(&'*');
+        if suppress {
+            chars.next();
+        }
+
+        let mut digits = String::new();
+        while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+            digits.push(d);
+            chars.next();
+        }
+
+        let mut var = None;
+
+        if !suppress && chars.peek() == Some(&'$') {
+            chars.next();
+            if positional == Some(false) {
+                return molt_err!("cannot mix \"%\" and \"%n$\" conversion specifiers");
+            }
+            positional = Some(true);
+
+            let n: usize = digits.parse().unwrap_or(0);
+            if n == 0 || (num_vars > 0 && n > num_vars) {
+                return molt_err!("\"%n$\" argument index out of range");
+            }
+            if assigned.len() < n {
+                assigned.resize(n, false);
+            }
+            if assigned[n - 1] {
+                return molt_err!(
+                    "variable is assigned by multiple \"%n$\" conversion specifiers"
+                );
+            }
+            assigned[n - 1] = true;
+            var = Some(n - 1);
+
+            digits.clear();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                digits.push(d);
+                chars.next();
+            }
+        } else if !suppress {
+            if positional == Some(true) {
+                return molt_err!("cannot mix \"%\" and \"%n$\" conversion specifiers");
+            }
+            positional = Some(false);
+            var = Some(assigned.len());
+        }
+
+        // NEXT, the width.
+        let width = if digits.is_empty() {
+            None
+        } else {
+            match digits.parse::<usize>() {
+                Ok(0) | Err(_) => None,
+                Ok(width) => Some(width),
+            }
+        };
+
+        // NEXT, skip any size modifier.
+        while let Some('h' | 'l' | 'L') = chars.peek() {
+            chars.next();
+        }
+
+        // NEXT, the conversion.
+        let Some(conversion) = chars.next() else {
+            return molt_err!("unterminated conversion specifier in format string");
+        };
+
+        let kind = match conversion {
+            'd' | 'u' => Kind::Int(10),
+            'o' => Kind::Int(8),
+            'x' | 'X' => Kind::Int(16),
+            'b' => Kind::Int(2),
+            'i' => Kind::Int(0),
+            'c' => {
+                if width.is_some() {
+                    return molt_err!(
+                        "field width may not be specified in %c conversion"
+                    );
+                }
+                Kind::Char
+            }
+            'f' | 'e' | 'E' | 'g' | 'G' => Kind::Float,
+            's' => Kind::Str,
+            '[' => parse_set(&mut chars)?,
+            'n' => Kind::Count,
+            _ => return molt_err!("bad scan conversion character \"{}\"", conversion),
+        };
+
+        if positional == Some(false) && var.is_some() {
+            assigned.push(true);
+        }
+
+        directives.push(Directive::Conversion { var, width, kind });
+    }
+
+    // NEXT, make sure the conversions and variables correspond.
+    if num_vars > 0 && positional != Some(true) && assigned.len() != num_vars {
+        return molt_err!("different numbers of variable names and field specifiers");
+    }
+
+    if num_vars > 0 && positional == Some(true) {
+        assigned.resize(num_vars, false);
+    }
+
+    if assigned.contains(&false) {
+        return molt_err!("variable is not assigned by any conversion specifiers");
+    }
+
+    Ok((directives, assigned.len()))
+}
+
+/// Parses the character set of a `%[...]` conversion, following the `[`.  A `]` first in
+/// the set, after any `^`, is part of the set, as is a `-` first or last.
+fn parse_set(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Result<Kind, Exception> {
+    let negated = chars.peek() == Some(&'^');
+    if negated {
+        chars.next();
+    }
+
+    let mut ranges = Vec::new();
+    let mut first = true;
+
+    loop {
+        let Some(c) = chars.next() else {
+            return molt_err!("unmatched [ in format string");
+        };
+
+        if c == ']' && !first {
+            break;
+        }
+        first = false;
+
+        if chars.peek() == Some(&'-') {
+            chars.next();
+            match chars.peek() {
+                Some(&hi) if hi != ']' => {
+                    chars.next();
+                    ranges.push((c.min(hi), c.max(hi)));
+                }
+                _ => {
+                    ranges.push((c, c));
+                    ranges.push(('-', '-'));
+                }
+            }
+        } else {
+            ranges.push((c, c));
+        }
+    }
+
+    Ok(Kind::Set(CharSet { ranges }, negated))
+}
+
+/// Skips whitespace in the input.
+fn skip_space(input: &[char], pos: &mut usize) {
+    while input.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+/// Scans an optionally signed integer in the radix from the start of the field, returning
+/// it and the number of characters scanned, or `None` if there are no digits.  A radix
+/// of 0 takes the radix from the integer's prefix; a radix of 16 allows a `0x` prefix.
+fn scan_int(field: &[char], radix: u32) -> Result<Option<(MoltInt, usize)>, Exception> {
+    let mut i = 0;
+    let negative = field.first() == Some(&'-');
+    if matches!(field.first(), Some('-' | '+')) {
+        i += 1;
+    }
+
+    // The prefix only counts if a digit follows it; "0x" alone scans as 0.
+    let has_prefix = |letters: &str, radix: u32| {
+        field.get(i) == Some(&'0')
+            && field.get(i + 1).is_some_and(|c| letters.contains(*c))
+            && field.get(i + 2).is_some_and(|c| c.is_digit(radix))
+    };
+
+    let radix = match radix {
+        0 if has_prefix("xX", 16) => {
+            i += 2;
+            16
+        }
+        0 if has_prefix("oO", 8) => {
+            i += 2;
+            8
+        }
+        0 if has_prefix("bB", 2) => {
+            i += 2;
+            2
+        }
+        0 if field.get(i) == Some(&'0') => 8,
+        0 => 10,
+        16 if has_prefix("xX", 16) => {
+            i += 2;
+            16
+        }
+        radix => radix,
+    };
+
+    let digits: String = field[i..].iter().take_while(|c| c.is_digit(radix)).collect();
+    if digits.is_empty() {
+        return Ok(None);
+    }
+
+    let len = i + digits.chars().count();
+
+    // Integers are 64 bits; an unsigned integer too large for MoltInt wraps around.
+    let magnitude = match u64::from_str_radix(&digits, radix) {
+        Ok(magnitude) => magnitude,
+        Err(_) => return molt_err!("integer value too large to represent"),
+    };
+    let int = magnitude as MoltInt;
+
+    Ok(Some((if negative { int.wrapping_neg() } else { int }, len)))
+}
+
+/// Scans a floating-point number from the start of the field, returning it and the number
+/// of characters scanned, or `None` if there is no number.
+fn scan_float(field: &[char]) -> Option<(MoltFloat, usize)> {
+    let digits_from = |i: usize| {
+        field[i.min(field.len())..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count()
+    };
+
+    let mut i = 0;
+    if matches!(field.first(), Some('-' | '+')) {
+        i += 1;
+    }
+
+    let int_digits = digits_from(i);
+    i += int_digits;
+
+    let mut frac_digits = 0;
+    if field.get(i) == Some(&'.') {
+        frac_digits = digits_from(i + 1);
+        if int_digits + frac_digits > 0 {
+            i += 1 + frac_digits;
+        }
+    }
+
+    if int_digits + frac_digits == 0 {
+        return None;
+    }
+
+    // The exponent only counts if a digit follows it.
+    if matches!(field.get(i), Some('e' | 'E')) {
+        let mut j = i + 1;
+        if matches!(field.get(j), Some('-' | '+')) {
+            j += 1;
+        }
+        let exp_digits = digits_from(j);
+        if exp_digits > 0 {
+            i = j + exp_digits;
+        }
+    }
+
+    let text: String = field[..i].iter().collect();
+    text.parse().ok().map(|flt| (flt, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_underflow() {
+        let scanned = scan("", "%d", 0).unwrap();
+        assert!(scanned.underflow);
+        assert_eq!(scanned.conversions, 0);
+
+        let scanned = scan("12", "%d %d", 0).unwrap();
+        assert!(scanned.underflow);
+        assert_eq!(scanned.conversions, 1);
+
+        let scanned = scan("12", "%dx", 0).unwrap();
+        assert!(scanned.underflow);
+    }
+}
@@ -9,18 +9,30 @@
 //! the current are said to be higher in the stack, following Standard TCL practice (e.g.,
 //! `upvar`, `uplevel`).
 //!
+//! Namespace variables live in the global scope, under their qualified names without the
+//! leading `::`, e.g., `a::b::x`.  A qualified variable name always refers to one of
+//! these; an unqualified name does too in the scope pushed by `namespace eval`, whose
+//! variables are the namespace's.
+//!
+//! A global array variable can be linked to something outside the interpreter, e.g., the
+//! `env` array to the process's environment: an [`ArrayLink`]'s callbacks are told of each
+//! element that's set or unset, however it's done.
+//!
 //! Molt clients do not interact with this mechanism directly, but via the
 //! `Interp` (or the Molt language itself).
 
+use crate::namespace;
 use crate::types::Exception;
 use crate::types::MoltList;
 use crate::value::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::rc::Rc;
 
 /// A variable in a `Scope`.  If the variable is defined in the given `Scope`, it is a
 /// `Scalar` or an `Array`; if it is an alias to a variable in a higher scope (e.g., a global)
-/// then the `Upvar` gives the referenced scope.  The `New` variant is used transiently as
+/// then the `Upvar` gives the referenced scope and the variable's name there.  The `New` variant is used transiently as
 /// part of setting a variable for the first time.
 #[derive(Eq, PartialEq, Clone)]
 enum Var {
@@ -30,9 +42,9 @@ enum Var {
     /// An array variable, with its hash table from names to values.
     Array(HashMap<String, Value>),
 
-    /// An alias to a variable at a higher stack level, with the referenced stack level.
-    /// Note that aliases can chain.
-    Upvar(usize),
+    /// An alias to a variable at a higher stack level, with the referenced stack level and
+    /// the variable's name at that level.  Note that aliases can chain.
+    Upvar(usize, String),
 
     /// A variable that has just been created so that it can be set.
     New,
@@ -41,7 +53,7 @@ enum Var {
 impl Var {
     /// This is an upvar'd variable?
     fn is_upvar(&self) -> bool {
-        if let Var::Upvar(_) = self {
+        if let Var::Upvar(..) = self {
             true
         } else {
             false
@@ -54,7 +66,7 @@ impl Debug for Var {
         match self {
             Var::Scalar(value) => write!(f, "Var::Scalar({})", value.as_str()),
             Var::Array(_) => write!(f, "Var::Array(TODO)"),
-            Var::Upvar(level) => write!(f, "Var::Upvar({})", level),
+            Var::Upvar(level, name) => write!(f, "Var::Upvar({}, {})", level, name),
             Var::New => write!(f, "Var::New"),
         }
     }
@@ -67,20 +79,62 @@ impl Debug for Var {
 struct Scope {
     /// Vars in this scope by name.
     map: HashMap<String, Var>,
+
+    /// The scope's current namespace, e.g., `a::b`; empty for the global namespace.
+    namespace: Rc<str>,
+
+    /// Set for a scope pushed by `namespace eval`: its unqualified variables are the
+    /// namespace's.
+    is_namespace: bool,
 }
 
 impl Scope {
     /// Create a new empty scope.
     pub fn new() -> Self {
-        Scope { map: HashMap::new() }
+        Scope::default()
     }
 }
 
+/// The scopes above a given level, set aside by `ScopeStack::suspend_above` while a
+/// script runs in that level's scope, as `uplevel` does.
+#[derive(Debug)]
+pub(crate) struct SuspendedScopes {
+    /// The level that was made current.
+    level: usize,
+    scopes: Vec<Scope>,
+}
+
+/// The most popped scopes kept for reuse by `ScopeStack::push`.
+const MAX_FREE_SCOPES: usize = 64;
+
+/// Popped scopes whose tables have grown beyond this many slots aren't kept, so that
+/// one huge proc frame doesn't pin its memory for the life of the interpreter.
+const MAX_FREE_SCOPE_CAPACITY: usize = 256;
+
+/// Callbacks that mirror the changes to a global array variable's elements somewhere
+/// else, e.g., into the process's environment.  The variable itself is changed as usual.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ArrayLink {
+    /// Called with the index and new value of each element that's set.
+    pub on_set: fn(&str, &str),
+
+    /// Called with the index of each element that's unset, including when the whole
+    /// array is unset.
+    pub on_unset: fn(&str),
+}
+
 /// The scope stack: a stack of variable scopes corresponding to the Molt `proc`
 /// call stack.
+///
+/// Popped scopes are cleared and kept on a free list for reuse, so that steady-state
+/// proc calls don't allocate a new hash table for each frame.
 #[derive(Default, Debug, Clone)]
 pub(crate) struct ScopeStack {
     stack: Vec<Scope>,
+    free: Vec<Scope>,
+
+    /// The linked global arrays, by name.
+    links: HashMap<String, ArrayLink>,
 }
 
 impl ScopeStack {
@@ -90,7 +144,11 @@ impl ScopeStack {
     /// Creates a scope stack containing only scope `0`, the global scope.  This is usually
     /// done once, as part of creating an `Interp`.
     pub fn new() -> Self {
-        let mut ss = Self { stack: Vec::new() };
+        let mut ss = Self {
+            stack: Vec::new(),
+            free: Vec::new(),
+            links: HashMap::new(),
+        };
 
         ss.stack.push(Scope::new());
 
@@ -99,7 +157,7 @@ impl ScopeStack {
 
     /// Requires the value of the named scalar variable in the current scope.
     pub fn get(&self, name: &str) -> Result<Value, Exception> {
-        match self.var(self.current(), name) {
+        match self.var(self.current(), name)? {
             Some(Var::Scalar(value)) => Ok(value.clone()),
             Some(Var::Array(_)) => {
                 molt_err!("can't read \"{}\": variable is array", name)
@@ -111,7 +169,7 @@ impl ScopeStack {
 
     /// Requires the value of an array element given its variable name and index.
     pub fn get_elem(&self, name: &str, index: &str) -> Result<Value, Exception> {
-        match self.var(self.current(), name) {
+        match self.var(self.current(), name)? {
             Some(Var::Scalar(_)) => {
                 molt_err!("can't read \"{}({})\": variable isn't array", name, index)
             }
@@ -135,8 +193,8 @@ impl ScopeStack {
     /// if it doesn't already exist.  It's an error if the variable exists but is an array
     /// variable.
     pub fn set_global(&mut self, name: &str, val: Value) -> Result<(), Exception> {
-        match self.var_mut(0, name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+        match self.var_mut(0, name)? {
+            Some(Var::Upvar(..)) => unreachable!(),
             Some(Var::Array(_)) => molt_err!("can't set \"{}\": variable is array", name),
             Some(var) => {
                 // It was either Var::Scalar or Var::New; either way, replace it with a new
@@ -152,8 +210,8 @@ impl ScopeStack {
     /// if it doesn't already exist.  It's an error if the variable exists but is an array
     /// variable.
     pub fn set(&mut self, name: &str, val: Value) -> Result<(), Exception> {
-        match self.var_mut(self.current(), name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+        match self.var_mut(self.current(), name)? {
+            Some(Var::Upvar(..)) => unreachable!(),
             Some(Var::Array(_)) => molt_err!("can't set \"{}\": variable is array", name),
             Some(var) => {
                 // It was either Var::Scalar or Var::New; either way, replace it with a new
@@ -165,6 +223,81 @@ impl ScopeStack {
         }
     }
 
+    /// Sets the named scalars in the current scope in order, as `set` would one at a time,
+    /// stopping at the first error.  A variable found directly in the current scope is set
+    /// with a single lookup; only a name linked by `upvar` or `global` takes the general
+    /// path.
+    pub fn set_many(&mut self, pairs: &[(&str, Value)]) -> Result<(), Exception> {
+        let top = self.current();
+        let is_namespace = self.stack[top].is_namespace;
+
+        for (name, val) in pairs {
+            if is_namespace || namespace::is_qualified(name) {
+                self.set(name, val.clone())?;
+                continue;
+            }
+
+            let map = &mut self.stack[top].map;
+            match map.get_mut(*name) {
+                Some(Var::Upvar(..)) => self.set(name, val.clone())?,
+                Some(Var::Array(_)) => {
+                    return molt_err!("can't set \"{}\": variable is array", name)
+                }
+                Some(var) => *var = Var::Scalar(val.clone()),
+                None => {
+                    map.insert((*name).into(), Var::Scalar(val.clone()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the elements of the named array in the current scope, creating the array if
+    /// needed.  The variable is looked up once; if it's a scalar, the error names the first
+    /// element, and no element is set.
+    pub fn set_elems(
+        &mut self,
+        name: &str,
+        pairs: &[(&str, Value)],
+    ) -> Result<(), Exception> {
+        let Some((first, _)) = pairs.first() else {
+            return Ok(());
+        };
+        let top = self.current();
+        let link = self.link(top, name);
+
+        let map = match self.var_mut(top, name)? {
+            Some(Var::Scalar(_)) => {
+                return molt_err!(
+                    "can't set \"{}({})\": variable isn't array",
+                    name,
+                    first
+                )
+            }
+            Some(Var::Array(map)) => map,
+            Some(var) => {
+                assert_eq!(*var, Var::New);
+                *var = Var::Array(HashMap::new());
+                let Var::Array(map) = var else { unreachable!() };
+                map
+            }
+            None => unreachable!(),
+        };
+
+        for (index, val) in pairs {
+            map.insert((*index).into(), val.clone());
+        }
+
+        if let Some(link) = link {
+            for (index, val) in pairs {
+                (link.on_set)(index, val.as_str());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Sets the value of the indexed array element in the current scope, creating the
     /// and/or the element if they don't already exist. It's an error if the variable exists
     /// but is a scalar variable.
@@ -175,34 +308,45 @@ impl ScopeStack {
         val: Value,
     ) -> Result<(), Exception> {
         let top = self.current();
+        let link = self.link(top, name);
 
-        match self.var_mut(top, name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+        match self.var_mut(top, name)? {
+            Some(Var::Upvar(..)) => unreachable!(),
             Some(Var::Scalar(_)) => {
-                molt_err!("can't set \"{}({})\": variable isn't array", name, index)
+                return molt_err!(
+                    "can't set \"{}({})\": variable isn't array",
+                    name,
+                    index
+                );
             }
             Some(Var::Array(map)) => {
                 // It was already an array; just update the indexed element (which will
                 // create it if it didn't exist).
+                if let Some(link) = link {
+                    (link.on_set)(index, val.as_str());
+                }
                 map.insert(index.into(), val);
-                Ok(())
             }
             Some(var) => {
                 assert_eq!(*var, Var::New);
                 // Create new variable on the top of the stack.
+                if let Some(link) = link {
+                    (link.on_set)(index, val.as_str());
+                }
                 let mut map = HashMap::new();
                 map.insert(index.into(), val);
                 *var = Var::Array(map);
-                Ok(())
             }
             None => unreachable!(),
         }
+
+        Ok(())
     }
 
     /// Returns true if there's a variable with the given name, of whatever type, and
     /// false otherwise.
     pub fn exists(&self, name: &str) -> bool {
-        self.var(self.current(), name).is_some()
+        matches!(self.var(self.current(), name), Ok(Some(_)))
     }
 
     /// Returns true if there's a variable with the given name, of whatever type, and
@@ -222,22 +366,50 @@ impl ScopeStack {
 
     /// Unset a variable at a given level in the stack.  If the variable at that level
     /// is linked to a higher level, follows the chain down, unsetting as it goes.
+    ///
+    /// The chain is followed at most once per stack level, so a corrupted, cyclic chain
+    /// can't loop forever.
     fn unset_at(&mut self, level: usize, name: &str, array_only: bool) {
-        // FIRST, if the variable at this level links to a lower level, follow the chain.
-        if let Some(Var::Upvar(at)) = self.stack[level].map.get(name) {
-            // NOTE: Using the variable true_level prevents a "doubly-borrowed" error.
-            // Once Polonius is in use, this should no longer be necessary.
-            let true_level = *at;
-            self.unset_at(true_level, name, array_only);
+        // FIRST, collect the levels and names on the chain.
+        let mut links = vec![(level, name.to_string())];
+
+        while let Some((level, name)) = links.last() {
+            let Some(Var::Upvar(at, other)) = self.stack[*level].map.get(name) else {
+                break;
+            };
+            if links.len() > self.stack.len() {
+                break;
+            }
+            links.push((*at, other.clone()));
+        }
+
+        // NEXT, find the variable itself, which may be a namespace variable.
+        let (level, name) = links.last().cloned().unwrap_or_default();
+        let (level, key) = self.locate(level, &name);
+        let key = key.into_owned();
+
+        // NEXT, tell a linked array's link that its elements are going away.
+        if level == 0 {
+            if let (Some(link), Some(Var::Array(map))) =
+                (self.links.get(&key), self.stack[0].map.get(&key))
+            {
+                for index in map.keys() {
+                    (link.on_unset)(index);
+                }
+            }
         }
 
-        // NEXT, remove the variable at this level.
+        // NEXT, remove the variable at each level.  If only arrays are to be unset, only
+        // the variable itself, at the end of the chain, can be removed.
         if array_only {
-            if let Some(Var::Array(_)) = self.stack[level].map.get(name) {
-                self.stack[level].map.remove(name);
+            if let Some(Var::Array(_)) = self.stack[level].map.get(&key) {
+                self.stack[level].map.remove(&key);
             }
         } else {
-            self.stack[level].map.remove(name);
+            for (level, name) in links {
+                self.stack[level].map.remove(&name);
+            }
+            self.stack[level].map.remove(&key);
         }
     }
 
@@ -247,10 +419,46 @@ impl ScopeStack {
     /// **Note:** does not try to create the variable at the referenced scope level, if it
     /// does not exist; the variable will be created on the first `set`, if any.  This is
     /// consistent with standard TCL behavior.
-    pub fn upvar(&mut self, level: usize, name: &str) {
-        assert!(level < self.current(), "Can't upvar to current stack level");
+    ///
+    /// It's an error to link a variable to itself, i.e., to the current level or to a
+    /// variable whose alias chain leads back to the current level.
+    pub fn upvar(&mut self, level: usize, name: &str) -> Result<(), Exception> {
+        self.upvar_as(level, name, name)
+    }
+
+    /// Links the variable `name` in the current scope to the variable `other` at the
+    /// given level, counting from `0`, the global scope.  The level may be the current
+    /// one, if the names differ.  An existing variable called `name` in the current
+    /// scope is replaced by the link.
+    ///
+    /// It's an error to link a variable to itself.
+    pub fn upvar_as(
+        &mut self,
+        level: usize,
+        other: &str,
+        name: &str,
+    ) -> Result<(), Exception> {
         let top = self.current();
-        self.stack[top].map.insert(name.into(), Var::Upvar(level));
+        assert!(level <= top, "Invalid scope level");
+
+        let (at, key) = self.resolve(level, other)?;
+        if at == top && key == name || self.chain_visits(level, other, name) {
+            return molt_err!("can't upvar from variable to itself");
+        }
+
+        self.stack[top]
+            .map
+            .insert(name.into(), Var::Upvar(level, other.into()));
+        Ok(())
+    }
+
+    /// Is `name` a scalar or array variable defined in the current scope itself, rather
+    /// than a link to a variable elsewhere?
+    pub fn is_defined_here(&self, name: &str) -> bool {
+        matches!(
+            self.stack[self.current()].map.get(name),
+            Some(Var::Scalar(_)) | Some(Var::Array(_))
+        )
     }
 
     /// Returns the index of the current stack level, counting from 0, the global scope.
@@ -261,33 +469,91 @@ impl ScopeStack {
     }
 
     /// Pushes a new scope onto the stack.  The scope contains no variables by default, though
-    /// the procedure that is pushing it onto the stack will often add some.
+    /// the procedure that is pushing it onto the stack will often add some.  Its namespace
+    /// is the current scope's.
     pub fn push(&mut self) {
-        self.stack.push(Scope::new());
+        let namespace = self.namespace().clone();
+        self.push_scope(namespace, false);
+    }
+
+    /// Pushes a new scope for `namespace eval`, whose unqualified variables are those of
+    /// the given namespace.
+    pub fn push_namespace(&mut self, namespace: Rc<str>) {
+        self.push_scope(namespace, true);
+    }
+
+    fn push_scope(&mut self, namespace: Rc<str>, is_namespace: bool) {
+        let mut scope = self.free.pop().unwrap_or_default();
+        scope.namespace = namespace;
+        scope.is_namespace = is_namespace;
+        self.stack.push(scope);
+    }
+
+    /// Returns the current scope's namespace, e.g., `a::b`; empty for the global
+    /// namespace.
+    pub fn namespace(&self) -> &Rc<str> {
+        &self.stack[self.current()].namespace
+    }
+
+    /// Sets the current scope's namespace, e.g., that of the procedure whose scope it is.
+    pub fn set_namespace(&mut self, namespace: Rc<str>) {
+        let top = self.current();
+        if !Rc::ptr_eq(&self.stack[top].namespace, &namespace) {
+            self.stack[top].namespace = namespace;
+        }
     }
 
     /// Pops the current scope from the stack. Panics if we're at the global scope; this implies an
     /// coding error at the Rust level.
+    ///
+    /// The popped scope is emptied, dropping its variables and upvar links, before it is
+    /// kept for reuse.
     pub fn pop(&mut self) {
-        self.stack.pop();
+        let scope = self.stack.pop();
         assert!(!self.stack.is_empty(), "Popped global scope!");
+
+        if let Some(mut scope) = scope {
+            if self.free.len() < MAX_FREE_SCOPES
+                && scope.map.capacity() <= MAX_FREE_SCOPE_CAPACITY
+            {
+                scope.map.clear();
+                self.free.push(scope);
+            }
+        }
+    }
+
+    /// Sets aside the scopes above the given level, making it the current scope.  Scopes
+    /// pushed in the meantime are numbered from the level up, as if the scopes set aside
+    /// were gone.  They must be popped again before the scopes set aside are resumed.
+    pub fn suspend_above(&mut self, level: usize) -> SuspendedScopes {
+        assert!(level <= self.current(), "Invalid scope level");
+        SuspendedScopes { level, scopes: self.stack.split_off(level + 1) }
+    }
+
+    /// Puts back the scopes set aside by `suspend_above`.  Panics if the scopes pushed
+    /// since haven't all been popped; this implies a coding error at the Rust level.
+    pub fn resume(&mut self, suspended: SuspendedScopes) {
+        assert_eq!(self.current(), suspended.level, "Scopes left on suspended stack");
+        self.stack.extend(suspended.scopes);
     }
 
-    /// Gets a list of the names of the variables defined in the current scope.
+    /// Gets a list of the names of the variables defined in the current scope.  In the
+    /// scope of `namespace eval`, these are the namespace's variables.
     pub fn vars_in_scope(&self) -> MoltList {
-        self.stack[self.current()]
-            .map
-            .keys()
-            .cloned()
-            .map(|x| Value::from(&x))
-            .collect()
+        let scope = &self.stack[self.current()];
+        if scope.is_namespace || self.current() == 0 {
+            return self.namespace_vars(&scope.namespace);
+        }
+
+        scope.map.keys().cloned().map(|x| Value::from(&x)).collect()
     }
 
     /// Gets a list of the local variables defined in the current scope.  Upvar'd variables
-    /// are not local; and no variables are local in the global scope.
+    /// are not local; and no variables are local in the global scope or in the scope of
+    /// `namespace eval`.
     pub fn vars_in_local_scope(&self) -> MoltList {
         // If we are at the global scope, there are no local variables.
-        if self.current() == 0 {
+        if self.current() == 0 || self.stack[self.current()].is_namespace {
             return Vec::new();
         }
 
@@ -299,24 +565,65 @@ impl ScopeStack {
             .collect()
     }
 
-    /// Gets a list of the variables defined in the global scope.
+    /// Gets a list of the variables defined in the global scope.  Variables in other
+    /// namespaces are not included.
     pub fn vars_in_global_scope(&self) -> MoltList {
-        self.stack[0].map.keys().cloned().map(|x| Value::from(&x)).collect()
+        self.namespace_vars("")
+    }
+
+    /// Gets a list of the unqualified names of the variables in the namespace.
+    fn namespace_vars(&self, namespace: &str) -> MoltList {
+        self.stack[0]
+            .map
+            .keys()
+            .filter_map(|key| {
+                let tail = if namespace.is_empty() {
+                    key.as_str()
+                } else {
+                    key.strip_prefix(namespace)?.strip_prefix("::")?
+                };
+                (!tail.contains("::")).then(|| Value::from(tail))
+            })
+            .collect()
+    }
+
+    /// Gets the value of the named scalar variable in the global scope, or `None` if
+    /// there's no such scalar.
+    pub fn global_scalar(&self, name: &str) -> Option<Value> {
+        match self.stack[0].map.get(name) {
+            Some(Var::Scalar(value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Gets the content of the named array variable in the global scope as a flat list
+    /// of names and values, sorted by name, or `None` if there's no such array.
+    pub fn global_array(&self, name: &str) -> Option<MoltList> {
+        match self.stack[0].map.get(name) {
+            Some(Var::Array(map)) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+
+                Some(
+                    keys.into_iter()
+                        .flat_map(|key| [Value::from(key), map[key].clone()])
+                        .collect(),
+                )
+            }
+            _ => None,
+        }
     }
 
     /// Determines whether the name names an array variable or not.
     pub fn array_exists(&self, name: &str) -> bool {
-        match self.var(self.current(), name) {
-            Some(Var::Array(_)) => true,
-            _ => false,
-        }
+        matches!(self.var(self.current(), name), Ok(Some(Var::Array(_))))
     }
 
     /// Gets a list of the array indices for the named array.  Returns the empty list
     /// if `name` doesn't name an array variable.
     pub fn array_indices(&self, name: &str) -> MoltList {
         match self.var(self.current(), name) {
-            Some(Var::Array(map)) => {
+            Ok(Some(Var::Array(map))) => {
                 map.keys().cloned().map(|x| Value::from(&x)).collect()
             }
             _ => Vec::new(),
@@ -326,7 +633,7 @@ impl ScopeStack {
     /// Gets the size of the named array.  Returns 0 if `name` doesn't name an array variable.
     pub fn array_size(&self, name: &str) -> usize {
         match self.var(self.current(), name) {
-            Some(Var::Array(map)) => map.len(),
+            Ok(Some(Var::Array(map))) => map.len(),
             _ => 0,
         }
     }
@@ -335,15 +642,10 @@ impl ScopeStack {
     /// variable is not an array, returns the empty list.
     pub fn array_get(&self, name: &str) -> MoltList {
         match self.var(self.current(), name) {
-            Some(Var::Array(map)) => {
-                let mut list = Vec::new();
-
-                for (key, value) in map {
-                    list.push(Value::from(key));
-                    list.push(value.clone());
-                }
-                list
-            }
+            Ok(Some(Var::Array(map))) => map
+                .iter()
+                .flat_map(|(key, value)| [Value::from(key), value.clone()])
+                .collect(),
             _ => Vec::new(),
         }
     }
@@ -352,8 +654,14 @@ impl ScopeStack {
     /// Does nothing if the array element doesn't exist, or the variable isn't an array
     /// variable.
     pub fn unset_element(&mut self, name: &str, index: &str) {
-        if let Some(Var::Array(map)) = self.var_mut(self.current(), name) {
-            map.remove(index);
+        let link = self.link(self.current(), name);
+
+        if let Ok(Some(Var::Array(map))) = self.var_mut(self.current(), name) {
+            if map.remove(index).is_some() {
+                if let Some(link) = link {
+                    (link.on_unset)(index);
+                }
+            }
         }
     }
 
@@ -362,16 +670,16 @@ impl ScopeStack {
     pub fn array_set(&mut self, name: &str, kvlist: &[Value]) -> Result<(), Exception> {
         // List must be even.
         assert!(kvlist.len() % 2 == 0);
+        let link = self.link(self.current(), name);
 
-        match self.var_mut(self.current(), name) {
-            Some(Var::Upvar(_)) => unreachable!(),
+        match self.var_mut(self.current(), name)? {
+            Some(Var::Upvar(..)) => unreachable!(),
             Some(Var::Scalar(_)) => {
-                molt_err!("can't array set \"{}\": variable isn't array", name)
+                return molt_err!("can't array set \"{}\": variable isn't array", name);
             }
             Some(Var::Array(map)) => {
                 // It was already an array; just add the new elements.
                 insert_kvlist(map, &kvlist);
-                Ok(())
             }
             Some(var) => {
                 assert_eq!(*var, Var::New);
@@ -379,10 +687,32 @@ impl ScopeStack {
                 let mut map = HashMap::new();
                 insert_kvlist(&mut map, &kvlist);
                 *var = Var::Array(map);
-                Ok(())
             }
             None => unreachable!(),
         }
+
+        if let Some(link) = link {
+            for kv in kvlist.chunks(2) {
+                (link.on_set)(kv[0].as_str(), kv[1].as_str());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the level at which the named variable lives, or would live if it were
+    /// set, and its name there, following any `upvar` alias from the current scope.
+    pub fn locate_var<'a>(
+        &self,
+        name: &'a str,
+    ) -> Result<(usize, Cow<'a, str>), Exception> {
+        self.resolve(self.current(), name)
+    }
+
+    /// Links the named global array variable to the given callbacks, which are then told
+    /// of every change to its elements.  The variable needn't exist yet.
+    pub fn link_array(&mut self, name: &str, link: ArrayLink) {
+        self.links.insert(name.into(), link);
     }
 
     /// Unsets an array variable in the current scope, i.e., removes it from the scope.
@@ -397,20 +727,98 @@ impl ScopeStack {
     //--------------------------------------------------------------
     // Utilities
 
+    /// Follows the alias chain for the variable of the given name, starting at the given
+    /// level, and returns the level at which the variable itself lives (or would live, if
+    /// it doesn't exist yet), and its name there.  The name differs only for a namespace
+    /// variable, which is found by its qualified name in the global scope.
+    ///
+    /// A well-formed chain visits each stack level at most once; a longer chain must
+    /// contain a cycle, and is reported as an error rather than followed forever.
+    fn resolve<'a>(
+        &self,
+        level: usize,
+        name: &'a str,
+    ) -> Result<(usize, Cow<'a, str>), Exception> {
+        let mut level = level;
+        let mut key = Cow::Borrowed(name);
+        let mut hops = 0;
+
+        while let Some(Var::Upvar(at, other)) = self.stack[level].map.get(key.as_ref()) {
+            hops += 1;
+
+            if hops > self.stack.len() {
+                return molt_err!(
+                    "too many levels of indirection for variable \"{}\"",
+                    name
+                );
+            }
+            level = *at;
+            if *other != key {
+                key = Cow::Owned(other.clone());
+            }
+        }
+
+        Ok(match key {
+            Cow::Borrowed(name) => self.locate(level, name),
+            Cow::Owned(name) => {
+                let (level, key) = self.locate(level, &name);
+                (level, Cow::Owned(key.into_owned()))
+            }
+        })
+    }
+
+    /// Returns true if the alias chain starting with the variable of the given name at
+    /// the given level passes through the named variable in the current scope.  The
+    /// chain must already be known to be well-formed.
+    fn chain_visits(&self, level: usize, name: &str, target: &str) -> bool {
+        let top = self.current();
+        let (mut level, mut name) = (level, name);
+
+        while let Some(Var::Upvar(at, other)) = self.stack[level].map.get(name) {
+            if level == top && name == target {
+                return true;
+            }
+            (level, name) = (*at, other);
+        }
+        false
+    }
+
+    /// Returns where the variable of the given name at the given level lives, ignoring
+    /// any alias: in the global scope under its qualified name, if it's a namespace
+    /// variable, and at the given level otherwise.
+    fn locate<'a>(&self, level: usize, name: &'a str) -> (usize, Cow<'a, str>) {
+        let scope = &self.stack[level];
+
+        if namespace::is_qualified(name)
+            || (scope.is_namespace && !scope.map.contains_key(name))
+        {
+            (0, namespace::qualify(&scope.namespace, name))
+        } else {
+            (level, Cow::Borrowed(name))
+        }
+    }
+
+    /// Returns the link of the variable of the given name at the given level, if it's a
+    /// linked global array.
+    fn link(&self, level: usize, name: &str) -> Option<ArrayLink> {
+        if self.links.is_empty() {
+            return None;
+        }
+
+        match self.resolve(level, name) {
+            Ok((0, key)) => self.links.get(key.as_ref()).copied(),
+            _ => None,
+        }
+    }
+
     /// Retrieves an immutable borrow of the variable of the given name, searching the
     /// the scope stack for the variable starting at the current level and following the
     /// alias chain as needed.
     ///
     /// This call is the basis for all public APIs that retrieve information about a variable.
-    ///
-    /// TODO: Try using a loop rather than recursion, and see if that's any faster.
-    fn var(&self, level: usize, name: &str) -> Option<&Var> {
-        let var = self.stack[level].map.get(name);
-        if let Some(Var::Upvar(at)) = var {
-            self.var(*at, name)
-        } else {
-            var
-        }
+    fn var(&self, level: usize, name: &str) -> Result<Option<&Var>, Exception> {
+        let (level, key) = self.resolve(level, name)?;
+        Ok(self.stack[level].map.get(key.as_ref()))
     }
 
     /// Retrieves an mutable borrow of the variable of the given name, searching the
@@ -418,23 +826,13 @@ impl ScopeStack {
     /// alias chain as needed.  If the variable is not found, creates a `Var::New` in the
     /// relevant place.  The caller should *always* replace the `Var::New` with either
     /// `Var::Scalar` or `Var::Array`.
-    ///
-    /// TODO: Try using a loop rather than recursion, and see if that's any faster.
-    fn var_mut(&mut self, level: usize, name: &str) -> Option<&mut Var> {
-        let var = self.stack[level].map.entry(name.into()).or_insert(Var::New);
-
-        // NOTE: 11/28/2019.  Without this transmutation, the borrow checker will not allow the
-        // recursive call to var_mut, even though it can be seen that all we are using
-        // from the first borrow is the alias level. Under Polonius, a new borrow checker
-        // currently under development, this pattern is allowed, and the unsafe code can
-        // be deleted.
-        let var: Option<&mut Var> = unsafe { ::core::mem::transmute(var) };
-
-        if let Some(Var::Upvar(at)) = var {
-            self.var_mut(*at, name)
-        } else {
-            var
-        }
+    fn var_mut(
+        &mut self,
+        level: usize,
+        name: &str,
+    ) -> Result<Option<&mut Var>, Exception> {
+        let (level, key) = self.resolve(level, name)?;
+        Ok(Some(self.stack[level].map.entry(key.into_owned()).or_insert(Var::New)))
     }
 }
 
@@ -449,6 +847,55 @@ fn insert_kvlist(map: &mut HashMap<String, Value>, list: &[Value]) {
 mod tests {
     use super::*;
 
+    thread_local! {
+        static LINKED: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    fn log_set(index: &str, value: &str) {
+        LINKED.with(|log| log.borrow_mut().push(format!("set {} {}", index, value)));
+    }
+
+    fn log_unset(index: &str) {
+        LINKED.with(|log| log.borrow_mut().push(format!("unset {}", index)));
+    }
+
+    fn take_log() -> Vec<String> {
+        LINKED.with(|log| std::mem::take(&mut *log.borrow_mut()))
+    }
+
+    #[test]
+    fn test_linked_array() {
+        let mut ss = ScopeStack::new();
+        ss.link_array("a", ArrayLink { on_set: log_set, on_unset: log_unset });
+
+        // Each way of setting elements, including via an upvar.
+        ss.set_elem("a", "x", Value::from(1)).unwrap();
+        ss.set_elems("a", &[("y", Value::from(2))]).unwrap();
+        ss.array_set("a", &[Value::from("z"), Value::from(3)]).unwrap();
+        ss.push();
+        ss.upvar(0, "a").unwrap();
+        ss.set_elem("a", "w", Value::from(4)).unwrap();
+        assert_eq!(take_log(), vec!["set x 1", "set y 2", "set z 3", "set w 4"]);
+
+        // Unsetting an element, a missing element, and the whole array.
+        ss.unset_element("a", "w");
+        ss.unset_element("a", "nonesuch");
+        assert_eq!(take_log(), vec!["unset w"]);
+        ss.pop();
+        ss.unset("a");
+        let mut log = take_log();
+        log.sort();
+        assert_eq!(log, vec!["unset x", "unset y", "unset z"]);
+
+        // Errors and unlinked variables aren't reported.
+        ss.set("b", Value::from(1)).unwrap();
+        assert!(ss.set_elem("b", "x", Value::from(1)).is_err());
+        ss.set_elem("c", "x", Value::from(1)).unwrap();
+        ss.push();
+        ss.set_elem("a", "x", Value::from(1)).unwrap();
+        assert!(take_log().is_empty());
+    }
+
     #[test]
     fn test_new() {
         let ss = ScopeStack::new();
@@ -543,6 +990,57 @@ mod tests {
         assert_eq!(ss.stack.len(), 1);
     }
 
+    #[test]
+    fn test_pop_recycles_scopes() {
+        let mut ss = ScopeStack::new();
+        ss.set("g", "global".into()).unwrap();
+
+        // Fill a frame with a scalar, an array, and upvar links, then pop it.
+        ss.push();
+        ss.set("x", "stale".into()).unwrap();
+        ss.set_elem("arr", "i", "stale".into()).unwrap();
+        ss.upvar(0, "g").unwrap();
+        ss.upvar(0, "link").unwrap();
+        ss.pop();
+        assert_eq!(ss.free.len(), 1);
+
+        // The recycled frame is empty: nothing stale is visible.
+        ss.push();
+        assert!(ss.free.is_empty());
+        assert!(ss.vars_in_scope().is_empty());
+        assert!(ss.get("x").is_err());
+        assert!(ss.get_elem("arr", "i").is_err());
+        assert!(ss.get("g").is_err());
+
+        // Setting names that were links sets locals, not the globals.
+        ss.set("g", "local".into()).unwrap();
+        ss.set("link", "local".into()).unwrap();
+        ss.pop();
+        assert_eq!(ss.get("g"), Ok("global".into()));
+        assert!(ss.get("link").is_err());
+    }
+
+    #[test]
+    fn test_free_list_is_bounded() {
+        let mut ss = ScopeStack::new();
+        for _ in 0..(MAX_FREE_SCOPES + 10) {
+            ss.push();
+        }
+        for _ in 0..(MAX_FREE_SCOPES + 10) {
+            ss.pop();
+        }
+        assert_eq!(ss.free.len(), MAX_FREE_SCOPES);
+
+        // Frames with large tables aren't kept.
+        ss.free.clear();
+        ss.push();
+        for i in 0..(2 * MAX_FREE_SCOPE_CAPACITY) {
+            ss.set(&format!("v{}", i), Value::empty()).unwrap();
+        }
+        ss.pop();
+        assert!(ss.free.is_empty());
+    }
+
     #[test]
     #[should_panic]
     fn test_pop_global_scope() {
@@ -551,6 +1049,31 @@ mod tests {
         ss.pop();
     }
 
+    #[test]
+    fn test_suspend_above() {
+        let mut ss = ScopeStack::new();
+        ss.set("a", Value::from(1)).unwrap();
+        ss.push();
+        ss.set("b", Value::from(2)).unwrap();
+        ss.push();
+        ss.set("c", Value::from(3)).unwrap();
+
+        let suspended = ss.suspend_above(1);
+        assert_eq!(ss.current(), 1);
+        assert!(ss.exists("b"));
+        assert!(!ss.exists("c"));
+
+        // A scope pushed meanwhile takes the suspended scope's number.
+        ss.push();
+        assert_eq!(ss.current(), 2);
+        assert!(!ss.exists("c"));
+        ss.pop();
+
+        ss.resume(suspended);
+        assert_eq!(ss.current(), 2);
+        assert_eq!(ss.get("c").unwrap().as_int(), Ok(3));
+    }
+
     #[test]
     fn test_current() {
         let mut ss = ScopeStack::new();
@@ -598,7 +1121,7 @@ mod tests {
         let _ = ss.set("b", Value::from("2"));
 
         ss.push();
-        ss.upvar(0, "a");
+        ss.upvar(0, "a").unwrap();
         assert_eq!(ss.get("a").unwrap().as_str(), "1");
         assert!(ss.get("b").is_err());
 
@@ -641,7 +1164,7 @@ mod tests {
         assert!(ss.get("a").is_err());
 
         // Link a@1 to a@0
-        ss.upvar(0, "a");
+        ss.upvar(0, "a").unwrap();
         assert!(ss.get("a").is_ok());
 
         // Unset it; it should be unset in both scopes.
@@ -652,6 +1175,76 @@ mod tests {
         assert!(ss.get("a").is_err());
     }
 
+    #[test]
+    fn test_upvar_self_link() {
+        let mut ss = ScopeStack::new();
+
+        assert_eq!(ss.upvar(0, "a"), molt_err!("can't upvar from variable to itself"));
+
+        ss.push();
+        assert_eq!(ss.upvar(1, "a"), molt_err!("can't upvar from variable to itself"));
+        assert!(ss.upvar(0, "a").is_ok());
+    }
+
+    #[test]
+    fn test_upvar_as() {
+        let mut ss = ScopeStack::new();
+        let _ = ss.set("a", Value::from("1"));
+
+        ss.push();
+        assert!(ss.upvar_as(0, "a", "x").is_ok());
+        assert_eq!(ss.get("x").unwrap().as_str(), "1");
+        assert!(!ss.exists("a"));
+
+        // A second name at the same level, chained to the first.
+        assert!(ss.upvar_as(1, "x", "y").is_ok());
+        let _ = ss.set("y", Value::from("2"));
+        assert_eq!(ss.locate_var("y").unwrap(), (0, Cow::Borrowed("a")));
+
+        assert_eq!(
+            ss.upvar_as(1, "y", "x"),
+            molt_err!("can't upvar from variable to itself")
+        );
+        let _ = ss.set("z", Value::from("3"));
+        assert!(ss.is_defined_here("z"));
+        assert!(!ss.is_defined_here("x"));
+        assert!(ss.upvar_as(0, "a", "z").is_ok());
+        assert!(!ss.is_defined_here("z"));
+        assert_eq!(ss.get("z").unwrap().as_str(), "2");
+
+        // Unsetting a link unsets the variable and the links on the way to it.
+        ss.unset("y");
+        assert!(!ss.exists("x"));
+        ss.pop();
+        assert!(!ss.exists("a"));
+    }
+
+    #[test]
+    fn test_upvar_cycle() {
+        let mut ss = ScopeStack::new();
+        ss.push();
+
+        // Build a two-link cycle by hand: x@1 -> x@0 -> x@1.
+        ss.stack[1].map.insert("x".into(), Var::Upvar(0, "x".into()));
+        ss.stack[0].map.insert("x".into(), Var::Upvar(1, "x".into()));
+
+        let msg = "too many levels of indirection for variable \"x\"";
+        assert_eq!(ss.get("x"), molt_err!(msg));
+        assert_eq!(ss.get_elem("x", "i"), molt_err!(msg));
+        assert_eq!(ss.set("x", Value::from(1)), molt_err!(msg));
+        assert_eq!(ss.set_elem("x", "i", Value::from(1)), molt_err!(msg));
+        assert_eq!(ss.array_set("x", &[]), molt_err!(msg));
+        assert_eq!(ss.upvar(0, "x"), molt_err!(msg));
+        assert!(!ss.exists("x"));
+        assert!(!ss.array_exists("x"));
+        assert_eq!(ss.array_size("x"), 0);
+
+        // Unsetting terminates, and breaks the cycle.
+        ss.unset("x");
+        assert!(ss.get("x").is_err());
+        assert!(ss.set("x", Value::from(1)).is_ok());
+    }
+
     #[test]
     fn test_vars_in_scope() {
         let mut ss = ScopeStack::new();
@@ -675,7 +1268,7 @@ mod tests {
         assert!(ss.vars_in_scope().contains(&Value::from("c")));
 
         // Upvar a var
-        ss.upvar(0, "a");
+        ss.upvar(0, "a").unwrap();
         assert_eq!(ss.vars_in_scope().len(), 2);
         assert!(ss.vars_in_scope().contains(&Value::from("a")));
 
@@ -710,7 +1303,7 @@ mod tests {
         assert!(ss.vars_in_local_scope().contains(&Value::from("b")));
 
         // Upvar a var; it isn't local.
-        ss.upvar(0, "c");
+        ss.upvar(0, "c").unwrap();
         assert_eq!(ss.vars_in_local_scope().len(), 2);
         assert!(!ss.vars_in_local_scope().contains(&Value::from("c")));
 
@@ -756,8 +1349,8 @@ mod tests {
         let mut ss = ScopeStack::new();
 
         ss.push();
-        ss.upvar(0, "a");
-        ss.upvar(0, "b");
+        ss.upvar(0, "a").unwrap();
+        ss.upvar(0, "b").unwrap();
         let _ = dbg!(ss.set("a", Value::from("1")));
         let _ = dbg!(ss.set_elem("b", "1", Value::from("2")));
         ss.pop();
@@ -884,4 +1477,40 @@ mod tests {
         assert!(ss.exists("b"));
         assert!(ss.elem_exists("b", "1"));
     }
+
+    #[test]
+    fn test_namespace_vars() {
+        let mut ss = ScopeStack::new();
+        ss.set("x", "global".into()).unwrap();
+
+        // In a namespace scope, unqualified names are the namespace's variables.
+        ss.push_namespace("a".into());
+        ss.set("x", "a".into()).unwrap();
+        ss.set_many(&[("y", "1".into())]).unwrap();
+        assert_eq!(ss.get("x").unwrap().as_str(), "a");
+        assert_eq!(ss.get("::x").unwrap().as_str(), "global");
+        assert_eq!(ss.vars_in_scope().len(), 2);
+        assert!(ss.vars_in_local_scope().is_empty());
+        ss.pop();
+
+        assert_eq!(ss.get("x").unwrap().as_str(), "global");
+        assert_eq!(ss.get("a::x").unwrap().as_str(), "a");
+        assert_eq!(ss.get("::a::y").unwrap().as_str(), "1");
+        assert_eq!(ss.vars_in_global_scope(), vec![Value::from("x")]);
+
+        // In a proc scope, unqualified names are local; relative qualified names are
+        // relative to the scope's namespace.
+        ss.push();
+        ss.set_namespace("a".into());
+        ss.set("x", "local".into()).unwrap();
+        ss.set_elem("b::arr", "i", "elem".into()).unwrap();
+        assert_eq!(ss.get("::a::x").unwrap().as_str(), "a");
+        assert_eq!(ss.vars_in_scope(), vec![Value::from("x")]);
+        ss.unset("::a::y");
+        ss.pop();
+
+        assert_eq!(ss.get_elem("a::b::arr", "i").unwrap().as_str(), "elem");
+        assert!(!ss.exists("a::y"));
+        assert_eq!(ss.get("a::x").unwrap().as_str(), "a");
+    }
 }
@@ -22,16 +22,31 @@
 //!
 //! See the Molt Book (or the Molt test suite) for examples of test scripts.
 
-use crate::{check_args, molt_ok, prelude::Interp, MoltResult, ResultCode, Value};
-use std::{env, fs, path::PathBuf};
+use crate::util;
+use crate::{
+    check_args, molt_ok, prelude::Interp, MoltResult, ResultCode, StateFilter, Value,
+};
+use std::collections::HashSet;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 /// Executes the Molt test harness, given the command-line arguments,
 /// in the context of the given interpreter.
 ///
-///
 /// The first element of the `args` array must be the name of the test script
-/// to execute.  The remaining elements are meant to be test harness options,
-/// but are currently ignored.
+/// to execute, or of a directory of test scripts.  Given a directory, the harness
+/// executes each `*.tcl` file in it, in order by name, and restores the interpreter's
+/// procs and global variables after each file so that one file can't affect the next.
+/// The remaining elements are options that select the files to execute from a directory:
+///
+/// * `-file pattern`: executes only the files whose names match the glob pattern.
+/// * `-notfile pattern`: skips the files whose names match the glob pattern.
+///
+/// Each option may be given more than once.
+///
+/// Returns `Ok` if every test passed, and `Err` otherwise.
 ///
 /// See [`molt::interp`](../molt/interp/index.html) for details on how to configure and
 /// add commands to a Molt interpreter.
@@ -58,14 +73,14 @@ use std::{env, fs, path::PathBuf};
 /// }
 /// ```
 
-pub fn test_harness<Ctx>(
-    interp: &mut Interp<(Ctx, TestCtx)>,
+pub fn test_harness<Ctx: HasTestCtx>(
+    interp: &mut Interp<Ctx>,
     args: &[String],
 ) -> Result<(), ()> {
     // FIRST, announce who we are.
     println!("Molt {} -- Test Harness", env!("CARGO_PKG_VERSION"));
 
-    // NEXT, get the script file name
+    // NEXT, get the script file name and the options.
     if args.is_empty() {
         eprintln!("missing test script");
         return Err(());
@@ -73,46 +88,216 @@ pub fn test_harness<Ctx>(
 
     let path = PathBuf::from(&args[0]);
 
+    let options = match FileFilter::parse(&args[1..]) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            return Err(());
+        }
+    };
+
     // NEXT, install the test commands into the interpreter.
     // interp.add_command("test", test_cmd);
 
-    // NEXT, execute the script.
-    match fs::read_to_string(&args[0]) {
-        Ok(script) => {
-            if let Some(parent) = path.parent() {
-                let _ = env::set_current_dir(parent);
+    // NEXT, execute the directory or the script.
+    if path.is_dir() {
+        return run_test_dir(interp, &path, &options);
+    }
+
+    if let Err(msg) = run_test_file(interp, &path, true) {
+        eprintln!("{}", msg);
+        return Err(());
+    }
+
+    // NEXT, output the test results:
+    let ctx = interp.context.test_ctx_mut();
+    println!("\n{}", ctx);
+
+    if ctx.all_passed() {
+        Ok(())
+    } else {
+        Err(())
+    }
+}
+
+/// The `-file` and `-notfile` options, which select the test scripts to execute
+/// from a directory.
+struct FileFilter {
+    files: Vec<String>,
+    notfiles: Vec<String>,
+}
+
+impl FileFilter {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut files = Vec::new();
+        let mut notfiles = Vec::new();
+        let mut iter = args.iter();
+
+        while let Some(opt) = iter.next() {
+            let patterns = match opt.as_str() {
+                "-file" => &mut files,
+                "-notfile" => &mut notfiles,
+                _ => return Err(format!("invalid option: \"{}\"", opt)),
+            };
+
+            match iter.next() {
+                Some(pattern) => patterns.push(pattern.clone()),
+                None => return Err(format!("missing value for {}", opt)),
             }
+        }
 
-            if let Err(exception) = interp.eval(&script) {
-                if exception.code() == ResultCode::Error {
-                    eprintln!("{}", exception.value());
-                    return Err(());
-                } else {
-                    eprintln!("Unexpected eval return: {:?}", exception);
-                    return Err(());
-                }
+        if files.is_empty() {
+            files.push("*.tcl".into());
+        }
+
+        Ok(Self { files, notfiles })
+    }
+
+    fn selects(&self, name: &str) -> bool {
+        self.files.iter().any(|pattern| util::glob_match(pattern, name))
+            && !self.notfiles.iter().any(|pattern| util::glob_match(pattern, name))
+    }
+}
+
+/// Executes a test script.  If `chdir` is set, makes the script's directory the current
+/// working directory first, so that the script can `source` others relative to it.
+fn run_test_file<Ctx: HasTestCtx>(
+    interp: &mut Interp<Ctx>,
+    path: &Path,
+    chdir: bool,
+) -> Result<(), String> {
+    let mut script_path = path;
+
+    if chdir {
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            if !parent.as_os_str().is_empty() && env::set_current_dir(parent).is_ok() {
+                script_path = Path::new(name);
             }
         }
+    }
+
+    match interp.eval_file(&script_path.to_string_lossy()) {
+        Ok(_) => Ok(()),
+        Err(exception) if exception.code() == ResultCode::Error => {
+            Err(exception.value().to_string())
+        }
+        Err(exception) => Err(format!("Unexpected eval return: {:?}", exception)),
+    }
+}
+
+/// Executes each selected test script in the directory, restoring the interpreter's
+/// state after each, and outputs the results for each file and in total.
+fn run_test_dir<Ctx: HasTestCtx>(
+    interp: &mut Interp<Ctx>,
+    dir: &Path,
+    filter: &FileFilter,
+) -> Result<(), ()> {
+    // FIRST, find the test scripts.
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
         Err(e) => {
-            println!("{}", e);
+            eprintln!("{}", e);
             return Err(());
         }
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| filter.selects(name))
+        .collect();
+    names.sort();
+
+    if names.is_empty() {
+        eprintln!("no test scripts selected in \"{}\"", dir.display());
+        return Err(());
     }
 
-    // NEXT, output the test results:
-    let ctx = &mut interp.context.1;
-    println!(
-        "\n{} tests, {} passed, {} failed, {} errors",
-        ctx.num_tests, ctx.num_passed, ctx.num_failed, ctx.num_errors
-    );
+    // NEXT, execute them from the directory, so that they can `source` their helpers.
+    if let Err(e) = env::set_current_dir(dir) {
+        eprintln!("{}", e);
+        return Err(());
+    }
+
+    let snapshot = Snapshot::take(interp);
+    let mut results: Vec<(String, TestCtx, Option<String>)> = Vec::new();
+
+    for name in names {
+        let before = *interp.context.test_ctx_mut();
+        let outcome = run_test_file(interp, Path::new(&name), false).err();
+        let counts = interp.context.test_ctx_mut().since(&before);
+
+        if let Some(msg) = &outcome {
+            println!("\n*** ERROR in {}\n    {}", name, msg);
+        }
+
+        snapshot.restore(interp);
+        results.push((name, counts, outcome));
+    }
 
-    if ctx.num_failed + ctx.num_errors == 0 {
+    // NEXT, output the test results.
+    println!();
+    for (name, counts, outcome) in &results {
+        match outcome {
+            Some(_) => println!("{}: {} (script error)", name, counts),
+            None => println!("{}: {}", name, counts),
+        }
+    }
+
+    let ctx = interp.context.test_ctx_mut();
+    println!("\n{}", ctx);
+
+    if ctx.all_passed() && results.iter().all(|(_, _, outcome)| outcome.is_none()) {
         Ok(())
     } else {
         Err(())
     }
 }
 
+/// The interpreter's procs and global variables, saved before executing the scripts in
+/// a directory so that they can be restored after each one.
+struct Snapshot {
+    state: String,
+    procs: HashSet<String>,
+    vars: HashSet<String>,
+}
+
+impl Snapshot {
+    fn take<Ctx>(interp: &Interp<Ctx>) -> Self {
+        Self {
+            state: interp.export_state(&StateFilter::all()),
+            procs: interp.proc_names().iter().map(|name| name.to_string()).collect(),
+            vars: interp
+                .vars_in_global_scope()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+
+    /// Removes the procs and global variables defined since the snapshot was taken,
+    /// and restores the definitions and values of the rest.
+    fn restore<Ctx>(&self, interp: &mut Interp<Ctx>) {
+        for name in interp.proc_names() {
+            if !self.procs.contains(name.as_str()) {
+                interp.remove_proc(name.as_str());
+            }
+        }
+
+        for name in interp.vars_in_global_scope() {
+            if !self.vars.contains(name.as_str()) {
+                interp.unset(name.as_str());
+            }
+        }
+
+        if let Err(exception) = interp.import_state(&self.state) {
+            eprintln!("could not restore the interpreter: {}", exception.value());
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
 pub struct TestCtx {
     num_tests: usize,
     num_passed: usize,
@@ -129,6 +314,44 @@ impl TestCtx {
             num_errors: 0,
         }
     }
+
+    fn all_passed(&self) -> bool {
+        self.num_failed + self.num_errors == 0
+    }
+
+    /// The counts of the tests run since the `before` counts were taken.
+    fn since(&self, before: &TestCtx) -> TestCtx {
+        TestCtx {
+            num_tests: self.num_tests - before.num_tests,
+            num_passed: self.num_passed - before.num_passed,
+            num_failed: self.num_failed - before.num_failed,
+            num_errors: self.num_errors - before.num_errors,
+        }
+    }
+}
+
+impl std::fmt::Display for TestCtx {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} tests, {} passed, {} failed, {} errors",
+            self.num_tests, self.num_passed, self.num_failed, self.num_errors
+        )
+    }
+}
+
+/// Gives the test harness access to its [`TestCtx`] within an interpreter's context.
+///
+/// This is implemented for the `(Ctx, TestCtx)` tuple layout; implement it for your own
+/// context type to add the `test` command to an interpreter whose context isn't a tuple.
+pub trait HasTestCtx {
+    fn test_ctx_mut(&mut self) -> &mut TestCtx;
+}
+
+impl<Ctx> HasTestCtx for (Ctx, TestCtx) {
+    fn test_ctx_mut(&mut self) -> &mut TestCtx {
+        &mut self.1
+    }
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -208,7 +431,7 @@ impl TestInfo {
 /// point I'll need something much more robust.
 ///
 /// Note: See the Molt Book for the full syntax.
-pub fn test_cmd<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, argv: &[Value]) -> MoltResult {
+pub fn test_cmd<Ctx: HasTestCtx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     // FIRST, check the minimum command line.
     check_args(1, argv, 4, 0, "name description args...")?;
 
@@ -222,7 +445,7 @@ pub fn test_cmd<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, argv: &[Value]) -> Mol
 }
 
 // The simple version of the test command.
-fn simple_test<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, argv: &[Value]) -> MoltResult {
+fn simple_test<Ctx: HasTestCtx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 6, 6, "name description script -ok|-error result")?;
 
     // FIRST, get the test info
@@ -249,7 +472,7 @@ fn simple_test<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, argv: &[Value]) -> Molt
 }
 
 // The fancier, more flexible version of the test.
-fn fancy_test<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, argv: &[Value]) -> MoltResult {
+fn fancy_test<Ctx: HasTestCtx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
     check_args(1, argv, 4, 0, "name description option value ?option value...?")?;
 
     // FIRST, get the test tinfo
@@ -302,41 +525,13 @@ fn fancy_test<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, argv: &[Value]) -> MoltR
 }
 
 // Run the actual test and save the result.
-fn run_test<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, info: &TestInfo) {
-    // FIRST, push a variable scope; -setup, -body, and -cleanup will share it.
-    interp.push_scope();
-
-    // NEXT, execute the parts of the test.
-
-    // Setup
-    if let Err(exception) = interp.eval(&info.setup) {
-        if exception.code() == ResultCode::Error {
-            info.print_helper_error("-setup", exception.value().as_str());
-        }
-    }
-    // if let Err(ResultCode::Error(msg)) = interp.eval(&info.setup) {
-    //     info.print_helper_error("-setup", &msg.to_string());
-    // }
-
-    // Body
-    let body = Value::from(&info.body);
-    let result = interp.eval_value(&body);
-
-    // Cleanup
-    if let Err(exception) = interp.eval(&info.cleanup) {
-        if exception.code() == ResultCode::Error {
-            info.print_helper_error("-cleanup", exception.value().as_str());
-        }
-    }
-    // if let Err(ResultCode::Error(msg)) = interp.eval(&info.cleanup) {
-    //     info.print_helper_error("-cleanup", &msg.to_string());
-    // }
-
-    // NEXT, pop the scope.
-    interp.pop_scope();
+fn run_test<Ctx: HasTestCtx>(interp: &mut Interp<Ctx>, info: &TestInfo) {
+    // FIRST, execute the parts of the test in a variable scope of their own, popped
+    // when they are done.
+    let result = eval_test_parts(&mut interp.local_scope(), info);
 
     // NEXT, get the context and save the results.
-    let ctx = &mut interp.context.1;
+    let ctx = interp.context.test_ctx_mut();
     ctx.num_tests += 1;
 
     match &result {
@@ -367,7 +562,39 @@ fn run_test<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>, info: &TestInfo) {
     info.print_error(&result);
 }
 
+// Evaluates the test's -setup, -body, and -cleanup, returning the body's result.
+fn eval_test_parts<Ctx: HasTestCtx>(
+    interp: &mut Interp<Ctx>,
+    info: &TestInfo,
+) -> MoltResult {
+    // Setup
+    if let Err(exception) = interp.eval(&info.setup) {
+        if exception.code() == ResultCode::Error {
+            info.print_helper_error("-setup", exception.value().as_str());
+        }
+    }
+    // if let Err(ResultCode::Error(msg)) = interp.eval(&info.setup) {
+    //     info.print_helper_error("-setup", &msg.to_string());
+    // }
+
+    // Body
+    let body = Value::from(&info.body);
+    let result = interp.eval_value(&body);
+
+    // Cleanup
+    if let Err(exception) = interp.eval(&info.cleanup) {
+        if exception.code() == ResultCode::Error {
+            info.print_helper_error("-cleanup", exception.value().as_str());
+        }
+    }
+    // if let Err(ResultCode::Error(msg)) = interp.eval(&info.cleanup) {
+    //     info.print_helper_error("-cleanup", &msg.to_string());
+    // }
+
+    result
+}
+
 // Increment the failure counter.
-fn incr_errors<Ctx>(interp: &mut Interp<(Ctx, TestCtx)>) {
-    interp.context.1.num_errors += 1;
+fn incr_errors<Ctx: HasTestCtx>(interp: &mut Interp<Ctx>) {
+    interp.context.test_ctx_mut().num_errors += 1;
 }
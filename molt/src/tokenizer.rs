@@ -188,6 +188,31 @@ impl<'a> Tokenizer<'a> {
                     val as char
                 }
 
+                // \u{h...}: 1-6 hex digits in braces
+                'u' if self.is('{') => {
+                    let mark = self.mark();
+                    self.skip_char('{');
+                    let digits = self.mark();
+
+                    while self.has(|ch| ch.is_ascii_hexdigit()) && self.index - digits < 6
+                    {
+                        self.next();
+                    }
+
+                    let hex = &self.input[digits..self.index];
+
+                    if !hex.is_empty() && self.is('}') {
+                        let val = u32::from_str_radix(hex, 16).unwrap();
+                        if let Some(ch) = std::char::from_u32(val) {
+                            self.skip_char('}');
+                            return ch;
+                        }
+                    }
+
+                    self.reset_to(mark);
+                    c
+                }
+
                 // \xhh, \uhhhh, \Uhhhhhhhh
                 'x' | 'u' | 'U' => {
                     let mark = self.mark();
@@ -429,6 +454,16 @@ mod tests {
         assert_eq!(bsubst("\\u077-"), ('w', Some('-')));
         assert_eq!(bsubst("\\u0077-"), ('w', Some('-')));
         assert_eq!(bsubst("\\u00077-"), ('\x07', Some('7')));
+
+        // \u{h...}: 1-6 hex digits in braces.
+        assert_eq!(bsubst("\\u{77}-"), ('w', Some('-')));
+        assert_eq!(bsubst("\\u{1F600}-"), ('😀', Some('-')));
+        assert_eq!(bsubst("\\u{00263A}-"), ('☺', Some('-')));
+        assert_eq!(bsubst("\\u{}-"), ('u', Some('{')));
+        assert_eq!(bsubst("\\u{77-"), ('u', Some('{')));
+        assert_eq!(bsubst("\\u{0000077}"), ('u', Some('{')));
+        assert_eq!(bsubst("\\u{D800}"), ('u', Some('{')));
+        assert_eq!(bsubst("\\u{110000}"), ('u', Some('{')));
     }
 
     #[test]
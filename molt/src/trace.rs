@@ -0,0 +1,642 @@
+//! Traces
+//!
+//! A variable trace watches a variable, and is called when the variable is read, written, or
+//! unset.  A trace is set in Rust with [`Interp::trace_var`], whose callback is a
+//! [`TraceFn`], or in Molt with the `trace add variable` command, whose callback is a
+//! command prefix.
+//!
+//! A trace on a scalar or an array watches the variable itself, and for an array every
+//! element; a trace on an array element, e.g., `a(x)`, watches just that element.  A
+//! trace belongs to the variable it was set on, rather than to its name: a trace set on
+//! a proc's local variable goes away when the proc returns, and one set via an `upvar`
+//! alias watches the linked variable however it is reached.
+//!
+//! Traces are called as in Standard TCL:
+//!
+//! * `read` traces before the value is read, so that they can compute it; an error makes
+//!   the read fail with "can't read".
+//! * `write` traces after the value is written; an error makes the write fail with
+//!   "can't set", though the value is written.
+//! * `unset` traces after the variable or element is unset, including when a proc's
+//!   local variables go away on return.  The variable's traces are then removed.
+//!   Errors are ignored.
+//!
+//! While a variable's traces are being called, its traces are disabled, so that a trace
+//! can read and write the variable freely.
+//!
+//! An execution trace watches commands, and is called when a command is entered and
+//! when it is left.  A trace on every command is set in Rust with
+//! [`Interp::add_exec_trace`], whose callback is an [`ExecTraceFn`]; a trace on one
+//! command is set in Molt with the `trace add execution` command.  An `enter` trace is
+//! called before the command, and an error keeps the command from being called; a
+//! `leave` trace is called after it, and an error replaces its result.  While execution
+//! traces are being called, execution traces are disabled.
+//!
+//! [`Interp::trace_var`]: ../interp/struct.Interp.html#method.trace_var
+//! [`Interp::add_exec_trace`]: ../interp/struct.Interp.html#method.add_exec_trace
+
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use std::fmt;
+
+/// The name of the `trace` command.
+pub const _TRACE: &str = "trace";
+
+/// An operation on a variable that a trace can watch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceOp {
+    /// The variable is read.
+    Read,
+    /// The variable is written.
+    Write,
+    /// The variable is unset.
+    Unset,
+}
+
+impl TraceOp {
+    /// Returns the operation's name as the `trace` command spells it, e.g., `read`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TraceOp::Read => "read",
+            TraceOp::Write => "write",
+            TraceOp::Unset => "unset",
+        }
+    }
+
+    /// Parses a list of operation names, as given to `trace add variable`.
+    fn from_list(ops: &Value) -> Result<Vec<TraceOp>, Exception> {
+        let names = ops.as_list()?;
+
+        if names.is_empty() {
+            return molt_err!(
+                "bad operation list \"\": must be one or more of read, unset, or write"
+            );
+        }
+
+        names
+            .iter()
+            .map(|name| match name.as_str() {
+                "read" => Ok(TraceOp::Read),
+                "write" => Ok(TraceOp::Write),
+                "unset" => Ok(TraceOp::Unset),
+                other => {
+                    molt_err!(
+                        "bad operation \"{}\": must be read, unset, or write",
+                        other
+                    )
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for TraceOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A variable trace defined in Rust; see
+/// [`Interp::trace_var`](../interp/struct.Interp.html#method.trace_var).  It is passed
+/// the variable's name as it was accessed, the element's index or the empty string, and
+/// the operation.
+pub type TraceFn<Ctx> =
+    fn(&mut Interp<Ctx>, &str, &str, TraceOp) -> Result<(), Exception>;
+
+/// Identifies a trace set by
+/// [`Interp::trace_var`](../interp/struct.Interp.html#method.trace_var), so that it can
+/// be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceId(usize);
+
+/// What a trace calls.
+pub(crate) enum TraceCallback<Ctx: 'static> {
+    /// A Rust function.
+    Native(TraceFn<Ctx>),
+    /// A command prefix, to which the names and operation are appended.
+    Script(Value),
+    /// Notes that the global variable with the given name was written, for `vwait`.
+    Notify(String),
+}
+
+impl<Ctx> Clone for TraceCallback<Ctx> {
+    fn clone(&self) -> Self {
+        match self {
+            TraceCallback::Native(func) => TraceCallback::Native(*func),
+            TraceCallback::Script(prefix) => TraceCallback::Script(prefix.clone()),
+            TraceCallback::Notify(name) => TraceCallback::Notify(name.clone()),
+        }
+    }
+}
+
+/// A trace on a variable, which lives at `level` in the scope stack under `key`.
+pub(crate) struct VarTrace<Ctx: 'static> {
+    id: TraceId,
+    level: usize,
+    key: String,
+    /// The element's index, for a trace on an array element.
+    index: Option<String>,
+    ops: Vec<TraceOp>,
+    callback: TraceCallback<Ctx>,
+}
+
+impl<Ctx> VarTrace<Ctx> {
+    /// Is the trace on the variable, or on the given element of it?
+    fn watches(&self, level: usize, key: &str, index: Option<&str>) -> bool {
+        self.level == level
+            && self.key == key
+            && (self.index.is_none() || self.index.as_deref() == index)
+    }
+
+    /// Returns the trace's callback.
+    pub(crate) fn callback(&self) -> &TraceCallback<Ctx> {
+        &self.callback
+    }
+
+    /// Returns the element's index, for a trace on an array element.
+    pub(crate) fn index(&self) -> Option<&str> {
+        self.index.as_deref()
+    }
+
+    /// Returns the variable's name at its level.
+    pub(crate) fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Does the trace watch the operation?
+    pub(crate) fn has_op(&self, op: TraceOp) -> bool {
+        self.ops.contains(&op)
+    }
+}
+
+/// The interpreter's variable traces.
+pub(crate) struct VarTraces<Ctx: 'static> {
+    traces: Vec<VarTrace<Ctx>>,
+    next_id: usize,
+
+    /// The variables whose traces are being called, by level and key.
+    active: Vec<(usize, String)>,
+}
+
+impl<Ctx> Default for VarTraces<Ctx> {
+    fn default() -> Self {
+        Self { traces: Vec::new(), next_id: 0, active: Vec::new() }
+    }
+}
+
+impl<Ctx> VarTraces<Ctx> {
+    /// Are there no traces at all?  Checked before any other work on every variable
+    /// access.
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+
+    /// Adds a trace, returning its ID.
+    pub(crate) fn add(
+        &mut self,
+        level: usize,
+        key: String,
+        index: Option<String>,
+        ops: Vec<TraceOp>,
+        callback: TraceCallback<Ctx>,
+    ) -> TraceId {
+        self.next_id += 1;
+        let id = TraceId(self.next_id);
+        self.traces.push(VarTrace { id, level, key, index, ops, callback });
+        id
+    }
+
+    /// Removes the trace with the given ID, returning true if there was one.
+    pub(crate) fn remove(&mut self, id: TraceId) -> bool {
+        let len = self.traces.len();
+        self.traces.retain(|t| t.id != id);
+        self.traces.len() < len
+    }
+
+    /// Removes the script trace on the variable or element with exactly the given
+    /// operations and command prefix, if there is one.
+    pub(crate) fn remove_script(
+        &mut self,
+        level: usize,
+        key: &str,
+        index: Option<&str>,
+        ops: &[TraceOp],
+        prefix: &Value,
+    ) {
+        let found = self.traces.iter().position(|t| {
+            t.level == level
+                && t.key == key
+                && t.index.as_deref() == index
+                && same_ops(&t.ops, ops)
+                && matches!(&t.callback, TraceCallback::Script(p) if p == prefix)
+        });
+
+        if let Some(i) = found {
+            self.traces.remove(i);
+        }
+    }
+
+    /// Returns the operations and command prefix of each script trace on the variable
+    /// or element, most recent first, as `trace info variable` does.
+    pub(crate) fn script_info(
+        &self,
+        level: usize,
+        key: &str,
+        index: Option<&str>,
+    ) -> MoltList {
+        self.traces
+            .iter()
+            .rev()
+            .filter(|t| t.level == level && t.key == key && t.index.as_deref() == index)
+            .filter_map(|t| match &t.callback {
+                TraceCallback::Script(prefix) => {
+                    let ops: MoltList =
+                        t.ops.iter().map(|op| Value::from(op.as_str())).collect();
+                    Some(Value::from(vec![Value::from(ops), prefix.clone()]))
+                }
+                TraceCallback::Native(_) | TraceCallback::Notify(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the callbacks of the traces to call for the operation on the variable or
+    /// element, most recent first; none if its traces are already being called.
+    pub(crate) fn to_call(
+        &self,
+        level: usize,
+        key: &str,
+        index: Option<&str>,
+        op: TraceOp,
+    ) -> Vec<TraceCallback<Ctx>> {
+        if self.is_active(level, key) {
+            return Vec::new();
+        }
+
+        self.traces
+            .iter()
+            .rev()
+            .filter(|t| t.watches(level, key, index) && t.has_op(op))
+            .map(|t| t.callback.clone())
+            .collect()
+    }
+
+    /// Removes and returns the traces that go away when the variable or element is unset:
+    /// all of the variable's for the variable, and the element's for an element.
+    pub(crate) fn take_unset(
+        &mut self,
+        level: usize,
+        key: &str,
+        index: Option<&str>,
+    ) -> Vec<VarTrace<Ctx>> {
+        self.take(|t| {
+            t.level == level
+                && t.key == key
+                && (index.is_none() || t.index.as_deref() == index)
+        })
+    }
+
+    /// Removes and returns the traces at the given level and above, as the scope is
+    /// popped.
+    pub(crate) fn take_level(&mut self, level: usize) -> Vec<VarTrace<Ctx>> {
+        self.take(|t| t.level >= level)
+    }
+
+    /// Puts back the traces taken by `take_level` while their scopes were set aside, as
+    /// by `uplevel`.
+    pub(crate) fn restore(&mut self, traces: Vec<VarTrace<Ctx>>) {
+        self.traces.extend(traces);
+    }
+
+    fn take(&mut self, pred: impl Fn(&VarTrace<Ctx>) -> bool) -> Vec<VarTrace<Ctx>> {
+        let (taken, kept) = std::mem::take(&mut self.traces).into_iter().partition(pred);
+        self.traces = kept;
+        taken
+    }
+
+    /// Are the variable's traces being called?
+    pub(crate) fn is_active(&self, level: usize, key: &str) -> bool {
+        self.active.iter().any(|(l, k)| *l == level && k == key)
+    }
+
+    /// Notes that the variable's traces are being called.
+    pub(crate) fn enter(&mut self, level: usize, key: &str) {
+        self.active.push((level, key.into()));
+    }
+
+    /// Notes that the variable's traces are done.
+    pub(crate) fn leave(&mut self) {
+        self.active.pop();
+    }
+}
+
+/// Do the two lists name the same operations, in whatever order?
+fn same_ops<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+    a.iter().all(|op| b.contains(op)) && b.iter().all(|op| a.contains(op))
+}
+
+/// The point in a command's execution at which an execution trace is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecOp {
+    /// The command is about to be called.
+    Enter,
+    /// The command has returned.
+    Leave,
+}
+
+impl ExecOp {
+    /// Returns the operation's name as the `trace` command spells it, e.g., `enter`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecOp::Enter => "enter",
+            ExecOp::Leave => "leave",
+        }
+    }
+
+    /// Parses a list of operation names, as given to `trace add execution`.
+    fn from_list(ops: &Value) -> Result<Vec<ExecOp>, Exception> {
+        let names = ops.as_list()?;
+
+        if names.is_empty() {
+            return molt_err!(
+                "bad operation list \"\": must be one or more of enter or leave"
+            );
+        }
+
+        names
+            .iter()
+            .map(|name| match name.as_str() {
+                "enter" => Ok(ExecOp::Enter),
+                "leave" => Ok(ExecOp::Leave),
+                other => molt_err!("bad operation \"{}\": must be enter or leave", other),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ExecOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An execution trace defined in Rust; see
+/// [`Interp::add_exec_trace`](../interp/struct.Interp.html#method.add_exec_trace).  It
+/// is passed the command's words, the operation, and, for `leave`, the command's result.
+pub type ExecTraceFn<Ctx> =
+    fn(&mut Interp<Ctx>, &[Value], ExecOp, Option<&MoltResult>) -> Result<(), Exception>;
+
+/// What an execution trace calls.
+pub(crate) enum ExecCallback<Ctx: 'static> {
+    /// A Rust function.
+    Native(ExecTraceFn<Ctx>),
+    /// A command prefix, to which the command string, the result code and result for
+    /// `leave`, and the operation are appended.
+    Script(Value),
+}
+
+impl<Ctx> Clone for ExecCallback<Ctx> {
+    fn clone(&self) -> Self {
+        match self {
+            ExecCallback::Native(func) => ExecCallback::Native(*func),
+            ExecCallback::Script(prefix) => ExecCallback::Script(prefix.clone()),
+        }
+    }
+}
+
+/// An execution trace on the command with the given key, or on every command.
+struct ExecTrace<Ctx: 'static> {
+    id: TraceId,
+    command: Option<String>,
+    ops: Vec<ExecOp>,
+    callback: ExecCallback<Ctx>,
+}
+
+/// The interpreter's execution traces.
+pub(crate) struct ExecTraces<Ctx: 'static> {
+    traces: Vec<ExecTrace<Ctx>>,
+    next_id: usize,
+
+    /// Set while execution traces are being called.
+    active: bool,
+}
+
+impl<Ctx> Default for ExecTraces<Ctx> {
+    fn default() -> Self {
+        Self { traces: Vec::new(), next_id: 0, active: false }
+    }
+}
+
+impl<Ctx> ExecTraces<Ctx> {
+    /// Are there no traces at all?  Checked before any other work on every command.
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.traces.is_empty()
+    }
+
+    /// Adds a trace on the command with the given key, or on every command, returning
+    /// its ID.
+    pub(crate) fn add(
+        &mut self,
+        command: Option<String>,
+        ops: Vec<ExecOp>,
+        callback: ExecCallback<Ctx>,
+    ) -> TraceId {
+        self.next_id += 1;
+        let id = TraceId(self.next_id);
+        self.traces.push(ExecTrace { id, command, ops, callback });
+        id
+    }
+
+    /// Removes the trace with the given ID, returning true if there was one.
+    pub(crate) fn remove(&mut self, id: TraceId) -> bool {
+        let len = self.traces.len();
+        self.traces.retain(|t| t.id != id);
+        self.traces.len() < len
+    }
+
+    /// Removes the script trace on the command with exactly the given operations and
+    /// command prefix, if there is one.
+    pub(crate) fn remove_script(
+        &mut self,
+        command: &str,
+        ops: &[ExecOp],
+        prefix: &Value,
+    ) {
+        let found = self.traces.iter().position(|t| {
+            t.command.as_deref() == Some(command)
+                && same_ops(&t.ops, ops)
+                && matches!(&t.callback, ExecCallback::Script(p) if p == prefix)
+        });
+
+        if let Some(i) = found {
+            self.traces.remove(i);
+        }
+    }
+
+    /// Returns the operations and command prefix of each script trace on the command,
+    /// most recent first, as `trace info execution` does.
+    pub(crate) fn script_info(&self, command: &str) -> MoltList {
+        self.traces
+            .iter()
+            .rev()
+            .filter(|t| t.command.as_deref() == Some(command))
+            .filter_map(|t| match &t.callback {
+                ExecCallback::Script(prefix) => {
+                    let ops: MoltList =
+                        t.ops.iter().map(|op| Value::from(op.as_str())).collect();
+                    Some(Value::from(vec![Value::from(ops), prefix.clone()]))
+                }
+                ExecCallback::Native(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the callbacks of the traces to call for the operation on the command,
+    /// in the order they were added; none if execution traces are already being called.
+    pub(crate) fn to_call(&self, command: &str, op: ExecOp) -> Vec<ExecCallback<Ctx>> {
+        if self.active {
+            return Vec::new();
+        }
+
+        self.traces
+            .iter()
+            .filter(|t| t.command.as_deref().is_none_or(|c| c == command))
+            .filter(|t| t.ops.contains(&op))
+            .map(|t| t.callback.clone())
+            .collect()
+    }
+
+    /// Notes whether execution traces are being called.
+    pub(crate) fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+}
+
+/// # trace *subcommand* variable|execution *name* ...
+///
+/// Adds, removes, and queries variable and execution traces.
+///
+/// * `trace add variable name ops command`: calls the command prefix, with the
+///   variable's name, the element's index or the empty string, and the operation, when
+///   one of the operations in the list `ops` (`read`, `write`, `unset`) is done on the
+///   variable.
+/// * `trace add execution name ops command`: calls the command prefix when the named
+///   command is entered or left, as the list `ops` (`enter`, `leave`) says.  It's passed
+///   the command string, then for `leave` the result code and result, then the
+///   operation.
+/// * `trace remove variable|execution name ops command`: removes the trace with the
+///   same operations and command.
+/// * `trace info variable|execution name`: returns a list of the variable's or command's
+///   traces, each a list of its operations and its command.
+pub fn cmd_trace<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [("add", cmd_trace_add), ("info", cmd_trace_info), ("remove", cmd_trace_remove),],
+    );
+    f(interp, argv)
+}
+
+/// The kinds of trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TraceType {
+    Execution,
+    Variable,
+}
+
+/// Parses the trace type, `execution` or `variable`.
+fn trace_type(value: &Value) -> Result<TraceType, Exception> {
+    match value.as_str() {
+        "execution" => Ok(TraceType::Execution),
+        "variable" => Ok(TraceType::Variable),
+        other => molt_err!("bad option \"{}\": must be execution or variable", other),
+    }
+}
+
+/// # trace add variable|execution *name* *ops* *command*
+fn cmd_trace_add<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 6, 6, "type name opList command")?;
+
+    match trace_type(&argv[2])? {
+        TraceType::Execution => {
+            let ops = ExecOp::from_list(&argv[4])?;
+            interp.add_command_trace(argv[3].as_str(), ops, argv[5].clone())?;
+        }
+        TraceType::Variable => {
+            let ops = TraceOp::from_list(&argv[4])?;
+            interp.add_trace(&argv[3], ops, TraceCallback::Script(argv[5].clone()))?;
+        }
+    }
+    molt_ok!()
+}
+
+/// # trace remove variable|execution *name* *ops* *command*
+fn cmd_trace_remove<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 6, 6, "type name opList command")?;
+
+    match trace_type(&argv[2])? {
+        TraceType::Execution => {
+            let ops = ExecOp::from_list(&argv[4])?;
+            interp.remove_command_trace(argv[3].as_str(), &ops, &argv[5]);
+        }
+        TraceType::Variable => {
+            let ops = TraceOp::from_list(&argv[4])?;
+            interp.remove_script_trace(&argv[3], &ops, &argv[5])?;
+        }
+    }
+    molt_ok!()
+}
+
+/// # trace info variable|execution *name*
+fn cmd_trace_info<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "type name")?;
+
+    match trace_type(&argv[2])? {
+        TraceType::Execution => molt_ok!(interp.command_traces(argv[3].as_str())),
+        TraceType::Variable => molt_ok!(interp.script_traces(&argv[3])?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_op_from_list() {
+        assert_eq!(
+            TraceOp::from_list(&Value::from("read write unset")),
+            Ok(vec![TraceOp::Read, TraceOp::Write, TraceOp::Unset])
+        );
+        assert_eq!(
+            TraceOp::from_list(&Value::from("frob")).unwrap_err().value().as_str(),
+            "bad operation \"frob\": must be read, unset, or write"
+        );
+        assert!(TraceOp::from_list(&Value::from("")).is_err());
+    }
+
+    #[test]
+    fn test_exec_op_from_list() {
+        assert_eq!(
+            ExecOp::from_list(&Value::from("leave enter")),
+            Ok(vec![ExecOp::Leave, ExecOp::Enter])
+        );
+        assert_eq!(
+            ExecOp::from_list(&Value::from("enterstep"))
+                .unwrap_err()
+                .value()
+                .as_str(),
+            "bad operation \"enterstep\": must be enter or leave"
+        );
+        assert!(ExecOp::from_list(&Value::from("")).is_err());
+    }
+
+    #[test]
+    fn test_same_ops() {
+        use TraceOp::*;
+        assert!(same_ops(&[Read, Write], &[Write, Read]));
+        assert!(!same_ops(&[Read, Write], &[Read]));
+        assert!(!same_ops(&[Read], &[Unset]));
+    }
+}
@@ -23,9 +23,11 @@
 //! [`Value`]: ../value/index.html
 //! [`interp`]: interp/index.html
 
+use crate::list::list_to_string;
 pub use crate::value::Value;
 use indexmap::IndexMap;
 use std::fmt;
+use std::rc::Rc;
 use std::str::FromStr;
 
 // Molt Numeric Types
@@ -49,6 +51,20 @@ pub type MoltFloat = f64;
 ///
 /// Lists are an important data structure, both in Molt code proper and in Rust code
 /// that implements and works with Molt commands.  A list is a vector of `Value`s.
+///
+/// Being a `Vec`, a `MoltList` can be built with `collect` and extended with `extend`,
+/// e.g., with the elements of another list `Value`; and a `Value` can be collected
+/// directly from any iterator of items convertible to `Value`s.
+///
+/// ```
+/// # use molt_forked::prelude::*;
+/// let mut list: MoltList = ["a", "b"].iter().map(|s| Value::from(*s)).collect();
+/// list.extend(Value::from("c d").as_list_iter().unwrap());
+/// assert_eq!(Value::from(list).as_str(), "a b c d");
+///
+/// let value: Value = ["a", "b"].iter().copied().collect();
+/// assert_eq!(value.as_str(), "a b");
+/// ```
 pub type MoltList = Vec<Value>;
 
 /// The standard dictionary type for Molt code.
@@ -138,6 +154,26 @@ pub enum ResultCode {
     /// explicitly, unless implementing application-specific control structures.  See
     /// The Molt Book documentation for the `return` and `catch` command for the semantics.
     Continue,
+
+    /// A `tailcall` in a Molt procedure.  The `Exception::value` is the command to call,
+    /// as a list, in place of the procedure once its scope has been popped.  It can't be
+    /// caught; if it is received outside of any procedure, the interpreter will convert
+    /// it into an error.
+    ///
+    /// Clients will rarely need to interact with or reference this result code
+    /// explicitly.  See The Molt Book documentation for the `tailcall` command.
+    TailCall,
+
+    /// A script stopped through the interpreter's
+    /// [`CancelHandle`](../cancel/struct.CancelHandle.html).  The `Exception::value` is
+    /// "evaluation canceled", and the exception has error data, with error code
+    /// `CANCELED`, so that it can be reported like an error.  It passes through loops and
+    /// procedures to the top level; `catch` catches it as an error unless
+    /// [`Interp::set_cancel_uncatchable`] is set.
+    ///
+    /// [`Interp::set_cancel_uncatchable`]: ../interp/struct.Interp.html#method.set_cancel_uncatchable
+    Cancelled,
+
     /// A mechanism for defining application-specific result codes.
     /// Clients will rarely need to interact with or reference this result code
     /// explicitly, unless implementing application-specific control structures. See
@@ -155,6 +191,8 @@ impl fmt::Display for ResultCode {
             ResultCode::Return => write!(f, "return"),
             ResultCode::Break => write!(f, "break"),
             ResultCode::Continue => write!(f, "continue"),
+            ResultCode::TailCall => write!(f, "tailcall"),
+            ResultCode::Cancelled => write!(f, "cancelled"),
             ResultCode::Other(code) => write!(f, "{}", *code),
         }
     }
@@ -215,6 +253,8 @@ impl ResultCode {
             ResultCode::Return => 2,
             ResultCode::Break => 3,
             ResultCode::Continue => 4,
+            ResultCode::TailCall => 2,
+            ResultCode::Cancelled => 1,
             ResultCode::Other(num) => *num,
         }
     }
@@ -245,6 +285,9 @@ pub struct Exception {
     /// Require input in interact mode
     /// equal to Error otherwise
     uncompleted: bool,
+    /// The unclosed delimiter that made the input incomplete, with the byte offset and
+    /// line number at which it was opened.
+    open_delimiter: Option<(Delimiter, usize, usize)>,
     /// The result value
     value: Value,
 
@@ -287,7 +330,31 @@ impl Exception {
     pub fn is_uncompleted(&self) -> bool {
         self.uncompleted
     }
-    /// Returns the exception's error code, only if `is_error()`.
+
+    /// Returns true if the exception is a parse error that more input could fix: a brace,
+    /// bracket, or quote left open.  Other parse errors, e.g., extra characters after a
+    /// close-brace, remain errors however many lines are added.
+    #[inline]
+    pub fn is_recoverable_by_more_input(&self) -> bool {
+        self.uncompleted
+    }
+
+    /// Returns the kind of delimiter left open, if the exception was produced by
+    /// parsing incomplete input.
+    #[inline]
+    pub fn uncompleted_kind(&self) -> Option<Delimiter> {
+        self.open_delimiter.map(|(kind, _, _)| kind)
+    }
+
+    /// Returns the byte offset and the line number (counting from 1) at which the
+    /// unclosed delimiter was opened, if the exception was produced by parsing
+    /// incomplete input.
+    #[inline]
+    pub fn uncompleted_position(&self) -> Option<(usize, usize)> {
+        self.open_delimiter.map(|(_, offset, line)| (offset, line))
+    }
+    /// Returns the exception's error code, only if `is_error()`, or if the exception is a
+    /// cancellation.
     /// exception.
     ///
     /// # Panics
@@ -298,8 +365,32 @@ impl Exception {
         self.error_data().expect("exception is not an error").error_code()
     }
 
+    /// Returns the exception's error code as a list, only if `is_error()`.  Error codes
+    /// are conventionally lists whose first element names the class of error, e.g.,
+    /// `ARITH DIVZERO {divide by zero}`.  Returns an error if the code isn't a valid list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exception is not an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// let exception = interp
+    ///     .eval("throw {ARITH DIVZERO {divide by zero}} \"divide by zero\"")
+    ///     .unwrap_err();
+    /// let code = exception.error_code_list().unwrap();
+    /// assert_eq!(code[0].as_str(), "ARITH");
+    /// assert_eq!(code[2].as_str(), "divide by zero");
+    /// ```
+    pub fn error_code_list(&self) -> Result<Rc<MoltList>, Exception> {
+        self.error_code().as_list()
+    }
+
     /// Returns the exception's error info, i.e., the human-readable error
-    /// stack trace, only if `is_error()`.
+    /// stack trace, only if `is_error()`, or if the exception is a cancellation.
     ///
     /// # Panics
     ///
@@ -310,7 +401,7 @@ impl Exception {
     }
 
     /// Gets the exception's [`ErrorData`], if any; the error data is available only when
-    /// the `code()` is `ResultCode::Error`.  The error data contains the error's error code
+    /// the `code()` is `ResultCode::Error` or `ResultCode::Cancelled`.  The error data contains the error's error code
     /// and stack trace information.
     ///
     /// # Example
@@ -459,6 +550,21 @@ impl Exception {
         }
     }
 
+    /// Adds the "while executing" lines for the command that threw the error, given as its
+    /// words, to the error info.  The words are formatted only if the error info is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exception is not an error exception.
+    #[inline]
+    pub(crate) fn add_error_command(&mut self, words: &[Value]) {
+        if let Some(data) = &mut self.error_data {
+            data.add_command(words);
+        } else {
+            panic!("add_error_command called for non-Error Exception");
+        }
+    }
+
     /// Creates an `Error` exception with the given error message.  This is primarily
     /// intended for use by the [`molt_err!`] macro, but it can also be used directly.
     ///
@@ -475,7 +581,7 @@ impl Exception {
     /// [`molt_err`]: ../macro.molt_err.html
     #[inline]
     pub fn molt_err(msg: Value) -> Self {
-        let data = ErrorData::new(Value::from("NONE"), msg.as_str());
+        let data = ErrorData::new(Value::from("NONE"), msg.clone());
 
         Self {
             code: ResultCode::Error,
@@ -484,6 +590,7 @@ impl Exception {
             next_code: ResultCode::Error,
             error_data: Some(data),
             uncompleted: false,
+            open_delimiter: None,
         }
     }
     #[inline]
@@ -497,6 +604,14 @@ impl Exception {
         self.uncompleted = true;
     }
 
+    /// Marks the exception as incomplete input, recording the delimiter left open and
+    /// the byte offset and line at which it was opened.
+    #[inline]
+    pub fn to_uncomplete_at(&mut self, kind: Delimiter, offset: usize, line: usize) {
+        self.uncompleted = true;
+        self.open_delimiter = Some((kind, offset, line));
+    }
+
     /// Creates an `Error` exception with the given error code and message.  An
     /// error code is a `MoltList` that indicates the nature of the error.  Standard TCL
     /// uses the error code to flag specific arithmetic and I/O errors; most other
@@ -517,7 +632,7 @@ impl Exception {
     ///
     /// [`molt_err`]: ../macro.molt_err.html
     pub fn molt_err2(error_code: Value, msg: Value) -> Self {
-        let data = ErrorData::new(error_code, msg.as_str());
+        let data = ErrorData::new(error_code, msg.clone());
 
         Self {
             code: ResultCode::Error,
@@ -526,6 +641,7 @@ impl Exception {
             next_code: ResultCode::Error,
             error_data: Some(data),
             uncompleted: false,
+            open_delimiter: None,
         }
     }
 
@@ -544,6 +660,7 @@ impl Exception {
             next_code: ResultCode::Okay,
             error_data: None,
             uncompleted: false,
+            open_delimiter: None,
         }
     }
 
@@ -567,6 +684,7 @@ impl Exception {
             next_code,
             error_data: None,
             uncompleted: false,
+            open_delimiter: None,
         }
     }
 
@@ -587,7 +705,7 @@ impl Exception {
         let error_code = error_code.unwrap_or_else(|| Value::from("NONE"));
         let error_info = error_info.unwrap_or_else(Value::empty);
 
-        let data = ErrorData::rethrow(error_code, error_info.as_str());
+        let data = ErrorData::rethrow(error_code, error_info);
 
         Self {
             code: if level == 0 { ResultCode::Error } else { ResultCode::Return },
@@ -596,6 +714,7 @@ impl Exception {
             next_code: ResultCode::Error,
             error_data: Some(data),
             uncompleted: false,
+            open_delimiter: None,
         }
     }
 
@@ -613,6 +732,7 @@ impl Exception {
             next_code: ResultCode::Break,
             error_data: None,
             uncompleted: false,
+            open_delimiter: None,
         }
     }
 
@@ -630,6 +750,43 @@ impl Exception {
             next_code: ResultCode::Continue,
             error_data: None,
             uncompleted: false,
+            open_delimiter: None,
+        }
+    }
+
+    /// Creates a `Cancelled` exception, the result of a script stopped through the
+    /// interpreter's [`CancelHandle`](../cancel/struct.CancelHandle.html).
+    ///
+    /// This method is primarily for use by the interpreter, and should rarely if ever be
+    /// needed in client code.
+    pub fn molt_cancelled() -> Self {
+        let msg = Value::from("evaluation canceled");
+
+        Self {
+            code: ResultCode::Cancelled,
+            value: msg.clone(),
+            level: 0,
+            next_code: ResultCode::Cancelled,
+            error_data: Some(ErrorData::new(Value::from("CANCELED"), msg)),
+            uncompleted: false,
+            open_delimiter: None,
+        }
+    }
+
+    /// Creates a `TailCall` exception, to call the `command`, a list of words, in place
+    /// of the procedure that returns it.
+    ///
+    /// This method is primarily for use by the `tailcall` command, and should rarely if
+    /// ever be needed in client code.
+    pub fn molt_tailcall(command: Value) -> Self {
+        Self {
+            code: ResultCode::TailCall,
+            value: command,
+            level: 0,
+            next_code: ResultCode::TailCall,
+            error_data: None,
+            uncompleted: false,
+            open_delimiter: None,
         }
     }
 
@@ -657,30 +814,105 @@ impl Exception {
             false
         }
     }
+
+    /// Returns the exception as the CLI shell reports it: for errors, the error info,
+    /// i.e., the message followed by the stack trace; otherwise, just the value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let mut interp = Interp::default();
+    /// let exception = interp.eval("error oops").unwrap_err();
+    /// assert_eq!(exception.to_string(), "oops");
+    /// assert_eq!(exception.into_report_string(), "oops\nwhile executing\n  \"error oops\"");
+    /// ```
+    pub fn into_report_string(self) -> String {
+        match self.error_data {
+            Some(data) => data.error_info().to_string(),
+            None => self.value.to_string(),
+        }
+    }
+}
+
+/// Displays the exception's value, i.e., its error message.
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+/// Lets an `Exception` travel through `Box<dyn Error>`, `anyhow`, and the like.
+impl std::error::Error for Exception {}
+
+/// Converts an I/O error into an error exception whose error code is a
+/// `POSIX errno message` list, as in Standard TCL.
+impl From<std::io::Error> for Exception {
+    fn from(err: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let (errno, msg) = match err.kind() {
+            ErrorKind::NotFound => ("ENOENT", "no such file or directory"),
+            ErrorKind::PermissionDenied => ("EACCES", "permission denied"),
+            ErrorKind::AlreadyExists => ("EEXIST", "file already exists"),
+            ErrorKind::InvalidInput => ("EINVAL", "invalid argument"),
+            ErrorKind::Interrupted => ("EINTR", "interrupted system call"),
+            ErrorKind::WouldBlock => ("EAGAIN", "resource temporarily unavailable"),
+            ErrorKind::BrokenPipe => ("EPIPE", "broken pipe"),
+            ErrorKind::TimedOut => ("ETIMEDOUT", "connection timed out"),
+            ErrorKind::ConnectionRefused => ("ECONNREFUSED", "connection refused"),
+            ErrorKind::ConnectionReset => ("ECONNRESET", "connection reset by peer"),
+            ErrorKind::AddrInUse => ("EADDRINUSE", "address already in use"),
+            _ => ("EIO", ""),
+        };
+        let msg = if msg.is_empty() { err.to_string() } else { msg.to_string() };
+        let error_code = Value::from(vec![
+            Value::from("POSIX"),
+            Value::from(errno),
+            Value::from(msg.as_str()),
+        ]);
+
+        Exception::molt_err2(error_code, Value::from(msg))
+    }
 }
 
 /// This struct contains the error code and stack trace (i.e., the "error info" string)
 /// for `ResultCode::Error` exceptions.
-#[derive(Debug, Clone, Eq, PartialEq)]
+///
+/// The stack trace is kept as a list of lines, and joined into a string only when the
+/// error info is requested; an error that is caught and discarded never pays for it.
+#[derive(Debug, Clone)]
 pub struct ErrorData {
     /// The error code; defaults to "NONE"
     error_code: Value,
 
     /// The TCL stack trace.
-    stack_trace: Vec<String>,
+    stack_trace: StackTrace,
 
     /// Is this a new error?
     is_new: bool,
 }
 
+impl PartialEq for ErrorData {
+    /// Two ErrorData are equal if they have the same error code and error info, however
+    /// their stack traces were built.
+    fn eq(&self, other: &Self) -> bool {
+        self.error_code == other.error_code
+            && self.is_new == other.is_new
+            && self.stack_trace.to_string() == other.stack_trace.to_string()
+    }
+}
+
+impl Eq for ErrorData {}
+
 impl ErrorData {
     // Creates a new ErrorData given the error code and error message.
     // The error data is marked as "new", meaning that the stack_trace is know to contain
     // a single error message.
-    fn new(error_code: Value, error_msg: &str) -> Self {
+    fn new(error_code: Value, error_msg: Value) -> Self {
         Self {
             error_code,
-            stack_trace: vec![error_msg.into()],
+            stack_trace: StackTrace(vec![TraceLine::Text(error_msg)]),
             is_new: true,
         }
     }
@@ -689,10 +921,10 @@ impl ErrorData {
     // The error data is marked as not-new, meaning that the stack_trace has
     // been initialized with a partial stack trace, not just the first error message.
     #[inline]
-    fn rethrow(error_code: Value, error_info: &str) -> Self {
+    fn rethrow(error_code: Value, error_info: Value) -> Self {
         Self {
             error_code,
-            stack_trace: vec![error_info.into()],
+            stack_trace: StackTrace(vec![TraceLine::Text(error_info)]),
             is_new: false,
         }
     }
@@ -709,20 +941,64 @@ impl ErrorData {
         self.is_new
     }
 
-    /// Returns the human-readable stack trace as a string.
+    /// Returns the human-readable stack trace as a string.  The string is built when the
+    /// value's string rep is first needed.
     #[inline]
     pub fn error_info(&self) -> Value {
-        Value::from(self.stack_trace.join("\n"))
+        match self.stack_trace.0.as_slice() {
+            [TraceLine::Text(line)] => line.clone(),
+            _ => Value::from_other(self.stack_trace.clone()),
+        }
     }
 
     /// Adds to the stack trace, which, having been extended, is no longer new.
     #[inline]
     pub(crate) fn add_info(&mut self, info: &str) {
-        self.stack_trace.push(info.into());
+        self.stack_trace.0.push(TraceLine::Text(info.into()));
+        self.is_new = false;
+    }
+
+    /// Adds the command that threw the error to the stack trace, which, having been
+    /// extended, is no longer new.
+    #[inline]
+    pub(crate) fn add_command(&mut self, words: &[Value]) {
+        self.stack_trace.0.push(TraceLine::Executing(words.to_vec()));
         self.is_new = false;
     }
 }
 
+/// A line of an error's stack trace.
+#[derive(Debug, Clone)]
+enum TraceLine {
+    /// A line of text, e.g., the error message.
+    Text(Value),
+
+    /// The command that threw the error, as its words; it's shown as "while executing"
+    /// followed by the command.
+    Executing(MoltList),
+}
+
+/// An error's stack trace, which is displayed as its lines joined by newlines.
+#[derive(Debug, Clone)]
+struct StackTrace(Vec<TraceLine>);
+
+impl fmt::Display for StackTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, line) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match line {
+                TraceLine::Text(text) => write!(f, "{}", text)?,
+                TraceLine::Executing(words) => {
+                    write!(f, "while executing\n  \"{}\"", list_to_string(words))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// In TCL, variable references have two forms.  A string like "_some_var_(_some_index_)" is
 /// the name of an array element; any other string is the name of a scalar variable.  This
 /// struct is used when parsing variable references.  The `name` is the variable name proper;
@@ -758,7 +1034,79 @@ impl VarName {
 
     /// Returns the parsed array index, if any.
     pub fn index(&self) -> Option<&str> {
-        self.index.as_ref().map(|x| &**x)
+        self.index.as_deref()
+    }
+}
+
+/// A delimiter that must be closed for a script to be complete.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Delimiter {
+    /// An open brace, `{`.
+    Brace,
+    /// An open bracket, `[`.
+    Bracket,
+    /// An open double quote, `"`.
+    Quote,
+}
+
+impl fmt::Display for Delimiter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Delimiter::Brace => write!(f, "brace"),
+            Delimiter::Bracket => write!(f, "bracket"),
+            Delimiter::Quote => write!(f, "quote"),
+        }
+    }
+}
+
+/// Whether a script is syntactically complete; see `Interp::completeness`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Completeness {
+    /// The script parses.
+    Complete,
+    /// The script has an unclosed delimiter, opened at the given line (counting from 1).
+    /// If delimiters are nested, the innermost one is reported.
+    Incomplete { kind: Delimiter, line: usize },
+    /// The script can't be parsed, and no further input would fix it, e.g., `a {b}c`.
+    Malformed { message: String },
+}
+
+/// Arity metadata for an embedded command registered with `gen_command!`.
+///
+/// `min` and `max` count the whole `argv`, including the command name, and have the
+/// same meaning as in [`check_args`]: a `max` of `0` means "no upper limit".  `usage`
+/// is the argument signature, e.g. `"x ?y?"`; it is used both in the generated
+/// `wrong # args` message and in the `help` output.
+///
+/// When an embedded entry carries an `ArgSpec`, the generated dispatcher checks the
+/// argument count before calling the command function, so the function need not call
+/// `check_args` itself.
+///
+/// [`check_args`]: ../fn.check_args.html
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ArgSpec {
+    pub min: usize,
+    pub max: usize,
+    pub usage: &'static str,
+}
+
+/// Selects the state exported by `Interp::export_state`.
+///
+/// Each field is a list of glob patterns, as for `string match`; a proc or global
+/// variable is exported if its name matches any of the patterns.  The default filter
+/// exports nothing; [`StateFilter::all`] exports every proc and global variable.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct StateFilter {
+    /// Patterns for the names of the procs to export.
+    pub procs: Vec<String>,
+    /// Patterns for the names of the global variables to export.
+    pub vars: Vec<String>,
+}
+
+impl StateFilter {
+    /// Creates a filter that exports every proc and global variable.
+    pub fn all() -> Self {
+        Self { procs: vec!["*".into()], vars: vec!["*".into()] }
     }
 }
 
@@ -774,6 +1122,7 @@ mod tests {
         assert_eq!(Value::from_other(ResultCode::Return).as_str(), "return");
         assert_eq!(Value::from_other(ResultCode::Break).as_str(), "break");
         assert_eq!(Value::from_other(ResultCode::Continue).as_str(), "continue");
+        assert_eq!(Value::from_other(ResultCode::Cancelled).as_str(), "cancelled");
         assert_eq!(Value::from_other(ResultCode::Other(5)).as_str(), "5");
     }
 
@@ -796,12 +1145,73 @@ mod tests {
         assert_eq!(ResultCode::Return.as_int(), 2);
         assert_eq!(ResultCode::Break.as_int(), 3);
         assert_eq!(ResultCode::Continue.as_int(), 4);
+        assert_eq!(ResultCode::Cancelled.as_int(), 1);
         assert_eq!(ResultCode::Other(5).as_int(), 5);
     }
 
+    #[test]
+    fn test_exception_display() {
+        let ex = Exception::molt_err("error message".into());
+        assert_eq!(ex.to_string(), "error message");
+        assert_eq!(format!("[{}]", ex), "[error message]");
+
+        let ex = Exception::molt_break();
+        assert_eq!(ex.to_string(), "");
+    }
+
+    #[test]
+    fn test_exception_into_report_string() {
+        let mut ex = Exception::molt_err("error message".into());
+        ex.add_error_info("next line");
+        assert_eq!(ex.into_report_string(), "error message\nnext line");
+
+        let ex = Exception::molt_return("value".into());
+        assert_eq!(ex.into_report_string(), "value");
+    }
+
+    fn boxed_error(result: MoltResult) -> Result<Value, Box<dyn std::error::Error>> {
+        let value = result?;
+        Ok(value)
+    }
+
+    #[test]
+    fn test_exception_as_std_error() {
+        assert_eq!(boxed_error(Ok("ok".into())).unwrap(), "ok".into());
+
+        let err = boxed_error(Err(Exception::molt_err("failed".into()))).unwrap_err();
+        assert_eq!(err.to_string(), "failed");
+        assert!(err.source().is_none());
+        let ex = err.downcast_ref::<Exception>().unwrap();
+        assert!(ex.is_error());
+    }
+
+    #[test]
+    fn test_exception_from_io_error() {
+        use std::io::{Error, ErrorKind};
+
+        let ex = Exception::from(Error::from(ErrorKind::NotFound));
+        assert!(ex.is_error());
+        assert_eq!(ex.value(), "no such file or directory".into());
+        assert_eq!(
+            ex.error_code(),
+            Value::from("POSIX ENOENT {no such file or directory}")
+        );
+
+        let ex = Exception::from(Error::other("disk on fire"));
+        assert_eq!(ex.value(), "disk on fire".into());
+        assert_eq!(ex.error_code(), Value::from("POSIX EIO {disk on fire}"));
+
+        // `?` converts I/O errors in functions returning a MoltResult.
+        fn read(path: &str) -> MoltResult {
+            Ok(std::fs::read_to_string(path)?.into())
+        }
+        let ex = read("/nonexistent/molt/file").unwrap_err();
+        assert_eq!(ex.error_code_list().unwrap()[1], "ENOENT".into());
+    }
+
     #[test]
     fn test_error_data_new() {
-        let data = ErrorData::new("CODE".into(), "error message");
+        let data = ErrorData::new("CODE".into(), "error message".into());
 
         assert_eq!(data.error_code(), "CODE".into());
         assert_eq!(data.error_info(), "error message".into());
@@ -810,7 +1220,7 @@ mod tests {
 
     #[test]
     fn test_error_data_rethrow() {
-        let data = ErrorData::rethrow("CODE".into(), "stack trace");
+        let data = ErrorData::rethrow("CODE".into(), "stack trace".into());
 
         assert_eq!(data.error_code(), "CODE".into());
         assert_eq!(data.error_info(), "stack trace".into());
@@ -819,7 +1229,7 @@ mod tests {
 
     #[test]
     fn test_error_data_add_info() {
-        let mut data = ErrorData::new("CODE".into(), "error message");
+        let mut data = ErrorData::new("CODE".into(), "error message".into());
 
         assert_eq!(data.error_info(), "error message".into());
         assert!(data.is_new());
@@ -829,6 +1239,26 @@ mod tests {
         assert!(!data.is_new());
     }
 
+    #[test]
+    fn test_error_data_add_command() {
+        let mut data = ErrorData::new("CODE".into(), "error message".into());
+        data.add_command(&["expr".into(), "1/0".into()]);
+        data.add_info("    (procedure body)");
+
+        assert_eq!(
+            data.error_info().as_str(),
+            "error message\nwhile executing\n  \"expr 1/0\"\n    (procedure body)"
+        );
+        assert!(!data.is_new());
+
+        // Equality depends on the text, not on how the stack trace was built.
+        let mut lines = ErrorData::new("CODE".into(), "error message".into());
+        lines.add_info("while executing");
+        lines.add_info("  \"expr 1/0\"");
+        lines.add_info("    (procedure body)");
+        assert_eq!(data, lines);
+    }
+
     #[test]
     fn test_exception_molt_err() {
         let mut exception = Exception::molt_err("error message".into());
@@ -960,4 +1390,25 @@ mod tests {
         assert!(!exception.is_error());
         assert!(!exception.error_data().is_some());
     }
+
+    #[test]
+    fn test_exception_molt_tailcall() {
+        let exception = Exception::molt_tailcall("foo a b".into());
+
+        assert_eq!(exception.code(), ResultCode::TailCall);
+        assert_eq!(exception.value(), "foo a b".into());
+        assert!(!exception.is_error());
+        assert!(exception.error_data().is_none());
+    }
+
+    #[test]
+    fn test_exception_molt_cancelled() {
+        let exception = Exception::molt_cancelled();
+
+        assert_eq!(exception.code(), ResultCode::Cancelled);
+        assert_eq!(exception.value(), "evaluation canceled".into());
+        assert!(!exception.is_error());
+        assert_eq!(exception.error_code(), "CANCELED".into());
+        assert_eq!(exception.error_info(), "evaluation canceled".into());
+    }
 }
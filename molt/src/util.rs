@@ -1,12 +1,14 @@
-//! Internal Utilities
+//! Utilities
 //!
-//! This module contains function for use by molt only.
+//! Most of this module is for use by molt only.  [`concat`] is for the authors of commands
+//! that, like `eval`, build a script from their arguments; the name-suggestion functions
+//! are public because the command-generating macros call them.
 
 use crate::tokenizer::Tokenizer;
 use crate::types::*;
 use std::cmp::Ordering;
 
-pub fn is_varname_char(ch: char) -> bool {
+pub(crate) fn is_varname_char(ch: char) -> bool {
     ch.is_alphanumeric() || ch == '_'
 }
 
@@ -23,7 +25,7 @@ pub fn is_varname_char(ch: char) -> bool {
 ///
 /// * The resulting string has the form of an integer, but might be out of the valid range.
 
-pub fn read_int(ptr: &mut Tokenizer) -> Option<String> {
+pub(crate) fn read_int(ptr: &mut Tokenizer) -> Option<String> {
     let mut p = ptr.clone();
     let mut result = String::new();
     let mut missing_digits = true;
@@ -78,7 +80,7 @@ pub fn read_int(ptr: &mut Tokenizer) -> Option<String> {
 ///
 /// * The resulting string has the form of a floating point number but might be out of the
 ///   valid range.
-pub fn read_float(ptr: &mut Tokenizer) -> Option<String> {
+pub(crate) fn read_float(ptr: &mut Tokenizer) -> Option<String> {
     let mut p = ptr.clone();
     let mut result = String::new();
     let mut missing_mantissa = true;
@@ -174,6 +176,190 @@ pub(crate) fn compare_len(
     }
 }
 
+/// Matches a string against a glob-style pattern, as `string match` does in standard
+/// TCL.  In the pattern, `*` matches any sequence of characters, `?` matches any single
+/// character, `[chars]` matches any character in the set (which may include ranges like
+/// `a-z`), and `\x` matches the character `x` literally.
+pub(crate) fn glob_match(pattern: &str, string: &str) -> bool {
+    let pat: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = string.chars().collect();
+
+    let mut p = 0;
+    let mut t = 0;
+
+    // The pattern index just past the most recent '*', and the text index it was
+    // matched up to; used to backtrack when a later match fails.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        let step = match pat.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => glob_match_set(&pat[p + 1..], text[t]).map(|len| len + 1),
+            Some('\\') if p + 1 < pat.len() => {
+                if pat[p + 1] == text[t] {
+                    Some(2)
+                } else {
+                    None
+                }
+            }
+            Some(ch) if *ch == text[t] => Some(1),
+            _ => None,
+        };
+
+        match (step, star) {
+            (Some(len), _) => {
+                p += len;
+                t += 1;
+            }
+            (None, Some((star_p, star_t))) => {
+                // Let the last '*' absorb one more character, and try again.
+                p = star_p;
+                t = star_t + 1;
+                star = Some((star_p, t));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    // Any trailing '*'s match the empty string.
+    pat[p..].iter().all(|ch| *ch == '*')
+}
+
+/// The greatest edit distance at which [`similar_names`] suggests a name.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// The most names [`similar_names`] suggests.
+const MAX_SUGGESTIONS: usize = 3;
+
+// The whitespace that `concat` trims from its arguments, as in Tcl.
+const CONCAT_TRIM: &[char] = &[' ', '\t', '\n', '\r', '\x0B', '\x0C'];
+
+/// Concatenates the values as the `concat` command does: each value is trimmed of leading
+/// and trailing whitespace, and the non-empty results are joined with single spaces.
+/// Whitespace within a value is preserved, and no list quoting is added.  Trailing
+/// whitespace escaped by a backslash keeps one whitespace character, so that the escape
+/// isn't left dangling.
+///
+/// Commands that, like `eval`, evaluate their arguments as a script build it this way.
+///
+/// ```
+/// use molt_forked::util::concat;
+/// # use molt_forked::prelude::*;
+/// let values = [Value::from("a"), Value::from(" b  c "), Value::from("  "), Value::from("{d}")];
+/// assert_eq!(concat(&values).as_str(), "a b  c {d}");
+/// ```
+pub fn concat(values: &[Value]) -> Value {
+    let mut result = String::new();
+
+    for value in values {
+        let s = value.as_str().trim_start_matches(CONCAT_TRIM);
+        let mut trimmed = s.trim_end_matches(CONCAT_TRIM);
+        if trimmed.ends_with('\\') && trimmed.len() < s.len() {
+            let next = s[trimmed.len()..].chars().next().map_or(0, char::len_utf8);
+            trimmed = &s[..trimmed.len() + next];
+        }
+
+        if !trimmed.is_empty() {
+            if !result.is_empty() {
+                result.push(' ');
+            }
+            result.push_str(trimmed);
+        }
+    }
+
+    Value::from(result)
+}
+
+/// Returns the Levenshtein distance between two strings: the number of single-character
+/// insertions, deletions, and substitutions needed to turn one into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+
+    // prev[j] is the distance between the first i-1 chars of a and the first j of b.
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = substitute.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[b.len()]
+}
+
+/// Returns up to three of the candidate names closest to the given misspelled name,
+/// nearest first and alphabetically among equals.  Candidates more than three edits away
+/// are never suggested.
+pub fn similar_names<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    scored.sort_unstable();
+    scored.dedup();
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Formats suggested names as the `, did you mean: a, b?` clause of an "unknown command"
+/// message, or returns the empty string if there are none.
+pub fn did_you_mean(suggestions: &[&str]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(", did you mean: {}?", suggestions.join(", "))
+    }
+}
+
+/// Matches a character against the body of a `[chars]` set, i.e., the pattern just after
+/// the '['.  On a match, returns the length of the set's body, including the closing ']'.
+fn glob_match_set(set: &[char], ch: char) -> Option<usize> {
+    let mut matched = false;
+    let mut i = 0;
+
+    while i < set.len() && set[i] != ']' {
+        let mut lo = set[i];
+        if lo == '\\' && i + 1 < set.len() {
+            i += 1;
+            lo = set[i];
+        }
+
+        if i + 2 < set.len() && set[i + 1] == '-' && set[i + 2] != ']' {
+            let hi = set[i + 2];
+            let (lo, hi) = if lo <= hi { (lo, hi) } else { (hi, lo) };
+            matched |= lo <= ch && ch <= hi;
+            i += 3;
+        } else {
+            matched |= lo == ch;
+            i += 1;
+        }
+    }
+
+    if matched {
+        // An unterminated set extends to the end of the pattern.
+        Some((i + 1).min(set.len()))
+    } else {
+        None
+    }
+}
+
 // From carlomilanesi, rust forums
 // https://users.rust-lang.org/t/how-to-get-a-substring-of-a-string/1351/11
 use std::ops::{Bound, RangeBounds};
@@ -233,6 +419,75 @@ impl StringUtils for str {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("string", "string"), 0);
+        assert_eq!(edit_distance("stirng", "string"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("sets", "set"), 1);
+        assert_eq!(edit_distance("ünï", "uni"), 2);
+    }
+
+    #[test]
+    fn test_similar_names() {
+        let names = ["set", "source", "split", "string", "subst", "unset"];
+
+        assert_eq!(similar_names("stirng", names), vec!["string"]);
+        assert_eq!(similar_names("splt", names), vec!["split", "set", "subst"]);
+
+        // At most three, alphabetically among equals.
+        assert_eq!(similar_names("sett", names), vec!["set", "split", "subst"]);
+        assert_eq!(similar_names("set", ["set", "set"]), vec!["set"]);
+
+        // Nothing within distance 3.
+        assert_eq!(similar_names("xyzzyplugh", names), Vec::<&str>::new());
+        assert_eq!(similar_names("sxxxx", ["s"]), Vec::<&str>::new());
+        assert_eq!(similar_names("sxxx", ["s"]), vec!["s"]);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean(&[]), "");
+        assert_eq!(did_you_mean(&["set"]), ", did you mean: set?");
+        assert_eq!(did_you_mean(&["set", "unset"]), ", did you mean: set, unset?");
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "a"));
+        assert!(glob_match("abc", "abc"));
+        assert!(!glob_match("abc", "abd"));
+
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "abc"));
+        assert!(glob_match("a*", "abc"));
+        assert!(glob_match("*c", "abc"));
+        assert!(glob_match("a*c", "ac"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("*ab", "aab"));
+        assert!(!glob_match("a*d", "abc"));
+
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "ac"));
+
+        assert!(glob_match("[abc]x", "bx"));
+        assert!(!glob_match("[abc]x", "dx"));
+        assert!(glob_match("[a-c]", "b"));
+        assert!(glob_match("[c-a]", "b"));
+        assert!(!glob_match("[a-c]", "d"));
+        assert!(glob_match("x[0-9]*", "x5yz"));
+
+        assert!(glob_match("a\\*", "a*"));
+        assert!(!glob_match("a\\*", "ab"));
+        assert!(glob_match("\\?", "?"));
+
+        assert!(glob_match("é*", "éte"));
+    }
+
     #[test]
     fn test_util_read_int() {
         let mut p = Tokenizer::new("abc");
@@ -167,20 +167,27 @@
 
 use crate::{
     dict::{dict_to_string, list_to_dict},
-    expr::Datum,
+    encoding::Encoding,
+    expr::{self, Datum},
     list::{get_list, list_to_string},
     parser::{self, Script},
     types::{Exception, MoltDict, MoltFloat, MoltInt, MoltList, VarName},
 };
 use std::{
     any::{Any, TypeId},
-    cell::{RefCell, UnsafeCell},
+    cell::{Cell, RefCell, UnsafeCell},
     fmt::{Debug, Display},
     hash::{Hash, Hasher},
     rc::Rc,
     str::FromStr,
 };
 
+thread_local! {
+    // The number of significant digits in a float's string rep, or 0 for the fewest that
+    // parse back to the same float.  See `Value::set_float_precision`.
+    static FLOAT_PRECISION: Cell<usize> = const { Cell::new(0) };
+}
+
 //-----------------------------------------------------------------------------
 // Public Data Types
 
@@ -204,6 +211,9 @@ impl Hash for Value {
 struct InnerValue {
     string_rep: UnsafeCell<Option<String>>,
     data_rep: RefCell<DataRep>,
+    // The number of chars in the string rep, counted on first use.  Like the string
+    // rep itself, it never changes once set.
+    char_len: Cell<Option<usize>>,
 }
 
 impl std::fmt::Debug for Value {
@@ -222,6 +232,7 @@ impl Value {
         let inner = InnerValue {
             string_rep: UnsafeCell::new(Some(str)),
             data_rep: RefCell::new(DataRep::None),
+            char_len: Cell::new(None),
         };
 
         Self { inner: Rc::new(inner) }
@@ -232,6 +243,7 @@ impl Value {
         let inner = InnerValue {
             string_rep: UnsafeCell::new(None),
             data_rep: RefCell::new(data),
+            char_len: Cell::new(None),
         };
 
         Self { inner: Rc::new(inner) }
@@ -248,8 +260,35 @@ impl Display for Value {
 impl Eq for Value {}
 impl PartialEq for Value {
     /// Two Values are equal if their string representations are equal.  Application code will
-    /// often want to compare values numerically.
+    /// often want to compare values numerically; see [`Value::eq_numeric`].
+    ///
+    /// Two fast paths avoid the string comparison, neither of which can disagree with it:
+    /// clones of the same `Value` are always equal, and two values that have no string rep
+    /// yet and hold the same integer or bytes, or bit-for-bit the same float, will format to
+    /// the same string.  A value with a string rep is always compared by string, since distinct
+    /// strings, e.g., `0x10` and `16`, can share a data rep.  Anything else, including an
+    /// integer compared with a float, falls back to comparing the strings.
     fn eq(&self, other: &Self) -> bool {
+        if Rc::ptr_eq(&self.inner, &other.inner) {
+            return true;
+        }
+
+        if !self.has_string_rep() && !other.has_string_rep() {
+            let same =
+                match (&*self.inner.data_rep.borrow(), &*other.inner.data_rep.borrow()) {
+                    (DataRep::Int(a), DataRep::Int(b)) => Some(a == b),
+                    (DataRep::Bytes(a), DataRep::Bytes(b)) => Some(a == b),
+                    (DataRep::Flt(a), DataRep::Flt(b)) if a.to_bits() == b.to_bits() => {
+                        Some(true)
+                    }
+                    _ => None,
+                };
+
+            if let Some(same) = same {
+                return same;
+            }
+        }
+
         self.as_str() == other.as_str()
     }
 }
@@ -360,12 +399,11 @@ impl From<MoltFloat> for Value {
     ///
     /// # String Representation
     ///
-    /// The string representation of the value will be however Rust's `format!` macro
-    /// formats floating point numbers by default.  **Note**: this isn't quite what we
-    /// want; Standard TCL goes to great lengths to ensure that the formatted string
-    /// will yield exactly the same floating point number when it is parsed.  Rust
-    /// will format the number `5.0` as `5`, turning it into a integer if parsed naively. So
-    /// there is more work to be done here.
+    /// As in Standard TCL, the string representation is the shortest that parses back
+    /// into exactly the same floating point number, and always looks like a float: the
+    /// number `5.0` is formatted as `5.0`, not `5`.  Very large and very small numbers
+    /// are formatted with an exponent, e.g., `1e+300`.  See
+    /// [`Value::set_float_precision`] to use a fixed number of digits instead.
     ///
     /// # Example
     ///
@@ -414,6 +452,197 @@ impl From<&[Value]> for Value {
     }
 }
 
+impl<T: Into<Value>> FromIterator<T> for Value {
+    /// Creates a new `Value` whose data representation is a `MoltList`, from the items
+    /// of an iterator.  This is the simplest way for a command to return a list.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// fn cmd_names(_interp: &mut Interp<()>, _argv: &[Value]) -> MoltResult {
+    ///     let names = ["a", "b c", "d"];
+    ///     molt_ok!(names.iter().map(|name| name.to_uppercase()).collect::<Value>())
+    /// }
+    ///
+    /// let mut interp = Interp::new((), gen_command!((), [("names", cmd_names)], []), false, "app");
+    /// assert_eq!(interp.eval("names").unwrap().as_str(), "A {B C} D");
+    ///
+    /// let value: Value = (1..=3).collect();
+    /// assert_eq!(value.as_list().unwrap().len(), 3);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let list: MoltList = iter.into_iter().map(Into::into).collect();
+        Value::from(list)
+    }
+}
+
+impl From<i32> for Value {
+    /// Creates a new `Value` whose data representation is a `MoltInt`.
+    fn from(int: i32) -> Self {
+        Value::inner_from_data(DataRep::Int(int as MoltInt))
+    }
+}
+
+impl From<u32> for Value {
+    /// Creates a new `Value` whose data representation is a `MoltInt`.
+    fn from(int: u32) -> Self {
+        Value::inner_from_data(DataRep::Int(int as MoltInt))
+    }
+}
+
+impl From<usize> for Value {
+    /// Creates a new `Value` whose data representation is a `MoltInt`.
+    ///
+    /// A `usize` too large for a `MoltInt` is never truncated: the `Value` gets the
+    /// number's decimal string rep instead, and `as_int` on it will fail.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt_forked::prelude::*;
+    ///
+    /// let items = vec!["a", "b", "c"];
+    /// let value = Value::from(items.len());
+    /// assert_eq!(value.as_int(), Ok(3));
+    /// ```
+    fn from(int: usize) -> Self {
+        match MoltInt::try_from(int) {
+            Ok(int) => Value::inner_from_data(DataRep::Int(int)),
+            Err(_) => Value::inner_from_string(int.to_string()),
+        }
+    }
+}
+
+impl TryFrom<u64> for Value {
+    type Error = Exception;
+
+    /// Creates a new `Value` whose data representation is a `MoltInt`, returning an
+    /// error if the number is too large for a `MoltInt`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt_forked::prelude::*;
+    ///
+    /// assert_eq!(Value::try_from(17u64), Ok(Value::from(17)));
+    /// assert!(Value::try_from(u64::MAX).is_err());
+    /// ```
+    fn try_from(int: u64) -> Result<Self, Self::Error> {
+        match MoltInt::try_from(int) {
+            Ok(int) => Ok(Value::inner_from_data(DataRep::Int(int))),
+            Err(_) => molt_err!("integer value too large to represent: \"{}\"", int),
+        }
+    }
+}
+
+impl TryFrom<&Value> for MoltInt {
+    type Error = Exception;
+
+    /// Equivalent to [`Value::as_int`](#method.as_int).
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_int()
+    }
+}
+
+impl TryFrom<&Value> for MoltFloat {
+    type Error = Exception;
+
+    /// Equivalent to [`Value::as_float`](#method.as_float).
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_float()
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = Exception;
+
+    /// Equivalent to [`Value::as_bool`](#method.as_bool).
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_bool()
+    }
+}
+
+impl TryFrom<&Value> for String {
+    type Error = Exception;
+
+    /// Returns a copy of the value's string rep; this never fails.
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        Ok(value.as_str().to_string())
+    }
+}
+
+impl TryFrom<&Value> for usize {
+    type Error = Exception;
+
+    /// Converts the value to a `usize`, returning an error if the value isn't an integer
+    /// or is negative.  This is the safe way to get an index from a command argument.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt_forked::prelude::*;
+    ///
+    /// fn cmd_nth(_interp: &mut Interp<()>, argv: &[Value]) -> MoltResult {
+    ///     check_args(1, argv, 3, 3, "list index")?;
+    ///     let list = argv[1].as_list()?;
+    ///     let index = usize::try_from(&argv[2])?;
+    ///     molt_ok!(list.get(index).cloned().unwrap_or_else(Value::empty))
+    /// }
+    ///
+    /// let mut interp = Interp::default();
+    /// let argv = [Value::from("nth"), Value::from("a b c"), Value::from(1)];
+    /// assert_eq!(cmd_nth(&mut interp, &argv), Ok(Value::from("b")));
+    ///
+    /// let argv = [Value::from("nth"), Value::from("a b c"), Value::from(-1)];
+    /// assert!(cmd_nth(&mut interp, &argv).is_err());
+    /// ```
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let int = value.as_int()?;
+
+        if int < 0 {
+            return molt_err!("expected non-negative integer but got \"{}\"", value);
+        }
+
+        match usize::try_from(int) {
+            Ok(int) => Ok(int),
+            Err(_) => molt_err!("integer value too large to represent: \"{}\"", value),
+        }
+    }
+}
+
+impl TryFrom<&Value> for u32 {
+    type Error = Exception;
+
+    /// Converts the value to a `u32`, returning an error if the value isn't an integer,
+    /// is negative, or is too large.
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let int = value.as_int()?;
+
+        if int < 0 {
+            return molt_err!("expected non-negative integer but got \"{}\"", value);
+        }
+
+        match u32::try_from(int) {
+            Ok(int) => Ok(int),
+            Err(_) => molt_err!("integer value too large to represent: \"{}\"", value),
+        }
+    }
+}
+
+impl TryFrom<&Value> for i32 {
+    type Error = Exception;
+
+    /// Converts the value to an `i32`, returning an error if the value isn't an integer
+    /// or is out of range.
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match i32::try_from(value.as_int()?) {
+            Ok(int) => Ok(int),
+            Err(_) => molt_err!("integer value too large to represent: \"{}\"", value),
+        }
+    }
+}
+
 impl Value {
     /// Returns the empty `Value`, a value whose string representation is the empty
     /// string.
@@ -441,7 +670,8 @@ impl Value {
         // FIRST, get the string rep, computing it from the data_rep if necessary.
         // self.inner.string_rep.get_or_init(|| (self.inner.data_rep.borrow()).to_string())
 
-        // NOTE: This method is the only place where the string_rep is queried.
+        // NOTE: This method and `has_string_rep` are the only places where the string_rep
+        // is queried; neither holds the reference past the point where it's set below.
         let slot = unsafe { &*self.inner.string_rep.get() };
 
         if let Some(inner) = slot {
@@ -451,13 +681,84 @@ impl Value {
         // NOTE: This is the only place where the string_rep is set.
         // Because we returned it if it was Some, it is only ever set once.
         // Thus, this is safe: as_str() is the only way to retrieve the string_rep,
-        // and it computes the string_rep lazily after which it is immutable.
+        // has_string_rep() only checks whether it's there, and as_str() computes the
+        // string_rep lazily after which it is immutable.
         let slot = unsafe { &mut *self.inner.string_rep.get() };
         *slot = Some((self.inner.data_rep.borrow()).to_string());
 
         slot.as_ref().expect("string rep")
     }
 
+    /// Returns true if the value's string rep has been computed.  A value without one
+    /// still holds the data rep it was created with: every conversion of the data rep
+    /// computes the string rep first.
+    fn has_string_rep(&self) -> bool {
+        // NOTE: Like `as_str`, this only reads the slot; it never sets it.
+        let slot = unsafe { &*self.inner.string_rep.get() };
+        slot.is_some()
+    }
+
+    /// Compares two values as `expr`'s `==` operator would: numerically if both look like
+    /// numbers, as integers if both are integers, and by string otherwise.  Thus, `7.0` is
+    /// numerically equal to `7`, though the two values are not `==` in Rust.  Returns an
+    /// error if a value looks like a number but can't be converted, e.g., an integer too
+    /// large for a `MoltInt`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// # fn dummy() -> Result<(), Exception> {
+    /// assert!(Value::from("7.0").eq_numeric(&Value::from(7))?);
+    /// assert!(Value::from("0x10").eq_numeric(&Value::from("16"))?);
+    /// assert!(!Value::from("abc").eq_numeric(&Value::from("7"))?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eq_numeric(&self, other: &Value) -> Result<bool, Exception> {
+        if Rc::ptr_eq(&self.inner, &other.inner) {
+            return Ok(true);
+        }
+
+        let a = expr::expr_parse_value(self)?;
+        let b = expr::expr_parse_value(other)?;
+
+        if let (Some(a), Some(b)) = (a.as_int(), b.as_int()) {
+            return Ok(a == b);
+        }
+
+        let to_float =
+            |d: &Datum| d.as_float().or_else(|| d.as_int().map(|i| i as MoltFloat));
+
+        match (to_float(&a), to_float(&b)) {
+            (Some(a), Some(b)) => Ok(a == b),
+            _ => Ok(self.as_str() == other.as_str()),
+        }
+    }
+
+    /// Returns the number of chars, i.e., Unicode code points, in the value's string
+    /// representation.  This is what `string length` reports; note that one
+    /// user-perceived character, such as a flag or an emoji ZWJ sequence, may consist of
+    /// several code points.  The count is computed once and cached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// assert_eq!(Value::from("abc").char_len(), 3);
+    /// assert_eq!(Value::from("日本語").char_len(), 3);
+    /// assert_eq!(Value::from("日本語").as_str().len(), 9);
+    /// ```
+    pub fn char_len(&self) -> usize {
+        if let Some(len) = self.inner.char_len.get() {
+            return len;
+        }
+
+        let len = self.as_str().chars().count();
+        self.inner.char_len.set(Some(len));
+        len
+    }
+
     /// Returns the value's string representation if it is already ready.
     /// This is useful for implementing command line switches.
     ///
@@ -797,23 +1098,160 @@ impl Value {
         }
     }
 
-    /// Computes the string rep for a MoltFloat.
+    /// Returns the number of significant digits with which floats are given their string
+    /// reps, or `0`, the default, for the fewest digits that parse back to the same float.
+    pub fn float_precision() -> usize {
+        FLOAT_PRECISION.with(Cell::get)
+    }
+
+    /// Sets the number of significant digits, at most 17, with which floats are given
+    /// their string reps on the current thread, as TCL's `tcl_precision` variable does.
+    /// `0`, the default, gives the fewest digits that parse back to the same float; any
+    /// other precision may lose some of the float's value.  Floats whose string reps
+    /// have already been computed keep them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use molt_forked::prelude::*;
+    ///
+    /// assert_eq!(Value::from(2.0 / 3.0).as_str(), "0.6666666666666666");
     ///
-    /// TODO: This needs a lot of work, so that floating point outputs will parse back into
-    /// the same floating point numbers.
+    /// Value::set_float_precision(4);
+    /// assert_eq!(Value::from(2.0 / 3.0).as_str(), "0.6667");
+    /// Value::set_float_precision(0);
+    /// ```
+    pub fn set_float_precision(digits: usize) {
+        FLOAT_PRECISION.with(|precision| precision.set(digits.min(17)));
+    }
+
+    /// Computes the string rep for a MoltFloat, as TCL does, so that it parses back
+    /// into the same float: a float with an integral value keeps a trailing `.0`, and
+    /// one less than `1e-4` or at least `1e17` in magnitude is given an exponent.
     fn fmt_float(f: &mut std::fmt::Formatter, flt: MoltFloat) -> std::fmt::Result {
         if flt == std::f64::INFINITY {
-            write!(f, "Inf")
+            return write!(f, "Inf");
         } else if flt == std::f64::NEG_INFINITY {
-            write!(f, "-Inf")
+            return write!(f, "-Inf");
         } else if flt.is_nan() {
-            write!(f, "NaN")
+            return write!(f, "NaN");
+        }
+
+        // FIRST, get the significant digits and the decimal exponent.  Rust's exponential
+        // format gives the shortest digits that round-trip when no precision is given.
+        let precision = Value::float_precision();
+        let text = if precision == 0 {
+            format!("{:e}", flt)
+        } else {
+            format!("{:.*e}", precision - 1, flt)
+        };
+        let (mantissa, exponent) = text.split_once('e').expect("exponential format");
+        let exponent: i32 = exponent.parse().expect("exponent");
+        let (sign, mantissa) = match mantissa.strip_prefix('-') {
+            Some(mantissa) => ("-", mantissa),
+            None => ("", mantissa),
+        };
+        let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+        let digits = match digits.trim_end_matches('0') {
+            "" => "0",
+            digits => digits,
+        };
+
+        // NEXT, write them in exponential format if the number is very large or small.
+        if !(-4..=16).contains(&exponent) {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            return if precision == 0 {
+                write!(f, "{}{}{}{}e{:+}", sign, first, point, rest, exponent)
+            } else {
+                write!(f, "{}{}{}{}e{:+03}", sign, first, point, rest, exponent)
+            };
+        }
+
+        // NEXT, write them in fixed-point format, always with a fraction.
+        if exponent < 0 {
+            let zeros = "0".repeat((-exponent - 1) as usize);
+            return write!(f, "{}0.{}{}", sign, zeros, digits);
+        }
+
+        let whole = exponent as usize + 1;
+        if digits.len() <= whole {
+            let zeros = "0".repeat(whole - digits.len());
+            write!(f, "{}{}{}.0", sign, digits, zeros)
         } else {
-            // TODO: Needs improvement.
-            write!(f, "{}", flt)
+            let (int, frac) = digits.split_at(whole);
+            write!(f, "{}{}.{}", sign, int, frac)
         }
     }
 
+    /// Creates a new `Value` holding a byte string, such as the `binary` command makes.
+    /// Its string representation has one character per byte, from U+0000 to U+00FF.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let value = Value::from_bytes(vec![b'h', b'i', 0xFF]);
+    /// assert_eq!(value.as_str(), "hi\u{FF}");
+    /// assert_eq!(*value.as_bytes().unwrap(), vec![b'h', b'i', 0xFF]);
+    /// ```
+    pub fn from_bytes(bytes: Vec<u8>) -> Value {
+        Value::inner_from_data(DataRep::Bytes(Rc::new(bytes)))
+    }
+
+    /// Tries to return the `Value` as a byte string, converting its string representation
+    /// if necessary.  Each character is a byte, so it's an error if any character is
+    /// beyond U+00FF.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// assert_eq!(*Value::from("abc").as_bytes().unwrap(), b"abc".to_vec());
+    /// assert!(Value::from("\u{100}").as_bytes().is_err());
+    /// ```
+    pub fn as_bytes(&self) -> Result<Rc<Vec<u8>>, Exception> {
+        // FIRST, if we have the desired type, return it.
+        if let DataRep::Bytes(bytes) = &*self.inner.data_rep.borrow() {
+            return Ok(bytes.clone());
+        }
+
+        // NEXT, try to convert the string_rep.
+        let bytes = Rc::new(get_bytes(self.as_str())?);
+        *self.inner.data_rep.borrow_mut() = DataRep::Bytes(bytes.clone());
+        Ok(bytes)
+    }
+
+    /// Creates a new `Value` from bytes in the given encoding, e.g., a byte buffer read
+    /// from a file.  It's an error if the bytes aren't valid in the encoding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let value = Value::from_encoded(&[b'c', 0xE9], Encoding::Latin1).unwrap();
+    /// assert_eq!(value.as_str(), "c\u{E9}");
+    /// assert!(Value::from_encoded(&[b'c', 0xE9], Encoding::Utf8).is_err());
+    /// ```
+    pub fn from_encoded(bytes: &[u8], encoding: Encoding) -> Result<Value, Exception> {
+        Ok(Value::from(encoding.decode(bytes)?))
+    }
+
+    /// Encodes the `Value`'s string representation as bytes in the given encoding.  It's
+    /// an error if the encoding can't represent one of its characters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let value = Value::from("c\u{E9}");
+    /// assert_eq!(value.to_encoded(Encoding::Utf8).unwrap(), vec![b'c', 0xC3, 0xA9]);
+    /// assert!(value.to_encoded(Encoding::Ascii).is_err());
+    /// ```
+    pub fn to_encoded(&self, encoding: Encoding) -> Result<Vec<u8>, Exception> {
+        encoding.encode(self.as_str())
+    }
+
     /// Tries to return the `Value` as an `Rc<MoltList>`, parsing the
     /// value's string representation if necessary.
     ///
@@ -850,6 +1288,28 @@ impl Value {
         Ok(list)
     }
 
+    /// Tries to return an iterator over the `Value`'s list elements, parsing the
+    /// value's string representation if necessary.  The iterator shares the parsed list,
+    /// and yields a clone of each element, which is cheap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use molt_forked::prelude::*;
+    /// let value = Value::from("a {b c} d");
+    /// let mut list: MoltList = vec![Value::from("x")];
+    /// list.extend(value.as_list_iter().unwrap());
+    /// assert_eq!(Value::from(list).as_str(), "x a {b c} d");
+    ///
+    /// let lengths: Vec<usize> = value.as_list_iter().unwrap().map(|v| v.as_str().len()).collect();
+    /// assert_eq!(lengths, vec![1, 3, 1]);
+    /// ```
+    pub fn as_list_iter(&self) -> Result<ListIter, Exception> {
+        let list = self.as_list()?;
+        let back = list.len();
+        Ok(ListIter { list, front: 0, back })
+    }
+
     /// Tries to return the `Value` as a `MoltList`, parsing the
     /// value's string representation if necessary.
     ///
@@ -903,6 +1363,13 @@ impl Value {
     /// `Interp`'s `var` and `set_var` methods, which use it implicitly; however it is
     /// available to extension authors if need be.
     ///
+    /// As in Tcl, a name containing a "(" and ending with a ")" is an array element: the
+    /// array name is the text before the *first* "(", and the index is everything between
+    /// it and the *final* ")".  The index may thus contain spaces and parentheses of its
+    /// own, e.g., `a(x)y)` is element `x)y` of array `a`, and `a()` is element `""`.  The
+    /// combined form is ambiguous only when the array name itself contains a "("; such
+    /// elements are reachable only by name and index, e.g., via `Interp::set_element`.
+    ///
     /// # Example
     ///
     /// ```
@@ -1082,7 +1549,7 @@ impl Value {
         None
     }
 
-    /// For use by `expr::expr` in parsing out `Values`.
+    /// For use by `expr::expr_value` in parsing out `Values`.
     pub(crate) fn already_number(&self) -> Option<Datum> {
         let iref = self.inner.data_rep.borrow();
 
@@ -1094,6 +1561,44 @@ impl Value {
     }
 }
 
+/// An iterator over the elements of a list `Value`; see
+/// [`Value::as_list_iter`](struct.Value.html#method.as_list_iter).
+#[derive(Clone, Debug)]
+pub struct ListIter {
+    list: Rc<MoltList>,
+    front: usize,
+    back: usize,
+}
+
+impl Iterator for ListIter {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.list[self.front - 1].clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for ListIter {
+    fn next_back(&mut self) -> Option<Value> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.list[self.back].clone())
+    }
+}
+
+impl ExactSizeIterator for ListIter {}
+
 //-----------------------------------------------------------------------------
 // The MoltAny Trait: a tool for handling external types.
 
@@ -1133,6 +1638,22 @@ impl<T: Any + Display + Debug> MoltAny for T {
     }
 }
 
+// Converts a string to the bytes it holds, one per character.
+fn get_bytes(str: &str) -> Result<Vec<u8>, Exception> {
+    str.chars()
+        .enumerate()
+        .map(|(i, ch)| match u8::try_from(ch) {
+            Ok(byte) => Ok(byte),
+            Err(_) => molt_err!(
+                "expected byte sequence but character {} was '{}' (U+{:06X})",
+                i,
+                ch,
+                ch as u32
+            ),
+        })
+        .collect()
+}
+
 //-----------------------------------------------------------------------------
 // DataRep enum: a sum type for the different kinds of data_reps.
 
@@ -1151,6 +1672,9 @@ enum DataRep {
     /// A Molt float
     Flt(MoltFloat),
 
+    /// A byte string
+    Bytes(Rc<Vec<u8>>),
+
     /// A Molt List
     List(Rc<MoltList>),
 
@@ -1171,6 +1695,13 @@ impl Display for DataRep {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             DataRep::Bool(flag) => write!(f, "{}", if *flag { 1 } else { 0 }),
+            DataRep::Bytes(bytes) => {
+                write!(
+                    f,
+                    "{}",
+                    bytes.iter().map(|byte| *byte as char).collect::<String>()
+                )
+            }
             DataRep::Dict(dict) => write!(f, "{}", dict_to_string(&*dict)),
             DataRep::Int(int) => write!(f, "{}", int),
             DataRep::Flt(flt) => Value::fmt_float(f, *flt),
@@ -1239,6 +1770,85 @@ mod tests {
         assert_ne!(val, val3);
     }
 
+    // Compares the values both ways, checking that the fast paths agree with comparing
+    // the string reps and that equal values hash alike.
+    fn check_eq(a: &Value, b: &Value) -> bool {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |v: &Value| {
+            let mut hasher = DefaultHasher::new();
+            v.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let result = a == b;
+        assert_eq!(result, a.as_str() == b.as_str());
+        if result {
+            assert_eq!(hash(a), hash(b));
+        }
+        result
+    }
+
+    #[test]
+    fn compare_fast_paths() {
+        // Clones share the inner value.
+        let val = Value::from("abc");
+        assert!(check_eq(&val, &val.clone()));
+
+        // Neither value has a string rep yet.
+        assert!(Value::from(7) == Value::from(7));
+        assert!(Value::from(7) != Value::from(8));
+        assert!(Value::from(1.5) == Value::from(1.5));
+        assert!(Value::from(MoltFloat::NAN) == Value::from(MoltFloat::NAN));
+
+        assert!(check_eq(&Value::from(7), &Value::from(7)));
+        assert!(!check_eq(&Value::from(7), &Value::from(8)));
+        assert!(check_eq(&Value::from(1.5), &Value::from(1.5)));
+        assert!(!check_eq(&Value::from(1.5), &Value::from(2.5)));
+
+        // An int and a float compare as strings.
+        assert_eq!(Value::from(7.0) == Value::from(7), Value::from(7.0).as_str() == "7");
+        assert_eq!(
+            check_eq(&Value::from(7.0), &Value::from(7)),
+            Value::from(7.0).as_str() == "7"
+        );
+        assert_eq!(
+            check_eq(&Value::from(0.0), &Value::from(-0.0)),
+            Value::from(-0.0).as_str() == "0"
+        );
+
+        // Strings with the same data rep compare as strings.
+        let hex = Value::from("0x10");
+        let dec = Value::from("16");
+        assert_eq!(hex.as_int(), dec.as_int());
+        assert!(!check_eq(&hex, &dec));
+        assert!(!check_eq(&Value::from("07"), &Value::from(7)));
+        assert!(!check_eq(&Value::from("7.0"), &Value::from(7)));
+        assert!(check_eq(&Value::from("7"), &Value::from(7)));
+    }
+
+    #[test]
+    fn eq_numeric() {
+        let eq = |a: Value, b: Value| a.eq_numeric(&b).unwrap();
+
+        assert!(eq(Value::from("7.0"), Value::from(7)));
+        assert!(eq(Value::from(7.0), Value::from("7")));
+        assert!(eq(Value::from("0x10"), Value::from(16)));
+        assert!(eq(Value::from(" 5 "), Value::from("5.0")));
+        assert!(!eq(Value::from("7.5"), Value::from(7)));
+        assert!(!eq(Value::from(1), Value::from(2)));
+
+        // Non-numeric values compare as strings.
+        assert!(eq(Value::from("abc"), Value::from("abc")));
+        assert!(!eq(Value::from("abc"), Value::from("7")));
+        assert!(!eq(Value::from("07x"), Value::from("7")));
+
+        // Failed numeric conversions are errors.
+        assert!(Value::from("99999999999999999999")
+            .eq_numeric(&Value::from(1))
+            .is_err());
+    }
+
     #[test]
     fn from_bool() {
         // Using From<bool>
@@ -1318,6 +1928,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dict_round_trip_order() {
+        let dictval = Value::from("z 1 a 2 m 3 a 4");
+        let dict = dictval.as_dict().unwrap();
+        let keys: Vec<&str> = dict.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["z", "a", "m"]);
+
+        // The string rep is regenerated in insertion order, with the duplicate collapsed.
+        let dictval = Value::from(dictval.to_dict().unwrap());
+        assert_eq!(dictval.as_str(), "z 1 a 4 m 3");
+        assert_eq!(Value::from(dictval.as_str()).to_dict(), dictval.to_dict());
+    }
+
     #[test]
     fn to_dict() {
         let dictval = Value::from("qrs xyz");
@@ -1343,18 +1966,113 @@ mod tests {
         assert_eq!(val.as_int(), Ok(7));
         assert_eq!(val.as_float(), Ok(7.0));
 
-        // TODO: Note, 7.0 might not get converted to "7" long term.
-        // In Standard TCL, its string_rep would be "7.0".  Need to address
-        // MoltFloat formatting/parsing.
+        // As in Standard TCL, a float keeps its fraction.
         let val = Value::from(7.0);
-        assert_eq!(val.as_str(), "7");
-        assert_eq!(val.as_int(), Ok(7));
+        assert_eq!(val.as_str(), "7.0");
+        assert_eq!(val.as_int(), molt_err!("expected integer but got \"7.0\""));
         assert_eq!(val.as_float(), Ok(7.0));
 
         let val = Value::from("abc");
         assert_eq!(val.as_int(), molt_err!("expected integer but got \"abc\""));
     }
 
+    #[test]
+    fn float_string_rep() {
+        let rep = |flt: MoltFloat| Value::from(flt).as_str().to_string();
+
+        assert_eq!(rep(0.0), "0.0");
+        assert_eq!(rep(-0.0), "-0.0");
+        assert_eq!(rep(5.0), "5.0");
+        assert_eq!(rep(-2.5), "-2.5");
+        assert_eq!(rep(0.1), "0.1");
+        assert_eq!(rep(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(rep(0.0001), "0.0001");
+        assert_eq!(rep(0.00001), "1e-5");
+        assert_eq!(rep(1.5e-7), "1.5e-7");
+        assert_eq!(rep(1e16), "10000000000000000.0");
+        assert_eq!(rep(1e17), "1e+17");
+        assert_eq!(rep(-1.25e300), "-1.25e+300");
+        assert_eq!(rep(MoltFloat::MAX), "1.7976931348623157e+308");
+
+        for flt in [0.1 + 0.2, 1.0 / 3.0, 123456.789e10, 5e-324, MoltFloat::MAX] {
+            assert_eq!(Value::get_float(&rep(flt)), Ok(flt));
+        }
+
+        Value::set_float_precision(3);
+        assert_eq!(Value::float_precision(), 3);
+        assert_eq!(rep(1.0 / 3.0), "0.333");
+        assert_eq!(rep(2.0), "2.0");
+        assert_eq!(rep(1234.5), "1230.0");
+        assert_eq!(rep(1e-5), "1e-05");
+        assert_eq!(rep(1.5e300), "1.5e+300");
+        Value::set_float_precision(99);
+        assert_eq!(Value::float_precision(), 17);
+        assert_eq!(rep(0.1), "0.10000000000000001");
+        Value::set_float_precision(0);
+    }
+
+    #[test]
+    fn from_other_ints() {
+        assert_eq!(Value::from(5i32).as_int(), Ok(5));
+        assert_eq!(Value::from(5u32).as_int(), Ok(5));
+        assert_eq!(Value::from(5usize).as_int(), Ok(5));
+        assert_eq!(Value::try_from(5u64), Ok(Value::from(5)));
+
+        let big = Value::from(usize::MAX);
+        assert_eq!(big.as_str(), usize::MAX.to_string());
+        assert!(big.as_int().is_err());
+
+        assert_eq!(
+            Value::try_from(u64::MAX),
+            molt_err!("integer value too large to represent: \"{}\"", u64::MAX)
+        );
+    }
+
+    #[test]
+    fn try_from_value() {
+        assert_eq!(MoltInt::try_from(&Value::from("12")), Ok(12));
+        assert_eq!(MoltFloat::try_from(&Value::from("1.5")), Ok(1.5));
+        assert_eq!(bool::try_from(&Value::from("yes")), Ok(true));
+        assert_eq!(String::try_from(&Value::from(12)), Ok("12".to_string()));
+        assert_eq!(usize::try_from(&Value::from("0x10")), Ok(16));
+        assert_eq!(u32::try_from(&Value::from(7)), Ok(7));
+        assert_eq!(i32::try_from(&Value::from(-7)), Ok(-7));
+
+        assert_eq!(
+            usize::try_from(&Value::from("abc")),
+            molt_err!("expected integer but got \"abc\"")
+        );
+        assert_eq!(
+            usize::try_from(&Value::from(-3)),
+            molt_err!("expected non-negative integer but got \"-3\"")
+        );
+        assert_eq!(
+            u32::try_from(&Value::from(-3)),
+            molt_err!("expected non-negative integer but got \"-3\"")
+        );
+        assert_eq!(
+            u32::try_from(&Value::from(MoltInt::MAX)),
+            molt_err!("integer value too large to represent: \"{}\"", MoltInt::MAX)
+        );
+        assert_eq!(
+            i32::try_from(&Value::from(MoltInt::MIN)),
+            molt_err!("integer value too large to represent: \"{}\"", MoltInt::MIN)
+        );
+    }
+
+    #[test]
+    fn char_len() {
+        assert_eq!(Value::empty().char_len(), 0);
+        assert_eq!(Value::from("abc").char_len(), 3);
+        assert_eq!(Value::from("a\u{1F600}b").char_len(), 3);
+
+        // Computed from the data rep, and cached thereafter.
+        let value = Value::from(-123);
+        assert_eq!(value.char_len(), 4);
+        assert_eq!(value.inner.char_len.get(), Some(4));
+        assert_eq!(value.clone().char_len(), 4);
+    }
+
     #[test]
     fn get_int() {
         // Test the internal integer parser
@@ -1462,7 +2180,37 @@ mod tests {
         assert!(val.as_script().is_ok());
 
         let val = Value::from("a {b");
-        assert_eq!(val.as_script(), molt_err_uncompleted!("missing close-brace"));
+        let exception = val.as_script().unwrap_err();
+        assert_eq!(exception.value(), Value::from("missing close-brace"));
+        assert_eq!(exception.uncompleted_kind(), Some(crate::types::Delimiter::Brace));
+    }
+
+    #[test]
+    fn from_iter() {
+        let value: Value = ["a", "b c"].iter().copied().collect();
+        assert_eq!(value.as_str(), "a {b c}");
+        assert_eq!(value.as_list().unwrap().len(), 2);
+
+        let value: Value = Vec::<Value>::new().into_iter().collect();
+        assert_eq!(value.as_str(), "");
+    }
+
+    #[test]
+    fn as_list_iter() {
+        let value = Value::from("a {b c} d");
+        let items: MoltList = value.as_list_iter().unwrap().collect();
+        assert_eq!(items, vec![Value::from("a"), Value::from("b c"), Value::from("d")]);
+
+        let mut iter = value.as_list_iter().unwrap();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(Value::from("d")));
+        assert_eq!(iter.next(), Some(Value::from("a")));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back(), Some(Value::from("b c")));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        assert!(Value::from("a {b").as_list_iter().is_err());
     }
 
     #[test]
@@ -1474,6 +2222,28 @@ mod tests {
         let val = Value::from("a(b)");
         assert_eq!(val.as_var_name().name(), "a");
         assert_eq!(val.as_var_name().index(), Some("b"));
+
+        // Pathological indices: first "(" to final ")".
+        for (literal, index) in [
+            ("a()", ""),
+            ("a(b c)", "b c"),
+            ("a(x)y)", "x)y"),
+            ("a(x))", "x)"),
+            ("a(()", "("),
+            ("a((p))", "(p)"),
+            ("a($x)", "$x"),
+        ] {
+            let var_name = Value::from(literal).as_var_name();
+            assert_eq!(var_name.name(), "a");
+            assert_eq!(var_name.index(), Some(index));
+        }
+
+        // Not array elements.
+        for literal in ["a(", "a(b", "a(b)c", "a)"] {
+            let var_name = Value::from(literal).as_var_name();
+            assert_eq!(var_name.name(), literal);
+            assert_eq!(var_name.index(), None);
+        }
     }
 
     #[test]
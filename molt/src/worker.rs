@@ -0,0 +1,289 @@
+//! Worker Interpreters
+//!
+//! This module, enabled by the `threads` feature, defines the `worker` command, which runs
+//! scripts in interpreters on other threads.  Because [`Value`] is not `Sync`, all traffic
+//! between the threads is plain `String`s: scripts are sent as text, and results and errors
+//! come back as text.
+//!
+//! Add the command to an interpreter as an extra native command:
+//!
+//! ```
+//! # use molt_forked::prelude::*;
+//! let command = gen_command!((), [("worker", molt_forked::worker::cmd_worker)], []);
+//! ```
+//!
+//! Each worker runs a fresh `Interp::default()` on its own thread.  Worker handles belong to
+//! the thread that created them; a handle is not visible to interpreters on other threads.
+//!
+//! [`Value`]: ../value/index.html
+use crate::check_args;
+use crate::interp::Interp;
+use crate::types::*;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// A request sent to a worker thread.
+enum Request {
+    /// Evaluate the script and send back the reply.
+    Eval(String, Sender<Reply>),
+
+    /// Evaluate the script and discard the result.
+    Post(String),
+}
+
+/// A worker's reply to an `Eval` request.
+enum Reply {
+    Ok(String),
+    Error { message: String, error_code: String, error_info: String },
+}
+
+/// A handle on a running worker thread.
+struct Worker {
+    requests: Sender<Request>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct Workers {
+    next_id: usize,
+    workers: HashMap<String, Worker>,
+}
+
+thread_local! {
+    static WORKERS: RefCell<Workers> = RefCell::new(Workers::default());
+}
+
+/// # worker *subcommand* ?*arg* ...?
+///
+/// Creates and manages worker interpreters running on other threads.
+///
+/// * `worker create`: spawns a worker and returns its handle.
+/// * `worker eval handle script`: evaluates the script in the worker, waiting for the result.
+///   An error in the worker is rethrown with the worker's `errorInfo`.
+/// * `worker post handle script`: queues the script for evaluation and returns immediately;
+///   the result, including any error, is discarded.
+/// * `worker destroy handle`: waits for the worker's queued scripts to finish, and ends
+///   its thread.
+pub fn cmd_worker<Ctx>(interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    let f = _gen_subcommand_generic!(
+        1,
+        [
+            ("create", cmd_worker_create),
+            ("destroy", cmd_worker_destroy),
+            ("eval", cmd_worker_eval),
+            ("post", cmd_worker_post),
+        ],
+    );
+    f(interp, argv)
+}
+
+/// # worker create
+fn cmd_worker_create<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 2, 2, "")?;
+
+    let (requests, receiver) = channel();
+    let thread = thread::spawn(move || run_worker(receiver));
+
+    let handle = WORKERS.with(|workers| {
+        let mut workers = workers.borrow_mut();
+        workers.next_id += 1;
+        let handle = format!("worker{}", workers.next_id);
+        workers.workers.insert(handle.clone(), Worker { requests, thread });
+        handle
+    });
+
+    molt_ok!(handle)
+}
+
+/// # worker destroy *handle*
+fn cmd_worker_destroy<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 3, 3, "handle")?;
+
+    let worker =
+        WORKERS.with(|workers| workers.borrow_mut().workers.remove(argv[2].as_str()));
+    let Some(worker) = worker else {
+        return molt_err!("unknown worker \"{}\"", argv[2]);
+    };
+
+    // Closing the channel ends the worker's loop once its queued requests are done.
+    drop(worker.requests);
+
+    if worker.thread.join().is_err() {
+        return molt_err!("worker \"{}\" panicked", argv[2]);
+    }
+    molt_ok!()
+}
+
+/// # worker eval *handle* *script*
+fn cmd_worker_eval<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "handle script")?;
+
+    let (reply_to, replies) = channel();
+    send(&argv[2], Request::Eval(argv[3].to_string(), reply_to))?;
+
+    match replies.recv() {
+        Ok(Reply::Ok(result)) => molt_ok!(result),
+        Ok(Reply::Error { message, error_code, error_info }) => {
+            let mut exception = Exception::molt_return_err(
+                Value::from(message),
+                0,
+                Some(Value::from(error_code)),
+                Some(Value::from(error_info)),
+            );
+            exception.add_error_info(&format!("    (in worker \"{}\")", argv[2]));
+            Err(exception)
+        }
+        Err(_) => molt_err!("worker \"{}\" is not running", argv[2]),
+    }
+}
+
+/// # worker post *handle* *script*
+fn cmd_worker_post<Ctx>(_interp: &mut Interp<Ctx>, argv: &[Value]) -> MoltResult {
+    check_args(2, argv, 4, 4, "handle script")?;
+
+    send(&argv[2], Request::Post(argv[3].to_string()))?;
+    molt_ok!()
+}
+
+/// Sends the request to the named worker.
+fn send(handle: &Value, request: Request) -> Result<(), Exception> {
+    let sent = WORKERS.with(|workers| {
+        workers
+            .borrow()
+            .workers
+            .get(handle.as_str())
+            .map(|worker| worker.requests.send(request).is_ok())
+    });
+
+    match sent {
+        Some(true) => Ok(()),
+        Some(false) => molt_err!("worker \"{}\" is not running", handle),
+        None => molt_err!("unknown worker \"{}\"", handle),
+    }
+}
+
+/// The body of a worker thread: evaluates requests until the channel is closed.
+fn run_worker(requests: Receiver<Request>) {
+    let mut interp = Interp::default();
+
+    for request in requests {
+        match request {
+            Request::Eval(script, reply_to) => {
+                let reply = match interp.eval(&script) {
+                    Ok(value) => Reply::Ok(value.to_string()),
+                    Err(exception) if exception.is_error() => Reply::Error {
+                        message: exception.value().to_string(),
+                        error_code: exception.error_code().to_string(),
+                        error_info: exception.error_info().to_string(),
+                    },
+                    Err(exception) => Reply::Ok(exception.value().to_string()),
+                };
+                // The caller may have gone away; there's no one left to tell.
+                let _ = reply_to.send(reply);
+            }
+            Request::Post(script) => {
+                let _ = interp.eval(&script);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn interp() -> Interp<()> {
+        let command = gen_command!((), [("worker", cmd_worker)], []);
+        Interp::new((), command, false, "test")
+    }
+
+    #[test]
+    fn test_worker_eval() {
+        let mut interp = interp();
+        let worker = interp.eval("worker create").unwrap();
+        interp.set_scalar("x", Value::from("parent")).unwrap();
+
+        let result = interp
+            .eval(&format!("worker eval {} {{set x 6; expr {{$x * 7}}}}", worker))
+            .unwrap();
+        assert_eq!(result.as_str(), "42");
+
+        // The worker has its own variables.
+        assert_eq!(interp.scalar("x").unwrap().as_str(), "parent");
+        let result = interp.eval(&format!("worker eval {} {{set x}}", worker)).unwrap();
+        assert_eq!(result.as_str(), "6");
+
+        interp.eval(&format!("worker destroy {}", worker)).unwrap();
+    }
+
+    #[test]
+    fn test_worker_post() {
+        let mut interp = interp();
+        let worker = interp.eval("worker create").unwrap();
+
+        interp.eval(&format!("worker post {} {{set y 1}}", worker)).unwrap();
+        interp.eval(&format!("worker post {} {{incr y}}", worker)).unwrap();
+        let result = interp.eval(&format!("worker eval {} {{set y}}", worker)).unwrap();
+        assert_eq!(result.as_str(), "2");
+        assert!(interp.scalar("y").is_err());
+
+        interp.eval(&format!("worker destroy {}", worker)).unwrap();
+    }
+
+    #[test]
+    fn test_worker_error() {
+        let mut interp = interp();
+        let worker = interp.eval("worker create").unwrap();
+
+        let exception = interp
+            .eval(&format!("worker eval {} {{error oops {{}} MYCODE}}", worker))
+            .unwrap_err();
+        assert_eq!(exception.value().as_str(), "oops");
+        assert_eq!(exception.error_code().as_str(), "MYCODE");
+        assert_eq!(
+            exception.error_info().as_str(),
+            format!(
+                "oops\nwhile executing\n  \"error oops {{}} MYCODE\"\n    (in worker \"{}\")",
+                worker
+            )
+        );
+
+        interp.eval(&format!("worker destroy {}", worker)).unwrap();
+    }
+
+    #[test]
+    fn test_worker_destroy_while_busy() {
+        let mut interp = interp();
+        let worker = interp.eval("worker create").unwrap();
+
+        interp
+            .eval(&format!(
+                "worker post {} {{for {{set i 0}} {{$i < 20000}} {{incr i}} {{}}}}",
+                worker
+            ))
+            .unwrap();
+        interp.eval(&format!("worker destroy {}", worker)).unwrap();
+
+        let exception =
+            interp.eval(&format!("worker eval {} {{set i}}", worker)).unwrap_err();
+        assert_eq!(exception.value().as_str(), format!("unknown worker \"{}\"", worker));
+    }
+
+    #[test]
+    fn test_worker_errors() {
+        let mut interp = interp();
+
+        let exception = interp.eval("worker destroy nonesuch").unwrap_err();
+        assert_eq!(exception.value().as_str(), "unknown worker \"nonesuch\"");
+
+        let exception = interp.eval("worker frob").unwrap_err();
+        assert_eq!(
+            exception.value().as_str(),
+            "unknown or ambiguous subcommand \"frob\", must be:\ncreate, destroy, eval or post."
+        );
+    }
+}
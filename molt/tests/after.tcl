@@ -0,0 +1,96 @@
+# Test Script: after command.
+#
+# The test harness doesn't run the event queue, so these tests check scheduling,
+# inspection, and cancellation; vwait.tcl runs the scripts.  The tests also run with
+# the `wasm` feature, which can't sleep; `test_wasm` says whether it's on.
+
+# after-1.*: Syntax
+
+test after-1.1 {syntax} {
+    after
+} -error {wrong # args: should be "after option ?arg ...?"}
+
+test after-1.2 {bad argument} {
+    after frob
+} -error {bad argument "frob": must be cancel, idle, info, or an integer}
+
+test after-1.3 {idle syntax} {
+    after idle
+} -error {wrong # args: should be "after idle script ?script ...?"}
+
+test after-1.4 {cancel syntax} {
+    after cancel
+} -error {wrong # args: should be "after cancel id|command"}
+
+test after-1.5 {info syntax} {
+    after info a b
+} -error {wrong # args: should be "after info ?id?"}
+
+# after-2.*: Sleeping
+
+# Under wasm, sleeping isn't available.
+if {$test_wasm} {
+    test after-2.1 {sleep} {
+        after 1
+    } -error {can't sleep under wasm: give a script to run later}
+
+    test after-2.2 {negative delay} {
+        after -5
+    } -error {can't sleep under wasm: give a script to run later}
+} else {
+    test after-2.1 {sleep} {
+        after 1
+    } -ok {}
+
+    test after-2.2 {negative delay} {
+        after -5
+    } -ok {}
+}
+
+# after-3.*: Scheduling and cancellation
+
+test after-3.1 {schedule timer} {
+    set id [after 1000 {set x 1}]
+    set result [list [string match after#* $id] [after info $id]]
+    after cancel $id
+    set result
+} -ok {1 {{set x 1} timer}}
+
+test after-3.2 {schedule idle, concatenating scripts} {
+    set id [after idle set x {1 2}]
+    set result [after info $id]
+    after cancel $id
+    set result
+} -ok {{set x 1 2} idle}
+
+test after-3.3 {info lists events in order} {
+    set a [after 1000 a]
+    set b [after idle b]
+    set result [expr {[after info] eq [list $a $b]}]
+    after cancel $a
+    after cancel $b
+    set result
+} -ok {1}
+
+test after-3.4 {cancel by id} {
+    set id [after 1000 {set x 1}]
+    after cancel $id
+    after info
+} -ok {}
+
+test after-3.5 {cancel by script} {
+    after 1000 set x 1
+    after idle {set x 1}
+    after cancel set x 1
+    set result [llength [after info]]
+    after cancel {set x 1}
+    list $result [after info]
+} -ok {1 {}}
+
+test after-3.6 {cancel of unknown event is ignored} {
+    after cancel after#99999
+} -ok {}
+
+test after-3.7 {info of unknown event} {
+    after info after#99999
+} -error {event "after#99999" doesn't exist}
@@ -0,0 +1,63 @@
+# Test Script: alias command
+
+test alias-1.1 {alias: define and call} -body {
+    alias ll {lsort -dictionary}
+    ll {b10 b9 a}
+} -cleanup {
+    alias ll {}
+} -ok {a b9 b10}
+
+test alias-1.2 {alias: arguments are appended} -setup {
+    proc show {args} { return $args }
+} -body {
+    alias greet {show hello {big world}}
+    greet a {b c}
+} -cleanup {
+    alias greet {}
+    rename show {}
+} -ok {hello {big world} a {b c}}
+
+test alias-1.3 {alias: takes the place of a command} -body {
+    alias llength {string length}
+    llength abcde
+} -cleanup {
+    alias llength {}
+} -ok {5}
+
+test alias-2.1 {alias: query} -setup {
+    alias ll {lsort -dictionary}
+} -body {
+    list [alias ll] [alias]
+} -cleanup {
+    alias ll {}
+} -ok {{lsort -dictionary} ll}
+
+test alias-2.2 {alias: query unknown} {
+    alias nonesuch
+} -error {alias "nonesuch" not found}
+
+test alias-3.1 {alias: delete} -setup {
+    alias ll {lsort -dictionary}
+} -body {
+    alias ll {}
+    list [alias] [catch {ll {b a}}]
+} -ok {{} 1}
+
+test alias-3.2 {alias: delete unknown} {
+    alias nonesuch {}
+} -error {alias "nonesuch" not found}
+
+test alias-4.1 {alias: loop} {
+    alias loop {loop 1}
+} -error {cannot define or rename alias "loop": would create a loop}
+
+test alias-4.2 {alias: wrong # args} {
+    alias a b c
+} -error {wrong # args: should be "alias ?name? ?prefix?"}
+
+test alias-4.3 {alias: interp alias sees the same aliases} -body {
+    alias ll {lsort -dictionary}
+    interp alias {} ll
+} -cleanup {
+    alias ll {}
+} -ok {lsort -dictionary}
@@ -2,18 +2,27 @@
 #
 # If I add the "glob" command, I'll use that to pick up the tests.
 
+source after.tcl
+source alias.tcl
 source append.tcl
 source array.tcl
 source assert_eq.tcl
+source binary.tcl
 source break.tcl
 source catch.tcl
+source clock.tcl
+source concat.tcl
 source continue.tcl
 source dict.tcl
+source encoding.tcl
 source error.tcl
+source eval.tcl
 source exit.tcl
 source expr.tcl
+source flush.tcl
 source for.tcl
 source foreach.tcl
+source format.tcl
 source if.tcl
 source info.tcl
 source incr.tcl
@@ -21,15 +30,51 @@ source interp.tcl
 source join.tcl
 source lappend.tcl
 source lindex.tcl
+source linsert.tcl
 source list.tcl
 source llength.tcl
+source lmap.tcl
+source lrange.tcl
+source lreplace.tcl
+source lrepeat.tcl
+source lreverse.tcl
+source lsearch.tcl
+source lsort.tcl
+source namespace.tcl
+source package.tcl
 source parser.tcl
 source proc.tcl
+source profile.tcl
 source rename.tcl
 source return.tcl
+source scan.tcl
 source set.tcl
 source string.tcl
+source subst.tcl
+source switch.tcl
+source tailcall.tcl
 source test.tcl
 source throw.tcl
+source trace.tcl
+source unknown.tcl
 source unset.tcl
+source uplevel.tcl
+source upvar.tcl
+source vwait.tcl
 source while.tcl
+
+# Commands behind optional features; tcl_tests.rs says which are on.
+
+if {$test_exec} {
+    source exec.tcl
+}
+
+if {$test_file_io} {
+    source channel.tcl
+    source file.tcl
+    source glob.tcl
+}
+
+if {$test_regexp} {
+    source regexp.tcl
+}
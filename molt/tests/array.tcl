@@ -164,16 +164,76 @@ test array-6.4 {array set, old var, empty list} {
 } -ok {1}
 
 test array-6.5 {array set on index} {
-    catch {
-        array set a(1) {a b c d}
-    } result1
+    assert_err {array set a(1) {a b c d}} {can't set "a(1)": variable isn't array}
+    assert_err {set a 1} {can't set "a": variable is array}
+} -ok {can't set "a": variable is array}
 
-    catch {
-        set a 1
-    } result2
+#----------------------------------------------------------------------------
+# Pathological indices
+
+test array-7.1 {literal variable name, index with space} {
+    set "a(b c)" 1
+    append "a(b c)" 2
+    lappend "a(d e)" x
+    incr "a(f g)"
+    list [array size a] [set "a(b c)"] [set "a(d e)"] [set "a(f g)"]
+} -ok {3 12 x 1}
+
+test array-7.2 {variable reference, index with space and semi-colon} {
+    set "a(b c)" 1
+    set "a(d;e)" 2
+    list $a(b c) "<$a(d;e)>" ${a(b c)}
+} -ok {1 <2> 1}
+
+test array-7.3 {empty index} {
+    set a() e
+    append a() f
+    list $a() ${a()} [set "a()"] [array names a]
+} -ok {ef ef ef {{}}}
+
+test array-7.4 {index with close-paren, via substitution} {
+    set key "x)y"
+    set a($key) 1
+    incr a($key)
+    list $a($key) [set "a(x)y)"] [array names a]
+} -ok {2 2 x)y}
+
+test array-7.5 {index with parentheses, round trip} {
+    set key "(p)"
+    set a($key) 1
+    set a(\$x) 2
+    list $a($key) [set "a((p))"] $a(\$x) [set {a($x)}]
+} -ok {1 1 2 2}
+
+test array-7.6 {substitution in index, reads and writes} {
+    set i 1
+    set j 2
+    set m($i,$j) 3
+    incr m($i,$j)
+    append m($i,$j) 0
+    list $m($i,$j) $m(1,2) [set m([expr {$i + 1}],$j) 5] $m(2,$j)
+} -ok {40 40 5 5}
+
+test array-7.7 {unset element with pathological index} {
+    set "a(b c)" 1
+    set key "x)"
+    set a($key) 2
+    set a() 3
+    unset "a(b c)" a($key)
+    unset "a()"
+    array size a
+} -ok {0}
+
+test array-7.8 {no close-paren is a scalar} {
+    set "a(" 1
+    set "a(b" 2
+    list [info exists "a("] [set "a(b"] [array exists a]
+} -ok {1 2 0}
 
-    list $result1 $result2
-} -ok {{can't set "a(1)": variable isn't array} {can't set "a": variable is array}}
+test array-7.9 {missing close-paren in reference} {
+    set a(1) 1
+    assert_err {set x $a(1}
+} -ok {missing )}
 
 #----------------------------------------------------------------------------
 # Cleanup
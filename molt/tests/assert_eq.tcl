@@ -1,4 +1,4 @@
-# Test Script: assert_eq
+# Test Script: assert_eq, assert_err, assert_code
 
 test assert_eq-1.1 {assert_eq command} {
     assert_eq a a
@@ -6,8 +6,71 @@ test assert_eq-1.1 {assert_eq command} {
 
 test assert_eq-1.2 {assert_eq command} {
     assert_eq a b
-} -error {assertion failed: received "a", expected "b".}
+} -error {assert_eq failed:
+  expected: b
+  actual:   a}
+
+test assert_eq-1.3 {assert_eq shows white space} {
+    assert_eq "a " {}
+} -error {assert_eq failed:
+  expected: {}
+  actual:   {a }}
 
 test assert_eq-2.1 {assert_eq errors} {
     assert_eq
 } -error {wrong # args: should be "assert_eq received expected"}
+
+test assert_err-1.1 {assert_err returns the message} {
+    assert_err {error "my message"}
+} -ok {my message}
+
+test assert_err-1.2 {assert_err with pattern} {
+    assert_err {error "my message"} "my *"
+} -ok {my message}
+
+test assert_err-1.3 {assert_err sets errorCode} {
+    global errorCode
+    assert_err {error oops {} MYCODE}
+    set errorCode
+} -ok {MYCODE}
+
+test assert_err-2.1 {assert_err, no error} {
+    assert_err {set a "b c"}
+} -error {assert_err failed: no error
+  result:   {b c}}
+
+test assert_err-2.2 {assert_err, other code} {
+    assert_err {break}
+} -error {assert_err failed: no error
+  code:     break
+  result:   {}}
+
+test assert_err-2.3 {assert_err, mismatch} {
+    assert_err {error "my message"} "your *"
+} -error {assert_err failed:
+  pattern:  {your *}
+  error:    {my message}}
+
+test assert_err-3.1 {assert_err errors} {
+    assert_err
+} -error {wrong # args: should be "assert_err script ?pattern?"}
+
+test assert_code-1.1 {assert_code returns the result} {
+    list [assert_code {set a 1} ok] [assert_code {return -level 0 -code 5 x} 5] \
+        [assert_code {error e} error] [assert_code {continue} 4]
+} -ok {1 x e {}}
+
+test assert_code-2.1 {assert_code, mismatch} {
+    assert_code {set a 1} break
+} -error {assert_code failed:
+  expected: break
+  actual:   ok
+  result:   1}
+
+test assert_code-2.2 {assert_code, bad code} {
+    assert_code {set a 1} nonesuch
+} -error {invalid result code: "nonesuch"}
+
+test assert_code-2.3 {assert_code errors} {
+    assert_code {set a 1}
+} -error {wrong # args: should be "assert_code script code"}
@@ -0,0 +1,167 @@
+# Test Script: binary command.
+
+# binary-1.*: Syntax
+
+test binary-1.1 {syntax} {
+    binary
+} -error {wrong # args: should be "binary subcommand ?arg ...?"}
+
+test binary-1.2 {unknown subcommand} {
+    binary frob
+} -error {unknown or ambiguous subcommand "frob", must be:
+decode, encode, format or scan.}
+
+test binary-1.3 {format syntax} {
+    binary format
+} -error {wrong # args: should be "binary format formatString ?arg ...?"}
+
+test binary-1.4 {scan syntax} {
+    binary scan abc
+} -error {wrong # args: should be "binary scan value formatString ?varName ...?"}
+
+test binary-1.5 {encode syntax} {
+    binary encode hex
+} -error {wrong # args: should be "binary encode hex data"}
+
+test binary-1.6 {unknown encoding} {
+    binary encode base32 abc
+} -error {unknown or ambiguous subcommand "base32", must be:
+base64 or hex.}
+
+test binary-1.7 {bad field specifier} {
+    binary format z 1
+} -error {bad field specifier "z"}
+
+# binary-2.*: binary format
+
+test binary-2.1 {strings padded with nulls or spaces} {
+    list [binary encode hex [binary format a5 abc]] [binary format A5a2 abc xyz]
+} -ok {6162630000 {abc  xy}}
+
+test binary-2.2 {whole string} {
+    binary format a* hello
+} -ok {hello}
+
+test binary-2.3 {binary and hex digits} {
+    binary encode hex [binary format B8b8H4h4 10000001 10000001 1f2e 1f2e]
+} -ok {81811f2ef1e2}
+
+test binary-2.4 {bad binary digits} {
+    binary format B* 102
+} -error {expected binary string but got "102" instead}
+
+test binary-2.5 {bad hex digits} {
+    binary format H* 1g
+} -error {expected hexadecimal string but got "1g" instead}
+
+test binary-2.6 {integers} {
+    binary encode hex [binary format csSiIwW 1 -2 -2 1 1 1 1]
+} -ok {01fefffffe010000000000000101000000000000000000000000000001}
+
+test binary-2.7 {integer lists} {
+    binary encode hex [binary format S2c* {1 2 3} {4 5}]
+} -ok {000100020405}
+
+test binary-2.8 {list too short} {
+    binary format c3 {1 2}
+} -error {number of elements in list does not match count}
+
+test binary-2.9 {floats} {
+    binary encode hex [binary format RQ 1.5 -2]
+} -ok {3fc00000c000000000000000}
+
+test binary-2.10 {nulls, backing up, and absolute positions} {
+    binary encode hex [binary format cx2cXc@6c 1 2 3 4]
+} -ok {01000003000004}
+
+test binary-2.11 {x with *} {
+    binary format x*
+} -error {cannot use "*" in format string with "x"}
+
+test binary-2.12 {@ with no count} {
+    binary format @
+} -error {missing count for "@" field specifier}
+
+test binary-2.13 {not enough arguments} {
+    binary format cc 1
+} -error {not enough arguments for all format specifiers}
+
+test binary-2.14 {not a byte string} {
+    binary format a* Ā
+} -error {expected byte sequence but character 0 was 'Ā' (U+000100)}
+
+# binary-3.*: binary scan
+
+test binary-3.1 {strings} {
+    set n [binary scan "ab  \0cd" A5a2 x y]
+    list $n $x $y
+} -ok {2 ab cd}
+
+test binary-3.2 {binary and hex digits} {
+    binary scan [binary decode hex 8181] B8h* x y
+    list $x $y
+} -ok {10000001 18}
+
+test binary-3.3 {integers} {
+    binary scan [binary format csIw -1 -2 3 4] csIw a b c d
+    list $a $b $c $d
+} -ok {-1 -2 3 4}
+
+test binary-3.4 {unsigned integers} {
+    binary scan [binary format cSw -1 -1 -1] cuSuwu a b c
+    list $a $b $c
+} -ok {255 65535 18446744073709551615}
+
+test binary-3.5 {counts make lists} {
+    binary scan [binary format c5 {1 2 3 4 5}] c2c* x y
+    list $x $y
+} -ok {{1 2} {3 4 5}}
+
+test binary-3.6 {floats} {
+    binary scan [binary format rQ 1.5 -2.25] rQ x y
+    list $x $y
+} -ok {1.5 -2.25}
+
+test binary-3.7 {running out of data stops the scan} {
+    unset -nocomplain y
+    list [binary scan [binary format c 1] cc x y] $x [info exists y]
+} -ok {1 1 0}
+
+test binary-3.8 {skipping and positioning} {
+    binary scan [binary format c* {1 2 3 4}] xcXc@3c a b c
+    list $a $b $c
+} -ok {2 2 4}
+
+test binary-3.9 {not enough variables} {
+    binary scan abc aa x
+} -error {not enough arguments for all format specifiers}
+
+# binary-4.*: binary encode and decode
+
+test binary-4.1 {hex} {
+    list [binary encode hex "Hi\0\xff"] [binary decode hex "48 69"]
+} -ok {486900ff Hi}
+
+test binary-4.2 {bad hex} {
+    binary decode hex 4x
+} -error {invalid hexadecimal digit "x" at position 1}
+
+test binary-4.3 {odd hex} {
+    binary decode hex 486
+} -error {odd number of hexadecimal digits}
+
+test binary-4.4 {base64} {
+    list [binary encode base64 a] [binary encode base64 ab] [binary encode base64 abc]
+} -ok {YQ== YWI= YWJj}
+
+test binary-4.5 {base64 round trip} {
+    binary decode base64 [binary encode base64 "Hello, World!"]
+} -ok {Hello, World!}
+
+test binary-4.6 {base64 whitespace and missing padding} {
+    binary decode base64 "SGVs\nbG8"
+} -ok {Hello}
+
+test binary-4.7 {bad base64} {
+    binary decode base64 SG!s
+} -error {invalid base64 character "!" at position 2}
@@ -0,0 +1,121 @@
+# Test Script: open, close, read, gets, and eof commands.
+#
+# These commands are behind the `file-io` feature; all.tcl sources this file only when
+# `test_file_io` says it's on.  The tests write their files in `$test_tmpdir`, which
+# tcl_tests.rs removes when they are done.
+
+# channel-1.*: Errors
+
+test channel-1.1 {open a missing file} {
+    catch {open $::test_tmpdir/nonesuch} msg
+    string map [list $::test_tmpdir TMP] $msg
+} -ok {couldn't open "TMP/nonesuch": no such file or directory}
+
+test channel-1.2 {bad access mode} {
+    open $::test_tmpdir/nonesuch q
+} -error {illegal access mode "q"}
+
+test channel-1.3 {gets from an unknown channel} {
+    gets nonesuch
+} -error {can not find channel named "nonesuch"}
+
+test channel-1.4 {puts to an unknown channel} {
+    puts nonesuch hello
+} -error {can not find channel named "nonesuch"}
+
+test channel-1.5 {close a standard channel} {
+    close stdout
+} -error {can not close standard channel "stdout"}
+
+test channel-1.6 {gets from a write-only channel} -body {
+    set f [open $::test_tmpdir/errors w]
+    catch {gets $f} msg
+    string map [list $f F] $msg
+} -cleanup {
+    close $f
+} -ok {channel "F" wasn't opened for reading}
+
+test channel-1.7 {close a closed channel} {
+    set f [open $::test_tmpdir/errors w]
+    close $f
+    catch {close $f} msg
+    string map [list $f F] $msg
+} -ok {can not find channel named "F"}
+
+test channel-1.8 {puts to a read-only channel} -body {
+    set f [open $::test_tmpdir/errors]
+    catch {puts $f hello} msg
+    string map [list $f F] $msg
+} -cleanup {
+    close $f
+} -ok {channel "F" wasn't opened for writing}
+
+test channel-1.9 {negative read count} -body {
+    set f [open $::test_tmpdir/errors]
+    read $f -1
+} -cleanup {
+    close $f
+} -error {expected non-negative integer but got "-1"}
+
+# channel-2.*: Writing and reading
+
+test channel-2.1 {write and read} {
+    set f [open $::test_tmpdir/write w]
+    puts $f {line one}
+    puts -nonewline $f {line two}
+    lappend result [string match file* $f] [close $f]
+    set f [open $::test_tmpdir/write]
+    lappend result [read $f] [close $f]
+} -ok {1 {} {line one
+line two} {}}
+
+test channel-2.2 {gets and eof} -setup {
+    set f [open $::test_tmpdir/gets w]
+    puts $f {line one}
+    puts -nonewline $f {line two}
+    close $f
+} -body {
+    set f [open $::test_tmpdir/gets]
+    lappend result [gets $f] [eof $f]
+    lappend result [gets $f line] $line [eof $f]
+    lappend result [gets $f line] $line
+} -cleanup {
+    close $f
+} -ok {{line one} 0 8 {line two} 1 -1 {}}
+
+test channel-2.3 {read a count} -setup {
+    set f [open $::test_tmpdir/read w]
+    puts -nonewline $f "line one\nline two"
+    close $f
+} -body {
+    set f [open $::test_tmpdir/read r]
+    lappend result [read $f 4] [eof $f]
+    lappend result [read $f] [eof $f]
+} -cleanup {
+    close $f
+} -ok {line 0 { one
+line two} 1}
+
+test channel-2.4 {append and read -nonewline} -body {
+    set f [open $::test_tmpdir/append w]
+    puts $f a
+    close $f
+    set f [open $::test_tmpdir/append a]
+    puts $f b
+    close $f
+    set f [open $::test_tmpdir/append]
+    read -nonewline $f
+} -cleanup {
+    close $f
+} -ok "a\nb"
+
+test channel-2.5 {read counts characters} -setup {
+    set f [open $::test_tmpdir/unicode w]
+    puts -nonewline $f ☺xy
+    close $f
+} -body {
+    set f [open $::test_tmpdir/unicode]
+    read $f 2
+} -cleanup {
+    close $f
+} -ok {☺x}
@@ -0,0 +1,90 @@
+# Test Script: clock command.
+
+# clock-1.*: Syntax
+
+test clock-1.1 {syntax} {
+    clock
+} -error {wrong # args: should be "clock subcommand ?arg ...?"}
+
+test clock-1.2 {unknown subcommand} {
+    clock frob
+} -error {unknown or ambiguous subcommand "frob", must be:
+format, microseconds, milliseconds, scan or seconds.}
+
+test clock-1.3 {seconds syntax} {
+    clock seconds x
+} -error {wrong # args: should be "clock seconds "}
+
+test clock-1.4 {format syntax} {
+    clock format
+} -error {wrong # args: should be "clock format clockval ?-format string? ?-gmt boolean?"}
+
+test clock-1.5 {format missing option value} {
+    clock format 0 -format
+} -error {wrong # args: should be "clock format clockval ?-format string? ?-gmt boolean?"}
+
+test clock-1.6 {format bad option} {
+    clock format 0 -zone UTC
+} -error {bad option "-zone": must be -format or -gmt}
+
+test clock-1.7 {format bad time} {
+    clock format now
+} -error {expected integer but got "now"}
+
+# clock-2.*: Current time
+
+test clock-2.1 {seconds} {
+    string is integer -strict [clock seconds]
+} -ok {1}
+
+test clock-2.2 {milliseconds and microseconds agree with seconds} {
+    set s [clock seconds]
+    set ms [clock milliseconds]
+    set us [clock microseconds]
+    expr {$ms / 1000 - $s <= 1 && $us / 1000 - $ms <= 1000}
+} -ok {1}
+
+# clock-3.*: clock format
+
+test clock-3.1 {default format} {
+    clock format 0
+} -ok {Thu Jan 01 00:00:00 UTC 1970}
+
+test clock-3.2 {-format} {
+    clock format 951829509 -format {%Y-%m-%d %H:%M:%S}
+} -ok {2000-02-29 13:05:09}
+
+test clock-3.3 {names and 12-hour clock} {
+    clock format 951829509 -format {%A, %B %e, %I:%M %p} -gmt 1
+} -ok {Tuesday, February 29, 01:05 PM}
+
+test clock-3.4 {before the Epoch} {
+    clock format -1 -format {%F %T}
+} -ok {1969-12-31 23:59:59}
+
+# clock-4.*: clock scan
+
+test clock-4.1 {free-form date and time} {
+    clock scan {2000-02-29 13:05:09}
+} -ok {951829509}
+
+test clock-4.2 {free-form date} {
+    clock scan 2000-02-29
+} -ok {951782400}
+
+test clock-4.3 {free-form failure} {
+    clock scan tomorrow
+} -error {unable to convert date-time string "tomorrow"}
+
+test clock-4.4 {-format} {
+    clock scan {29 Feb 2000 1:05:09 pm} -format {%d %b %Y %I:%M:%S %p}
+} -ok {951829509}
+
+test clock-4.5 {format mismatch} {
+    clock scan 2000/02/29 -format %Y-%m-%d
+} -error {input string does not match supplied format}
+
+test clock-4.6 {round trip} {
+    set t 1234567890
+    clock scan [clock format $t -format {%Y-%m-%dT%H:%M:%S}]
+} -ok {1234567890}
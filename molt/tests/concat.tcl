@@ -0,0 +1,37 @@
+# Test Script: concat command.
+
+test concat-1.1 {no arguments} {
+    concat
+} -ok {}
+
+test concat-1.2 {one argument} {
+    concat a
+} -ok {a}
+
+test concat-1.3 {leading and trailing whitespace is trimmed} {
+    concat {a} { b }
+} -ok {a b}
+
+test concat-1.4 {braces are kept, and no quoting is added} {
+    concat "{a}" " { b } "
+} -ok {{a} { b }}
+
+test concat-1.5 {internal whitespace is preserved} {
+    concat "a b" "c  d"
+} -ok {a b c  d}
+
+test concat-1.6 {empty and blank arguments contribute nothing} {
+    list [concat " " "" a "  "] [concat "" "\t\n"]
+} -ok {a {}}
+
+test concat-1.7 {tabs and newlines are trimmed} {
+    concat "a\n" "\tb\r\n"
+} -ok {a b}
+
+test concat-1.8 {escaped trailing whitespace keeps one char} {
+    concat "a\\  " b
+} -ok {a\  b}
+
+test concat-1.9 {lists} {
+    concat [list a b] [list c {d e}]
+} -ok {a b c {d e}}
@@ -21,6 +21,10 @@ test dict-1.5 {dict create: duplicated keys} {
     dict create a 1 b 2 b 3 c 4
 } -ok {a 1 b 3 c 4}
 
+test dict-1.6 {dict create: insertion order} {
+    dict create z 1 a 2 m 3 a 4
+} -ok {z 1 a 4 m 3}
+
 # dict size
 test dict-2.1 {dict size: signature} {
     dict size
@@ -97,6 +101,22 @@ test dict-4.7 {dict exists: nested, non-dictionary} {
     dict exists {a 1 b 2} b c
 } -ok {0}
 
+test dict-4.8 {dict exists: empty dictionary} {
+    dict exists {} a
+} -ok {0}
+
+test dict-4.9 {dict exists: intermediate key missing} {
+    dict exists {a {b 1}} c b
+} -ok {0}
+
+test dict-4.10 {dict exists: deeply nested} {
+    list [dict exists {a {b {c 1}}} a b c] [dict exists {a {b {c 1}}} a b d]
+} -ok {1 0}
+
+test dict-4.11 {dict exists: malformed intermediate value} {
+    dict exists {a {b c d}} a b
+} -ok {0}
+
 # dict set
 test dict-5.1 {dict set: signature} {
     dict set
@@ -125,10 +145,22 @@ test dict-5.5 {dict set: assign into non-dict} {
     dict set var a x 2
 } -error {missing value to go with key}
 
+test dict-5.6 {dict set: existing key keeps its position} {
+    dict set var a 1
+    dict set var b 2
+    dict set var c 3
+    dict set var a 4
+} -ok {a 4 b 2 c 3}
+
+test dict-5.7 {dict set: existing nested key keeps its position} {
+    set var {a 1 b {x 2 y 3} c 4}
+    dict set var b x 5
+} -ok {a 1 b {x 5 y 3} c 4}
+
 # dict keys
 test dict-6.1 {dict keys: signature} {
     dict keys
-} -error {wrong # args: should be "dict keys dictionary"}
+} -error {wrong # args: should be "dict keys dictionary ?pattern?"}
 
 test dict-6.2 {dict keys: empty} {
     dict keys {}
@@ -138,10 +170,26 @@ test dict-6.3 {dict keys: list of keys} {
     dict keys {a 1 b 2}
 } -ok {a b}
 
+test dict-6.4 {dict keys: insertion order} {
+    dict keys {z 1 a 2 m 3}
+} -ok {z a m}
+
+test dict-6.5 {dict keys: pattern} {
+    dict keys {apple 1 banana 2 apricot 3 cherry 4} a*
+} -ok {apple apricot}
+
+test dict-6.6 {dict keys: pattern, no match} {
+    dict keys {a 1 b 2} x*
+} -ok {}
+
+test dict-6.7 {dict keys: pattern with set} {
+    dict keys {a1 x b2 y c3 z} {[ac]?}
+} -ok {a1 c3}
+
 # dict values
 test dict-7.1 {dict values: signature} {
     dict values
-} -error {wrong # args: should be "dict values dictionary"}
+} -error {wrong # args: should be "dict values dictionary ?pattern?"}
 
 test dict-7.2 {dict values: empty} {
     dict values {}
@@ -151,6 +199,18 @@ test dict-7.3 {dict values: list of values} {
     dict values {a 1 b 2}
 } -ok {1 2}
 
+test dict-7.4 {dict values: insertion order} {
+    dict values {z 3 a 1 m 2}
+} -ok {3 1 2}
+
+test dict-7.5 {dict values: pattern} {
+    dict values {a red b green c rose} r*
+} -ok {red rose}
+
+test dict-7.6 {dict values: pattern, no match} {
+    dict values {a 1 b 2} 3
+} -ok {}
+
 # dict remove
 test dict-8.1 {dict remove: signature} {
     dict remove
@@ -172,6 +232,10 @@ test dict-8.5 {dict remove: non-empty dictionary, keys} {
     dict remove {a 1 b 2 c 3 d 4} b c e
 } -ok {a 1 d 4}
 
+test dict-8.6 {dict remove: remaining keys keep their order} {
+    dict remove {d 1 c 2 b 3 a 4} c
+} -ok {d 1 b 3 a 4}
+
 # dict unset
 test dict-9.1 {dict unset: signature} {
     dict unset
@@ -206,3 +270,312 @@ test dict-9.6 {dict unset: inner dict not a dict} {
     set var {a 1 b 2}
     dict unset var b z
 } -error {missing value to go with key}
+
+# dict merge
+test dict-10.1 {dict merge: no dictionaries} {
+    dict merge
+} -ok {}
+
+test dict-10.2 {dict merge: one dictionary} {
+    dict merge {b 1 a 2}
+} -ok {b 1 a 2}
+
+test dict-10.3 {dict merge: later values win, first position kept} {
+    dict merge {a 1 b 2 c 3} {c 30 d 4 a 10} {e 5 b 20}
+} -ok {a 10 b 20 c 30 d 4 e 5}
+
+test dict-10.4 {dict merge: not a dictionary} {
+    dict merge {a 1} {b}
+} -error {missing value to go with key}
+
+# dict filter
+test dict-11.1 {dict filter: signature} {
+    dict filter {a 1}
+} -error {wrong # args: should be "dict filter dictionary filterType ?arg ...?"}
+
+test dict-11.2 {dict filter: bad filter type} {
+    dict filter {a 1} nonesuch x
+} -error {bad filterType "nonesuch": must be key, script, or value}
+
+test dict-11.3 {dict filter key: one pattern} {
+    dict filter {apple 1 banana 2 apricot 3} key a*
+} -ok {apple 1 apricot 3}
+
+test dict-11.4 {dict filter key: several patterns, dictionary order} {
+    dict filter {apple 1 banana 2 cherry 3 apricot 4} key c* a*
+} -ok {apple 1 cherry 3 apricot 4}
+
+test dict-11.5 {dict filter key: no patterns} {
+    dict filter {a 1 b 2} key
+} -ok {}
+
+test dict-11.6 {dict filter value: patterns} {
+    dict filter {a red b green c rose d blue} value r* b*
+} -ok {a red c rose d blue}
+
+test dict-11.7 {dict filter script: signature} {
+    dict filter {a 1} script {k v}
+} -error {wrong # args: should be "dict filter dictionary script {keyVariable valueVariable} script"}
+
+test dict-11.8 {dict filter script: variable list} {
+    dict filter {a 1} script {k} {set k}
+} -error {must have exactly two variable names}
+
+test dict-11.9 {dict filter script: selects entries} {
+    dict filter {a 1 b 2 c 3 d 4} script {k v} {expr {$v % 2 == 0}}
+} -ok {b 2 d 4}
+
+test dict-11.10 {dict filter script: continue skips the entry} {
+    dict filter {a 1 b 2 c 3} script {k v} {
+        if {$k eq "a"} { continue }
+        expr {1}
+    }
+} -ok {b 2 c 3}
+
+test dict-11.11 {dict filter script: break keeps entries so far} {
+    dict filter {a 1 b 2 c 3} script {k v} {
+        if {$k eq "c"} { break }
+        expr {1}
+    }
+} -ok {a 1 b 2}
+
+test dict-11.12 {dict filter script: non-boolean result} {
+    dict filter {a 1} script {k v} {set k}
+} -error {expected boolean but got "a"}
+
+test dict-11.13 {dict filter script: errors propagate} {
+    dict filter {a 1} script {k v} {error oops}
+} -error {oops}
+
+# dict map
+test dict-12.1 {dict map: signature} {
+    dict map {k v} {a 1}
+} -error {wrong # args: should be "dict map {keyVariable valueVariable} dictionary script"}
+
+test dict-12.2 {dict map: variable list} {
+    dict map {k v x} {a 1} {set v}
+} -error {must have exactly two variable names}
+
+test dict-12.3 {dict map: maps values, keeps order} {
+    dict map {k v} {c 3 a 1 b 2} {expr {$v * 10}}
+} -ok {c 30 a 10 b 20}
+
+test dict-12.4 {dict map: empty dictionary} {
+    dict map {k v} {} {set v}
+} -ok {}
+
+test dict-12.5 {dict map: continue skips the key} {
+    dict map {k v} {a 1 b 2 c 3} {
+        if {$k eq "b"} { continue }
+        set x "$k$v"
+    }
+} -ok {a a1 c c3}
+
+test dict-12.6 {dict map: break returns entries so far} {
+    dict map {k v} {a 1 b 2 c 3} {
+        if {$k eq "c"} { break }
+        set v
+    }
+} -ok {a 1 b 2}
+
+test dict-12.7 {dict map: errors propagate} {
+    dict map {k v} {a 1} {error oops}
+} -error {oops}
+
+test dict-12.8 {dict map: loop variables keep their last values} {
+    dict map {k v} {a 1 b 2} {set v}
+    list $k $v
+} -ok {b 2}
+
+# dict getdef
+test dict-13.1 {dict getdef: signature} {
+    dict getdef {a 1} a
+} -error {wrong # args: should be "dict getdef dictionary ?key ...? key default"}
+
+test dict-13.2 {dict getdef: key found} {
+    dict getdef {a 1 b 2} b x
+} -ok {2}
+
+test dict-13.3 {dict getdef: key missing} {
+    dict getdef {a 1 b 2} c x
+} -ok {x}
+
+test dict-13.4 {dict getdef: empty dictionary} {
+    dict getdef {} a x
+} -ok {x}
+
+test dict-13.5 {dict getdef: nested path found} {
+    dict getdef {a 1 b {x 2 y 3}} b y z
+} -ok {3}
+
+test dict-13.6 {dict getdef: nested key missing} {
+    dict getdef {a 1 b {x 2 y 3}} b w z
+} -ok {z}
+
+test dict-13.7 {dict getdef: intermediate key missing} {
+    dict getdef {a 1} b c d z
+} -ok {z}
+
+test dict-13.8 {dict getdef: intermediate value not a dictionary} {
+    dict getdef {a 1 b 2} b c z
+} -error {missing value to go with key}
+
+test dict-13.9 {dict getdef: not a dictionary} {
+    dict getdef {a 1 b} a z
+} -error {missing value to go with key}
+
+test dict-13.10 {dict getdef: source is unchanged} {
+    set d {a 1 b {x 2}}
+    list [dict getdef $d b y z] [dict getdef $d c z] $d
+} -ok {z z {a 1 b {x 2}}}
+
+test dict-13.11 {dict getwithdefault: same as getdef} {
+    list [dict getwithdefault {a {b 1}} a b x] [dict getwithdefault {a {b 1}} a c x]
+} -ok {1 x}
+
+# dict for
+test dict-14.1 {dict for: signature} {
+    dict for {k v} {a 1}
+} -error {wrong # args: should be "dict for {keyVariable valueVariable} dictionary script"}
+
+test dict-14.2 {dict for: variable list} {
+    dict for {k} {a 1} {}
+} -error {must have exactly two variable names}
+
+test dict-14.3 {dict for: visits entries in order} {
+    set result {}
+    dict for {k v} {c 3 a 1 b 2} { lappend result $k=$v }
+    set result
+} -ok {c=3 a=1 b=2}
+
+test dict-14.4 {dict for: returns the empty string} {
+    dict for {k v} {a 1} { set v }
+} -ok {}
+
+test dict-14.5 {dict for: continue} {
+    set result {}
+    dict for {k v} {a 1 b 2 c 3} {
+        if {$k eq "b"} { continue }
+        lappend result $k
+    }
+    set result
+} -ok {a c}
+
+test dict-14.6 {dict for: break} {
+    set result {}
+    dict for {k v} {a 1 b 2 c 3} {
+        if {$k eq "b"} { break }
+        lappend result $k
+    }
+    set result
+} -ok {a}
+
+test dict-14.7 {dict for: errors propagate} {
+    dict for {k v} {a 1} {error oops}
+} -error {oops}
+
+test dict-14.8 {dict for: not a dictionary} {
+    dict for {k v} {a 1 b} {}
+} -error {missing value to go with key}
+
+# dict update
+test dict-15.1 {dict update: signature} {
+    dict update d a
+} -error {wrong # args: should be "dict update dictVarName key varName ?key varName ...? script"}
+
+test dict-15.2 {dict update: unpaired names} {
+    dict update d a x b {}
+} -error {wrong # args: should be "dict update dictVarName key varName ?key varName ...? script"}
+
+test dict-15.3 {dict update: no such variable} {
+    dict update nonesuch a x {}
+} -error {can't read "nonesuch": no such variable}
+
+test dict-15.4 {dict update: writes variables back} {
+    set d {a 1 b 2}
+    dict update d a x b y { incr x; set y changed }
+    set d
+} -ok {a 2 b changed}
+
+test dict-15.5 {dict update: missing key, unset variable, and new key} {
+    set d {a 1 b 2}
+    set z old
+    dict update d a x b y c z {
+        set r [info exists z]
+        unset y
+        set z new
+    }
+    list $r $d
+} -ok {0 {a 1 c new}}
+
+test dict-15.6 {dict update: returns the body's result} {
+    set d {a 1}
+    dict update d a x { expr {$x + 10} }
+} -ok {11}
+
+test dict-15.7 {dict update: writes back after an error} {
+    set d {a 1}
+    catch { dict update d a x { set x 2; error oops } } msg
+    list $msg $d
+} -ok {oops {a 2}}
+
+test dict-15.8 {dict update: the dictionary variable unset} {
+    set d {a 1}
+    dict update d a x { unset d }
+    info exists d
+} -ok {0}
+
+test dict-15.9 {dict update: break passes through} {
+    set d {a 1}
+    foreach i {1 2 3} {
+        dict update d a x { incr x; if {$x >= 3} { break } }
+    }
+    set d
+} -ok {a 3}
+
+# dict with
+test dict-16.1 {dict with: signature} {
+    dict with d
+} -error {wrong # args: should be "dict with dictVarName ?key ...? script"}
+
+test dict-16.2 {dict with: sets variables from keys} {
+    set d {name Fred age 42}
+    dict with d { list $name $age }
+} -ok {Fred 42}
+
+test dict-16.3 {dict with: writes variables back} {
+    set d {a 1 b 2}
+    dict with d { incr a; unset b }
+    set d
+} -ok {a 2}
+
+test dict-16.4 {dict with: nested path} {
+    set d {x 1 inner {a 1 b 2}}
+    dict with d inner { incr a 10 }
+    set d
+} -ok {x 1 inner {a 11 b 2}}
+
+test dict-16.5 {dict with: missing key in path} {
+    set d {a 1}
+    dict with d b {}
+} -error {key "b" not known in dictionary}
+
+test dict-16.6 {dict with: new variables aren't added} {
+    set d {a 1}
+    dict with d { set extra 2 }
+    set d
+} -ok {a 1}
+
+test dict-16.7 {dict with: writes back after an error} {
+    set d {a 1}
+    catch { dict with d { set a 5; error oops } }
+    set d
+} -ok {a 5}
+
+test dict-16.8 {dict with: continue passes through} {
+    set d {a 0}
+    foreach i {1 2 3} {
+        dict with d { incr a; continue }
+    }
+    set d
+} -ok {a 3}
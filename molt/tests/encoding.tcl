@@ -0,0 +1,83 @@
+# Test Script: encoding command.
+
+# encoding-1.*: Syntax
+
+test encoding-1.1 {syntax} {
+    encoding
+} -error {wrong # args: should be "encoding subcommand ?arg ...?"}
+
+test encoding-1.2 {unknown subcommand} {
+    encoding frob
+} -error {unknown or ambiguous subcommand "frob", must be:
+convertfrom, convertto, names or system.}
+
+test encoding-1.3 {convertto syntax} {
+    encoding convertto
+} -error {wrong # args: should be "encoding convertto ?encoding? string"}
+
+test encoding-1.4 {convertfrom syntax} {
+    encoding convertfrom a b c
+} -error {wrong # args: should be "encoding convertfrom ?encoding? data"}
+
+test encoding-1.5 {unknown encoding} {
+    encoding convertto ebcdic abc
+} -error {unknown encoding "ebcdic"}
+
+# encoding-2.*: names and system
+
+test encoding-2.1 {names} {
+    encoding names
+} -ok {ascii iso8859-1 utf-8 utf-16be utf-16le}
+
+test encoding-2.2 {system} {
+    encoding system
+} -ok {utf-8}
+
+# encoding-3.*: convertto
+
+test encoding-3.1 {utf-8 by default} {
+    binary encode hex [encoding convertto "café"]
+} -ok {636166c3a9}
+
+test encoding-3.2 {utf-16} {
+    list [binary encode hex [encoding convertto utf-16be "Aé"]] \
+        [binary encode hex [encoding convertto utf-16le "Aé"]]
+} -ok {004100e9 4100e900}
+
+test encoding-3.3 {latin-1} {
+    binary encode hex [encoding convertto iso8859-1 "café"]
+} -ok {636166e9}
+
+test encoding-3.4 {unrepresentable character} {
+    encoding convertto ascii "café"
+} -error {unexpected character at index 3: 'U+0000E9'}
+
+# encoding-4.*: convertfrom
+
+test encoding-4.1 {utf-8 by default} {
+    encoding convertfrom [binary decode hex 636166c3a9]
+} -ok "café"
+
+test encoding-4.2 {round trips} {
+    set result {}
+    foreach enc [encoding names] {
+        lappend result [encoding convertfrom $enc [encoding convertto $enc abc]]
+    }
+    set result
+} -ok {abc abc abc abc abc}
+
+test encoding-4.3 {latin-1} {
+    encoding convertfrom latin1 [binary decode hex 636166e9]
+} -ok "café"
+
+test encoding-4.4 {invalid utf-8} {
+    encoding convertfrom utf-8 [binary decode hex 6166c3]
+} -error {unexpected byte sequence starting at index 2: '\xC3'}
+
+test encoding-4.5 {odd utf-16} {
+    encoding convertfrom utf-16le [binary decode hex 410042]
+} -error {unexpected byte sequence starting at index 2: '\x42'}
+
+test encoding-4.6 {not a byte string} {
+    encoding convertfrom "Ā"
+} -error {expected byte sequence but character 0 was 'Ā' (U+000100)}
@@ -2,7 +2,7 @@
 
 test error-1.1 {error error} {
     error
-} -error {wrong # args: should be "error message"}
+} -error {wrong # args: should be "error message ?info? ?code?"}
 
 test error-2.1 {error returns error} {
     error "simulated error"
@@ -29,3 +29,48 @@ test error-3.1 {error sets return options} {
     set a [catch { error "Message" } result opts]
     list $a $result [dict get $opts -code] [dict get $opts -level] [dict get $opts -errorcode]
 } -ok {1 Message 1 0 NONE}
+
+test error-3.2 {error sets error code} {
+    catch { error "Message" "" {MY CODE} } result opts
+    dict get $opts -errorcode
+} -ok {MY CODE}
+
+test error-3.3 {error sets error info} {
+    catch { error "Message" "Initial info" } result opts
+    list $result [dict get $opts -errorinfo] [dict get $opts -errorcode]
+} -ok {Message {Initial info} NONE}
+
+test error-3.4 {error sets errorCode and errorInfo variables} {
+    global errorCode errorInfo
+    catch { error "Message" "Initial info" {A B {c d}} }
+    list [lindex $errorCode 2] $errorInfo
+} -ok {{c d} {Initial info}}
+
+test error-4.1 {errorInfo survives later successful commands} {
+    global errorInfo
+    catch { error "Message" "Stale info" }
+    set a 1
+    set errorInfo
+} -ok {Stale info}
+
+test error-4.2 {overwriting errorInfo doesn't affect later traces} {
+    global errorInfo
+    set errorInfo "junk"
+    catch { error Message }
+    set errorInfo
+} -ok {Message
+while executing
+  "error Message"}
+
+test error-4.3 {errors are reported even if errorInfo is an array} -setup {
+    global errorInfo errorCode
+    set savedInfo $errorInfo
+    unset errorInfo
+    array set errorInfo {a 1}
+} -body {
+    list [catch { error "Message" "" {MY CODE} } result] $result $errorCode
+} -cleanup {
+    global errorInfo
+    unset errorInfo
+    set errorInfo $savedInfo
+} -ok {1 Message {MY CODE}}
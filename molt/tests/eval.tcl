@@ -0,0 +1,47 @@
+# Test Script: eval command.
+
+test eval-1.1 {syntax} {
+    eval
+} -error {wrong # args: should be "eval arg ?arg ...?"}
+
+test eval-1.2 {one argument} {
+    eval {set x 1}
+} -ok {1}
+
+test eval-1.3 {several arguments are concatenated} {
+    eval list a b
+} -ok {a b}
+
+test eval-1.4 {list arguments keep their structure} {
+    eval [list set z "a b"]
+    set z
+} -ok {a b}
+
+test eval-1.5 {arguments are concatenated, not quoted} {
+    eval set w "{c d}"
+} -ok {c d}
+
+test eval-1.6 {local scope} {
+    proc evaltest {} {
+        set loc 5
+        eval {set loc}
+    }
+    evaltest
+} -ok {5}
+
+test eval-1.7 {control codes pass through} {
+    set r {}
+    foreach i {1 2 3} {
+        lappend r $i
+        eval break
+    }
+    set r
+} -ok {1}
+
+test eval-2.1 {errorInfo notes the eval} {
+    catch {eval error oops} msg opts
+    dict get $opts -errorinfo
+} -ok {oops
+while executing
+  "error oops"
+    (while evaluating eval'd script)}
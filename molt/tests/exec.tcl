@@ -0,0 +1,85 @@
+# Test Script: exec command.
+#
+# This command is behind the `exec` feature; all.tcl sources this file only when
+# `test_exec` says it's on.  The tests run `sh`, `echo`, and `cat`, so they only run on
+# Unix.
+
+set execdir [exec mktemp -d]
+
+# exec-1.*: Syntax and errors
+
+test exec-1.1 {bad option} {
+    exec -frob echo
+} -error {bad option "-frob": must be -ignorestderr, -keepnewline, or --}
+
+test exec-1.2 {no command} {
+    exec < /dev/null
+} -error {didn't specify command to execute}
+
+test exec-1.3 {redirection as last word} {
+    exec echo hi >
+} -error {can't specify ">" as last word in command}
+
+test exec-1.4 {pipelines} {
+    exec echo hi | cat
+} -error {"|" isn't supported: exec runs a single program}
+
+test exec-1.5 {no such program} {
+    exec molt-no-such-program
+} -error {couldn't execute "molt-no-such-program": no such file or directory}
+
+test exec-1.6 {non-zero exit status} {
+    set code [catch {exec sh -c {exit 3}} msg opts]
+    list $code $msg [lreplace [dict get $opts -errorcode] 1 1 PID]
+} -ok {1 {child process exited abnormally} {CHILDSTATUS PID 3}}
+
+test exec-1.7 {non-zero exit status, with output} {
+    exec sh -c {echo failed; exit 1}
+} -error {failed}
+
+# exec-2.*: Output
+
+test exec-2.1 {output} {
+    exec echo hello world
+} -ok {hello world}
+
+test exec-2.2 {-keepnewline} {
+    exec -keepnewline echo hi
+} -ok "hi\n"
+
+test exec-2.3 {input from a value} {
+    exec cat << {a b}
+} -ok {a b}
+
+test exec-2.4 {stderr to the result} {
+    exec sh -c {echo out; echo err >&2} 2>@1
+} -ok "out\nerr"
+
+test exec-2.5 {stderr is an error} {
+    exec sh -c {echo out; echo err >&2}
+} -error "out\nerr"
+
+test exec-2.6 {-ignorestderr} {
+    exec -ignorestderr -- sh -c {echo out; echo err >&2}
+} -ok {out}
+
+# exec-3.*: Redirection to and from files
+
+test exec-3.1 {write, append, and read} {
+    set out $::execdir/out1.txt
+    list [exec echo one > $out] [exec echo two >>$out] [exec cat < $out]
+} -ok {{} {} {one
+two}}
+
+test exec-3.2 {stdout and stderr to a file} {
+    set out $::execdir/out2.txt
+    list [exec sh -c {echo three; echo four >&2} > $out 2>@1] [exec cat $out]
+} -ok {{} {three
+four}}
+
+test exec-3.3 {stderr to a file} {
+    set out $::execdir/out3.txt
+    list [exec sh -c {echo five >&2} 2> $out] [exec cat $out]
+} -ok {{} five}
+
+exec rm -r $execdir
@@ -19,19 +19,26 @@ test expr-1.1 {literals} {
 
 test expr-1.2 {literals} {
     lexpr {1.1} {-1.1} {+1.1} {1.1e3} {-1.1e3} {1.1e-3}
-} -ok {1.1 -1.1 1.1 1100 -1100 0.0011}
+} -ok {1.1 -1.1 1.1 1100.0 -1100.0 0.0011}
 
 test expr-1.3 {literals} {
     lexpr {1.0} {-1.0} {+1.0}
-    # In Tcl, would be "1.0", etc.
-    # TODO: Look into floating point compatibility with TCL.
-} -ok {1 -1 1}
+} -ok {1.0 -1.0 1.0}
 
 test expr-1.4 {literals} {
    lexpr true yes on false no off
    # TCL, would return the symbolic constants in this case.
 } -ok {1 1 1 0 0 0}
 
+test expr-1.5 {float string reps} {
+    lexpr {1e300} {-1.5e-5} {1e16} {1e17} {0.1 + 0.2}
+} -ok {1e+300 -1.5e-5 10000000000000000.0 1e+17 0.30000000000000004}
+
+test expr-1.6 {float string reps parse back to the same float} {
+    set x [expr {1.0 / 3.0}]
+    list [expr {$x * 3.0 == 1.0}] [string is integer [expr {2.0 * 3}]]
+} -ok {1 0}
+
 # expr-2.*: arithmetic
 
 test expr-2.1 {arithmetic} {
@@ -60,7 +67,7 @@ test expr-2.6 {arithmetic} {
 
 test expr-2.7 {arithmetic} {
     lexpr {1.1 + 2} {3 + 2.1} {3 + 1.0}
-} -ok {3.1 5.1 4}
+} -ok {3.1 5.1 4.0}
 
 test expr-2.8 {arithmetic} {
     lexpr {3.1 - 1} {1.1 - 3} {3.1 - 0}
@@ -68,11 +75,11 @@ test expr-2.8 {arithmetic} {
 
 test expr-2.9 {arithmetic} {
     lexpr {2.5 * 3} {0.0 * 2} {2.0 * 0}
-} -ok {7.5 0 0}
+} -ok {7.5 0.0 0.0}
 
 test expr-2.10 {arithmetic} {
     lexpr {4.0 / 2} {5 / 2.0} {6.2 / 2}
-} -ok {2 2.5 3.1}
+} -ok {2.0 2.5 3.1}
 
 test expr-2.11 {arithmetic} {
     expr {2.1 / 0.0}
@@ -142,6 +149,27 @@ test expr-2.19 {remainder overflow} {
     expr {1 % (-9223372036854775807 - 1)}
 } -ok {1} ;# Arguably, should be '-error "integer overflow"'
 
+test expr-2.20 {abs overflow} {
+    expr {abs(-9223372036854775807 - 1)}
+} -error {integer overflow}
+
+test expr-2.21 {int overflow} {
+    list [catch {expr {int(1e300)}}] [catch {expr {int(-1e300)}}] \
+        [catch {expr {int(9.3e18)}} msg] $msg
+} -ok {1 1 1 {integer overflow}}
+
+test expr-2.22 {int of the least integer} {
+    expr {int(-9223372036854775808.0)}
+} -ok {-9223372036854775808}
+
+test expr-2.23 {round overflow} {
+    expr {round(-1e300)}
+} -error {integer overflow}
+
+test expr-2.24 {float argument overflow} {
+    expr {srand(1e300)}
+} -error {integer overflow}
+
 # expr-3.*: Logical Operators
 proc aflag {flag} {
     global a
@@ -251,6 +279,87 @@ test expr-5.3 {bit-wise} {
     lexpr {~1} {~0}
 } -ok {-2 -1}
 
+test expr-5.4 {bit-wise: xor} {
+    lexpr {1 ^ 2} {2 ^ 2} {3 ^ 2}
+} -ok {3 0 1}
+
+test expr-5.5 {bit-wise: shifts} {
+    lexpr {1 << 4} {0xFF00 >> 8} {-8 >> 1} {-8 >> 70} {8 >> 64} {0 << 100}
+} -ok {16 255 -4 -1 0 0}
+
+test expr-5.6 {bit-wise: precedence} {
+    lexpr {5 & 3 == 1} {7 ^ 2 | 8} {1 | 2 ^ 3 & 4} {1 << 2 + 1}
+} -ok {0 13 3 8}
+
+test expr-5.7 {unary precedence} {
+    lexpr {~2*3} {-2*3} {!0 + 1} {-2 * -3} {~0 & 0xF}
+} -ok {-9 -6 2 6 15}
+
+test expr-5.8 {bit-wise: negative shift} {
+    expr {1 << -1}
+} -error {negative shift argument}
+
+test expr-5.9 {bit-wise: negative shift} {
+    expr {1 >> -1}
+} -error {negative shift argument}
+
+test expr-5.10 {bit-wise: shift overflow} {
+    expr {1 << 64}
+} -error {integer overflow}
+
+test expr-5.11 {bit-wise: shift overflow} {
+    expr {0x4000000000000000 << 2}
+} -error {integer overflow}
+
+test expr-5.12 {bit-wise: floats} {
+    expr {1.5 & 1}
+} -error {can't use floating-point value as operand of "&"}
+
+test expr-5.13 {unary minus overflow} {
+    expr {-(-9223372036854775807 - 1)}
+} -error {integer overflow}
+
+# expr-7.*: rand() and srand()
+
+test expr-7.1 {rand range} {
+    set ok 1
+    for {set i 0} {$i < 1000} {incr i} {
+        set x [expr {rand()}]
+        if {$x < 0.0 || $x >= 1.0} {
+            set ok 0
+        }
+    }
+    set ok
+} -ok {1}
+
+test expr-7.2 {int(rand()*N) covers 0..N-1} {
+    set seen {}
+    for {set i 0} {$i < 1000} {incr i} {
+        set n [expr {int(rand()*4)}]
+        if {$n < 0 || $n > 3} {
+            error "out of range: $n"
+        }
+        dict set seen $n 1
+    }
+    dict size $seen
+} -ok {4}
+
+test expr-7.3 {srand returns the first value of the sequence} {
+    set a [expr {srand(42)}]
+    set b [expr {rand()}]
+    set c [expr {srand(42)}]
+    set d [expr {rand()}]
+    list [expr {$a == $c}] [expr {$b == $d}] [expr {$a != $b}]
+} -ok {1 1 1}
+
+test expr-7.4 {rand: no arguments} {
+    expr {rand(1)}
+} -error {syntax error in expression "rand(1)"}
+
+test expr-7.5 {srand: one argument} {
+    expr {srand()}
+} -error {syntax error in expression "srand()"}
+
 # expr-6.*: ?: operator
 
 test expr-6.1 {questy} {
@@ -301,3 +410,86 @@ rename aflag ""
 rename bflag ""
 rename a ""
 rename b ""
+
+test expr-8.1 {math functions: unknown function} {
+    expr {nonesuch(1)}
+} -error {unknown math function "nonesuch"}
+
+test expr-8.2 {math functions: tcl::mathfunc proc} -setup {
+    proc tcl::mathfunc::half {x} { expr {$x / 2.0} }
+} -body {
+    expr {half(5) + 1}
+} -cleanup {
+    rename tcl::mathfunc::half {}
+} -ok {3.5}
+
+test expr-8.3 {math functions: mathfunc proc, several arguments} -setup {
+    proc mathfunc::sum3 {a b c} { expr {$a + $b + $c} }
+} -body {
+    expr {sum3(1, 2 * 3, sum3(1, 1, 1))}
+} -cleanup {
+    rename mathfunc::sum3 {}
+} -ok {10}
+
+test expr-8.4 {math functions: no arguments} -setup {
+    proc tcl::mathfunc::seven {} { return 7 }
+} -body {
+    expr { seven( ) * 2 }
+} -cleanup {
+    rename tcl::mathfunc::seven {}
+} -ok {14}
+
+test expr-8.5 {math functions: wrong # args} -setup {
+    proc tcl::mathfunc::half {x} { expr {$x / 2.0} }
+} -body {
+    expr {half(1, 2)}
+} -cleanup {
+    rename tcl::mathfunc::half {}
+} -error {wrong # args: should be "tcl::mathfunc::half x"}
+
+test expr-8.6 {math functions: not called when not evaluated} -setup {
+    proc tcl::mathfunc::boom {} { error "called boom" }
+} -body {
+    expr {1 || boom()}
+} -cleanup {
+    rename tcl::mathfunc::boom {}
+} -ok {1}
+
+test expr-8.7 {math functions: built-ins not called when not evaluated} {
+    list [expr {1 || rand()}] [expr {0 && abs(1)}]
+} -ok {1 0}
+
+test expr-8.8 {math functions: built-ins take precedence} -setup {
+    proc tcl::mathfunc::abs {x} { return 99 }
+} -body {
+    expr {abs(-1)}
+} -cleanup {
+    rename tcl::mathfunc::abs {}
+} -ok {1}
+
+# expr-9.*: Command arguments
+
+test expr-9.1 {expr: wrong # args} {
+    expr
+} -error {wrong # args: should be "expr arg ?arg ...?"}
+
+test expr-9.2 {expr: single braced argument} {
+    expr {1 + 2}
+} -ok {3}
+
+test expr-9.3 {expr: several arguments are concatenated} {
+    expr 1 + 2
+} -ok {3}
+
+test expr-9.4 {expr: arguments are joined before parsing} {
+    set x "1 + "
+    expr $x 2
+} -ok {3}
+
+test expr-9.5 {expr: whitespace around arguments is trimmed} {
+    expr " 2 " * " 3 "
+} -ok {6}
+
+test expr-9.6 {expr: syntax errors show the joined expression} {
+    expr 1 +
+} -error {syntax error in expression "1 +"}
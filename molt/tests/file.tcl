@@ -0,0 +1,54 @@
+# Test Script: file command.
+#
+# This command is behind the `file-io` feature; all.tcl sources this file only when
+# `test_file_io` says it's on.  The tests work in `$test_tmpdir`, which tcl_tests.rs
+# removes when they are done.
+
+# file-1.*: Directories and files
+
+test file-1.1 {mkdir} {
+    set root $::test_tmpdir/file1
+    lappend result [file exists $root] [file mkdir [file join $root a b]]
+    lappend result [file isdirectory $root/a/b] [file isfile $root/a/b]
+} -ok {0 {} 1 0}
+
+test file-1.2 {isfile} {
+    set root $::test_tmpdir/file2
+    file mkdir $root
+    close [open $root/x.txt w]
+    list [file isfile $root/x.txt] [file isdirectory $root/x.txt]
+} -ok {1 0}
+
+test file-1.3 {mkdir over a file} {
+    set root $::test_tmpdir/file3
+    file mkdir $root
+    close [open $root/x.txt w]
+    catch {file mkdir $root/x.txt} msg
+    string map [list $root ROOT] $msg
+} -ok {can't create directory "ROOT/x.txt": file already exists}
+
+# file-2.*: Deleting
+
+test file-2.1 {delete files, ignoring missing ones} {
+    set root $::test_tmpdir/file4
+    file mkdir $root
+    close [open $root/x.txt w]
+    list [file delete $root/x.txt $root/nonesuch] [file exists $root/x.txt]
+} -ok {{} 0}
+
+test file-2.2 {delete a directory that isn't empty} {
+    set root $::test_tmpdir/file5
+    file mkdir $root/a
+    catch {file delete $root} msg
+    string map [list $root ROOT] $msg
+} -ok {error deleting "ROOT": directory not empty}
+
+test file-2.3 {delete -force} {
+    set root $::test_tmpdir/file6
+    file mkdir $root/a
+    list [file delete -force -- $root] [file exists $root]
+} -ok {{} 0}
+
+test file-2.4 {bad option} {
+    file delete -frob $::test_tmpdir
+} -error {bad option "-frob": must be -force or --}
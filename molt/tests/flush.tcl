@@ -0,0 +1,20 @@
+# Test Script: flush
+#
+# The output itself is checked by the Rust tests, which can replace the writers.
+
+test flush-1.1 {flush stdout} {
+    puts "flushing"
+    list [flush] [flush stdout]
+} -ok {{} {}}
+
+test flush-1.2 {flush stderr} {
+    flush stderr
+} -ok {}
+
+test flush-2.1 {flush errors} {
+    flush stdin
+} -error {can not find channel named "stdin"}
+
+test flush-2.2 {flush errors} {
+    flush stdout stderr
+} -error {wrong # args: should be "flush ?channelId?"}
@@ -77,10 +77,10 @@ test for-2.5 {for loop result} {
 } -ok {}
 
 test for-3.1 {break in for start} {
-    catch {
+    assert_code {
         for {break} {1} {} {}
-    }
-} -ok {3}
+    } break
+} -ok {}
 
 test for-3.2 {break in for test} {
     for {} {[break]} {} {}
@@ -91,10 +91,10 @@ test for-3.3 {break in for next} {
 } -ok {}
 
 test for-3.4 {continue in for start} {
-    catch {
+    assert_code {
         for {continue} {1} {} {}
-    }
-} -ok {4}
+    } continue
+} -ok {}
 
 test for-3.5 {continue in for test} {
     for {} {[continue]} {} {}
@@ -103,3 +103,30 @@ test for-3.5 {continue in for test} {
 test for-3.6 {continue in for next} {
     for {} {1} {continue} {}
 } -error {invoked "continue" outside of a loop}
+
+test for-4.1 {continue still runs next} {
+    set a {}
+    set n 0
+    for {set i 0} {$i < 5} {incr i} {
+        incr n
+        continue
+        lappend a $i
+    }
+    list $i $n $a
+} -ok {5 5 {}}
+
+test for-4.2 {continue in every other iteration still runs next} {
+    set a {}
+    for {set i 0} {$i < 6} {lappend a next; incr i} {
+        if {$i % 2} {
+            continue
+        }
+        lappend a $i
+    }
+    set a
+} -ok {0 next next 2 next next 4 next next}
+
+test for-4.3 {for with empty body and next} {
+    for {set i 0} {[incr i] < 1000} {} {}
+    set i
+} -ok {1000}
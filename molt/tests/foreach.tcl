@@ -54,3 +54,14 @@ test foreach-4.2 {continue in loop body} {
     }
     list $a $b
 } -ok {3 start}
+
+test foreach-5.1 {foreach result is empty} {
+    foreach a {1 2 3} {
+        set b $a
+    }
+} -ok {}
+
+test foreach-5.2 {empty body still assigns the variables} {
+    foreach {a b} {1 2 3 4 5} {}
+    list $a $b
+} -ok {5 {}}
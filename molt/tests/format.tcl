@@ -0,0 +1,183 @@
+# Test Script: format command.
+
+# format-1.*: Syntax and errors
+
+test format-1.1 {syntax} {
+    format
+} -error {wrong # args: should be "format formatString ?arg ...?"}
+
+test format-1.2 {no specifiers} {
+    format "hello, world"
+} -ok {hello, world}
+
+test format-1.3 {not enough arguments} {
+    format "%d and %d" 1
+} -error {not enough arguments for all format specifiers}
+
+test format-1.4 {bad conversion} {
+    format %q 1
+} -error {bad field specifier "q"}
+
+test format-1.5 {incomplete specifier} {
+    format "50%"
+} -error {format string ended in middle of field specifier}
+
+test format-1.6 {bad integer} {
+    format %d abc
+} -error {expected integer but got "abc"}
+
+test format-1.7 {bad float} {
+    format %f abc
+} -error {expected floating-point number but got "abc"}
+
+# format-2.*: Integers
+
+test format-2.1 {decimal} {
+    format "%d %i %u" 42 -7 3
+} -ok {42 -7 3}
+
+test format-2.2 {width and flags} {
+    format {[%5d|%-5d|%05d|%+d|% d]} 42 42 -42 42 42
+} -ok {[   42|42   |-0042|+42| 42]}
+
+test format-2.3 {precision} {
+    format {[%.3d|%6.3d]} 7 -7
+} -ok {[007|  -007]}
+
+test format-2.4 {other radixes} {
+    format "%x %X %o %b" 255 255 8 5
+} -ok {ff FF 10 101}
+
+test format-2.5 {alternate forms} {
+    format "%#x %#o %#b %#06x" 255 8 5 255
+} -ok {0xff 010 0b101 0x00ff}
+
+test format-2.6 {size modifiers} {
+    format "%ld %lld %hd" 1 2 3
+} -ok {1 2 3}
+
+test format-2.7 {character} {
+    format %c%c%c 72 105 33
+} -ok {Hi!}
+
+test format-2.8 {precision with zero flag} {
+    format {[%06.3d]} 7
+} -ok {[   007]}
+
+test format-2.9 {alternate forms, upper case and zero} {
+    format "%#X %#x %#08x" 255 0 255
+} -ok {0XFF 0 0x0000ff}
+
+test format-2.10 {negative hex} {
+    format %x -1
+} -ok {ffffffffffffffff}
+
+test format-2.11 {non-ASCII character} {
+    format %c 9731
+} -ok {☃}
+
+# format-3.*: Floating-point numbers
+
+test format-3.1 {fixed-point} {
+    format {%f|%.2f|%8.3f|%-8.1f|%08.2f} 1.5 3.14159 2.5 2 -3.14159
+} -ok {1.500000|3.14|   2.500|2.0     |-0003.14}
+
+test format-3.2 {integer argument} {
+    format %.1f 3
+} -ok {3.0}
+
+test format-3.3 {exponential} {
+    format "%e %.2E" 12345.678 0.000123
+} -ok {1.234568e+04 1.23E-04}
+
+test format-3.4 {general} {
+    format "%g %g %g %g %.3g" 100000 1000000 0.0001 0.00001 3.14159
+} -ok {100000 1e+06 0.0001 1e-05 3.14}
+
+test format-3.5 {alternate forms} {
+    format "%#.0f %#g" 3 1.5
+} -ok {3. 1.50000}
+
+test format-3.6 {sign and rounding} {
+    format "%+.1f %.0f" 2 2.5
+} -ok {+2.0 2}
+
+test format-3.7 {zero} {
+    format "%e %g" 0 0
+} -ok {0.000000e+00 0}
+
+test format-3.8 {general, upper case and rounding} {
+    format "%G %.0g %g" 1e-10 123 999999.5
+} -ok {1E-10 1e+02 1e+06}
+
+test format-3.9 {infinities and NaN} {
+    format {%f|%5.1f|%f} Inf -Inf NaN
+} -ok {Inf| -Inf|NaN}
+
+# format-4.*: Strings
+
+test format-4.1 {strings} {
+    format "%s and %s" this that
+} -ok {this and that}
+
+test format-4.2 {width and precision} {
+    format {[%6s|%-6s|%.2s]} abc abc abc
+} -ok {[   abc|abc   |ab]}
+
+test format-4.3 {width counts chars} {
+    format {[%4s]} äö
+} -ok {[  äö]}
+
+test format-4.4 {percent} {
+    format "%d%%" 50
+} -ok {50%}
+
+test format-4.5 {zero flag pads strings with spaces} {
+    format {[%05s]} ab
+} -ok {[   ab]}
+
+test format-4.6 {width counts wide chars once} {
+    format {[%5s]} 日本
+} -ok {[   日本]}
+
+# format-5.*: Star and position specifiers
+
+test format-5.1 {star width and precision} {
+    format {[%*d|%.*f]} 5 1 2 3.14159
+} -ok {[    1|3.14]}
+
+test format-5.2 {negative star width} {
+    format {[%*d]} -4 1
+} -ok {[1   ]}
+
+test format-5.3 {positions} {
+    format {%2$s %1$s %2$s} a b
+} -ok {b a b}
+
+test format-5.4 {mixed positions} {
+    format {%1$s %s} a
+} -error {cannot mix "%" and "%n$" conversion specifiers}
+
+test format-5.5 {position out of range} {
+    format {%3$s} a
+} -error {"%n$" argument index out of range}
+
+test format-5.6 {extra arguments are ignored} {
+    format %s a b
+} -ok {a}
+
+test format-5.7 {width too large} {
+    format %-1000000000000d 1
+} -error {field width or precision "1000000000000" is too large}
+
+test format-5.8 {precision too large} {
+    format %.1000001f 1.0
+} -error {field width or precision "1000001" is too large}
+
+test format-5.9 {star width too large} {
+    format %*s -9223372036854775807 a
+} -error {field width or precision "9223372036854775807" is too large}
+
+test format-5.10 {position with flags} {
+    format {%1$05d} 42
+} -ok {00042}
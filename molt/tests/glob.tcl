@@ -0,0 +1,52 @@
+# Test Script: glob command.
+#
+# This command is behind the `file-io` feature; all.tcl sources this file only when
+# `test_file_io` says it's on.  The tests work in `$test_tmpdir`, which tcl_tests.rs
+# removes when they are done.
+
+set globdir $test_tmpdir/glob
+file mkdir $globdir/sub
+foreach name {b.tcl a.tcl c.txt .hidden.tcl sub/d.tcl} {
+    close [open $globdir/$name w]
+}
+
+# glob-1.*: Syntax and errors
+
+test glob-1.1 {syntax} {
+    glob -nocomplain
+} -error {wrong # args: should be "glob ?-nocomplain? ?-directory dir? ?--? pattern ?pattern ...?"}
+
+test glob-1.2 {bad option} {
+    glob -types f *
+} -error {bad option "-types": must be -directory, -nocomplain, or --}
+
+test glob-1.3 {no match} {
+    catch {glob $::globdir/*.c $::globdir/nonesuch} msg
+    string map [list $::globdir ROOT] $msg
+} -ok {no files matched glob patterns "ROOT/*.c ROOT/nonesuch"}
+
+test glob-1.4 {no match, -nocomplain} {
+    glob -nocomplain $::globdir/*.c
+} -ok {}
+
+# glob-2.*: Matching
+
+test glob-2.1 {-directory, sorted, skipping hidden files} {
+    string map [list $::globdir ROOT] [glob -directory $::globdir *.tcl]
+} -ok {ROOT/a.tcl ROOT/b.tcl}
+
+test glob-2.2 {braces and explicit dots} {
+    string map [list $::globdir ROOT] [glob -directory $::globdir -- *.{txt,tcl} .*.tcl]
+} -ok {ROOT/c.txt ROOT/a.tcl ROOT/b.tcl ROOT/.hidden.tcl}
+
+test glob-2.3 {patterns in directories} {
+    string map [list $::globdir ROOT] [glob $::globdir/*/*.tcl]
+} -ok {ROOT/sub/d.tcl}
+
+test glob-2.4 {trailing slash matches directories} {
+    string map [list $::globdir ROOT] [glob $::globdir/*/]
+} -ok {ROOT/sub/}
+
+test glob-2.5 {no pattern characters} {
+    string map [list $::globdir ROOT] [glob $::globdir/sub/d.tcl]
+} -ok {ROOT/sub/d.tcl}
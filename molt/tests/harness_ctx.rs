@@ -0,0 +1,43 @@
+use molt_forked::prelude::*;
+
+// An application context that isn't a `(Ctx, TestCtx)` tuple.
+#[derive(Default)]
+struct AppCtx {
+    greeting: String,
+    tests: TestCtx,
+}
+
+impl HasTestCtx for AppCtx {
+    fn test_ctx_mut(&mut self) -> &mut TestCtx {
+        &mut self.tests
+    }
+}
+
+fn cmd_greet(interp: &mut Interp<AppCtx>, argv: &[Value]) -> MoltResult {
+    molt_ok!("{}, {}", interp.context.greeting, argv[1])
+}
+
+#[test]
+fn test_harness_with_struct_context() {
+    let mut interp = Interp::new(
+        AppCtx { greeting: "Hello".into(), ..Default::default() },
+        gen_command!(
+            AppCtx,
+            [],
+            [
+                (
+                    "test",
+                    test_cmd,
+                    ArgSpec { min: 4, max: 0, usage: "name description args..." }
+                ),
+                ("greet", cmd_greet, ArgSpec { min: 2, max: 2, usage: "name" })
+            ]
+        ),
+        false,
+        "app",
+    );
+
+    let args = vec![String::from("tests/test.tcl")];
+    assert!(test_harness(&mut interp, &args).is_ok());
+    assert_eq!(interp.eval("greet world"), Ok(Value::from("Hello, world")));
+}
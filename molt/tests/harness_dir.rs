@@ -0,0 +1,138 @@
+use molt_forked::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// The harness changes the current directory, so the tests must not run concurrently.
+static CWD: Mutex<()> = Mutex::new(());
+
+fn new_interp() -> Interp<((), TestCtx)> {
+    Interp::new(
+        ((), TestCtx::new()),
+        gen_command!(
+            ((), TestCtx),
+            [(_SOURCE, cmd_source)],
+            [(
+                "test",
+                test_cmd,
+                ArgSpec { min: 4, max: 0, usage: "name description args..." }
+            )]
+        ),
+        false,
+        "test",
+    )
+}
+
+// Creates a fresh directory of test scripts, returning its absolute path.
+fn make_suite(name: &str, files: &[(&str, &str)]) -> String {
+    let dir: PathBuf = std::env::temp_dir().join(format!(
+        "molt-harness-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    for (file, script) in files {
+        fs::write(dir.join(file), script).unwrap();
+    }
+
+    dir.to_str().unwrap().to_string()
+}
+
+fn run(
+    interp: &mut Interp<((), TestCtx)>,
+    dir: &str,
+    options: &[&str],
+) -> Result<(), ()> {
+    let mut args = vec![dir.to_string()];
+    args.extend(options.iter().map(|opt| opt.to_string()));
+    test_harness(interp, &args)
+}
+
+const POLLUTER: &str = r#"
+proc leaked {} { return 1 }
+set leaked_var 1
+set shared changed
+proc helper {} { return changed }
+test pollute-1 {} { leaked } -ok 1
+"#;
+
+const CHECKER: &str = r#"
+test check-1 {new procs are removed} { expr {"leaked" in [info procs]} } -ok 0
+test check-2 {new globals are removed} { expr {"leaked_var" in [info globals]} } -ok 0
+test check-3 {globals are restored} { global shared; set shared } -ok original
+test check-4 {procs are restored} { helper } -ok original
+"#;
+
+const FAILING: &str = r#"
+test fail-1 {fails} { set x 1 } -ok 2
+"#;
+
+#[test]
+fn test_dir_isolates_files() {
+    let _guard = CWD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = make_suite(
+        "isolation",
+        &[
+            ("a_pollute.tcl", POLLUTER),
+            ("b_check.tcl", CHECKER),
+            ("c_fail.tcl", FAILING),
+            ("notes.txt", "not a test script"),
+        ],
+    );
+
+    let mut interp = new_interp();
+    interp.set_scalar("shared", Value::from("original")).unwrap();
+    interp.eval("proc helper {} { return original }").unwrap();
+
+    // b_check.tcl passes only if a_pollute.tcl's changes were undone.
+    assert!(run(&mut interp, &dir, &["-notfile", "c_*"]).is_ok());
+    assert!(!interp.has_proc("leaked"));
+    assert_eq!(interp.scalar("shared"), Ok(Value::from("original")));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_dir_file_filters() {
+    let _guard = CWD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = make_suite(
+        "filters",
+        &[("a_pass.tcl", "test a-1 {} { set x 1 } -ok 1"), ("c_fail.tcl", FAILING)],
+    );
+
+    // All scripts: the failure is reported.
+    assert!(run(&mut new_interp(), &dir, &[]).is_err());
+
+    // Only the passing script.
+    assert!(run(&mut new_interp(), &dir, &["-file", "a_*.tcl"]).is_ok());
+
+    // Several patterns.
+    assert!(run(&mut new_interp(), &dir, &["-file", "x*", "-file", "c*"]).is_err());
+
+    // Nothing selected is an error, as are bad options.
+    assert!(run(&mut new_interp(), &dir, &["-file", "*.txt"]).is_err());
+    assert!(run(&mut new_interp(), &dir, &["-nonesuch", "x"]).is_err());
+    assert!(run(&mut new_interp(), &dir, &["-file"]).is_err());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_dir_script_error() {
+    let _guard = CWD.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = make_suite(
+        "error",
+        &[
+            ("a_broken.tcl", "error \"broken script\""),
+            ("b_pass.tcl", "test b-1 {} { set x 1 } -ok 1"),
+        ],
+    );
+
+    // A script error fails the run.
+    assert!(run(&mut new_interp(), &dir, &[]).is_err());
+    assert!(run(&mut new_interp(), &dir, &["-notfile", "a_*"]).is_ok());
+
+    let _ = fs::remove_dir_all(&dir);
+}
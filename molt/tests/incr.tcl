@@ -36,3 +36,65 @@ test incr-3.1 {incr scalar as array} -body {
     set x ""
     incr x(0)
 } -error {can't set "x(0)": variable isn't array}
+
+test incr-2.5 {incr new array element} -body {
+    incr counts(apple)
+    incr counts(apple)
+    incr counts(pear)
+    list $counts(apple) $counts(pear)
+} -cleanup {
+    unset counts
+} -ok {2 1}
+
+test incr-2.6 {negative increment} -body {
+    incr a -3
+} -cleanup {
+    unset a
+} -ok {-3}
+
+test incr-2.7 {negative increment of existing var} -body {
+    set a 10
+    incr a -15
+} -cleanup {
+    unset a
+} -ok {-5}
+
+test incr-3.2 {existing value isn't an integer} -body {
+    set a 1.5
+    incr a
+} -cleanup {
+    unset a
+} -error {expected integer but got "1.5"}
+
+test incr-3.3 {increment isn't an integer} -body {
+    incr a x
+} -error {expected integer but got "x"}
+
+test incr-3.4 {incr array variable} -body {
+    set arr(1) 1
+    incr arr
+} -cleanup {
+    unset arr
+} -error {can't read "arr": variable is array}
+
+test incr-3.5 {failed incr leaves the variable unchanged} -body {
+    set a abc
+    catch {incr a}
+    set a
+} -cleanup {
+    unset a
+} -ok {abc}
+
+test incr-3.6 {overflow} -body {
+    set a 9223372036854775807
+    list [catch {incr a} msg] $msg $a
+} -cleanup {
+    unset a
+} -ok {1 {integer overflow} 9223372036854775807}
+
+test incr-3.7 {negative overflow} -body {
+    set a -9223372036854775807
+    incr a -2
+} -cleanup {
+    unset a
+} -error {integer overflow}
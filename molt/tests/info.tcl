@@ -7,7 +7,8 @@ test info-1.1 {info errors} {
 # TODO: Really need glob matching.
 test info-1.2 {info errors} {
     info nonesuch
-} -error {unknown or ambiguous subcommand "nonesuch": must be args, body, cmdtype, commands, complete, default, exists, globals, locals, procs, or vars}
+} -error {unknown or ambiguous subcommand "nonesuch", must be:
+args, body, cmdtype, commands, complete, default, exists, globals, hostname, locals, nameofexecutable, patchlevel, procs, script, tclversion or vars.}
 
 test info-2.1 {info complete errors} {
     info complete
@@ -81,6 +82,50 @@ test info-4.1 {info procs command, added procs} -setup {
     rename thatProc ""
 } -ok {1 1 0}
 
+test info-4.2 {info procs command, sorted} -setup {
+    proc tst_zeta {} {}
+    proc tst_alpha {} {}
+    proc tst_mid {} {}
+} -body {
+    info procs tst_*
+} -cleanup {
+    rename tst_zeta ""
+    rename tst_alpha ""
+    rename tst_mid ""
+} -ok {tst_alpha tst_mid tst_zeta}
+
+test info-4.3 {info procs command, no match} {
+    info procs nonesuch*
+} -ok {}
+
+test info-4.4 {info procs command, braces in pattern are literal} -setup {
+    proc a{b} {} {}
+    proc ab {} {}
+} -body {
+    list [info procs {a{b}}] [llength [info procs a\{*]] [info procs a?]
+} -cleanup {
+    rename a{b} ""
+    rename ab ""
+} -ok {a{b} 1 ab}
+
+test info-4.5 {info procs command, errors} {
+    info procs a b
+} -error {wrong # args: should be "info procs ?pattern?"}
+
+test info-4.6 {info commands command, pattern} -setup {
+    proc tst_zeta {} {}
+    proc tst_alpha {} {}
+} -body {
+    list [info commands s?t] [info commands tst_*]
+} -cleanup {
+    rename tst_zeta ""
+    rename tst_alpha ""
+} -ok {set {tst_alpha tst_zeta}}
+
+test info-4.7 {info commands command, errors} {
+    info commands a b
+} -error {wrong # args: should be "info commands ?pattern?"}
+
 test info-5.1 {info body command, binary command} {
     info body set
 } -error {"set" isn't a procedure}
@@ -242,3 +287,31 @@ test info-11.2 {info globals command: some defined; in proc} -setup {
     global a b
     unset a b
 } -ok {0 0 1 1 1}
+
+test info-12.1 {info script: the file being sourced} {
+    info script
+} -ok {info.tcl}
+
+test info-12.2 {info script: in a proc} -setup {
+    proc myproc {} { info script }
+} -body {
+    myproc
+} -cleanup {
+    rename myproc ""
+} -ok {info.tcl}
+
+test info-13.1 {info patchlevel} {
+    expr {[string first . [info patchlevel]] > 0}
+} -ok {1}
+
+test info-13.2 {info tclversion} {
+    info tclversion
+} -ok {8.5}
+
+test info-14.1 {info nameofexecutable} {
+    expr {[info nameofexecutable] ne ""}
+} -ok {1}
+
+test info-14.2 {info hostname} {
+    catch {info hostname}
+} -ok {0}
@@ -7,3 +7,213 @@ test interp-1.1 {stack level checking} -setup {
 } -cleanup {
     rename bad_recursion {}
 } -error {too many nested calls to Interp::eval (infinite loop?)}
+
+test interp-2.1 {unknown command: suggestions} {
+    stirng length abc
+} -error {unknown command "stirng", did you mean: string? (run help -all for the full list)}
+
+test interp-2.2 {unknown command: procs are suggested} -setup {
+    proc my_helper {} {}
+} -body {
+    my_helpr
+} -cleanup {
+    rename my_helper {}
+} -error {unknown command "my_helpr", did you mean: my_helper? (run help -all for the full list)}
+
+test interp-2.3 {unknown command: nothing similar} {
+    xyzzyplugh
+} -error {unknown command "xyzzyplugh" (run help -all for the full list)}
+
+test interp-3.1 {interp create: automatic names} -body {
+    list [interp create] [interp create] [interp children]
+} -cleanup {
+    interp delete interp0 interp1
+} -ok {interp0 interp1 {interp0 interp1}}
+
+test interp-3.2 {interp create: named child} -body {
+    list [interp create -safe -- kid] [interp exists kid] [interp issafe kid] [interp issafe]
+} -cleanup {
+    interp delete kid
+} -ok {kid 1 1 0}
+
+test interp-3.3 {interp create: name taken} -setup {
+    interp create kid
+} -body {
+    interp create kid
+} -cleanup {
+    interp delete kid
+} -error {interpreter named "kid" already exists, cannot create}
+
+test interp-3.4 {interp delete} -body {
+    interp create kid
+    interp delete kid
+    list [interp exists kid] [interp children]
+} -ok {0 {}}
+
+test interp-3.5 {interp delete: no such child} {
+    interp delete nonesuch
+} -error {could not find interpreter "nonesuch"}
+
+test interp-3.6 {interp create: safe child can't see the host} -setup {
+    interp create -safe kid
+} -body {
+    list [catch {interp eval kid info hostname} msg1] $msg1 \
+        [catch {interp eval kid info nameofexecutable} msg2] $msg2 \
+        [expr {[interp eval kid info tclversion] eq [info tclversion]}]
+} -cleanup {
+    interp delete kid
+} -ok {1 {not allowed to invoke subcommand hostname of info} 1 {not allowed to invoke subcommand nameofexecutable of info} 1}
+
+test interp-4.1 {interp eval: separate variables and procs} -setup {
+    interp create kid
+    set x parent
+} -body {
+    interp eval kid {
+        set x child
+        proc hello {} { return "hello from the child" }
+    }
+    list $x [interp eval kid set x] [interp eval kid hello] [info procs hello]
+} -cleanup {
+    interp delete kid
+    unset x
+} -ok {parent child {hello from the child} {}}
+
+test interp-4.2 {interp eval: errors propagate} -setup {
+    interp create kid
+} -body {
+    interp eval kid {error "oops"}
+} -cleanup {
+    interp delete kid
+} -error {oops}
+
+test interp-4.3 {interp eval: no such child} {
+    interp eval nonesuch {set x 1}
+} -error {could not find interpreter "nonesuch"}
+
+test interp-4.4 {interp eval: safe child lacks unsafe commands} -setup {
+    interp create -safe kid
+} -body {
+    list [catch {interp eval kid {source x.tcl}}] [interp eval kid {info exists env}] \
+        [interp eval kid {llength {a b c}}]
+} -cleanup {
+    interp delete kid
+} -ok {1 0 3}
+
+test interp-4.5 {interp eval: other children have source and env} -setup {
+    interp create kid
+} -body {
+    list [interp eval kid {info commands source}] [interp eval kid {info exists env}]
+} -cleanup {
+    interp delete kid
+} -ok {source 1}
+
+test interp-4.6 {interp create: a safe interpreter's children are safe} -setup {
+    interp create -safe kid
+} -body {
+    interp eval kid {
+        interp create grandkid
+        interp issafe grandkid
+    }
+} -cleanup {
+    interp delete kid
+} -ok {1}
+
+test interp-5.1 {interp alias: child calls parent} -setup {
+    interp create -safe kid
+    global log
+    set log {}
+    proc record {args} {
+        global log
+        lappend log $args
+        return logged
+    }
+} -body {
+    interp alias kid log {} record child
+    list [interp eval kid {log a b}] $log [interp aliases kid] [interp alias kid log]
+} -cleanup {
+    interp delete kid
+    rename record {}
+    global log
+    unset log
+} -ok {logged {{child a b}} log {record child}}
+
+test interp-5.2 {interp alias: parent calls child} -setup {
+    interp create kid
+    interp eval kid {proc double {x} { expr {2 * $x} }}
+} -body {
+    interp alias {} kid_double kid double
+    kid_double 21
+} -cleanup {
+    interp alias {} kid_double {}
+    interp delete kid
+} -ok {42}
+
+test interp-5.3 {interp alias: within one interpreter} -body {
+    interp alias {} add_one {} expr 1 +
+    add_one 41
+} -cleanup {
+    interp alias {} add_one {}
+} -ok {42}
+
+test interp-5.4 {interp alias: delete} -setup {
+    interp alias {} add_one {} expr 1 +
+} -body {
+    interp alias {} add_one {}
+    list [interp aliases] [catch {add_one 1}]
+} -ok {{} 1}
+
+test interp-5.5 {interp alias: loop} {
+    interp alias {} loop {} loop
+} -error {cannot define or rename alias "loop": would create a loop}
+
+test interp-5.6 {interp alias: not found} {
+    interp alias {} nonesuch
+} -error {alias "nonesuch" not found}
+
+test interp-5.7 {interp alias: child deletes itself} -setup {
+    interp create kid
+    interp alias kid suicide {} interp delete kid
+} -body {
+    list [interp eval kid {suicide; set x done}] [interp exists kid] [interp children]
+} -ok {done 0 {}}
+
+test interp-6.1 {interp limit: commands} -setup {
+    interp create kid
+} -body {
+    interp limit kid commands -value 100
+    list [interp limit kid commands] \
+        [catch {interp eval kid {while 1 {incr i}}} msg] $msg \
+        [interp eval kid {set x done}]
+} -cleanup {
+    interp delete kid
+    unset msg
+} -ok {{-value 100} 1 {command count limit exceeded} done}
+
+test interp-6.2 {interp limit: time} -setup {
+    interp create kid
+} -body {
+    interp limit kid time -milliseconds 10
+    list [interp limit kid time -milliseconds] \
+        [catch {interp eval kid {while 1 {}}} msg] $msg
+} -cleanup {
+    interp delete kid
+    unset msg
+} -ok {10 1 {time limit exceeded}}
+
+test interp-6.3 {interp limit: remove} -setup {
+    interp create kid
+    interp limit kid commands -value 10
+} -body {
+    interp limit kid commands -value {}
+    interp limit kid commands -value
+} -cleanup {
+    interp delete kid
+} -ok {}
+
+test interp-6.4 {interp limit: bad type} -setup {
+    interp create kid
+} -body {
+    interp limit kid memory
+} -cleanup {
+    interp delete kid
+} -error {bad limit type "memory": must be commands or time}
@@ -39,3 +39,19 @@ test list-2.2 {index list} {
 test list-3.1 {no arguments} {
     lindex
 } -error {wrong # args: should be "lindex list ?index ...?"}
+
+test lindex-4.1 {end} {
+    list [lindex {a b c} end] [lindex {a b c} end-1] [lindex {a {b c d}} end end]
+} -ok {c b d}
+
+test lindex-4.2 {end out of range} {
+    list [lindex {a b c} end-3] [lindex {a b c} end+1] [lindex {} end]
+} -ok {{} {} {}}
+
+test lindex-4.3 {index arithmetic} {
+    lindex {a b c} 0+2
+} -ok {c}
+
+test lindex-4.4 {bad index} {
+    lindex {a b c} x
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
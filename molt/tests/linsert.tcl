@@ -0,0 +1,41 @@
+# Test Script: linsert command.
+
+test linsert-1.1 {syntax} {
+    linsert {a b}
+} -error {wrong # args: should be "linsert list index ?element ...?"}
+
+test linsert-1.2 {bad index} {
+    linsert {a b} x y
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+test linsert-2.1 {insert at the beginning} {
+    linsert {a b c} 0 x y
+} -ok {x y a b c}
+
+test linsert-2.2 {insert in the middle} {
+    linsert {a b c} 1 x
+} -ok {a x b c}
+
+test linsert-2.3 {insert at end} {
+    linsert {a b c} end x
+} -ok {a b c x}
+
+test linsert-2.4 {insert at end-1} {
+    linsert {a b c} end-1 x
+} -ok {a b x c}
+
+test linsert-2.5 {index out of range} {
+    list [linsert {a b} -5 x] [linsert {a b} 10 x]
+} -ok {{x a b} {a b x}}
+
+test linsert-2.6 {no elements} {
+    linsert {a b} 1
+} -ok {a b}
+
+test linsert-2.7 {empty list} {
+    linsert {} end {x y}
+} -ok {{x y}}
+
+test linsert-2.8 {index arithmetic} {
+    linsert {a b c} 1+1 x
+} -ok {a b x c}
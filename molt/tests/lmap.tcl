@@ -0,0 +1,57 @@
+# Test Script: lmap
+
+test lmap-1.1 {lmap argument error} {
+    lmap
+} -error {wrong # args: should be "lmap varList list ?varList list ...? command"}
+
+test lmap-1.2 {lmap unpaired lists} {
+    lmap a {1 2} b {}
+} -error {wrong # args: should be "lmap varList list ?varList list ...? command"}
+
+test lmap-1.3 {lmap empty varlist} {
+    lmap {} {1 2} {set x}
+} -error {lmap varlist is empty}
+
+test lmap-1.4 {error in body} {
+    lmap x {1 2 3} {
+        error "Simulated error"
+    }
+} -error {Simulated error}
+
+test lmap-2.1 {empty list} {
+    lmap a {} { set a }
+} -ok {}
+
+test lmap-2.2 {collects body results} {
+    lmap a {1 2 3} { expr {$a * 2} }
+} -ok {2 4 6}
+
+test lmap-2.3 {stride > 1} {
+    lmap {a b} {1 2 3} { list $a $b }
+} -ok {{1 2} {3 {}}}
+
+test lmap-2.4 {multiple lists} {
+    lmap a {1 2 3} b {x y} { list $a $b }
+} -ok {{1 x} {2 y} {3 {}}}
+
+test lmap-3.1 {continue skips the iteration} {
+    lmap a {1 2 3 4} {
+        if {$a % 2 == 0} { continue }
+        set a
+    }
+} -ok {1 3}
+
+test lmap-3.2 {break returns collected items} {
+    lmap a {1 2 3 4} {
+        if {$a == 3} { break }
+        set a
+    }
+} -ok {1 2}
+
+test lmap-3.3 {continue and break together} {
+    lmap a {1 2 3 4 5 6} {
+        if {$a == 2} { continue }
+        if {$a == 5} { break }
+        set a
+    }
+} -ok {1 3 4}
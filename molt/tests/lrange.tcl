@@ -0,0 +1,33 @@
+# Test Script: lrange command.
+
+test lrange-1.1 {syntax} {
+    lrange {a b} 0
+} -error {wrong # args: should be "lrange list first last"}
+
+test lrange-1.2 {bad index} {
+    lrange {a b} 0 x
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+test lrange-2.1 {range} {
+    lrange {a b c d} 1 2
+} -ok {b c}
+
+test lrange-2.2 {end} {
+    lrange {a b c d} 1 end
+} -ok {b c d}
+
+test lrange-2.3 {end-N} {
+    lrange {a b c d} end-2 end-1
+} -ok {b c}
+
+test lrange-2.4 {out of range} {
+    lrange {a b c} -3 10
+} -ok {a b c}
+
+test lrange-2.5 {empty range} {
+    list [lrange {a b c} 2 1] [lrange {a b c} 5 6] [lrange {} 0 end]
+} -ok {{} {} {}}
+
+test lrange-2.6 {single element stays a list} {
+    lrange {a {b c} d} 1 1
+} -ok {{b c}}
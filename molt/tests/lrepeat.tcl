@@ -0,0 +1,41 @@
+# Test Script: lrepeat
+
+test lrepeat-1.1 {lrepeat argument error} {
+    lrepeat
+} -error {wrong # args: should be "lrepeat count ?value ...?"}
+
+test lrepeat-1.2 {lrepeat negative count} {
+    lrepeat -1 x
+} -error {bad count "-1": must be integer >= 0}
+
+test lrepeat-1.3 {lrepeat non-integer count} {
+    lrepeat x y
+} -error {expected integer but got "x"}
+
+test lrepeat-1.4 {lrepeat result too long} {
+    lrepeat 9223372036854775807 x
+} -error {max length exceeded}
+
+test lrepeat-1.5 {lrepeat result too long for several elements} {
+    lrepeat 4611686018427387904 a b c
+} -error {max length exceeded}
+
+test lrepeat-2.1 {zero count} {
+    lrepeat 0 x
+} -ok {}
+
+test lrepeat-2.2 {no elements} {
+    lrepeat 3
+} -ok {}
+
+test lrepeat-2.3 {single element} {
+    lrepeat 3 x
+} -ok {x x x}
+
+test lrepeat-2.4 {several elements} {
+    lrepeat 2 a {b c}
+} -ok {a {b c} a {b c}}
+
+test lrepeat-2.5 {no elements, huge count} {
+    lrepeat 9223372036854775807
+} -ok {}
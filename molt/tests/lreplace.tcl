@@ -0,0 +1,41 @@
+# Test Script: lreplace command.
+
+test lreplace-1.1 {syntax} {
+    lreplace {a b} 0
+} -error {wrong # args: should be "lreplace list first last ?element ...?"}
+
+test lreplace-1.2 {bad index} {
+    lreplace {a b} x 0
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+test lreplace-2.1 {replace} {
+    lreplace {a b c d} 1 2 x y z
+} -ok {a x y z d}
+
+test lreplace-2.2 {delete} {
+    lreplace {a b c d} 1 2
+} -ok {a d}
+
+test lreplace-2.3 {end} {
+    lreplace {a b c d} end end x
+} -ok {a b c x}
+
+test lreplace-2.4 {end-N} {
+    lreplace {a b c d} end-2 end-1
+} -ok {a d}
+
+test lreplace-2.5 {last before first inserts} {
+    lreplace {a b c} 1 0 x
+} -ok {a x b c}
+
+test lreplace-2.6 {first past the end appends} {
+    lreplace {a b c} 10 12 x
+} -ok {a b c x}
+
+test lreplace-2.7 {out of range} {
+    lreplace {a b c} -1 10 x
+} -ok {x}
+
+test lreplace-2.8 {empty list} {
+    lreplace {} 0 0 x
+} -ok {x}
@@ -0,0 +1,13 @@
+# Test Script: lreverse command.
+
+test lreverse-1.1 {syntax} {
+    lreverse
+} -error {wrong # args: should be "lreverse list"}
+
+test lreverse-2.1 {reverse} {
+    lreverse {a {b c} d}
+} -ok {d {b c} a}
+
+test lreverse-2.2 {empty list} {
+    lreverse {}
+} -ok {}
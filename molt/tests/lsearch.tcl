@@ -0,0 +1,76 @@
+# Test Script: lsearch command.
+
+# lsearch-1.*: Syntax
+
+test lsearch-1.1 {syntax} {
+    lsearch {a b}
+} -error {wrong # args: should be "lsearch ?-option value ...? list pattern"}
+
+test lsearch-1.2 {bad option} {
+    lsearch -regexp {a b} a
+} -error {bad option "-regexp": must be -all, -exact, -glob, -inline, -not, or -start}
+
+test lsearch-1.3 {missing start index} {
+    lsearch -start {a b} a
+} -error {missing starting index}
+
+test lsearch-1.4 {bad start index} {
+    lsearch -start x {a b} a
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+# lsearch-2.*: Matching
+
+test lsearch-2.1 {glob by default} {
+    lsearch {apple banana cherry} b*
+} -ok {1}
+
+test lsearch-2.2 {no match} {
+    lsearch {apple banana cherry} z*
+} -ok {-1}
+
+test lsearch-2.3 {first match} {
+    lsearch {a b a b} b
+} -ok {1}
+
+test lsearch-2.4 {exact} {
+    list [lsearch -exact {a* b} a*] [lsearch -exact {abc} a*]
+} -ok {0 -1}
+
+test lsearch-2.5 {glob} {
+    lsearch -glob {x a1 b2} {[ab][0-9]}
+} -ok {1}
+
+test lsearch-2.6 {empty list} {
+    lsearch {} a
+} -ok {-1}
+
+# lsearch-3.*: Options
+
+test lsearch-3.1 {-all} {
+    list [lsearch -all {a b a b} a] [lsearch -all {a b} z]
+} -ok {{0 2} {}}
+
+test lsearch-3.2 {-inline} {
+    list [lsearch -inline {apple banana} b*] [lsearch -inline {apple} z*]
+} -ok {banana {}}
+
+test lsearch-3.3 {-all -inline} {
+    lsearch -all -inline {apple banana blueberry} b*
+} -ok {banana blueberry}
+
+test lsearch-3.4 {-not} {
+    list [lsearch -not {a a b} a] [lsearch -all -not -inline {a b c a} a]
+} -ok {2 {b c}}
+
+test lsearch-3.5 {-start} {
+    list [lsearch -start 2 {a b a b} a] [lsearch -start end {a b a b} a] \
+        [lsearch -start -5 {a b} a]
+} -ok {2 -1 0}
+
+test lsearch-3.6 {-start with -all} {
+    lsearch -all -start end-1 {a b a a} a
+} -ok {2 3}
+
+test lsearch-3.7 {pattern that looks like an option} {
+    lsearch {-all x} -all
+} -ok {0}
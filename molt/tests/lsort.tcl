@@ -0,0 +1,101 @@
+# Test Script: lsort command.
+
+# lsort-1.*: Syntax
+
+test lsort-1.1 {syntax} {
+    lsort
+} -error {wrong # args: should be "lsort ?options? list"}
+
+test lsort-1.2 {bad option} {
+    lsort -frob {a b}
+} -error {bad option "-frob": must be -ascii, -decreasing, -dictionary, -increasing, -index, -integer, -real, or -unique}
+
+test lsort-1.3 {-index without an index} {
+    lsort -index {a b}
+} -error {"-index" option must be followed by list index}
+
+test lsort-1.4 {bad list} {
+    lsort "a \{b"
+} -error {unmatched open brace in list}
+
+# lsort-2.*: Modes
+
+test lsort-2.1 {ascii} {
+    lsort {banana Apple cherry apple 10 9}
+} -ok {10 9 Apple apple banana cherry}
+
+test lsort-2.2 {empty list} {
+    lsort {}
+} -ok {}
+
+test lsort-2.3 {integer} {
+    lsort -integer {10 9 -3 0x10 100}
+} -ok {-3 9 10 0x10 100}
+
+test lsort-2.4 {integer error} {
+    lsort -integer {1 two 3}
+} -error {expected integer but got "two"}
+
+test lsort-2.5 {real} {
+    lsort -real {1.5 -2 1e1 0.25}
+} -ok {-2 0.25 1.5 1e1}
+
+test lsort-2.6 {real error} {
+    lsort -real {1.5 x}
+} -error {expected floating-point number but got "x"}
+
+test lsort-2.7 {dictionary} {
+    lsort -dictionary {x10 x9 X11 bigboy bigBoy bigbang}
+} -ok {bigbang bigBoy bigboy x9 x10 X11}
+
+test lsort-2.8 {last mode option wins} {
+    lsort -integer -ascii {10 9}
+} -ok {10 9}
+
+# lsort-3.*: Order and uniqueness
+
+test lsort-3.1 {decreasing} {
+    lsort -decreasing {b c a}
+} -ok {c b a}
+
+test lsort-3.2 {increasing} {
+    lsort -decreasing -increasing {b c a}
+} -ok {a b c}
+
+test lsort-3.3 {stable} {
+    lsort -integer -index 0 {{1 b} {0 a} {1 a} {0 b}}
+} -ok {{0 a} {0 b} {1 b} {1 a}}
+
+test lsort-3.4 {unique} {
+    lsort -unique {c a b a c}
+} -ok {a b c}
+
+test lsort-3.5 {unique keeps the last of equal elements} {
+    lsort -integer -unique {1 01 2 0x1}
+} -ok {0x1 2}
+
+# lsort-4.*: -index
+
+test lsort-4.1 {index} {
+    lsort -index 1 {{a 3} {b 1} {c 2}}
+} -ok {{b 1} {c 2} {a 3}}
+
+test lsort-4.2 {index end} {
+    lsort -integer -index end {{a 30} {b 4 1} {c 2}}
+} -ok {{b 4 1} {c 2} {a 30}}
+
+test lsort-4.3 {nested index} {
+    lsort -index {1 0} {{a {z 1}} {b {x 2}} {c {y 3}}}
+} -ok {{b {x 2}} {c {y 3}} {a {z 1}}}
+
+test lsort-4.4 {missing element} {
+    lsort -index 1 {{a 1} b}
+} -error {element 1 missing from sublist "b"}
+
+test lsort-4.5 {bad index} {
+    lsort -index foo {{a 1}}
+} -error {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?}
+
+test lsort-4.6 {unique by index} {
+    lsort -unique -index 0 {{a 1} {b 2} {a 3}}
+} -ok {{a 3} {b 2}}
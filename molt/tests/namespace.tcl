@@ -0,0 +1,254 @@
+# Test Script: namespace command.
+
+# namespace-1.*: Syntax
+
+test namespace-1.1 {syntax} {
+    namespace
+} -error {wrong # args: should be "namespace subcommand ?arg ...?"}
+
+test namespace-1.2 {unknown subcommand} {
+    namespace frob
+} -error {unknown or ambiguous subcommand "frob", must be:
+children, current or eval.}
+
+test namespace-1.3 {eval syntax} {
+    namespace eval ns1
+} -error {wrong # args: should be "namespace eval name arg ?arg ...?"}
+
+test namespace-1.4 {current syntax} {
+    namespace current x
+} -error {wrong # args: should be "namespace current "}
+
+test namespace-1.5 {children syntax} {
+    namespace children a b c
+} -error {wrong # args: should be "namespace children ?name? ?pattern?"}
+
+# namespace-2.*: namespace current
+
+test namespace-2.1 {global namespace} {
+    namespace current
+} -ok {::}
+
+test namespace-2.2 {in namespace eval} {
+    namespace eval ns2 { namespace current }
+} -ok {::ns2}
+
+test namespace-2.3 {nested namespace eval} {
+    namespace eval ns2 { namespace eval inner { namespace current } }
+} -ok {::ns2::inner}
+
+test namespace-2.4 {qualified namespace eval} {
+    list [namespace eval ::ns2::inner { namespace current }] \
+        [namespace eval ns2 { namespace eval :: { namespace current } }]
+} -ok {::ns2::inner ::}
+
+test namespace-2.5 {arguments are concatenated} {
+    namespace eval ns2 list a b
+} -ok {a b}
+
+test namespace-2.6 {restored afterwards} {
+    catch {namespace eval ns2 { error oops }}
+    namespace current
+} -ok {::}
+
+# namespace-3.*: Procs
+
+test namespace-3.1 {proc is defined in the namespace} {
+    namespace eval ns3 {
+        proc hello {} { return "hello from [namespace current]" }
+    }
+    list [ns3::hello] [::ns3::hello] [info exists hello]
+} -ok {{hello from ::ns3} {hello from ::ns3} 0}
+
+test namespace-3.2 {unqualified name isn't global} {
+    namespace eval ns3 { proc zzyzx {} { return 1 } }
+    zzyzx
+} -error {unknown command "zzyzx" (run help -all for the full list)}
+
+test namespace-3.3 {unqualified call within the namespace} {
+    namespace eval ns3 {
+        proc helper {} { return helped }
+        proc caller {} { helper }
+    }
+    ns3::caller
+} -ok {helped}
+
+test namespace-3.4 {global commands are found from a namespace} {
+    proc ns3_global {} { return global }
+    namespace eval ns3 { proc uses_global {} { list [ns3_global] [llength {a b}] } }
+    ns3::uses_global
+} -ok {global 2}
+
+test namespace-3.5 {namespace proc hides global proc} {
+    proc shadowed {} { return global }
+    namespace eval ns3 {
+        proc shadowed {} { return local }
+        proc call_shadowed {} { list [shadowed] [::shadowed] }
+    }
+    list [shadowed] [ns3::call_shadowed]
+} -ok {global {local global}}
+
+test namespace-3.6 {qualified proc name} {
+    proc ns3::sub::qualified {} { namespace current }
+    list [ns3::sub::qualified] [namespace eval ns3 { sub::qualified }]
+} -ok {::ns3::sub ::ns3::sub}
+
+test namespace-3.7 {proc defined with absolute name} {
+    namespace eval ns3 { proc ::ns3_absolute {} { namespace current } }
+    ns3_absolute
+} -ok {::}
+
+test namespace-3.8 {rename moves the proc to the new namespace} {
+    proc ns3_moved {} { namespace current }
+    rename ns3_moved ns3::moved
+    list [ns3::moved] [info exists ns3_moved]
+} -ok {::ns3 0}
+
+test namespace-3.9 {info body of a namespace proc} {
+    namespace eval ns3 { proc body {} { return b } }
+    list [info body ns3::body] [namespace eval ns3 { info body body }]
+} -ok {{ return b } { return b }}
+
+# namespace-4.*: Variables
+
+test namespace-4.1 {namespace eval sets namespace variables} {
+    namespace eval ns4 { set x 1 }
+    list [set ns4::x] [set ::ns4::x] [info exists x]
+} -ok {1 1 0}
+
+test namespace-4.2 {namespace variables persist} {
+    namespace eval ns4 { incr x }
+    namespace eval ns4 { set x }
+} -ok {2}
+
+test namespace-4.3 {namespace variables from a proc} {
+    namespace eval ns4 {
+        set count 0
+        proc bump {} { incr ::ns4::count }
+    }
+    ns4::bump
+    ns4::bump
+} -ok {2}
+
+test namespace-4.4 {proc variables are local} {
+    namespace eval ns4 {
+        set y outer
+        proc local {} { set y inner }
+    }
+    ns4::local
+    set ns4::y
+} -ok {outer}
+
+test namespace-4.5 {global variables} {
+    global ns4_global
+    set ns4_global g
+    namespace eval ns4 { set ::ns4_global }
+} -ok {g}
+
+test namespace-4.6 {nested namespace variables} {
+    namespace eval ns4 { namespace eval inner { set z deep } }
+    list [set ns4::inner::z] [namespace eval ns4 { set inner::z }]
+} -ok {deep deep}
+
+test namespace-4.7 {arrays} {
+    namespace eval ns4 { set arr(a) 1; set arr(b) 2 }
+    list [set ns4::arr(b)] [array size ns4::arr]
+} -ok {2 2}
+
+test namespace-4.8 {info vars lists the namespace variables} {
+    namespace eval ns4v { set p 1; set q 2 }
+    set vars [namespace eval ns4v { info vars }]
+    list [llength $vars] [expr {"p" in $vars}] [expr {"q" in $vars}]
+} -ok {2 1 1}
+
+test namespace-4.9 {namespace variables aren't globals} {
+    namespace eval ns4 { set notglobal 1 }
+    list [expr {"notglobal" in [info globals]}] [expr {"ns4::notglobal" in [info globals]}]
+} -ok {0 0}
+
+test namespace-4.10 {unset} {
+    namespace eval ns4 { set gone 1; unset gone }
+    info exists ns4::gone
+} -ok {0}
+
+test namespace-4.11 {unset qualified} {
+    set ns4::gone2 1
+    unset ::ns4::gone2
+    namespace eval ns4 { info exists gone2 }
+} -ok {0}
+
+test namespace-4.12 {\$::x} {
+    set ::ns4_dollar 7
+    list $::ns4_dollar [namespace eval ns4 { set r $::ns4_dollar }]
+} -ok {7 7}
+
+test namespace-4.13 {\$ns::x} {
+    namespace eval ns4 { set d 3; namespace eval inner { set w 4 } }
+    list $ns4::d $::ns4::d $ns4::inner::w [namespace eval ns4 { set r $inner::w }]
+} -ok {3 3 4 4}
+
+test namespace-4.14 {\$ns::arr(k)} {
+    namespace eval ns4 { set arr(k) v }
+    list $ns4::arr(k) "<$::ns4::arr(k)>"
+} -ok {v <v>}
+
+test namespace-4.15 {qualified names in expr} {
+    namespace eval ns4 { set d 3 }
+    expr {$ns4::d + $::ns4::d}
+} -ok {6}
+
+test namespace-4.16 {a single colon ends the variable name} {
+    set ns4_c x
+    list "$ns4_c:y" $ns4_c:
+} -ok {x:y x:}
+
+# namespace-5.*: namespace children
+
+test namespace-5.1 {children of a namespace} {
+    namespace eval ns5 {
+        namespace eval b {}
+        namespace eval a { namespace eval deep {} }
+    }
+    namespace children ns5
+} -ok {::ns5::a ::ns5::b}
+
+test namespace-5.2 {children of the current namespace} {
+    namespace eval ns5 { namespace children }
+} -ok {::ns5::a ::ns5::b}
+
+test namespace-5.3 {global children include new namespaces} {
+    expr {"::ns5" in [namespace children]}
+} -ok {1}
+
+test namespace-5.4 {pattern} {
+    namespace eval ns5 { namespace eval abc {} }
+    list [namespace children ns5 a*] [namespace children ns5 ::ns5::b*]
+} -ok {{::ns5::a ::ns5::abc} ::ns5::b}
+
+test namespace-5.5 {procs create their namespaces} {
+    proc ns5::c::p {} {}
+    namespace children ns5 c
+} -ok {::ns5::c}
+
+test namespace-5.6 {unknown namespace} {
+    namespace children nonesuch
+} -error {namespace "nonesuch" not found in "::"}
+
+# namespace-6.*: Errors
+
+test namespace-6.1 {errorInfo notes the namespace eval} {
+    catch {namespace eval ns6 { error oops }} msg opts
+    dict get $opts -errorinfo
+} -ok {oops
+while executing
+  "error oops"
+    (in namespace eval "::ns6" script)}
+
+test namespace-6.2 {control codes pass through} {
+    set r {}
+    foreach i {1 2 3} {
+        lappend r $i
+        namespace eval ns6 break
+    }
+    set r
+} -ok {1}
@@ -0,0 +1,81 @@
+# Test Script: package command.
+
+# package-1.*: Syntax
+
+test package-1.1 {syntax} {
+    package
+} -error {wrong # args: should be "package subcommand ?arg ...?"}
+
+test package-1.2 {unknown subcommand} {
+    package frob
+} -error {unknown or ambiguous subcommand "frob", must be:
+names, provide or require.}
+
+test package-1.3 {provide syntax} {
+    package provide
+} -error {wrong # args: should be "package provide package ?version?"}
+
+test package-1.4 {require syntax} {
+    package require
+} -error {wrong # args: should be "package require ?-exact? package ?version?"}
+
+test package-1.5 {require -exact needs a version} {
+    package require -exact pkg_a
+} -error {wrong # args: should be "package require ?-exact? package ?version?"}
+
+test package-1.6 {bad version} {
+    package provide pkg_a 1.x
+} -error {expected version number but got "1.x"}
+
+# package-2.*: provide
+
+test package-2.1 {not provided} {
+    package provide pkg_none
+} -ok {}
+
+test package-2.2 {provide} {
+    package provide pkg_b 1.2
+    package provide pkg_b
+} -ok {1.2}
+
+test package-2.3 {provide again} {
+    package provide pkg_b 1.2
+} -ok {}
+
+test package-2.4 {conflicting versions} {
+    package provide pkg_b 1.3
+} -error {conflicting versions provided for package "pkg_b": 1.2, then 1.3}
+
+test package-2.5 {names} {
+    expr {"pkg_b" in [package names]}
+} -ok {1}
+
+# package-3.*: require
+
+test package-3.1 {require provided} {
+    package require pkg_b
+} -ok {1.2}
+
+test package-3.2 {require satisfied version} {
+    package require pkg_b 1.0
+} -ok {1.2}
+
+test package-3.3 {require newer version} {
+    package require pkg_b 1.5
+} -error {version conflict for package "pkg_b": have 1.2, need 1.5}
+
+test package-3.4 {require other major version} {
+    package require pkg_b 2
+} -error {version conflict for package "pkg_b": have 1.2, need 2}
+
+test package-3.5 {require exact version} {
+    list [package require -exact pkg_b 1.2] [catch {package require -exact pkg_b 1.0} msg] $msg
+} -ok {1.2 1 {version conflict for package "pkg_b": have 1.2, need exactly 1.0}}
+
+test package-3.6 {unknown package} {
+    package require pkg_nonesuch
+} -error {can't find package pkg_nonesuch}
+
+test package-3.7 {unknown package version} {
+    package require pkg_nonesuch 1.0
+} -error {can't find package pkg_nonesuch 1.0}
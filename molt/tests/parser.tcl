@@ -34,3 +34,131 @@ test parser-2.5 {Splat expands quoted word} {
 test parser-2.6 {Splat expands interpolated script} {
     list - {*}[list a b c] -
 } -ok {- a b c -}
+
+# Multibyte characters
+
+test parser-3.1 {unicode escapes} {
+    list "\u263A" "\u{1F600}" "a\u{41}b" "\u{}" "\u{110000}"
+} -ok "\u263A \U0001F600 aAb u{} u{110000}"
+
+test parser-3.2 {multibyte variable names} {
+    set "é😀" 1
+    set a(☺) 2
+    list ${é😀} $a(☺)
+} -ok {1 2}
+
+test parser-3.3 {multibyte characters next to delimiters} {
+    set x ☺
+    list "$x☺" {☺} [set x]☺ ☺[set x]
+} -ok {☺☺ ☺ ☺☺ ☺☺}
+
+test parser-3.4 {multibyte proc names} -setup {
+    proc ☺ {} { return 😀 }
+} -body {
+    ☺
+} -cleanup {
+    rename ☺ ""
+} -ok 😀
+
+
+# Comments
+
+test parser-4.1 {comment at the start of a script} {
+    if 1 "# comment\nset x 1"
+} -ok 1
+
+test parser-4.2 {comment after a semicolon} {
+    set x 1;# comment
+    set x
+} -ok 1
+
+test parser-4.3 {comment after a semicolon and white space} {
+    set x 2 ;  # comment ; set x 3
+    set x
+} -ok 2
+
+test parser-4.4 {a semicolon doesn't end a comment} {
+    set x 1
+    # set x 2; set x 3
+    set x
+} -ok 1
+
+test parser-4.5 {backslash-newline continues a comment} {
+    set x 1
+    # comment \
+    set x 2
+    set x
+} -ok 1
+
+test parser-4.6 {escaped backslash before newline ends the comment} {
+    set x 1
+    # comment \\
+    set x 2
+    set x
+} -ok 2
+
+test parser-4.7 {# in the middle of a command is an ordinary character} {
+    list a#b # c
+} -ok {a#b # c}
+
+test parser-4.8 {# as an argument} {
+    set x 1 # comment
+} -error {wrong # args: should be "set varName ?newValue?"}
+
+test parser-4.9 {comment after leading white space} {
+    if 1 " \t  # comment\n set x 4"
+} -ok 4
+
+test parser-4.10 {comment in a bracketed script} {
+    set x [
+        # comment
+        list a b
+    ]
+} -ok {a b}
+
+test parser-4.11 {comment in a loop body} {
+    set result {}
+    foreach i {1 2} {
+        # comment
+        lappend result $i ;# another
+    }
+    set result
+} -ok {1 2}
+
+test parser-4.12 {comments in a proc body} -setup {
+    proc commented {} {
+        # The first comment
+        set x 1 ;# trailing comment
+        # The last comment
+        return $x
+    }
+} -body {
+    commented
+} -cleanup {
+    rename commented ""
+} -ok 1
+
+test parser-4.13 {comment-only script} {
+    if 1 "# just a comment"
+} -ok {}
+
+test parser-4.14 {braces in comments must balance in braced bodies} {
+    if 1 "proc p {} {\n    # an open brace \{\n    return 1\n}"
+} -error {missing close-brace: possible unbalanced brace in comment}
+
+test parser-4.15 {balanced braces in a comment in a braced body} -setup {
+    proc p {} {
+        # {balanced}
+        return 1
+    }
+} -body {
+    p
+} -cleanup {
+    rename p ""
+} -ok 1
+
+test parser-4.16 {parse reports comments after the parsed form} {
+    set out [parse "# one\nset a 1 ;# two"]
+    string range $out [expr {[string first "\n" $out] + 1}] end
+} -ok {Comment { line: 1, text: "# one" }
+Comment { line: 2, text: "# two" }}
@@ -0,0 +1,110 @@
+# Test Script: profile command.
+
+# Returns the number of calls to the named command in the profile report.
+proc pr_count {name} {
+    foreach entry [profile report] {
+        if {[lindex $entry 0] eq $name} {
+            return [lindex $entry 1]
+        }
+    }
+    return 0
+}
+
+# profile-1.*: Syntax
+
+test profile-1.1 {syntax} {
+    profile
+} -error {wrong # args: should be "profile subcommand ?arg ...?"}
+
+test profile-1.2 {unknown subcommand} {
+    profile frob
+} -error {unknown or ambiguous subcommand "frob", must be:
+clear, off, on or report.}
+
+test profile-1.3 {on syntax} {
+    profile on now
+} -error {wrong # args: should be "profile on "}
+
+test profile-1.4 {report syntax} {
+    profile report -sortby time extra
+} -error {wrong # args: should be "profile report ?-sortby time|count?"}
+
+test profile-1.5 {bad option} {
+    profile report -order time
+} -error {bad option "-order": must be -sortby}
+
+test profile-1.6 {bad sort key} {
+    profile report -sortby name
+} -error {bad sort key "name": must be time or count}
+
+# profile-2.*: Recording
+
+test profile-2.1 {nothing recorded until profiling is on} -setup {
+    profile clear
+} -body {
+    set a 1
+    profile report
+} -ok {}
+
+test profile-2.2 {commands and procs are counted} -setup {
+    proc pr_square {x} { expr {$x * $x} }
+    profile clear
+} -body {
+    profile on
+    pr_square 2
+    pr_square 3
+    profile off
+    list [pr_count pr_square] [pr_count expr]
+} -cleanup {
+    profile clear
+    rename pr_square ""
+} -ok {2 2}
+
+test profile-2.3 {off stops recording and keeps the data} -setup {
+    profile clear
+} -body {
+    profile on
+    set a 1
+    profile off
+    set a 2
+    pr_count set
+} -cleanup {
+    profile clear
+} -ok {1}
+
+test profile-2.4 {clear discards the data} -setup {
+    profile clear
+} -body {
+    profile on
+    set a 1
+    profile off
+    profile clear
+    profile report
+} -ok {}
+
+test profile-2.5 {entries are name, count, and time} -setup {
+    profile clear
+} -body {
+    profile on
+    set a 1
+    profile off
+    set entry [lindex [profile report] 0]
+    list [lindex $entry 0] [lindex $entry 1] [string is integer [lindex $entry 2]]
+} -cleanup {
+    profile clear
+} -ok {set 1 1}
+
+test profile-2.6 {sort by count} -setup {
+    profile clear
+} -body {
+    profile on
+    set a 1
+    set a 2
+    incr a
+    profile off
+    lmap entry [profile report -sortby count] { lindex $entry 0 }
+} -cleanup {
+    profile clear
+} -ok {set incr}
+
+rename pr_count ""
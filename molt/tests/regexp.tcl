@@ -0,0 +1,154 @@
+# Test Script: regexp and regsub commands.
+#
+# These commands are behind the `regexp` feature; all.tcl sources this file only when
+# `test_regexp` says it's on.
+
+# regexp-1.*: Syntax and errors
+
+test regexp-1.1 {syntax} {
+    regexp a
+} -error {wrong # args: should be "regexp ?-option ...? exp string ?matchVar? ?subMatchVar ...?"}
+
+test regexp-1.2 {bad option} {
+    regexp -frob a b
+} -error {bad option "-frob": must be -all, -indices, -inline, -nocase, -start, or --}
+
+test regexp-1.3 {-inline with match variables} {
+    regexp -inline a b m
+} -error {regexp match variables not allowed when using -inline}
+
+test regexp-1.4 {bad pattern} {
+    catch {regexp {(} a} msg
+    string match "couldn't compile regular expression pattern: *" $msg
+} -ok {1}
+
+# regexp-2.*: Matching
+
+test regexp-2.1 {match} {
+    list [regexp {b+} abbbc] [regexp {x} abc] [regexp {B} abc]
+} -ok {1 0 0}
+
+test regexp-2.2 {-nocase} {
+    regexp -nocase {B} abc
+} -ok {1}
+
+test regexp-2.3 {-- ends the options} {
+    regexp -- -x a-xb
+} -ok {1}
+
+test regexp-2.4 {match variables} {
+    set result [regexp {(\w+)@(\w+)(!)?} {to: joe@host} m user host bang]
+    list $result $m $user $host $bang
+} -ok {1 joe@host joe host {}}
+
+test regexp-2.5 {unmatched variables are left alone} {
+    set v old
+    regexp {x} abc v
+    set v
+} -ok {old}
+
+# regexp-3.*: Options
+
+test regexp-3.1 {-all counts the matches} {
+    regexp -all {a} banana
+} -ok {3}
+
+test regexp-3.2 {-all sets the variables from the last match} {
+    regexp -all {a(.)} banana m s
+    list $m $s
+} -ok {an n}
+
+test regexp-3.3 {-all with empty matches} {
+    regexp -all {x*} abc
+} -ok {4}
+
+test regexp-3.4 {-inline} {
+    regexp -inline {(\d+)-(\d+)} {x 10-20}
+} -ok {10-20 10 20}
+
+test regexp-3.5 {-all -inline} {
+    regexp -all -inline {\d+} {1 22 333}
+} -ok {1 22 333}
+
+test regexp-3.6 {-all -inline, no match} {
+    regexp -all -inline {x} abc
+} -ok {}
+
+test regexp-3.7 {-indices counts characters} {
+    regexp -indices {b(c)?(x)?} {äbc} m s t
+    list $m $s $t
+} -ok {{1 2} {2 2} {-1 -1}}
+
+test regexp-3.8 {-start} {
+    list [regexp -inline -start 2 {a.} {abacad}] [regexp -start 1 {^a} aa]
+} -ok {ac 0}
+
+test regexp-3.9 {-start end-N} {
+    regexp -inline -start end-1 {.+} {abcd}
+} -ok {cd}
+
+test regexp-3.10 {-start past the end} {
+    regexp -start 10 {.} abc
+} -ok {0}
+
+# regsub-1.*: Syntax and errors
+
+test regsub-1.1 {syntax} {
+    regsub a b
+} -error {wrong # args: should be "regsub ?-option ...? exp string subSpec ?varName?"}
+
+test regsub-1.2 {too many args} {
+    regsub -all a b c d e
+} -error {wrong # args: should be "regsub ?-option ...? exp string subSpec ?varName?"}
+
+test regsub-1.3 {bad option} {
+    regsub -indices a b c
+} -error {bad option "-indices": must be -all, -nocase, -start, or --}
+
+# regsub-2.*: Substitution
+
+test regsub-2.1 {first match} {
+    list [regsub {a} banana o] [regsub {x} banana o]
+} -ok {bonana banana}
+
+test regsub-2.2 {-all} {
+    regsub -all {a} banana o
+} -ok {bonono}
+
+test regsub-2.3 {-nocase} {
+    regsub -nocase {B} banana c
+} -ok {canana}
+
+test regsub-2.4 {-start} {
+    regsub -start 2 {a} banana o
+} -ok {banona}
+
+test regsub-2.5 {-all with empty matches} {
+    regsub -all {x*} abc -
+} -ok {-a-b-c-}
+
+test regsub-2.6 {submatch specs} {
+    regsub {(\w+) (\w+)} {hello world} {\2 \1}
+} -ok {world hello}
+
+test regsub-2.7 {& spec} {
+    regsub -all {\d+} {a1b22} {<&>}
+} -ok {a<1>b<22>}
+
+test regsub-2.8 {escapes} {
+    regsub {b} abc {[\0|\&|\\|\x]}
+} -ok {a[b|&|\|\x]c}
+
+test regsub-2.9 {unmatched submatch} {
+    regsub {b(x)?} abc {<\1>}
+} -ok {a<>c}
+
+# regsub-3.*: Result variable
+
+test regsub-3.1 {result variable} {
+    list [regsub -all {a} banana o result] $result
+} -ok {3 bonono}
+
+test regsub-3.2 {result variable, no match} {
+    list [regsub {x} banana o result] $result
+} -ok {0 banana}
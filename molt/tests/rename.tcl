@@ -23,3 +23,48 @@ test rename-2.2 {rename command} -setup {
 } -cleanup {
     rename howdy ""
 } -ok hello
+
+test rename-2.3 {rename onto an existing command} -setup {
+    proc hello {} { return "hello" }
+} -body {
+    list [catch {rename hello set} msg] $msg [hello]
+} -cleanup {
+    rename hello ""
+} -ok {1 {can't rename to "set": command already exists} hello}
+
+test rename-3.1 {proc removes itself while executing} -setup {
+    proc selfdestruct {} {
+        rename selfdestruct ""
+        return "finished"
+    }
+} -body {
+    list [selfdestruct] [catch {selfdestruct} msg] \
+        [string first {unknown command "selfdestruct"} $msg]
+} -ok {finished 1 0}
+
+test rename-3.2 {proc renames itself and recurses at the new name} -setup {
+    proc fact {n} {
+        catch { rename fact fact2 }
+        if {$n <= 1} {
+            return 1
+        }
+        expr {$n * [fact2 [expr {$n - 1}]]}
+    }
+} -body {
+    list [fact 5] [fact2 3]
+} -cleanup {
+    rename fact2 ""
+} -ok {120 6}
+
+test rename-3.3 {proc removed at the bottom of a recursion} -setup {
+    proc down {n} {
+        if {$n == 0} {
+            rename down ""
+            return 0
+        }
+        expr {[down [expr {$n - 1}]] + 1}
+    }
+} -body {
+    set result [down 20]
+    list $result [catch {down 1}]
+} -ok {20 1}
@@ -68,3 +68,64 @@ test return-3.7 {return, -error* applied} {
     set code [catch {return -code error -errorcode A -errorinfo B -level 0 x} result opts]
     list $code $result $errorCode [expr {$errorInfo eq [dict get $opts -errorinfo]}]
 } -ok {1 x A 1}
+
+# Custom result codes
+
+test return-4.1 {return -code N, -level 1} {
+    set code [catch {return -code 5 x} result opts]
+    list $code $result $opts
+} -ok {2 x {-code 5 -level 1}}
+
+test return-4.2 {return -code N, -level 0 is immediate} {
+    set code [catch {return -code 5 -level 0 x} result opts]
+    list $code $result $opts
+} -ok {5 x {-code 5 -level 0}}
+
+test return-4.3 {return -code N from a proc} -setup {
+    proc stop {value} { return -code 5 $value }
+} -body {
+    set code [catch {stop x} result opts]
+    list $code $result $opts
+} -cleanup {
+    rename stop ""
+} -ok {5 x {-code 5 -level 0}}
+
+test return-4.4 {loops pass custom codes through} -setup {
+    proc stop {value} { return -code 5 $value }
+} -body {
+    list \
+        [catch {while 1 {stop a}} result] $result \
+        [catch {for {} 1 {} {stop b}} result] $result \
+        [catch {foreach i {1 2} {stop $i}} result] $result
+} -cleanup {
+    rename stop ""
+} -ok {5 a 5 b 5 1}
+
+test return-4.5 {custom control structure} -setup {
+    proc forever {body} {
+        while 1 {
+            set code [catch $body result]
+            if {$code == 5} {
+                return $result
+            } elseif {$code != 0} {
+                return -code $code $result
+            }
+        }
+    }
+    proc stop {value} { return -code 5 $value }
+} -body {
+    global n
+    set n 0
+    forever {
+        global n
+        incr n
+        if {$n >= 3} {
+            stop "done at $n"
+        }
+    }
+} -cleanup {
+    global n
+    unset n
+    rename forever ""
+    rename stop ""
+} -ok {done at 3}
@@ -0,0 +1,158 @@
+# Test Script: scan command.
+
+# scan-1.*: Syntax and errors
+
+test scan-1.1 {syntax} {
+    scan abc
+} -error {wrong # args: should be "scan string format ?varName ...?"}
+
+test scan-1.2 {bad conversion} {
+    scan 1 %q
+} -error {bad scan conversion character "q"}
+
+test scan-1.3 {unmatched bracket} {
+    scan abc {%[abc}
+} -error {unmatched [ in format string}
+
+test scan-1.4 {too many variables} {
+    scan "1 2" %d a b
+} -error {different numbers of variable names and field specifiers}
+
+test scan-1.5 {too few variables} {
+    scan "1 2" "%d %d" a
+} -error {different numbers of variable names and field specifiers}
+
+test scan-1.6 {mixed positions} {
+    scan "1 2" {%d %1$d}
+} -error {cannot mix "%" and "%n$" conversion specifiers}
+
+test scan-1.7 {width on %c} {
+    scan a %2c
+} -error {field width may not be specified in %c conversion}
+
+test scan-1.8 {repeated position} {
+    scan "1 2" {%1$d %1$d}
+} -error {variable is assigned by multiple "%n$" conversion specifiers}
+
+test scan-1.9 {unassigned position} {
+    scan a {%2$s}
+} -error {variable is not assigned by any conversion specifiers}
+
+test scan-1.10 {unterminated specifier} {
+    scan 1 %
+} -error {unterminated conversion specifier in format string}
+
+test scan-1.11 {integer too large} {
+    scan 99999999999999999999 %d
+} -error {integer value too large to represent}
+
+# scan-2.*: Variables and the result
+
+test scan-2.1 {assigns variables} -body {
+    list [scan "12 abc 3.5" "%d %s %f" a b c] $a $b $c
+} -cleanup {
+    unset a b c
+} -ok {3 12 abc 3.5}
+
+test scan-2.2 {stops at a mismatch} -body {
+    set b unset
+    list [scan "12 abc" "%d %d" a b] $a $b
+} -cleanup {
+    unset a b
+} -ok {1 12 unset}
+
+test scan-2.3 {empty input} {
+    scan "" %d a
+} -ok {-1}
+
+test scan-2.4 {inline values} {
+    scan "12 abc" "%d %s"
+} -ok {12 abc}
+
+test scan-2.5 {inline values, with a mismatch} {
+    scan "12 abc" "%d %d"
+} -ok {12 {}}
+
+test scan-2.6 {inline values, empty input} {
+    scan "" %d
+} -ok {}
+
+test scan-2.7 {positions} -body {
+    list [scan "a b" {%2$s %1$s} x y] $x $y
+} -cleanup {
+    unset x y
+} -ok {2 b a}
+
+test scan-2.8 {positions, with suppression} {
+    scan "a b" {%1$s %*s}
+} -ok {a}
+
+test scan-2.9 {inline values, stops at a literal mismatch} {
+    scan 12-34 %d:%d
+} -ok {12 {}}
+
+# scan-3.*: Conversions
+
+test scan-3.1 {integers} {
+    scan "-12 ff 17 101 0x1f" "%d %x %o %b %i"
+} -ok {-12 255 15 5 31}
+
+test scan-3.2 {widths} {
+    scan 12345abc "%2d%3d%2s"
+} -ok {12 345 ab}
+
+test scan-3.3 {floats} {
+    scan "1.5 -2e3 .25" "%f %e %g"
+} -ok {1.5 -2000.0 0.25}
+
+test scan-3.4 {characters} {
+    scan " A" %c%c
+} -ok {32 65}
+
+test scan-3.5 {character sets} {
+    scan abc123def {%[a-z]%[0-9]%[^1]}
+} -ok {abc 123 def}
+
+test scan-3.6 {literals and percent} {
+    scan "12:34 50%" "%d:%d %d%%"
+} -ok {12 34 50}
+
+test scan-3.7 {suppression and counts} {
+    scan "1 22 3" "%d %*d%n %d"
+} -ok {1 4 3}
+
+test scan-3.8 {integer prefixes and signs} {
+    scan "0x1f 017 0b11 0o7 9 +5 -1" "%i %i %i %i %i %d %u"
+} -ok {31 15 3 7 9 5 -1}
+
+test scan-3.9 {hex prefix without digits} {
+    scan 0x %x%s
+} -ok {0 x}
+
+test scan-3.10 {no integer} {
+    scan abc %d
+} -ok {{}}
+
+test scan-3.11 {float widths and trailing exponent} {
+    list [scan 1.234 %3f] [scan 1.5e %f%s] [scan 7 %f]
+} -ok {1.2 {1.5 e} 7.0}
+
+test scan-3.12 {no float} {
+    scan .x %f
+} -ok {{}}
+
+test scan-3.13 {string widths} {
+    scan "abcdef  hello world" "%3s%s %s %s"
+} -ok {abc def hello world}
+
+test scan-3.14 {character set with bracket and dash} {
+    scan {]-x} {%[]-]%s}
+} -ok {{]-} x}
+
+test scan-3.15 {character set mismatch} {
+    scan 123abc {%[a-z]}
+} -ok {{}}
+
+test scan-3.16 {counts after whitespace} {
+    scan "ab  cd" "%s%n %s%n"
+} -ok {ab 2 cd 6}
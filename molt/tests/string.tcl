@@ -69,6 +69,17 @@ test string-7.2 {string lengths} {
         [string length abc]
 } -ok {0 1 2 3}
 
+test string-7.3 {string length counts chars, not bytes} {
+    list [string length 日本語] [string length "a\u{1F600}b"] [string length ñ]
+} -ok {3 3 1}
+
+test string-7.4 {string length counts code points, not graphemes} {
+    # A flag is two regional indicators; the family emoji is three people joined by
+    # two zero-width joiners.
+    list [string length "\u{1F1EF}\u{1F1F5}"] \
+        [string length "\u{1F468}\u200D\u{1F469}\u200D\u{1F467}"]
+} -ok {2 5}
+
 # string tolower
 test string-8.1 {string tolower: blank} {
     string tolower {}
@@ -82,6 +93,14 @@ test string-8.3 {string tolower: Unicode} {
     string tolower МАРС
 } -ok марс
 
+test string-8.4 {string tolower: syntax} {
+    string tolower
+} -error {wrong # args: should be "string tolower string ?first? ?last?"}
+
+test string-8.5 {string tolower: index range} {
+    list [string tolower ABC 1] [string tolower ABCDE 1 end-1] [string tolower ABC 2 1]
+} -ok {AbC AbcdE ABC}
+
 # string toupper
 test string-8.1 {string toupper: blank} {
     string toupper {}
@@ -95,6 +114,38 @@ test string-8.3 {string toupper: Unicode} {
     string toupper венера
 } -ok ВЕНЕРА
 
+test string-8.4 {string toupper: capitalize} {
+    string toupper abc 0 0
+} -ok Abc
+
+test string-8.5 {string toupper: index range} {
+    list [string toupper abcdef 1 end-1] [string toupper abc end] [string toupper abc 2 1] \
+        [string toupper abc -5] [string toupper abc 1 10] [string toupper abc 5]
+} -ok {aBCDEf abC abc Abc aBC abc}
+
+test string-8.6 {string toupper: ß expands to SS} {
+    list [string toupper straße] [string length [string toupper ß]] \
+        [string toupper aßb 1 1] [string toupper ßx 1]
+} -ok {STRASSE 2 aSSb ßX}
+
+test string-8.7 {string toupper: bad index} {
+    string toupper abc x
+} -error {bad index "x": must be integer?[+-]integer? or end?[+-]integer?}
+
+# string totitle
+test string-8.8 {string totitle: syntax} {
+    string totitle
+} -error {wrong # args: should be "string totitle string ?first? ?last?"}
+
+test string-8.9 {string totitle: upper case word} {
+    list [string totitle HELLO] [string totitle {hELLO WORLD}] [string totitle {}]
+} -ok {Hello {Hello world} {}}
+
+test string-8.10 {string totitle: index range} {
+    list [string totitle {hello world} 6] [string totitle {hello world} 6 end] \
+        [string totitle ABC 1 end] [string totitle ßa]
+} -ok {{hello World} {hello World} ABc SSa}
+
 # string first
 test string-9.1 {string first} {
     string first foo foobarbaz
@@ -137,12 +188,16 @@ test string-9.9 {string first: startIndex beyond string end} {
 
 test string-9.10 {string first: non-numerical startIndex} {
     string first a abc NOT_A_NUMBER
-} -error {expected integer but got "NOT_A_NUMBER"}
+} -error {bad index "NOT_A_NUMBER": must be integer?[+-]integer? or end?[+-]integer?}
 
 test string-9.11 {string first: startIndex with Unicode} {
     string first б абв 1
 } -ok 1
 
+test string-9.12 {string first: multibyte characters before the match} {
+    list [string first b ééb] [string first é aébé 2] [string first ☺ 😀☺ 1]
+} -ok {2 3 1}
+
 # string trim
 test string-10.1 {string trim: empty} {
     string trim {}
@@ -226,7 +281,7 @@ test string-13.9 {string last: lastIndex beyond string end} {
 
 test string-13.10 {string last: non-numerical lastIndex} {
     string last a abc NOT_A_NUMBER
-} -error {expected integer but got "NOT_A_NUMBER"}
+} -error {bad index "NOT_A_NUMBER": must be integer?[+-]integer? or end?[+-]integer?}
 
 test string-13.11 {string last: startIndex with Unicode 1} {
     string last б абв 1
@@ -317,6 +372,10 @@ test string-14.19 {string map: deletion} {
     string map {0 {} 3 {}} 22233322
 } -ok 22222
 
+test string-14.20 {string map: -nocase with Unicode} {
+    list [string map -nocase {É x} éÉ] [string map -nocase {i x} İi]
+} -ok {xx İx}
+
 # string range
 test string-15.1 {string range: basic} {
     string range 012345 1 3
@@ -357,3 +416,215 @@ test string-15.10 {string range: Unicode 1} {
 test string-15.11 {string range: Unicode 2} {
     string range カタカナ 2 3
 } -ok カナ
+
+test string-15.12 {string range: first > last, both in range} {
+    string range 012345 3 0
+} -ok {}
+
+test string-15.13 {string range: end indices} {
+    list [string range 012345 end-2 end] [string range 012345 0 end-1] \
+        [string range 012345 1+1 end-1] [string range 012345 end end+5]
+} -ok {345 01234 234 5}
+
+test string-15.14 {string range: end indices with Unicode} {
+    string range "日本語\u{1F600}" end-1 end
+} -ok "語\u{1F600}"
+
+test string-15.15 {string range: bad index} {
+    string range 012345 0 end-x
+} -error {bad index "end-x": must be integer?[+-]integer? or end?[+-]integer?}
+
+# string index
+test string-16.1 {string index: syntax} {
+    string index abc
+} -error {wrong # args: should be "string index string charIndex"}
+
+test string-16.2 {string index: ASCII} {
+    list [string index abc 0] [string index abc 2] [string index abc end] \
+        [string index abc end-1] [string index abc 0+1]
+} -ok {a c c b b}
+
+test string-16.3 {string index: out of range} {
+    list [string index abc -1] [string index abc 3] [string index abc end+1] \
+        [string index abc end-3] [string index {} end] [string index {} 0]
+} -ok {{} {} {} {} {} {}}
+
+test string-16.4 {string index: chars, not bytes} {
+    list [string index 日本語 1] [string index 日本語 end] \
+        [string index "a\u{1F600}b" 1] [string index "a\u{1F600}b" end-1]
+} -ok "本 語 \u{1F600} \u{1F600}"
+
+test string-16.5 {string index: a flag is two chars} {
+    expr {[string index "\u{1F1EF}\u{1F1F5}" 0] eq "\u{1F1EF}"}
+} -ok 1
+
+test string-16.6 {string index: bad index} {
+    list [catch {string index abc foo} msg] $msg \
+        [catch {string index abc end1} msg] $msg \
+        [catch {string index abc 1+x} msg] $msg
+} -ok {1 {bad index "foo": must be integer?[+-]integer? or end?[+-]integer?} 1 {bad index "end1": must be integer?[+-]integer? or end?[+-]integer?} 1 {bad index "1+x": must be integer?[+-]integer? or end?[+-]integer?}}
+
+# string bytelength
+test string-17.1 {string bytelength: syntax} {
+    string bytelength
+} -error {wrong # args: should be "string bytelength string"}
+
+test string-17.2 {string bytelength} {
+    list [string bytelength {}] [string bytelength abc] [string bytelength 日本語] \
+        [string bytelength "\u{1F600}"] [string bytelength ñ]
+} -ok {0 3 9 4 2}
+
+# string wordend, string wordstart
+test string-18.1 {string wordend: syntax} {
+    string wordend abc
+} -error {wrong # args: should be "string wordend string charIndex"}
+
+test string-18.2 {string wordstart: syntax} {
+    string wordstart abc
+} -error {wrong # args: should be "string wordstart string charIndex"}
+
+test string-18.3 {string wordend: underscores and punctuation} {
+    set s "foo_bar, baz!"
+    list [string wordend $s 0] [string wordend $s 5] [string wordend $s 7] \
+        [string wordend $s 8] [string wordend $s 10] [string wordend $s end]
+} -ok {7 7 8 9 12 13}
+
+test string-18.4 {string wordstart: underscores and punctuation} {
+    set s "foo_bar, baz!"
+    list [string wordstart $s 0] [string wordstart $s 5] [string wordstart $s 7] \
+        [string wordstart $s 8] [string wordstart $s 10] [string wordstart $s end]
+} -ok {0 0 7 8 9 12}
+
+test string-18.5 {string wordend, wordstart: out of range} {
+    list [string wordend abc -1] [string wordend abc 10] [string wordend {} 0] \
+        [string wordstart abc -1] [string wordstart abc 10] [string wordstart {} 0]
+} -ok {3 3 0 0 0 0}
+
+test string-18.6 {string wordend, wordstart: Unicode} {
+    list [string wordend "日本語 x" 1] [string wordstart "x ñandú" 5]
+} -ok {3 2}
+
+# string first, string last: index syntax
+test string-19.1 {string first: end indices} {
+    list [string first a banana end-2] [string first a banana end] [string first a banana 1+2]
+} -ok {3 5 3}
+
+test string-19.2 {string last: end indices} {
+    list [string last a banana end-1] [string last a banana end] [string last a banana 2-1]
+} -ok {3 5 1}
+
+# string match
+test string-20.1 {string match: syntax} {
+    string match a
+} -error {wrong # args: should be "string match ?-nocase? pattern string"}
+
+test string-20.2 {string match: bad option} {
+    string match -frob a a
+} -error {bad option "-frob": must be -nocase}
+
+test string-20.3 {string match: glob} {
+    list [string match a*c abbbc] [string match a?c abc] [string match a?c abbc] \
+        [string match {[a-c]x} bx] [string match {[a-c]x} dx] [string match {a\*} a*]
+} -ok {1 1 0 1 0 1}
+
+test string-20.4 {string match: -nocase} {
+    list [string match A*C abc] [string match -nocase A*C abc] \
+        [string match -nocase {[A-C]X} bx]
+} -ok {0 1 1}
+
+test string-20.5 {string match: Unicode} {
+    string match ?б* абв
+} -ok {1}
+
+# string repeat
+test string-21.1 {string repeat: syntax} {
+    string repeat a
+} -error {wrong # args: should be "string repeat string count"}
+
+test string-21.2 {string repeat} {
+    list [string repeat ab 3] [string repeat ab 0] [string repeat ab -1]
+} -ok {ababab {} {}}
+
+test string-21.3 {string repeat: bad count} {
+    string repeat a x
+} -error {expected integer but got "x"}
+
+test string-21.4 {string repeat: result too large} {
+    string repeat ab 9223372036854775807
+} -error {string size overflow}
+
+test string-21.5 {string repeat: empty string, huge count} {
+    string repeat {} 9223372036854775807
+} -ok {}
+
+# string reverse
+test string-22.1 {string reverse: syntax} {
+    string reverse
+} -error {wrong # args: should be "string reverse string"}
+
+test string-22.2 {string reverse} {
+    list [string reverse abc] [string reverse {}] [string reverse абв]
+} -ok {cba {} вба}
+
+# string trim, trimleft, trimright: chars
+test string-23.1 {string trim: chars} {
+    list [string trim xxhixyx xy] [string trimleft xxhixyx xy] [string trimright xxhixyx xy]
+} -ok {hi hixyx xxhi}
+
+test string-23.2 {string trim: chars aren't whitespace} {
+    string trim "  xhix  " x
+} -ok {  xhix  }
+
+test string-23.3 {string trim: empty chars} {
+    string trim xhix {}
+} -ok {xhix}
+
+test string-23.4 {string trim: syntax} {
+    string trim a b c
+} -error {wrong # args: should be "string trim string ?chars?"}
+
+# string is
+test string-24.1 {string is: syntax} {
+    string is alpha
+} -error {wrong # args: should be "string is class ?-strict? ?-failindex var? str"}
+
+test string-24.2 {string is: bad class} {
+    string is frob x
+} -error {bad class "frob": must be alnum, alpha, ascii, boolean, control, dict, digit, double, false, graph, integer, list, lower, print, punct, space, true, upper, wordchar, or xdigit}
+
+test string-24.3 {string is: bad option} {
+    string is alpha -frob x
+} -error {bad option "-frob": must be -strict or -failindex}
+
+test string-24.4 {string is: character classes} {
+    list [string is alpha abcé] [string is alpha ab1] [string is alnum ab1] \
+        [string is digit 123] [string is digit 12a] [string is xdigit 0fA] \
+        [string is space " \t\n"] [string is lower abc] [string is upper ABc] \
+        [string is wordchar a_1] [string is wordchar a-1] [string is ascii abc] \
+        [string is ascii é] [string is punct .,!] [string is punct a.] \
+        [string is control "\x01"] [string is print "a b"] [string is graph "a b"]
+} -ok {1 0 1 1 0 1 1 1 0 1 0 1 0 1 0 1 1 0}
+
+test string-24.5 {string is: value classes} {
+    list [string is integer 42] [string is integer 4.2] [string is double 4.2] \
+        [string is double x] [string is boolean yes] [string is boolean 2] \
+        [string is true on] [string is true off] [string is false no] \
+        [string is list {a {b c}}] [string is list "a \{b"] [string is dict {a 1}] \
+        [string is dict {a 1 b}]
+} -ok {1 0 1 0 1 0 1 0 1 1 0 1 0}
+
+test string-24.6 {string is: empty string} {
+    list [string is integer {}] [string is alpha {}] [string is integer -strict {}] \
+        [string is alpha -strict {}]
+} -ok {1 1 0 0}
+
+test string-24.7 {string is: -failindex} {
+    set i unset
+    set r [list [string is alpha -failindex i abc] $i]
+    lappend r [string is alpha -failindex i ab1c] $i
+    lappend r [string is integer -failindex i abc] $i
+} -ok {1 unset 0 2 0 0}
+
+test string-24.8 {string is: -failindex needs a variable} {
+    string is alpha -failindex x
+} -error {wrong # args: should be "string is class ?-strict? ?-failindex var? str"}
@@ -0,0 +1,85 @@
+# Test Script: subst
+
+test subst-1.1 {subst argument error} {
+    subst
+} -error {wrong # args: should be "subst ?-nobackslashes? ?-nocommands? ?-novariables? string"}
+
+test subst-1.2 {subst bad option} {
+    subst -nonesuch abc
+} -error {bad option "-nonesuch": must be -nobackslashes, -nocommands, or -novariables}
+
+test subst-2.1 {no substitutions} {
+    subst {a b c}
+} -ok {a b c}
+
+test subst-2.2 {all substitutions} {
+    set name World
+    subst {Hello, $name! [string length $name]\t.}
+} -ok "Hello, World! 5\t."
+
+test subst-2.3 {braced variable names and array references} {
+    set a xyz
+    set b(1) two
+    set i 1
+    subst {${a}-$b(1)-$b($i)}
+} -ok {xyz-two-two}
+
+test subst-2.4 {braces and quotes are not special} {
+    set x 1
+    subst {{$x} "$x"}
+} -ok {{1} "1"}
+
+test subst-2.5 {bare dollar sign} {
+    subst {cost: $ 5}
+} -ok {cost: $ 5}
+
+test subst-2.6 {nested brackets} {
+    subst {<[string toupper [string tolower ABC][list x]]>}
+} -ok {<ABCX>}
+
+test subst-2.7 {close bracket outside a command} {
+    subst {a]b}
+} -ok {a]b}
+
+test subst-3.1 {-nobackslashes} {
+    set x 1
+    subst -nobackslashes {\t$x[set x]}
+} -ok {\t11}
+
+test subst-3.2 {-nocommands} {
+    set x 1
+    subst -nocommands {\t$x[set x]}
+} -ok "\t1\[set x\]"
+
+test subst-3.3 {-novariables} {
+    set x 1
+    subst -novariables {\t$x[set x]}
+} -ok "\t\$x1"
+
+test subst-3.4 {all switches} {
+    subst -nobackslashes -nocommands -novariables {\t$x[set x]}
+} -ok {\t$x[set x]}
+
+test subst-4.1 {errors are propagated} {
+    subst {a[error oops]b}
+} -error {oops}
+
+test subst-4.2 {unknown variable} {
+    subst {a$nonesuch}
+} -error {can't read "nonesuch": no such variable}
+
+test subst-4.3 {missing close-bracket} {
+    subst {a[set x}
+} -error {missing close-bracket}
+
+test subst-5.1 {break truncates the result} {
+    subst {a[break]b[error notreached]}
+} -ok {a}
+
+test subst-5.2 {continue skips the substitution} {
+    subst {a[continue]b}
+} -ok {ab}
+
+test subst-5.3 {return substitutes its value} {
+    subst {a[return x]b}
+} -ok {axb}
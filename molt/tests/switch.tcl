@@ -0,0 +1,129 @@
+# Test Script: switch command.
+
+# switch-1.*: Syntax
+
+test switch-1.1 {syntax} {
+    switch
+} -error {wrong # args: should be "switch ?-option ...? string ?pattern body ...? ?default body?"}
+
+test switch-1.2 {syntax: string only} {
+    switch x
+} -error {wrong # args: should be "switch ?-option ...? string ?pattern body ...? ?default body?"}
+
+test switch-1.3 {empty list} {
+    switch x {}
+} -error {wrong # args: should be "switch ?-option ...? string ?pattern body ...? ?default body?"}
+
+test switch-1.4 {odd pattern list} {
+    switch x {a 1 b}
+} -error {extra switch pattern with no body}
+
+test switch-1.5 {odd pattern args} {
+    switch x a 1 b
+} -error {extra switch pattern with no body}
+
+test switch-1.6 {bad option} {
+    switch -regexp x {a 1}
+} -error {bad option "-regexp": must be -exact, -glob, or --}
+
+test switch-1.7 {last body is a fall-through} {
+    switch a {a -}
+} -error {no body specified for pattern "a"}
+
+# switch-2.*: Exact matching
+
+test switch-2.1 {separate arguments} {
+    switch b a {set r 1} b {set r 2} c {set r 3}
+} -ok {2}
+
+test switch-2.2 {single list} {
+    switch c {
+        a { set r 1 }
+        b { set r 2 }
+        c { set r 3 }
+    }
+} -ok {3}
+
+test switch-2.3 {no match} {
+    switch z {a 1 b 2}
+} -ok {}
+
+test switch-2.4 {first match wins} {
+    switch a {a {set r first} a {set r second}}
+} -ok {first}
+
+test switch-2.5 {exact isn't glob} {
+    list [switch -exact abc {a* {set r glob} default {set r none}}] \
+        [switch a* {a* {set r exact}}]
+} -ok {none exact}
+
+test switch-2.6 {string that looks like an option} {
+    switch -glob {-glob {set r ok}}
+} -ok {ok}
+
+test switch-2.7 {--} {
+    switch -- -exact {-exact {set r ok}}
+} -ok {ok}
+
+# switch-3.*: Glob matching
+
+test switch-3.1 {glob} {
+    switch -glob hello {
+        a* { set r a }
+        h* { set r h }
+        default { set r none }
+    }
+} -ok {h}
+
+test switch-3.2 {glob classes} {
+    switch -glob b7 {{[a-c][0-9]} {set r class} default {set r none}}
+} -ok {class}
+
+test switch-3.3 {later option wins} {
+    switch -glob -exact a* {a* {set r exact} default {set r none}}
+} -ok {exact}
+
+# switch-4.*: Fall-through and default
+
+test switch-4.1 {fall-through} {
+    switch b {
+        a -
+        b -
+        c { set r abc }
+        d { set r d }
+    }
+} -ok {abc}
+
+test switch-4.2 {default} {
+    switch z {a {set r a} default {set r default}}
+} -ok {default}
+
+test switch-4.3 {default only counts last} {
+    list [switch default {default {set r first} x {set r x}}] \
+        [switch z {default {set r first} x {set r x}}]
+} -ok {first {}}
+
+test switch-4.4 {fall-through into default} {
+    switch a {a - default {set r default}}
+} -ok {default}
+
+# switch-5.*: Bodies
+
+test switch-5.1 {body sees local variables} {
+    set x 5
+    switch y {y {incr x}}
+    set x
+} -ok {6}
+
+test switch-5.2 {errors propagate} {
+    switch a {a {error oops}}
+} -error {oops}
+
+test switch-5.3 {break in a loop} {
+    set r {}
+    foreach i {1 2 3} {
+        switch $i {2 break}
+        lappend r $i
+    }
+    set r
+} -ok {1}
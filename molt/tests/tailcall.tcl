@@ -0,0 +1,108 @@
+# Test Script: tailcall command.
+
+# tailcall-1.*: Syntax
+
+test tailcall-1.1 {syntax} {
+    tailcall
+} -error {wrong # args: should be "tailcall command ?arg ...?"}
+
+test tailcall-1.2 {outside a proc} {
+    uplevel #0 {tailcall set a 1}
+} -error {tailcall can only be called from a proc}
+
+# tailcall-2.*: Semantics
+
+test tailcall-2.1 {result of the command is the proc's} -setup {
+    proc tc_list {} { tailcall list a b c }
+} -body {
+    tc_list
+} -cleanup {
+    rename tc_list ""
+} -ok {a b c}
+
+test tailcall-2.2 {rest of the body is skipped} -setup {
+    proc tc_skip {} {
+        tailcall set ::tc_var called
+        set ::tc_var skipped
+    }
+} -body {
+    tc_skip
+    set ::tc_var
+} -cleanup {
+    rename tc_skip ""
+    unset ::tc_var
+} -ok {called}
+
+test tailcall-2.3 {command runs in the caller's scope} -setup {
+    proc tc_caller {} { set a mine; tailcall set a caller }
+} -body {
+    set a local
+    tc_caller
+    set a
+} -cleanup {
+    rename tc_caller ""
+} -ok {caller}
+
+test tailcall-2.4 {arguments are substituted in the proc} -setup {
+    proc tc_args {x} { set y [expr {$x * 2}]; tailcall list $x $y }
+} -body {
+    tc_args 4
+} -cleanup {
+    rename tc_args ""
+} -ok {4 8}
+
+test tailcall-2.5 {errors come from the command} -setup {
+    proc tc_error {} { tailcall error "from tailcall" }
+} -body {
+    tc_error
+} -cleanup {
+    rename tc_error ""
+} -error {from tailcall}
+
+test tailcall-2.6 {can't be caught} -setup {
+    proc tc_catch {} {
+        catch {tailcall list caught}
+        return not-caught
+    }
+} -body {
+    tc_catch
+} -cleanup {
+    rename tc_catch ""
+} -ok {caught}
+
+test tailcall-2.7 {command is found in the proc's namespace} -setup {
+    namespace eval tc_ns {
+        proc helper {} { return helped }
+        proc call {} { tailcall helper }
+    }
+} -body {
+    tc_ns::call
+} -cleanup {
+    rename tc_ns::helper ""
+    rename tc_ns::call ""
+} -ok {helped}
+
+# tailcall-3.*: Recursion
+
+test tailcall-3.1 {tail recursion isn't limited} -setup {
+    proc tc_count {n total} {
+        if {$n == 0} {
+            return $total
+        }
+        tailcall tc_count [expr {$n - 1}] [expr {$total + $n}]
+    }
+} -body {
+    tc_count 1000 0
+} -cleanup {
+    rename tc_count ""
+} -ok {500500}
+
+test tailcall-3.2 {mutual recursion} -setup {
+    proc tc_even {n} { if {$n == 0} { return 1 }; tailcall tc_odd [expr {$n - 1}] }
+    proc tc_odd {n} { if {$n == 0} { return 0 }; tailcall tc_even [expr {$n - 1}] }
+} -body {
+    list [tc_even 501] [tc_odd 501]
+} -cleanup {
+    rename tc_even ""
+    rename tc_odd ""
+} -ok {0 1}
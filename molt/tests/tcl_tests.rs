@@ -1,4 +1,7 @@
 use molt_forked::prelude::*;
+#[cfg(any(feature = "regexp", feature = "file-io", feature = "exec"))]
+use std::rc::Rc;
+
 #[test]
 fn test_tcl_tests() {
     // FIRST, create and initialize the interpreter.
@@ -30,7 +33,55 @@ fn test_tcl_tests() {
     );
     interp.set_recursion_limit(200);
 
+    // Tell the tests whether the `wasm` feature is on, e.g., because the whole workspace
+    // is being tested; some commands, like `after` with no script, aren't available.
+    interp
+        .set_scalar("test_wasm", Value::from(cfg!(feature = "wasm")))
+        .unwrap();
+
+    // The optional commands aren't in the native list; add the ones whose features are
+    // on, and tell the tests which they are.
+    #[cfg(feature = "regexp")]
+    {
+        interp.add_command(_REGEXP, Rc::new(cmd_regexp));
+        interp.add_command(_REGSUB, Rc::new(cmd_regsub));
+    }
+    interp
+        .set_scalar("test_regexp", Value::from(cfg!(feature = "regexp")))
+        .unwrap();
+
+    #[cfg(feature = "exec")]
+    interp.add_command(_EXEC, Rc::new(cmd_exec));
+    interp
+        .set_scalar("test_exec", Value::from(cfg!(all(feature = "exec", unix))))
+        .unwrap();
+
+    // The file-io tests get a fresh directory for their files.
+    #[cfg(feature = "file-io")]
+    let tmpdir = {
+        interp.add_command(_OPEN, Rc::new(cmd_open));
+        interp.add_command(_CLOSE, Rc::new(cmd_close));
+        interp.add_command(_READ, Rc::new(cmd_read));
+        interp.add_command(_GETS, Rc::new(cmd_gets));
+        interp.add_command(_EOF, Rc::new(cmd_eof));
+        interp.add_command(_FILE, Rc::new(cmd_file));
+        interp.add_command(_GLOB, Rc::new(cmd_glob));
+        let tmpdir =
+            std::env::temp_dir().join(format!("molt-tests-{}", std::process::id()));
+        std::fs::create_dir_all(&tmpdir).unwrap();
+        let name = tmpdir.to_string_lossy().replace('\\', "/");
+        interp.set_scalar("test_tmpdir", Value::from(name)).unwrap();
+        tmpdir
+    };
+    interp
+        .set_scalar("test_file_io", Value::from(cfg!(feature = "file-io")))
+        .unwrap();
+
     let args = vec![String::from("tests/all.tcl")];
+    let result = test_harness(&mut interp, &args);
+
+    #[cfg(feature = "file-io")]
+    std::fs::remove_dir_all(tmpdir).unwrap();
 
-    assert!(test_harness(&mut interp, &args).is_ok());
+    assert!(result.is_ok());
 }
@@ -29,3 +29,15 @@ test throw-3.1 {throw sets return options} {
     set a [catch { throw CODE "Message" } result opts]
     list $a $result [dict get $opts -code] [dict get $opts -level] [dict get $opts -errorcode]
 } -ok {1 Message 1 0 CODE}
+
+test throw-3.2 {throw preserves list error code} {
+    catch { throw {ARITH DIVZERO {divide by zero}} "divide by zero" } result opts
+    set code [dict get $opts -errorcode]
+    list [llength $code] [lindex $code 0] [lindex $code 2]
+} -ok {3 ARITH {divide by zero}}
+
+test throw-3.3 {throw sets errorCode variable} {
+    global errorCode
+    catch { throw {ARITH DIVZERO {divide by zero}} "divide by zero" }
+    lindex $errorCode 0
+} -ok {ARITH}
@@ -0,0 +1,371 @@
+# Test Script: trace command.
+
+# trace-1.*: Syntax
+
+test trace-1.1 {syntax} {
+    trace
+} -error {wrong # args: should be "trace subcommand ?arg ...?"}
+
+test trace-1.2 {unknown subcommand} {
+    trace frob
+} -error {unknown or ambiguous subcommand "frob", must be:
+add, info or remove.}
+
+test trace-1.3 {add syntax} {
+    trace add variable x read
+} -error {wrong # args: should be "trace add type name opList command"}
+
+test trace-1.4 {info syntax} {
+    trace info variable
+} -error {wrong # args: should be "trace info type name"}
+
+test trace-1.5 {only variable and execution traces} {
+    trace add command x delete cb
+} -error {bad option "command": must be execution or variable}
+
+test trace-1.6 {bad operation} {
+    trace add variable x {read frob} cb
+} -error {bad operation "frob": must be read, unset, or write}
+
+test trace-1.7 {empty operation list} {
+    trace add variable x {} cb
+} -error {bad operation list "": must be one or more of read, unset, or write}
+
+# trace-2.*: write traces
+
+test trace-2.1 {write trace} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log $args }
+    trace add variable x write logger
+    set x 1
+    set x 2
+    set log
+} -ok {{x {} write} {x {} write}}
+
+test trace-2.2 {write trace sees the new value} {
+    global gx seen
+    proc seen {name index op} { global gx seen; set seen $gx }
+    trace add variable gx write seen
+    set gx 5
+    unset gx
+    set seen
+} -ok {5}
+
+test trace-2.3 {write trace can change the value} {
+    global gy
+    proc double {name index op} { global gy; set gy [expr {$gy * 2}] }
+    trace add variable gy write double
+    set result [list [set gy 4] $gy]
+    unset gy
+    set result
+} -ok {8 8}
+
+test trace-2.4 {write trace error} {
+    proc readonly {args} { error "read only" }
+    trace add variable x write readonly
+    list [catch {set x 1} msg] $msg $x
+} -ok {1 {can't set "x": read only} 1}
+
+test trace-2.5 {commands that write} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log [lindex $args 2] }
+    trace add variable x write logger
+    set x 1
+    incr x
+    append x a
+    lappend x b
+    set log
+} -ok {write write write write}
+
+test trace-2.6 {command prefix} {
+    global log
+    set log {}
+    proc prefixed {tag name index op} { global log; lappend log $tag $op }
+    trace add variable x write {prefixed mine}
+    set x 1
+    set log
+} -ok {mine write}
+
+# trace-3.*: read traces
+
+test trace-3.1 {read trace} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log $args }
+    set x 1
+    trace add variable x read logger
+    set y $x
+    set y [set x]
+    set log
+} -ok {{x {} read} {x {} read}}
+
+test trace-3.2 {read trace computes the value} {
+    global gz count
+    set count 0
+    proc counter {name index op} { global gz count; set gz [incr count] }
+    trace add variable gz read counter
+    set result [list $gz $gz $gz]
+    unset gz
+    set result
+} -ok {1 2 3}
+
+test trace-3.3 {read trace error} {
+    set x 1
+    proc secret {args} { error "no peeking" }
+    trace add variable x read secret
+    catch {set x} msg
+    set msg
+} -ok {can't read "x": no peeking}
+
+test trace-3.4 {read in expr} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log [lindex $args 2] }
+    set x 1
+    trace add variable x read logger
+    expr {$x + 1}
+    set log
+} -ok {read}
+
+# trace-4.*: unset traces
+
+test trace-4.1 {unset trace} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log $args }
+    set x 1
+    trace add variable x unset logger
+    unset x
+    set log
+} -ok {{x {} unset}}
+
+test trace-4.2 {unset removes the trace} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log [lindex $args 2] }
+    set x 1
+    trace add variable x {write unset} logger
+    unset x
+    set x 2
+    list $log [trace info variable x]
+} -ok {unset {}}
+
+test trace-4.3 {local variables are unset on return} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log $args }
+    proc body {} {
+        set local 1
+        trace add variable local unset logger
+    }
+    body
+    set log
+} -ok {{local {} unset}}
+
+test trace-4.4 {unset trace errors are ignored} {
+    proc fails {args} { error "oops" }
+    set x 1
+    trace add variable x unset fails
+    unset x
+    info exists x
+} -ok {0}
+
+# trace-5.*: Arrays
+
+test trace-5.1 {array trace sees each element} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log $args }
+    trace add variable a {read write unset} logger
+    set a(x) 1
+    set a(y) $a(x)
+    unset a(x)
+    set log
+} -ok {{a x write} {a x read} {a y write} {a x unset}}
+
+test trace-5.2 {element trace sees only its element} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log $args }
+    trace add variable a(x) write logger
+    set a(x) 1
+    set a(y) 2
+    set log
+} -ok {{a x write}}
+
+test trace-5.3 {array set} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log [lindex $args 1] }
+    trace add variable a write logger
+    array set a {p 1}
+    set log
+} -ok {p}
+
+test trace-5.4 {unsetting the array} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log $args }
+    array set a {x 1 y 2}
+    trace add variable a unset logger
+    trace add variable a(x) unset logger
+    unset a
+    set log
+} -ok {{a {} unset} {a x unset}}
+
+# trace-6.*: Scope
+
+test trace-6.1 {trace via global} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log [lindex $args 2] }
+    proc setter {} { global gw; set gw 2 }
+    trace add variable ::gw write logger
+    setter
+    trace remove variable ::gw write logger
+    set log
+} -ok {write}
+
+test trace-6.2 {trace set in a proc on a global} {
+    proc tracer {} { global g; trace add variable g write logger }
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log [lindex $args 2] }
+    tracer
+    set ::g 1
+    trace remove variable ::g write logger
+    set ::g 2
+    set log
+} -ok {write}
+
+test trace-6.3 {traces are disabled while running} {
+    global gv calls
+    set calls 0
+    proc bump {name index op} { global gv calls; incr calls; set gv [expr {$gv + 1}] }
+    trace add variable gv write bump
+    set gv 1
+    set result [list $gv $calls]
+    unset gv
+    set result
+} -ok {2 1}
+
+test trace-6.4 {namespace variable} {
+    global log
+    set log {}
+    proc logger {args} { global log; lappend log [lindex $args 0] }
+    trace add variable ::trace_ns::v write logger
+    namespace eval trace_ns { set v 1 }
+    trace remove variable ::trace_ns::v write logger
+    set log
+} -ok {v}
+
+# trace-7.*: trace info and trace remove
+
+test trace-7.1 {trace info} {
+    trace add variable x write cb1
+    trace add variable x {read unset} {cb2 arg}
+    trace info variable x
+} -ok {{{read unset} {cb2 arg}} {write cb1}}
+
+test trace-7.2 {trace remove} {
+    trace add variable x write cb1
+    trace add variable x {read unset} cb2
+    trace remove variable x {unset read} cb2
+    trace remove variable x write nonesuch
+    trace info variable x
+} -ok {{write cb1}}
+
+test trace-7.3 {trace info for an element} {
+    trace add variable a(x) write cb
+    list [trace info variable a(x)] [trace info variable a]
+} -ok {{{write cb}} {}}
+
+# trace-8.*: execution traces
+
+test trace-8.1 {enter and leave} -setup {
+    proc tr_double {x} { expr {2 * $x} }
+    proc tr_log {args} { lappend ::tr_calls $args }
+    set ::tr_calls {}
+    trace add execution tr_double {enter leave} tr_log
+} -body {
+    tr_double 21
+    set ::tr_calls
+} -cleanup {
+    trace remove execution tr_double {enter leave} tr_log
+    rename tr_double ""
+    rename tr_log ""
+    unset ::tr_calls
+} -ok {{{tr_double 21} enter} {{tr_double 21} 0 42 leave}}
+
+test trace-8.2 {leave sees errors} -setup {
+    proc tr_log {args} { lappend ::tr_calls $args }
+    set ::tr_calls {}
+    trace add execution error leave tr_log
+} -body {
+    catch {error oops}
+    set ::tr_calls
+} -cleanup {
+    trace remove execution error leave tr_log
+    rename tr_log ""
+    unset ::tr_calls
+} -ok {{{error oops} 1 oops leave}}
+
+test trace-8.3 {an enter error keeps the command from being called} -setup {
+    proc tr_deny {args} { error "denied" }
+    set ::tr_x 0
+    trace add execution incr enter tr_deny
+} -body {
+    list [catch {incr ::tr_x} msg] $msg [set ::tr_x]
+} -cleanup {
+    trace remove execution incr enter tr_deny
+    rename tr_deny ""
+    unset ::tr_x
+} -ok {1 denied 0}
+
+test trace-8.4 {a leave error replaces the result} -setup {
+    proc tr_cmd {} { return ok }
+    proc tr_deny {args} { error "denied" }
+    trace add execution tr_cmd leave tr_deny
+} -body {
+    list [catch {tr_cmd} msg] $msg
+} -cleanup {
+    trace remove execution tr_cmd leave tr_deny
+    rename tr_cmd ""
+    rename tr_deny ""
+} -ok {1 denied}
+
+test trace-8.5 {traces are disabled while running} -setup {
+    proc tr_log {args} { lappend ::tr_calls [lindex $args end] }
+    set ::tr_calls {}
+    trace add execution lappend enter tr_log
+} -body {
+    lappend ::tr_other x
+    set ::tr_calls
+} -cleanup {
+    trace remove execution lappend enter tr_log
+    rename tr_log ""
+    unset ::tr_calls ::tr_other
+} -ok {enter}
+
+test trace-8.6 {trace info and trace remove} -setup {
+    proc tr_cmd {} {}
+} -body {
+    trace add execution tr_cmd enter cb1
+    trace add execution tr_cmd {leave enter} {cb2 arg}
+    set a [trace info execution tr_cmd]
+    trace remove execution tr_cmd {enter leave} {cb2 arg}
+    list $a [trace info execution tr_cmd]
+} -cleanup {
+    trace remove execution tr_cmd enter cb1
+    rename tr_cmd ""
+} -ok {{{{leave enter} {cb2 arg}} {enter cb1}} {{enter cb1}}}
+
+test trace-8.7 {unknown command} {
+    trace add execution nonesuch enter cb
+} -error {unknown command "nonesuch"}
+
+test trace-8.8 {bad operation} {
+    trace add execution list enterstep cb
+} -error {bad operation "enterstep": must be enter or leave}
@@ -0,0 +1,65 @@
+# Test Script: the unknown command handler.
+
+# unknown-1.*: Without a handler
+
+test unknown-1.1 {unknown command is an error} {
+    no_such_command a b
+} -error {unknown command "no_such_command" (run help -all for the full list)}
+
+# unknown-2.*: The unknown proc
+
+test unknown-2.1 {unknown gets the command's words} -setup {
+    proc unknown {args} { return "unknown: $args" }
+} -body {
+    no_such_command a {b c}
+} -cleanup {
+    rename unknown ""
+} -ok {unknown: no_such_command a {b c}}
+
+test unknown-2.2 {defined commands don't call unknown} -setup {
+    proc unknown {args} { return "unknown" }
+} -body {
+    list a b
+} -cleanup {
+    rename unknown ""
+} -ok {a b}
+
+test unknown-2.3 {unknown's errors are the command's} -setup {
+    proc unknown {name args} { error "no $name here" }
+} -body {
+    no_such_command
+} -cleanup {
+    rename unknown ""
+} -error {no no_such_command here}
+
+test unknown-2.4 {auto-loading} -setup {
+    proc unknown {name args} {
+        if {$name eq "lazy_double"} {
+            proc lazy_double {x} { expr {2 * $x} }
+            return [lazy_double {*}$args]
+        }
+        error "unknown command \"$name\""
+    }
+} -body {
+    list [lazy_double 2] [info commands lazy_double] [lazy_double 3]
+} -cleanup {
+    rename unknown ""
+    rename lazy_double ""
+} -ok {4 lazy_double 6}
+
+test unknown-2.5 {unknown is called in the caller's scope} -setup {
+    proc unknown {name args} { upvar 1 x x; set x $name }
+    proc unknown_caller {} { set x 0; no_such_command; return $x }
+} -body {
+    unknown_caller
+} -cleanup {
+    rename unknown ""
+    rename unknown_caller ""
+} -ok {no_such_command}
+
+test unknown-2.6 {removing unknown restores the error} -setup {
+    proc unknown {args} { return ok }
+    rename unknown ""
+} -body {
+    no_such_command
+} -error {unknown command "no_such_command" (run help -all for the full list)}
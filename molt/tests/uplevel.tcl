@@ -0,0 +1,136 @@
+# Test Script: uplevel command.
+
+# uplevel-1.*: Syntax
+
+test uplevel-1.1 {syntax} {
+    uplevel
+} -error {wrong # args: should be "uplevel ?level? command ?arg ...?"}
+
+test uplevel-1.2 {level without a command} {
+    uplevel 1
+} -error {wrong # args: should be "uplevel ?level? command ?arg ...?"}
+
+test uplevel-1.3 {bad level} {
+    uplevel #x {set a 1}
+} -error {bad level "#x"}
+
+test uplevel-1.4 {level too high} {
+    uplevel 99 {set a 1}
+} -error {bad level "99"}
+
+test uplevel-1.5 {absolute level too high} {
+    uplevel #99 {set a 1}
+} -error {bad level "#99"}
+
+# uplevel-2.*: Levels
+
+test uplevel-2.1 {default level is the caller} -setup {
+    proc up_set {} { uplevel {set a caller} }
+} -body {
+    set a local
+    up_set
+    set a
+} -cleanup {
+    rename up_set ""
+} -ok {caller}
+
+test uplevel-2.2 {relative level} -setup {
+    proc up_inner {} { uplevel 2 {set a outer} }
+    proc up_outer {} { set a mine; up_inner; set a }
+} -body {
+    set a local
+    list [up_outer] $a
+} -cleanup {
+    rename up_inner ""
+    rename up_outer ""
+} -ok {mine outer}
+
+test uplevel-2.3 {absolute level} -setup {
+    proc up_global {} { uplevel #0 {set up_global_var yes} }
+} -body {
+    up_global
+    uplevel #0 {set up_global_var}
+} -cleanup {
+    rename up_global ""
+    uplevel #0 {unset up_global_var}
+} -ok {yes}
+
+test uplevel-2.4 {level 0 is the current scope} {
+    set a here
+    uplevel 0 {append a !}
+} -ok {here!}
+
+test uplevel-2.5 {procs called from uplevel push their scopes above it} -setup {
+    proc up_locals {} { info locals }
+    proc up_call {} { set hidden 1; uplevel 1 up_locals }
+} -body {
+    up_call
+} -cleanup {
+    rename up_locals ""
+    rename up_call ""
+} -ok {}
+
+test uplevel-2.6 {the caller's scope is restored} -setup {
+    proc up_restore {} { set mine 1; uplevel 1 {set b 2}; info locals }
+} -body {
+    up_restore
+} -cleanup {
+    rename up_restore ""
+} -ok {mine}
+
+# uplevel-3.*: Scripts and results
+
+test uplevel-3.1 {concatenated arguments} {
+    set a 1
+    uplevel 0 set a 2
+    set a
+} -ok {2}
+
+test uplevel-3.2 {result} {
+    uplevel 0 {expr {1 + 2}}
+} -ok {3}
+
+test uplevel-3.3 {errors} -setup {
+    proc up_error {} { uplevel 1 {error oops} }
+} -body {
+    up_error
+} -cleanup {
+    rename up_error ""
+} -error {oops}
+
+test uplevel-3.4 {errors restore the caller's scope} -setup {
+    proc up_error {} { set mine 1; catch {uplevel 1 {error oops}}; info locals }
+} -body {
+    up_error
+} -cleanup {
+    rename up_error ""
+} -ok {mine}
+
+test uplevel-3.5 {traces on the caller's variables} -setup {
+    proc up_trace {} { uplevel 1 {set traced 1} }
+    proc up_log {args} { lappend ::up_log [lindex $args 2] }
+} -body {
+    set ::up_log {}
+    trace add variable traced write up_log
+    up_trace
+    set ::up_log
+} -cleanup {
+    rename up_trace ""
+    rename up_log ""
+} -ok {write}
+
+test uplevel-3.6 {traces in the suspended scopes are kept} -setup {
+    proc up_log {args} { lappend ::up_log [lindex $args 2] }
+    proc up_keep {} {
+        trace add variable mine write up_log
+        uplevel 1 {set mine 1}
+        set mine 2
+    }
+} -body {
+    set ::up_log {}
+    up_keep
+    list [set ::up_log] $mine
+} -cleanup {
+    rename up_keep ""
+    rename up_log ""
+} -ok {write 1}
@@ -0,0 +1,195 @@
+# Test Script: upvar command.
+
+# upvar-1.*: Syntax
+
+test upvar-1.1 {syntax} {
+    upvar a
+} -error {wrong # args: should be "upvar ?level? otherVar localVar ?otherVar localVar ...?"}
+
+test upvar-1.2 {unpaired names} {
+    upvar a b c
+} -error {wrong # args: should be "upvar ?level? otherVar localVar ?otherVar localVar ...?"}
+
+test upvar-1.3 {bad level} {
+    upvar #x a b
+} -error {bad level "#x"}
+
+test upvar-1.4 {level too high} {
+    upvar 99 a b
+} -error {bad level "99"}
+
+test upvar-1.5 {array element as other variable} {
+    upvar 0 a(1) b
+} -error {bad variable name "a(1)": upvar can't link to an array element}
+
+test upvar-1.6 {array element as local variable} {
+    upvar 0 a b(1)
+} -error {bad variable name "b(1)": upvar won't create a scalar variable that looks like an array element}
+
+test upvar-1.7 {local variable exists} {
+    set b 1
+    upvar 0 a b
+} -error {variable "b" already exists}
+
+test upvar-1.8 {link to itself} {
+    upvar 0 a a
+} -error {can't upvar from variable to itself}
+
+test upvar-1.9 {global replaces a local variable} -setup {
+    global up_x
+    set up_x 1
+    proc up_global {} {
+        set up_x 2
+        global up_x
+        return $up_x
+    }
+} -body {
+    up_global
+} -cleanup {
+    global up_x
+    unset up_x
+    rename up_global ""
+} -ok {1}
+
+# upvar-2.*: Levels
+
+test upvar-2.1 {default level is the caller} -setup {
+    proc up_incr {name} { upvar $name v; incr v }
+} -body {
+    set count 1
+    up_incr count
+    set count
+} -cleanup {
+    rename up_incr ""
+} -ok {2}
+
+test upvar-2.2 {explicit relative level} -setup {
+    proc up_inner {} { upvar 2 a v; set v inner }
+    proc up_outer {} { set a mine; up_inner; set a }
+} -body {
+    set a local
+    list [up_outer] $a
+} -cleanup {
+    rename up_inner ""
+    rename up_outer ""
+} -ok {mine inner}
+
+test upvar-2.3 {absolute level} -setup {
+    proc up_global {} { upvar #0 up_global_var v; set v yes }
+} -body {
+    up_global
+    uplevel #0 {set up_global_var}
+} -cleanup {
+    rename up_global ""
+    uplevel #0 {unset up_global_var}
+} -ok {yes}
+
+test upvar-2.4 {level 0 gives a second name} {
+    set a 1
+    upvar 0 a b
+    set b 2
+    set a
+} -ok {2}
+
+test upvar-2.5 {a name that looks like a level} -setup {
+    proc up_digit {} { upvar 1 v; set v set }
+} -body {
+    up_digit
+    set 1
+} -cleanup {
+    rename up_digit ""
+} -ok {set}
+
+# upvar-3.*: Several pairs
+
+test upvar-3.1 {several pairs} -setup {
+    proc up_swap {x y} {
+        upvar 1 $x a $y b
+        set t $a; set a $b; set b $t
+        return
+    }
+} -body {
+    set p 1
+    set q 2
+    up_swap p q
+    list $p $q
+} -cleanup {
+    rename up_swap ""
+} -ok {2 1}
+
+test upvar-3.2 {the variable needn't exist} -setup {
+    proc up_new {} { upvar fresh v; set v made }
+} -body {
+    up_new
+    set fresh
+} -cleanup {
+    rename up_new ""
+} -ok {made}
+
+test upvar-3.3 {unsetting the link unsets the variable} -setup {
+    proc up_unset {} { upvar doomed v; unset v }
+} -body {
+    set doomed 1
+    up_unset
+    info exists doomed
+} -ok {0}
+
+# upvar-4.*: Arrays
+
+test upvar-4.1 {set elements through the link} -setup {
+    proc up_fill {name} { upvar $name arr; set arr(a) 1; set arr(b) 2 }
+} -body {
+    up_fill data
+    lsort [array names data]
+} -cleanup {
+    rename up_fill ""
+} -ok {a b}
+
+test upvar-4.2 {read elements through the link} -setup {
+    proc up_sum {name} {
+        upvar $name arr
+        set sum 0
+        foreach key [array names arr] { incr sum $arr($key) }
+        set sum
+    }
+} -body {
+    array set nums {a 1 b 2 c 3}
+    up_sum nums
+} -cleanup {
+    rename up_sum ""
+} -ok {6}
+
+test upvar-4.3 {array commands through the link} -setup {
+    proc up_array {name} {
+        upvar $name arr
+        list [array exists arr] [array size arr] [array get arr]
+    }
+} -body {
+    array set one {k v}
+    up_array one
+} -cleanup {
+    rename up_array ""
+} -ok {1 1 {k v}}
+
+test upvar-4.4 {unset an element through the link} -setup {
+    proc up_drop {name} { upvar $name arr; unset arr(a) }
+} -body {
+    array set two {a 1 b 2}
+    up_drop two
+    array names two
+} -cleanup {
+    rename up_drop ""
+} -ok {b}
+
+test upvar-4.5 {traces follow the link} -setup {
+    proc up_log {args} { lappend ::up_log [lrange $args 1 2] }
+    proc up_write {name} { upvar $name arr; set arr(x) 1 }
+} -body {
+    set ::up_log {}
+    trace add variable watched write up_log
+    up_write watched
+    set ::up_log
+} -cleanup {
+    rename up_log ""
+    rename up_write ""
+} -ok {{x write}}
@@ -0,0 +1,107 @@
+# Test Script: vwait command.
+#
+# These tests also run with the `wasm` feature, which can't block; `test_wasm` says
+# whether it's on, and the blocking waits are checked only when it isn't.
+
+# vwait-1.*: Syntax
+
+test vwait-1.1 {syntax} {
+    vwait
+} -error {wrong # args: should be "vwait varName ?script ...?"}
+
+# vwait-2.*: Scripts
+
+test vwait-2.1 {script scheduled when the variable is written} {
+    vwait vwait_a set ::vwait_aot yes
+    set result [llength [after info]]
+    set ::vwait_a 1
+    set id [after info]
+    lappend result [after info $id]
+    after cancel $id
+    set result
+} -ok {0 {{set ::vwait_aot yes} idle}}
+
+test vwait-2.2 {script scheduled once} {
+    vwait vwait_b {incr ::vwait_count}
+    set ::vwait_b 1
+    set ::vwait_b 2
+    set ids [after info]
+    foreach id $ids { after cancel $id }
+    llength $ids
+} -ok {1}
+
+test vwait-2.3 {qualified name} {
+    vwait ::vwait_c {set ::vwait_aot yes}
+    set ::vwait_c 1
+    set id [after info]
+    after cancel $id
+    llength $id
+} -ok {1}
+
+test vwait-2.4 {unset schedules the script} {
+    set ::vwait_d 1
+    vwait vwait_d {set ::vwait_aot yes}
+    unset ::vwait_d
+    set id [after info]
+    after cancel $id
+    llength $id
+} -ok {1}
+
+# vwait-3.*: Blocking waits
+
+if {!$test_wasm} {
+    test vwait-3.1 {nothing to wait for} {
+        vwait vwait_never
+    } -error {can't wait for variable "vwait_never": would wait forever}
+
+    test vwait-3.2 {wait for an event} {
+        after 1 {set ::vwait_e done}
+        vwait vwait_e
+        set ::vwait_e
+    } -ok {done}
+
+    test vwait-3.3 {events run until one writes the variable} {
+        after 1 {lappend ::vwait_log first}
+        after 5 {lappend ::vwait_log second; set ::vwait_f 1}
+        set id [after 1000 {lappend ::vwait_log never}]
+        vwait ::vwait_f
+        set result [list $::vwait_log [after info]]
+        after cancel $id
+        string map [list $id ID] $result
+    } -ok {{first second} ID}
+
+    test vwait-3.4 {unset counts, and the trace is removed} {
+        set ::vwait_g 1
+        after 1 {unset ::vwait_g}
+        vwait vwait_g
+        list [info exists ::vwait_g] [trace info variable ::vwait_g]
+    } -ok {0 {}}
+
+    test vwait-3.5 {background error} {
+        after 1 {error oops}
+        vwait vwait_h
+    } -error {oops}
+
+    test vwait-3.6 {wait in a proc uses the global variable} -setup {
+        proc vwait_proc {} {
+            set vwait_i local
+            after 1 {set vwait_i global}
+            vwait vwait_i
+            set vwait_i
+        }
+    } -body {
+        list [vwait_proc] $::vwait_i
+    } -cleanup {
+        rename vwait_proc ""
+    } -ok {local global}
+
+    test vwait-3.7 {script runs once, after the write} {
+        set ::vwait_runs 0
+        vwait vwait_j {incr ::vwait_runs}
+        set ::vwait_j 1
+        set ::vwait_j 2
+        after 1 {set ::vwait_k 1}
+        vwait vwait_k
+        set ::vwait_runs
+    } -ok {1}
+}
@@ -60,3 +60,20 @@ test while-5.1 {while with continue} {
     }
     set list
 } -ok {1 3 5 7 9}
+
+test while-6.1 {while result is empty} {
+    set i 0
+    while {$i < 3} {
+        incr i
+    }
+} -ok {}
+
+test while-6.2 {while with empty body} {
+    set i 0
+    while {[incr i] < 1000} {}
+    set i
+} -ok {1000}
+
+test while-6.3 {while body is parsed before the loop runs} {
+    while {0} {"a"b}
+} -error {extra characters after close-quote}